@@ -0,0 +1,103 @@
+/* C ABI for secp256kfun_capi. Regenerate with:
+ *
+ *     cbindgen --config cbindgen.toml --output include/secp256kfun.h
+ *
+ * All functions return 1 on success and 0 on failure. See the crate documentation for the
+ * encodings of the byte buffers.
+ */
+
+#ifndef SECP256KFUN_H
+#define SECP256KFUN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+int32_t sfun_schnorr_public_key(const uint8_t *secret_key, uint8_t *out_public_key);
+
+int32_t sfun_schnorr_sign(const uint8_t *secret_key,
+                          const uint8_t *message,
+                          size_t message_len,
+                          uint8_t *out_signature);
+
+int32_t sfun_schnorr_verify(const uint8_t *public_key,
+                            const uint8_t *message,
+                            size_t message_len,
+                            const uint8_t *signature);
+
+int32_t sfun_ecdsa_public_key(const uint8_t *secret_key, uint8_t *out_public_key);
+
+int32_t sfun_ecdsa_sign(const uint8_t *secret_key,
+                        const uint8_t *message_hash,
+                        uint8_t *out_signature);
+
+int32_t sfun_ecdsa_verify(const uint8_t *public_key,
+                          const uint8_t *message_hash,
+                          const uint8_t *signature);
+
+int32_t sfun_adaptor_encrypted_sign(const uint8_t *secret_key,
+                                    const uint8_t *encryption_key,
+                                    const uint8_t *message,
+                                    size_t message_len,
+                                    uint8_t *out_encrypted_signature);
+
+int32_t sfun_adaptor_verify_encrypted_signature(const uint8_t *public_key,
+                                                const uint8_t *encryption_key,
+                                                const uint8_t *message,
+                                                size_t message_len,
+                                                const uint8_t *encrypted_signature);
+
+int32_t sfun_adaptor_decrypt_signature(const uint8_t *decryption_key,
+                                       const uint8_t *encrypted_signature,
+                                       uint8_t *out_signature);
+
+int32_t sfun_adaptor_recover_decryption_key(const uint8_t *encryption_key,
+                                            const uint8_t *encrypted_signature,
+                                            const uint8_t *signature,
+                                            uint8_t *out_decryption_key);
+
+int32_t sfun_musig_aggregate_keys(const uint8_t *public_keys,
+                                  size_t n_keys,
+                                  uint8_t *out_agg_key);
+
+int32_t sfun_musig_gen_nonce(const uint8_t *public_keys,
+                             size_t n_keys,
+                             const uint8_t *secret_key,
+                             const uint8_t *session_id,
+                             uint8_t *out_secret_nonce,
+                             uint8_t *out_public_nonce);
+
+int32_t sfun_musig_sign(const uint8_t *public_keys,
+                        size_t n_keys,
+                        size_t my_index,
+                        const uint8_t *secret_key,
+                        const uint8_t *secret_nonce,
+                        const uint8_t *public_nonces,
+                        const uint8_t *message,
+                        size_t message_len,
+                        uint8_t *out_partial_signature);
+
+int32_t sfun_musig_verify_partial_signature(const uint8_t *public_keys,
+                                            size_t n_keys,
+                                            size_t index,
+                                            const uint8_t *public_nonces,
+                                            const uint8_t *message,
+                                            size_t message_len,
+                                            const uint8_t *partial_signature);
+
+int32_t sfun_musig_combine_partial_signatures(const uint8_t *public_keys,
+                                              size_t n_keys,
+                                              const uint8_t *public_nonces,
+                                              const uint8_t *message,
+                                              size_t message_len,
+                                              const uint8_t *partial_signatures,
+                                              uint8_t *out_signature);
+
+#ifdef __cplusplus
+} /* extern "C" */
+#endif
+
+#endif /* SECP256KFUN_H */
@@ -0,0 +1,706 @@
+//! A C ABI for the signing primitives of [`schnorr_fun`] and [`ecdsa_fun`].
+//!
+//! This exists so that apps written in languages that can call C (e.g. Swift and Kotlin on mobile)
+//! can use these implementations without a Rust toolchain in their build. The header is in
+//! `include/secp256kfun.h`.
+//!
+//! All functions return `1` on success and `0` on failure (e.g. a `NULL` pointer or bytes that don't
+//! decode to a valid scalar or point). Nothing is written to the output buffers on failure.
+//!
+//! Keys, points and signatures are passed as fixed length byte buffers:
+//!
+//! - secret keys, scalars: 32 bytes
+//! - BIP340 (x-only) public keys: 32 bytes
+//! - ECDSA public keys, encryption keys: 33 bytes (compressed)
+//! - Schnorr and ECDSA signatures: 64 bytes
+//! - Schnorr adaptor (encrypted) signatures: 65 bytes (`R` x-only, `s_hat`, a `needs_negation` byte)
+//! - MuSig public nonces: 66 bytes, secret nonces: 64 bytes
+//!
+//! Schnorr signing uses BIP340 with SHA256 and deterministic nonces. Messages are signed as-is
+//! ([`Message::raw`]).
+#![allow(non_snake_case)]
+#![warn(missing_docs)]
+
+use core::slice;
+use ecdsa_fun::ECDSA;
+use rand_chacha::ChaCha20Rng;
+use schnorr_fun::{
+    adaptor::{Adaptor, EncryptedSign, EncryptedSignature},
+    binonce::{Nonce, NonceKeyPair},
+    fun::{marker::*, nonce, Point, Scalar},
+    musig::{self, AggKey, MuSig},
+    Message, Schnorr, Signature,
+};
+use sha2::Sha256;
+
+const OK: i32 = 1;
+const ERR: i32 = 0;
+
+type NonceGen = nonce::Deterministic<Sha256>;
+
+fn schnorr() -> Schnorr<Sha256, NonceGen> {
+    Schnorr::default()
+}
+
+fn musig() -> MuSig<Sha256, NonceGen> {
+    musig::new_with_deterministic_nonces()
+}
+
+unsafe fn read<const N: usize>(ptr: *const u8) -> Option<[u8; N]> {
+    if ptr.is_null() {
+        return None;
+    }
+    let mut bytes = [0u8; N];
+    bytes.copy_from_slice(slice::from_raw_parts(ptr, N));
+    Some(bytes)
+}
+
+unsafe fn read_slice<'a>(ptr: *const u8, len: usize) -> Option<&'a [u8]> {
+    if len == 0 {
+        return Some(&[]);
+    }
+    if ptr.is_null() {
+        return None;
+    }
+    Some(slice::from_raw_parts(ptr, len))
+}
+
+unsafe fn read_array<const N: usize, T>(
+    ptr: *const u8,
+    len: usize,
+    decode: impl Fn([u8; N]) -> Option<T>,
+) -> Option<Vec<T>> {
+    if ptr.is_null() {
+        return None;
+    }
+    (0..len)
+        .map(|i| decode(read::<N>(ptr.add(i * N))?))
+        .collect()
+}
+
+unsafe fn write<const N: usize>(ptr: *mut u8, bytes: [u8; N]) -> i32 {
+    if ptr.is_null() {
+        return ERR;
+    }
+    slice::from_raw_parts_mut(ptr, N).copy_from_slice(&bytes);
+    OK
+}
+
+fn secret_key(bytes: [u8; 32]) -> Option<Scalar> {
+    Scalar::from_bytes(bytes)
+}
+
+fn encode_encrypted_signature(encrypted_signature: &EncryptedSignature) -> [u8; 65] {
+    let mut bytes = [0u8; 65];
+    bytes[..32].copy_from_slice(&encrypted_signature.R.to_xonly_bytes());
+    bytes[32..64].copy_from_slice(&encrypted_signature.s_hat.to_bytes());
+    bytes[64] = encrypted_signature.needs_negation as u8;
+    bytes
+}
+
+fn decode_encrypted_signature(bytes: [u8; 65]) -> Option<EncryptedSignature> {
+    Some(EncryptedSignature {
+        R: Point::from_xonly_bytes(bytes[..32].try_into().unwrap())?,
+        s_hat: Scalar::from_bytes(bytes[32..64].try_into().unwrap())?,
+        needs_negation: match bytes[64] {
+            0 => false,
+            1 => true,
+            _ => return None,
+        },
+    })
+}
+
+macro_rules! try_ffi {
+    ($expr:expr) => {
+        match $expr {
+            Some(value) => value,
+            None => return ERR,
+        }
+    };
+}
+
+/// Compute the BIP340 x-only public key of `secret_key`.
+///
+/// # Safety
+///
+/// `secret_key` must point to 32 readable bytes and `out_public_key` to 32 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sfun_schnorr_public_key(
+    secret_key: *const u8,
+    out_public_key: *mut u8,
+) -> i32 {
+    let x = try_ffi!(read(secret_key).and_then(self::secret_key));
+    let keypair = schnorr().new_keypair(x);
+    write(out_public_key, keypair.public_key().to_xonly_bytes())
+}
+
+/// Create a BIP340 signature on `message`.
+///
+/// # Safety
+///
+/// `secret_key` must point to 32 readable bytes, `message` to `message_len` readable bytes and
+/// `out_signature` to 64 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sfun_schnorr_sign(
+    secret_key: *const u8,
+    message: *const u8,
+    message_len: usize,
+    out_signature: *mut u8,
+) -> i32 {
+    let x = try_ffi!(read(secret_key).and_then(self::secret_key));
+    let message = try_ffi!(read_slice(message, message_len));
+    let schnorr = schnorr();
+    let signature = schnorr.sign(&schnorr.new_keypair(x), Message::<Public>::raw(message));
+    write(out_signature, signature.to_bytes())
+}
+
+/// Verify a BIP340 signature. Returns `1` only if the signature is valid.
+///
+/// # Safety
+///
+/// `public_key` must point to 32 readable bytes, `message` to `message_len` readable bytes and
+/// `signature` to 64 readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sfun_schnorr_verify(
+    public_key: *const u8,
+    message: *const u8,
+    message_len: usize,
+    signature: *const u8,
+) -> i32 {
+    let X = try_ffi!(read(public_key).and_then(Point::<EvenY>::from_xonly_bytes));
+    let message = try_ffi!(read_slice(message, message_len));
+    let signature = try_ffi!(read(signature).and_then(Signature::from_bytes));
+    schnorr().verify(&X, Message::<Public>::raw(message), &signature) as i32
+}
+
+/// Compute the compressed ECDSA public key of `secret_key`.
+///
+/// # Safety
+///
+/// `secret_key` must point to 32 readable bytes and `out_public_key` to 33 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sfun_ecdsa_public_key(
+    secret_key: *const u8,
+    out_public_key: *mut u8,
+) -> i32 {
+    let x = try_ffi!(read(secret_key).and_then(self::secret_key));
+    let ecdsa = ECDSA::verify_only();
+    write(out_public_key, ecdsa.verification_key_for(&x).to_bytes())
+}
+
+/// Create a low-s ECDSA signature on a 32-byte message hash.
+///
+/// # Safety
+///
+/// `secret_key` and `message_hash` must point to 32 readable bytes and `out_signature` to 64
+/// writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sfun_ecdsa_sign(
+    secret_key: *const u8,
+    message_hash: *const u8,
+    out_signature: *mut u8,
+) -> i32 {
+    let x = try_ffi!(read(secret_key).and_then(self::secret_key));
+    let message_hash = try_ffi!(read(message_hash));
+    let ecdsa = ECDSA::new(NonceGen::default()).enforce_low_s();
+    write(out_signature, ecdsa.sign(&x, &message_hash).to_bytes())
+}
+
+/// Verify an ECDSA signature on a 32-byte message hash. Returns `1` only if the signature is valid.
+///
+/// # Safety
+///
+/// `public_key` must point to 33 readable bytes, `message_hash` to 32 readable bytes and
+/// `signature` to 64 readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sfun_ecdsa_verify(
+    public_key: *const u8,
+    message_hash: *const u8,
+    signature: *const u8,
+) -> i32 {
+    let X = try_ffi!(read(public_key).and_then(Point::<Normal>::from_bytes));
+    let message_hash = try_ffi!(read(message_hash));
+    let signature = try_ffi!(read(signature).and_then(ecdsa_fun::Signature::from_bytes));
+    ECDSA::verify_only().verify(&X, &message_hash, &signature) as i32
+}
+
+/// Create a Schnorr adaptor (encrypted) signature on `message` under `encryption_key`.
+///
+/// # Safety
+///
+/// `secret_key` must point to 32 readable bytes, `encryption_key` to 33 readable bytes, `message`
+/// to `message_len` readable bytes and `out_encrypted_signature` to 65 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sfun_adaptor_encrypted_sign(
+    secret_key: *const u8,
+    encryption_key: *const u8,
+    message: *const u8,
+    message_len: usize,
+    out_encrypted_signature: *mut u8,
+) -> i32 {
+    let x = try_ffi!(read(secret_key).and_then(self::secret_key));
+    let Y = try_ffi!(read(encryption_key).and_then(Point::<Normal>::from_bytes));
+    let message = try_ffi!(read_slice(message, message_len));
+    let schnorr = schnorr();
+    let encrypted_signature =
+        schnorr.encrypted_sign(&schnorr.new_keypair(x), &Y, Message::<Public>::raw(message));
+    write(
+        out_encrypted_signature,
+        encode_encrypted_signature(&encrypted_signature),
+    )
+}
+
+/// Verify a Schnorr adaptor signature. Returns `1` only if the encrypted signature is valid.
+///
+/// # Safety
+///
+/// `public_key` must point to 32 readable bytes, `encryption_key` to 33 readable bytes, `message`
+/// to `message_len` readable bytes and `encrypted_signature` to 65 readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sfun_adaptor_verify_encrypted_signature(
+    public_key: *const u8,
+    encryption_key: *const u8,
+    message: *const u8,
+    message_len: usize,
+    encrypted_signature: *const u8,
+) -> i32 {
+    let X = try_ffi!(read(public_key).and_then(Point::<EvenY>::from_xonly_bytes));
+    let Y = try_ffi!(read(encryption_key).and_then(Point::<Normal>::from_bytes));
+    let message = try_ffi!(read_slice(message, message_len));
+    let encrypted_signature =
+        try_ffi!(read(encrypted_signature).and_then(decode_encrypted_signature));
+    schnorr().verify_encrypted_signature(
+        &X,
+        &Y,
+        Message::<Public>::raw(message),
+        &encrypted_signature,
+    ) as i32
+}
+
+/// Decrypt a Schnorr adaptor signature into a BIP340 signature with the decryption key.
+///
+/// # Safety
+///
+/// `decryption_key` must point to 32 readable bytes, `encrypted_signature` to 65 readable bytes
+/// and `out_signature` to 64 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sfun_adaptor_decrypt_signature(
+    decryption_key: *const u8,
+    encrypted_signature: *const u8,
+    out_signature: *mut u8,
+) -> i32 {
+    let y = try_ffi!(read(decryption_key).and_then(self::secret_key));
+    let encrypted_signature =
+        try_ffi!(read(encrypted_signature).and_then(decode_encrypted_signature));
+    let signature = schnorr().decrypt_signature(y, encrypted_signature);
+    write(out_signature, signature.to_bytes())
+}
+
+/// Recover the decryption key from an adaptor signature and the signature decrypted from it.
+///
+/// # Safety
+///
+/// `encryption_key` must point to 33 readable bytes, `encrypted_signature` to 65 readable bytes,
+/// `signature` to 64 readable bytes and `out_decryption_key` to 32 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sfun_adaptor_recover_decryption_key(
+    encryption_key: *const u8,
+    encrypted_signature: *const u8,
+    signature: *const u8,
+    out_decryption_key: *mut u8,
+) -> i32 {
+    let Y = try_ffi!(read(encryption_key).and_then(Point::<Normal>::from_bytes));
+    let encrypted_signature =
+        try_ffi!(read(encrypted_signature).and_then(decode_encrypted_signature));
+    let signature = try_ffi!(read(signature).and_then(Signature::from_bytes));
+    let y = try_ffi!(schnorr().recover_decryption_key(&Y, &encrypted_signature, &signature));
+    write(out_decryption_key, y.to_secret_bytes().into_inner())
+}
+
+unsafe fn agg_key(public_keys: *const u8, n_keys: usize) -> Option<AggKey<EvenY>> {
+    if n_keys == 0 {
+        return None;
+    }
+    let keys = read_array(public_keys, n_keys, Point::<Normal>::from_bytes)?;
+    Some(musig().new_agg_key(keys).into_xonly_key())
+}
+
+/// Aggregate `n_keys` compressed public keys into a MuSig2 x-only aggregate key.
+///
+/// # Safety
+///
+/// `public_keys` must point to `n_keys * 33` readable bytes and `out_agg_key` to 32 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sfun_musig_aggregate_keys(
+    public_keys: *const u8,
+    n_keys: usize,
+    out_agg_key: *mut u8,
+) -> i32 {
+    let agg_key = try_ffi!(agg_key(public_keys, n_keys));
+    write(out_agg_key, agg_key.agg_public_key().to_xonly_bytes())
+}
+
+/// Generate a MuSig2 nonce for signing under the aggregate of `public_keys`.
+///
+/// ⚠ `session_id` must be unique for every signing attempt. Passing 32 bytes from a secure random
+/// number generator is the easiest way to ensure this.
+///
+/// # Safety
+///
+/// `public_keys` must point to `n_keys * 33` readable bytes, `secret_key` and `session_id` to 32
+/// readable bytes, `out_secret_nonce` to 64 writable bytes and `out_public_nonce` to 66 writable
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sfun_musig_gen_nonce(
+    public_keys: *const u8,
+    n_keys: usize,
+    secret_key: *const u8,
+    session_id: *const u8,
+    out_secret_nonce: *mut u8,
+    out_public_nonce: *mut u8,
+) -> i32 {
+    let agg_key = try_ffi!(agg_key(public_keys, n_keys));
+    let x = try_ffi!(read(secret_key).and_then(self::secret_key));
+    let session_id = try_ffi!(read::<32>(session_id));
+    if out_secret_nonce.is_null() || out_public_nonce.is_null() {
+        return ERR;
+    }
+    let musig = musig();
+    let mut nonce_rng: ChaCha20Rng = musig.seed_nonce_rng(&agg_key, &x, &session_id);
+    let nonce = musig.gen_nonce(&mut nonce_rng);
+    write(out_secret_nonce, nonce.to_bytes());
+    write(out_public_nonce, nonce.public().to_bytes())
+}
+
+/// Create a MuSig2 partial signature.
+///
+/// `public_nonces` must be in the same order as the keys and `my_index` is the position of our key.
+/// A secret nonce must never be used twice.
+///
+/// # Safety
+///
+/// `public_keys` must point to `n_keys * 33` readable bytes, `secret_key` to 32 readable bytes,
+/// `secret_nonce` to 64 readable bytes, `public_nonces` to `n_keys * 66` readable bytes, `message`
+/// to `message_len` readable bytes and `out_partial_signature` to 32 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sfun_musig_sign(
+    public_keys: *const u8,
+    n_keys: usize,
+    my_index: usize,
+    secret_key: *const u8,
+    secret_nonce: *const u8,
+    public_nonces: *const u8,
+    message: *const u8,
+    message_len: usize,
+    out_partial_signature: *mut u8,
+) -> i32 {
+    let agg_key = try_ffi!(agg_key(public_keys, n_keys));
+    let x = try_ffi!(read(secret_key).and_then(self::secret_key));
+    let secret_nonce = try_ffi!(read(secret_nonce).and_then(NonceKeyPair::from_bytes));
+    let nonces = try_ffi!(read_array(public_nonces, n_keys, Nonce::from_bytes));
+    let message = try_ffi!(read_slice(message, message_len));
+    let musig = musig();
+    let keypair = musig.new_keypair(x);
+    if agg_key.keys().nth(my_index) != Some(keypair.public_key())
+        || nonces[my_index] != secret_nonce.public()
+    {
+        return ERR;
+    }
+    let session = musig.start_sign_session(&agg_key, nonces, Message::<Public>::raw(message));
+    let partial_signature = musig.sign(&agg_key, &session, my_index, &keypair, secret_nonce);
+    write(out_partial_signature, partial_signature.to_bytes())
+}
+
+/// Verify the MuSig2 partial signature of the party at `index`. Returns `1` only if it is valid.
+///
+/// # Safety
+///
+/// `public_keys` must point to `n_keys * 33` readable bytes, `public_nonces` to `n_keys * 66`
+/// readable bytes, `message` to `message_len` readable bytes and `partial_signature` to 32
+/// readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sfun_musig_verify_partial_signature(
+    public_keys: *const u8,
+    n_keys: usize,
+    index: usize,
+    public_nonces: *const u8,
+    message: *const u8,
+    message_len: usize,
+    partial_signature: *const u8,
+) -> i32 {
+    if index >= n_keys {
+        return ERR;
+    }
+    let agg_key = try_ffi!(agg_key(public_keys, n_keys));
+    let nonces = try_ffi!(read_array(public_nonces, n_keys, Nonce::from_bytes));
+    let message = try_ffi!(read_slice(message, message_len));
+    let partial_signature = try_ffi!(read(partial_signature).and_then(Scalar::from_bytes));
+    let musig = musig();
+    let session = musig.start_sign_session(&agg_key, nonces, Message::<Public>::raw(message));
+    musig.verify_partial_signature(&agg_key, &session, index, partial_signature) as i32
+}
+
+/// Combine `n_keys` MuSig2 partial signatures (in key order) into a BIP340 signature.
+///
+/// This doesn't check the partial signatures. Verify them individually or verify the result with
+/// [`sfun_schnorr_verify`].
+///
+/// # Safety
+///
+/// `public_keys` must point to `n_keys * 33` readable bytes, `public_nonces` to `n_keys * 66`
+/// readable bytes, `message` to `message_len` readable bytes, `partial_signatures` to `n_keys * 32`
+/// readable bytes and `out_signature` to 64 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sfun_musig_combine_partial_signatures(
+    public_keys: *const u8,
+    n_keys: usize,
+    public_nonces: *const u8,
+    message: *const u8,
+    message_len: usize,
+    partial_signatures: *const u8,
+    out_signature: *mut u8,
+) -> i32 {
+    let agg_key = try_ffi!(agg_key(public_keys, n_keys));
+    let nonces = try_ffi!(read_array(public_nonces, n_keys, Nonce::from_bytes));
+    let message = try_ffi!(read_slice(message, message_len));
+    let partial_signatures = try_ffi!(read_array(
+        partial_signatures,
+        n_keys,
+        Scalar::<Public, Zero>::from_bytes
+    ));
+    let musig = musig();
+    let session = musig.start_sign_session(&agg_key, nonces, Message::<Public>::raw(message));
+    let signature = musig.combine_partial_signatures(&agg_key, &session, partial_signatures);
+    write(out_signature, signature.to_bytes())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn random_secret() -> [u8; 32] {
+        Scalar::random(&mut rand::thread_rng())
+            .to_secret_bytes()
+            .into_inner()
+    }
+
+    #[test]
+    fn schnorr_and_ecdsa_roundtrip() {
+        let secret = random_secret();
+        let message = b"hello from C";
+        let hash = [42u8; 32];
+        unsafe {
+            let mut public_key = [0u8; 32];
+            let mut signature = [0u8; 64];
+            assert_eq!(
+                sfun_schnorr_public_key(secret.as_ptr(), public_key.as_mut_ptr()),
+                OK
+            );
+            assert_eq!(
+                sfun_schnorr_sign(
+                    secret.as_ptr(),
+                    message.as_ptr(),
+                    message.len(),
+                    signature.as_mut_ptr()
+                ),
+                OK
+            );
+            assert_eq!(
+                sfun_schnorr_verify(
+                    public_key.as_ptr(),
+                    message.as_ptr(),
+                    message.len(),
+                    signature.as_ptr()
+                ),
+                OK
+            );
+            assert_eq!(
+                sfun_schnorr_verify(public_key.as_ptr(), message.as_ptr(), 1, signature.as_ptr()),
+                ERR
+            );
+
+            let mut public_key = [0u8; 33];
+            assert_eq!(
+                sfun_ecdsa_public_key(secret.as_ptr(), public_key.as_mut_ptr()),
+                OK
+            );
+            assert_eq!(
+                sfun_ecdsa_sign(secret.as_ptr(), hash.as_ptr(), signature.as_mut_ptr()),
+                OK
+            );
+            assert_eq!(
+                sfun_ecdsa_verify(public_key.as_ptr(), hash.as_ptr(), signature.as_ptr()),
+                OK
+            );
+            assert_eq!(
+                sfun_ecdsa_sign(core::ptr::null(), hash.as_ptr(), signature.as_mut_ptr()),
+                ERR
+            );
+            assert_eq!(
+                sfun_ecdsa_public_key([0u8; 32].as_ptr(), public_key.as_mut_ptr()),
+                ERR
+            );
+        }
+    }
+
+    #[test]
+    fn adaptor_roundtrip() {
+        let secret = random_secret();
+        let decryption_key = random_secret();
+        let message = b"adaptor";
+        unsafe {
+            let mut public_key = [0u8; 32];
+            let mut encryption_key = [0u8; 33];
+            let mut encrypted_signature = [0u8; 65];
+            let mut signature = [0u8; 64];
+            let mut recovered = [0u8; 32];
+            sfun_schnorr_public_key(secret.as_ptr(), public_key.as_mut_ptr());
+            sfun_ecdsa_public_key(decryption_key.as_ptr(), encryption_key.as_mut_ptr());
+            assert_eq!(
+                sfun_adaptor_encrypted_sign(
+                    secret.as_ptr(),
+                    encryption_key.as_ptr(),
+                    message.as_ptr(),
+                    message.len(),
+                    encrypted_signature.as_mut_ptr()
+                ),
+                OK
+            );
+            assert_eq!(
+                sfun_adaptor_verify_encrypted_signature(
+                    public_key.as_ptr(),
+                    encryption_key.as_ptr(),
+                    message.as_ptr(),
+                    message.len(),
+                    encrypted_signature.as_ptr()
+                ),
+                OK
+            );
+            assert_eq!(
+                sfun_adaptor_decrypt_signature(
+                    decryption_key.as_ptr(),
+                    encrypted_signature.as_ptr(),
+                    signature.as_mut_ptr()
+                ),
+                OK
+            );
+            assert_eq!(
+                sfun_schnorr_verify(
+                    public_key.as_ptr(),
+                    message.as_ptr(),
+                    message.len(),
+                    signature.as_ptr()
+                ),
+                OK
+            );
+            assert_eq!(
+                sfun_adaptor_recover_decryption_key(
+                    encryption_key.as_ptr(),
+                    encrypted_signature.as_ptr(),
+                    signature.as_ptr(),
+                    recovered.as_mut_ptr()
+                ),
+                OK
+            );
+            assert_eq!(recovered, decryption_key);
+        }
+    }
+
+    #[test]
+    fn musig_roundtrip() {
+        let secrets = [random_secret(), random_secret()];
+        let message = b"musig";
+        unsafe {
+            let mut public_keys = [0u8; 66];
+            for (i, secret) in secrets.iter().enumerate() {
+                sfun_ecdsa_public_key(secret.as_ptr(), public_keys[i * 33..].as_mut_ptr());
+            }
+            let mut agg_key = [0u8; 32];
+            assert_eq!(
+                sfun_musig_aggregate_keys(public_keys.as_ptr(), 2, agg_key.as_mut_ptr()),
+                OK
+            );
+            let mut secret_nonces = [[0u8; 64]; 2];
+            let mut public_nonces = [0u8; 132];
+            for (i, secret) in secrets.iter().enumerate() {
+                assert_eq!(
+                    sfun_musig_gen_nonce(
+                        public_keys.as_ptr(),
+                        2,
+                        secret.as_ptr(),
+                        random_secret().as_ptr(),
+                        secret_nonces[i].as_mut_ptr(),
+                        public_nonces[i * 66..].as_mut_ptr()
+                    ),
+                    OK
+                );
+            }
+            let mut partial_signatures = [0u8; 64];
+            for (i, secret) in secrets.iter().enumerate() {
+                assert_eq!(
+                    sfun_musig_sign(
+                        public_keys.as_ptr(),
+                        2,
+                        i,
+                        secret.as_ptr(),
+                        secret_nonces[i].as_ptr(),
+                        public_nonces.as_ptr(),
+                        message.as_ptr(),
+                        message.len(),
+                        partial_signatures[i * 32..].as_mut_ptr()
+                    ),
+                    OK
+                );
+                assert_eq!(
+                    sfun_musig_verify_partial_signature(
+                        public_keys.as_ptr(),
+                        2,
+                        i,
+                        public_nonces.as_ptr(),
+                        message.as_ptr(),
+                        message.len(),
+                        partial_signatures[i * 32..].as_ptr()
+                    ),
+                    OK
+                );
+            }
+            // signing with the wrong index fails
+            assert_eq!(
+                sfun_musig_sign(
+                    public_keys.as_ptr(),
+                    2,
+                    1,
+                    secrets[0].as_ptr(),
+                    secret_nonces[0].as_ptr(),
+                    public_nonces.as_ptr(),
+                    message.as_ptr(),
+                    message.len(),
+                    partial_signatures.as_mut_ptr()
+                ),
+                ERR
+            );
+            let mut signature = [0u8; 64];
+            assert_eq!(
+                sfun_musig_combine_partial_signatures(
+                    public_keys.as_ptr(),
+                    2,
+                    public_nonces.as_ptr(),
+                    message.as_ptr(),
+                    message.len(),
+                    partial_signatures.as_ptr(),
+                    signature.as_mut_ptr()
+                ),
+                OK
+            );
+            assert_eq!(
+                sfun_schnorr_verify(
+                    agg_key.as_ptr(),
+                    message.as_ptr(),
+                    message.len(),
+                    signature.as_ptr()
+                ),
+                OK
+            );
+        }
+    }
+}
@@ -17,7 +17,7 @@ fn sign_ecdsa(c: &mut Criterion) {
     {
         use secp256k1::{Message, Secp256k1, SecretKey};
         let secp = Secp256k1::new();
-        let secret_key = SecretKey::from_slice(&SK.to_bytes()[..]).unwrap();
+        let secret_key = SecretKey::from_slice(SK.to_secret_bytes().expose_secret()).unwrap();
         {
             group.bench_function("secp256k1::ecdsa_sign", |b| {
                 b.iter(|| {
@@ -52,7 +52,7 @@ fn verify_ecdsa(c: &mut Criterion) {
         use secp256k1::{ecdsa::Signature, Message, PublicKey, Secp256k1, SecretKey};
         let secp = Secp256k1::new();
         let sig = Signature::from_compact(signature.to_bytes().as_ref()).unwrap();
-        let secret_key = SecretKey::from_slice(&SK.to_bytes()[..]).unwrap();
+        let secret_key = SecretKey::from_slice(SK.to_secret_bytes().expose_secret()).unwrap();
         let public_key = PublicKey::from_secret_key(&secp, &secret_key);
         {
             group.bench_function("secp256k1::ecdsa_verify", |b| {
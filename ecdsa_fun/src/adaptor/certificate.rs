@@ -0,0 +1,183 @@
+use super::Adaptor;
+use crate::Signature;
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    hash::HashAdd,
+    marker::*,
+    nonce::NonceGen,
+    Point, Scalar, Tag,
+};
+
+/// An encryption key signed by the party that knows (or will learn) its decryption key, binding
+/// it to an escrow session and outcome.
+///
+/// In an escrow or multi-outcome swap each outcome has its own encryption key (anticipation
+/// point) and the counterparty encrypts a signature to the key of the outcome it is paying for. If
+/// the keys are just sent over the wire the party holding the decryption keys can swap the keys of
+/// two outcomes (or reuse a key from another session) so that revealing one outcome decrypts the
+/// signature meant for another. A certificate lets the counterparty check that an encryption key
+/// was committed to for exactly this `session_id` and `outcome` before encrypting to it.
+///
+/// Created with [`Adaptor::certify_encryption_key`] and checked with
+/// [`Adaptor::verify_encryption_key_certificate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct EncryptionKeyCertificate {
+    /// The certified encryption key.
+    pub encryption_key: Point,
+    /// The signature on the encryption key, session id and outcome.
+    pub signature: Signature,
+}
+
+/// The message hash signed by an [`EncryptionKeyCertificate`].
+fn certificate_message_hash<H: Digest<OutputSize = U32> + Tag + Default>(
+    session_id: &[u8],
+    outcome: &[u8],
+    encryption_key: &Point,
+) -> [u8; 32] {
+    H::default()
+        .tag(b"ecdsa_fun/adaptor/encryption_key_certificate")
+        .add((session_id.len() as u64).to_be_bytes())
+        .add(session_id)
+        .add((outcome.len() as u64).to_be_bytes())
+        .add(outcome)
+        .add(encryption_key)
+        .finalize()
+        .into()
+}
+
+impl<T, NG: NonceGen> Adaptor<T, NG> {
+    /// Certifies that `encryption_key` is the key for `outcome` in the session `session_id`.
+    ///
+    /// The certificate is an ECDSA signature by `signing_key` over a tagged hash `H` of the three.
+    /// Only certify a key for one outcome of one session.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ecdsa_fun::{
+    ///     adaptor::{Adaptor, HashTranscript},
+    ///     fun::Scalar,
+    ///     nonce,
+    /// };
+    /// use rand::rngs::ThreadRng;
+    /// use rand_chacha::ChaCha20Rng;
+    /// use sha2::Sha256;
+    /// let adaptor = Adaptor::<
+    ///     HashTranscript<Sha256, ChaCha20Rng>,
+    ///     nonce::Synthetic<Sha256, nonce::GlobalRng<ThreadRng>>,
+    /// >::default();
+    /// // the escrow agent publishes an encryption key for each outcome
+    /// let escrow_key = Scalar::random(&mut rand::thread_rng());
+    /// let escrow_verification_key = adaptor.ecdsa.verification_key_for(&escrow_key);
+    /// let decryption_key = Scalar::random(&mut rand::thread_rng());
+    /// let certificate = adaptor.certify_encryption_key::<Sha256>(
+    ///     &escrow_key,
+    ///     b"escrow-42",
+    ///     b"seller-delivered",
+    ///     adaptor.encryption_key_for(&decryption_key),
+    /// );
+    /// // the buyer checks the key is for the outcome they are paying for before encrypting to it
+    /// assert!(adaptor.verify_encryption_key_certificate::<Sha256>(
+    ///     &escrow_verification_key,
+    ///     b"escrow-42",
+    ///     b"seller-delivered",
+    ///     &certificate,
+    /// ));
+    /// assert!(!adaptor.verify_encryption_key_certificate::<Sha256>(
+    ///     &escrow_verification_key,
+    ///     b"escrow-42",
+    ///     b"refund",
+    ///     &certificate,
+    /// ));
+    /// ```
+    pub fn certify_encryption_key<H: Digest<OutputSize = U32> + Tag + Default>(
+        &self,
+        signing_key: &Scalar,
+        session_id: &[u8],
+        outcome: &[u8],
+        encryption_key: Point,
+    ) -> EncryptionKeyCertificate {
+        let message_hash = certificate_message_hash::<H>(session_id, outcome, &encryption_key);
+        EncryptionKeyCertificate {
+            encryption_key,
+            signature: self.ecdsa.sign(signing_key, &message_hash),
+        }
+    }
+}
+
+impl<T, NG> Adaptor<T, NG> {
+    /// Checks that `certificate` certifies its encryption key for `outcome` in the session
+    /// `session_id` under `verification_key`.
+    ///
+    /// See [`certify_encryption_key`](Self::certify_encryption_key).
+    #[must_use]
+    pub fn verify_encryption_key_certificate<H: Digest<OutputSize = U32> + Tag + Default>(
+        &self,
+        verification_key: &Point<impl PointType, Public, NonZero>,
+        session_id: &[u8],
+        outcome: &[u8],
+        certificate: &EncryptionKeyCertificate,
+    ) -> bool {
+        let message_hash =
+            certificate_message_hash::<H>(session_id, outcome, &certificate.encryption_key);
+        self.ecdsa
+            .verify(verification_key, &message_hash, &certificate.signature)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{adaptor::HashTranscript, fun::nonce};
+    use rand_chacha::ChaCha20Rng;
+    use sha2::Sha256;
+
+    #[test]
+    fn certificates_bind_session_and_outcome() {
+        let adaptor = Adaptor::<HashTranscript<Sha256, ChaCha20Rng>, _>::new(
+            nonce::Deterministic::<Sha256>::default(),
+        );
+        let escrow_key = Scalar::random(&mut rand::thread_rng());
+        let verification_key = adaptor.ecdsa.verification_key_for(&escrow_key);
+        let encryption_key = Point::random(&mut rand::thread_rng());
+        let certificate = adaptor.certify_encryption_key::<Sha256>(
+            &escrow_key,
+            b"session-1",
+            b"outcome-a",
+            encryption_key,
+        );
+        assert!(adaptor.verify_encryption_key_certificate::<Sha256>(
+            &verification_key,
+            b"session-1",
+            b"outcome-a",
+            &certificate
+        ));
+        for (session_id, outcome) in [
+            (&b"session-2"[..], &b"outcome-a"[..]),
+            (b"session-1", b"outcome-b"),
+            // the length prefixes stop the boundary moving
+            (b"session-1o", b"utcome-a"),
+        ] {
+            assert!(!adaptor.verify_encryption_key_certificate::<Sha256>(
+                &verification_key,
+                session_id,
+                outcome,
+                &certificate
+            ));
+        }
+
+        let mut substituted = certificate;
+        substituted.encryption_key = Point::random(&mut rand::thread_rng());
+        assert!(!adaptor.verify_encryption_key_certificate::<Sha256>(
+            &verification_key,
+            b"session-1",
+            b"outcome-a",
+            &substituted
+        ));
+    }
+}
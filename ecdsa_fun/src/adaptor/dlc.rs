@@ -0,0 +1,305 @@
+use super::{Adaptor, EncryptedSignature, EncryptedSignatureInternal, DLEQ};
+use crate::Signature;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    g,
+    hash::HashAdd,
+    marker::*,
+    nonce::NonceGen,
+    op, s, Point, Scalar, Tag, G,
+};
+use sigma_fun::{ProverTranscript, Transcript};
+
+/// The point an oracle's [BIP340] attestation to `outcome` will be the discrete logarithm of.
+///
+/// An oracle with key `S` that has announced the nonce `R` attests to an outcome by publishing the
+/// BIP340 signature `(R, s)` on it. Before it does anyone can compute `s * G = R + c * S` where `c`
+/// is the BIP340 challenge `H(R || S || outcome)` and encrypt a signature to it so the signature
+/// can only be decrypted with that outcome's attestation. This is the anticipation point of
+/// [Discreet Log Contracts]. `outcome` is the raw message the oracle signs and `H` must be SHA256
+/// to match BIP340.
+///
+/// [BIP340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+/// [Discreet Log Contracts]: https://adiabat.github.io/dlc.pdf
+pub fn anticipation_point<H: Digest<OutputSize = U32> + Tag + Default + Clone>(
+    oracle_key: &Point<EvenY>,
+    oracle_nonce: &Point<EvenY>,
+    outcome: &[u8],
+) -> Point {
+    anticipation_points::<H>(oracle_key, oracle_nonce, [outcome])
+        .pop()
+        .expect("one outcome")
+}
+
+/// The [`anticipation_point`] of each of `outcomes` in order.
+pub fn anticipation_points<H: Digest<OutputSize = U32> + Tag + Default + Clone>(
+    oracle_key: &Point<EvenY>,
+    oracle_nonce: &Point<EvenY>,
+    outcomes: impl IntoIterator<Item = impl AsRef<[u8]>>,
+) -> Vec<Point> {
+    let challenge_hash = H::default().tag(b"BIP0340/challenge");
+    let (S, R) = (oracle_key, oracle_nonce);
+    outcomes
+        .into_iter()
+        .map(|outcome| {
+            let c = Scalar::from_hash(challenge_hash.clone().add(R).add(S).add(outcome.as_ref()))
+                .public()
+                .mark_zero();
+            g!(R + c * S)
+                .normalize()
+                .non_zero()
+                // this would mean the oracle's attestation is zero
+                .expect("computationally unreachable")
+        })
+        .collect()
+}
+
+impl<T: Transcript<DLEQ>, NG> Adaptor<T, NG> {
+    /// Creates an encrypted signature on each message hash encrypted to its encryption key.
+    ///
+    /// This is for the contract execution transactions of a DLC where each outcome's transaction
+    /// is signed and encrypted to the outcome's [`anticipation_point`].
+    pub fn encrypted_sign_outcomes(
+        &self,
+        signing_key: &Scalar,
+        outcomes: &[(Point, [u8; 32])],
+    ) -> Vec<EncryptedSignature>
+    where
+        T: ProverTranscript<DLEQ>,
+        NG: NonceGen,
+    {
+        outcomes
+            .iter()
+            .map(|(encryption_key, message_hash)| {
+                self.encrypted_sign(signing_key, encryption_key, message_hash)
+            })
+            .collect()
+    }
+
+    /// Verifies encrypted signatures from [`encrypted_sign_outcomes`] all made with the same
+    /// signing key.
+    ///
+    /// Returns whether every encrypted signature is valid for its outcome under
+    /// `verification_key`, which is `false` if the number of encrypted signatures and outcomes
+    /// differ. The DLEQ proofs are still checked one at a time but the signature equations are
+    /// checked together: a random linear combination of them is checked with a single multi-scalar
+    /// multiplication whose coefficients are derived by hashing everything with `H`. To find which
+    /// one is invalid use [`verify_encrypted_signature`] on each.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ecdsa_fun::{
+    ///     adaptor::{self, Adaptor, HashTranscript},
+    ///     fun::{g, marker::*, nonce, Scalar, G},
+    /// };
+    /// use rand::rngs::ThreadRng;
+    /// use rand_chacha::ChaCha20Rng;
+    /// use sha2::Sha256;
+    /// let adaptor = Adaptor::<
+    ///     HashTranscript<Sha256, ChaCha20Rng>,
+    ///     nonce::Synthetic<Sha256, nonce::GlobalRng<ThreadRng>>,
+    /// >::default();
+    /// // the oracle's key and the nonce it announced for the event
+    /// let oracle_key = g!({ Scalar::random(&mut rand::thread_rng()) } * G)
+    ///     .normalize()
+    ///     .into_point_with_even_y()
+    ///     .0;
+    /// let oracle_nonce = g!({ Scalar::random(&mut rand::thread_rng()) } * G)
+    ///     .normalize()
+    ///     .into_point_with_even_y()
+    ///     .0;
+    /// let points =
+    ///     adaptor::anticipation_points::<Sha256>(&oracle_key, &oracle_nonce, [b"rain", b"sun!"]);
+    /// // Alice signs a contract execution transaction for each outcome
+    /// let alice_key = Scalar::random(&mut rand::thread_rng());
+    /// let alice_verification_key = adaptor.ecdsa.verification_key_for(&alice_key);
+    /// let cet_sighashes = [[1u8; 32], [2u8; 32]];
+    /// let outcomes = points.into_iter().zip(cet_sighashes).collect::<Vec<_>>();
+    /// let encrypted_signatures = adaptor.encrypted_sign_outcomes(&alice_key, &outcomes);
+    /// // Bob checks them all at once
+    /// assert!(adaptor.verify_encrypted_signatures::<Sha256>(
+    ///     &alice_verification_key,
+    ///     &outcomes,
+    ///     &encrypted_signatures
+    /// ));
+    /// ```
+    ///
+    /// [`encrypted_sign_outcomes`]: Adaptor::encrypted_sign_outcomes
+    /// [`verify_encrypted_signature`]: Adaptor::verify_encrypted_signature
+    #[must_use]
+    pub fn verify_encrypted_signatures<H: Digest<OutputSize = U32> + Tag + Default + Clone>(
+        &self,
+        verification_key: &impl Borrow<Point>,
+        outcomes: &[(Point, [u8; 32])],
+        encrypted_signatures: &[EncryptedSignature],
+    ) -> bool {
+        if outcomes.len() != encrypted_signatures.len() {
+            return false;
+        }
+        let X = verification_key.borrow();
+        let mut batch_hash = H::default().tag(b"ecdsa_fun/adaptor/batch").add(X);
+        for ((Y, message_hash), EncryptedSignature(ciphertext)) in
+            outcomes.iter().zip(encrypted_signatures)
+        {
+            let EncryptedSignatureInternal {
+                R,
+                R_hat,
+                s_hat,
+                proof,
+            } = ciphertext;
+            if !self
+                .dleq_proof_system
+                .verify(&(*R_hat, (*Y, R.point)), proof)
+            {
+                return false;
+            }
+            batch_hash = batch_hash
+                .add(Y)
+                .add(&message_hash[..])
+                .add(R.point)
+                .add(R_hat)
+                .add(s_hat);
+        }
+
+        // each equation is s_hat⁻¹ * m * G + s_hat⁻¹ * R_x * X - R_hat = 0
+        let mut g_scalar = s!(0).public();
+        let mut x_scalar = s!(0).public();
+        let mut scalars = Vec::with_capacity(outcomes.len() + 2);
+        let mut points = Vec::with_capacity(outcomes.len() + 2);
+        for (i, ((_, message_hash), EncryptedSignature(ciphertext))) in
+            outcomes.iter().zip(encrypted_signatures).enumerate()
+        {
+            let coefficient =
+                Scalar::from_hash(batch_hash.clone().add((i as u32).to_be_bytes())).public();
+            let m = Scalar::<Public, _>::from_bytes_mod_order(*message_hash);
+            let a_over_s_hat = s!(coefficient * { ciphertext.s_hat.invert() }).public();
+            g_scalar += s!(a_over_s_hat * m).public();
+            x_scalar += s!(a_over_s_hat * { ciphertext.R.x_scalar }).public();
+            scalars.push(s!(-coefficient).public().mark_zero());
+            points.push(ciphertext.R_hat);
+        }
+        scalars.push(g_scalar);
+        points.push(G.normalize());
+        scalars.push(x_scalar);
+        points.push(*X);
+        op::point_scalar_dot_product_vartime(&scalars, &points).is_zero()
+    }
+
+    /// Finds which of the encrypted signatures from [`encrypted_sign_outcomes`] `signature` was
+    /// decrypted from and recovers its decryption key.
+    ///
+    /// When `signature` has been published (e.g. the counterparty broadcast the contract execution
+    /// transaction for the outcome that happened) this returns the index of the outcome and its
+    /// decryption key. For an [`anticipation_point`] the decryption key is the `s` of the oracle's
+    /// attestation. Returns `None` if `signature` isn't the decryption of any of them.
+    ///
+    /// [`encrypted_sign_outcomes`]: Adaptor::encrypted_sign_outcomes
+    pub fn recover_outcome(
+        &self,
+        outcomes: &[(Point, [u8; 32])],
+        encrypted_signatures: &[EncryptedSignature],
+        signature: &Signature,
+    ) -> Option<(usize, Scalar)> {
+        outcomes
+            .iter()
+            .zip(encrypted_signatures)
+            .enumerate()
+            // the nonce identifies the encrypted signature without doing any multiplications
+            .filter(|(_, (_, ciphertext))| ciphertext.0.R.x_scalar == signature.R_x)
+            .find_map(|(i, ((encryption_key, _), ciphertext))| {
+                self.recover_decryption_key(encryption_key, signature, ciphertext)
+                    .map(|decryption_key| (i, decryption_key))
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{adaptor::HashTranscript, fun::nonce};
+    use rand::rngs::ThreadRng;
+    use rand_chacha::ChaCha20Rng;
+    use sha2::Sha256;
+
+    #[test]
+    fn dlc_outcomes() {
+        let adaptor = Adaptor::<
+            HashTranscript<Sha256, ChaCha20Rng>,
+            nonce::Synthetic<Sha256, nonce::GlobalRng<ThreadRng>>,
+        >::default();
+        let mut oracle_secret = Scalar::random(&mut rand::thread_rng());
+        let (oracle_key, negate) = g!(oracle_secret * G).normalize().into_point_with_even_y();
+        oracle_secret.conditional_negate(negate);
+        let mut nonce_secret = Scalar::random(&mut rand::thread_rng());
+        let (oracle_nonce, negate) = g!(nonce_secret * G).normalize().into_point_with_even_y();
+        nonce_secret.conditional_negate(negate);
+
+        let events = [&b"heads"[..], b"tails", b"edge"];
+        let points = anticipation_points::<Sha256>(&oracle_key, &oracle_nonce, events);
+        assert_eq!(
+            points[1],
+            anticipation_point::<Sha256>(&oracle_key, &oracle_nonce, b"tails")
+        );
+
+        let signing_key = Scalar::random(&mut rand::thread_rng());
+        let verification_key = adaptor.ecdsa.verification_key_for(&signing_key);
+        let outcomes = points
+            .into_iter()
+            .zip([[1u8; 32], [2u8; 32], [3u8; 32]])
+            .collect::<Vec<_>>();
+        let encrypted_signatures = adaptor.encrypted_sign_outcomes(&signing_key, &outcomes);
+        assert!(adaptor.verify_encrypted_signatures::<Sha256>(
+            &verification_key,
+            &outcomes,
+            &encrypted_signatures
+        ));
+        for (i, ((Y, m), ciphertext)) in outcomes.iter().zip(&encrypted_signatures).enumerate() {
+            assert!(adaptor.verify_encrypted_signature(&verification_key, Y, m, ciphertext));
+            // a signature encrypted to the wrong outcome doesn't verify
+            let mut swapped = outcomes.clone();
+            swapped.swap(i, (i + 1) % 3);
+            assert!(!adaptor.verify_encrypted_signatures::<Sha256>(
+                &verification_key,
+                &swapped,
+                &encrypted_signatures
+            ));
+        }
+        let mut wrong_messages = outcomes.clone();
+        wrong_messages[2].1 = [4u8; 32];
+        assert!(!adaptor.verify_encrypted_signatures::<Sha256>(
+            &verification_key,
+            &wrong_messages,
+            &encrypted_signatures
+        ));
+        assert!(!adaptor.verify_encrypted_signatures::<Sha256>(
+            &verification_key,
+            &outcomes[..2],
+            &encrypted_signatures
+        ));
+
+        // the oracle attests to "tails" with a BIP340 signature
+        let c = Scalar::from_hash(
+            Sha256::default()
+                .tag(b"BIP0340/challenge")
+                .add(oracle_nonce)
+                .add(oracle_key)
+                .add(&b"tails"[..]),
+        );
+        let attestation = s!(nonce_secret + c * oracle_secret).non_zero().unwrap();
+        let signature = adaptor.decrypt_signature(&attestation, encrypted_signatures[1].clone());
+        assert!(adaptor
+            .ecdsa
+            .verify(&verification_key, &[2u8; 32], &signature));
+        assert_eq!(
+            adaptor.recover_outcome(&outcomes, &encrypted_signatures, &signature),
+            Some((1, attestation))
+        );
+        assert_eq!(
+            adaptor.recover_outcome(&outcomes[..1], &encrypted_signatures[..1], &signature),
+            None
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use super::DLEQ;
-use crate::fun::{marker::*, Point, Scalar};
+use crate::fun::{digest::generic_array::GenericArray, marker::*, FromSliceError, Point, Scalar};
 use sigma_fun::CompactProof;
 
 /// `PointNonce` is a [`NonZero`] Point that also has an x-coordinate that is NonZero
@@ -12,17 +12,24 @@ pub(crate) struct PointNonce {
     pub x_scalar: Scalar<Public>,
 }
 
-secp256kfun::impl_fromstr_deserialize! {
-    name => "compressed secp256k1 point",
-    fn from_bytes(bytes: [u8;33]) -> Option<PointNonce> {
+impl PointNonce {
+    fn from_bytes(bytes: [u8; 33]) -> Option<Self> {
         Point::from_bytes(bytes).and_then(|point| {
-            let x_scalar = Scalar::<Public, Zero>::from_bytes_mod_order(point.to_xonly_bytes()).public()
+            let x_scalar = Scalar::<Public, Zero>::from_bytes_mod_order(point.to_xonly_bytes())
+                .public()
                 .non_zero()?;
             Some(PointNonce { point, x_scalar })
         })
     }
 }
 
+secp256kfun::impl_fromstr_deserialize! {
+    name => "compressed secp256k1 point",
+    fn from_bytes(bytes: [u8;33]) -> Option<PointNonce> {
+        PointNonce::from_bytes(bytes)
+    }
+}
+
 secp256kfun::impl_display_debug_serialize! {
     fn to_bytes(point_nonce: &PointNonce) -> [u8;33] {
         point_nonce.point.to_bytes()
@@ -52,20 +59,58 @@ pub(crate) struct EncryptedSignatureInternal {
 #[derive(Clone, PartialEq)]
 pub struct EncryptedSignature(pub(crate) EncryptedSignatureInternal);
 
-#[cfg(feature = "serde")]
+impl EncryptedSignature {
+    /// The 162 byte encoding of the encrypted signature.
+    ///
+    /// It is the compressed `R` and `R_hat` points, then `s_hat` and finally the challenge and
+    /// response of the DLEQ proof (the same as its `bincode` encoding).
+    pub fn to_bytes(&self) -> [u8; 162] {
+        let es = &self.0;
+        let mut bytes = [0u8; 162];
+        bytes[..33].copy_from_slice(&es.R.point.to_bytes());
+        bytes[33..66].copy_from_slice(&es.R_hat.to_bytes());
+        bytes[66..98].copy_from_slice(&es.s_hat.to_bytes());
+        bytes[98..130].copy_from_slice(&es.proof.challenge);
+        bytes[130..].copy_from_slice(&es.proof.response.to_bytes());
+        bytes
+    }
+
+    /// Decodes an encrypted signature from the encoding produced by [`to_bytes`].
+    ///
+    /// This doesn't check the DLEQ proof. That happens when the encrypted signature is verified.
+    ///
+    /// [`to_bytes`]: Self::to_bytes
+    pub fn from_bytes(bytes: [u8; 162]) -> Option<Self> {
+        Some(EncryptedSignature(EncryptedSignatureInternal {
+            R: PointNonce::from_bytes(bytes[..33].try_into().expect("33 bytes"))?,
+            R_hat: Point::from_slice(&bytes[33..66])?,
+            s_hat: Scalar::from_slice(&bytes[66..98])?.non_zero()?,
+            proof: CompactProof {
+                challenge: GenericArray::clone_from_slice(&bytes[98..130]),
+                response: Scalar::from_slice(&bytes[130..])?,
+            },
+        }))
+    }
+}
+
+impl TryFrom<&[u8]> for EncryptedSignature {
+    type Error = FromSliceError;
+
+    fn try_from(slice: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(FromSliceError::to_array(slice)?).ok_or(FromSliceError::Invalid)
+    }
+}
+
 secp256kfun::impl_display_debug_serialize! {
     fn to_bytes(es: &EncryptedSignature) -> [u8;162] {
-        let mut bytes = [0u8;162];
-        bytes.copy_from_slice(bincode::serialize(&es.0).unwrap().as_slice());
-        bytes
+        es.to_bytes()
     }
 }
 
-#[cfg(feature = "serde")]
 secp256kfun::impl_fromstr_deserialize! {
     name => "ECDSA adaptor signature",
     fn from_bytes(bytes: [u8;162]) -> Option<EncryptedSignature> {
-        bincode::deserialize(&bytes[..]).ok().map(EncryptedSignature)
+        EncryptedSignature::from_bytes(bytes)
     }
 }
 
@@ -109,5 +154,43 @@ mod test {
         let deseriazed = bincode::deserialize::<EncryptedSignature>(&serialized[..]).unwrap();
 
         assert_eq!(deseriazed, encrypted_signature);
+        // the encoding is the same as the bincode encoding of the internal representation
+        assert_eq!(
+            bincode::serialize(&encrypted_signature.0).unwrap(),
+            encrypted_signature.to_bytes()
+        );
+    }
+
+    #[test]
+    fn encrypted_signature_try_from_slice() {
+        use super::*;
+        use crate::{adaptor::Adaptor, fun::nonce};
+        use rand_chacha::ChaCha20Rng;
+        use sha2::Sha256;
+        use sigma_fun::HashTranscript;
+
+        let ecdsa_adaptor = Adaptor::<HashTranscript<Sha256, ChaCha20Rng>, _>::new(
+            nonce::Deterministic::<Sha256>::default(),
+        );
+        let secret_key = Scalar::random(&mut rand::thread_rng());
+        let encryption_key = Point::random(&mut rand::thread_rng());
+        let encrypted_signature =
+            ecdsa_adaptor.encrypted_sign(&secret_key, &encryption_key, &[42u8; 32]);
+        let bytes = encrypted_signature.to_bytes();
+        assert_eq!(
+            EncryptedSignature::try_from(&bytes[..]),
+            Ok(encrypted_signature)
+        );
+        assert_eq!(
+            EncryptedSignature::try_from(&bytes[1..]),
+            Err(FromSliceError::WrongLength {
+                expected: 162,
+                got: 161
+            })
+        );
+        assert_eq!(
+            EncryptedSignature::try_from(&[0u8; 162][..]),
+            Err(FromSliceError::Invalid)
+        );
     }
 }
@@ -63,7 +63,8 @@
 //!     None => panic!("signature is not the decryption of our original encrypted signature"),
 //! }
 //! ```
-use crate::{Signature, ECDSA};
+use crate::{NonceDerivation, Signature, ECDSA};
+use core::borrow::Borrow;
 use secp256kfun::{
     derive_nonce_rng,
     digest::generic_array::typenum::U32,
@@ -77,6 +78,13 @@ use sigma_fun::{secp256k1, Eq, FiatShamir, ProverTranscript, Transcript};
 
 mod encrypted_signature;
 pub use encrypted_signature::*;
+mod certificate;
+pub use certificate::*;
+#[cfg(feature = "alloc")]
+mod dlc;
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub use dlc::*;
 
 pub type DLEQ = Eq<secp256k1::DLG<U32>, secp256k1::DL<U32>>;
 
@@ -98,9 +106,17 @@ where
 
 impl<T: Transcript<DLEQ> + Default, NG: Tag> Adaptor<T, NG> {
     pub fn new(nonce_gen: NG) -> Self {
+        Self::new_with_nonce_derivation(nonce_gen, NonceDerivation::default())
+    }
+
+    /// Create an `Adaptor` instance that derives nonces with a particular version of the nonce
+    /// derivation.
+    ///
+    /// See [`ECDSA::new_with_nonce_derivation`].
+    pub fn new_with_nonce_derivation(nonce_gen: NG, nonce_derivation: NonceDerivation) -> Self {
         let sigma = DLEQ::default();
         Self {
-            ecdsa: ECDSA::new(nonce_gen),
+            ecdsa: ECDSA::new_with_nonce_derivation(nonce_gen, nonce_derivation),
             dleq_proof_system: FiatShamir::new(sigma, T::default(), Some("DLEQ")),
         }
     }
@@ -134,7 +150,7 @@ impl<T: Transcript<DLEQ>, NG> Adaptor<T, NG> {
     pub fn encrypted_sign(
         &self,
         signing_key: &Scalar,
-        encryption_key: &Point,
+        encryption_key: &impl Borrow<Point>,
         message: &[u8; 32],
     ) -> EncryptedSignature
     where
@@ -142,14 +158,24 @@ impl<T: Transcript<DLEQ>, NG> Adaptor<T, NG> {
         NG: NonceGen,
     {
         let x = signing_key;
-        let Y = encryption_key;
+        let Y = encryption_key.borrow();
         let m = Scalar::<Public, _>::from_bytes_mod_order(*message);
-        let mut rng = derive_nonce_rng!(
-            nonce_gen => self.ecdsa.nonce_gen,
-            secret => x,
-            public => [Y, &message[..]],
-            seedable_rng => rand_chacha::ChaCha20Rng
-        );
+        let mut rng = if self.ecdsa.nonce_derivation.is_v1() {
+            derive_nonce_rng!(
+                nonce_gen => self.ecdsa.nonce_gen,
+                secret => x,
+                public => [Y, &message[..]],
+                seedable_rng => rand_chacha::ChaCha20Rng
+            )
+        } else {
+            let X = self.ecdsa.verification_key_for(x);
+            derive_nonce_rng!(
+                nonce_gen => self.ecdsa.nonce_gen,
+                secret => x,
+                public => [X, Y, &message[..]],
+                seedable_rng => rand_chacha::ChaCha20Rng
+            )
+        };
 
         let r = Scalar::random(&mut rng);
         let R_hat = g!(r * G).normalize();
@@ -183,6 +209,58 @@ impl<T: Transcript<DLEQ>, NG> Adaptor<T, NG> {
         .into()
     }
 
+    /// Creates an encrypted signature under the child key `master_signing_key + tweak`.
+    ///
+    /// This is for signing with keys derived from a master key, e.g. with the BIP32 tweak of a
+    /// non-hardened child, without the child secret key having to leave the signer. It returns
+    /// `None` if the child key would be zero (the derived key is invalid).
+    ///
+    /// The encrypted signature is verified with [`verify_encrypted_signature_tweaked`] and
+    /// decrypts to an ordinary signature under the child verification key.
+    ///
+    /// # Example
+    /// ```
+    /// use ecdsa_fun::{
+    ///     adaptor::{Adaptor, HashTranscript},
+    ///     fun::{marker::*, nonce, Scalar},
+    /// };
+    /// # type NonceGen = nonce::Synthetic<sha2::Sha256, nonce::GlobalRng<rand::rngs::ThreadRng>>;
+    /// # type Transcript = HashTranscript<sha2::Sha256, rand_chacha::ChaCha20Rng>;
+    /// let adaptor = Adaptor::<Transcript, NonceGen>::default();
+    /// let master_signing_key = Scalar::random(&mut rand::thread_rng());
+    /// let master_verification_key = adaptor.ecdsa.verification_key_for(&master_signing_key);
+    /// // e.g. the BIP32 tweak for the child
+    /// let tweak = Scalar::random(&mut rand::thread_rng()).mark_zero();
+    /// let encryption_key = adaptor.encryption_key_for(&Scalar::random(&mut rand::thread_rng()));
+    /// let message_hash = [42u8; 32];
+    /// let encrypted_signature = adaptor
+    ///     .encrypted_sign_tweaked(&master_signing_key, &tweak, &encryption_key, &message_hash)
+    ///     .expect("child key is not zero");
+    /// assert!(adaptor.verify_encrypted_signature_tweaked(
+    ///     &master_verification_key,
+    ///     &tweak,
+    ///     &encryption_key,
+    ///     &message_hash,
+    ///     &encrypted_signature,
+    /// ));
+    /// ```
+    ///
+    /// [`verify_encrypted_signature_tweaked`]: Adaptor::verify_encrypted_signature_tweaked
+    pub fn encrypted_sign_tweaked(
+        &self,
+        master_signing_key: &Scalar,
+        tweak: &Scalar<impl Secrecy, Zero>,
+        encryption_key: &impl Borrow<Point>,
+        message: &[u8; 32],
+    ) -> Option<EncryptedSignature>
+    where
+        T: ProverTranscript<DLEQ>,
+        NG: NonceGen,
+    {
+        let child_signing_key = s!(master_signing_key + tweak).non_zero()?;
+        Some(self.encrypted_sign(&child_signing_key, encryption_key, message))
+    }
+
     /// Returns the corresponding encryption key for a decryption key
     ///
     /// # Example
@@ -201,15 +279,15 @@ impl<T: Transcript<DLEQ>, NG> Adaptor<T, NG> {
     ///
     /// [synopsis]: crate::adaptor#synopsis
     #[must_use]
-    pub fn verify_encrypted_signature(
+    pub fn verify_encrypted_signature<VT: PointType, VS: Secrecy>(
         &self,
-        verification_key: &Point<impl PointType, impl Secrecy>,
-        encryption_key: &Point,
+        verification_key: &impl Borrow<Point<VT, VS>>,
+        encryption_key: &impl Borrow<Point>,
         message_hash: &[u8; 32],
         ciphertext: &EncryptedSignature,
     ) -> bool {
-        let X = verification_key;
-        let Y = encryption_key;
+        let X = verification_key.borrow();
+        let Y = encryption_key.borrow();
         let m = Scalar::<Public, _>::from_bytes_mod_order(*message_hash);
         let EncryptedSignature(EncryptedSignatureInternal {
             R,
@@ -229,6 +307,36 @@ impl<T: Transcript<DLEQ>, NG> Adaptor<T, NG> {
         g!((s_hat_inv * m) * G + (s_hat_inv * R.x_scalar) * X) == *R_hat
     }
 
+    /// Verifies an encrypted signature made with [`encrypted_sign_tweaked`] under the child key
+    /// `master_verification_key + tweak * G`.
+    ///
+    /// Returns `false` if the child key is the point at infinity.
+    ///
+    /// [`encrypted_sign_tweaked`]: Adaptor::encrypted_sign_tweaked
+    #[must_use]
+    pub fn verify_encrypted_signature_tweaked<VT: PointType, VS: Secrecy>(
+        &self,
+        master_verification_key: &impl Borrow<Point<VT, VS>>,
+        tweak: &Scalar<impl Secrecy, Zero>,
+        encryption_key: &impl Borrow<Point>,
+        message_hash: &[u8; 32],
+        ciphertext: &EncryptedSignature,
+    ) -> bool {
+        let master_verification_key = master_verification_key.borrow();
+        match g!(master_verification_key + tweak * G)
+            .normalize()
+            .non_zero()
+        {
+            Some(child_verification_key) => self.verify_encrypted_signature(
+                &child_verification_key,
+                encryption_key,
+                message_hash,
+                ciphertext,
+            ),
+            None => false,
+        }
+    }
+
     /// Decrypts an encrypted signature yielding the signature.
     ///
     /// There are two crucial things to understand when calling this:
@@ -251,7 +359,7 @@ impl<T: Transcript<DLEQ>, NG> Adaptor<T, NG> {
         let EncryptedSignature(EncryptedSignatureInternal { R, s_hat, .. }) = ciphertext;
         let y = decryption_key;
         let mut s = s!(s_hat * { y.invert() });
-        s.conditional_negate(s.is_high());
+        s.conditional_negate_ct(s.is_high_ct());
         Signature {
             R_x: R.x_scalar.public(),
             s: s.public(),
@@ -271,9 +379,9 @@ impl<T: Transcript<DLEQ>, NG> Adaptor<T, NG> {
     ///
     /// [`ECDSA::verify`]: crate::ECDSA::verify
     /// [synopsis]: crate::adaptor#synopsis
-    pub fn recover_decryption_key(
+    pub fn recover_decryption_key<ET: Normalized, ES: Secrecy>(
         &self,
-        encryption_key: &Point<impl Normalized, impl Secrecy>,
+        encryption_key: &impl Borrow<Point<ET, ES>>,
         signature: &Signature,
         ciphertext: &EncryptedSignature,
     ) -> Option<Scalar> {
@@ -281,7 +389,7 @@ impl<T: Transcript<DLEQ>, NG> Adaptor<T, NG> {
         // Check we are not looking at some unrelated signature
         if R.x_scalar != signature.R_x
                 // Enforce low_s
-            || (signature.s.is_high() && self.ecdsa.enforce_low_s)
+            || (signature.s.is_high() && self.ecdsa.enforces_low_s())
         {
             return None;
         }
@@ -289,6 +397,7 @@ impl<T: Transcript<DLEQ>, NG> Adaptor<T, NG> {
         let y = s!(s.invert() * s_hat);
         let Y = g!(y * G);
 
+        let encryption_key = encryption_key.borrow();
         if Y == *encryption_key {
             Some(y)
         } else if -Y == *encryption_key {
@@ -337,4 +446,60 @@ mod test {
             assert_eq!(recoverd_decryption_sk, decryption_key);
         }
     }
+
+    #[test]
+    fn tweaked_signing_key() {
+        let nonce_gen = nonce::Synthetic::<Sha256, nonce::GlobalRng<ThreadRng>>::default();
+        let ecdsa_adaptor = Adaptor::<HashTranscript<Sha256, ChaCha20Rng>, _>::new(nonce_gen);
+        let msg = b"hello world you are beautiful!!!";
+        let master_signing_key = Scalar::random(&mut rand::thread_rng());
+        let master_verification_key = ecdsa_adaptor
+            .ecdsa
+            .verification_key_for(&master_signing_key);
+        let tweak = Scalar::random(&mut rand::thread_rng()).mark_zero();
+        let child_verification_key = g!(master_verification_key + tweak * G)
+            .normalize()
+            .non_zero()
+            .unwrap();
+        let decryption_key = Scalar::random(&mut rand::thread_rng());
+        let encryption_key = ecdsa_adaptor.encryption_key_for(&decryption_key);
+        let ciphertext = ecdsa_adaptor
+            .encrypted_sign_tweaked(&master_signing_key, &tweak, &encryption_key, msg)
+            .unwrap();
+        assert!(ecdsa_adaptor.verify_encrypted_signature_tweaked(
+            &master_verification_key,
+            &tweak,
+            &encryption_key,
+            msg,
+            &ciphertext,
+        ));
+        assert!(!ecdsa_adaptor.verify_encrypted_signature(
+            &master_verification_key,
+            &encryption_key,
+            msg,
+            &ciphertext,
+        ));
+
+        let signature = ecdsa_adaptor.decrypt_signature(&decryption_key, ciphertext.clone());
+        assert!(ecdsa_adaptor
+            .ecdsa
+            .verify(&child_verification_key, msg, &signature));
+        assert_eq!(
+            ecdsa_adaptor.recover_decryption_key(&encryption_key, &signature, &ciphertext),
+            Some(decryption_key)
+        );
+
+        // the tweak that cancels out the master key gives an invalid child key
+        let cancelling_tweak = s!(-master_signing_key).mark_zero();
+        assert!(ecdsa_adaptor
+            .encrypted_sign_tweaked(&master_signing_key, &cancelling_tweak, &encryption_key, msg)
+            .is_none());
+        assert!(!ecdsa_adaptor.verify_encrypted_signature_tweaked(
+            &master_verification_key,
+            &cancelling_tweak,
+            &encryption_key,
+            msg,
+            &ciphertext,
+        ));
+    }
 }
@@ -0,0 +1,300 @@
+//! Anti-exfil signing where the host contributes randomness to the signer's nonce.
+//!
+//! A hardware signer that chooses its own nonces can leak its secret key a few bits at a time
+//! through the nonces of the signatures it produces and nobody looking at the signatures can
+//! tell. This is the host commitment protocol from libsecp256k1-zkp's ECDSA anti-exfil module
+//! which lets the host make sure the nonce has randomness from it in it:
+//!
+//! 1. The host picks 32 fresh random bytes `ρ` and sends the signer [`HostCommitment::new`]`(ρ)`.
+//! 2. The signer derives a nonce `r₀` from its key, the message hash and the host commitment and
+//!    sends back `R₀ = r₀ * G` with [`ECDSA::commit_nonce_to_host`].
+//! 3. The host reveals `ρ` and the signer signs with [`ECDSA::sign_with_host_randomness`] using
+//!    the nonce `r₀ + t` where `t = H(R₀ || ρ)`.
+//! 4. The host checks the signature is valid and that its nonce is `R₀ + t * G` with
+//!    [`ECDSA::verify_host_commitment`].
+//!
+//! The signer commits to its nonce before it learns `ρ` so it can't choose a nonce that encodes
+//! anything and the host commits to `ρ` before it learns `R₀` so it can't bias the nonce either.
+//! With `H = Sha256` the host commitment, the signer commitment encoding and the tweak are the
+//! same as libsecp256k1-zkp's so a host using this module can talk to a signer using that library
+//! and vice versa.
+//!
+//! The signer rederives `r₀` from the host commitment in the last step rather than storing it so
+//! only instances with a [`Deterministic`] nonce generator can do this. The host's randomness does
+//! the job that the randomness in a synthetic nonce would.
+//!
+//! # Example
+//!
+//! ```
+//! use ecdsa_fun::{anti_exfil::HostCommitment, fun::Scalar, nonce, ECDSA};
+//! use sha2::Sha256;
+//! let ecdsa = ECDSA::new(nonce::Deterministic::<Sha256>::default());
+//! let secret_key = Scalar::random(&mut rand::thread_rng());
+//! let message_hash = [42u8; 32];
+//!
+//! // host
+//! let mut host_randomness = [0u8; 32];
+//! rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut host_randomness);
+//! let host_commitment = HostCommitment::new::<Sha256>(&host_randomness);
+//! // signer
+//! let signer_commitment = ecdsa.commit_nonce_to_host(&secret_key, &message_hash, &host_commitment);
+//! // host sends host_randomness, signer
+//! let signature =
+//!     ecdsa.sign_with_host_randomness::<Sha256>(&secret_key, &message_hash, &host_randomness);
+//! // host
+//! assert!(ecdsa.verify_host_commitment::<Sha256>(
+//!     &ecdsa.verification_key_for(&secret_key),
+//!     &message_hash,
+//!     &signature,
+//!     &signer_commitment,
+//!     &host_randomness
+//! ));
+//! ```
+//!
+//! [`Deterministic`]: crate::nonce::Deterministic
+use crate::{
+    fun::{
+        derive_nonce,
+        digest::{generic_array::typenum::U32, Digest},
+        g,
+        hash::{HashAdd, Tag},
+        marker::*,
+        nonce::Deterministic,
+        s, Point, Scalar, G,
+    },
+    Signature, ECDSA,
+};
+
+/// Separates anti-exfil nonces from the nonces [`ECDSA::sign`] and [`ECDSA::commit_nonce`]
+/// derive.
+///
+/// With it the public input is always longer than `sign`'s (which is `X || message_hash`) and
+/// differs from `commit_nonce`'s at the same position.
+const ANTI_EXFIL_DOMAIN: [u8; 32] =
+    *b"\x00ecdsa_fun/anti_exfil\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00";
+
+/// The host's commitment to the randomness it will contribute to a signature's nonce.
+///
+/// Create it with [`new`](Self::new) and send it to the signer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct HostCommitment([u8; 32]);
+
+impl HostCommitment {
+    /// Commits to `host_randomness` which must be freshly chosen for each signature.
+    pub fn new<H: Digest<OutputSize = U32> + Tag + Default>(host_randomness: &[u8; 32]) -> Self {
+        let hash = H::default()
+            .tag(b"s2c/ecdsa/data")
+            .add(&host_randomness[..]);
+        Self(hash.finalize().into())
+    }
+
+    /// The commitment as bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+
+    /// The commitment from the bytes produced by [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+}
+
+/// The signer's commitment to the nonce it will tweak with the host's randomness.
+///
+/// Created with [`ECDSA::commit_nonce_to_host`] and checked by the host with
+/// [`ECDSA::verify_host_commitment`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct SignerCommitment {
+    R: Point,
+}
+
+impl SignerCommitment {
+    /// The nonce `R₀` before it is tweaked with the host's randomness.
+    pub fn R(&self) -> Point {
+        self.R
+    }
+
+    /// The commitment as the 33-byte compressed encoding of `R₀`.
+    pub fn to_bytes(&self) -> [u8; 33] {
+        self.R.to_bytes()
+    }
+
+    /// Decodes a commitment encoded with [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: [u8; 33]) -> Option<Self> {
+        Some(Self {
+            R: Point::from_bytes(bytes)?,
+        })
+    }
+}
+
+fn tweak<H: Digest<OutputSize = U32> + Tag + Default>(
+    R: &Point,
+    host_randomness: &[u8; 32],
+) -> Scalar<Public> {
+    Scalar::from_hash(
+        H::default()
+            .tag(b"s2c/ecdsa/point")
+            .add(*R)
+            .add(&host_randomness[..]),
+    )
+    .public()
+}
+
+impl<NH: Digest<OutputSize = U32> + Clone> ECDSA<Deterministic<NH>> {
+    fn anti_exfil_nonce(
+        &self,
+        secret_key: &Scalar,
+        message_hash: &[u8; 32],
+        host_commitment: &HostCommitment,
+    ) -> Scalar {
+        let X = self.verification_key_for(secret_key);
+        derive_nonce!(
+            nonce_gen => self.nonce_gen,
+            secret => secret_key,
+            public => [
+                X,
+                ANTI_EXFIL_DOMAIN,
+                &message_hash[..],
+                &host_commitment.0[..]
+            ]
+        )
+    }
+
+    /// Commits to the nonce for a signature on `message_hash` before the host reveals its
+    /// randomness.
+    ///
+    /// Send the result to the host. This is called `commit_nonce_to_host` to tell it apart from
+    /// [`commit_nonce`](Self::commit_nonce) which commits to a nonce before the message is known.
+    /// See the [module documentation](crate::anti_exfil).
+    pub fn commit_nonce_to_host(
+        &self,
+        secret_key: &Scalar,
+        message_hash: &[u8; 32],
+        host_commitment: &HostCommitment,
+    ) -> SignerCommitment {
+        let r = self.anti_exfil_nonce(secret_key, message_hash, host_commitment);
+        SignerCommitment {
+            R: g!(r * G).normalize(),
+        }
+    }
+
+    /// Signs `message_hash` with the nonce committed to by [`commit_nonce_to_host`] tweaked by the
+    /// host's randomness.
+    ///
+    /// `host_randomness` must open the host commitment passed to [`commit_nonce_to_host`]. If it
+    /// doesn't this signs with an unrelated nonce and the host's [`verify_host_commitment`] will
+    /// fail.
+    ///
+    /// [`commit_nonce_to_host`]: Self::commit_nonce_to_host
+    /// [`verify_host_commitment`]: Self::verify_host_commitment
+    pub fn sign_with_host_randomness<H: Digest<OutputSize = U32> + Tag + Default>(
+        &self,
+        secret_key: &Scalar,
+        message_hash: &[u8; 32],
+        host_randomness: &[u8; 32],
+    ) -> Signature {
+        let host_commitment = HostCommitment::new::<H>(host_randomness);
+        let r = self.anti_exfil_nonce(secret_key, message_hash, &host_commitment);
+        let R = g!(r * G).normalize();
+        let t = tweak::<H>(&R, host_randomness);
+        let r = s!(r + t).non_zero().expect("computationally unreachable");
+        crate::sign_with_nonce(secret_key, &r, message_hash)
+    }
+}
+
+impl<NG> ECDSA<NG> {
+    /// Checks that `signature` is valid and that its nonce is the one the signer committed to in
+    /// `signer_commitment` tweaked by `host_randomness`.
+    ///
+    /// This is what the host does at the end of the protocol. See the [module
+    /// documentation](crate::anti_exfil).
+    #[must_use]
+    pub fn verify_host_commitment<H: Digest<OutputSize = U32> + Tag + Default>(
+        &self,
+        verification_key: &Point<impl PointType, Public, NonZero>,
+        message_hash: &[u8; 32],
+        signature: &Signature,
+        signer_commitment: &SignerCommitment,
+        host_randomness: &[u8; 32],
+    ) -> bool {
+        let t = tweak::<H>(&signer_commitment.R, host_randomness);
+        let nonce_matches = g!(signer_commitment.R + t * G)
+            .normalize()
+            .non_zero()
+            .map_or(false, |R| {
+                Scalar::<Public, _>::from_bytes_mod_order(R.to_xonly_bytes()) == signature.R_x
+            });
+        nonce_matches && self.verify(verification_key, message_hash, signature)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+
+    #[test]
+    fn anti_exfil_sign_and_verify() {
+        let ecdsa = crate::test_instance!();
+        let secret_key = Scalar::random(&mut rand::thread_rng());
+        let verification_key = ecdsa.verification_key_for(&secret_key);
+        let message_hash = [3u8; 32];
+        let host_randomness = [7u8; 32];
+        let host_commitment = HostCommitment::new::<Sha256>(&host_randomness);
+
+        let signer_commitment =
+            ecdsa.commit_nonce_to_host(&secret_key, &message_hash, &host_commitment);
+        assert_eq!(
+            SignerCommitment::from_bytes(signer_commitment.to_bytes()),
+            Some(signer_commitment)
+        );
+        let signature =
+            ecdsa.sign_with_host_randomness::<Sha256>(&secret_key, &message_hash, &host_randomness);
+        assert!(ecdsa.verify(&verification_key, &message_hash, &signature));
+        assert!(ecdsa.verify_host_commitment::<Sha256>(
+            &verification_key,
+            &message_hash,
+            &signature,
+            &signer_commitment,
+            &host_randomness
+        ));
+
+        // different randomness from the host gives a different nonce
+        assert!(!ecdsa.verify_host_commitment::<Sha256>(
+            &verification_key,
+            &message_hash,
+            &signature,
+            &signer_commitment,
+            &[8u8; 32]
+        ));
+        // a signer that ignores the host's randomness is caught
+        let plain_signature = ecdsa.sign(&secret_key, &message_hash);
+        assert!(!ecdsa.verify_host_commitment::<Sha256>(
+            &verification_key,
+            &message_hash,
+            &plain_signature,
+            &signer_commitment,
+            &host_randomness
+        ));
+        // the committed nonce isn't one `commit_nonce` or `sign` would use
+        let R_x = |R: Point| Scalar::<Public, _>::from_bytes_mod_order(R.to_xonly_bytes());
+        assert_ne!(R_x(signer_commitment.R()), plain_signature.R_x);
+        assert_ne!(
+            signer_commitment.R(),
+            ecdsa
+                .commit_nonce(&secret_key, &host_commitment.to_bytes())
+                .commitment()
+                .R()
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! Bitcoin's `OP_CHECKSIG` decision for ECDSA signatures without a full node.
+//!
+//! A signature a wallet produces has to pass two sets of rules: consensus (what a block may
+//! contain) and the stricter policy that nodes apply before relaying a transaction. [`checksig`]
+//! takes the bytes that would be on the script stack (the signature with its sighash flag and the
+//! public key), the sighash and the policy [`ScriptFlags`] and returns a [`ChecksigDecision`] of
+//! whether each rule set accepts it. [`eval_checksig`] is the underlying evaluation with an
+//! explicit flag set which, like Bitcoin's interpreter, distinguishes a signature that fails to
+//! verify (`Ok(false)` which a script can go on from) from an encoding error that fails the
+//! script.
+//!
+//! The rules mirrored are:
+//!
+//! - Strict DER encoding of the signature ([BIP66]): always enforced since it is consensus.
+//! - Low `s` ([BIP146]): [`ScriptFlags::low_s`].
+//! - Defined sighash flag and compressed or uncompressed public key encoding (`STRICTENC`):
+//!   [`ScriptFlags::strict_encoding`]. Without it "hybrid" public keys (`0x06`/`0x07` prefix) are
+//!   accepted as consensus allows.
+//!
+//! An empty signature is never an error, it just doesn't verify.
+//!
+//! # Example
+//!
+//! ```
+//! use ecdsa_fun::{
+//!     checksig::{checksig, ScriptFlags},
+//!     fun::{s, Scalar},
+//!     nonce, Signature, ECDSA,
+//! };
+//! let ecdsa = ECDSA::new(nonce::Deterministic::<sha2::Sha256>::default());
+//! let secret_key = Scalar::random(&mut rand::thread_rng());
+//! let public_key = ecdsa.verification_key_for(&secret_key).to_bytes();
+//! let sighash = [42u8; 32];
+//! let signature = ecdsa.sign(&secret_key, &sighash);
+//!
+//! let decision = checksig(
+//!     &signature.to_bitcoin_script_bytes(0x01),
+//!     &public_key,
+//!     &sighash,
+//!     ScriptFlags::STANDARD,
+//! );
+//! assert!(decision.consensus && decision.policy);
+//!
+//! // a high s is valid by consensus but isn't relayed
+//! let high_s = Signature { R_x: signature.R_x, s: s!(-signature.s).public() };
+//! let decision = checksig(
+//!     &high_s.to_bitcoin_script_bytes(0x01),
+//!     &public_key,
+//!     &sighash,
+//!     ScriptFlags::STANDARD,
+//! );
+//! assert!(decision.consensus && !decision.policy);
+//! ```
+//!
+//! [BIP66]: https://github.com/bitcoin/bips/blob/master/bip-0066.mediawiki
+//! [BIP146]: https://github.com/bitcoin/bips/blob/master/bip-0146.mediawiki#low_s
+use crate::{fun::Point, Signature, ECDSA};
+use core::fmt;
+
+/// The script verification flags that affect `OP_CHECKSIG` beyond consensus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ScriptFlags {
+    /// Reject signatures with a high `s` (`SCRIPT_VERIFY_LOW_S`).
+    pub low_s: bool,
+    /// Reject undefined sighash flags and public keys that aren't compressed or uncompressed SEC1
+    /// (`SCRIPT_VERIFY_STRICTENC`).
+    pub strict_encoding: bool,
+}
+
+impl ScriptFlags {
+    /// Consensus rules only.
+    pub const CONSENSUS: Self = Self {
+        low_s: false,
+        strict_encoding: false,
+    };
+
+    /// The flags Bitcoin Core's relay policy applies to `OP_CHECKSIG`.
+    pub const STANDARD: Self = Self {
+        low_s: true,
+        strict_encoding: true,
+    };
+}
+
+/// Whether consensus and policy accept a signature. Returned by [`checksig`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ChecksigDecision {
+    /// Whether `OP_CHECKSIG` succeeds under consensus rules.
+    pub consensus: bool,
+    /// Whether `OP_CHECKSIG` succeeds under consensus rules plus the policy flags.
+    pub policy: bool,
+}
+
+/// Evaluates `OP_CHECKSIG` on `signature` (DER followed by the sighash flag) and `public_key` over
+/// `sighash` under consensus rules and under consensus plus `policy` returning both decisions.
+///
+/// See the [module documentation](crate::checksig).
+pub fn checksig(
+    signature: &[u8],
+    public_key: &[u8],
+    sighash: &[u8; 32],
+    policy: ScriptFlags,
+) -> ChecksigDecision {
+    ChecksigDecision {
+        consensus: eval_checksig(signature, public_key, sighash, ScriptFlags::CONSENSUS)
+            == Ok(true),
+        policy: eval_checksig(signature, public_key, sighash, policy) == Ok(true),
+    }
+}
+
+/// Evaluates `OP_CHECKSIG` with `flags` on top of consensus rules.
+///
+/// Returns `Ok(false)` if the signature doesn't verify and an error if an encoding rule fails the
+/// script outright.
+pub fn eval_checksig(
+    signature: &[u8],
+    public_key: &[u8],
+    sighash: &[u8; 32],
+    flags: ScriptFlags,
+) -> Result<bool, ScriptError> {
+    let (&sighash_flag, der) = match signature.split_last() {
+        Some(split) => split,
+        None => return Ok(false),
+    };
+    if !is_valid_signature_encoding(signature) {
+        return Err(ScriptError::SigDer);
+    }
+    // the encoding is valid so this only fails if an integer is zero or not less than the order
+    let parsed = Signature::from_der(der);
+    if flags.low_s && parsed.as_ref().map_or(false, |parsed| parsed.s.is_high()) {
+        return Err(ScriptError::SigHighS);
+    }
+    if flags.strict_encoding && !matches!(sighash_flag & !0x80, 0x01..=0x03) {
+        return Err(ScriptError::SigHashType);
+    }
+    if flags.strict_encoding && !is_compressed_or_uncompressed(public_key) {
+        return Err(ScriptError::PubKeyType);
+    }
+
+    let (mut parsed, public_key) = match (parsed, parse_public_key(public_key)) {
+        (Some(parsed), Some(public_key)) => (parsed, public_key),
+        _ => return Ok(false),
+    };
+    // consensus allows high s so verify the equivalent low s signature whatever this crate's
+    // defaults are
+    if parsed.s.is_high() {
+        parsed.s = -parsed.s;
+    }
+    Ok(ECDSA::verify_only().verify(&public_key, sighash, &parsed))
+}
+
+/// Bitcoin's `IsValidSignatureEncoding` from [BIP66] (the signature includes its sighash flag).
+///
+/// [BIP66]: https://github.com/bitcoin/bips/blob/master/bip-0066.mediawiki
+fn is_valid_signature_encoding(sig: &[u8]) -> bool {
+    let size = sig.len();
+    if !(9..=73).contains(&size) || sig[0] != 0x30 || sig[1] as usize != size - 3 {
+        return false;
+    }
+    let len_r = sig[3] as usize;
+    if 5 + len_r >= size {
+        return false;
+    }
+    let len_s = sig[5 + len_r] as usize;
+    if len_r + len_s + 7 != size {
+        return false;
+    }
+    let integer_ok = |start: usize, len: usize| {
+        sig[start - 2] == 0x02
+            && len != 0
+            && sig[start] & 0x80 == 0
+            && !(len > 1 && sig[start] == 0x00 && sig[start + 1] & 0x80 == 0)
+    };
+    integer_ok(4, len_r) && integer_ok(len_r + 6, len_s)
+}
+
+/// Bitcoin's `IsCompressedOrUncompressedPubKey`. Only the length and prefix are checked.
+fn is_compressed_or_uncompressed(public_key: &[u8]) -> bool {
+    match public_key.first().copied() {
+        Some(0x02 | 0x03) => public_key.len() == 33,
+        Some(0x04) => public_key.len() == 65,
+        _ => false,
+    }
+}
+
+/// Parses a public key the way consensus does including hybrid keys whose prefix (`0x06` or
+/// `0x07`) must match the parity of `y`.
+fn parse_public_key(public_key: &[u8]) -> Option<Point> {
+    match (*public_key.first()?, public_key.len()) {
+        (0x02 | 0x03, 33) => Point::from_slice(public_key),
+        (0x04, 65) => Point::from_bytes_uncompressed(public_key.try_into().ok()?),
+        (prefix @ (0x06 | 0x07), 65) => {
+            let mut uncompressed: [u8; 65] = public_key.try_into().ok()?;
+            uncompressed[0] = 0x04;
+            let point = Point::from_bytes_uncompressed(uncompressed)?;
+            if point.is_y_even() != (prefix == 0x06) {
+                return None;
+            }
+            Some(point)
+        }
+        _ => None,
+    }
+}
+
+/// An `OP_CHECKSIG` encoding rule that fails the script. Returned by [`eval_checksig`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ScriptError {
+    /// The signature isn't strict DER (`SCRIPT_ERR_SIG_DER`).
+    SigDer,
+    /// The signature has a high `s` (`SCRIPT_ERR_SIG_HIGH_S`).
+    SigHighS,
+    /// The sighash flag isn't defined (`SCRIPT_ERR_SIG_HASHTYPE`).
+    SigHashType,
+    /// The public key isn't compressed or uncompressed (`SCRIPT_ERR_PUBKEYTYPE`).
+    PubKeyType,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ScriptError::SigDer => "non-canonical DER signature",
+            ScriptError::SigHighS => "non-canonical signature: S value is unnecessarily high",
+            ScriptError::SigHashType => "signature hash type missing or not understood",
+            ScriptError::PubKeyType => "public key is neither compressed or uncompressed",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ScriptError {}
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use super::*;
+    use crate::fun::{s, Scalar};
+
+    #[test]
+    fn checksig_rules() {
+        let ecdsa = crate::test_instance!();
+        let secret_key = Scalar::random(&mut rand::thread_rng());
+        let public_key = ecdsa.verification_key_for(&secret_key);
+        let sighash = [7u8; 32];
+        let signature = ecdsa.sign(&secret_key, &sighash);
+        let compressed = public_key.to_bytes();
+        let script_sig = signature.to_bitcoin_script_bytes(0x01);
+        let accept_both = ChecksigDecision {
+            consensus: true,
+            policy: true,
+        };
+        let consensus_only = ChecksigDecision {
+            consensus: true,
+            policy: false,
+        };
+
+        assert_eq!(
+            checksig(&script_sig, &compressed, &sighash, ScriptFlags::STANDARD),
+            accept_both
+        );
+        assert_eq!(
+            eval_checksig(&script_sig, &compressed, &[8u8; 32], ScriptFlags::STANDARD),
+            Ok(false)
+        );
+        assert_eq!(
+            eval_checksig(&[], &compressed, &sighash, ScriptFlags::STANDARD),
+            Ok(false)
+        );
+
+        let high_s = Signature {
+            R_x: signature.R_x,
+            s: s!(-signature.s).public(),
+        };
+        assert_eq!(
+            eval_checksig(
+                &high_s.to_bitcoin_script_bytes(0x01),
+                &compressed,
+                &sighash,
+                ScriptFlags::STANDARD
+            ),
+            Err(ScriptError::SigHighS)
+        );
+        assert_eq!(
+            checksig(
+                &high_s.to_bitcoin_script_bytes(0x01),
+                &compressed,
+                &sighash,
+                ScriptFlags::STANDARD
+            ),
+            consensus_only
+        );
+
+        let undefined_sighash = signature.to_bitcoin_script_bytes(0x04);
+        assert_eq!(
+            eval_checksig(
+                &undefined_sighash,
+                &compressed,
+                &sighash,
+                ScriptFlags::STANDARD
+            ),
+            Err(ScriptError::SigHashType)
+        );
+        assert_eq!(
+            checksig(
+                &undefined_sighash,
+                &compressed,
+                &sighash,
+                ScriptFlags::STANDARD
+            ),
+            consensus_only
+        );
+
+        let uncompressed = public_key.to_bytes_uncompressed();
+        let mut hybrid = uncompressed;
+        hybrid[0] = if public_key.is_y_even() { 0x06 } else { 0x07 };
+        assert_eq!(
+            checksig(&script_sig, &uncompressed, &sighash, ScriptFlags::STANDARD),
+            accept_both
+        );
+        assert_eq!(
+            eval_checksig(&script_sig, &hybrid, &sighash, ScriptFlags::STANDARD),
+            Err(ScriptError::PubKeyType)
+        );
+        assert_eq!(
+            checksig(&script_sig, &hybrid, &sighash, ScriptFlags::STANDARD),
+            consensus_only
+        );
+        hybrid[0] ^= 1;
+        assert_eq!(
+            eval_checksig(&script_sig, &hybrid, &sighash, ScriptFlags::CONSENSUS),
+            Ok(false)
+        );
+
+        // padding the integer breaks strict DER which is consensus
+        let mut padded = vec![0x30, script_sig[1] + 1, 0x02, script_sig[3] + 1, 0x00];
+        padded.extend_from_slice(&script_sig[4..]);
+        assert_eq!(
+            eval_checksig(&padded, &compressed, &sighash, ScriptFlags::CONSENSUS),
+            Err(ScriptError::SigDer)
+        );
+        assert_eq!(
+            checksig(&padded, &compressed, &sighash, ScriptFlags::CONSENSUS),
+            ChecksigDecision {
+                consensus: false,
+                policy: false
+            }
+        );
+    }
+}
@@ -0,0 +1,344 @@
+//! A harness for checking that signature verification agrees with other implementations.
+//!
+//! Consensus critical applications have to accept exactly the signatures every other node does so
+//! it's not enough for verification to be correct: it also has to make the same decisions about
+//! odd encodings. [`verify_compat`] takes raw signature and public key bytes and tries every
+//! [`SignatureEncoding`], [`PublicKeyEncoding`] and [`SPolicy`] this crate supports, returning a
+//! [`CompatMatrix`] of which combinations parse and which verify. Fuzz it against another
+//! implementation's decisions on the same bytes to find places where they disagree.
+//!
+//! With the `secure-defaults` feature high `s` values are always rejected so the
+//! [`SPolicy::AllowHigh`] decisions are the same as the [`SPolicy::RequireLow`] ones.
+//!
+//! # Example
+//!
+//! ```
+//! use ecdsa_fun::{
+//!     compat::{verify_compat, PublicKeyEncoding, SPolicy, SignatureEncoding},
+//!     fun::Scalar,
+//!     nonce, ECDSA,
+//! };
+//! let ecdsa = ECDSA::new(nonce::Deterministic::<sha2::Sha256>::default());
+//! let secret_key = Scalar::random(&mut rand::thread_rng());
+//! let public_key = ecdsa.verification_key_for(&secret_key);
+//! let message = [42u8; 32];
+//! let signature = ecdsa.sign(&secret_key, &message);
+//!
+//! let matrix = verify_compat(&signature.to_der(), &public_key.to_bytes(), &message);
+//! assert!(matrix.accepts(
+//!     SignatureEncoding::Der,
+//!     PublicKeyEncoding::Compressed,
+//!     SPolicy::RequireLow
+//! ));
+//! // 72 bytes of DER aren't a 64 byte compact signature
+//! assert!(!matrix.signature_parses(SignatureEncoding::Compact));
+//! // in a fuzzer you'd compare this with the other implementation's decisions
+//! let _bits = matrix.to_bits();
+//! ```
+use crate::{fun::Point, Signature, ECDSA};
+use core::fmt;
+
+/// How the signature bytes are interpreted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SignatureEncoding {
+    /// The 64 byte `R_x || s` encoding of [`Signature::from_bytes`].
+    Compact,
+    /// Strict DER as parsed by [`Signature::from_der`].
+    Der,
+    /// DER followed by a sighash flag as parsed by [`Signature::from_bitcoin_script_bytes`].
+    BitcoinScript,
+}
+
+impl SignatureEncoding {
+    /// Every signature encoding.
+    pub const ALL: [Self; 3] = [Self::Compact, Self::Der, Self::BitcoinScript];
+
+    /// Parses `bytes` with this encoding.
+    pub fn parse(self, bytes: &[u8]) -> Option<Signature> {
+        match self {
+            Self::Compact => Signature::try_from(bytes).ok(),
+            Self::Der => Signature::from_der(bytes),
+            Self::BitcoinScript => {
+                Signature::from_bitcoin_script_bytes(bytes).map(|(signature, _)| signature)
+            }
+        }
+    }
+}
+
+impl fmt::Display for SignatureEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Compact => "compact",
+            Self::Der => "der",
+            Self::BitcoinScript => "bitcoin-script",
+        })
+    }
+}
+
+/// How the public key bytes are interpreted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PublicKeyEncoding {
+    /// The 33 byte SEC1 compressed encoding.
+    Compressed,
+    /// The 65 byte SEC1 uncompressed encoding.
+    Uncompressed,
+}
+
+impl PublicKeyEncoding {
+    /// Every public key encoding.
+    pub const ALL: [Self; 2] = [Self::Compressed, Self::Uncompressed];
+
+    /// Parses `bytes` with this encoding.
+    pub fn parse(self, bytes: &[u8]) -> Option<Point> {
+        match self {
+            Self::Compressed => Point::from_slice(bytes),
+            Self::Uncompressed => Point::from_bytes_uncompressed(bytes.try_into().ok()?),
+        }
+    }
+}
+
+impl fmt::Display for PublicKeyEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Compressed => "compressed",
+            Self::Uncompressed => "uncompressed",
+        })
+    }
+}
+
+/// Whether signatures with a high `s` are accepted (see [`ECDSA::enforce_low_s`]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SPolicy {
+    /// High `s` values are accepted (the default).
+    AllowHigh,
+    /// High `s` values are rejected as [BIP146] requires for relay.
+    ///
+    /// [BIP146]: https://github.com/bitcoin/bips/blob/master/bip-0146.mediawiki#low_s
+    RequireLow,
+}
+
+impl SPolicy {
+    /// Every `s` policy.
+    pub const ALL: [Self; 2] = [Self::AllowHigh, Self::RequireLow];
+
+    fn verifier(self) -> ECDSA<()> {
+        match self {
+            Self::AllowHigh => ECDSA::verify_only(),
+            Self::RequireLow => ECDSA::verify_only().enforce_low_s(),
+        }
+    }
+}
+
+impl fmt::Display for SPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::AllowHigh => "allow-high-s",
+            Self::RequireLow => "require-low-s",
+        })
+    }
+}
+
+/// The decisions [`verify_compat`] made for every combination of parsing modes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CompatMatrix {
+    signature_parses: u8,
+    public_key_parses: u8,
+    accepts: u16,
+}
+
+impl CompatMatrix {
+    fn index(
+        signature: SignatureEncoding,
+        public_key: PublicKeyEncoding,
+        policy: SPolicy,
+    ) -> usize {
+        ((signature as usize * PublicKeyEncoding::ALL.len()) + public_key as usize)
+            * SPolicy::ALL.len()
+            + policy as usize
+    }
+
+    /// Whether the signature bytes parse with `encoding`.
+    pub fn signature_parses(&self, encoding: SignatureEncoding) -> bool {
+        self.signature_parses >> encoding as usize & 1 == 1
+    }
+
+    /// Whether the public key bytes parse with `encoding`.
+    pub fn public_key_parses(&self, encoding: PublicKeyEncoding) -> bool {
+        self.public_key_parses >> encoding as usize & 1 == 1
+    }
+
+    /// Whether the signature is accepted when parsed and verified this way.
+    pub fn accepts(
+        &self,
+        signature: SignatureEncoding,
+        public_key: PublicKeyEncoding,
+        policy: SPolicy,
+    ) -> bool {
+        self.accepts >> Self::index(signature, public_key, policy) & 1 == 1
+    }
+
+    /// Whether any combination accepts the signature.
+    pub fn any_accepts(&self) -> bool {
+        self.accepts != 0
+    }
+
+    /// Every combination of parsing modes and whether it accepts the signature.
+    pub fn decisions(
+        &self,
+    ) -> impl Iterator<Item = ((SignatureEncoding, PublicKeyEncoding, SPolicy), bool)> + '_ {
+        SignatureEncoding::ALL
+            .into_iter()
+            .flat_map(move |signature| {
+                PublicKeyEncoding::ALL
+                    .into_iter()
+                    .flat_map(move |public_key| {
+                        SPolicy::ALL.into_iter().map(move |policy| {
+                            (
+                                (signature, public_key, policy),
+                                self.accepts(signature, public_key, policy),
+                            )
+                        })
+                    })
+            })
+    }
+
+    /// Packs every decision into an integer so two harness runs can be compared cheaply.
+    ///
+    /// Bits 0–11 are the acceptance decisions in the order of [`decisions`], bits 16–18 whether
+    /// the signature parses with each [`SignatureEncoding`] and bits 24–25 whether the public key
+    /// parses with each [`PublicKeyEncoding`].
+    ///
+    /// [`decisions`]: Self::decisions
+    pub fn to_bits(&self) -> u32 {
+        self.accepts as u32
+            | (self.signature_parses as u32) << 16
+            | (self.public_key_parses as u32) << 24
+    }
+}
+
+impl fmt::Display for CompatMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for ((signature, public_key, policy), accepts) in self.decisions() {
+            writeln!(
+                f,
+                "{}/{}/{}: {}",
+                signature,
+                public_key,
+                policy,
+                if accepts { "accept" } else { "reject" }
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses and verifies a signature on message hash `message` in every way this crate supports.
+///
+/// See the [module documentation](crate::compat).
+pub fn verify_compat(signature: &[u8], public_key: &[u8], message: &[u8; 32]) -> CompatMatrix {
+    let mut matrix = CompatMatrix {
+        signature_parses: 0,
+        public_key_parses: 0,
+        accepts: 0,
+    };
+    for public_key_encoding in PublicKeyEncoding::ALL {
+        if public_key_encoding.parse(public_key).is_some() {
+            matrix.public_key_parses |= 1 << public_key_encoding as usize;
+        }
+    }
+    for signature_encoding in SignatureEncoding::ALL {
+        let parsed_signature = match signature_encoding.parse(signature) {
+            Some(parsed_signature) => parsed_signature,
+            None => continue,
+        };
+        matrix.signature_parses |= 1 << signature_encoding as usize;
+        for public_key_encoding in PublicKeyEncoding::ALL {
+            let parsed_key = match public_key_encoding.parse(public_key) {
+                Some(parsed_key) => parsed_key,
+                None => continue,
+            };
+            for policy in SPolicy::ALL {
+                if policy
+                    .verifier()
+                    .verify(&parsed_key, message, &parsed_signature)
+                {
+                    matrix.accepts |=
+                        1 << CompatMatrix::index(signature_encoding, public_key_encoding, policy);
+                }
+            }
+        }
+    }
+    matrix
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use super::*;
+    use crate::fun::{s, Scalar};
+    use alloc::string::ToString;
+
+    #[test]
+    fn compat_matrix() {
+        let ecdsa = crate::test_instance!();
+        let secret_key = Scalar::random(&mut rand::thread_rng());
+        let public_key = ecdsa.verification_key_for(&secret_key);
+        let message = [7u8; 32];
+        let signature = ecdsa.sign(&secret_key, &message);
+
+        let matrix = verify_compat(
+            &signature.to_bytes(),
+            &public_key.to_bytes_uncompressed(),
+            &message,
+        );
+        assert_eq!(
+            matrix
+                .decisions()
+                .filter(|(_, accepts)| *accepts)
+                .map(|(decision, _)| decision)
+                .collect::<alloc::vec::Vec<_>>(),
+            alloc::vec![
+                (
+                    SignatureEncoding::Compact,
+                    PublicKeyEncoding::Uncompressed,
+                    SPolicy::AllowHigh
+                ),
+                (
+                    SignatureEncoding::Compact,
+                    PublicKeyEncoding::Uncompressed,
+                    SPolicy::RequireLow
+                ),
+            ]
+        );
+        assert!(!matrix.public_key_parses(PublicKeyEncoding::Compressed));
+        assert!(matrix
+            .to_string()
+            .starts_with("compact/compressed/allow-high-s: reject\n"));
+
+        let high_s = Signature {
+            R_x: signature.R_x,
+            s: s!(-signature.s).public(),
+        };
+        let matrix = verify_compat(
+            &high_s.to_bitcoin_script_bytes(0x01),
+            &public_key.to_bytes(),
+            &message,
+        );
+        assert!(matrix.signature_parses(SignatureEncoding::BitcoinScript));
+        assert!(!matrix.signature_parses(SignatureEncoding::Der));
+        assert!(!matrix.accepts(
+            SignatureEncoding::BitcoinScript,
+            PublicKeyEncoding::Compressed,
+            SPolicy::RequireLow
+        ));
+        assert_eq!(
+            matrix.accepts(
+                SignatureEncoding::BitcoinScript,
+                PublicKeyEncoding::Compressed,
+                SPolicy::AllowHigh
+            ),
+            !cfg!(feature = "secure-defaults")
+        );
+
+        assert_eq!(verify_compat(&[], &[], &message).to_bits(), 0);
+    }
+}
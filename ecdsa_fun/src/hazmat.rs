@@ -0,0 +1,45 @@
+//! ☢ Hazardous materials ☢
+//!
+//! Low-level signing functions that let the caller choose the nonce.
+//!
+//! **⚠ DO NOT USE THESE UNLESS YOU KNOW EXACTLY WHAT YOU ARE DOING ⚠**. If the same nonce is ever
+//! used to sign two different message hashes, or if the nonce is even slightly biased or
+//! predictable, the secret key can be extracted from the resulting signatures. The ordinary
+//! [`ECDSA::sign`] derives nonces with a [`NonceGen`] precisely so you don't have to think about
+//! this.
+//!
+//! These exist for protocols where the nonce is produced externally (e.g. by an MPC protocol or a
+//! deterministic derivation that can't be expressed as a [`NonceGen`]).
+//!
+//! [`ECDSA::sign`]: crate::ECDSA::sign
+//! [`NonceGen`]: crate::nonce::NonceGen
+use crate::{fun::Scalar, Signature};
+
+/// Create an ECDSA signature on `message_hash` using `nonce` as the secret nonce.
+///
+/// The resulting signature always has a low `s` (see [BIP-146]).
+///
+/// **⚠ The `nonce` must be uniformly random, secret and never used again. See the [module
+/// documentation](crate::hazmat).**
+///
+/// # Example
+///
+/// ```
+/// use ecdsa_fun::{fun::Scalar, hazmat, ECDSA};
+/// let ecdsa = ECDSA::verify_only();
+/// let secret_key = Scalar::random(&mut rand::thread_rng());
+/// let message_hash = [42u8; 32];
+/// // ⚠ this nonce came from an external protocol that guarantees it is fresh
+/// let nonce = Scalar::random(&mut rand::thread_rng());
+/// let signature = hazmat::sign_with_nonce(&secret_key, &nonce, &message_hash);
+/// assert!(ecdsa.verify(
+///     &ecdsa.verification_key_for(&secret_key),
+///     &message_hash,
+///     &signature
+/// ));
+/// ```
+///
+/// [BIP-146]: https://github.com/bitcoin/bips/blob/master/bip-0146.mediawiki#low_s
+pub fn sign_with_nonce(secret_key: &Scalar, nonce: &Scalar, message_hash: &[u8; 32]) -> Signature {
+    crate::sign_with_nonce(secret_key, nonce, message_hash)
+}
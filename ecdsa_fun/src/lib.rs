@@ -14,14 +14,40 @@ mod libsecp_compat;
 
 use fun::Tag;
 
-use fun::{derive_nonce, g, marker::*, nonce::NonceGen, s, Point, Scalar, G};
+use fun::{
+    derive_nonce, g, marker::*, nonce::NonceGen, s, signature::SignatureScheme, KeyPair, Point,
+    Scalar, G,
+};
 pub use secp256kfun as fun;
 pub use secp256kfun::nonce;
+pub mod prelude;
 mod signature;
 pub use signature::Signature;
+pub mod anti_exfil;
+pub mod checksig;
+pub mod compat;
+mod nonce_commitment;
+pub mod public_inputs;
+pub mod recoverable;
+pub mod rfc6979;
+pub mod sighash;
+pub use nonce_commitment::{CommittedNonce, RCommitment};
+use recoverable::RecoverableSignature;
 #[cfg(feature = "adaptor")]
 #[cfg_attr(docsrs, doc(cfg(feature = "adaptor")))]
 pub mod adaptor;
+#[cfg(feature = "hazmat")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hazmat")))]
+pub mod hazmat;
+#[cfg(feature = "jose")]
+#[cfg_attr(docsrs, doc(cfg(feature = "jose")))]
+pub mod jose;
+#[cfg(feature = "reserves")]
+#[cfg_attr(docsrs, doc(cfg(feature = "reserves")))]
+pub mod reserves;
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub mod self_test;
 
 /// An instance of the ECDSA signature scheme.
 #[derive(Default, Clone, Debug)]
@@ -32,8 +58,63 @@ pub struct ECDSA<NG> {
     pub nonce_gen: NG,
     /// `enforce_low_s`: Whether the verify algorithm should enforce that the `s` component of the signature is low (see [BIP-146]).
     ///
+    /// With the `secure-defaults` feature this field doesn't exist and low `s` is always enforced.
+    ///
     /// [BIP-146]: https://github.com/bitcoin/bips/blob/master/bip-0146.mediawiki#low_s
+    #[cfg(not(feature = "secure-defaults"))]
     pub enforce_low_s: bool,
+    /// Which version of the nonce derivation is used when signing. `nonce_gen` was tagged with it
+    /// so it can only be set at construction.
+    nonce_derivation: NonceDerivation,
+}
+
+/// The version of the protocol used to derive nonces when signing.
+///
+/// Each version domain separates its nonces with a different tag on the [`NonceGen`] so changes to
+/// how nonces are derived never silently change the deterministic signatures of a previous
+/// version. New versions may be added in the future but existing versions will continue to produce
+/// the same signatures.
+///
+/// You should only select an old version if you need to reproduce signatures made by an older
+/// version of this library (e.g. in replay or compatibility tests).
+///
+/// [`NonceGen`]: crate::nonce::NonceGen
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum NonceDerivation {
+    /// The nonce derivation used in `v0.10` and earlier.
+    ///
+    /// The nonce generator is tagged with `secp256kfun/ecdsa_fun` and only the message hash is
+    /// bound into the nonce. Not available with the `secure-defaults` feature.
+    #[cfg(not(feature = "secure-defaults"))]
+    V1,
+    /// The nonce generator is tagged with `secp256kfun/ecdsa_fun/v2` and both the verification key
+    /// and the message hash are bound into the nonce.
+    ///
+    /// Binding the verification key protects against related-key attacks and bugs where the
+    /// wrong secret key is paired with a nonce.
+    #[default]
+    V2,
+}
+
+impl NonceDerivation {
+    /// The tag applied to the [`NonceGen`] for this version.
+    ///
+    /// [`NonceGen`]: crate::nonce::NonceGen
+    pub fn tag(&self) -> &'static [u8] {
+        match self {
+            #[cfg(not(feature = "secure-defaults"))]
+            NonceDerivation::V1 => b"secp256kfun/ecdsa_fun",
+            NonceDerivation::V2 => b"secp256kfun/ecdsa_fun/v2",
+        }
+    }
+
+    pub(crate) fn is_v1(&self) -> bool {
+        #[cfg(not(feature = "secure-defaults"))]
+        let is_v1 = *self == NonceDerivation::V1;
+        #[cfg(feature = "secure-defaults")]
+        let is_v1 = false;
+        is_v1
+    }
 }
 
 impl ECDSA<()> {
@@ -42,7 +123,9 @@ impl ECDSA<()> {
     pub fn verify_only() -> Self {
         ECDSA {
             nonce_gen: (),
+            #[cfg(not(feature = "secure-defaults"))]
             enforce_low_s: false,
+            nonce_derivation: NonceDerivation::default(),
         }
     }
 }
@@ -63,12 +146,37 @@ impl<NG> ECDSA<NG> {
     ///
     /// [`NonceGen`]: crate::nonce::NonceGen
     pub fn new(nonce_gen: NG) -> Self
+    where
+        NG: Tag,
+    {
+        Self::new_with_nonce_derivation(nonce_gen, NonceDerivation::default())
+    }
+
+    /// Creates a ECDSA instance that derives nonces with a particular version of the nonce
+    /// derivation.
+    ///
+    /// Unless you need to reproduce signatures from an older version you should use [`new`].
+    ///
+    /// # Example
+    /// ```
+    /// use ecdsa_fun::{nonce, NonceDerivation, ECDSA};
+    /// use sha2::Sha256;
+    /// let nonce_gen = nonce::Deterministic::<Sha256>::default();
+    /// // produce the same deterministic signatures as ecdsa_fun v0.10
+    /// # #[cfg(not(feature = "secure-defaults"))]
+    /// let ecdsa = ECDSA::new_with_nonce_derivation(nonce_gen, NonceDerivation::V1);
+    /// ```
+    ///
+    /// [`new`]: Self::new
+    pub fn new_with_nonce_derivation(nonce_gen: NG, nonce_derivation: NonceDerivation) -> Self
     where
         NG: Tag,
     {
         ECDSA {
-            nonce_gen: nonce_gen.tag(b"secp256kfun/ecdsa_fun"),
+            nonce_gen: nonce_gen.tag(nonce_derivation.tag()),
+            #[cfg(not(feature = "secure-defaults"))]
             enforce_low_s: false,
+            nonce_derivation,
         }
     }
 
@@ -80,12 +188,36 @@ impl<NG> ECDSA<NG> {
     /// valid high s signatures. This is especially true if you are using the
     /// ECDSA adaptor signature scheme.
     ///
+    /// With the `secure-defaults` feature low `s` is always enforced and this does nothing.
+    ///
     /// [BIP-146]: https://github.com/bitcoin/bips/blob/master/bip-0146.mediawiki#low_s
     pub fn enforce_low_s(self) -> Self {
-        ECDSA {
-            nonce_gen: self.nonce_gen,
+        #[cfg(not(feature = "secure-defaults"))]
+        let self_ = ECDSA {
             enforce_low_s: true,
-        }
+            ..self
+        };
+        #[cfg(feature = "secure-defaults")]
+        let self_ = self;
+        self_
+    }
+
+    /// Which version of the nonce derivation is used when signing.
+    ///
+    /// See [`NonceDerivation`].
+    pub fn nonce_derivation(&self) -> NonceDerivation {
+        self.nonce_derivation
+    }
+
+    /// Whether verification rejects signatures with a high `s`.
+    ///
+    /// Always `true` with the `secure-defaults` feature.
+    pub fn enforces_low_s(&self) -> bool {
+        #[cfg(not(feature = "secure-defaults"))]
+        let enforces_low_s = self.enforce_low_s;
+        #[cfg(feature = "secure-defaults")]
+        let enforces_low_s = true;
+        enforces_low_s
     }
 }
 
@@ -110,10 +242,42 @@ impl<NG> ECDSA<NG> {
         message: &[u8; 32],
         signature: &Signature,
     ) -> bool {
+        self.verify_with_nonce(verification_key, message, signature)
+            .is_some()
+    }
+
+    /// Verify an ECDSA signature returning the nonce point `R` it implies if it is valid.
+    ///
+    /// The signature only contains the x-coordinate of `R` so this is useful for auditors and
+    /// watchtowers that want to log the nonces they see and cross-reference them to detect nonce
+    /// reuse. The y-coordinate of the returned point is the one that the verification equation
+    /// implies. It is the signer's `r * G` or its negation if the signer negated `s` to make it
+    /// low.
+    ///
+    /// # Example
+    /// ```
+    /// use ecdsa_fun::{fun::Scalar, nonce, ECDSA};
+    /// let ecdsa = ECDSA::new(nonce::Deterministic::<sha2::Sha256>::default());
+    /// let secret_key = Scalar::random(&mut rand::thread_rng());
+    /// let verification_key = ecdsa.verification_key_for(&secret_key);
+    /// let signature = ecdsa.sign(&secret_key, b"the message hash is 32 bytes....");
+    /// let R = ecdsa
+    ///     .verify_with_nonce(&verification_key, b"the message hash is 32 bytes....", &signature)
+    ///     .expect("signature is valid");
+    /// assert!(R.x_eq_scalar(&signature.R_x));
+    /// assert_eq!(ecdsa.verify_with_nonce(&verification_key, &[0u8; 32], &signature), None);
+    /// ```
+    #[must_use]
+    pub fn verify_with_nonce(
+        &self,
+        verification_key: &Point<impl PointType, Public, NonZero>,
+        message: &[u8; 32],
+        signature: &Signature,
+    ) -> Option<Point> {
         let (R_x, s) = signature.as_tuple();
         // This ensures that there is only one valid s value per R_x for any given message.
-        if s.is_high() && self.enforce_low_s {
-            return false;
+        if s.is_high() && self.enforces_low_s() {
+            return None;
         }
 
         let m = Scalar::<Public, _>::from_bytes_mod_order(*message).public();
@@ -121,10 +285,82 @@ impl<NG> ECDSA<NG> {
 
         g!((s_inv * m) * G + (s_inv * R_x) * verification_key)
             .non_zero()
-            .map_or(false, |implied_R| implied_R.x_eq_scalar(R_x))
+            .filter(|implied_R| implied_R.x_eq_scalar(R_x))
+            .map(|implied_R| implied_R.normalize())
+    }
+
+    /// Verifies a signature like [`verify`] but on failure diagnoses why.
+    ///
+    /// This is for tracking down incompatibilities with other implementations and is only compiled
+    /// in builds with `debug_assertions` (e.g. tests).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ecdsa_fun::{fun::Scalar, VerifyDiagnosis};
+    /// let ecdsa = ecdsa_fun::test_instance!();
+    /// let secret_key = Scalar::random(&mut rand::thread_rng());
+    /// let verification_key = ecdsa.verification_key_for(&secret_key);
+    /// let mut message_hash = [0u8; 32];
+    /// message_hash[0] = 1;
+    /// let signature = ecdsa.sign(&secret_key, &message_hash);
+    /// assert_eq!(ecdsa.verify_explain(&verification_key, &message_hash, &signature), Ok(()));
+    /// message_hash.reverse();
+    /// assert_eq!(
+    ///     ecdsa.verify_explain(&verification_key, &message_hash, &signature),
+    ///     Err(VerifyDiagnosis::ReversedMessage)
+    /// );
+    /// ```
+    ///
+    /// [`verify`]: Self::verify
+    #[cfg(debug_assertions)]
+    pub fn verify_explain(
+        &self,
+        verification_key: &Point<impl PointType, Public, NonZero>,
+        message: &[u8; 32],
+        signature: &Signature,
+    ) -> Result<(), VerifyDiagnosis> {
+        let (R_x, s) = signature.as_tuple();
+        if s.is_high() && self.enforces_low_s() {
+            return Err(VerifyDiagnosis::HighS);
+        }
+        let m = Scalar::<Public, _>::from_bytes_mod_order(*message).public();
+        let s_inv = s.invert();
+        let implied_R = g!((s_inv * m) * G + (s_inv * R_x) * verification_key).normalize();
+        if let Some(implied_R) = implied_R.non_zero() {
+            if implied_R.x_eq_scalar(R_x) {
+                return Ok(());
+            }
+        }
+        let mut reversed = *message;
+        reversed.reverse();
+        if self.verify(verification_key, &reversed, signature) {
+            return Err(VerifyDiagnosis::ReversedMessage);
+        }
+        Err(VerifyDiagnosis::RMismatch {
+            implied_R: implied_R.to_bytes(),
+        })
     }
 }
 
+/// Why a signature failed to verify. Returned by [`ECDSA::verify_explain`].
+#[cfg(debug_assertions)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VerifyDiagnosis {
+    /// `s` is high and the instance [enforces low `s`](ECDSA::enforce_low_s). The signer didn't
+    /// negate `s` after signing.
+    HighS,
+    /// The signature is valid for the message hash with its bytes reversed. One side is reading the
+    /// hash as a little endian number.
+    ReversedMessage,
+    /// The x-coordinate of the nonce implied by the message hash and verification key isn't `R_x`.
+    /// The signer most likely signed a different message hash or with a different key.
+    RMismatch {
+        /// The encoding of the nonce implied by the signature i.e. `s⁻¹(m * G + R_x * X)`.
+        implied_R: [u8; 33],
+    },
+}
+
 impl<NG: NonceGen> ECDSA<NG> {
     /// Deterministically produce a ECDSA signature on a message hash.
     ///
@@ -157,38 +393,180 @@ impl<NG: NonceGen> ECDSA<NG> {
     /// assert!(ecdsa.verify(&verification_key, &message_hash, &signature));
     /// ```
     pub fn sign(&self, secret_key: &Scalar, message_hash: &[u8; 32]) -> Signature {
-        let x = secret_key;
-        let m = Scalar::<Public, _>::from_bytes_mod_order(*message_hash).public();
-        let r = derive_nonce!(
-            nonce_gen => self.nonce_gen,
-            secret => x,
-            public => [&message_hash[..]]
-        );
-        let R = g!(r * G).normalize(); // Must be normal so we can get x-coordinate
-
-        // This coverts R is its x-coordinate mod q. This acts as a kind of poor
-        // man's version of the Fiat-Shamir challenge in a Schnorr
-        // signature. The lack of any known algebraic relationship between r and
-        // R_x is what makes ECDSA signatures difficult to forge.
-        let R_x = Scalar::<Public, _>::from_bytes_mod_order(R.to_xonly_bytes())
-            // There *is* a single point that will be zero here but since we're
-            // choosing R pseudorandomly it won't occur.
-            .public()
-            .non_zero()
-            .expect("computationally unreachable");
+        let r = self.derive_nonce(secret_key, message_hash);
+        sign_with_nonce(secret_key, &r, message_hash)
+    }
 
-        let mut s = s!((m + R_x * x) / r)
-            // Given R_x is determined by x and m through a hash, reaching
-            // (m + R_x * x) = 0 is intractable.
-            .non_zero()
-            .expect("computationally unreachable")
-            .public();
+    fn derive_nonce(&self, secret_key: &Scalar, message_hash: &[u8; 32]) -> Scalar {
+        if self.nonce_derivation.is_v1() {
+            derive_nonce!(
+                nonce_gen => self.nonce_gen,
+                secret => secret_key,
+                public => [&message_hash[..]]
+            )
+        } else {
+            let X = self.verification_key_for(secret_key);
+            derive_nonce!(
+                nonce_gen => self.nonce_gen,
+                secret => secret_key,
+                public => [X, &message_hash[..]]
+            )
+        }
+    }
+
+    /// Signs each of a set of related message hashes returning the signatures in the same order.
+    ///
+    /// This is for signing several variants of the same thing at once e.g. the sighashes of
+    /// versions of a transaction that pay different fees. Each nonce is derived from the key and
+    /// its own message hash exactly as in [`sign`](Self::sign) so different message hashes always
+    /// get different nonces.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ecdsa_fun::{fun::Scalar, nonce, ECDSA};
+    /// let ecdsa = ECDSA::new(nonce::Deterministic::<sha2::Sha256>::default());
+    /// let secret_key = Scalar::random(&mut rand::thread_rng());
+    /// let verification_key = ecdsa.verification_key_for(&secret_key);
+    /// let sighashes = [[1u8; 32], [2u8; 32], [3u8; 32]];
+    /// let signatures = ecdsa.sign_batch(&secret_key, &sighashes);
+    /// for (sighash, signature) in sighashes.iter().zip(&signatures) {
+    ///     assert!(ecdsa.verify(&verification_key, sighash, signature));
+    /// }
+    /// ```
+    pub fn sign_batch<const N: usize>(
+        &self,
+        secret_key: &Scalar,
+        message_hashes: &[[u8; 32]; N],
+    ) -> [Signature; N] {
+        message_hashes.map(|message_hash| self.sign(secret_key, &message_hash))
+    }
 
-        // s values must be low (less than half group order), otherwise signatures
-        // would be malleable i.e. (R,s) and (R,-s) would both be valid signatures.
-        s.conditional_negate(s.is_high());
+    /// Signs a message hash grinding the nonce until the signature [has a low
+    /// `R_x`](Signature::has_low_r) like Bitcoin Core does.
+    ///
+    /// A low `R_x` DER encodes in 32 bytes rather than 33 so every signature has the same (minimal)
+    /// size. The first nonce tried is the one [`sign`](Self::sign) uses. Each retry adds Core's
+    /// extra entropy (a 32-bit little-endian counter padded to 32 bytes) to the nonce derivation.
+    /// It takes two tries on average.
+    ///
+    /// To get the same signatures as Core use [`rfc6979::sign_low_r`] which uses Core's nonces.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ecdsa_fun::{fun::Scalar, nonce, ECDSA};
+    /// let ecdsa = ECDSA::new(nonce::Deterministic::<sha2::Sha256>::default());
+    /// let secret_key = Scalar::random(&mut rand::thread_rng());
+    /// let signature = ecdsa.sign_low_r(&secret_key, &[42u8; 32]);
+    /// assert!(signature.has_low_r());
+    /// assert!(signature.to_der().len() <= 71);
+    /// ```
+    pub fn sign_low_r(&self, secret_key: &Scalar, message_hash: &[u8; 32]) -> Signature {
+        let mut signature = self.sign(secret_key, message_hash);
+        let mut counter = 0u32;
+        while !signature.has_low_r() {
+            counter += 1;
+            let mut extra_entropy = [0u8; 32];
+            extra_entropy[..4].copy_from_slice(&counter.to_le_bytes());
+            // the public inputs are longer than any of `sign`'s so the nonces are unrelated
+            let r = if self.nonce_derivation.is_v1() {
+                derive_nonce!(
+                    nonce_gen => self.nonce_gen,
+                    secret => secret_key,
+                    public => [&message_hash[..], extra_entropy]
+                )
+            } else {
+                let X = self.verification_key_for(secret_key);
+                derive_nonce!(
+                    nonce_gen => self.nonce_gen,
+                    secret => secret_key,
+                    public => [X, &message_hash[..], extra_entropy]
+                )
+            };
+            signature = sign_with_nonce(secret_key, &r, message_hash);
+        }
+        signature
+    }
+}
+
+/// Produces an ECDSA signature using `r` as the secret nonce.
+///
+/// This is not exposed publicly except through the `hazmat` module.
+pub(crate) fn sign_with_nonce(x: &Scalar, r: &Scalar, message_hash: &[u8; 32]) -> Signature {
+    sign_recoverable_with_nonce(x, r, message_hash).signature
+}
+
+pub(crate) fn sign_recoverable_with_nonce(
+    x: &Scalar,
+    r: &Scalar,
+    message_hash: &[u8; 32],
+) -> RecoverableSignature {
+    let m = Scalar::<Public, _>::from_bytes_mod_order(*message_hash).public();
+    let R = g!(r * G).normalize(); // Must be normal so we can get x-coordinate
 
-        Signature { R_x, s }
+    // This coverts R is its x-coordinate mod q. This acts as a kind of poor
+    // man's version of the Fiat-Shamir challenge in a Schnorr
+    // signature. The lack of any known algebraic relationship between r and
+    // R_x is what makes ECDSA signatures difficult to forge.
+    let R_x = Scalar::<Public, _>::from_bytes_mod_order(R.to_xonly_bytes())
+        // There *is* a single point that will be zero here but since we're
+        // choosing R pseudorandomly it won't occur.
+        .public()
+        .non_zero()
+        .expect("computationally unreachable");
+
+    let mut s = s!((m + R_x * x) / r)
+        // Given R_x is determined by x and m through a hash, reaching
+        // (m + R_x * x) = 0 is intractable.
+        .non_zero()
+        .expect("computationally unreachable")
+        .public();
+
+    // s values must be low (less than half group order), otherwise signatures
+    // would be malleable i.e. (R,s) and (R,-s) would both be valid signatures.
+    let negated = s.is_high();
+    s.conditional_negate(negated);
+
+    RecoverableSignature {
+        signature: Signature { R_x, s },
+        recovery_id: recoverable::recovery_id(&R, negated),
+    }
+}
+
+impl<NG: NonceGen> SignatureScheme for ECDSA<NG> {
+    type KeyPair = KeyPair;
+    type PublicKey = Point;
+    type PublicKeyBytes = [u8; 33];
+    type Signature = Signature;
+
+    fn new_keypair(&self, secret_key: Scalar) -> Self::KeyPair {
+        KeyPair::<Normal>::new(secret_key)
+    }
+
+    fn public_key(&self, keypair: &Self::KeyPair) -> Self::PublicKey {
+        keypair.public_key()
+    }
+
+    fn sign(&self, keypair: &Self::KeyPair, message: &[u8; 32]) -> Self::Signature {
+        ECDSA::sign(self, keypair.secret_key(), message)
+    }
+
+    fn verify(
+        &self,
+        public_key: &Self::PublicKey,
+        message: &[u8; 32],
+        signature: &Self::Signature,
+    ) -> bool {
+        ECDSA::verify(self, public_key, message, signature)
+    }
+
+    fn public_key_to_bytes(public_key: &Self::PublicKey) -> Self::PublicKeyBytes {
+        public_key.to_bytes()
+    }
+
+    fn public_key_from_bytes(bytes: &[u8]) -> Option<Self::PublicKey> {
+        Point::from_slice(bytes)
     }
 }
 
@@ -219,6 +597,31 @@ mod test {
     }
 
     #[test]
+    fn generic_signature_scheme() {
+        type Scheme = ECDSA<nonce::Deterministic<sha2::Sha256>>;
+        let ecdsa: Scheme = test_instance!();
+        let keypair = SignatureScheme::new_keypair(&ecdsa, Scalar::random(&mut rand::thread_rng()));
+        let public_key = SignatureScheme::public_key(&ecdsa, &keypair);
+        let signature = SignatureScheme::sign(&ecdsa, &keypair, &[42u8; 32]);
+        assert!(ecdsa.verify(&public_key, &[42u8; 32], &signature));
+        assert!(!SignatureScheme::verify(
+            &ecdsa,
+            &public_key,
+            &[43u8; 32],
+            &signature
+        ));
+        let bytes = Scheme::public_key_to_bytes(&public_key);
+        assert_eq!(Scheme::public_key_from_bytes(&bytes), Some(public_key));
+        let signature_bytes = Scheme::signature_to_bytes(&signature);
+        assert_eq!(signature_bytes, signature.to_bytes());
+        assert_eq!(
+            Scheme::signature_from_bytes(signature_bytes),
+            Some(signature)
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "secure-defaults"))]
     fn low_s() {
         let ecdsa_enforce_low_s = test_instance!().enforce_low_s();
         let ecdsa = test_instance!();
@@ -236,4 +639,62 @@ mod test {
             assert!(ecdsa.verify(&public_key, &message, &sig));
         }
     }
+
+    #[test]
+    #[cfg(feature = "secure-defaults")]
+    fn secure_defaults_reject_high_s() {
+        let ecdsa = test_instance!();
+        assert!(ecdsa.enforces_low_s());
+        let secret_key = Scalar::random(&mut rand::thread_rng());
+        let public_key = ecdsa.verification_key_for(&secret_key);
+        let mut sig = ecdsa.sign(&secret_key, &[42u8; 32]);
+        sig.s = -sig.s;
+        assert!(!ecdsa.verify(&public_key, &[42u8; 32], &sig));
+        assert!(!ECDSA::verify_only().verify(&public_key, &[42u8; 32], &sig));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn verify_explain_diagnoses_common_mistakes() {
+        let ecdsa = test_instance!().enforce_low_s();
+        let message = [42u8; 32];
+        let secret_key = Scalar::random(&mut rand::thread_rng());
+        let public_key = ecdsa.verification_key_for(&secret_key);
+        let mut sig = ecdsa.sign(&secret_key, &message);
+        assert_eq!(ecdsa.verify_explain(&public_key, &message, &sig), Ok(()));
+
+        let other_key = ecdsa.verification_key_for(&Scalar::random(&mut rand::thread_rng()));
+        assert!(matches!(
+            ecdsa.verify_explain(&other_key, &message, &sig),
+            Err(VerifyDiagnosis::RMismatch { .. })
+        ));
+
+        sig.s = -sig.s;
+        assert_eq!(
+            ecdsa.verify_explain(&public_key, &message, &sig),
+            Err(VerifyDiagnosis::HighS)
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "secure-defaults"))]
+    fn nonce_derivation_versions() {
+        use core::str::FromStr;
+        let ecdsa = test_instance!();
+        let v1_ecdsa = ECDSA::new_with_nonce_derivation(
+            nonce::Deterministic::<sha2::Sha256>::default(),
+            NonceDerivation::V1,
+        );
+        let secret_key =
+            Scalar::from_str("18451f9e08af9530814243e202a4a977130e672079f5c14dcf15bd4dee723072")
+                .unwrap();
+        let message = [42u8; 32];
+        let v1_sig = v1_ecdsa.sign(&secret_key, &message);
+        let sig = ecdsa.sign(&secret_key, &message);
+        // to check we don't accidentally change the v1 deterministic signatures
+        assert_eq!(v1_sig, Signature::from_str("c024ffa9024aa76b07cd9741c3144087deaa52379ee423d87ed61e05d4a71052447daac01d4c65eca429917027a75463590b55c9209ba911d82a4169bab61a9e").unwrap());
+        assert_ne!(sig.R_x, v1_sig.R_x);
+        let public_key = ecdsa.verification_key_for(&secret_key);
+        assert!(ecdsa.verify(&public_key, &message, &sig));
+    }
 }
@@ -0,0 +1,186 @@
+use crate::{fun::Scalar, Signature, ECDSA};
+use secp256kfun::{derive_nonce, g, marker::*, nonce::NonceGen, Point, G};
+
+/// Separates committed nonces from the nonces [`ECDSA::sign`] derives.
+///
+/// With it the public input is always longer than `sign`'s (which is `X || message_hash`) and
+/// can't begin like that of other derivations with a point after `X`.
+const COMMIT_NONCE_DOMAIN: [u8; 32] =
+    *b"\x00ecdsa_fun/commit_nonce\x00\x00\x00\x00\x00\x00\x00\x00\x00";
+
+/// The public nonce `R` of a signature that has been committed to before the message is known.
+///
+/// Created with [`CommittedNonce::commitment`]. Send it to whoever needs the signature to use
+/// `R` and check the signature with [`matches`](Self::matches) once it is complete.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct RCommitment {
+    R: Point,
+}
+
+impl RCommitment {
+    /// The committed public nonce.
+    pub fn R(&self) -> Point {
+        self.R
+    }
+
+    /// The `R_x` that a signature with this nonce will have.
+    pub fn R_x(&self) -> Scalar<Public> {
+        Scalar::<Public, _>::from_bytes_mod_order(self.R.to_xonly_bytes())
+            .public()
+            .non_zero()
+            .expect("computationally unreachable")
+    }
+
+    /// Whether `signature` was made with the committed nonce.
+    ///
+    /// This only checks the nonce. You still need to [`verify`](ECDSA::verify) the signature.
+    #[must_use]
+    pub fn matches(&self, signature: &Signature) -> bool {
+        signature.R_x == self.R_x()
+    }
+}
+
+/// A secret nonce that has been committed to but not yet used to sign.
+///
+/// Created with [`ECDSA::commit_nonce`] and consumed by [`ECDSA::complete_sign`]. It isn't
+/// `Clone` but that doesn't stop the nonce being used twice: with a deterministic [`NonceGen`]
+/// calling [`ECDSA::commit_nonce`] again with the same session id gives the same nonce. Never
+/// reuse a session id.
+///
+/// Its `Debug` output only shows the commitment.
+pub struct CommittedNonce {
+    r: Scalar,
+    commitment: RCommitment,
+}
+
+impl core::fmt::Debug for CommittedNonce {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("CommittedNonce")
+            .field("commitment", &self.commitment)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CommittedNonce {
+    /// The commitment to the public nonce to send to the other parties.
+    pub fn commitment(&self) -> RCommitment {
+        self.commitment
+    }
+}
+
+impl<NG: NonceGen> ECDSA<NG> {
+    /// Commits to the nonce of a signature by `secret_key` before the message is known.
+    ///
+    /// Once the message hash is known finish the signature with [`complete_sign`]. This is for
+    /// protocols where the message has to be bound to a previously committed `R`.
+    ///
+    /// `session_id` must be unique for each signature. With a deterministic [`NonceGen`] the same
+    /// `secret_key` and `session_id` always give the same nonce and completing signatures on two
+    /// different message hashes with the same nonce reveals the secret key.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ecdsa_fun::{fun::Scalar, nonce, ECDSA};
+    /// use rand::rngs::ThreadRng;
+    /// use sha2::Sha256;
+    /// let ecdsa = ECDSA::new(nonce::Synthetic::<Sha256, nonce::GlobalRng<ThreadRng>>::default());
+    /// let secret_key = Scalar::random(&mut rand::thread_rng());
+    /// let committed_nonce = ecdsa.commit_nonce(&secret_key, b"escrow-42");
+    /// let commitment = committed_nonce.commitment();
+    /// // ... send commitment to the counterparty, later learn the message
+    /// let message_hash = [42u8; 32];
+    /// let signature = ecdsa.complete_sign(&secret_key, committed_nonce, &message_hash);
+    /// assert!(commitment.matches(&signature));
+    /// assert!(ecdsa.verify(
+    ///     &ecdsa.verification_key_for(&secret_key),
+    ///     &message_hash,
+    ///     &signature
+    /// ));
+    /// ```
+    ///
+    /// [`complete_sign`]: Self::complete_sign
+    /// [`NonceGen`]: crate::nonce::NonceGen
+    pub fn commit_nonce(&self, secret_key: &Scalar, session_id: &[u8]) -> CommittedNonce {
+        let X = self.verification_key_for(secret_key);
+        let r = derive_nonce!(
+            nonce_gen => self.nonce_gen,
+            secret => secret_key,
+            public => [
+                X,
+                COMMIT_NONCE_DOMAIN,
+                (session_id.len() as u64).to_be_bytes(),
+                session_id
+            ]
+        );
+        let R = g!(r * G).normalize();
+        CommittedNonce {
+            r,
+            commitment: RCommitment { R },
+        }
+    }
+
+    /// Completes a signature on `message_hash` with a nonce committed to by [`commit_nonce`].
+    ///
+    /// `secret_key` must be the same one passed to [`commit_nonce`].
+    ///
+    /// [`commit_nonce`]: Self::commit_nonce
+    pub fn complete_sign(
+        &self,
+        secret_key: &Scalar,
+        committed_nonce: CommittedNonce,
+        message_hash: &[u8; 32],
+    ) -> Signature {
+        crate::sign_with_nonce(secret_key, &committed_nonce.r, message_hash)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn committed_nonce_signatures() {
+        let ecdsa = crate::test_instance!();
+        let secret_key = Scalar::random(&mut rand::thread_rng());
+        let verification_key = ecdsa.verification_key_for(&secret_key);
+        let message_hash = [7u8; 32];
+
+        let committed_nonce = ecdsa.commit_nonce(&secret_key, b"session-1");
+        assert_eq!(
+            ecdsa.commit_nonce(&secret_key, b"session-1").commitment(),
+            committed_nonce.commitment()
+        );
+        let commitment = committed_nonce.commitment();
+        let other_session = ecdsa.commit_nonce(&secret_key, b"session-2").commitment();
+        assert_ne!(other_session, commitment);
+
+        let signature = ecdsa.complete_sign(&secret_key, committed_nonce, &message_hash);
+        assert!(ecdsa.verify(&verification_key, &message_hash, &signature));
+        assert!(commitment.matches(&signature));
+        assert!(!other_session.matches(&signature));
+        // a session id that looks like the message hash doesn't give the nonce `sign` uses
+        let plain_signature = ecdsa.sign(&secret_key, &message_hash);
+        assert!(!ecdsa
+            .commit_nonce(&secret_key, &message_hash)
+            .commitment()
+            .matches(&plain_signature));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn committed_nonce_debug_is_redacted() {
+        use alloc::format;
+        let ecdsa = crate::test_instance!();
+        let secret_key = Scalar::random(&mut rand::thread_rng());
+        let committed_nonce = ecdsa.commit_nonce(&secret_key, b"session-1");
+        let debug = format!("{:?}", committed_nonce);
+        assert!(debug.contains("commitment"));
+        assert!(!debug.contains(&format!("{}", committed_nonce.r)));
+    }
+}
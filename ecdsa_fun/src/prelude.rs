@@ -0,0 +1,21 @@
+//! The stable core of `ecdsa_fun` in one import.
+//!
+//! This includes everything in [`secp256kfun::prelude`] along with the ECDSA signature scheme.
+//!
+//! ```
+//! use ecdsa_fun::prelude::*;
+//! let ecdsa = ECDSA::new(Deterministic::<sha2::Sha256>::default());
+//! let secret_key = Scalar::random(&mut rand::thread_rng());
+//! let message_hash = [42u8; 32];
+//! let signature = ecdsa.sign(&secret_key, &message_hash);
+//! assert!(ecdsa.verify(
+//!     &ecdsa.verification_key_for(&secret_key),
+//!     &message_hash,
+//!     &signature
+//! ));
+//! ```
+//!
+//! Like [`secp256kfun::prelude`] items are only removed or renamed in a breaking release.
+//!
+//! [`secp256kfun::prelude`]: crate::fun::prelude
+pub use crate::{fun::prelude::*, NonceDerivation, Signature, ECDSA};
@@ -0,0 +1,132 @@
+//! The terms of the verification equation of a signature as plain data.
+//!
+//! A zero-knowledge circuit that proves "I know a valid signature under this key" has to do the
+//! same arithmetic as [`ECDSA::verify`]. [`VerificationEquation`] exports every term of it so the
+//! circuit builder can mirror (and test against) this crate's math:
+//!
+//! ```text
+//! R = u1 * G + u2 * X    where    u1 = m / s  and  u2 = r / s
+//! ```
+//!
+//! and the signature is valid if `R` isn't zero and its x-coordinate reduced mod the curve order
+//! is `r`. `m` is the 32-byte message hash reduced mod the curve order.
+//!
+//! # Example
+//!
+//! ```
+//! use ecdsa_fun::fun::{Scalar, G};
+//! let ecdsa = ecdsa_fun::test_instance!();
+//! let secret_key = Scalar::random(&mut rand::thread_rng());
+//! let verification_key = ecdsa.verification_key_for(&secret_key);
+//! let message_hash = b"prove I know a signature on this";
+//! let signature = ecdsa.sign(&secret_key, message_hash);
+//! let equation = ecdsa.verification_equation(&verification_key, message_hash, &signature);
+//! let [(u1, base), (u2, X)] = equation.terms();
+//! assert_eq!(base, G.normalize());
+//! assert_eq!(X, verification_key);
+//! assert!(equation.holds());
+//! ```
+//!
+//! [`ECDSA::verify`]: crate::ECDSA::verify
+use crate::{
+    fun::{g, marker::*, s, Point, Scalar, G},
+    Signature, ECDSA,
+};
+
+/// The terms of the verification equation of an ECDSA signature.
+///
+/// See the [module documentation](crate::public_inputs).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerificationEquation {
+    /// The verification key `X`.
+    pub verification_key: Point,
+    /// The message hash as a scalar `m`.
+    pub message: Scalar<Public, Zero>,
+    /// The signature's `r` (`R_x`).
+    pub r: Scalar<Public>,
+    /// The signature's `s`.
+    pub s: Scalar<Public>,
+    /// `u1 = m / s`.
+    pub u1: Scalar<Public, Zero>,
+    /// `u2 = r / s`.
+    pub u2: Scalar<Public>,
+}
+
+impl VerificationEquation {
+    /// The scalar and base point pairs that sum to `R`: `[(u1, G), (u2, X)]`.
+    pub fn terms(&self) -> [(Scalar<Public, Zero>, Point); 2] {
+        [
+            (self.u1, G.normalize()),
+            (self.u2.mark_zero(), self.verification_key),
+        ]
+    }
+
+    /// The sum of the [`terms`](Self::terms).
+    pub fn implied_R(&self) -> Point<Normal, Public, Zero> {
+        g!(self.u1 * G + self.u2 * self.verification_key).normalize()
+    }
+
+    /// Whether the equation holds, i.e. the signature is valid.
+    ///
+    /// This doesn't check that `s` is low (which [`ECDSA::verify`] does by default) or that `u1`
+    /// and `u2` were computed correctly.
+    ///
+    /// [`ECDSA::verify`]: crate::ECDSA::verify
+    pub fn holds(&self) -> bool {
+        self.implied_R()
+            .non_zero()
+            .map_or(false, |R| R.x_eq_scalar(&self.r))
+    }
+}
+
+impl<NG> ECDSA<NG> {
+    /// Exports the terms of the verification equation of `signature` on `message` under
+    /// `verification_key`.
+    ///
+    /// The terms are returned whether or not the signature is valid. Check with
+    /// [`VerificationEquation::holds`].
+    pub fn verification_equation(
+        &self,
+        verification_key: &Point,
+        message: &[u8; 32],
+        signature: &Signature,
+    ) -> VerificationEquation {
+        let (r, s) = signature.as_tuple();
+        let m = Scalar::<Public, _>::from_bytes_mod_order(*message);
+        let s_inv = s.invert();
+        VerificationEquation {
+            verification_key: *verification_key,
+            message: m,
+            r: *r,
+            s: *s,
+            u1: s!(s_inv * m).public(),
+            u2: s!(s_inv * r).public(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn verification_equation_mirrors_verify() {
+        let ecdsa = crate::test_instance!();
+        let secret_key = Scalar::random(&mut rand::thread_rng());
+        let verification_key = ecdsa.verification_key_for(&secret_key);
+        let message = [42u8; 32];
+        let signature = ecdsa.sign(&secret_key, &message);
+        let equation = ecdsa.verification_equation(&verification_key, &message, &signature);
+        assert!(equation.holds());
+        assert_eq!(
+            Some(equation.implied_R()),
+            ecdsa
+                .verify_with_nonce(&verification_key, &message, &signature)
+                .map(|R| R.mark_zero())
+        );
+        assert_eq!(s!(equation.u1 * equation.s), equation.message);
+        assert!(!ecdsa
+            .verification_equation(&verification_key, &[7u8; 32], &signature)
+            .holds());
+    }
+}
@@ -0,0 +1,253 @@
+//! ECDSA signatures that the public key can be recovered from.
+//!
+//! An ECDSA signature only contains the x-coordinate of the nonce `R` (reduced modulo the curve
+//! order) so given a signature and message hash there are up to four public keys it could be valid
+//! under. A [`RecoveryId`] records which of the candidates for `R` the signer used (the parity of
+//! its y-coordinate and whether its x-coordinate was greater than the curve order) so exactly one
+//! public key can be recovered from the signature with [`ECDSA::recover`]. This is how Ethereum's
+//! `ecrecover` and Bitcoin's signed messages identify the signer.
+//!
+//! [`RecoverableSignature::to_bytes`] produces the 64-byte compact signature followed by the
+//! recovery id (`0..=3`). This is the layout of libsecp256k1's
+//! `secp256k1_ecdsa_recoverable_signature_serialize_compact` output and of Ethereum signatures
+//! before `v` has `27` (or the chain id) added to it.
+//!
+//! Recovering a public key proves nothing on its own since almost any signature recovers to
+//! *some* key. Compare the recovered key (or its address) to the one you expect.
+//!
+//! # Example
+//!
+//! ```
+//! use ecdsa_fun::{fun::Scalar, nonce, recoverable::RecoverableSignature, ECDSA};
+//! let ecdsa = ECDSA::new(nonce::Deterministic::<sha2::Sha256>::default());
+//! let secret_key = Scalar::random(&mut rand::thread_rng());
+//! let message_hash = [42u8; 32];
+//! let signature = ecdsa.sign_recoverable(&secret_key, &message_hash);
+//! let bytes = signature.to_bytes();
+//!
+//! let received = RecoverableSignature::from_bytes(bytes).unwrap();
+//! assert_eq!(
+//!     ecdsa.recover(&message_hash, &received),
+//!     Some(ecdsa.verification_key_for(&secret_key))
+//! );
+//! // it's still an ordinary ECDSA signature
+//! assert!(ecdsa.verify(
+//!     &ecdsa.verification_key_for(&secret_key),
+//!     &message_hash,
+//!     &received.signature
+//! ));
+//! ```
+use crate::{
+    fun::{g, marker::*, nonce::NonceGen, Point, Scalar, G},
+    Signature, ECDSA,
+};
+
+/// The curve order `n` big-endian.
+const CURVE_ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+/// Which nonce point an ECDSA signature was made with out of the ones its `R_x` could mean.
+///
+/// Bit `0` is set if the y-coordinate of `R` is odd and bit `1` if its x-coordinate is `R_x + n`
+/// rather than `R_x` (which only happens with negligible probability).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct RecoveryId(u8);
+
+impl RecoveryId {
+    /// Creates a recovery id from its integer form. Returns `None` unless `id` is `0..=3`.
+    pub fn new(id: u8) -> Option<Self> {
+        if id < 4 {
+            Some(Self(id))
+        } else {
+            None
+        }
+    }
+
+    /// The recovery id as an integer in `0..=3`.
+    pub fn to_u8(self) -> u8 {
+        self.0
+    }
+
+    /// Whether `R` has an odd y-coordinate.
+    pub fn is_y_odd(self) -> bool {
+        self.0 & 1 == 1
+    }
+
+    /// Whether the x-coordinate of `R` was greater than the curve order.
+    pub fn is_x_reduced(self) -> bool {
+        self.0 & 2 == 2
+    }
+}
+
+/// An ECDSA signature along with the [`RecoveryId`] needed to recover the public key.
+///
+/// Create one with [`ECDSA::sign_recoverable`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct RecoverableSignature {
+    /// The ordinary signature.
+    pub signature: Signature,
+    /// Which nonce point the signature was made with.
+    pub recovery_id: RecoveryId,
+}
+
+impl RecoverableSignature {
+    /// The length of the encoding of a recoverable signature.
+    pub const SIZE: usize = Signature::SIZE + 1;
+
+    /// Encodes the signature as `R_x || s || recovery_id`.
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[..64].copy_from_slice(&self.signature.to_bytes());
+        bytes[64] = self.recovery_id.to_u8();
+        bytes
+    }
+
+    /// Decodes a signature encoded with [`to_bytes`](Self::to_bytes).
+    ///
+    /// Returns `None` if the signature is invalid or the recovery id isn't `0..=3`.
+    pub fn from_bytes(bytes: [u8; Self::SIZE]) -> Option<Self> {
+        Some(Self {
+            signature: Signature::from_bytes(bytes[..64].try_into().expect("64 bytes"))?,
+            recovery_id: RecoveryId::new(bytes[64])?,
+        })
+    }
+}
+
+impl From<RecoverableSignature> for Signature {
+    fn from(signature: RecoverableSignature) -> Self {
+        signature.signature
+    }
+}
+
+/// The recovery id of the signature with nonce `R` where `negated` is whether `s` was negated to
+/// make it low (which negates the nonce the signature is valid for).
+pub(crate) fn recovery_id(R: &Point, negated: bool) -> RecoveryId {
+    let y_odd = !R.is_y_even() ^ negated;
+    let x_reduced = R.to_xonly_bytes() >= CURVE_ORDER;
+    RecoveryId(y_odd as u8 | (x_reduced as u8) << 1)
+}
+
+impl<NG: NonceGen> ECDSA<NG> {
+    /// Produces an ECDSA signature on a message hash with the [`RecoveryId`] so the public key can
+    /// be recovered from it.
+    ///
+    /// The signature is the same one [`sign`](Self::sign) produces. See the [module
+    /// documentation](crate::recoverable).
+    pub fn sign_recoverable(
+        &self,
+        secret_key: &Scalar,
+        message_hash: &[u8; 32],
+    ) -> RecoverableSignature {
+        let r = self.derive_nonce(secret_key, message_hash);
+        crate::sign_recoverable_with_nonce(secret_key, &r, message_hash)
+    }
+}
+
+impl<NG> ECDSA<NG> {
+    /// Recovers the public key that `signature` on `message_hash` is valid under.
+    ///
+    /// Returns `None` if no key can be recovered e.g. because the recovery id points to an `R`
+    /// that doesn't exist or `s` is high and the instance [enforces low
+    /// `s`](ECDSA::enforce_low_s).
+    #[must_use]
+    pub fn recover(
+        &self,
+        message_hash: &[u8; 32],
+        signature: &RecoverableSignature,
+    ) -> Option<Point> {
+        let (R_x, s) = signature.signature.as_tuple();
+        if s.is_high() && self.enforces_low_s() {
+            return None;
+        }
+        let mut x = R_x.to_bytes();
+        if signature.recovery_id.is_x_reduced() {
+            x = add_curve_order(x)?;
+        }
+        let mut R_bytes = [0u8; 33];
+        R_bytes[0] = 0x02 | signature.recovery_id.is_y_odd() as u8;
+        R_bytes[1..].copy_from_slice(&x);
+        let R = Point::<Normal, Public, NonZero>::from_bytes(R_bytes)?;
+
+        let m = Scalar::<Public, _>::from_bytes_mod_order(*message_hash).public();
+        let R_x_inv = R_x.invert();
+        g!((R_x_inv * s) * R - (R_x_inv * m) * G)
+            .normalize()
+            .non_zero()
+    }
+}
+
+/// `x + n` or `None` if it overflows 256 bits.
+fn add_curve_order(mut x: [u8; 32]) -> Option<[u8; 32]> {
+    let mut carry = 0u16;
+    for (byte, n) in x.iter_mut().zip(CURVE_ORDER).rev() {
+        let sum = *byte as u16 + n as u16 + carry;
+        *byte = sum as u8;
+        carry = sum >> 8;
+    }
+    if carry == 0 {
+        Some(x)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sign_and_recover() {
+        let ecdsa = crate::test_instance!();
+        let mut seen_parities = [false; 2];
+        for i in 0..16u8 {
+            let secret_key = Scalar::random(&mut rand::thread_rng());
+            let public_key = ecdsa.verification_key_for(&secret_key);
+            let message_hash = [i; 32];
+            let signature = ecdsa.sign_recoverable(&secret_key, &message_hash);
+            assert_eq!(signature.signature, ecdsa.sign(&secret_key, &message_hash));
+            assert!(!signature.recovery_id.is_x_reduced());
+            seen_parities[signature.recovery_id.to_u8() as usize] = true;
+            assert_eq!(ecdsa.recover(&message_hash, &signature), Some(public_key));
+            assert_eq!(
+                RecoverableSignature::from_bytes(signature.to_bytes()),
+                Some(signature.clone())
+            );
+
+            // the wrong parity recovers a different key
+            let mut flipped = signature.clone();
+            flipped.recovery_id = RecoveryId::new(signature.recovery_id.to_u8() ^ 1).unwrap();
+            assert_ne!(ecdsa.recover(&message_hash, &flipped), Some(public_key));
+            assert_ne!(ecdsa.recover(&[i + 1; 32], &signature), Some(public_key));
+        }
+        assert_eq!(seen_parities, [true, true]);
+
+        assert_eq!(RecoveryId::new(4), None);
+        let mut bytes = ecdsa
+            .sign_recoverable(&Scalar::one(), &[0u8; 32])
+            .to_bytes();
+        bytes[64] = 4;
+        assert_eq!(RecoverableSignature::from_bytes(bytes), None);
+    }
+
+    #[test]
+    fn x_reduced_recovery_id() {
+        assert_eq!(add_curve_order([0u8; 32]), Some(CURVE_ORDER));
+        assert_eq!(add_curve_order([0xff; 32]), None);
+        let mut signature = crate::test_instance!().sign_recoverable(&Scalar::one(), &[1u8; 32]);
+        // R_x + n is only an x-coordinate if R_x < p - n which is almost impossible
+        signature.recovery_id = RecoveryId::new(signature.recovery_id.to_u8() | 2).unwrap();
+        assert_eq!(ECDSA::verify_only().recover(&[1u8; 32], &signature), None);
+    }
+}
@@ -0,0 +1,400 @@
+//! Proof of reserves attestations over a mix of ECDSA and Schnorr keys.
+//!
+//! A custodian proves it controls a set of keys by signing a challenge (e.g. chosen by an auditor
+//! or a recent block hash) with every one of them. Keys used with ECDSA (e.g. segwit v0 outputs)
+//! and keys used with BIP340 Schnorr signatures (taproot outputs) can be mixed in one
+//! [`ReserveAttestation`].
+//!
+//! The attestation commits to the set of keys with a [`Merkle`] root over the keys in the order
+//! they were added. The root is what the custodian publishes so that anyone can later check that a
+//! key was part of the attested set with a [`membership_proof`]. [`ReserveAttestation::verify`] checks the ECDSA signatures one by
+//! one and all of the Schnorr signatures in a single batch. It rejects attestations that
+//! include the same key twice since that would count its funds twice.
+//!
+//! `H` must be SHA256 for the Schnorr signatures to be BIP340 signatures.
+//!
+//! [`membership_proof`]: ReserveAttestation::membership_proof
+//!
+//! # Example
+//!
+//! ```
+//! use ecdsa_fun::{
+//!     fun::{nonce, Scalar},
+//!     reserves::{verify_membership, ReserveAttestation, ReserveProof},
+//!     ECDSA,
+//! };
+//! use sha2::Sha256;
+//! let ecdsa = ECDSA::new(nonce::Deterministic::<Sha256>::default());
+//! let schnorr = schnorr_fun::Schnorr::<Sha256, _>::new(nonce::Deterministic::<Sha256>::default());
+//! let ecdsa_key = Scalar::random(&mut rand::thread_rng());
+//! let taproot_key = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//!
+//! let mut attestation = ReserveAttestation::new(b"audit 2024-06-30".to_vec());
+//! attestation.push(ReserveProof::sign_ecdsa::<Sha256, _>(&ecdsa, &ecdsa_key, &attestation.challenge));
+//! attestation.push(ReserveProof::sign_schnorr(&schnorr, &taproot_key, &attestation.challenge));
+//!
+//! // the auditor checks the attestation and gets the commitment to the set of keys
+//! let root = attestation.verify::<Sha256>().unwrap();
+//! assert_eq!(Some(root), attestation.merkle_root::<Sha256>());
+//!
+//! // later the owner of a key can show it was included
+//! let membership = attestation.membership_proof::<Sha256>(1).unwrap();
+//! assert!(verify_membership::<Sha256>(&root, &attestation.proofs[1], &membership));
+//! ```
+use crate::{
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        g,
+        hash::{HashAdd, Tag},
+        marker::*,
+        merkle::{Merkle, MerkleProof},
+        nonce::NonceGen,
+        op, s, KeyPair, Point, Scalar, G,
+    },
+    Signature as EcdsaSignature, ECDSA,
+};
+use alloc::{collections::BTreeSet, vec::Vec};
+use core::fmt;
+use schnorr_fun::{Message, Schnorr, Signature as SchnorrSignature};
+
+/// The application tag of the [`Message`] Schnorr keys sign.
+pub const SCHNORR_APP_TAG: &str = "proof-of-reserves";
+
+const CHALLENGE_TAG: &[u8] = b"ecdsa_fun/reserves/challenge";
+const LEAF_TAG: &[u8] = b"ecdsa_fun/reserves/leaf";
+const BRANCH_TAG: &[u8] = b"ecdsa_fun/reserves/branch";
+const BATCH_TAG: &[u8] = b"ecdsa_fun/reserves/batch";
+
+/// The message hash ECDSA keys sign for `challenge`.
+pub fn ecdsa_message_hash<H: Digest<OutputSize = U32> + Tag + Default>(
+    challenge: &[u8],
+) -> [u8; 32] {
+    H::default()
+        .tag(CHALLENGE_TAG)
+        .add(challenge)
+        .finalize()
+        .into()
+}
+
+/// The message Schnorr keys sign for `challenge`.
+pub fn schnorr_message(challenge: &[u8]) -> Message<'_, Public> {
+    Message::plain(SCHNORR_APP_TAG, challenge)
+}
+
+/// Checks that the key of `proof` is one of the keys committed to by `root`.
+///
+/// `membership` comes from [`ReserveAttestation::membership_proof`].
+#[must_use]
+pub fn verify_membership<H: Digest<OutputSize = U32> + Tag + Default + Clone>(
+    root: &[u8; 32],
+    proof: &ReserveProof,
+    membership: &MerkleProof,
+) -> bool {
+    reserves_merkle::<H>().verify(root, &proof.leaf_hash::<H>(), membership)
+}
+
+fn reserves_merkle<H: Digest<OutputSize = U32> + Tag + Default + Clone>() -> Merkle<H> {
+    Merkle::new(LEAF_TAG, BRANCH_TAG)
+}
+
+/// A signature on the challenge by one of the keys.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub enum ReserveProof {
+    /// A key that signs with ECDSA.
+    Ecdsa {
+        /// The key.
+        public_key: Point,
+        /// Its signature on [`ecdsa_message_hash`].
+        signature: EcdsaSignature,
+    },
+    /// A key that signs with BIP340 Schnorr signatures.
+    Schnorr {
+        /// The key.
+        public_key: Point<EvenY>,
+        /// Its signature on [`schnorr_message`].
+        signature: SchnorrSignature,
+    },
+}
+
+impl ReserveProof {
+    /// Signs `challenge` with an ECDSA key.
+    pub fn sign_ecdsa<H: Digest<OutputSize = U32> + Tag + Default, NG: NonceGen>(
+        ecdsa: &ECDSA<NG>,
+        secret_key: &Scalar,
+        challenge: &[u8],
+    ) -> Self {
+        ReserveProof::Ecdsa {
+            public_key: ecdsa.verification_key_for(secret_key),
+            signature: ecdsa.sign(secret_key, &ecdsa_message_hash::<H>(challenge)),
+        }
+    }
+
+    /// Signs `challenge` with a Schnorr key.
+    pub fn sign_schnorr<H: Digest<OutputSize = U32> + Clone, NG: NonceGen>(
+        schnorr: &Schnorr<H, NG>,
+        keypair: &KeyPair<EvenY>,
+        challenge: &[u8],
+    ) -> Self {
+        ReserveProof::Schnorr {
+            public_key: keypair.public_key(),
+            signature: schnorr.sign(keypair, schnorr_message(challenge)),
+        }
+    }
+
+    /// The hash of the key's leaf in the [`merkle_root`].
+    ///
+    /// [`merkle_root`]: ReserveAttestation::merkle_root
+    pub fn leaf_hash<H: Digest<OutputSize = U32> + Tag + Default + Clone>(&self) -> [u8; 32] {
+        reserves_merkle::<H>().leaf_hash(&self.leaf_bytes())
+    }
+
+    /// The encoding of the key in its Merkle leaf. The first byte says which scheme it is used
+    /// with so the same key used with both is two different leaves.
+    fn leaf_bytes(&self) -> [u8; 34] {
+        let mut bytes = [0u8; 34];
+        match self {
+            ReserveProof::Ecdsa { public_key, .. } => {
+                bytes[0] = 0;
+                bytes[1..].copy_from_slice(&public_key.to_bytes());
+            }
+            ReserveProof::Schnorr { public_key, .. } => {
+                bytes[0] = 1;
+                bytes[1..].copy_from_slice(&public_key.normalize().to_bytes());
+            }
+        }
+        bytes
+    }
+}
+
+/// Signatures on a challenge by a set of keys. See the [module documentation](crate::reserves).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct ReserveAttestation {
+    /// The challenge signed by every key.
+    pub challenge: Vec<u8>,
+    /// The signature of each key in the order they are committed to.
+    pub proofs: Vec<ReserveProof>,
+}
+
+impl ReserveAttestation {
+    /// Starts an attestation for `challenge` with no keys.
+    pub fn new(challenge: Vec<u8>) -> Self {
+        Self {
+            challenge,
+            proofs: Vec::new(),
+        }
+    }
+
+    /// Adds the signature of another key.
+    pub fn push(&mut self, proof: ReserveProof) {
+        self.proofs.push(proof)
+    }
+
+    /// The Merkle root committing to the keys in the order they were added. Returns `None` if
+    /// there are no keys.
+    ///
+    /// The tree is a [`Merkle`] tree over the [`leaf_hash`] of each key. This doesn't check the
+    /// signatures.
+    ///
+    /// [`leaf_hash`]: ReserveProof::leaf_hash
+    pub fn merkle_root<H: Digest<OutputSize = U32> + Tag + Default + Clone>(
+        &self,
+    ) -> Option<[u8; 32]> {
+        reserves_merkle::<H>().root(&self.leaf_hashes::<H>())
+    }
+
+    /// The proof that the key at `index` is in the tree with the [`merkle_root`].
+    ///
+    /// Check it with [`verify_membership`].
+    ///
+    /// [`merkle_root`]: Self::merkle_root
+    pub fn membership_proof<H: Digest<OutputSize = U32> + Tag + Default + Clone>(
+        &self,
+        index: usize,
+    ) -> Option<MerkleProof> {
+        reserves_merkle::<H>().proof(&self.leaf_hashes::<H>(), index)
+    }
+
+    fn leaf_hashes<H: Digest<OutputSize = U32> + Tag + Default + Clone>(&self) -> Vec<[u8; 32]> {
+        self.proofs
+            .iter()
+            .map(ReserveProof::leaf_hash::<H>)
+            .collect()
+    }
+
+    /// Verifies the signature of every key and returns the [`merkle_root`] of the key set.
+    ///
+    /// [`merkle_root`]: Self::merkle_root
+    pub fn verify<H: Digest<OutputSize = U32> + Tag + Default + Clone>(
+        &self,
+    ) -> Result<[u8; 32], ReservesError> {
+        if self.proofs.is_empty() {
+            return Err(ReservesError::Empty);
+        }
+        let mut leaves = BTreeSet::new();
+        for (index, proof) in self.proofs.iter().enumerate() {
+            if !leaves.insert(proof.leaf_bytes()) {
+                return Err(ReservesError::DuplicateKey { index });
+            }
+        }
+
+        let root = self.merkle_root::<H>().ok_or(ReservesError::Empty)?;
+        let ecdsa = ECDSA::verify_only();
+        let message_hash = ecdsa_message_hash::<H>(&self.challenge);
+        let schnorr = Schnorr::<H>::verify_only();
+        let message = schnorr_message(&self.challenge);
+        // the batch weights are derived from everything being verified
+        let batch_hash = self
+            .proofs
+            .iter()
+            .fold(
+                H::default().tag(BATCH_TAG).add(root),
+                |hash, proof| match proof {
+                    ReserveProof::Ecdsa { .. } => hash,
+                    ReserveProof::Schnorr { signature, .. } => hash.add(signature.to_bytes()),
+                },
+            )
+            .add(message_hash);
+
+        let mut s_sum = Scalar::<Public, Zero>::zero();
+        let mut scalars = Vec::new();
+        let mut points = Vec::new();
+        for (index, proof) in self.proofs.iter().enumerate() {
+            match proof {
+                ReserveProof::Ecdsa {
+                    public_key,
+                    signature,
+                } => {
+                    if !ecdsa.verify(public_key, &message_hash, signature) {
+                        return Err(ReservesError::InvalidEcdsaSignature { index });
+                    }
+                }
+                ReserveProof::Schnorr {
+                    public_key,
+                    signature,
+                } => {
+                    let weight =
+                        Scalar::from_hash(batch_hash.clone().add((index as u64).to_be_bytes()))
+                            .public();
+                    let c = schnorr.challenge(&signature.R, public_key, message);
+                    s_sum = s!(s_sum + weight * signature.s).public();
+                    scalars.push(s!(weight * c).public());
+                    points.push(*public_key);
+                    scalars.push(weight.mark_zero());
+                    points.push(signature.R);
+                }
+            }
+        }
+
+        if !points.is_empty() && g!(s_sum * G) != op::point_scalar_dot_product(scalars, points) {
+            return Err(ReservesError::InvalidSchnorrSignatures);
+        }
+
+        Ok(root)
+    }
+}
+
+/// Error returned by [`ReserveAttestation::verify`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReservesError {
+    /// The attestation has no keys.
+    Empty,
+    /// The key at `index` was already included.
+    DuplicateKey {
+        /// The index of the proof in [`ReserveAttestation::proofs`].
+        index: usize,
+    },
+    /// The ECDSA signature at `index` is invalid.
+    InvalidEcdsaSignature {
+        /// The index of the proof in [`ReserveAttestation::proofs`].
+        index: usize,
+    },
+    /// At least one of the Schnorr signatures is invalid.
+    InvalidSchnorrSignatures,
+}
+
+impl fmt::Display for ReservesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReservesError::Empty => write!(f, "the attestation has no keys"),
+            ReservesError::DuplicateKey { index } => {
+                write!(f, "the key at index {index} appears more than once")
+            }
+            ReservesError::InvalidEcdsaSignature { index } => {
+                write!(f, "the ECDSA signature at index {index} is invalid")
+            }
+            ReservesError::InvalidSchnorrSignatures => {
+                write!(f, "at least one of the Schnorr signatures is invalid")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ReservesError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fun::nonce;
+    use sha2::Sha256;
+
+    #[test]
+    fn mixed_attestation() {
+        let ecdsa = ECDSA::new(nonce::Deterministic::<Sha256>::default());
+        let schnorr = Schnorr::<Sha256, _>::new(nonce::Deterministic::<Sha256>::default());
+        let mut attestation = ReserveAttestation::new(b"challenge".to_vec());
+        assert_eq!(attestation.verify::<Sha256>(), Err(ReservesError::Empty));
+        for i in 0..5 {
+            let secret_key = Scalar::random(&mut rand::thread_rng());
+            attestation.push(if i % 2 == 0 {
+                ReserveProof::sign_ecdsa::<Sha256, _>(&ecdsa, &secret_key, b"challenge")
+            } else {
+                ReserveProof::sign_schnorr(&schnorr, &schnorr.new_keypair(secret_key), b"challenge")
+            });
+        }
+        let root = attestation.verify::<Sha256>().unwrap();
+
+        for (index, proof) in attestation.proofs.iter().enumerate() {
+            let membership = attestation.membership_proof::<Sha256>(index).unwrap();
+            assert!(verify_membership::<Sha256>(&root, proof, &membership));
+        }
+
+        // the root depends on where the keys are in the tree
+        let mut reordered = attestation.clone();
+        reordered.proofs.swap(0, 2);
+        assert_ne!(reordered.verify::<Sha256>().unwrap(), root);
+
+        let mut duplicated = attestation.clone();
+        duplicated.push(attestation.proofs[1].clone());
+        assert_eq!(
+            duplicated.verify::<Sha256>(),
+            Err(ReservesError::DuplicateKey { index: 5 })
+        );
+
+        let mut wrong_challenge = attestation.clone();
+        wrong_challenge.challenge = b"other challenge".to_vec();
+        assert_eq!(
+            wrong_challenge.verify::<Sha256>(),
+            Err(ReservesError::InvalidEcdsaSignature { index: 0 })
+        );
+
+        let mut bad_schnorr = attestation.clone();
+        if let ReserveProof::Schnorr { signature, .. } = &mut bad_schnorr.proofs[3] {
+            signature.s = s!(signature.s + 1).public();
+        }
+        assert_eq!(
+            bad_schnorr.verify::<Sha256>(),
+            Err(ReservesError::InvalidSchnorrSignatures)
+        );
+    }
+}
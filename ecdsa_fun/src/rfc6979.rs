@@ -0,0 +1,167 @@
+//! [RFC 6979] deterministic nonces as libsecp256k1 and Bitcoin Core derive them.
+//!
+//! [`ECDSA`](crate::ECDSA) derives nonces with its own [`NonceGen`](crate::nonce::NonceGen). Use
+//! this module when you need byte-for-byte the signatures other implementations produce e.g. to
+//! check a hardware wallet or to reproduce Bitcoin Core's signatures. `H` must be SHA256 for that.
+//!
+//! [`nonce`] is libsecp256k1's `nonce_function_rfc6979`: HMAC-DRBG seeded with the secret key, the
+//! message hash (reduced modulo the curve order) and optionally 32 bytes of extra data.
+//! [`sign_low_r`] is the nonce grinding Bitcoin Core's `CKey::Sign` does.
+//!
+//! # Example
+//!
+//! ```
+//! use ecdsa_fun::{fun::Scalar, rfc6979, ECDSA};
+//! use sha2::Sha256;
+//! let secret_key = Scalar::random(&mut rand::thread_rng());
+//! let signature = rfc6979::sign_low_r::<Sha256>(&secret_key, &[42u8; 32]);
+//! assert!(signature.has_low_r());
+//! let ecdsa = ECDSA::verify_only();
+//! assert!(ecdsa.verify(&ecdsa.verification_key_for(&secret_key), &[42u8; 32], &signature));
+//! ```
+//!
+//! [RFC 6979]: https://www.rfc-editor.org/rfc/rfc6979
+use crate::{
+    fun::{
+        digest::{core_api::BlockSizeUser, generic_array::typenum::U32, Digest},
+        hash::hmac,
+        marker::*,
+        Scalar,
+    },
+    sign_with_nonce, Signature,
+};
+
+/// The RFC 6979 nonce for signing `message_hash` with `secret_key`.
+///
+/// `extra_data` is mixed into the seed after the message hash like libsecp256k1's `ndata`.
+pub fn nonce<H: BlockSizeUser + Digest<OutputSize = U32> + Default>(
+    secret_key: &Scalar,
+    message_hash: &[u8; 32],
+    extra_data: Option<&[u8; 32]>,
+) -> Scalar {
+    let secret_key = secret_key.to_secret_bytes();
+    // bits2octets
+    let message = Scalar::<Public, Zero>::from_bytes_mod_order(*message_hash).to_bytes();
+    let extra_data = extra_data.map_or(&[][..], |extra_data| &extra_data[..]);
+    let seed = [secret_key.expose_secret(), &message[..], extra_data];
+
+    let mut k = [0u8; 32];
+    let mut v = [1u8; 32];
+    for separator in [[0u8], [1u8]] {
+        k = hmac::<H>(&k, &[&v, &separator, seed[0], seed[1], seed[2]]).into();
+        v = hmac::<H>(&k, &[&v]).into();
+    }
+    loop {
+        v = hmac::<H>(&k, &[&v]).into();
+        if let Some(nonce) =
+            Scalar::<Secret, Zero>::from_bytes(v).and_then(|nonce| nonce.non_zero())
+        {
+            return nonce;
+        }
+        k = hmac::<H>(&k, &[&v, &[0u8]]).into();
+        v = hmac::<H>(&k, &[&v]).into();
+    }
+}
+
+/// Signs `message_hash` with the RFC 6979 nonce the way libsecp256k1's `secp256k1_ecdsa_sign`
+/// does by default.
+pub fn sign<H: BlockSizeUser + Digest<OutputSize = U32> + Default>(
+    secret_key: &Scalar,
+    message_hash: &[u8; 32],
+) -> Signature {
+    sign_with_nonce(
+        secret_key,
+        &nonce::<H>(secret_key, message_hash, None),
+        message_hash,
+    )
+}
+
+/// Signs `message_hash` grinding RFC 6979 nonces until the signature [has a low
+/// `R_x`](Signature::has_low_r) exactly as Bitcoin Core's `CKey::Sign` does.
+///
+/// The first try has no extra data (so it's the same as [`sign`]) and try `i` has the
+/// little-endian 32-bit `i` padded to 32 bytes.
+pub fn sign_low_r<H: BlockSizeUser + Digest<OutputSize = U32> + Default>(
+    secret_key: &Scalar,
+    message_hash: &[u8; 32],
+) -> Signature {
+    let mut signature = sign::<H>(secret_key, message_hash);
+    let mut extra_data = [0u8; 32];
+    let mut counter = 0u32;
+    while !signature.has_low_r() {
+        counter += 1;
+        extra_data[..4].copy_from_slice(&counter.to_le_bytes());
+        let r = nonce::<H>(secret_key, message_hash, Some(&extra_data));
+        signature = sign_with_nonce(secret_key, &r, message_hash);
+    }
+    signature
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{fun::nonce::Deterministic, ECDSA};
+    use core::str::FromStr;
+    use sha2::Sha256;
+
+    // SHA256("Satoshi Nakamoto")
+    const MESSAGE_HASH: &str = "a0dc65ffca799873cbea0ac274015b9526505daaaed385155425f7337704883e";
+
+    #[test]
+    fn rfc6979_vectors() {
+        let message_hash = crate::fun::hex::decode_array::<32>(MESSAGE_HASH).unwrap();
+        for (secret_key, expected_nonce, expected_signature) in [
+            (
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "8f8a276c19f4149656b280621e358cce24f5f52542772691ee69063b74f15d15",
+                "934b1ea10a4b3c1757e2b0c017d0b6143ce3c9a7e6a4a49860d7a6ab210ee3d82442ce9d2b916064108014783e923ec36b49743e2ffa1c4496f01a512aafd9e5",
+            ),
+            (
+                "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140",
+                "33a19b60e25fb6f4435af53a3d42d493644827367e6453928554f43e49aa6f90",
+                "fd567d121db66e382991534ada77a6bd3106f0a1098c231e47993447cd6af2d06b39cd0eb1bc8603e159ef5c20a5c8ad685a45b06ce9bebed3f153d10d93bed5",
+            ),
+        ] {
+            let secret_key = Scalar::from_str(secret_key).unwrap();
+            assert_eq!(
+                nonce::<Sha256>(&secret_key, &message_hash, None),
+                Scalar::<Secret, NonZero>::from_str(expected_nonce).unwrap()
+            );
+            let expected_signature = Signature::from_str(expected_signature).unwrap();
+            assert_eq!(sign::<Sha256>(&secret_key, &message_hash), expected_signature);
+
+            // both signatures have a high R_x so grinding has to find another nonce
+            assert!(!expected_signature.has_low_r());
+            let low_r = sign_low_r::<Sha256>(&secret_key, &message_hash);
+            assert!(low_r.has_low_r());
+            let ecdsa = ECDSA::verify_only();
+            assert!(ecdsa.verify(
+                &ecdsa.verification_key_for(&secret_key),
+                &message_hash,
+                &low_r
+            ));
+        }
+    }
+
+    #[test]
+    fn sign_low_r_keeps_low_signatures() {
+        let ecdsa = ECDSA::new(Deterministic::<Sha256>::default());
+        for i in 0..16u8 {
+            let secret_key = Scalar::random(&mut rand::thread_rng());
+            let message_hash = [i; 32];
+            let signature = ecdsa.sign_low_r(&secret_key, &message_hash);
+            assert!(signature.has_low_r());
+            assert!(ecdsa.verify(
+                &ecdsa.verification_key_for(&secret_key),
+                &message_hash,
+                &signature
+            ));
+            let plain = ecdsa.sign(&secret_key, &message_hash);
+            assert_eq!(plain.has_low_r(), plain == signature);
+
+            let rfc6979_plain = sign::<Sha256>(&secret_key, &message_hash);
+            let rfc6979_low_r = sign_low_r::<Sha256>(&secret_key, &message_hash);
+            assert_eq!(rfc6979_plain.has_low_r(), rfc6979_plain == rfc6979_low_r);
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Known-answer self tests of ECDSA signing and verification.
+//!
+//! [`self_test`] runs the [curve arithmetic self tests] followed by ECDSA known-answer tests and
+//! (with the `adaptor` feature) an adaptor signature round trip and returns a report of what
+//! passed. It is meant for deployments that have to run "power-on self tests" before using the
+//! library. The hash it is given must be the SHA256 implementation the application uses.
+//!
+//! The signing vectors are the widely used [RFC 6979] secp256k1 vectors. This library doesn't
+//! derive nonces with RFC 6979 so the known answer test is that signing with the RFC 6979 nonce
+//! produces the RFC 6979 signature.
+//!
+//! # Example
+//!
+//! ```
+//! let report = ecdsa_fun::self_test::self_test::<sha2::Sha256>();
+//! assert!(report.passed(), "{}", report);
+//! ```
+//!
+//! [curve arithmetic self tests]: crate::fun::self_test
+//! [RFC 6979]: https://www.rfc-editor.org/rfc/rfc6979
+use crate::{
+    fun::{
+        digest::{
+            core_api::BlockSizeUser,
+            generic_array::typenum::{U32, U64},
+            Digest, FixedOutput, Update,
+        },
+        hex,
+        marker::*,
+        nonce,
+        self_test::SelfTestReport,
+        Point, Scalar,
+    },
+    sign_with_nonce, Signature, ECDSA,
+};
+use core::str::FromStr;
+
+/// The message every signing vector signs.
+const MESSAGE: &[u8] = b"Satoshi Nakamoto";
+
+/// SHA256 of [`MESSAGE`].
+const MESSAGE_HASH: &str = "a0dc65ffca799873cbea0ac274015b9526505daaaed385155425f7337704883e";
+
+/// Secret key, public key, RFC 6979 nonce and signature of [`MESSAGE`].
+const SIGNING_VECTORS: [(&str, &str, &str, &str); 2] = [
+    (
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        "8f8a276c19f4149656b280621e358cce24f5f52542772691ee69063b74f15d15",
+        "934b1ea10a4b3c1757e2b0c017d0b6143ce3c9a7e6a4a49860d7a6ab210ee3d82442ce9d2b916064108014783e923ec36b49743e2ffa1c4496f01a512aafd9e5",
+    ),
+    (
+        "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140",
+        "0379be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        "33a19b60e25fb6f4435af53a3d42d493644827367e6453928554f43e49aa6f90",
+        "fd567d121db66e382991534ada77a6bd3106f0a1098c231e47993447cd6af2d06b39cd0eb1bc8603e159ef5c20a5c8ad685a45b06ce9bebed3f153d10d93bed5",
+    ),
+];
+
+/// Runs the curve arithmetic, ECDSA and (with the `adaptor` feature) ECDSA adaptor signature self
+/// tests with the SHA256 implementation `H`.
+pub fn self_test<H>() -> SelfTestReport
+where
+    H: Digest<OutputSize = U32>
+        + FixedOutput<OutputSize = U32>
+        + Update
+        + BlockSizeUser<BlockSize = U64>
+        + Default
+        + Clone,
+{
+    let mut report = crate::fun::self_test::self_test();
+    let message_hash = hex::decode_array::<32>(MESSAGE_HASH).expect("valid hex");
+
+    report.record(
+        "sha256",
+        Digest::finalize(Digest::chain_update(H::default(), MESSAGE))[..] == message_hash[..],
+    );
+
+    report.record(
+        "ecdsa/sign",
+        SIGNING_VECTORS
+            .iter()
+            .all(|(secret_key, _, nonce, signature)| {
+                let secret_key = Scalar::from_str(secret_key).expect("valid");
+                let nonce = Scalar::from_str(nonce).expect("valid");
+                let expected = Signature::from_str(signature).expect("valid");
+                sign_with_nonce(&secret_key, &nonce, &message_hash) == expected
+            }),
+    );
+
+    let ecdsa = ECDSA::verify_only();
+    let mut wrong_message_hash = message_hash;
+    wrong_message_hash[31] ^= 1;
+    report.record(
+        "ecdsa/verify",
+        SIGNING_VECTORS.iter().all(|(_, public_key, _, signature)| {
+            let public_key = Point::<Normal, Public, NonZero>::from_str(public_key).expect("valid");
+            let signature = Signature::from_str(signature).expect("valid");
+            ecdsa.verify(&public_key, &message_hash, &signature)
+                && !ecdsa.verify(&public_key, &wrong_message_hash, &signature)
+        }),
+    );
+
+    let ecdsa = ECDSA::new(nonce::Deterministic::<H>::default());
+    let secret_key = Scalar::from_str(SIGNING_VECTORS[1].0).expect("valid");
+    let public_key = ecdsa.verification_key_for(&secret_key);
+    let signature = ecdsa.sign(&secret_key, &message_hash);
+    report.record(
+        "ecdsa/sign_verify",
+        ecdsa.verify(&public_key, &message_hash, &signature) && !signature.s.is_high(),
+    );
+
+    #[cfg(feature = "adaptor")]
+    {
+        use crate::adaptor::{Adaptor, HashTranscript};
+        let adaptor = Adaptor::<HashTranscript<H, rand_chacha::ChaCha20Rng>, _>::new(
+            nonce::Deterministic::<H>::default(),
+        );
+        let decryption_key = Scalar::from_str(SIGNING_VECTORS[0].0).expect("valid");
+        let encryption_key = adaptor.encryption_key_for(&decryption_key);
+        let encrypted_signature =
+            adaptor.encrypted_sign(&secret_key, &encryption_key, &message_hash);
+        let signature = adaptor.decrypt_signature(&decryption_key, encrypted_signature.clone());
+        report.record(
+            "ecdsa/adaptor_round_trip",
+            adaptor.verify_encrypted_signature(
+                &public_key,
+                &encryption_key,
+                &message_hash,
+                &encrypted_signature,
+            ) && adaptor.ecdsa.verify(&public_key, &message_hash, &signature)
+                && adaptor.recover_decryption_key(
+                    &encryption_key,
+                    &signature,
+                    &encrypted_signature,
+                ) == Some(decryption_key),
+        );
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn self_test_passes() {
+        let report = self_test::<sha2::Sha256>();
+        assert!(report.passed(), "{}", report);
+        assert_eq!(
+            report.results.last().map(|result| result.name),
+            Some(if cfg!(feature = "adaptor") {
+                "ecdsa/adaptor_round_trip"
+            } else {
+                "ecdsa/sign_verify"
+            })
+        );
+    }
+}
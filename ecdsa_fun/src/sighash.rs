@@ -0,0 +1,202 @@
+//! Typed sighash flags for signatures in Bitcoin scripts.
+//!
+//! An ECDSA signature in a Bitcoin script is followed by a sighash flag saying which parts of the
+//! transaction it signed. Passing the flag around as a raw `u8` makes it easy to sign with one
+//! flag and encode another or to produce a flag Bitcoin doesn't define. Here the flag is a
+//! [`SighashFlag`] which can only be one of the six values Bitcoin defines for ECDSA signatures and
+//! [`ECDSA::sign_with_sighash`] binds it to the signature in a [`SighashSignature`] so the flag
+//! that is encoded is always the one the caller signed with.
+//!
+//! Computing the sighash itself (the message hash that depends on the flag) is up to the caller.
+//!
+//! # Example
+//!
+//! ```
+//! use ecdsa_fun::{
+//!     fun::Scalar,
+//!     nonce,
+//!     sighash::{SighashFlag, SighashSignature},
+//!     ECDSA,
+//! };
+//! let ecdsa = ECDSA::new(nonce::Deterministic::<sha2::Sha256>::default());
+//! let secret_key = Scalar::random(&mut rand::thread_rng());
+//! let verification_key = ecdsa.verification_key_for(&secret_key);
+//! let sighash = [42u8; 32]; // computed with SIGHASH_SINGLE|ANYONECANPAY
+//! let signature = ecdsa.sign_with_sighash(&secret_key, &sighash, SighashFlag::SingleAnyoneCanPay);
+//! assert!(ecdsa.verify(&verification_key, &sighash, &signature.signature));
+//!
+//! let bytes = signature.to_bitcoin_script_bytes();
+//! assert_eq!(bytes.last(), Some(&0x83));
+//! assert_eq!(SighashSignature::from_bitcoin_script_bytes(&bytes), Some(signature));
+//! // 0x84 isn't a sighash flag
+//! assert_eq!(SighashFlag::from_u8(0x84), None);
+//! ```
+use crate::{
+    fun::{nonce::NonceGen, Scalar},
+    Signature, ECDSA,
+};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// A sighash flag that Bitcoin defines for ECDSA signatures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SighashFlag {
+    /// `SIGHASH_ALL` (`0x01`): signs every input and output.
+    All,
+    /// `SIGHASH_NONE` (`0x02`): signs every input and no outputs.
+    None,
+    /// `SIGHASH_SINGLE` (`0x03`): signs every input and the output with the same index as the input
+    /// being signed.
+    Single,
+    /// `SIGHASH_ALL|SIGHASH_ANYONECANPAY` (`0x81`): signs the input being signed and every output.
+    AllAnyoneCanPay,
+    /// `SIGHASH_NONE|SIGHASH_ANYONECANPAY` (`0x82`): signs only the input being signed.
+    NoneAnyoneCanPay,
+    /// `SIGHASH_SINGLE|SIGHASH_ANYONECANPAY` (`0x83`): signs the input being signed and the output
+    /// with the same index.
+    SingleAnyoneCanPay,
+}
+
+impl SighashFlag {
+    /// The byte that follows the signature in a script.
+    pub fn to_u8(self) -> u8 {
+        match self {
+            SighashFlag::All => 0x01,
+            SighashFlag::None => 0x02,
+            SighashFlag::Single => 0x03,
+            SighashFlag::AllAnyoneCanPay => 0x81,
+            SighashFlag::NoneAnyoneCanPay => 0x82,
+            SighashFlag::SingleAnyoneCanPay => 0x83,
+        }
+    }
+
+    /// Interprets a sighash byte.
+    ///
+    /// Returns `None` for anything other than the six defined flags. Consensus accepts other
+    /// values but they aren't standard and are almost always a bug.
+    pub fn from_u8(byte: u8) -> Option<Self> {
+        Some(match byte {
+            0x01 => SighashFlag::All,
+            0x02 => SighashFlag::None,
+            0x03 => SighashFlag::Single,
+            0x81 => SighashFlag::AllAnyoneCanPay,
+            0x82 => SighashFlag::NoneAnyoneCanPay,
+            0x83 => SighashFlag::SingleAnyoneCanPay,
+            _ => return None,
+        })
+    }
+
+    /// Whether only the input being signed is signed (`SIGHASH_ANYONECANPAY`).
+    pub fn anyone_can_pay(self) -> bool {
+        self.to_u8() & 0x80 != 0
+    }
+
+    /// Whether the flag can be used to sign input `input_index` of a transaction with `n_outputs`
+    /// outputs.
+    ///
+    /// `SIGHASH_SINGLE` signs the output at the same index as the input so there has to be one.
+    /// Bitcoin doesn't reject a signature without one but the sighash it signs is the constant `1`
+    /// so the signature can be reused to spend any output the key controls.
+    pub fn is_valid_for_input(self, input_index: usize, n_outputs: usize) -> bool {
+        match self {
+            SighashFlag::Single | SighashFlag::SingleAnyoneCanPay => input_index < n_outputs,
+            _ => true,
+        }
+    }
+}
+
+impl From<SighashFlag> for u8 {
+    fn from(flag: SighashFlag) -> Self {
+        flag.to_u8()
+    }
+}
+
+/// An ECDSA signature along with the sighash flag it was made with.
+///
+/// Create one with [`ECDSA::sign_with_sighash`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SighashSignature {
+    /// The signature.
+    pub signature: Signature,
+    /// The flag the sighash that was signed was computed with.
+    pub sighash_flag: SighashFlag,
+}
+
+impl SighashSignature {
+    /// Encodes the signature as it appears in a Bitcoin script: the DER encoding followed by the
+    /// sighash flag.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn to_bitcoin_script_bytes(&self) -> Vec<u8> {
+        self.signature
+            .to_bitcoin_script_bytes(self.sighash_flag.to_u8())
+    }
+
+    /// Decodes a signature from a Bitcoin script encoding produced by [`to_bitcoin_script_bytes`].
+    ///
+    /// Unlike [`Signature::from_bitcoin_script_bytes`] this returns `None` if the sighash flag isn't
+    /// one of the [`SighashFlag`]s.
+    ///
+    /// [`to_bitcoin_script_bytes`]: Self::to_bitcoin_script_bytes
+    pub fn from_bitcoin_script_bytes(bytes: &[u8]) -> Option<Self> {
+        let (signature, sighash_flag) = Signature::from_bitcoin_script_bytes(bytes)?;
+        Some(Self {
+            signature,
+            sighash_flag: SighashFlag::from_u8(sighash_flag)?,
+        })
+    }
+}
+
+impl<NG: NonceGen> ECDSA<NG> {
+    /// Signs `sighash` which was computed with `sighash_flag` and returns the signature along with
+    /// the flag.
+    ///
+    /// See the [module documentation](crate::sighash).
+    pub fn sign_with_sighash(
+        &self,
+        secret_key: &Scalar,
+        sighash: &[u8; 32],
+        sighash_flag: SighashFlag,
+    ) -> SighashSignature {
+        SighashSignature {
+            signature: self.sign(secret_key, sighash),
+            sighash_flag,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sighash_flag_bytes() {
+        for byte in 0..=u8::MAX {
+            match SighashFlag::from_u8(byte) {
+                Some(flag) => {
+                    assert_eq!(flag.to_u8(), byte);
+                    assert_eq!(flag.anyone_can_pay(), byte & 0x80 != 0);
+                }
+                None => assert!(!matches!(byte, 0x01..=0x03 | 0x81..=0x83)),
+            }
+        }
+        assert!(SighashFlag::Single.is_valid_for_input(1, 2));
+        assert!(!SighashFlag::SingleAnyoneCanPay.is_valid_for_input(2, 2));
+        assert!(SighashFlag::All.is_valid_for_input(2, 2));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn script_bytes_reject_undefined_flags() {
+        let ecdsa = crate::test_instance!();
+        let secret_key = Scalar::random(&mut rand::thread_rng());
+        let signature = ecdsa.sign_with_sighash(&secret_key, &[1u8; 32], SighashFlag::None);
+        let mut bytes = signature.to_bitcoin_script_bytes();
+        assert_eq!(
+            SighashSignature::from_bitcoin_script_bytes(&bytes),
+            Some(signature)
+        );
+        *bytes.last_mut().unwrap() = 0x00;
+        assert_eq!(SighashSignature::from_bitcoin_script_bytes(&bytes), None);
+    }
+}
@@ -1,12 +1,19 @@
-use secp256kfun::{marker::*, Scalar};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use secp256kfun::{marker::*, signature::SignatureComponents, FromSliceError, Point, Scalar};
 /// An ECDSA signature
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Signature {
     pub R_x: Scalar<Public>,
     pub s: Scalar<Public>,
 }
 
+const _: () = assert!(Signature::SIZE == 2 * Scalar::<Public>::SIZE);
+
 impl Signature {
+    /// The length of the encoding of a signature.
+    pub const SIZE: usize = 64;
+
     pub fn to_bytes(&self) -> [u8; 64] {
         let mut bytes = [0u8; 64];
         bytes[0..32].copy_from_slice(&self.R_x.to_bytes()[..]);
@@ -17,6 +24,45 @@ impl Signature {
     pub fn as_tuple(&self) -> (&Scalar<Public>, &Scalar<Public>) {
         (&self.R_x, &self.s)
     }
+
+    /// Whether the top bit of `R_x` is clear so that it DER encodes in 32 bytes or fewer.
+    ///
+    /// Signatures from [`ECDSA::sign_low_r`](crate::ECDSA::sign_low_r) always have a low `R_x`.
+    pub fn has_low_r(&self) -> bool {
+        self.R_x.to_bytes()[0] < 0x80
+    }
+}
+
+/// `R` is always `None` since `R_x` doesn't determine the nonce point.
+impl SignatureComponents for Signature {
+    const SCHEME: &'static str = "ecdsa";
+
+    fn r(&self) -> [u8; 32] {
+        self.R_x.to_bytes()
+    }
+
+    fn R(&self) -> Option<Point<Normal, Public, NonZero>> {
+        None
+    }
+
+    fn s(&self) -> Scalar<Public, Zero> {
+        self.s.mark_zero()
+    }
+
+    fn from_components(r: [u8; 32], s: Scalar<Public, Zero>) -> Option<Self> {
+        Some(Self {
+            R_x: Scalar::from_bytes(r)?.non_zero()?,
+            s: s.non_zero()?,
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for Signature {
+    type Error = FromSliceError;
+
+    fn try_from(slice: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(FromSliceError::to_array(slice)?).ok_or(FromSliceError::Invalid)
+    }
 }
 
 impl Signature {
@@ -25,6 +71,199 @@ impl Signature {
         let s = Scalar::from_slice(&bytes[32..64])?.non_zero()?;
         Some(Self { R_x, s })
     }
+
+    /// Encodes the signature in the strict DER format Bitcoin requires ([BIP66]).
+    ///
+    /// The encoding is at most 72 bytes long.
+    ///
+    /// [BIP66]: https://github.com/bitcoin/bips/blob/master/bip-0066.mediawiki
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn to_der(&self) -> Vec<u8> {
+        let R_x = der_integer(&self.R_x);
+        let s = der_integer(&self.s);
+        let mut bytes = Vec::with_capacity(6 + R_x.len() + s.len());
+        bytes.extend_from_slice(&[0x30, (4 + R_x.len() + s.len()) as u8]);
+        bytes.extend_from_slice(&[0x02, R_x.len() as u8]);
+        bytes.extend_from_slice(&R_x);
+        bytes.extend_from_slice(&[0x02, s.len() as u8]);
+        bytes.extend_from_slice(&s);
+        bytes
+    }
+
+    /// Decodes a signature from strict DER as produced by [`to_der`].
+    ///
+    /// Returns `None` for any encoding [BIP66] doesn't allow (e.g. extra padding, trailing bytes)
+    /// or if either integer is zero or not less than the curve order. High `s` values are accepted.
+    ///
+    /// [`to_der`]: Self::to_der
+    /// [BIP66]: https://github.com/bitcoin/bips/blob/master/bip-0066.mediawiki
+    pub fn from_der(bytes: &[u8]) -> Option<Self> {
+        let (&tag, rest) = bytes.split_first()?;
+        let (&len, rest) = rest.split_first()?;
+        if tag != 0x30 || len as usize != rest.len() {
+            return None;
+        }
+        let (R_x, rest) = parse_der_integer(rest)?;
+        let (s, rest) = parse_der_integer(rest)?;
+        if !rest.is_empty() {
+            return None;
+        }
+        Some(Self { R_x, s })
+    }
+
+    /// Decodes a signature from DER the way consensus did before [BIP66].
+    ///
+    /// This is for parsing signatures in old Bitcoin transactions. It follows libsecp256k1's
+    /// `ecdsa_signature_parse_der_lax`: length bytes may use the long form and are mostly ignored,
+    /// integers may be padded with any number of zero bytes or have their sign bit set and bytes
+    /// after `s` are ignored. Returns `None` if either integer is zero or not less than the curve
+    /// order (libsecp256k1 parses these to a signature that never verifies). Use [`from_der`] for
+    /// anything else.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ecdsa_fun::Signature;
+    /// // R_x = 1 and s = 0x80 with an unnecessary zero byte in front of it
+    /// let lax = [0x30, 0x08, 0x02, 0x01, 0x01, 0x02, 0x03, 0x00, 0x00, 0x80];
+    /// assert_eq!(Signature::from_der(&lax), None);
+    /// let signature = Signature::from_der_lax(&lax).unwrap();
+    /// assert_eq!(signature.s.to_bytes()[31], 0x80);
+    /// ```
+    ///
+    /// [`from_der`]: Self::from_der
+    /// [BIP66]: https://github.com/bitcoin/bips/blob/master/bip-0066.mediawiki
+    pub fn from_der_lax(mut bytes: &[u8]) -> Option<Self> {
+        if take_byte(&mut bytes)? != 0x30 {
+            return None;
+        }
+        // the sequence length isn't checked but a long form length's bytes must be there
+        let len = take_byte(&mut bytes)?;
+        if len & 0x80 != 0 {
+            take(&mut bytes, (len & 0x7f) as usize)?;
+        }
+        let R_x = parse_lax_der_integer(&mut bytes)?;
+        let s = parse_lax_der_integer(&mut bytes)?;
+        Some(Self { R_x, s })
+    }
+
+    /// Encodes the signature as it appears in a Bitcoin script: the DER encoding followed by the
+    /// sighash flag.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ecdsa_fun::{fun::Scalar, nonce, Signature, ECDSA};
+    /// let ecdsa = ECDSA::new(nonce::Deterministic::<sha2::Sha256>::default());
+    /// let secret_key = Scalar::random(&mut rand::thread_rng());
+    /// let signature = ecdsa.sign(&secret_key, &[42u8; 32]);
+    /// let bytes = signature.to_bitcoin_script_bytes(0x01); // SIGHASH_ALL
+    /// assert_eq!(bytes.last(), Some(&0x01));
+    /// assert_eq!(
+    ///     Signature::from_bitcoin_script_bytes(&bytes),
+    ///     Some((signature, 0x01))
+    /// );
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn to_bitcoin_script_bytes(&self, sighash_flag: u8) -> Vec<u8> {
+        let mut bytes = self.to_der();
+        bytes.push(sighash_flag);
+        bytes
+    }
+
+    /// Decodes a signature and its sighash flag from a Bitcoin script encoding produced by
+    /// [`to_bitcoin_script_bytes`].
+    ///
+    /// This doesn't check the sighash flag is one Bitcoin defines.
+    ///
+    /// [`to_bitcoin_script_bytes`]: Self::to_bitcoin_script_bytes
+    pub fn from_bitcoin_script_bytes(bytes: &[u8]) -> Option<(Self, u8)> {
+        let (&sighash_flag, der) = bytes.split_last()?;
+        Some((Self::from_der(der)?, sighash_flag))
+    }
+}
+
+/// The minimal big-endian encoding of `int` with a leading zero byte if the high bit is set.
+#[cfg(feature = "alloc")]
+fn der_integer(int: &Scalar<Public>) -> Vec<u8> {
+    let bytes = int.to_bytes();
+    let start = bytes.iter().position(|&byte| byte != 0).unwrap_or(31);
+    let mut encoded = Vec::with_capacity(33);
+    if bytes[start] & 0x80 != 0 {
+        encoded.push(0x00);
+    }
+    encoded.extend_from_slice(&bytes[start..]);
+    encoded
+}
+
+fn parse_der_integer(bytes: &[u8]) -> Option<(Scalar<Public>, &[u8])> {
+    let (&tag, rest) = bytes.split_first()?;
+    let (&len, rest) = rest.split_first()?;
+    let len = len as usize;
+    if tag != 0x02 || len == 0 || len > rest.len() {
+        return None;
+    }
+    let (int, rest) = rest.split_at(len);
+    // negative
+    if int[0] & 0x80 != 0 {
+        return None;
+    }
+    // unnecessary leading zero
+    if len > 1 && int[0] == 0x00 && int[1] & 0x80 == 0 {
+        return None;
+    }
+    let int = if int[0] == 0x00 { &int[1..] } else { int };
+    if int.len() > 32 {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    bytes[32 - int.len()..].copy_from_slice(int);
+    let int = Scalar::from_bytes(bytes)?.non_zero()?;
+    Some((int, rest))
+}
+
+fn take_byte(bytes: &mut &[u8]) -> Option<u8> {
+    Some(take(bytes, 1)?[0])
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if len > bytes.len() {
+        return None;
+    }
+    let (taken, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Some(taken)
+}
+
+fn parse_lax_der_integer(bytes: &mut &[u8]) -> Option<Scalar<Public>> {
+    if take_byte(bytes)? != 0x02 {
+        return None;
+    }
+    let len = take_byte(bytes)?;
+    let len = if len & 0x80 != 0 {
+        let len_bytes = take(bytes, (len & 0x7f) as usize)?;
+        let start = len_bytes.iter().position(|&byte| byte != 0);
+        let len_bytes = start.map_or(&[][..], |start| &len_bytes[start..]);
+        if len_bytes.len() >= core::mem::size_of::<usize>() {
+            return None;
+        }
+        len_bytes
+            .iter()
+            .fold(0usize, |len, &byte| (len << 8) | byte as usize)
+    } else {
+        len as usize
+    };
+    let int = take(bytes, len)?;
+    let start = int.iter().position(|&byte| byte != 0)?;
+    let int = &int[start..];
+    if int.len() > 32 {
+        return None;
+    }
+    let mut int_bytes = [0u8; 32];
+    int_bytes[32 - int.len()..].copy_from_slice(int);
+    Scalar::from_bytes(int_bytes)?.non_zero()
 }
 
 secp256kfun::impl_fromstr_deserialize! {
@@ -39,3 +278,114 @@ secp256kfun::impl_display_debug_serialize! {
         sig.to_bytes()
     }
 }
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn signature_components() {
+        let signature = Signature {
+            R_x: Scalar::random(&mut rand::thread_rng()).public(),
+            s: Scalar::random(&mut rand::thread_rng()).public(),
+        };
+        let (r, s) = signature.to_components();
+        assert_eq!(SignatureComponents::R(&signature), None);
+        assert_eq!(Signature::from_components(r, s), Some(signature.clone()));
+        assert_eq!(Signature::from_components([0u8; 32], s), None);
+        assert_eq!(Signature::from_components(r, Scalar::zero()), None);
+    }
+
+    #[test]
+    fn bitcoin_script_encoding() {
+        let mut s_bytes = [0u8; 32];
+        s_bytes[0] = 0x80;
+        let signature = Signature {
+            R_x: Scalar::<Public>::one(),
+            s: Scalar::from_bytes(s_bytes).unwrap().non_zero().unwrap(),
+        };
+        let bytes = signature.to_bitcoin_script_bytes(0x81);
+        let mut expected = vec![0x30, 0x26, 0x02, 0x01, 0x01, 0x02, 0x21, 0x00];
+        expected.extend_from_slice(&s_bytes);
+        expected.push(0x81);
+        assert_eq!(bytes, expected);
+        assert_eq!(
+            Signature::from_bitcoin_script_bytes(&bytes),
+            Some((signature.clone(), 0x81))
+        );
+
+        let der = &bytes[..bytes.len() - 1];
+        let malformed = [
+            // missing sighash flag
+            der.to_vec(),
+            // trailing byte
+            [der, &[0x00, 0x01]].concat(),
+            // unnecessary leading zero on R_x
+            [&[0x30, 0x27, 0x02, 0x02, 0x00, 0x01], &der[5..]].concat(),
+            // negative R_x
+            [&[0x30, 0x26, 0x02, 0x01, 0x81], &der[5..], &[0x01]].concat(),
+            // zero R_x
+            [&[0x30, 0x26, 0x02, 0x01, 0x00], &der[5..], &[0x01]].concat(),
+            // s without the zero padding
+            [&der[..6], &[0x20], &s_bytes, &[0x01]].concat(),
+            // wrong length
+            [&[0x30, 0x25], &der[2..], &[0x01]].concat(),
+        ];
+        for bytes in malformed {
+            assert_eq!(Signature::from_bitcoin_script_bytes(&bytes), None);
+        }
+        assert_eq!(Signature::from_bitcoin_script_bytes(&[]), None);
+    }
+
+    #[test]
+    fn lax_der() {
+        let signature = Signature {
+            R_x: Scalar::random(&mut rand::thread_rng()).public(),
+            s: Scalar::random(&mut rand::thread_rng()).public(),
+        };
+        let der = signature.to_der();
+        assert_eq!(Signature::from_der_lax(&der), Some(signature.clone()));
+
+        let R_x = signature.R_x.to_bytes();
+        let s = signature.s.to_bytes();
+        let accepted = [
+            // wrong sequence length and trailing bytes
+            [
+                &[0x30, 0x00, 0x02, 0x20],
+                &R_x[..],
+                &[0x02, 0x20],
+                &s,
+                &[0xff, 0xff],
+            ]
+            .concat(),
+            // long form lengths with padding
+            [
+                &[0x30, 0x81, 0x46, 0x02, 0x82, 0x00, 0x22, 0x00, 0x00],
+                &R_x[..],
+                &[0x02, 0x20],
+                &s,
+            ]
+            .concat(),
+        ];
+        for bytes in accepted {
+            assert_eq!(Signature::from_der(&bytes), None);
+            assert_eq!(Signature::from_der_lax(&bytes), Some(signature.clone()));
+        }
+
+        let rejected = [
+            // not a sequence
+            [&[0x31, 0x44, 0x02, 0x20], &R_x[..], &[0x02, 0x20], &s].concat(),
+            // s runs off the end
+            [&[0x30, 0x44, 0x02, 0x20], &R_x[..], &[0x02, 0x21], &s].concat(),
+            // zero R_x
+            [&[0x30, 0x25, 0x02, 0x01, 0x00, 0x02, 0x20], &s[..]].concat(),
+            // R_x too big
+            [&[0x30, 0x45, 0x02, 0x21, 0x01], &R_x[..], &[0x02, 0x20], &s].concat(),
+            // missing long form length bytes
+            vec![0x30, 0x84, 0x00],
+        ];
+        for bytes in rejected {
+            assert_eq!(Signature::from_der_lax(&bytes), None);
+        }
+    }
+}
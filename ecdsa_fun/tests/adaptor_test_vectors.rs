@@ -3,7 +3,7 @@
 static DLC_SPEC_JSON: &str = include_str!("./test_vectors.json");
 use ecdsa_fun::{
     adaptor::{Adaptor, EncryptedSignature, HashTranscript},
-    fun::{serde, Point, Scalar},
+    fun::{marker::Public, serde, Point, Scalar},
     nonce::NoNonces,
     Signature,
 };
@@ -34,7 +34,7 @@ struct Verification {
     encryption_key: Point,
     signature: Signature,
     decryption_key: Scalar,
-    message_hash: Scalar,
+    message_hash: Scalar<Public>,
     error: Option<String>,
 }
 
@@ -59,7 +59,7 @@ fn ecdsa_verify_high_message() {
     let secp = secp256k1::Secp256k1::new();
     let secret_key = Scalar::random(&mut rand::thread_rng());
     let verification_key = ecdsa.verification_key_for(&secret_key);
-    let c_secret_key = SecretKey::from_slice(&secret_key.to_bytes()).unwrap();
+    let c_secret_key = SecretKey::from_slice(secret_key.to_secret_bytes().expose_secret()).unwrap();
     let message =
         hex::decode_array("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF")
             .unwrap();
@@ -76,7 +76,7 @@ fn ecdsa_sign_high_message() {
     let ecdsa = ecdsa_fun::test_instance!();
     let secp = secp256k1::Secp256k1::new();
     let secret_key = Scalar::random(&mut rand::thread_rng());
-    let c_secret_key = SecretKey::from_slice(&secret_key.to_bytes()).unwrap();
+    let c_secret_key = SecretKey::from_slice(secret_key.to_secret_bytes().expose_secret()).unwrap();
     let c_public_key = PublicKey::from_secret_key(&secp, &c_secret_key);
 
     let message =
@@ -89,3 +89,18 @@ fn ecdsa_sign_high_message() {
         .verify_ecdsa(&c_message, &c_siganture, &c_public_key)
         .is_ok());
 }
+
+/// DER encodings match the c-lib's in both directions
+#[test]
+fn ecdsa_der_encoding() {
+    let ecdsa = ecdsa_fun::test_instance!();
+    for _ in 0..TEST_SOUNDNESS {
+        let secret_key = Scalar::random(&mut rand::thread_rng());
+        let signature = ecdsa.sign(&secret_key, &rand_32_bytes());
+        let c_signature = ecdsa::Signature::from_compact(&signature.to_bytes()).unwrap();
+        let der = signature.to_der();
+        assert_eq!(&der[..], &c_signature.serialize_der()[..]);
+        assert_eq!(ecdsa_fun::Signature::from_der(&der), Some(signature));
+        assert_eq!(ecdsa::Signature::from_der(&der), Ok(c_signature));
+    }
+}
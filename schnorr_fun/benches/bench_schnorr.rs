@@ -48,6 +48,10 @@ fn verify_schnorr(c: &mut Criterion) {
             b.iter(|| schnorr.verify(verification_key, message, &sig))
         });
 
+        group.bench_function("fun::schnorr_verify_vartime", |b| {
+            b.iter(|| schnorr.verify_vartime(verification_key, message, &sig))
+        });
+
         {
             let sig = sig.clone().set_secrecy::<Secret>();
             group.bench_function("fun::schnorr_verify_ct", |b| {
@@ -69,5 +73,32 @@ fn verify_schnorr(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, verify_schnorr, sign_schnorr);
+fn verify_batch_schnorr(c: &mut Criterion) {
+    let mut group = c.benchmark_group("schnorr_verify_batch");
+    let message = Message::<Public>::raw(MESSAGE);
+    for n in [16, 256, 1024] {
+        let items = (0..n)
+            .map(|_| {
+                let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+                (keypair.public_key(), schnorr.sign(&keypair, message))
+            })
+            .collect::<Vec<_>>();
+        let refs = items
+            .iter()
+            .map(|(public_key, signature)| (public_key, &message, signature))
+            .collect::<Vec<_>>();
+        group.bench_function(format!("fun::schnorr_verify_batch_{}", n), |b| {
+            b.iter(|| schnorr.verify_batch(&refs))
+        });
+        group.bench_function(format!("fun::schnorr_verify_each_{}", n), |b| {
+            b.iter(|| {
+                refs.iter().all(|(public_key, message, signature)| {
+                    schnorr.verify(public_key, **message, signature)
+                })
+            })
+        });
+    }
+}
+
+criterion_group!(benches, verify_schnorr, verify_batch_schnorr, sign_schnorr);
 criterion_main!(benches);
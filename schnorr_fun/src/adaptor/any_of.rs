@@ -0,0 +1,266 @@
+//! Encrypted signatures that any one of several decryption keys can decrypt.
+//!
+//! An [`AnyOfEncryptedSignature`] is the OR composition of ordinary encrypted signatures: it has
+//! one branch per encryption key, each an [`EncryptedSignature`] on the same message. Whoever
+//! knows the decryption key for *any* of the encryption keys can decrypt their branch into a valid
+//! signature. When that signature is published the signer learns which branch it came from and
+//! recovers that branch's decryption key (and only that one).
+//!
+//! This is useful e.g. for DLCs attested to by a set of oracles with any-of semantics where each
+//! oracle's attestation point is one of the encryption keys.
+//!
+//! **⚠ This is a research oriented API ⚠**: Each branch decrypts to a *different* signature on the
+//! message so the signer must be happy for the message to be signed if any one of the decryption
+//! keys is revealed.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     adaptor::{Adaptor, AnyOf},
+//!     fun::{marker::*, Scalar},
+//!     Message,
+//! };
+//! let schnorr = schnorr_fun::test_instance!();
+//! let signing_keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let decryption_keys = (0..3)
+//!     .map(|_| Scalar::random(&mut rand::thread_rng()))
+//!     .collect::<Vec<_>>();
+//! let encryption_keys = decryption_keys
+//!     .iter()
+//!     .map(|y| schnorr.encryption_key_for(y))
+//!     .collect::<Vec<_>>();
+//! let message = Message::<Public>::plain("text-bitcoin", b"pay the winner");
+//! let encrypted_signature =
+//!     schnorr.encrypted_sign_any_of(&signing_keypair, &encryption_keys, message);
+//! assert!(schnorr.verify_any_of_encrypted_signature(
+//!     &signing_keypair.public_key(),
+//!     &encryption_keys,
+//!     message,
+//!     &encrypted_signature,
+//! ));
+//! // the second oracle attests
+//! let signature = schnorr
+//!     .decrypt_any_of_signature(decryption_keys[1], &encrypted_signature)
+//!     .unwrap();
+//! assert!(schnorr.verify(&signing_keypair.public_key(), message, &signature));
+//! assert_eq!(
+//!     schnorr.recover_any_of_decryption_key(&encrypted_signature, &signature),
+//!     Some((1, decryption_keys[1]))
+//! );
+//! ```
+use super::{Adaptor, EncryptedSign, EncryptedSignature};
+use crate::{
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        g,
+        marker::*,
+        nonce, KeyPair, Point, Scalar, G,
+    },
+    Message, Schnorr, Signature,
+};
+use alloc::vec::Vec;
+
+/// A signature encrypted under several encryption keys such that any of the corresponding
+/// decryption keys can decrypt it.
+///
+/// See the [module documentation](crate::adaptor::any_of).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+pub struct AnyOfEncryptedSignature {
+    /// Each encryption key along with the signature encrypted under it.
+    pub branches: Vec<(Point, EncryptedSignature)>,
+}
+
+/// Extension trait adding [any-of](crate::adaptor::any_of) encrypted signatures to [`Schnorr`].
+pub trait AnyOf {
+    /// Creates a signature on `message` that can be decrypted by the decryption key of any of
+    /// `encryption_keys`.
+    fn encrypted_sign_any_of(
+        &self,
+        signing_keypair: &KeyPair<EvenY>,
+        encryption_keys: &[Point],
+        message: Message<'_, impl Secrecy>,
+    ) -> AnyOfEncryptedSignature;
+
+    /// Verifies that there is exactly one well-formed branch for each of `encryption_keys` (in
+    /// order) so that each of the decryption keys will decrypt it to a signature on `message` under
+    /// `verification_key`.
+    #[must_use]
+    fn verify_any_of_encrypted_signature(
+        &self,
+        verification_key: &Point<EvenY, impl Secrecy>,
+        encryption_keys: &[Point],
+        message: Message<'_, impl Secrecy>,
+        encrypted_signature: &AnyOfEncryptedSignature,
+    ) -> bool;
+
+    /// Decrypts the branch encrypted to the public key of `decryption_key`.
+    ///
+    /// Returns `None` if no branch is encrypted to it.
+    fn decrypt_any_of_signature(
+        &self,
+        decryption_key: Scalar,
+        encrypted_signature: &AnyOfEncryptedSignature,
+    ) -> Option<Signature>;
+
+    /// Recovers the decryption key from a signature decrypted from one of the branches.
+    ///
+    /// Returns the index of the branch along with its decryption key or `None` if `signature`
+    /// wasn't decrypted from any of them.
+    fn recover_any_of_decryption_key(
+        &self,
+        encrypted_signature: &AnyOfEncryptedSignature,
+        signature: &Signature<impl Secrecy>,
+    ) -> Option<(usize, Scalar)>;
+}
+
+impl<CH, NG> AnyOf for Schnorr<CH, NG>
+where
+    CH: Digest<OutputSize = U32> + Clone,
+    NG: nonce::NonceGen,
+{
+    fn encrypted_sign_any_of(
+        &self,
+        signing_keypair: &KeyPair<EvenY>,
+        encryption_keys: &[Point],
+        message: Message<'_, impl Secrecy>,
+    ) -> AnyOfEncryptedSignature {
+        // the encryption key goes into each branch's nonce derivation so the branches never share
+        // a nonce
+        let branches = encryption_keys
+            .iter()
+            .map(|encryption_key| {
+                (
+                    *encryption_key,
+                    self.encrypted_sign(signing_keypair, encryption_key, message),
+                )
+            })
+            .collect();
+        AnyOfEncryptedSignature { branches }
+    }
+
+    fn verify_any_of_encrypted_signature(
+        &self,
+        verification_key: &Point<EvenY, impl Secrecy>,
+        encryption_keys: &[Point],
+        message: Message<'_, impl Secrecy>,
+        encrypted_signature: &AnyOfEncryptedSignature,
+    ) -> bool {
+        encrypted_signature.branches.len() == encryption_keys.len()
+            && encrypted_signature
+                .branches
+                .iter()
+                .zip(encryption_keys)
+                .all(|((branch_key, branch), encryption_key)| {
+                    branch_key == encryption_key
+                        && self.verify_encrypted_signature(
+                            verification_key,
+                            encryption_key,
+                            message,
+                            branch,
+                        )
+                })
+    }
+
+    fn decrypt_any_of_signature(
+        &self,
+        decryption_key: Scalar,
+        encrypted_signature: &AnyOfEncryptedSignature,
+    ) -> Option<Signature> {
+        let encryption_key = g!(decryption_key * G).normalize();
+        let (_, branch) = encrypted_signature
+            .branches
+            .iter()
+            .find(|(branch_key, _)| *branch_key == encryption_key)?;
+        Some(self.decrypt_signature(decryption_key, branch.clone()))
+    }
+
+    fn recover_any_of_decryption_key(
+        &self,
+        encrypted_signature: &AnyOfEncryptedSignature,
+        signature: &Signature<impl Secrecy>,
+    ) -> Option<(usize, Scalar)> {
+        encrypted_signature
+            .branches
+            .iter()
+            .enumerate()
+            .find_map(|(i, (encryption_key, branch))| {
+                self.recover_decryption_key(encryption_key, branch, signature)
+                    .map(|decryption_key| (i, decryption_key))
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn any_of_branches() {
+        let schnorr = crate::test_instance!();
+        let signing_keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let verification_key = signing_keypair.public_key();
+        let decryption_keys = (0..4)
+            .map(|_| Scalar::random(&mut rand::thread_rng()))
+            .collect::<Vec<_>>();
+        let encryption_keys = decryption_keys
+            .iter()
+            .map(|y| schnorr.encryption_key_for(y))
+            .collect::<Vec<_>>();
+        let message = Message::<Public>::plain("test", b"any of");
+        let encrypted_signature =
+            schnorr.encrypted_sign_any_of(&signing_keypair, &encryption_keys, message);
+        assert!(schnorr.verify_any_of_encrypted_signature(
+            &verification_key,
+            &encryption_keys,
+            message,
+            &encrypted_signature
+        ));
+
+        for (i, decryption_key) in decryption_keys.iter().enumerate() {
+            let signature = schnorr
+                .decrypt_any_of_signature(*decryption_key, &encrypted_signature)
+                .unwrap();
+            assert!(schnorr.verify(&verification_key, message, &signature));
+            assert_eq!(
+                schnorr.recover_any_of_decryption_key(&encrypted_signature, &signature),
+                Some((i, *decryption_key))
+            );
+        }
+        let stranger = Scalar::random(&mut rand::thread_rng());
+        assert_eq!(
+            schnorr.decrypt_any_of_signature(stranger, &encrypted_signature),
+            None
+        );
+
+        // a branch that's missing, reordered or encrypted under another key is rejected
+        let mut missing = encrypted_signature.clone();
+        missing.branches.pop();
+        let mut reordered = encrypted_signature.clone();
+        reordered.branches.swap(0, 1);
+        let mut wrong_key = encrypted_signature.clone();
+        wrong_key.branches[2].1 = schnorr.encrypted_sign(
+            &signing_keypair,
+            &schnorr.encryption_key_for(&stranger),
+            message,
+        );
+        for bad in [missing, reordered, wrong_key] {
+            assert!(!schnorr.verify_any_of_encrypted_signature(
+                &verification_key,
+                &encryption_keys,
+                message,
+                &bad
+            ));
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use secp256kfun::{marker::*, Point, Scalar};
+use secp256kfun::{marker::*, FromSliceError, Point, Scalar};
 
 /// A one-time encrypted Schnorr signature or "adaptor signature".
 ///
@@ -41,6 +41,57 @@ impl<OldSec> EncryptedSignature<OldSec> {
     }
 }
 
+impl<S> EncryptedSignature<S> {
+    /// The length of [`to_bytes`](Self::to_bytes).
+    pub const SIZE: usize = 65;
+
+    /// The 65 byte encoding of the encrypted signature.
+    ///
+    /// It is the x-only `R`, then `s_hat` and a byte that is `1` if `needs_negation` and `0`
+    /// otherwise (the same as its `bincode` encoding).
+    ///
+    /// With the `strict_ct` feature of secp256kfun enabled this is only available for `Public`
+    /// encrypted signatures like [`Scalar::to_bytes`].
+    pub fn to_bytes(&self) -> [u8; 65]
+    where
+        S: ExposeBytes,
+    {
+        let mut bytes = [0u8; 65];
+        bytes[..32].copy_from_slice(&self.R.to_xonly_bytes());
+        bytes[32..64].copy_from_slice(&self.s_hat.to_bytes());
+        bytes[64] = self.needs_negation as u8;
+        bytes
+    }
+}
+
+impl EncryptedSignature<Public> {
+    /// Decodes an encrypted signature from the encoding produced by [`to_bytes`].
+    ///
+    /// Returns `None` if `R` or `s_hat` are invalid or the last byte isn't `0` or `1`.
+    ///
+    /// [`to_bytes`]: Self::to_bytes
+    pub fn from_bytes(bytes: [u8; 65]) -> Option<Self> {
+        let needs_negation = match bytes[64] {
+            0 => false,
+            1 => true,
+            _ => return None,
+        };
+        Some(EncryptedSignature {
+            R: Point::from_xonly_bytes(bytes[..32].try_into().expect("32 bytes"))?,
+            s_hat: Scalar::from_slice(&bytes[32..64])?,
+            needs_negation,
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for EncryptedSignature<Public> {
+    type Error = FromSliceError;
+
+    fn try_from(slice: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(FromSliceError::to_array(slice)?).ok_or(FromSliceError::Invalid)
+    }
+}
+
 #[cfg(test)]
 mod test {
     #[cfg(feature = "serde")]
@@ -60,5 +111,37 @@ mod test {
         assert_eq!(serialized.len(), 65);
         let deserialized = bincode::deserialize::<EncryptedSignature>(&serialized).unwrap();
         assert_eq!(encrypted_signature, deserialized);
+        assert_eq!(encrypted_signature.to_bytes()[..], serialized[..]);
+    }
+
+    #[test]
+    fn encrypted_signature_try_from_slice() {
+        use super::*;
+        use crate::{adaptor::*, fun::Scalar, Message};
+        let schnorr = crate::test_instance!();
+        let kp = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let encryption_key = Point::random(&mut rand::thread_rng());
+        let encrypted_signature = schnorr.encrypted_sign(
+            &kp,
+            &encryption_key,
+            Message::<Public>::plain("test", b"foo"),
+        );
+        let mut bytes = encrypted_signature.to_bytes();
+        assert_eq!(
+            EncryptedSignature::try_from(&bytes[..]),
+            Ok(encrypted_signature)
+        );
+        assert_eq!(
+            EncryptedSignature::try_from(&bytes[..64]),
+            Err(FromSliceError::WrongLength {
+                expected: 65,
+                got: 64
+            })
+        );
+        bytes[64] = 2;
+        assert_eq!(
+            EncryptedSignature::try_from(&bytes[..]),
+            Err(FromSliceError::Invalid)
+        );
     }
 }
@@ -0,0 +1,230 @@
+//! Proofs that a published signature was decrypted from an encrypted signature.
+//!
+//! In an escrow or swap protocol the party that publishes the decryption of an encrypted
+//! signature reveals the decryption key to everyone who has seen the encrypted signature. When
+//! there's a dispute about who revealed a secret (and so who moved first) an [`ExtractionProof`]
+//! lets a third-party arbitrator check it for themselves. It is the encrypted signature together
+//! with the `s` of the published signature (the two share `R`) so it is only 97 bytes. Given the
+//! verification key, encryption key and message the arbitrator checks with
+//! [`Schnorr::verify_extraction_proof`] that:
+//!
+//! 1. The encrypted signature is valid so whoever holds the verification key's secret created it.
+//! 2. The published signature is its decryption under the encryption key so whoever published it
+//!    knew the decryption key.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     adaptor::{Adaptor, EncryptedSign},
+//!     fun::{marker::*, Scalar},
+//!     Message,
+//! };
+//! let schnorr = schnorr_fun::test_instance!();
+//! let signing_keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let decryption_key = Scalar::random(&mut rand::thread_rng());
+//! let encryption_key = schnorr.encryption_key_for(&decryption_key);
+//! let message = Message::<Public>::plain("escrow", b"release funds to the seller");
+//! let encrypted_signature = schnorr.encrypted_sign(&signing_keypair, &encryption_key, message);
+//! let signature = schnorr.decrypt_signature(decryption_key, encrypted_signature.clone());
+//!
+//! // the signer sees `signature` published and shows the arbitrator that it came from their
+//! // encrypted signature
+//! let proof = schnorr
+//!     .prove_extraction(&encryption_key, &encrypted_signature, &signature)
+//!     .unwrap();
+//! assert!(schnorr.verify_extraction_proof(
+//!     &signing_keypair.public_key(),
+//!     &encryption_key,
+//!     message,
+//!     &proof
+//! ));
+//! ```
+use super::{Adaptor, EncryptedSignature};
+use crate::{
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        marker::*,
+        FromSliceError, Point, Scalar,
+    },
+    Message, Schnorr, Signature,
+};
+
+/// An encrypted signature together with the signature that was decrypted from it.
+///
+/// See the [module documentation](crate::adaptor::extraction).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct ExtractionProof {
+    encrypted_signature: EncryptedSignature,
+    s: Scalar<Public, Zero>,
+}
+
+impl ExtractionProof {
+    /// The length of the encoding of the proof.
+    pub const SIZE: usize = 97;
+
+    /// Creates the proof without checking it.
+    ///
+    /// Returns `None` if the signature doesn't have the same `R` as the encrypted signature (in
+    /// which case it can't be its decryption). Use [`Schnorr::prove_extraction`] to check the
+    /// proof as well.
+    pub fn new(encrypted_signature: &EncryptedSignature, signature: &Signature) -> Option<Self> {
+        if signature.R != encrypted_signature.R {
+            return None;
+        }
+        Some(Self {
+            encrypted_signature: encrypted_signature.clone(),
+            s: signature.s,
+        })
+    }
+
+    /// The encrypted signature.
+    pub fn encrypted_signature(&self) -> &EncryptedSignature {
+        &self.encrypted_signature
+    }
+
+    /// The signature that was published.
+    pub fn signature(&self) -> Signature {
+        Signature {
+            R: self.encrypted_signature.R,
+            s: self.s,
+        }
+    }
+
+    /// The 97 byte encoding of the proof: the encoding of the encrypted signature followed by `s`.
+    pub fn to_bytes(&self) -> [u8; 97] {
+        let mut bytes = [0u8; 97];
+        bytes[..65].copy_from_slice(&self.encrypted_signature.to_bytes());
+        bytes[65..].copy_from_slice(&self.s.to_bytes());
+        bytes
+    }
+
+    /// Decodes the proof from the encoding produced by [`to_bytes`].
+    ///
+    /// [`to_bytes`]: Self::to_bytes
+    pub fn from_bytes(bytes: [u8; 97]) -> Option<Self> {
+        Some(Self {
+            encrypted_signature: EncryptedSignature::from_bytes(
+                bytes[..65].try_into().expect("65 bytes"),
+            )?,
+            s: Scalar::from_slice(&bytes[65..])?,
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for ExtractionProof {
+    type Error = FromSliceError;
+
+    fn try_from(slice: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(FromSliceError::to_array(slice)?).ok_or(FromSliceError::Invalid)
+    }
+}
+
+impl<CH, NG> Schnorr<CH, NG>
+where
+    CH: Digest<OutputSize = U32> + Clone,
+{
+    /// Creates a proof that `signature` was decrypted from `encrypted_signature` with the
+    /// decryption key of `encryption_key`.
+    ///
+    /// Returns `None` if it wasn't.
+    pub fn prove_extraction(
+        &self,
+        encryption_key: &Point,
+        encrypted_signature: &EncryptedSignature,
+        signature: &Signature,
+    ) -> Option<ExtractionProof> {
+        self.recover_decryption_key(encryption_key, encrypted_signature, signature)?;
+        ExtractionProof::new(encrypted_signature, signature)
+    }
+
+    /// Verifies an [`ExtractionProof`] that a signature on `message` under `verification_key`
+    /// was decrypted from an encrypted signature created by the owner of `verification_key` for
+    /// `encryption_key`.
+    #[must_use]
+    pub fn verify_extraction_proof(
+        &self,
+        verification_key: &Point<EvenY>,
+        encryption_key: &Point,
+        message: Message<'_, impl Secrecy>,
+        proof: &ExtractionProof,
+    ) -> bool {
+        self.verify_encrypted_signature(
+            verification_key,
+            encryption_key,
+            message,
+            &proof.encrypted_signature,
+        ) && self
+            .recover_decryption_key(
+                encryption_key,
+                &proof.encrypted_signature,
+                &proof.signature(),
+            )
+            .is_some()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adaptor::EncryptedSign;
+
+    #[test]
+    fn extraction_proofs() {
+        let schnorr = crate::test_instance!();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let decryption_key = Scalar::random(&mut rand::thread_rng());
+        let encryption_key = schnorr.encryption_key_for(&decryption_key);
+        let message = Message::<Public>::plain("test", b"escrow");
+        let encrypted_signature = schnorr.encrypted_sign(&keypair, &encryption_key, message);
+        let signature = schnorr.decrypt_signature(decryption_key, encrypted_signature.clone());
+        let proof = schnorr
+            .prove_extraction(&encryption_key, &encrypted_signature, &signature)
+            .unwrap();
+        assert_eq!(proof.signature(), signature);
+        assert!(schnorr.verify_extraction_proof(
+            &keypair.public_key(),
+            &encryption_key,
+            message,
+            &proof
+        ));
+        assert_eq!(
+            ExtractionProof::try_from(&proof.to_bytes()[..]),
+            Ok(proof.clone())
+        );
+
+        let other_key = schnorr.encryption_key_for(&Scalar::random(&mut rand::thread_rng()));
+        assert!(!schnorr.verify_extraction_proof(
+            &keypair.public_key(),
+            &other_key,
+            message,
+            &proof
+        ));
+        assert!(!schnorr.verify_extraction_proof(
+            &keypair.public_key(),
+            &encryption_key,
+            Message::<Public>::plain("test", b"other"),
+            &proof
+        ));
+
+        // a signature of the signer's that isn't the decryption proves nothing
+        let unrelated = schnorr.sign(&keypair, message);
+        assert_eq!(
+            schnorr.prove_extraction(&encryption_key, &encrypted_signature, &unrelated),
+            None
+        );
+        let mut forged = proof.clone();
+        forged.s = unrelated.s;
+        assert!(!schnorr.verify_extraction_proof(
+            &keypair.public_key(),
+            &encryption_key,
+            message,
+            &forged
+        ));
+    }
+}
@@ -57,8 +57,19 @@ use crate::{
     },
     Message, Schnorr, Signature,
 };
+use core::borrow::Borrow;
 mod encrypted_signature;
 pub use encrypted_signature::EncryptedSignature;
+#[cfg(all(feature = "unstable", feature = "alloc"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "unstable", feature = "alloc"))))]
+pub mod any_of;
+pub mod extraction;
+pub mod stream;
+#[cfg(feature = "unstable")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable")))]
+pub mod swap;
+#[cfg(all(feature = "unstable", feature = "alloc"))]
+pub use any_of::{AnyOf, AnyOfEncryptedSignature};
 
 /// Extension trait for [`Schnorr`] to add the encrypted signing algorithm.
 ///
@@ -72,7 +83,7 @@ pub trait EncryptedSign {
     fn encrypted_sign(
         &self,
         signing_keypair: &KeyPair<EvenY>,
-        encryption_key: &Point<Normal, impl Secrecy>,
+        encryption_key: &impl Borrow<Point>,
         message: Message<'_, impl Secrecy>,
     ) -> EncryptedSignature;
 }
@@ -85,11 +96,11 @@ where
     fn encrypted_sign(
         &self,
         signing_key: &KeyPair<EvenY>,
-        encryption_key: &Point<Normal, impl Secrecy>,
+        encryption_key: &impl Borrow<Point>,
         message: Message<'_, impl Secrecy>,
     ) -> EncryptedSignature {
         let (x, X) = signing_key.as_tuple();
-        let Y = encryption_key;
+        let Y = encryption_key.borrow();
 
         let mut r = derive_nonce!(
             nonce_gen => self.nonce_gen(),
@@ -140,10 +151,10 @@ pub trait Adaptor {
     ///
     /// [synopsis]: crate::adaptor#synopsis
     #[must_use]
-    fn verify_encrypted_signature(
+    fn verify_encrypted_signature<S: Secrecy>(
         &self,
-        verification_key: &Point<EvenY, impl Secrecy>,
-        encryption_key: &Point<impl PointType, impl Secrecy>,
+        verification_key: &impl Borrow<Point<EvenY, S>>,
+        encryption_key: &impl Borrow<Point>,
         message: Message<'_, impl Secrecy>,
         encrypted_signature: &EncryptedSignature<impl Secrecy>,
     ) -> bool;
@@ -182,7 +193,7 @@ pub trait Adaptor {
     /// [synopsis]: crate::adaptor#synopsis
     fn recover_decryption_key(
         &self,
-        encryption_key: &Point<impl Normalized, impl Secrecy>,
+        encryption_key: &impl Borrow<Point>,
         encrypted_signature: &EncryptedSignature<impl Secrecy>,
         signature: &Signature<impl Secrecy>,
     ) -> Option<Scalar>;
@@ -197,10 +208,10 @@ where
     }
 
     #[must_use]
-    fn verify_encrypted_signature(
+    fn verify_encrypted_signature<S: Secrecy>(
         &self,
-        verification_key: &Point<EvenY, impl Secrecy>,
-        encryption_key: &Point<impl PointType, impl Secrecy>,
+        verification_key: &impl Borrow<Point<EvenY, S>>,
+        encryption_key: &impl Borrow<Point>,
         message: Message<'_, impl Secrecy>,
         encrypted_signature: &EncryptedSignature<impl Secrecy>,
     ) -> bool {
@@ -209,8 +220,8 @@ where
             s_hat,
             needs_negation,
         } = encrypted_signature;
-        let X = verification_key;
-        let Y = encryption_key;
+        let X = verification_key.borrow();
+        let Y = encryption_key.borrow();
 
         //  needs_negation => R_hat = R + Y
         // !needs_negation => R_hat = R - Y
@@ -240,7 +251,7 @@ where
 
     fn recover_decryption_key(
         &self,
-        encryption_key: &Point<impl PointType, impl Secrecy>,
+        encryption_key: &impl Borrow<Point>,
         encrypted_signature: &EncryptedSignature<impl Secrecy>,
         signature: &Signature<impl Secrecy>,
     ) -> Option<Scalar> {
@@ -259,7 +270,7 @@ where
         y.conditional_negate(*needs_negation);
         let implied_encryption_key = g!(y * G);
 
-        if implied_encryption_key == *encryption_key {
+        if implied_encryption_key == *encryption_key.borrow() {
             Some(
                 y.non_zero()
                     .expect("unreachable - encryption_key is NonZero and y*G equals it"),
@@ -324,4 +335,29 @@ mod test {
             .expect("recovery works");
         assert_eq!(rec_decryption_key, decryption_key);
     }
+
+    #[test]
+    fn accepts_role_wrappers() {
+        use secp256kfun::roles::{DecryptionKey, EncryptionKey, VerificationKey};
+        let schnorr = Schnorr::<Sha256, nonce::Deterministic<Sha256>>::default();
+        let signing_keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let verification_key = VerificationKey(signing_keypair.public_key());
+        let decryption_key = DecryptionKey(Scalar::random(&mut rand::thread_rng()));
+        let encryption_key = EncryptionKey(schnorr.encryption_key_for(&decryption_key.0));
+        let message = Message::<Public>::plain("test", b"give 100 coins to Bob".as_ref());
+
+        let encrypted_signature =
+            schnorr.encrypted_sign(&signing_keypair, &encryption_key, message);
+        assert!(schnorr.verify_encrypted_signature(
+            &verification_key,
+            &encryption_key,
+            message,
+            &encrypted_signature,
+        ));
+        let signature = schnorr.decrypt_signature(decryption_key.0, encrypted_signature.clone());
+        assert_eq!(
+            schnorr.recover_decryption_key(&encryption_key, &encrypted_signature, &signature),
+            Some(decryption_key.0)
+        );
+    }
 }
@@ -0,0 +1,216 @@
+//! Creating and verifying very large numbers of encrypted signatures without collecting them.
+//!
+//! A DLC can have hundreds of thousands of outcomes each with its own encrypted signature. Rather
+//! than building a `Vec` of them, [`AdaptorStream`] takes the encryption keys and messages as an
+//! iterator and hands each encrypted signature to a callback as soon as it is made (e.g. to write
+//! it to a file or socket). Verification takes an iterator too so the signatures can be decoded
+//! one at a time with [`decode_concatenated`] from a buffer or a memory mapped file of their
+//! [`to_bytes`] encodings. Neither direction allocates.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     adaptor::stream::{decode_concatenated, AdaptorStream},
+//!     fun::{marker::*, Point, Scalar},
+//!     Message,
+//! };
+//! let schnorr = schnorr_fun::test_instance!();
+//! let signing_keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let outcomes = (0..1000u32)
+//!     .map(|outcome| (Point::random(&mut rand::thread_rng()), outcome.to_be_bytes()))
+//!     .collect::<Vec<_>>();
+//! let items = || {
+//!     outcomes
+//!         .iter()
+//!         .map(|(encryption_key, outcome)| (*encryption_key, Message::<Public>::raw(outcome)))
+//! };
+//!
+//! let mut file = Vec::new();
+//! schnorr
+//!     .encrypted_sign_stream(&signing_keypair, items(), |_, encrypted_signature| {
+//!         std::io::Write::write_all(&mut file, &encrypted_signature.to_bytes())
+//!     })
+//!     .unwrap();
+//!
+//! let encrypted_signatures = decode_concatenated(&file).map(Result::unwrap);
+//! let verified = schnorr.verify_encrypted_signature_stream(
+//!     &signing_keypair.public_key(),
+//!     items()
+//!         .zip(encrypted_signatures)
+//!         .map(|((encryption_key, message), encrypted_signature)| {
+//!             (encryption_key, message, encrypted_signature)
+//!         }),
+//! );
+//! assert_eq!(verified, Ok(1000));
+//! ```
+//!
+//! [`to_bytes`]: EncryptedSignature::to_bytes
+use super::{Adaptor, EncryptedSign, EncryptedSignature};
+use crate::{
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        marker::*,
+        nonce, FromSliceError, KeyPair, Point,
+    },
+    Message, Schnorr,
+};
+use core::fmt;
+
+/// Extension trait adding [streamed](crate::adaptor::stream) encrypted signing and verification to
+/// [`Schnorr`].
+pub trait AdaptorStream {
+    /// Creates an encrypted signature for each encryption key and message in `items` and passes it
+    /// to `sink` along with its position.
+    ///
+    /// Stops at the first error returned by `sink`. Returns how many encrypted signatures were made.
+    fn encrypted_sign_stream<'a, E>(
+        &self,
+        signing_keypair: &KeyPair<EvenY>,
+        items: impl IntoIterator<Item = (Point, Message<'a, Public>)>,
+        sink: impl FnMut(usize, EncryptedSignature) -> Result<(), E>,
+    ) -> Result<usize, E>;
+
+    /// Verifies each encrypted signature in `items` under its encryption key and message.
+    ///
+    /// Stops at the first invalid encrypted signature. Returns how many were verified.
+    fn verify_encrypted_signature_stream<'a>(
+        &self,
+        verification_key: &Point<EvenY, impl Secrecy>,
+        items: impl IntoIterator<Item = (Point, Message<'a, Public>, EncryptedSignature)>,
+    ) -> Result<usize, InvalidEncryptedSignature>;
+}
+
+impl<CH, NG> AdaptorStream for Schnorr<CH, NG>
+where
+    CH: Digest<OutputSize = U32> + Clone,
+    NG: nonce::NonceGen,
+{
+    fn encrypted_sign_stream<'a, E>(
+        &self,
+        signing_keypair: &KeyPair<EvenY>,
+        items: impl IntoIterator<Item = (Point, Message<'a, Public>)>,
+        mut sink: impl FnMut(usize, EncryptedSignature) -> Result<(), E>,
+    ) -> Result<usize, E> {
+        let mut count = 0;
+        for (encryption_key, message) in items {
+            sink(
+                count,
+                self.encrypted_sign(signing_keypair, &encryption_key, message),
+            )?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn verify_encrypted_signature_stream<'a>(
+        &self,
+        verification_key: &Point<EvenY, impl Secrecy>,
+        items: impl IntoIterator<Item = (Point, Message<'a, Public>, EncryptedSignature)>,
+    ) -> Result<usize, InvalidEncryptedSignature> {
+        let mut count = 0;
+        for (encryption_key, message, encrypted_signature) in items {
+            if !self.verify_encrypted_signature(
+                verification_key,
+                &encryption_key,
+                message,
+                &encrypted_signature,
+            ) {
+                return Err(InvalidEncryptedSignature { position: count });
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+/// Decodes the encrypted signatures in `bytes` which are [`to_bytes`] encodings one after another.
+///
+/// Each one is only decoded when the iterator reaches it. If the length of `bytes` isn't a
+/// multiple of 65 the last item is an error.
+///
+/// [`to_bytes`]: EncryptedSignature::to_bytes
+pub fn decode_concatenated(
+    bytes: &[u8],
+) -> impl Iterator<Item = Result<EncryptedSignature, FromSliceError>> + '_ {
+    bytes.chunks(65).map(EncryptedSignature::try_from)
+}
+
+/// An encrypted signature in a stream was invalid. Returned by
+/// [`AdaptorStream::verify_encrypted_signature_stream`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidEncryptedSignature {
+    /// The position of the encrypted signature in the stream.
+    pub position: usize,
+}
+
+impl fmt::Display for InvalidEncryptedSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "encrypted signature {} is invalid", self.position)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidEncryptedSignature {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fun::Scalar;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn stream_roundtrip() {
+        let schnorr = crate::test_instance!();
+        let signing_keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let verification_key = signing_keypair.public_key();
+        let encryption_keys = (0..10)
+            .map(|_| Point::random(&mut rand::thread_rng()))
+            .collect::<Vec<_>>();
+        let message = Message::<Public>::plain("test", b"outcome");
+        let items = || encryption_keys.iter().map(|key| (*key, message));
+
+        let mut bytes = vec![];
+        let count = schnorr.encrypted_sign_stream(&signing_keypair, items(), |i, sig| {
+            assert_eq!(bytes.len(), i * 65);
+            bytes.extend_from_slice(&sig.to_bytes());
+            Ok::<_, ()>(())
+        });
+        assert_eq!(count, Ok(10));
+
+        // the sink can stop the stream
+        assert_eq!(
+            schnorr.encrypted_sign_stream(&signing_keypair, items(), |i, _| {
+                if i == 3 {
+                    Err(i)
+                } else {
+                    Ok(())
+                }
+            }),
+            Err(3)
+        );
+
+        let verify = |bytes: &[u8]| {
+            schnorr.verify_encrypted_signature_stream(
+                &verification_key,
+                items()
+                    .zip(decode_concatenated(bytes))
+                    .map(|((key, message), sig)| (key, message, sig.unwrap())),
+            )
+        };
+        assert_eq!(verify(&bytes), Ok(10));
+
+        // swap two encrypted signatures
+        let mut swapped = bytes.clone();
+        swapped[4 * 65..5 * 65].copy_from_slice(&bytes[5 * 65..6 * 65]);
+        assert_eq!(
+            verify(&swapped),
+            Err(InvalidEncryptedSignature { position: 4 })
+        );
+
+        let truncated = decode_concatenated(&bytes[..bytes.len() - 1]).collect::<Vec<_>>();
+        assert_eq!(truncated.len(), 10);
+        assert!(truncated[..9].iter().all(Result::is_ok));
+        assert!(truncated[9].is_err());
+    }
+}
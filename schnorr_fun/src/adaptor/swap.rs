@@ -0,0 +1,557 @@
+//! Scriptless atomic swaps built from adaptor signatures.
+//!
+//! This implements the roles of the canonical BTC–BTC scriptless swap. Alice and Bob each lock
+//! coins in an output that needs signatures from both of them (e.g. a 2-of-2 taproot script) and
+//! has a timelocked refund transaction back to its funder. Alice holds a secret decryption key
+//! `y`. Taking Bob's coins reveals `y` to Bob which is exactly what he needs to take Alice's:
+//!
+//! ```text
+//! Alice                                                Bob
+//!   | --- Offer { Alice's key, Y } ---------------------> |
+//!   | <-- Accept { Bob's key, sig on alice_refund } ----- |
+//!   |  (funds her output)                                 |
+//!   | --- Commit { sig on bob_refund,                     |
+//!   |              sig on bob_redeem encrypted to Y } --> |
+//!   |                                                     |  (funds his output)
+//!   | <-- Lock { sig on alice_redeem encrypted to Y } --- |
+//!   |  (decrypts with y and publishes alice_redeem)       |
+//!   |                                                     |  (recovers y from alice_redeem,
+//!   |                                                     |   publishes bob_redeem)
+//! ```
+//!
+//! Alice's refund must have a longer timelock than Bob's. Alice redeems first so she has until
+//! Bob's refund timelock to take his coins and once she has, Bob needs time to take hers. If her
+//! refund unlocked first she could refund her own output and still take Bob's before his refund
+//! unlocked. Bob should be sure that the gap between the two timelocks is long enough for him to
+//! see `alice_redeem` and confirm `bob_redeem`. Building the transactions and their timelocks is
+//! up to the caller: everything here works with the agreed [`SwapSighashes`].
+//!
+//! Each role is a state machine: methods have to be called in the order shown above and return
+//! [`SwapError::OutOfOrder`] otherwise. The messages can be serialized with `serde` or `bincode`.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     adaptor::swap::{Alice, Bob, SwapSighashes},
+//!     fun::Scalar,
+//! };
+//! let schnorr = schnorr_fun::test_instance!();
+//! let sighashes = SwapSighashes {
+//!     alice_refund: [1u8; 32],
+//!     bob_refund: [2u8; 32],
+//!     alice_redeem: [3u8; 32],
+//!     bob_redeem: [4u8; 32],
+//! };
+//! let alice_keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let bob_keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let y = Scalar::random(&mut rand::thread_rng());
+//! let (mut alice, offer) = Alice::new(alice_keypair, y, sighashes);
+//! let (mut bob, accept) = Bob::new(&schnorr, bob_keypair, sighashes, offer);
+//! let commit = alice.receive_accept(&schnorr, accept).unwrap();
+//! let lock = bob.receive_commit(&schnorr, commit).unwrap();
+//! let alice_redeem = alice.receive_lock(&schnorr, lock).unwrap();
+//! // Alice publishes her redeem transaction and Bob sees his signature in it
+//! let bob_redeem = bob.redeem(&schnorr, &alice_redeem.bob).unwrap();
+//! ```
+use super::{Adaptor, EncryptedSign, EncryptedSignature};
+use crate::{
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        g,
+        marker::*,
+        nonce::NonceGen,
+        KeyPair, Point, Scalar, G,
+    },
+    Message, Schnorr, Signature,
+};
+use core::fmt;
+
+/// The sighashes of the four transactions in a swap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct SwapSighashes {
+    /// Spends Alice's output back to Alice after the (longer) refund timelock.
+    pub alice_refund: [u8; 32],
+    /// Spends Bob's output back to Bob after the (shorter) refund timelock.
+    pub bob_refund: [u8; 32],
+    /// Spends Bob's output to Alice.
+    pub alice_redeem: [u8; 32],
+    /// Spends Alice's output to Bob.
+    pub bob_redeem: [u8; 32],
+}
+
+/// Both signatures needed to spend one of the swap outputs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpendSignatures {
+    /// Alice's signature.
+    pub alice: Signature,
+    /// Bob's signature.
+    pub bob: Signature,
+}
+
+/// Alice's opening message.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct Offer {
+    /// Alice's public key.
+    pub public_key: Point<EvenY>,
+    /// The key Alice's redeem signature will be encrypted under.
+    pub encryption_key: Point,
+}
+
+/// Bob's reply to an [`Offer`], making it safe for Alice to fund her output.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct Accept {
+    /// Bob's public key.
+    pub public_key: Point<EvenY>,
+    /// Bob's signature on Alice's refund.
+    pub alice_refund: Signature,
+}
+
+/// Alice's message after funding, making it safe for Bob to fund his output.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct Commit {
+    /// Alice's signature on Bob's refund.
+    pub bob_refund: Signature,
+    /// Alice's signature on Bob's redeem encrypted to the [`Offer`]'s encryption key.
+    pub bob_redeem: EncryptedSignature,
+}
+
+/// Bob's message after funding, letting Alice redeem.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct Lock {
+    /// Bob's signature on Alice's redeem encrypted to the [`Offer`]'s encryption key.
+    pub alice_redeem: EncryptedSignature,
+}
+
+/// The party who knows the secret and redeems first.
+///
+/// Its `Debug` output leaves out the secret key and the decryption key.
+#[derive(Clone)]
+pub struct Alice {
+    keypair: KeyPair<EvenY>,
+    decryption_key: Scalar,
+    sighashes: SwapSighashes,
+    bob: Option<(Point<EvenY>, Signature)>,
+    redeemed: bool,
+}
+
+impl fmt::Debug for Alice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Alice")
+            .field("public_key", &self.keypair.public_key())
+            .field("sighashes", &self.sighashes)
+            .field("bob", &self.bob)
+            .field("redeemed", &self.redeemed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Alice {
+    /// Starts a swap as Alice where revealing `decryption_key` is what gives Bob his coins.
+    ///
+    /// Returns the [`Offer`] to send to Bob.
+    pub fn new(
+        keypair: KeyPair<EvenY>,
+        decryption_key: Scalar,
+        sighashes: SwapSighashes,
+    ) -> (Self, Offer) {
+        let offer = Offer {
+            public_key: keypair.public_key(),
+            encryption_key: g!(decryption_key * G).normalize(),
+        };
+        (
+            Self {
+                keypair,
+                decryption_key,
+                sighashes,
+                bob: None,
+                redeemed: false,
+            },
+            offer,
+        )
+    }
+
+    /// Checks Bob's signature on the refund and returns the [`Commit`] to send once Alice's output
+    /// is funded.
+    pub fn receive_accept<CH, NG>(
+        &mut self,
+        schnorr: &Schnorr<CH, NG>,
+        accept: Accept,
+    ) -> Result<Commit, SwapError>
+    where
+        CH: Digest<OutputSize = U32> + Clone,
+        NG: NonceGen,
+    {
+        if self.bob.is_some() {
+            return Err(SwapError::OutOfOrder);
+        }
+        if !schnorr.verify(
+            &accept.public_key,
+            Message::<Public>::raw(&self.sighashes.alice_refund),
+            &accept.alice_refund,
+        ) {
+            return Err(SwapError::InvalidSignature);
+        }
+        let commit = Commit {
+            bob_refund: schnorr.sign(
+                &self.keypair,
+                Message::<Public>::raw(&self.sighashes.bob_refund),
+            ),
+            bob_redeem: schnorr.encrypted_sign(
+                &self.keypair,
+                &g!(self.decryption_key * G).normalize(),
+                Message::<Public>::raw(&self.sighashes.bob_redeem),
+            ),
+        };
+        self.bob = Some((accept.public_key, accept.alice_refund));
+        Ok(commit)
+    }
+
+    /// Checks and decrypts Bob's encrypted signature giving Alice both signatures for her redeem.
+    ///
+    /// Publishing them reveals the decryption key to Bob.
+    pub fn receive_lock<CH, NG>(
+        &mut self,
+        schnorr: &Schnorr<CH, NG>,
+        lock: Lock,
+    ) -> Result<SpendSignatures, SwapError>
+    where
+        CH: Digest<OutputSize = U32> + Clone,
+        NG: NonceGen,
+    {
+        let (bob_key, _) = self.bob.as_ref().ok_or(SwapError::OutOfOrder)?;
+        let message = Message::<Public>::raw(&self.sighashes.alice_redeem);
+        if !schnorr.verify_encrypted_signature(
+            bob_key,
+            &g!(self.decryption_key * G).normalize(),
+            message,
+            &lock.alice_redeem,
+        ) {
+            return Err(SwapError::InvalidSignature);
+        }
+        self.redeemed = true;
+        Ok(SpendSignatures {
+            alice: schnorr.sign(&self.keypair, message),
+            bob: schnorr.decrypt_signature(self.decryption_key, lock.alice_redeem),
+        })
+    }
+
+    /// Both signatures for Alice's refund once Bob has accepted.
+    ///
+    /// Returns `None` before [`receive_accept`](Self::receive_accept) or after Alice has redeemed
+    /// (since refunding then would lose both coins).
+    pub fn refund<CH, NG>(&self, schnorr: &Schnorr<CH, NG>) -> Option<SpendSignatures>
+    where
+        CH: Digest<OutputSize = U32> + Clone,
+        NG: NonceGen,
+    {
+        let (_, bob_signature) = self.bob.as_ref().filter(|_| !self.redeemed)?;
+        Some(SpendSignatures {
+            alice: schnorr.sign(
+                &self.keypair,
+                Message::<Public>::raw(&self.sighashes.alice_refund),
+            ),
+            bob: bob_signature.clone(),
+        })
+    }
+}
+
+/// The party who learns the secret from Alice's redeem.
+///
+/// Its `Debug` output leaves out the secret key.
+#[derive(Clone)]
+pub struct Bob {
+    keypair: KeyPair<EvenY>,
+    sighashes: SwapSighashes,
+    offer: Offer,
+    alice: Option<(Commit, Lock)>,
+}
+
+impl fmt::Debug for Bob {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Bob")
+            .field("public_key", &self.keypair.public_key())
+            .field("sighashes", &self.sighashes)
+            .field("offer", &self.offer)
+            .field("alice", &self.alice)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Bob {
+    /// Accepts Alice's [`Offer`] returning the [`Accept`] to send back.
+    pub fn new<CH, NG>(
+        schnorr: &Schnorr<CH, NG>,
+        keypair: KeyPair<EvenY>,
+        sighashes: SwapSighashes,
+        offer: Offer,
+    ) -> (Self, Accept)
+    where
+        CH: Digest<OutputSize = U32> + Clone,
+        NG: NonceGen,
+    {
+        let accept = Accept {
+            public_key: keypair.public_key(),
+            alice_refund: schnorr.sign(&keypair, Message::<Public>::raw(&sighashes.alice_refund)),
+        };
+        (
+            Self {
+                keypair,
+                sighashes,
+                offer,
+                alice: None,
+            },
+            accept,
+        )
+    }
+
+    /// Checks Alice's refund signature and encrypted redeem signature and returns the [`Lock`]
+    /// to send once Bob's output is funded.
+    pub fn receive_commit<CH, NG>(
+        &mut self,
+        schnorr: &Schnorr<CH, NG>,
+        commit: Commit,
+    ) -> Result<Lock, SwapError>
+    where
+        CH: Digest<OutputSize = U32> + Clone,
+        NG: NonceGen,
+    {
+        if self.alice.is_some() {
+            return Err(SwapError::OutOfOrder);
+        }
+        let valid_refund = schnorr.verify(
+            &self.offer.public_key,
+            Message::<Public>::raw(&self.sighashes.bob_refund),
+            &commit.bob_refund,
+        );
+        let valid_redeem = schnorr.verify_encrypted_signature(
+            &self.offer.public_key,
+            &self.offer.encryption_key,
+            Message::<Public>::raw(&self.sighashes.bob_redeem),
+            &commit.bob_redeem,
+        );
+        if !(valid_refund && valid_redeem) {
+            return Err(SwapError::InvalidSignature);
+        }
+        let lock = Lock {
+            alice_redeem: schnorr.encrypted_sign(
+                &self.keypair,
+                &self.offer.encryption_key,
+                Message::<Public>::raw(&self.sighashes.alice_redeem),
+            ),
+        };
+        self.alice = Some((commit, lock.clone()));
+        Ok(lock)
+    }
+
+    /// Recovers the decryption key from Bob's signature in Alice's published redeem and uses it to
+    /// get both signatures for Bob's redeem.
+    pub fn redeem<CH, NG>(
+        &self,
+        schnorr: &Schnorr<CH, NG>,
+        published: &Signature,
+    ) -> Result<SpendSignatures, SwapError>
+    where
+        CH: Digest<OutputSize = U32> + Clone,
+        NG: NonceGen,
+    {
+        let (commit, lock) = self.alice.as_ref().ok_or(SwapError::OutOfOrder)?;
+        let decryption_key = schnorr
+            .recover_decryption_key(&self.offer.encryption_key, &lock.alice_redeem, published)
+            .ok_or(SwapError::NotDecrypted)?;
+        Ok(SpendSignatures {
+            alice: schnorr.decrypt_signature(decryption_key, commit.bob_redeem.clone()),
+            bob: schnorr.sign(
+                &self.keypair,
+                Message::<Public>::raw(&self.sighashes.bob_redeem),
+            ),
+        })
+    }
+
+    /// Both signatures for Bob's refund once Alice has committed.
+    ///
+    /// Returns `None` before [`receive_commit`](Self::receive_commit).
+    pub fn refund<CH, NG>(&self, schnorr: &Schnorr<CH, NG>) -> Option<SpendSignatures>
+    where
+        CH: Digest<OutputSize = U32> + Clone,
+        NG: NonceGen,
+    {
+        let (commit, _) = self.alice.as_ref()?;
+        Some(SpendSignatures {
+            alice: commit.bob_refund.clone(),
+            bob: schnorr.sign(
+                &self.keypair,
+                Message::<Public>::raw(&self.sighashes.bob_refund),
+            ),
+        })
+    }
+}
+
+/// Error returned by the swap state machines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapError {
+    /// The message was received at the wrong point in the protocol.
+    OutOfOrder,
+    /// A signature or encrypted signature from the other party was invalid.
+    InvalidSignature,
+    /// The published signature wasn't decrypted from our encrypted signature.
+    NotDecrypted,
+}
+
+impl fmt::Display for SwapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SwapError::OutOfOrder => write!(f, "swap message received out of order"),
+            SwapError::InvalidSignature => {
+                write!(f, "the other party's signature for the swap was invalid")
+            }
+            SwapError::NotDecrypted => write!(
+                f,
+                "the published signature was not decrypted from our encrypted signature"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SwapError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn swap_happy_path_and_refunds() {
+        let schnorr = crate::test_instance!();
+        let sighashes = SwapSighashes {
+            alice_refund: [1u8; 32],
+            bob_refund: [2u8; 32],
+            alice_redeem: [3u8; 32],
+            bob_redeem: [4u8; 32],
+        };
+        let alice_keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let bob_keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let verify = |keypair: &KeyPair<EvenY>, sighash: &[u8; 32], signature: &Signature| {
+            schnorr.verify(
+                &keypair.public_key(),
+                Message::<Public>::raw(sighash),
+                signature,
+            )
+        };
+        let (mut alice, offer) = Alice::new(
+            alice_keypair.clone(),
+            Scalar::random(&mut rand::thread_rng()),
+            sighashes,
+        );
+        assert!(alice.refund(&schnorr).is_none());
+        let (mut bob, accept) = Bob::new(&schnorr, bob_keypair.clone(), sighashes, offer);
+        assert!(bob.refund(&schnorr).is_none());
+
+        let mut bad_accept = accept.clone();
+        bad_accept.alice_refund = schnorr.sign(&bob_keypair, Message::<Public>::raw(&[0u8; 32]));
+        assert_eq!(
+            alice.receive_accept(&schnorr, bad_accept),
+            Err(SwapError::InvalidSignature)
+        );
+        let commit = alice.receive_accept(&schnorr, accept.clone()).unwrap();
+        assert_eq!(
+            alice.receive_accept(&schnorr, accept),
+            Err(SwapError::OutOfOrder)
+        );
+        let alice_refund = alice.refund(&schnorr).unwrap();
+        assert!(verify(
+            &alice_keypair,
+            &sighashes.alice_refund,
+            &alice_refund.alice
+        ));
+        assert!(verify(
+            &bob_keypair,
+            &sighashes.alice_refund,
+            &alice_refund.bob
+        ));
+
+        let lock = bob.receive_commit(&schnorr, commit).unwrap();
+        let bob_refund = bob.refund(&schnorr).unwrap();
+        assert!(verify(
+            &alice_keypair,
+            &sighashes.bob_refund,
+            &bob_refund.alice
+        ));
+        assert!(verify(&bob_keypair, &sighashes.bob_refund, &bob_refund.bob));
+        assert_eq!(
+            bob.redeem(&schnorr, &bob_refund.bob),
+            Err(SwapError::NotDecrypted)
+        );
+
+        let alice_redeem = alice.receive_lock(&schnorr, lock).unwrap();
+        assert!(alice.refund(&schnorr).is_none());
+        assert!(verify(
+            &alice_keypair,
+            &sighashes.alice_redeem,
+            &alice_redeem.alice
+        ));
+        assert!(verify(
+            &bob_keypair,
+            &sighashes.alice_redeem,
+            &alice_redeem.bob
+        ));
+
+        let bob_redeem = bob.redeem(&schnorr, &alice_redeem.bob).unwrap();
+        assert!(verify(
+            &alice_keypair,
+            &sighashes.bob_redeem,
+            &bob_redeem.alice
+        ));
+        assert!(verify(&bob_keypair, &sighashes.bob_redeem, &bob_redeem.bob));
+    }
+}
@@ -0,0 +1,310 @@
+//! Anti-exfil signing where the host contributes randomness to the signer's nonce.
+//!
+//! A hardware signer that chooses its own nonces can leak its secret key a few bits at a time
+//! through the nonces of the signatures it produces and nobody looking at the signatures can
+//! tell. This is the host commitment protocol from libsecp256k1-zkp's anti-exfil module (also
+//! known as sign-to-contract) which lets the host make sure the nonce has randomness from it in it:
+//!
+//! 1. The host picks 32 fresh random bytes `ρ` and sends the signer [`HostCommitment::new`]`(ρ)`.
+//! 2. The signer derives a nonce `r₀` from its key, the message and the host commitment and sends
+//!    back `R₀ = r₀ * G` with [`Schnorr::commit_nonce`].
+//! 3. The host reveals `ρ` and the signer signs with [`Schnorr::sign_with_host_randomness`] using
+//!    the nonce `r₀ + t` where `t = H(R₀ || ρ)`.
+//! 4. The host checks the signature is valid and that its nonce is `R₀ + t * G` with
+//!    [`Schnorr::verify_host_commitment`].
+//!
+//! The signer commits to its nonce before it learns `ρ` so it can't choose a nonce that encodes
+//! anything and the host commits to `ρ` before it learns `R₀` so it can't bias the nonce either.
+//!
+//! The signer rederives `r₀` from the host commitment in the last step rather than storing it so
+//! only instances with a [`Deterministic`] nonce generator can do this. The host's randomness does
+//! the job that the randomness in a synthetic nonce would.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     anti_exfil::HostCommitment,
+//!     fun::{marker::*, Scalar},
+//!     Message,
+//! };
+//! use sha2::Sha256;
+//! let schnorr = schnorr_fun::test_instance!();
+//! let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let message = Message::<Public>::plain("my-wallet", b"spend all the coins");
+//!
+//! // host
+//! let mut host_randomness = [0u8; 32];
+//! rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut host_randomness);
+//! let host_commitment = HostCommitment::new::<Sha256>(&host_randomness);
+//! // signer
+//! let signer_commitment = schnorr.commit_nonce(&keypair, message, &host_commitment);
+//! // host sends host_randomness, signer
+//! let signature = schnorr.sign_with_host_randomness(&keypair, message, &host_randomness);
+//! // host
+//! assert!(schnorr.verify_host_commitment(
+//!     &keypair.public_key(),
+//!     message,
+//!     &signature,
+//!     &signer_commitment,
+//!     &host_randomness
+//! ));
+//! ```
+//!
+//! [`Deterministic`]: crate::nonce::Deterministic
+use crate::{
+    fun::{
+        derive_nonce,
+        digest::{generic_array::typenum::U32, Digest},
+        g,
+        hash::{HashAdd, HashInto, Tag},
+        marker::*,
+        nonce::Deterministic,
+        s, KeyPair, Point, Scalar, G,
+    },
+    Message, Schnorr, Signature,
+};
+
+/// The host's commitment to the randomness it will contribute to a signature's nonce.
+///
+/// Create it with [`new`](Self::new) and send it to the signer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct HostCommitment([u8; 32]);
+
+impl HostCommitment {
+    /// Commits to `host_randomness` which must be freshly chosen for each signature.
+    pub fn new<H: Digest<OutputSize = U32> + Tag + Default>(host_randomness: &[u8; 32]) -> Self {
+        let hash = H::default()
+            .tag(b"schnorr_fun/anti_exfil/data")
+            .add(&host_randomness[..]);
+        Self(hash.finalize().into())
+    }
+
+    /// The commitment as bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+
+    /// The commitment from the bytes produced by [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl HashInto for HostCommitment {
+    fn hash_into(self, hash: &mut impl Digest) {
+        hash.update(self.0)
+    }
+}
+
+/// The signer's commitment to the nonce it will tweak with the host's randomness.
+///
+/// Created with [`Schnorr::commit_nonce`] and checked by the host with
+/// [`Schnorr::verify_host_commitment`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct SignerCommitment {
+    R: Point,
+}
+
+impl SignerCommitment {
+    /// The nonce `R₀` before it is tweaked with the host's randomness.
+    pub fn R(&self) -> Point {
+        self.R
+    }
+
+    /// The commitment as the 33-byte compressed encoding of `R₀`.
+    pub fn to_bytes(&self) -> [u8; 33] {
+        self.R.to_bytes()
+    }
+
+    /// Decodes a commitment encoded with [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: [u8; 33]) -> Option<Self> {
+        Some(Self {
+            R: Point::from_bytes(bytes)?,
+        })
+    }
+
+    /// The nonce a signature must use once the host has contributed `host_randomness`.
+    fn tweaked<H: Digest<OutputSize = U32> + Tag + Default>(
+        &self,
+        host_randomness: &[u8; 32],
+    ) -> Point<Normal, Public, Zero> {
+        let t = tweak::<H>(&self.R, host_randomness);
+        g!(self.R + t * G).normalize()
+    }
+}
+
+fn tweak<H: Digest<OutputSize = U32> + Tag + Default>(
+    R: &Point,
+    host_randomness: &[u8; 32],
+) -> Scalar<Public> {
+    Scalar::from_hash(
+        H::default()
+            .tag(b"schnorr_fun/anti_exfil/point")
+            .add(*R)
+            .add(&host_randomness[..]),
+    )
+    .public()
+}
+
+impl<CH, NH> Schnorr<CH, Deterministic<NH>>
+where
+    CH: Digest<OutputSize = U32> + Tag + Default + Clone,
+    NH: Digest<OutputSize = U32> + Tag + Clone,
+{
+    fn anti_exfil_nonce(
+        &self,
+        keypair: &KeyPair<EvenY>,
+        message: Message<'_, impl Secrecy>,
+        host_commitment: &HostCommitment,
+    ) -> Scalar {
+        let (x, X) = keypair.as_tuple();
+        // a differently tagged nonce generator so no message passed to `sign` can ever give the
+        // same nonce. Signing with `r₀` and `r₀ + t` would reveal the secret key.
+        derive_nonce!(
+            nonce_gen => self.nonce_gen().clone().tag(b"anti_exfil"),
+            secret => x,
+            public => [X, *host_commitment, message]
+        )
+    }
+
+    /// Commits to the nonce for a signature on `message` before the host reveals its randomness.
+    ///
+    /// Send the result to the host. See the [module documentation](crate::anti_exfil).
+    pub fn commit_nonce(
+        &self,
+        keypair: &KeyPair<EvenY>,
+        message: Message<'_, impl Secrecy>,
+        host_commitment: &HostCommitment,
+    ) -> SignerCommitment {
+        let r = self.anti_exfil_nonce(keypair, message, host_commitment);
+        SignerCommitment {
+            R: g!(r * G).normalize(),
+        }
+    }
+
+    /// Signs `message` with the nonce committed to by [`commit_nonce`] tweaked by the host's
+    /// randomness.
+    ///
+    /// `host_randomness` must open the host commitment passed to [`commit_nonce`]. If it doesn't
+    /// this signs with an unrelated nonce and the host's [`verify_host_commitment`] will fail.
+    ///
+    /// [`commit_nonce`]: Self::commit_nonce
+    /// [`verify_host_commitment`]: Self::verify_host_commitment
+    pub fn sign_with_host_randomness(
+        &self,
+        keypair: &KeyPair<EvenY>,
+        message: Message<'_, impl Secrecy>,
+        host_randomness: &[u8; 32],
+    ) -> Signature {
+        let host_commitment = HostCommitment::new::<CH>(host_randomness);
+        let r = self.anti_exfil_nonce(keypair, message, &host_commitment);
+        let R = g!(r * G).normalize();
+        let t = tweak::<CH>(&R, host_randomness);
+        let r = s!(r + t).non_zero().expect("computationally unreachable");
+        self.sign_with_nonce(keypair, r, message)
+    }
+}
+
+impl<CH, NG> Schnorr<CH, NG>
+where
+    CH: Digest<OutputSize = U32> + Tag + Default + Clone,
+{
+    /// Checks that `signature` is valid and that its nonce is the one the signer committed to in
+    /// `signer_commitment` tweaked by `host_randomness`.
+    ///
+    /// This is what the host does at the end of the protocol. See the [module
+    /// documentation](crate::anti_exfil).
+    #[must_use]
+    pub fn verify_host_commitment(
+        &self,
+        public_key: &Point<EvenY>,
+        message: Message<'_, Public>,
+        signature: &Signature,
+        signer_commitment: &SignerCommitment,
+        host_randomness: &[u8; 32],
+    ) -> bool {
+        let R = signer_commitment.tweaked::<CH>(host_randomness);
+        let nonce_matches = R.non_zero().map_or(false, |R| {
+            R.to_xonly_bytes() == signature.R.to_xonly_bytes()
+        });
+        nonce_matches && self.verify(public_key, message, signature)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+
+    #[test]
+    fn anti_exfil_sign_and_verify() {
+        let schnorr = crate::test_instance!();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let public_key = keypair.public_key();
+        let message = Message::<Public>::plain("test", b"anti-exfil");
+        let host_randomness = [7u8; 32];
+        let host_commitment = HostCommitment::new::<Sha256>(&host_randomness);
+
+        let signer_commitment = schnorr.commit_nonce(&keypair, message, &host_commitment);
+        assert_eq!(
+            SignerCommitment::from_bytes(signer_commitment.to_bytes()),
+            Some(signer_commitment)
+        );
+        let signature = schnorr.sign_with_host_randomness(&keypair, message, &host_randomness);
+        assert!(schnorr.verify(&public_key, message, &signature));
+        assert!(schnorr.verify_host_commitment(
+            &public_key,
+            message,
+            &signature,
+            &signer_commitment,
+            &host_randomness
+        ));
+
+        // the nonce really was tweaked
+        assert_ne!(
+            signer_commitment.R().to_xonly_bytes(),
+            signature.R.to_xonly_bytes()
+        );
+        // different randomness from the host gives a different nonce
+        assert!(!schnorr.verify_host_commitment(
+            &public_key,
+            message,
+            &signature,
+            &signer_commitment,
+            &[8u8; 32]
+        ));
+        // a signer that ignores the host's randomness is caught
+        let plain_signature = schnorr.sign(&keypair, message);
+        assert!(!schnorr.verify_host_commitment(
+            &public_key,
+            message,
+            &plain_signature,
+            &signer_commitment,
+            &host_randomness
+        ));
+        // as is one that signs with a nonce it didn't commit to
+        let other_commitment = schnorr.commit_nonce(
+            &keypair,
+            message,
+            &HostCommitment::new::<Sha256>(&[8u8; 32]),
+        );
+        assert!(!schnorr.verify_host_commitment(
+            &public_key,
+            message,
+            &signature,
+            &other_commitment,
+            &host_randomness
+        ));
+    }
+}
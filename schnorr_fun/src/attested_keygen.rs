@@ -0,0 +1,314 @@
+//! Key generation that produces evidence the key was freshly generated.
+//!
+//! An auditor taking delivery of a signing device wants to know that its key was generated on the
+//! device during setup and wasn't imported (e.g. a pre-existing or backdoored key). With
+//! [`AttestedKeyGen`] the key is generated in a commit-and-reveal exchange with a second party
+//! (the auditor or a randomness beacon):
+//!
+//! 1. The device picks a random secret `x_d` and sends a [`KeyGenCommitment`] to `X_d = x_d * G`
+//!    bound to its 32-byte device id.
+//! 2. The second party replies with 32 bytes of external entropy `e`.
+//! 3. The device's key is `x = x_d + t` where `t = H(commitment || X_d || e)`. It outputs a
+//!    [`KeyGenAttestation`] containing the transcript and a signature on it under the new key.
+//!
+//! Anyone can check the attestation with [`AttestedKeyGen::verify`]. Since `e` was chosen after the
+//! device committed to `X_d` the device couldn't have steered the result to a key it had before.
+//! The signature shows the device knows the secret key of the result.
+//!
+//! The external entropy is optional. Without it the attestation only shows the key is
+//! consistent with the committed randomness which says nothing about freshness, so auditors
+//! should insist on [`KeyGenAttestation::external_entropy`] being their own.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::attested_keygen::AttestedKeyGen;
+//! let keygen = AttestedKeyGen::<sha2::Sha256, _>::new(schnorr_fun::test_instance!());
+//! let device_id = [7u8; 32];
+//! // on the device
+//! let (pending, commitment) = keygen.commit(device_id, &mut rand::thread_rng());
+//! // the auditor sees the commitment and sends back entropy
+//! let auditor_entropy = [42u8; 32];
+//! let (keypair, attestation) = keygen.finish(pending, Some(auditor_entropy));
+//! // the auditor checks the device used their entropy and the commitment it sent first
+//! assert_eq!(attestation.commitment, commitment);
+//! assert_eq!(attestation.external_entropy, Some(auditor_entropy));
+//! assert!(keygen.verify(&attestation));
+//! assert_eq!(attestation.public_key, keypair.public_key());
+//! ```
+use crate::{Message, Schnorr, Signature};
+use core::fmt;
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    g,
+    hash::{HashAdd, Tag},
+    marker::*,
+    nonce::NonceGen,
+    rand_core::RngCore,
+    s, KeyPair, Point, Scalar, G,
+};
+
+/// Attested key generation. See the [module documentation](crate::attested_keygen).
+#[derive(Clone)]
+pub struct AttestedKeyGen<H, NG> {
+    /// The instance used to sign the attestation.
+    pub schnorr: Schnorr<H, NG>,
+    commit_hash: H,
+    tweak_hash: H,
+}
+
+/// The device's commitment to its randomness.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct KeyGenCommitment(pub [u8; 32]);
+
+/// A device's key generation waiting for external entropy.
+///
+/// Created with [`AttestedKeyGen::commit`].
+pub struct PendingKeyGen {
+    device_id: [u8; 32],
+    device_secret: Scalar,
+    commitment: KeyGenCommitment,
+}
+
+impl fmt::Debug for PendingKeyGen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PendingKeyGen")
+            .field("device_id", &self.device_id)
+            .field("commitment", &self.commitment)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The transcript of an attested key generation.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct KeyGenAttestation {
+    /// The id of the device that generated the key.
+    pub device_id: [u8; 32],
+    /// The commitment the device sent before receiving the external entropy.
+    pub commitment: KeyGenCommitment,
+    /// The device's committed contribution `X_d`.
+    pub device_key: Point,
+    /// The entropy contributed by the second party (if any).
+    pub external_entropy: Option<[u8; 32]>,
+    /// The generated public key.
+    pub public_key: Point<EvenY>,
+    /// A signature on the transcript under `public_key`.
+    pub signature: Signature,
+}
+
+impl<H: Tag + Default, NG> AttestedKeyGen<H, NG> {
+    /// Creates attested key generation signing attestations with `schnorr`.
+    pub fn new(schnorr: Schnorr<H, NG>) -> Self {
+        Self {
+            schnorr,
+            commit_hash: H::default().tag(b"schnorr_fun/attested-keygen/commit"),
+            tweak_hash: H::default().tag(b"schnorr_fun/attested-keygen/tweak"),
+        }
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Clone, NG> AttestedKeyGen<H, NG> {
+    /// The commitment of the device `device_id` to `device_key`.
+    pub fn commitment(&self, device_id: [u8; 32], device_key: &Point) -> KeyGenCommitment {
+        KeyGenCommitment(
+            self.commit_hash
+                .clone()
+                .add(device_id)
+                .add(device_key)
+                .finalize()
+                .into(),
+        )
+    }
+
+    fn tweak(
+        &self,
+        commitment: &KeyGenCommitment,
+        device_key: &Point,
+        external_entropy: Option<[u8; 32]>,
+    ) -> Scalar<Public, Zero> {
+        let hash = self.tweak_hash.clone().add(commitment.0).add(device_key);
+        let hash = match external_entropy {
+            Some(entropy) => hash.add([1u8]).add(entropy),
+            None => hash.add([0u8]),
+        };
+        Scalar::from_hash(hash).public().mark_zero()
+    }
+
+    /// `device_id || commitment || X_d || has_entropy || entropy || public_key`
+    fn transcript(
+        device_id: [u8; 32],
+        commitment: &KeyGenCommitment,
+        device_key: &Point,
+        external_entropy: Option<[u8; 32]>,
+        public_key: &Point<EvenY>,
+    ) -> [u8; 162] {
+        let mut bytes = [0u8; 162];
+        bytes[..32].copy_from_slice(&device_id);
+        bytes[32..64].copy_from_slice(&commitment.0);
+        bytes[64..97].copy_from_slice(&device_key.to_bytes());
+        if let Some(entropy) = external_entropy {
+            bytes[97] = 1;
+            bytes[98..130].copy_from_slice(&entropy);
+        }
+        bytes[130..].copy_from_slice(&public_key.to_xonly_bytes());
+        bytes
+    }
+
+    /// Checks that `attestation` is a valid transcript of an attested key generation.
+    ///
+    /// This checks the commitment, that the public key was derived from the device's contribution
+    /// and the external entropy and the signature under the public key. Check that the
+    /// [`commitment`](KeyGenAttestation::commitment) and
+    /// [`external_entropy`](KeyGenAttestation::external_entropy) are the ones that were actually
+    /// exchanged separately.
+    #[must_use]
+    pub fn verify(&self, attestation: &KeyGenAttestation) -> bool {
+        if self.commitment(attestation.device_id, &attestation.device_key) != attestation.commitment
+        {
+            return false;
+        }
+        let t = self.tweak(
+            &attestation.commitment,
+            &attestation.device_key,
+            attestation.external_entropy,
+        );
+        let expected = match g!(attestation.device_key + t * G).normalize().non_zero() {
+            Some(expected) => expected,
+            None => return false,
+        };
+        if expected.into_point_with_even_y().0 != attestation.public_key {
+            return false;
+        }
+        let transcript = Self::transcript(
+            attestation.device_id,
+            &attestation.commitment,
+            &attestation.device_key,
+            attestation.external_entropy,
+            &attestation.public_key,
+        );
+        self.schnorr.verify(
+            &attestation.public_key,
+            Message::<Public>::plain("attested-keygen", &transcript),
+            &attestation.signature,
+        )
+    }
+}
+
+impl<H, NG> AttestedKeyGen<H, NG>
+where
+    H: Digest<OutputSize = U32> + Clone,
+    NG: NonceGen,
+{
+    /// Picks the device's secret contribution and commits to it.
+    ///
+    /// Send the commitment to the second party and wait for their entropy before calling
+    /// [`finish`](Self::finish).
+    pub fn commit(
+        &self,
+        device_id: [u8; 32],
+        rng: &mut impl RngCore,
+    ) -> (PendingKeyGen, KeyGenCommitment) {
+        let device_secret = Scalar::random(rng);
+        let commitment = self.commitment(device_id, &g!(device_secret * G).normalize());
+        (
+            PendingKeyGen {
+                device_id,
+                device_secret,
+                commitment,
+            },
+            commitment,
+        )
+    }
+
+    /// Derives the key from the device's contribution and the `external_entropy` and attests to
+    /// it.
+    ///
+    /// # Panics
+    ///
+    /// If the derived secret key is zero (which will never happen).
+    pub fn finish(
+        &self,
+        pending: PendingKeyGen,
+        external_entropy: Option<[u8; 32]>,
+    ) -> (KeyPair<EvenY>, KeyGenAttestation) {
+        let PendingKeyGen {
+            device_id,
+            device_secret,
+            commitment,
+        } = pending;
+        let device_key = g!(device_secret * G).normalize();
+        let t = self.tweak(&commitment, &device_key, external_entropy);
+        let secret_key = s!(device_secret + t)
+            .non_zero()
+            .expect("computationally unreachable");
+        let keypair = KeyPair::<EvenY>::new(secret_key);
+        let public_key = keypair.public_key();
+        let transcript = Self::transcript(
+            device_id,
+            &commitment,
+            &device_key,
+            external_entropy,
+            &public_key,
+        );
+        let signature = self.schnorr.sign(
+            &keypair,
+            Message::<Public>::plain("attested-keygen", &transcript),
+        );
+        let attestation = KeyGenAttestation {
+            device_id,
+            commitment,
+            device_key,
+            external_entropy,
+            public_key,
+            signature,
+        };
+        (keypair, attestation)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+
+    #[test]
+    fn attested_keygen_verifies() {
+        let keygen = AttestedKeyGen::<Sha256, _>::new(crate::test_instance!());
+        let (pending, commitment) = keygen.commit([1u8; 32], &mut rand::thread_rng());
+        let (keypair, attestation) = keygen.finish(pending, Some([2u8; 32]));
+        assert_eq!(attestation.commitment, commitment);
+        assert_eq!(attestation.public_key, keypair.public_key());
+        assert!(keygen.verify(&attestation));
+
+        // the entropy, device id and key can't be swapped out afterwards
+        let mut other_entropy = attestation.clone();
+        other_entropy.external_entropy = Some([3u8; 32]);
+        assert!(!keygen.verify(&other_entropy));
+        let mut no_entropy = attestation.clone();
+        no_entropy.external_entropy = None;
+        assert!(!keygen.verify(&no_entropy));
+        let mut other_device = attestation.clone();
+        other_device.device_id = [9u8; 32];
+        assert!(!keygen.verify(&other_device));
+
+        // an imported key can't be attested to without the device's committed secret
+        let imported = crate::test_instance!().new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let mut imported_attestation = attestation.clone();
+        imported_attestation.public_key = imported.public_key();
+        assert!(!keygen.verify(&imported_attestation));
+
+        let (pending, _) = keygen.commit([1u8; 32], &mut rand::thread_rng());
+        let (_, attestation) = keygen.finish(pending, None);
+        assert!(keygen.verify(&attestation));
+    }
+}
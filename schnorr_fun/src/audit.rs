@@ -0,0 +1,411 @@
+//! A tamper evident log of everything a signer has signed.
+//!
+//! Custodians are often required to show an auditor every signature they produced and prove that
+//! none were left out or changed afterwards. An [`AuditLog`] appends an [`AuditEntry`] for each
+//! signing request and the signature it was answered with. Each entry commits to the hash of the
+//! entry before it so the hash of the last entry (the [`head`]) commits to the whole history.
+//! Publishing or countersigning the head from time to time means entries can't later be removed,
+//! reordered or edited without the head changing.
+//!
+//! An entry only stores hashes: the hash of the request (e.g. a [`SchnorrRequest`]) and a
+//! fingerprint of the signature. The log can be exported with [`AuditLog::to_bytes`] and checked
+//! by anyone with [`AuditLog::from_bytes`] which recomputes the chain. With the request and the
+//! signature in hand an auditor can then [`find`] the entry that recorded them.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     audit::{AuditLog, SchnorrRequest},
+//!     fun::Scalar,
+//!     Message,
+//! };
+//! let schnorr = schnorr_fun::test_instance!();
+//! let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let mut log = AuditLog::<sha2::Sha256>::new();
+//! let message = Message::plain("my-app", b"pay bob 1 BTC");
+//! let signature = log.sign(&schnorr, &keypair, message);
+//! log.sign(&schnorr, &keypair, Message::plain("my-app", b"pay carol 2 BTC"));
+//! let published_head = log.head();
+//!
+//! // the auditor checks the exported log against the published head
+//! let exported = log.to_bytes();
+//! let audited = AuditLog::<sha2::Sha256>::from_bytes(&exported)?;
+//! assert_eq!(audited.head(), published_head);
+//! let entry = audited
+//!     .find(
+//!         SchnorrRequest {
+//!             public_key: keypair.public_key(),
+//!             message,
+//!         },
+//!         &signature,
+//!     )
+//!     .expect("the signature was logged");
+//! assert_eq!(entry.sequence, 0);
+//! # Ok::<(), schnorr_fun::audit::AuditError>(())
+//! ```
+//!
+//! [`head`]: AuditLog::head
+//! [`find`]: AuditLog::find
+use crate::{Message, Schnorr, Signature};
+use alloc::vec::Vec;
+use core::{fmt, marker::PhantomData};
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    hash::{HashAdd, HashInto, Tag},
+    marker::*,
+    nonce::NonceGen,
+    KeyPair, Point,
+};
+
+const REQUEST_TAG: &[u8] = b"schnorr_fun/audit/request";
+const SIGNATURE_TAG: &[u8] = b"schnorr_fun/audit/signature";
+const ENTRY_TAG: &[u8] = b"schnorr_fun/audit/entry";
+
+/// The `prev_hash` of the first entry in a log.
+pub const GENESIS_HASH: [u8; 32] = [0u8; 32];
+
+/// A record of one signature in an [`AuditLog`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct AuditEntry {
+    /// The position of the entry in the log starting from `0`.
+    pub sequence: u64,
+    /// The [`hash`](Self::hash) of the previous entry ([`GENESIS_HASH`] for the first).
+    pub prev_hash: [u8; 32],
+    /// The hash of the signing request from [`request_hash`].
+    pub request_hash: [u8; 32],
+    /// The fingerprint of the signature from [`signature_fingerprint`].
+    pub signature_fingerprint: [u8; 32],
+}
+
+impl AuditEntry {
+    /// The length of the entry's encoding.
+    pub const SIZE: usize = 8 + 3 * 32;
+
+    /// The hash of the entry that the next entry's `prev_hash` must be.
+    pub fn hash<H: Tag + Digest<OutputSize = U32> + Default>(&self) -> [u8; 32] {
+        H::default()
+            .tag(ENTRY_TAG)
+            .add(self.to_bytes().as_slice())
+            .finalize()
+            .into()
+    }
+
+    /// Encodes the entry as the big-endian sequence number followed by the three hashes.
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[..8].copy_from_slice(&self.sequence.to_be_bytes());
+        bytes[8..40].copy_from_slice(&self.prev_hash);
+        bytes[40..72].copy_from_slice(&self.request_hash);
+        bytes[72..].copy_from_slice(&self.signature_fingerprint);
+        bytes
+    }
+
+    /// Decodes an entry encoded with [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: [u8; Self::SIZE]) -> Self {
+        let array = |i: usize| -> [u8; 32] { bytes[i..i + 32].try_into().expect("32 bytes") };
+        Self {
+            sequence: u64::from_be_bytes(bytes[..8].try_into().expect("8 bytes")),
+            prev_hash: array(8),
+            request_hash: array(40),
+            signature_fingerprint: array(72),
+        }
+    }
+
+    /// Whether this entry records `signature` as the answer to `request`.
+    pub fn records<H: Tag + Digest<OutputSize = U32> + Default>(
+        &self,
+        request: impl HashInto,
+        signature: &Signature,
+    ) -> bool {
+        self.request_hash == request_hash::<H>(request)
+            && self.signature_fingerprint == signature_fingerprint::<H>(signature)
+    }
+}
+
+/// The hash recorded for a signing request. For an ordinary Schnorr signature the request is a
+/// [`SchnorrRequest`].
+pub fn request_hash<H: Tag + Digest<OutputSize = U32> + Default>(
+    request: impl HashInto,
+) -> [u8; 32] {
+    H::default().tag(REQUEST_TAG).add(request).finalize().into()
+}
+
+/// The fingerprint recorded for a signature.
+pub fn signature_fingerprint<H: Tag + Digest<OutputSize = U32> + Default>(
+    signature: &Signature,
+) -> [u8; 32] {
+    H::default()
+        .tag(SIGNATURE_TAG)
+        .add(signature.to_bytes())
+        .finalize()
+        .into()
+}
+
+/// A hash chained log of signing requests and their signatures.
+///
+/// `H` is the hash function for the chain. See the [module documentation](crate::audit).
+#[derive(Clone)]
+pub struct AuditLog<H> {
+    entries: Vec<AuditEntry>,
+    head: [u8; 32],
+    hash: PhantomData<H>,
+}
+
+impl<H> PartialEq for AuditLog<H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl<H> Eq for AuditLog<H> {}
+
+impl<H> fmt::Debug for AuditLog<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuditLog")
+            .field("entries", &self.entries)
+            .field("head", &self.head)
+            .finish()
+    }
+}
+
+impl<H: Tag + Digest<OutputSize = U32> + Default> Default for AuditLog<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: Tag + Digest<OutputSize = U32> + Default> AuditLog<H> {
+    /// Starts an empty log.
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            head: GENESIS_HASH,
+            hash: PhantomData,
+        }
+    }
+
+    /// Rebuilds a log from its entries checking that they form a chain starting from
+    /// [`GENESIS_HASH`].
+    pub fn from_entries(entries: Vec<AuditEntry>) -> Result<Self, AuditError> {
+        let mut head = GENESIS_HASH;
+        for (position, entry) in entries.iter().enumerate() {
+            if entry.sequence != position as u64 {
+                return Err(AuditError::WrongSequence {
+                    position: position as u64,
+                    sequence: entry.sequence,
+                });
+            }
+            if entry.prev_hash != head {
+                return Err(AuditError::BrokenChain {
+                    sequence: entry.sequence,
+                });
+            }
+            head = entry.hash::<H>();
+        }
+        Ok(Self {
+            entries,
+            head,
+            hash: PhantomData,
+        })
+    }
+
+    /// Records that `request` was answered with `signature` and returns the new entry.
+    ///
+    /// Append before handing the signature out so that a signature can't exist without an entry.
+    pub fn append(&mut self, request: impl HashInto, signature: &Signature) -> &AuditEntry {
+        let entry = AuditEntry {
+            sequence: self.entries.len() as u64,
+            prev_hash: self.head,
+            request_hash: request_hash::<H>(request),
+            signature_fingerprint: signature_fingerprint::<H>(signature),
+        };
+        self.head = entry.hash::<H>();
+        self.entries.push(entry);
+        self.entries.last().expect("just pushed")
+    }
+
+    /// Signs `message` with `schnorr` and records it with a [`SchnorrRequest`] as the request.
+    pub fn sign<CH: Digest<OutputSize = U32> + Clone, NG: NonceGen>(
+        &mut self,
+        schnorr: &Schnorr<CH, NG>,
+        keypair: &KeyPair<EvenY>,
+        message: Message<'_, Public>,
+    ) -> Signature {
+        let signature = schnorr.sign(keypair, message);
+        let request = SchnorrRequest {
+            public_key: keypair.public_key(),
+            message,
+        };
+        self.append(request, &signature);
+        signature
+    }
+
+    /// The entries in the order they were appended.
+    pub fn entries(&self) -> &[AuditEntry] {
+        &self.entries
+    }
+
+    /// The hash of the last entry which commits to every entry in the log ([`GENESIS_HASH`] if
+    /// the log is empty).
+    pub fn head(&self) -> [u8; 32] {
+        self.head
+    }
+
+    /// Finds the entry recording `signature` as the answer to `request`.
+    pub fn find(&self, request: impl HashInto, signature: &Signature) -> Option<&AuditEntry> {
+        let request_hash = request_hash::<H>(request);
+        let signature_fingerprint = signature_fingerprint::<H>(signature);
+        self.entries.iter().find(|entry| {
+            entry.request_hash == request_hash
+                && entry.signature_fingerprint == signature_fingerprint
+        })
+    }
+
+    /// Exports the log as the concatenation of each entry's encoding.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.entries
+            .iter()
+            .flat_map(|entry| entry.to_bytes())
+            .collect()
+    }
+
+    /// Imports a log exported with [`to_bytes`](Self::to_bytes) checking the chain with
+    /// [`from_entries`](Self::from_entries).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, AuditError> {
+        if bytes.len() % AuditEntry::SIZE != 0 {
+            return Err(AuditError::Truncated);
+        }
+        let entries = bytes
+            .chunks_exact(AuditEntry::SIZE)
+            .map(|chunk| AuditEntry::from_bytes(chunk.try_into().expect("chunk is SIZE bytes")))
+            .collect();
+        Self::from_entries(entries)
+    }
+}
+
+/// A request to sign `message` with `public_key`. This is what [`AuditLog::sign`] records.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SchnorrRequest<'a> {
+    /// The key asked to sign.
+    pub public_key: Point<EvenY>,
+    /// The message to sign.
+    pub message: Message<'a, Public>,
+}
+
+impl HashInto for SchnorrRequest<'_> {
+    fn hash_into(self, hash: &mut impl Digest) {
+        self.public_key.hash_into(hash);
+        self.message.hash_into(hash);
+    }
+}
+
+/// Error returned when an [`AuditLog`] doesn't check out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuditError {
+    /// The exported log isn't a whole number of entries.
+    Truncated,
+    /// The entry at `position` has the wrong sequence number so an entry is missing or out of
+    /// order.
+    WrongSequence {
+        /// Where in the log the entry is.
+        position: u64,
+        /// The sequence number it has.
+        sequence: u64,
+    },
+    /// The entry's `prev_hash` isn't the hash of the entry before it so the log was edited.
+    BrokenChain {
+        /// The sequence number of the entry.
+        sequence: u64,
+    },
+}
+
+impl fmt::Display for AuditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditError::Truncated => write!(f, "audit log is not a whole number of entries"),
+            AuditError::WrongSequence { position, sequence } => write!(
+                f,
+                "audit log entry at position {} has sequence number {}",
+                position, sequence
+            ),
+            AuditError::BrokenChain { sequence } => write!(
+                f,
+                "audit log entry {} doesn't follow from the entry before it",
+                sequence
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AuditError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use secp256kfun::Scalar;
+    use sha2::Sha256;
+
+    #[test]
+    fn tampering_is_detected() {
+        let schnorr = crate::test_instance!();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let mut log = AuditLog::<Sha256>::new();
+        assert_eq!(log.head(), GENESIS_HASH);
+        let messages = [b"one".as_slice(), b"two", b"three"]
+            .map(|message| Message::<Public>::plain("test", message));
+        let signatures = messages.map(|message| log.sign(&schnorr, &keypair, message));
+        assert_eq!(log.entries().len(), 3);
+
+        let bytes = log.to_bytes();
+        let imported = AuditLog::<Sha256>::from_bytes(&bytes).unwrap();
+        assert_eq!(imported, log);
+        let request = |message| SchnorrRequest {
+            public_key: keypair.public_key(),
+            message,
+        };
+        for (i, (message, signature)) in messages.iter().zip(&signatures).enumerate() {
+            let entry = imported.find(request(*message), signature).unwrap();
+            assert_eq!(entry.sequence, i as u64);
+            assert!(entry.records::<Sha256>(request(*message), signature));
+        }
+        // a signature recorded for a different request isn't found
+        assert!(imported
+            .find(request(messages[0]), &signatures[1])
+            .is_none());
+
+        assert_eq!(
+            AuditLog::<Sha256>::from_bytes(&bytes[1..]),
+            Err(AuditError::Truncated)
+        );
+
+        let mut entries = log.entries().to_vec();
+        entries.remove(1);
+        assert_eq!(
+            AuditLog::<Sha256>::from_entries(entries),
+            Err(AuditError::WrongSequence {
+                position: 1,
+                sequence: 2
+            })
+        );
+
+        let mut entries = log.entries().to_vec();
+        entries[1].signature_fingerprint = [7u8; 32];
+        assert_eq!(
+            AuditLog::<Sha256>::from_entries(entries),
+            Err(AuditError::BrokenChain { sequence: 2 })
+        );
+
+        // dropping entries from the end keeps a valid chain but changes the head
+        let mut entries = log.entries().to_vec();
+        entries.pop();
+        let shortened = AuditLog::<Sha256>::from_entries(entries).unwrap();
+        assert_ne!(shortened.head(), log.head());
+    }
+}
@@ -0,0 +1,423 @@
+//! Batch verification of large streams of BIP340 signatures.
+//!
+//! Checking many signatures with a single multi-scalar multiplication is much faster than checking
+//! them one by one but a single invalid signature makes the whole batch fail. A relay under attack
+//! needs to know which signatures were invalid too. A [`BatchVerifier`] reads signatures from an
+//! iterator in groups of [`BatchStrategy::group_size`] (so memory use doesn't grow with the stream)
+//! and checks each group at once. When a group fails it falls back to pinpointing the invalid
+//! signatures in it, by default by splitting it in half repeatedly which finds a single bad
+//! signature with `O(log n)` checks. It can also stop early once it has seen enough invalid
+//! signatures to reject the whole stream.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     batch::{BatchStrategy, BatchVerifier},
+//!     fun::{marker::*, Scalar},
+//!     Message, Signature,
+//! };
+//! let schnorr = schnorr_fun::test_instance!();
+//! let mut items = (0..100u32)
+//!     .map(|_| {
+//!         let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//!         let message = Message::<Public>::raw(b"relay me");
+//!         (keypair.public_key(), message, schnorr.sign(&keypair, message))
+//!     })
+//!     .collect::<Vec<_>>();
+//! items[42].2 = Signature::random(&mut rand::thread_rng());
+//!
+//! let verifier = BatchVerifier::<sha2::Sha256>::new(BatchStrategy {
+//!     group_size: 32,
+//!     ..Default::default()
+//! });
+//! let report = verifier.verify(items.iter().cloned());
+//! assert_eq!(report.invalid, vec![42]);
+//! assert!(!report.is_valid());
+//! ```
+use crate::{
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        hash::{HashAdd, Tag},
+        marker::*,
+        op, s, Point, Scalar, G,
+    },
+    Message, Schnorr, Signature,
+};
+use alloc::vec::Vec;
+
+/// How a [`BatchVerifier`] splits up a stream of signatures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchStrategy {
+    /// How many signatures are checked together at once. Bigger groups are faster when every
+    /// signature is valid but take more work to search when one isn't. Must be at least 1.
+    pub group_size: usize,
+    /// How to find the invalid signatures in a group that failed.
+    pub fallback: Fallback,
+    /// Stop once this many invalid signatures have been found. `None` checks the whole stream.
+    pub max_invalid: Option<usize>,
+    /// The multi-scalar multiplication parameters each group is checked with.
+    pub msm: op::MsmContext,
+}
+
+impl Default for BatchStrategy {
+    fn default() -> Self {
+        Self {
+            group_size: 64,
+            fallback: Fallback::Bisect,
+            max_invalid: None,
+            msm: op::MsmContext::default(),
+        }
+    }
+}
+
+/// How a [`BatchVerifier`] finds the invalid signatures in a group that failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fallback {
+    /// Split the group in half and check each half as a batch, recursing into the halves that
+    /// fail. This takes `O(k log n)` checks to find `k` invalid signatures in a group of `n`.
+    Bisect,
+    /// Check every signature in the group individually. This is better when most of a failing
+    /// group is expected to be invalid.
+    Individual,
+}
+
+/// The result of [`BatchVerifier::verify`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchReport {
+    /// The positions in the stream of the signatures that were found to be invalid in ascending
+    /// order.
+    pub invalid: Vec<usize>,
+    /// How many signatures were read from the stream.
+    pub read: usize,
+    /// Whether verification stopped because [`BatchStrategy::max_invalid`] invalid signatures were
+    /// found. If so signatures that aren't in `invalid` may still be invalid.
+    pub stopped_early: bool,
+}
+
+impl BatchReport {
+    /// Whether every signature in the stream was valid.
+    pub fn is_valid(&self) -> bool {
+        self.invalid.is_empty()
+    }
+}
+
+/// Verifies streams of BIP340 signatures in batches. See the [module documentation](crate::batch).
+#[derive(Clone)]
+pub struct BatchVerifier<H> {
+    schnorr: Schnorr<H>,
+    coefficient_hash: H,
+    strategy: BatchStrategy,
+}
+
+struct Entry {
+    position: usize,
+    public_key: Point<EvenY>,
+    signature: Signature,
+    challenge: Scalar<Public, Zero>,
+}
+
+impl<H: Digest<OutputSize = U32> + Tag + Default + Clone> BatchVerifier<H> {
+    /// Creates a verifier that uses `strategy`.
+    ///
+    /// # Panics
+    ///
+    /// If `strategy.group_size` is 0.
+    pub fn new(strategy: BatchStrategy) -> Self {
+        assert!(strategy.group_size > 0, "group size must be at least 1");
+        Self {
+            schnorr: Schnorr::verify_only(),
+            coefficient_hash: H::default().tag(b"schnorr_fun/batch"),
+            strategy,
+        }
+    }
+
+    /// The strategy the verifier uses.
+    pub fn strategy(&self) -> &BatchStrategy {
+        &self.strategy
+    }
+
+    /// Verifies every signature in `items` under its public key and message.
+    ///
+    /// Only one group of signatures is held in memory at a time so `items` can be arbitrarily long.
+    #[must_use]
+    pub fn verify<'a>(
+        &self,
+        items: impl IntoIterator<Item = (Point<EvenY>, Message<'a, Public>, Signature)>,
+    ) -> BatchReport {
+        let mut report = BatchReport {
+            invalid: Vec::new(),
+            read: 0,
+            stopped_early: false,
+        };
+        let mut items = items.into_iter().enumerate().peekable();
+        let mut group = Vec::with_capacity(self.strategy.group_size);
+        while items.peek().is_some() && !self.limit_reached(&report) {
+            group.clear();
+            group.extend(items.by_ref().take(self.strategy.group_size).map(
+                |(position, (public_key, message, signature))| Entry {
+                    position,
+                    challenge: self.schnorr.challenge(&signature.R, &public_key, message),
+                    public_key,
+                    signature,
+                },
+            ));
+            report.read += group.len();
+            if self.check(&group) {
+                continue;
+            }
+            match self.strategy.fallback {
+                Fallback::Bisect => self.bisect(&group, &mut report),
+                Fallback::Individual => {
+                    for entry in &group {
+                        if self.limit_reached(&report) {
+                            break;
+                        }
+                        if !self.check(core::slice::from_ref(entry)) {
+                            report.invalid.push(entry.position);
+                        }
+                    }
+                }
+            }
+        }
+        report.stopped_early = self.limit_reached(&report);
+        report
+    }
+
+    fn limit_reached(&self, report: &BatchReport) -> bool {
+        self.strategy
+            .max_invalid
+            .map_or(false, |max_invalid| report.invalid.len() >= max_invalid)
+    }
+
+    /// Finds the invalid entries in a group that is known to fail.
+    fn bisect(&self, group: &[Entry], report: &mut BatchReport) {
+        if let [entry] = group {
+            report.invalid.push(entry.position);
+            return;
+        }
+        let (left, right) = group.split_at(group.len() / 2);
+        let left_failed = !self.check(left);
+        if left_failed {
+            self.bisect(left, report);
+            if self.limit_reached(report) {
+                return;
+            }
+        }
+        // if the left half passed the right half must be the one that failed
+        if !left_failed || !self.check(right) {
+            self.bisect(right, report);
+        }
+    }
+
+    fn check(&self, group: &[Entry]) -> bool {
+        check(&self.coefficient_hash, &self.strategy.msm, group)
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Tag + Default + Clone, NG> Schnorr<H, NG> {
+    /// Verifies many signatures at once returning whether every one of them is valid.
+    ///
+    /// This checks a random linear combination of the verification equations with a single
+    /// multi-scalar multiplication which is much faster than calling [`verify`] on each. It
+    /// doesn't tell you which signature was invalid. Use a [`BatchVerifier`] for that or for
+    /// streams too long to hold in memory.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use schnorr_fun::{
+    ///     fun::{marker::*, Scalar},
+    ///     Message,
+    /// };
+    /// let schnorr = schnorr_fun::test_instance!();
+    /// let keypairs = (0..10)
+    ///     .map(|_| schnorr.new_keypair(Scalar::random(&mut rand::thread_rng())))
+    ///     .collect::<Vec<_>>();
+    /// let message = Message::<Public>::raw(b"block 42");
+    /// let signatures = keypairs
+    ///     .iter()
+    ///     .map(|keypair| schnorr.sign(keypair, message))
+    ///     .collect::<Vec<_>>();
+    /// let public_keys = keypairs
+    ///     .iter()
+    ///     .map(|keypair| keypair.public_key())
+    ///     .collect::<Vec<_>>();
+    /// let mut items = public_keys
+    ///     .iter()
+    ///     .zip(&signatures)
+    ///     .map(|(public_key, signature)| (public_key, &message, signature))
+    ///     .collect::<Vec<_>>();
+    /// assert!(schnorr.verify_batch(&items));
+    /// // a signature under the wrong key
+    /// items[0].2 = items[1].2;
+    /// assert!(!schnorr.verify_batch(&items));
+    /// ```
+    ///
+    /// [`verify`]: Schnorr::verify
+    #[must_use]
+    pub fn verify_batch(
+        &self,
+        items: &[(&Point<EvenY>, &Message<'_, Public>, &Signature)],
+    ) -> bool {
+        let group = items
+            .iter()
+            .enumerate()
+            .map(|(position, (public_key, message, signature))| Entry {
+                position,
+                public_key: **public_key,
+                signature: (*signature).clone(),
+                challenge: self.challenge(&signature.R, public_key, **message),
+            })
+            .collect::<Vec<_>>();
+        check(
+            &H::default().tag(b"schnorr_fun/batch"),
+            &op::MsmContext::default(),
+            &group,
+        )
+    }
+}
+
+/// Checks a group of entries with a random linear combination of their verification equations.
+fn check<H: Digest<OutputSize = U32> + Clone>(
+    coefficient_hash: &H,
+    msm: &op::MsmContext,
+    group: &[Entry],
+) -> bool {
+    match group {
+        [] => return true,
+        [entry] => {
+            let (R, s) = entry.signature.as_tuple();
+            return op::double_mul_vartime(s, G, s!(-entry.challenge), entry.public_key) == R;
+        }
+        _ => {}
+    }
+    let mut batch_hash = coefficient_hash.clone();
+    for entry in group {
+        batch_hash = batch_hash
+            .add(entry.public_key)
+            .add(entry.signature.R)
+            .add(entry.signature.s)
+            .add(entry.challenge);
+    }
+    let mut scalars = Vec::with_capacity(group.len() * 2);
+    let mut points = Vec::with_capacity(group.len() * 2);
+    let mut s_sum = s!(0);
+    for (i, entry) in group.iter().enumerate() {
+        let coefficient =
+            Scalar::from_hash(batch_hash.clone().add((i as u32).to_be_bytes())).public();
+        s_sum += s!(coefficient * entry.signature.s).public();
+        scalars.push(s!(-coefficient).public().mark_zero());
+        points.push(entry.signature.R.normalize());
+        scalars.push(s!(-coefficient * entry.challenge).public());
+        points.push(entry.public_key.normalize());
+    }
+    scalars.push(s_sum.public());
+    points.push(G.normalize());
+    msm.point_scalar_dot_product_vartime(&scalars, &points)
+        .is_zero()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+
+    #[test]
+    fn finds_invalid_signatures() {
+        let schnorr = crate::test_instance!();
+        let message = Message::<Public>::raw(b"batch");
+        let mut items = (0..40)
+            .map(|_| {
+                let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+                (
+                    keypair.public_key(),
+                    message,
+                    schnorr.sign(&keypair, message),
+                )
+            })
+            .collect::<Vec<_>>();
+        let verify = |items: &[(Point<EvenY>, Message, Signature)], strategy| {
+            BatchVerifier::<Sha256>::new(strategy).verify(items.iter().cloned())
+        };
+
+        for group_size in [1, 7, 16, 64] {
+            let report = verify(
+                &items,
+                BatchStrategy {
+                    group_size,
+                    ..Default::default()
+                },
+            );
+            assert!(report.is_valid());
+            assert_eq!(report.read, 40);
+        }
+
+        for bad in [0, 13, 14, 39] {
+            items[bad].2 = Signature::random(&mut rand::thread_rng());
+        }
+        // a swapped public key is invalid too
+        items[20].0 = items[21].0;
+        for fallback in [Fallback::Bisect, Fallback::Individual] {
+            for group_size in [1, 3, 16, 64] {
+                let report = verify(
+                    &items,
+                    BatchStrategy {
+                        group_size,
+                        fallback,
+                        max_invalid: None,
+                        ..Default::default()
+                    },
+                );
+                assert_eq!(report.invalid, vec![0, 13, 14, 20, 39]);
+                assert_eq!(report.read, 40);
+                assert!(!report.stopped_early);
+
+                let report = verify(
+                    &items,
+                    BatchStrategy {
+                        group_size,
+                        fallback,
+                        max_invalid: Some(2),
+                        ..Default::default()
+                    },
+                );
+                assert_eq!(report.invalid, vec![0, 13]);
+                assert!(report.stopped_early);
+                assert!(report.read < 40 || group_size == 64);
+            }
+        }
+
+        assert!(verify(&[], BatchStrategy::default()).is_valid());
+    }
+
+    #[test]
+    fn verify_batch_all_or_nothing() {
+        let schnorr = crate::test_instance!();
+        let messages = (0..20u32).map(|i| i.to_be_bytes()).collect::<Vec<_>>();
+        let items = messages
+            .iter()
+            .map(|message| {
+                let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+                let message = Message::<Public>::raw(message);
+                (
+                    keypair.public_key(),
+                    message,
+                    schnorr.sign(&keypair, message),
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut refs = items
+            .iter()
+            .map(|(public_key, message, signature)| (public_key, message, signature))
+            .collect::<Vec<_>>();
+        assert!(schnorr.verify_batch(&refs));
+        assert!(schnorr.verify_batch(&refs[..1]));
+        assert!(schnorr.verify_batch(&[]));
+
+        // the wrong message for one signature
+        refs[7].1 = &items[8].1;
+        assert!(!schnorr.verify_batch(&refs));
+        assert!(!schnorr.verify_batch(&refs[7..8]));
+    }
+}
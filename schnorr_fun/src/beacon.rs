@@ -0,0 +1,457 @@
+//! A multi-party random beacon anchored to the participants' signing keys.
+//!
+//! In each round every participant signs with a fresh random nonce. The beacon's output for the
+//! round is the hash of the (half-)aggregate of everyone's signatures. To stop the last
+//! participant to speak from choosing their signature after seeing everyone else's, the round has
+//! two phases:
+//!
+//! 1. **Commit**: each participant picks their nonce `R_i` and sends a [`BeaconCommitment`] to it.
+//! 2. **Reveal**: once they have every commitment each participant signs the round id and the
+//!    list of all the commitments with `R_i` and reveals the signature.
+//!
+//! Since the message is fixed by the commitments and the signature is determined by `R_i`, the
+//! key and the message, a participant can't change their signature after the commit phase.
+//! Because every signature is over the whole list of commitments the transcript also shows that
+//! each participant had seen every commitment before they revealed. Someone assembling a
+//! transcript after the fact can't pick their nonce after seeing an honest participant's.
+//!
+//! [`Beacon::finish`] checks every signature against its commitment and aggregates them into a
+//! [`BeaconTranscript`]. The transcript is much smaller than the signatures (one nonce per
+//! participant and a single scalar) and anyone who knows the participants' keys can check it with
+//! [`Beacon::verify`] and recompute the output with [`Beacon::output`].
+//!
+//! The output is unpredictable and unbiased as long as one participant is honest but, as in any
+//! commit-reveal protocol, the last participant to reveal learns the output first and can abort.
+//! The application has to treat an abort as a fault of whoever aborted.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{beacon::Beacon, fun::Scalar};
+//! let beacon = Beacon::<sha2::Sha256>::new("my-lottery");
+//! let schnorr = schnorr_fun::test_instance!();
+//! let keypairs = (0..3)
+//!     .map(|_| schnorr.new_keypair(Scalar::random(&mut rand::thread_rng())))
+//!     .collect::<Vec<_>>();
+//! let participants = keypairs.iter().map(|kp| kp.public_key()).collect::<Vec<_>>();
+//! let round = 7;
+//! // every participant commits
+//! let (pending, commitments): (Vec<_>, Vec<_>) = keypairs
+//!     .iter()
+//!     .enumerate()
+//!     .map(|(i, kp)| beacon.commit(round, i, kp, &mut rand::thread_rng()))
+//!     .unzip();
+//! // after receiving every commitment they reveal their signatures
+//! let reveals = pending
+//!     .into_iter()
+//!     .map(|pending| beacon.reveal(pending, &commitments).unwrap())
+//!     .collect::<Vec<_>>();
+//! let transcript = beacon
+//!     .finish(round, &participants, &commitments, &reveals)
+//!     .unwrap();
+//! // anyone can check the transcript against the participants' keys
+//! assert!(beacon.verify(&participants, &transcript));
+//! let randomness: [u8; 32] = beacon.output(&transcript);
+//! ```
+use crate::{Message, Schnorr, Signature};
+use alloc::vec::Vec;
+use core::fmt;
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    g,
+    hash::{HashAdd, Tag},
+    marker::*,
+    rand_core::RngCore,
+    s, KeyPair, Point, Scalar, G,
+};
+
+/// A random beacon for one application. See the [module documentation](crate::beacon).
+#[derive(Clone)]
+pub struct Beacon<H> {
+    schnorr: Schnorr<H>,
+    commit_hash: H,
+    coefficient_hash: H,
+    output_hash: H,
+}
+
+/// A participant's commitment to their nonce for a round.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct BeaconCommitment(pub [u8; 32]);
+
+/// A participant who has committed to their nonce for a round.
+///
+/// Created with [`Beacon::commit`] and consumed by [`Beacon::reveal`].
+pub struct PendingReveal {
+    round: u64,
+    index: usize,
+    commitment: BeaconCommitment,
+    keypair: KeyPair<EvenY>,
+    nonce: Scalar,
+    R: Point<EvenY>,
+}
+
+impl fmt::Debug for PendingReveal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PendingReveal")
+            .field("index", &self.index)
+            .field("commitment", &self.commitment)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Why [`Beacon::finish`] couldn't finish a round.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BeaconError {
+    /// There wasn't exactly one commitment and one signature for each participant.
+    WrongNumberOfContributions,
+    /// The participant at `index` revealed a signature that doesn't match their commitment.
+    CommitmentMismatch {
+        /// The index of the participant.
+        index: usize,
+    },
+    /// The participant at `index` revealed an invalid signature.
+    InvalidSignature {
+        /// The index of the participant.
+        index: usize,
+    },
+}
+
+impl fmt::Display for BeaconError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BeaconError::WrongNumberOfContributions => write!(
+                f,
+                "there must be one commitment and one signature for each participant"
+            ),
+            BeaconError::CommitmentMismatch { index } => write!(
+                f,
+                "the signature of participant {} doesn't match their commitment",
+                index
+            ),
+            BeaconError::InvalidSignature { index } => {
+                write!(f, "the signature of participant {} is invalid", index)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BeaconError {}
+
+/// The public record of a finished beacon round.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct BeaconTranscript {
+    /// The round id.
+    pub round: u64,
+    /// The participants' commitments in the order of the participants.
+    pub commitments: Vec<BeaconCommitment>,
+    /// The participants' signature nonces in the order of the participants.
+    pub nonces: Vec<Point<EvenY>>,
+    /// The half-aggregated signature response.
+    pub s: Scalar<Public, Zero>,
+}
+
+impl<H: Digest<OutputSize = U32> + Tag + Default + Clone> Beacon<H> {
+    /// Creates the beacon for `domain` which should be unique to the application.
+    pub fn new(domain: &str) -> Self {
+        let hash = |kind: &[u8]| H::default().tag_vectored([kind, domain.as_bytes()].into_iter());
+        Self {
+            schnorr: Schnorr::verify_only(),
+            commit_hash: hash(b"schnorr_fun/beacon/commit/"),
+            coefficient_hash: hash(b"schnorr_fun/beacon/coefficient/"),
+            output_hash: hash(b"schnorr_fun/beacon/output/"),
+        }
+    }
+
+    fn round_message_data(round: u64, commitments: &[BeaconCommitment]) -> Vec<u8> {
+        let mut data = round.to_be_bytes().to_vec();
+        for commitment in commitments {
+            data.extend_from_slice(&commitment.0);
+        }
+        data
+    }
+
+    fn round_message(data: &[u8]) -> Message<'_, Public> {
+        Message::plain("schnorr_fun/beacon", data)
+    }
+
+    /// The commitment of the participant at `index` with `public_key` to `nonce` in `round`.
+    pub fn commitment(
+        &self,
+        round: u64,
+        index: usize,
+        public_key: &Point<EvenY>,
+        nonce: &Point<EvenY>,
+    ) -> BeaconCommitment {
+        BeaconCommitment(
+            self.commit_hash
+                .clone()
+                .add(round.to_be_bytes())
+                .add((index as u64).to_be_bytes())
+                .add(public_key)
+                .add(nonce)
+                .finalize()
+                .into(),
+        )
+    }
+
+    /// Picks a fresh nonce for `round` as the participant at `index` and commits to it.
+    ///
+    /// Send the commitment to the other participants and [`reveal`](Self::reveal) the signature
+    /// once you have all of theirs.
+    pub fn commit(
+        &self,
+        round: u64,
+        index: usize,
+        keypair: &KeyPair<EvenY>,
+        rng: &mut impl RngCore,
+    ) -> (PendingReveal, BeaconCommitment) {
+        let mut nonce = Scalar::random(rng);
+        let R = Point::even_y_from_scalar_mul(G, &mut nonce);
+        let commitment = self.commitment(round, index, &keypair.public_key(), &R);
+        (
+            PendingReveal {
+                round,
+                index,
+                commitment,
+                keypair: keypair.clone(),
+                nonce,
+                R,
+            },
+            commitment,
+        )
+    }
+
+    /// Signs the round and every participant's `commitments` once they have all been received.
+    ///
+    /// Returns an error if our own commitment isn't in `commitments` at our index (i.e. the
+    /// commitments we were sent are not the ones from this round).
+    pub fn reveal(
+        &self,
+        pending: PendingReveal,
+        commitments: &[BeaconCommitment],
+    ) -> Result<Signature, BeaconError> {
+        let PendingReveal {
+            round,
+            index,
+            commitment,
+            keypair,
+            nonce,
+            R,
+        } = pending;
+        if commitments.get(index) != Some(&commitment) {
+            return Err(BeaconError::CommitmentMismatch { index });
+        }
+        let (x, X) = keypair.as_tuple();
+        let data = Self::round_message_data(round, commitments);
+        let c = self.schnorr.challenge(&R, &X, Self::round_message(&data));
+        Ok(Signature {
+            R,
+            s: s!(nonce + c * x).public(),
+        })
+    }
+
+    /// The coefficients each signature is multiplied by in the aggregate.
+    fn coefficients<'a>(
+        &'a self,
+        round: u64,
+        participants: &'a [Point<EvenY>],
+        nonces: &'a [Point<EvenY>],
+    ) -> impl Iterator<Item = Scalar<Public>> + 'a {
+        let hash = self
+            .coefficient_hash
+            .clone()
+            .add(round.to_be_bytes())
+            .add(participants)
+            .add(nonces);
+        (0..participants.len() as u64)
+            .map(move |i| Scalar::from_hash(hash.clone().add(i.to_be_bytes())).public())
+    }
+
+    /// Checks every participant's revealed signature against their commitment and aggregates them
+    /// into the round's transcript.
+    pub fn finish(
+        &self,
+        round: u64,
+        participants: &[Point<EvenY>],
+        commitments: &[BeaconCommitment],
+        signatures: &[Signature],
+    ) -> Result<BeaconTranscript, BeaconError> {
+        if commitments.len() != participants.len() || signatures.len() != participants.len() {
+            return Err(BeaconError::WrongNumberOfContributions);
+        }
+        let data = Self::round_message_data(round, commitments);
+        let message = Self::round_message(&data);
+        for (index, (public_key, signature)) in participants.iter().zip(signatures).enumerate() {
+            if self.commitment(round, index, public_key, &signature.R) != commitments[index] {
+                return Err(BeaconError::CommitmentMismatch { index });
+            }
+        }
+        for (index, (public_key, signature)) in participants.iter().zip(signatures).enumerate() {
+            if !self.schnorr.verify(public_key, message, signature) {
+                return Err(BeaconError::InvalidSignature { index });
+            }
+        }
+        let nonces = signatures.iter().map(|sig| sig.R).collect::<Vec<_>>();
+        let s = self
+            .coefficients(round, participants, &nonces)
+            .zip(signatures)
+            .fold(s!(0).public(), |acc, (a, signature)| {
+                s!(acc + a * signature.s).public()
+            });
+        Ok(BeaconTranscript {
+            round,
+            commitments: commitments.to_vec(),
+            nonces,
+            s,
+        })
+    }
+
+    /// Checks that `transcript` is a valid round of the beacon between `participants`.
+    ///
+    /// This checks that the commitments are to the nonces and that the aggregate signature over
+    /// the round and the commitments is valid, i.e. `s * G = Σ aᵢ(Rᵢ + cᵢXᵢ)`.
+    #[must_use]
+    pub fn verify(&self, participants: &[Point<EvenY>], transcript: &BeaconTranscript) -> bool {
+        let BeaconTranscript {
+            round,
+            commitments,
+            nonces,
+            s,
+        } = transcript;
+        if commitments.len() != participants.len() || nonces.len() != participants.len() {
+            return false;
+        }
+        let data = Self::round_message_data(*round, commitments);
+        let message = Self::round_message(&data);
+        let mut scalars = Vec::with_capacity(participants.len() * 2);
+        let mut points = Vec::with_capacity(participants.len() * 2);
+        for (index, (a, (public_key, nonce))) in self
+            .coefficients(*round, participants, nonces)
+            .zip(participants.iter().zip(nonces))
+            .enumerate()
+        {
+            if self.commitment(*round, index, public_key, nonce) != commitments[index] {
+                return false;
+            }
+            let c = self.schnorr.challenge(nonce, public_key, message);
+            scalars.push(a.mark_zero());
+            points.push(*nonce);
+            scalars.push(s!(a * c).public());
+            points.push(*public_key);
+        }
+        g!(scalars .* points) == g!(s * G)
+    }
+
+    /// The beacon's output for the round recorded in `transcript`.
+    ///
+    /// Only meaningful once the transcript has been checked with [`verify`](Self::verify).
+    pub fn output(&self, transcript: &BeaconTranscript) -> [u8; 32] {
+        self.output_hash
+            .clone()
+            .add(transcript.round.to_be_bytes())
+            .add(&transcript.nonces[..])
+            .add(transcript.s)
+            .finalize()
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+
+    #[test]
+    fn beacon_round() {
+        let beacon = Beacon::<Sha256>::new("test");
+        let keypairs = (0..4)
+            .map(|_| KeyPair::<EvenY>::new(Scalar::random(&mut rand::thread_rng())))
+            .collect::<Vec<_>>();
+        let participants = keypairs
+            .iter()
+            .map(|kp| kp.public_key())
+            .collect::<Vec<_>>();
+        let (pending, commitments): (Vec<_>, Vec<_>) = keypairs
+            .iter()
+            .enumerate()
+            .map(|(i, kp)| beacon.commit(3, i, kp, &mut rand::thread_rng()))
+            .unzip();
+        let signatures = pending
+            .into_iter()
+            .map(|pending| beacon.reveal(pending, &commitments).unwrap())
+            .collect::<Vec<_>>();
+        let transcript = beacon
+            .finish(3, &participants, &commitments, &signatures)
+            .unwrap();
+        assert!(beacon.verify(&participants, &transcript));
+
+        // a participant can't swap in a different signature after committing
+        let (_, other_commitment) = beacon.commit(3, 1, &keypairs[1], &mut rand::thread_rng());
+        let mut other_commitments = commitments.clone();
+        other_commitments[1] = other_commitment;
+        assert_eq!(
+            beacon.finish(3, &participants, &other_commitments, &signatures),
+            Err(BeaconError::CommitmentMismatch { index: 1 })
+        );
+        // or reuse it in another round
+        assert!(beacon
+            .finish(4, &participants, &commitments, &signatures)
+            .is_err());
+
+        let mut tampered = transcript.clone();
+        tampered.s = s!(tampered.s + 1).public();
+        assert!(!beacon.verify(&participants, &tampered));
+        let mut tampered = transcript.clone();
+        tampered.nonces.swap(0, 1);
+        assert!(!beacon.verify(&participants, &tampered));
+        assert_ne!(beacon.output(&tampered), beacon.output(&transcript));
+        assert_ne!(
+            Beacon::<Sha256>::new("other").output(&transcript),
+            beacon.output(&transcript)
+        );
+    }
+
+    #[test]
+    fn signatures_bind_every_commitment() {
+        let beacon = Beacon::<Sha256>::new("test");
+        let keypairs = (0..3)
+            .map(|_| KeyPair::<EvenY>::new(Scalar::random(&mut rand::thread_rng())))
+            .collect::<Vec<_>>();
+        let participants = keypairs
+            .iter()
+            .map(|kp| kp.public_key())
+            .collect::<Vec<_>>();
+        let (pending, commitments): (Vec<_>, Vec<_>) = keypairs
+            .iter()
+            .enumerate()
+            .map(|(i, kp)| beacon.commit(9, i, kp, &mut rand::thread_rng()))
+            .unzip();
+        // participant 2 is shown a different commitment for participant 0
+        let (_, other_commitment) = beacon.commit(9, 0, &keypairs[0], &mut rand::thread_rng());
+        let mut seen_by_2 = commitments.clone();
+        seen_by_2[0] = other_commitment;
+        let signatures = pending
+            .into_iter()
+            .enumerate()
+            .map(|(i, pending)| {
+                let seen = if i == 2 { &seen_by_2 } else { &commitments };
+                beacon.reveal(pending, seen).unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            beacon.finish(9, &participants, &commitments, &signatures),
+            Err(BeaconError::InvalidSignature { index: 2 })
+        );
+    }
+}
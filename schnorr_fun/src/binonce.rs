@@ -4,16 +4,21 @@
 //! Your public nonces are derived from scalars which must be kept secret.
 //! Derived binonces should be unique and and must not be reused for signing under any circumstances
 //! as this can leak your secret key.
-use secp256kfun::{g, marker::*, rand_core::RngCore, Point, Scalar, G};
+use secp256kfun::{
+    g, marker::*, op, rand_core::RngCore, FromSliceError, Point, Scalar, ZeroPointError, G,
+};
 
 /// A nonce (pair of points) that each party must share with the others in the first stage of signing.
 ///
 /// The type argument determines whether the nonces can be `Zero` or not. The [musig
 /// spec](https://github.com/jonasnick/bips/pull/21) specifies that the aggregate nonce is allowed
 /// to be zero to avoid having to abort the protocol in this case.
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
 pub struct Nonce<Z = NonZero>(pub [Point<Normal, Public, Z>; 2]);
 
+const _: () = assert!(Nonce::<NonZero>::SIZE == 2 * Point::<Normal>::COMPRESSED_SIZE);
+const _: () = assert!(NonceKeyPair::SIZE == 2 * Scalar::<Secret>::SIZE);
+
 impl<Z: ZeroChoice> Nonce<Z> {
     /// Reads the pair of nonces from 66 bytes (two 33-byte serialized points).
     ///
@@ -27,6 +32,9 @@ impl<Z: ZeroChoice> Nonce<Z> {
 }
 
 impl<Z> Nonce<Z> {
+    /// The length of the encoding of a nonce.
+    pub const SIZE: usize = 66;
+
     /// Negate the two nonces
     pub fn conditional_negate(&mut self, needs_negation: bool) {
         self.0[0] = self.0[0].conditional_negate(needs_negation);
@@ -44,6 +52,65 @@ impl<Z> Nonce<Z> {
     }
 }
 
+impl Nonce<Zero> {
+    /// Aggregates the public nonces of the parties in a signing session.
+    ///
+    /// This is `NonceAgg` from [BIP327]: each of the two points is summed separately. Addition is
+    /// commutative so the order of `nonces` doesn't matter. Every implementation gets the same
+    /// aggregate nonce from the same set of nonces no matter what order the parties are listed
+    /// in. If either sum is the point at infinity it is kept as `Zero` (and serialized as 33 zero
+    /// bytes) rather than being an error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use schnorr_fun::binonce::{Nonce, NonceKeyPair};
+    /// let nonces = [(); 3].map(|_| NonceKeyPair::random(&mut rand::thread_rng()).public());
+    /// let agg_nonce = Nonce::aggregate(nonces);
+    /// assert_eq!(Nonce::aggregate([nonces[2], nonces[0], nonces[1]]), agg_nonce);
+    /// ```
+    ///
+    /// [BIP327]: https://github.com/bitcoin/bips/blob/master/bip-0327.mediawiki
+    pub fn aggregate(nonces: impl IntoIterator<Item = Nonce>) -> Self {
+        let mut agg_nonce = [Point::<NonNormal, Public, Zero>::zero(); 2];
+        for nonce in nonces {
+            op::pairwise_add_points(&mut agg_nonce, &nonce.0);
+        }
+        Nonce([agg_nonce[0].normalize(), agg_nonce[1].normalize()])
+    }
+
+    /// Converts to a `NonZero` nonce returning a [`ZeroPointError`] if either point is the
+    /// identity.
+    ///
+    /// Nonces received from other parties should be decoded as `Nonce` (which rejects the
+    /// identity) but if a protocol decodes them as `Nonce<Zero>` this checks them afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use schnorr_fun::{
+    ///     binonce::Nonce,
+    ///     fun::{marker::*, ZeroPointError},
+    /// };
+    /// let bytes = [0u8; 66];
+    /// assert_eq!(Nonce::<NonZero>::from_bytes(bytes), None);
+    /// let nonce = Nonce::<Zero>::from_bytes(bytes).unwrap();
+    /// assert_eq!(nonce.non_identity(), Err(ZeroPointError));
+    /// ```
+    pub fn non_identity(self) -> Result<Nonce, ZeroPointError> {
+        let [R1, R2] = self.0;
+        Ok(Nonce([R1.non_identity()?, R2.non_identity()?]))
+    }
+}
+
+impl<Z: ZeroChoice> TryFrom<&[u8]> for Nonce<Z> {
+    type Error = FromSliceError;
+
+    fn try_from(slice: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(FromSliceError::to_array(slice)?).ok_or(FromSliceError::Invalid)
+    }
+}
+
 secp256kfun::impl_fromstr_deserialize! {
     name => "public nonce pair",
     fn from_bytes<Z: ZeroChoice>(bytes: [u8;66]) -> Option<Nonce<Z>> {
@@ -61,8 +128,14 @@ secp256kfun::impl_display_serialize! {
 ///
 /// A nonce key pair can be created manually with [`from_secrets`]
 ///
+/// The secret nonces are left out of its `Debug` output. With the `zeroize` feature it implements
+/// `Zeroize` and the signing functions that consume it overwrite it, and the copy of the secret
+/// nonces they sign with, once they have signed. Temporaries the compiler makes while computing
+/// the signature share aren't cleared. Hold it in a `Zeroizing` if it should also be overwritten
+/// when dropped unused.
+///
 /// [`from_secrets`]: Self::from_secrets
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub struct NonceKeyPair {
     /// The public nonce
     pub public: Nonce<NonZero>,
@@ -71,6 +144,9 @@ pub struct NonceKeyPair {
 }
 
 impl NonceKeyPair {
+    /// The length of the encoding of a nonce key pair.
+    pub const SIZE: usize = 64;
+
     /// Load nonces from two secret scalars
     pub fn from_secrets(secret: [Scalar; 2]) -> Self {
         let [ref r1, ref r2] = secret;
@@ -97,8 +173,8 @@ impl NonceKeyPair {
     /// Serializes a nonce key pair to 64-bytes (two 32-bytes serialized scalars).
     pub fn to_bytes(&self) -> [u8; 64] {
         let mut bytes = [0u8; 64];
-        bytes[..32].copy_from_slice(self.secret[0].to_bytes().as_ref());
-        bytes[32..].copy_from_slice(self.secret[1].to_bytes().as_ref());
+        bytes[..32].copy_from_slice(self.secret[0].to_secret_bytes().expose_secret());
+        bytes[32..].copy_from_slice(self.secret[1].to_secret_bytes().expose_secret());
         bytes
     }
 
@@ -116,6 +192,34 @@ impl NonceKeyPair {
     pub fn random(rng: &mut impl RngCore) -> Self {
         Self::from_secrets([Scalar::random(rng), Scalar::random(rng)])
     }
+
+    /// Consumes the nonce key pair to sign with it. With the `zeroize` feature its copy of the
+    /// secret nonces is overwritten and the caller has to overwrite the returned copy once it has
+    /// signed.
+    pub(crate) fn into_secrets(self) -> [Scalar; 2] {
+        #[cfg_attr(not(feature = "zeroize"), allow(unused_mut))]
+        let mut nonce = self;
+        let secret = nonce.secret;
+        #[cfg(feature = "zeroize")]
+        secp256kfun::zeroize::Zeroize::zeroize(&mut nonce);
+        secret
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl secp256kfun::zeroize::Zeroize for NonceKeyPair {
+    fn zeroize(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+impl core::fmt::Debug for NonceKeyPair {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("NonceKeyPair")
+            .field("public", &self.public)
+            .field("secret", &format_args!(".."))
+            .finish()
+    }
 }
 
 secp256kfun::impl_fromstr_deserialize! {
@@ -0,0 +1,563 @@
+//! Partially blind Schnorr signatures with public info bound into the signature.
+//!
+//! In a blind signature the signer signs a message without seeing it and can't later link the
+//! signature to the session it was created in. E-cash mints issue tokens this way but they need to
+//! know what they are signing for: a token's denomination or expiry can't be blind. A *partially
+//! blind* signature has an `info` field that both the signer and the user agree on in the clear
+//! and that is bound into the signature. A signature made for one `info` doesn't verify under
+//! another.
+//!
+//! This is the scheme of [Abe and Okamoto] instantiated on secp256k1 with `info` hashed to a curve
+//! point `Z`. The signer with key `X = x * G` and the user run:
+//!
+//! ```text
+//! signer: A = u * G, B = s * G + d * Z                                      --(A, B)-->
+//! user:   α = A + t1 * G + t2 * X, β = B + t3 * G + t4 * Z
+//!         e = H(α || β || Z || m) - t2 - t4                                 <--(e)--
+//! signer: c = e - d, r = u - c * x                                          --(r, c, s, d)-->
+//! user:   (ρ, ω, σ, δ) = (r + t1, c + t2, s + t3, d + t4)
+//! ```
+//!
+//! and anyone can check `ω + δ = H(ρ * G + ω * X || σ * G + δ * Z || Z || m)`. The signatures are
+//! **not** BIP340 signatures.
+//!
+//! ⚠ Like blind Schnorr signatures the scheme can be attacked when the signer has many sessions
+//! open at the same time (see [ROS]). [`PartiallyBlindSigner`] keeps the signer's nonces and
+//! refuses to have more than `N` sessions open at once. The default `N = 1` means each session
+//! has to finish (or be [abandoned]) before the next one starts. The nonces can't be copied out of
+//! the signer so a session can't be answered twice either.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     blind::{PartiallyBlind, PartiallyBlindSigner},
+//!     fun::{marker::*, KeyPair, Scalar},
+//!     Message,
+//! };
+//! let blind = PartiallyBlind::<sha2::Sha256>::default();
+//! let keypair = KeyPair::<Normal>::new(Scalar::random(&mut rand::thread_rng()));
+//! let mut mint = PartiallyBlindSigner::<_>::new(blind.clone(), keypair);
+//! let info = b"denomination=100sat;expiry=2027-01-01";
+//! // mint
+//! let (id, commitment) = mint.start_session(info, &mut rand::thread_rng()).unwrap();
+//! // only one session is allowed at a time by default
+//! assert!(mint.start_session(info, &mut rand::thread_rng()).is_err());
+//! // user
+//! let message = Message::<Public>::plain("my-mint", b"token serial 8f2d...");
+//! let session = blind.blind(
+//!     &mint.public_key(),
+//!     info,
+//!     &commitment,
+//!     message,
+//!     &mut rand::thread_rng(),
+//! );
+//! // mint
+//! let response = mint.sign(id, session.challenge()).unwrap();
+//! // user
+//! let signature = blind.unblind(session, &response).unwrap();
+//! assert!(blind.verify(&mint.public_key(), info, message, &signature));
+//! assert!(!blind.verify(&mint.public_key(), b"denomination=1000sat", message, &signature));
+//! ```
+//!
+//! Fully blind signatures that unblind into ordinary BIP340 signatures are in [`bip340`].
+//!
+//! [Abe and Okamoto]: https://www.iacr.org/archive/crypto2000/18800271/18800271.pdf
+//! [ROS]: https://eprint.iacr.org/2020/945
+//! [abandoned]: PartiallyBlindSigner::abandon_session
+pub mod bip340;
+
+use crate::Message;
+use core::fmt;
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    g,
+    hash::{HashAdd, Tag},
+    marker::*,
+    rand_core::RngCore,
+    s, KeyPair, Point, Scalar, G,
+};
+
+/// Creates and verifies partially blind signatures.
+///
+/// See the [module documentation](crate::blind).
+#[derive(Clone, Debug)]
+pub struct PartiallyBlind<H> {
+    challenge_hash: H,
+    info_hash: H,
+}
+
+impl<H: Tag + Default> Default for PartiallyBlind<H> {
+    fn default() -> Self {
+        Self {
+            challenge_hash: H::default().tag(b"schnorr_fun/partially-blind/challenge"),
+            info_hash: H::default().tag(b"schnorr_fun/partially-blind/info"),
+        }
+    }
+}
+
+/// Identifies a session opened with [`PartiallyBlindSigner::start_session`] or
+/// [`bip340::BlindSigner::start_session`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SessionId(u64);
+
+impl SessionId {
+    /// The session's position in the order the signer started them (from 0).
+    pub fn to_u64(self) -> u64 {
+        self.0
+    }
+}
+
+/// Error returned by [`PartiallyBlindSigner`] and [`bip340::BlindSigner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlindSignError {
+    /// The maximum number of sessions are already open.
+    TooManySessions,
+    /// The session isn't open. It was already signed or abandoned or never started.
+    UnknownSession,
+}
+
+impl fmt::Display for BlindSignError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlindSignError::TooManySessions => write!(
+                f,
+                "too many blind signing sessions are open, finish or abandon one first"
+            ),
+            BlindSignError::UnknownSession => write!(f, "the blind signing session isn't open"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BlindSignError {}
+
+/// The signer's secret nonces for one session.
+struct SignerNonce {
+    u: Scalar,
+    s: Scalar,
+    d: Scalar,
+}
+
+/// The signing side of partially blind signatures allowing at most `N` open sessions.
+///
+/// See the [module documentation](crate::blind).
+pub struct PartiallyBlindSigner<H, const N: usize = 1> {
+    blind: PartiallyBlind<H>,
+    keypair: KeyPair,
+    next_session: u64,
+    open: [Option<(SessionId, SignerNonce)>; N],
+}
+
+impl<H, const N: usize> fmt::Debug for PartiallyBlindSigner<H, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PartiallyBlindSigner")
+            .field("public_key", &self.keypair.public_key())
+            .field("next_session", &self.next_session)
+            .field("open_sessions", &self.open_sessions())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<H, const N: usize> PartiallyBlindSigner<H, N> {
+    /// Creates a signer for `keypair`.
+    pub fn new(blind: PartiallyBlind<H>, keypair: KeyPair) -> Self {
+        Self {
+            blind,
+            keypair,
+            next_session: 0,
+            open: [(); N].map(|_| None),
+        }
+    }
+
+    /// The public key signatures will verify under.
+    pub fn public_key(&self) -> Point {
+        self.keypair.public_key()
+    }
+
+    /// The number of sessions that have been started but not signed or abandoned.
+    pub fn open_sessions(&self) -> usize {
+        self.open.iter().flatten().count()
+    }
+
+    /// Responds to the user's blinded `challenge` in session `id` and closes the session.
+    ///
+    /// The signer learns nothing about the message but the signature will only verify for the
+    /// `info` that the session was started with. The session's nonces are forgotten before this
+    /// returns so they can't be used again.
+    pub fn sign(
+        &mut self,
+        id: SessionId,
+        challenge: Scalar<Public, Zero>,
+    ) -> Result<SignerResponse, BlindSignError> {
+        let slot = self
+            .open
+            .iter_mut()
+            .find(|slot| matches!(slot, Some((open_id, _)) if *open_id == id))
+            .ok_or(BlindSignError::UnknownSession)?;
+        let (_, SignerNonce { u, s, d }) = slot.take().expect("just matched");
+        let x = self.keypair.secret_key();
+        let c = s!(challenge - d);
+        Ok(SignerResponse {
+            r: s!(u - c * x).public(),
+            c: c.public(),
+            s: s.public().mark_zero(),
+            d: d.public().mark_zero(),
+        })
+    }
+
+    /// Closes session `id` without signing so another one can be started.
+    ///
+    /// Returns whether the session was open.
+    pub fn abandon_session(&mut self, id: SessionId) -> bool {
+        match self
+            .open
+            .iter_mut()
+            .find(|slot| matches!(slot, Some((open_id, _)) if *open_id == id))
+        {
+            Some(slot) => {
+                *slot = None;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Clone, const N: usize> PartiallyBlindSigner<H, N> {
+    /// Starts a session for `info` returning its id and the commitment to send to the user.
+    ///
+    /// Fails if `N` sessions are already open.
+    pub fn start_session(
+        &mut self,
+        info: &[u8],
+        rng: &mut impl RngCore,
+    ) -> Result<(SessionId, SignerCommitment), BlindSignError> {
+        let slot = self
+            .open
+            .iter_mut()
+            .find(|slot| slot.is_none())
+            .ok_or(BlindSignError::TooManySessions)?;
+        let id = SessionId(self.next_session);
+        self.next_session += 1;
+        let info_point = self.blind.info_point(info);
+        let [u, s, d] = [(); 3].map(|_| Scalar::random(rng));
+        let commitment = SignerCommitment {
+            A: g!(u * G).normalize(),
+            B: g!(s * G + d * info_point).normalize(),
+        };
+        *slot = Some((id, SignerNonce { u, s, d }));
+        Ok((id, commitment))
+    }
+}
+
+/// The signer's first message in a session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct SignerCommitment {
+    /// `u * G`
+    pub A: Point,
+    /// `s * G + d * Z`
+    pub B: Point<Normal, Public, Zero>,
+}
+
+/// The signer's response to the user's blinded challenge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct SignerResponse {
+    /// `u - c * x`
+    pub r: Scalar<Public, Zero>,
+    /// `e - d`
+    pub c: Scalar<Public, Zero>,
+    /// The signer's `s` nonce.
+    pub s: Scalar<Public, Zero>,
+    /// The signer's `d` nonce.
+    pub d: Scalar<Public, Zero>,
+}
+
+/// A partially blind signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct PartiallyBlindSignature {
+    /// `ρ`
+    pub rho: Scalar<Public, Zero>,
+    /// `ω`
+    pub omega: Scalar<Public, Zero>,
+    /// `σ`
+    pub sigma: Scalar<Public, Zero>,
+    /// `δ`
+    pub delta: Scalar<Public, Zero>,
+}
+
+impl PartiallyBlindSignature {
+    /// The length of the encoding of the signature.
+    pub const SIZE: usize = 128;
+
+    /// Serializes the signature as `ρ || ω || σ || δ`.
+    pub fn to_bytes(&self) -> [u8; 128] {
+        let mut bytes = [0u8; 128];
+        for (chunk, scalar) in bytes
+            .chunks_mut(32)
+            .zip([self.rho, self.omega, self.sigma, self.delta])
+        {
+            chunk.copy_from_slice(&scalar.to_bytes());
+        }
+        bytes
+    }
+
+    /// Deserializes a signature from [`to_bytes`](Self::to_bytes).
+    ///
+    /// Returns `None` if any of the scalars are out of range.
+    pub fn from_bytes(bytes: [u8; 128]) -> Option<Self> {
+        let scalar = |i: usize| Scalar::from_slice(&bytes[i * 32..(i + 1) * 32]);
+        Some(Self {
+            rho: scalar(0)?,
+            omega: scalar(1)?,
+            sigma: scalar(2)?,
+            delta: scalar(3)?,
+        })
+    }
+}
+
+/// The user's state in a session.
+///
+/// Created with [`PartiallyBlind::blind`] and consumed by [`PartiallyBlind::unblind`].
+#[derive(Clone)]
+pub struct BlindSession {
+    t: [Scalar; 4],
+    challenge: Scalar<Public, Zero>,
+    public_key: Point,
+    info_point: Point,
+    commitment: SignerCommitment,
+}
+
+impl core::fmt::Debug for BlindSession {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("BlindSession")
+            .field("challenge", &self.challenge)
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl BlindSession {
+    /// The blinded challenge `e` to send to the signer.
+    pub fn challenge(&self) -> Scalar<Public, Zero> {
+        self.challenge
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Clone> PartiallyBlind<H> {
+    /// Hashes `info` to the point `Z` whose discrete logarithm nobody knows.
+    pub fn info_point(&self, info: &[u8]) -> Point {
+        (0u32..)
+            .find_map(|counter| {
+                let hash = self
+                    .info_hash
+                    .clone()
+                    .add(counter.to_be_bytes())
+                    .add(info)
+                    .finalize();
+                Point::<EvenY>::from_xonly_bytes(hash.into()).map(|point| point.normalize())
+            })
+            .expect("computationally unreachable")
+    }
+
+    fn challenge(
+        &self,
+        alpha: &Point<NonNormal, Public, Zero>,
+        beta: &Point<NonNormal, Public, Zero>,
+        info_point: &Point,
+        message: Message<'_, impl Secrecy>,
+    ) -> Scalar<Public, Zero> {
+        Scalar::from_hash(
+            self.challenge_hash
+                .clone()
+                .add(alpha.normalize())
+                .add(beta.normalize())
+                .add(info_point)
+                .add(message),
+        )
+        .public()
+        .mark_zero()
+    }
+
+    /// Blinds `message` for signing by the owner of `public_key` with the signer's `commitment`.
+    ///
+    /// The session's [`challenge`](BlindSession::challenge) is sent to the signer.
+    pub fn blind(
+        &self,
+        public_key: &Point,
+        info: &[u8],
+        commitment: &SignerCommitment,
+        message: Message<'_, impl Secrecy>,
+        rng: &mut impl RngCore,
+    ) -> BlindSession {
+        let info_point = self.info_point(info);
+        let t = [(); 4].map(|_| Scalar::random(rng));
+        let [t1, t2, t3, t4] = &t;
+        let (X, Z) = (public_key, &info_point);
+        let SignerCommitment { A, B } = commitment;
+        let alpha = g!(A + t1 * G + t2 * X);
+        let beta = g!(B + t3 * G + t4 * Z);
+        let epsilon = self.challenge(&alpha, &beta, &info_point, message);
+        let challenge = s!(epsilon - t2 - t4).public();
+        BlindSession {
+            t,
+            challenge,
+            public_key: *public_key,
+            info_point,
+            commitment: *commitment,
+        }
+    }
+
+    /// Checks the signer's `response` and unblinds it into a signature.
+    ///
+    /// Returns `None` if the response is invalid.
+    pub fn unblind(
+        &self,
+        session: BlindSession,
+        response: &SignerResponse,
+    ) -> Option<PartiallyBlindSignature> {
+        let BlindSession {
+            t: [t1, t2, t3, t4],
+            challenge,
+            public_key: X,
+            info_point: Z,
+            commitment: SignerCommitment { A, B },
+        } = session;
+        let SignerResponse { r, c, s, d } = response;
+        let valid = g!(r * G + c * X) == A && g!(s * G + d * Z) == B && s!(c + d) == challenge;
+        if !valid {
+            return None;
+        }
+        Some(PartiallyBlindSignature {
+            rho: s!(r + t1).public(),
+            omega: s!(c + t2).public(),
+            sigma: s!(s + t3).public(),
+            delta: s!(d + t4).public(),
+        })
+    }
+
+    /// Verifies a partially blind signature on `message` with `info` under `public_key`.
+    #[must_use]
+    pub fn verify(
+        &self,
+        public_key: &Point,
+        info: &[u8],
+        message: Message<'_, impl Secrecy>,
+        signature: &PartiallyBlindSignature,
+    ) -> bool {
+        let PartiallyBlindSignature {
+            rho,
+            omega,
+            sigma,
+            delta,
+        } = signature;
+        let (X, Z) = (public_key, self.info_point(info));
+        let alpha = g!(rho * G + omega * X);
+        let beta = g!(sigma * G + delta * Z);
+        self.challenge(&alpha, &beta, &Z, message) == s!(omega + delta)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+
+    #[test]
+    fn partially_blind_signing() {
+        let blind = PartiallyBlind::<Sha256>::default();
+        let keypair = KeyPair::<Normal>::new(Scalar::random(&mut rand::thread_rng()));
+        let mut signer = PartiallyBlindSigner::<_>::new(blind.clone(), keypair);
+        let public_key = signer.public_key();
+        let message = Message::<Public>::plain("test", b"blind");
+        let (id, commitment) = signer
+            .start_session(b"100", &mut rand::thread_rng())
+            .unwrap();
+        let session = blind.blind(
+            &public_key,
+            b"100",
+            &commitment,
+            message,
+            &mut rand::thread_rng(),
+        );
+        let response = signer.sign(id, session.challenge()).unwrap();
+        assert_eq!(
+            signer.sign(id, session.challenge()),
+            Err(BlindSignError::UnknownSession)
+        );
+
+        let mut bad_response = response;
+        bad_response.d = s!({ response.d } + 1).public();
+        assert_eq!(blind.unblind(session.clone(), &bad_response), None);
+
+        let signature = blind.unblind(session, &response).unwrap();
+        assert!(blind.verify(&public_key, b"100", message, &signature));
+        assert!(!blind.verify(&public_key, b"1000", message, &signature));
+        assert!(!blind.verify(
+            &public_key,
+            b"100",
+            Message::<Public>::plain("test", b"other"),
+            &signature
+        ));
+        // what the signer saw doesn't appear in the signature
+        assert_ne!(signature.rho, response.r);
+        assert_ne!(signature.omega, response.c);
+        assert_eq!(
+            PartiallyBlindSignature::from_bytes(signature.to_bytes()),
+            Some(signature)
+        );
+
+        // a session started for a different info doesn't give a signature for this one
+        let (id, commitment) = signer
+            .start_session(b"1000", &mut rand::thread_rng())
+            .unwrap();
+        let session = blind.blind(
+            &public_key,
+            b"100",
+            &commitment,
+            message,
+            &mut rand::thread_rng(),
+        );
+        let response = signer.sign(id, session.challenge()).unwrap();
+        assert_eq!(blind.unblind(session, &response), None);
+    }
+
+    #[test]
+    fn session_limit() {
+        let blind = PartiallyBlind::<Sha256>::default();
+        let keypair = KeyPair::<Normal>::new(Scalar::random(&mut rand::thread_rng()));
+        let mut signer = PartiallyBlindSigner::<_, 2>::new(blind, keypair);
+        let (first, _) = signer.start_session(b"1", &mut rand::thread_rng()).unwrap();
+        let (second, _) = signer.start_session(b"2", &mut rand::thread_rng()).unwrap();
+        assert_eq!(signer.open_sessions(), 2);
+        assert_eq!(
+            signer.start_session(b"3", &mut rand::thread_rng()),
+            Err(BlindSignError::TooManySessions)
+        );
+        assert!(signer.abandon_session(first));
+        assert!(!signer.abandon_session(first));
+        let (third, _) = signer.start_session(b"3", &mut rand::thread_rng()).unwrap();
+        assert_eq!(
+            signer.sign(first, Scalar::zero()),
+            Err(BlindSignError::UnknownSession)
+        );
+        assert!(signer.sign(second, Scalar::zero()).is_ok());
+        assert!(signer.sign(third, Scalar::zero()).is_ok());
+        assert_eq!(signer.open_sessions(), 0);
+    }
+}
@@ -0,0 +1,353 @@
+//! Blind Schnorr signatures that unblind into ordinary [BIP340] signatures.
+//!
+//! The signer with key `X = x * G` and the user run:
+//!
+//! ```text
+//! signer: R = k * G                                   --(R)-->
+//! user:   R' = R + α * G + β * X
+//!         c' = H(R' || X || m), c = c' + β            <--(c)--
+//! signer: s = k + c * x                               --(s)-->
+//! user:   s' = s + α
+//! ```
+//!
+//! and `(R', s')` is a BIP340 signature on `m` under `X` that the signer can't link to the
+//! session it signed in (when `R'` has an odd y-coordinate the user negates the challenge and the
+//! result so the signature still verifies against the even `R'`).
+//!
+//! ## Concurrent sessions
+//!
+//! ⚠ Plain blind Schnorr signatures are only secure when the signer doesn't have many sessions
+//! open at once. With `ℓ` concurrent sessions a user can produce `ℓ + 1` signatures: with Wagner's
+//! algorithm it's sub-exponential in `ℓ` and once `ℓ` is more than about 256 it takes seconds (see
+//! [ROS]). [`BlindSigner`] refuses to have more than `N` sessions open at once and the default
+//! `N = 1` means each session has to finish (or be [abandoned]) before the next one starts which
+//! is the setting in which the scheme is secure. Only raise `N` if you have to and keep it small.
+//!
+//! The signer's nonces are derived with its [`NonceGen`] from its secret key, a counter and a
+//! session id the caller provides. Like [`Frost::seed_nonce_rng`], if the nonce generator is
+//! [`Deterministic`] the session id **must** never repeat for the same key or the key can be
+//! recovered. Prefer a [`Synthetic`] nonce generator.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     blind::bip340::{BlindSigner, UserSession},
+//!     fun::{marker::*, Scalar},
+//!     nonce::{GlobalRng, Synthetic},
+//!     Message, Schnorr,
+//! };
+//! use rand::rngs::ThreadRng;
+//! use sha2::Sha256;
+//! let schnorr = Schnorr::<Sha256, Synthetic<Sha256, GlobalRng<ThreadRng>>>::default();
+//! let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let mut mint = BlindSigner::<_, _>::new(schnorr.clone(), keypair);
+//! // mint
+//! let (id, signer_nonce) = mint.start_session(b"token-request-2e71").unwrap();
+//! // only one session is allowed at a time by default
+//! assert!(mint.start_session(b"token-request-2e72").is_err());
+//! // user
+//! let message = Message::<Public>::plain("my-mint", b"token serial 8f2d...");
+//! let session = UserSession::new(
+//!     &schnorr,
+//!     mint.public_key(),
+//!     signer_nonce,
+//!     message,
+//!     &mut rand::thread_rng(),
+//! );
+//! // mint
+//! let response = mint.sign(id, session.challenge()).unwrap();
+//! // user
+//! let signature = session.unblind(response).unwrap();
+//! assert!(schnorr.verify(&mint.public_key(), message, &signature));
+//! ```
+//!
+//! [BIP340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+//! [ROS]: https://eprint.iacr.org/2020/945
+//! [abandoned]: BlindSigner::abandon_session
+//! [`NonceGen`]: crate::nonce::NonceGen
+//! [`Deterministic`]: crate::nonce::Deterministic
+//! [`Synthetic`]: crate::nonce::Synthetic
+//! [`Frost::seed_nonce_rng`]: crate::frost::Frost::seed_nonce_rng
+pub use super::{BlindSignError, SessionId};
+use crate::{Message, Schnorr, Signature};
+use core::fmt;
+use secp256kfun::{
+    derive_nonce,
+    digest::{generic_array::typenum::U32, Digest},
+    g,
+    marker::*,
+    nonce::NonceGen,
+    rand_core::RngCore,
+    s, KeyPair, Point, Scalar, G,
+};
+
+/// The signing side of blind Schnorr signatures allowing at most `N` open sessions.
+///
+/// See the [module documentation](crate::blind::bip340).
+pub struct BlindSigner<H, NG, const N: usize = 1> {
+    schnorr: Schnorr<H, NG>,
+    keypair: KeyPair<EvenY>,
+    next_session: u64,
+    open: [Option<(SessionId, Scalar)>; N],
+}
+
+impl<H, NG, const N: usize> fmt::Debug for BlindSigner<H, NG, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BlindSigner")
+            .field("public_key", &self.keypair.public_key())
+            .field("next_session", &self.next_session)
+            .field("open_sessions", &self.open_sessions())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<H, NG, const N: usize> BlindSigner<H, NG, N> {
+    /// Creates a signer for `keypair`.
+    pub fn new(schnorr: Schnorr<H, NG>, keypair: KeyPair<EvenY>) -> Self {
+        Self {
+            schnorr,
+            keypair,
+            next_session: 0,
+            open: [(); N].map(|_| None),
+        }
+    }
+
+    /// The public key signatures will verify under.
+    pub fn public_key(&self) -> Point<EvenY> {
+        self.keypair.public_key()
+    }
+
+    /// The number of sessions that have been started but not signed or abandoned.
+    pub fn open_sessions(&self) -> usize {
+        self.open.iter().flatten().count()
+    }
+
+    /// Responds to the user's blinded `challenge` in session `id` and closes the session.
+    ///
+    /// The session's nonce is forgotten before this returns so it can't be used again.
+    pub fn sign(
+        &mut self,
+        id: SessionId,
+        challenge: Scalar<Public, Zero>,
+    ) -> Result<Scalar<Public, Zero>, BlindSignError> {
+        let slot = self
+            .open
+            .iter_mut()
+            .find(|slot| matches!(slot, Some((open_id, _)) if *open_id == id))
+            .ok_or(BlindSignError::UnknownSession)?;
+        let (_, k) = slot.take().expect("just matched");
+        let x = self.keypair.secret_key();
+        Ok(s!(k + challenge * x).public())
+    }
+
+    /// Closes session `id` without signing so another one can be started.
+    ///
+    /// Returns whether the session was open.
+    pub fn abandon_session(&mut self, id: SessionId) -> bool {
+        match self
+            .open
+            .iter_mut()
+            .find(|slot| matches!(slot, Some((open_id, _)) if *open_id == id))
+        {
+            Some(slot) => {
+                *slot = None;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<H, NG: NonceGen, const N: usize> BlindSigner<H, NG, N> {
+    /// Starts a session returning its id and the nonce to send to the user.
+    ///
+    /// `session_id` goes into the nonce derivation. It must be unique if the nonce generator is
+    /// deterministic (see the [module documentation](crate::blind::bip340)). Fails if `N` sessions
+    /// are already open.
+    pub fn start_session(
+        &mut self,
+        session_id: &[u8],
+    ) -> Result<(SessionId, Point), BlindSignError> {
+        let slot = self
+            .open
+            .iter_mut()
+            .find(|slot| slot.is_none())
+            .ok_or(BlindSignError::TooManySessions)?;
+        let id = SessionId(self.next_session);
+        self.next_session += 1;
+        let (x, X) = self.keypair.as_tuple();
+        let k = derive_nonce!(
+            nonce_gen => self.schnorr.nonce_gen(),
+            secret => x,
+            public => [
+                b"schnorr_fun/blind".as_ref(),
+                X,
+                id.0.to_be_bytes(),
+                (session_id.len() as u64).to_be_bytes(),
+                session_id
+            ]
+        );
+        let R = g!(k * G).normalize();
+        *slot = Some((id, k));
+        Ok((id, R))
+    }
+}
+
+/// The user's state in a blind signing session.
+///
+/// Created with [`UserSession::new`] and consumed by [`UserSession::unblind`].
+#[derive(Clone)]
+pub struct UserSession {
+    alpha: Scalar,
+    public_key: Point<EvenY>,
+    signer_nonce: Point,
+    blinded_nonce: Point<EvenY>,
+    challenge: Scalar<Public, Zero>,
+    negated: bool,
+}
+
+impl fmt::Debug for UserSession {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("UserSession")
+            .field("public_key", &self.public_key)
+            .field("challenge", &self.challenge)
+            .finish_non_exhaustive()
+    }
+}
+
+impl UserSession {
+    /// Blinds `message` for signing by the owner of `public_key` who sent `signer_nonce`.
+    ///
+    /// The session's [`challenge`](Self::challenge) is sent to the signer.
+    pub fn new<H: Digest<OutputSize = U32> + Clone, NG>(
+        schnorr: &Schnorr<H, NG>,
+        public_key: Point<EvenY>,
+        signer_nonce: Point,
+        message: Message<'_, Public>,
+        rng: &mut impl RngCore,
+    ) -> Self {
+        let X = public_key;
+        let R = signer_nonce;
+        let (alpha, beta, blinded_nonce) = loop {
+            let (alpha, beta) = (Scalar::random(rng), Scalar::random(rng));
+            // R' is only zero if the blinding factors happen to cancel out R
+            if let Some(blinded_nonce) = g!(R + alpha * G + beta * X).normalize().non_zero() {
+                break (alpha, beta, blinded_nonce);
+            }
+        };
+        let (blinded_nonce, negated) = blinded_nonce.into_point_with_even_y();
+        let challenge = schnorr.challenge(&blinded_nonce, &X, message);
+        // when R' is negated the signer's s has to be negated too which negates its challenge
+        let challenge = if negated {
+            s!(beta - challenge)
+        } else {
+            s!(challenge + beta)
+        }
+        .public();
+        Self {
+            alpha,
+            public_key,
+            signer_nonce,
+            blinded_nonce,
+            challenge,
+            negated,
+        }
+    }
+
+    /// The blinded challenge to send to the signer.
+    pub fn challenge(&self) -> Scalar<Public, Zero> {
+        self.challenge
+    }
+
+    /// Checks the signer's response and unblinds it into a BIP340 signature.
+    ///
+    /// Returns `None` if the response is invalid.
+    pub fn unblind(self, response: Scalar<Public, Zero>) -> Option<Signature> {
+        let (R, X, c, s, alpha) = (
+            self.signer_nonce,
+            self.public_key,
+            self.challenge,
+            response,
+            self.alpha,
+        );
+        if g!(s * G) != g!(R + c * X) {
+            return None;
+        }
+        let s = if self.negated {
+            s!(-s - alpha)
+        } else {
+            s!(s + alpha)
+        };
+        Some(Signature {
+            R: self.blinded_nonce,
+            s: s.public(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::nonce::{Deterministic, GlobalRng, Synthetic};
+    use rand::rngs::ThreadRng;
+    use sha2::Sha256;
+
+    #[test]
+    fn blind_sign_and_verify() {
+        let schnorr = Schnorr::<Sha256, Synthetic<Sha256, GlobalRng<ThreadRng>>>::default();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let mut signer = BlindSigner::<_, _, 2>::new(schnorr.clone(), keypair);
+        let message = Message::<Public>::plain("test", b"blind");
+        // enough sessions that both parities of R' come up
+        for i in 0..16u8 {
+            let (id, R) = signer.start_session(&[i]).unwrap();
+            let session = UserSession::new(
+                &schnorr,
+                signer.public_key(),
+                R,
+                message,
+                &mut rand::thread_rng(),
+            );
+            let s = signer.sign(id, session.challenge()).unwrap();
+            assert_eq!(
+                signer.sign(id, session.challenge()),
+                Err(BlindSignError::UnknownSession)
+            );
+            assert_eq!(session.clone().unblind(s!(s + 1).public()), None);
+            let signature = session.unblind(s).unwrap();
+            assert!(schnorr.verify(&signer.public_key(), message, &signature));
+            assert_ne!(signature.R, R);
+            assert!(!schnorr.verify(
+                &signer.public_key(),
+                Message::<Public>::plain("test", b"other"),
+                &signature
+            ));
+        }
+    }
+
+    #[test]
+    fn session_limit() {
+        let schnorr = Schnorr::<Sha256, Deterministic<Sha256>>::default();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let mut signer = BlindSigner::<_, _, 2>::new(schnorr, keypair);
+        let (first, R1) = signer.start_session(b"1").unwrap();
+        let (second, R2) = signer.start_session(b"2").unwrap();
+        assert_ne!(R1, R2);
+        assert_eq!(signer.open_sessions(), 2);
+        assert_eq!(
+            signer.start_session(b"3"),
+            Err(BlindSignError::TooManySessions)
+        );
+        assert!(signer.abandon_session(first));
+        assert!(!signer.abandon_session(first));
+        let (third, _) = signer.start_session(b"3").unwrap();
+        assert_eq!(
+            signer.sign(first, Scalar::zero()),
+            Err(BlindSignError::UnknownSession)
+        );
+        assert!(signer.sign(second, Scalar::zero()).is_ok());
+        assert!(signer.sign(third, Scalar::zero()).is_ok());
+        assert_eq!(signer.open_sessions(), 0);
+    }
+}
@@ -0,0 +1,340 @@
+//! Picking batch verification parameters for the machine the code is running on.
+//!
+//! How many signatures a [`BatchVerifier`] should check at once (and how many it takes before
+//! batching beats checking them one by one) depends on the CPU's cache sizes and how fast it
+//! multiplies points. So does the number of points at which multi-scalar multiplication should
+//! switch to Pippenger's algorithm. [`calibrate`] times multi-scalar multiplication with each
+//! algorithm at each candidate size to find the switch over point and then times batch
+//! verification of a set of valid signatures with each candidate [`BatchStrategy::group_size`].
+//! It returns a [`Calibration`] holding the fastest parameters. Run it once at startup (it takes
+//! a fraction of a second with the default options) and keep the `Calibration` around to make
+//! verifiers with, or store it (it's serde serializable) so a machine only has to calibrate once.
+//!
+//! The window width of the multi-scalar multiplication itself is fixed at compile time by the
+//! `wnaf_window_*` features of secp256kfun so it isn't chosen here.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::calibrate::{calibrate, CalibrationOptions};
+//! let calibration = calibrate::<sha2::Sha256>(&CalibrationOptions {
+//!     group_sizes: vec![1, 8, 32],
+//!     msm_sizes: vec![16, 64],
+//!     rounds: 1,
+//! });
+//! let verifier = calibration.verifier::<sha2::Sha256>();
+//! assert!([1, 8, 32].contains(&verifier.strategy().group_size));
+//! assert_eq!(verifier.strategy().msm, calibration.msm);
+//! ```
+use crate::{
+    batch::{BatchStrategy, BatchVerifier},
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        g,
+        hash::{HashAdd, Tag},
+        marker::*,
+        nonce::Deterministic,
+        op::{self, MsmContext},
+        Scalar, G,
+    },
+    Message, Schnorr,
+};
+use alloc::vec::Vec;
+use std::time::Instant;
+
+/// What [`calibrate`] measures.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CalibrationOptions {
+    /// The group sizes to try. They must all be at least 1.
+    pub group_sizes: Vec<usize>,
+    /// The numbers of points to time multi-scalar multiplication with to find where Pippenger's
+    /// algorithm gets faster. They must all be at least 1.
+    pub msm_sizes: Vec<usize>,
+    /// How many times to verify the signatures with each group size. More rounds give a more
+    /// reliable answer on a noisy machine.
+    pub rounds: usize,
+}
+
+impl Default for CalibrationOptions {
+    fn default() -> Self {
+        Self {
+            group_sizes: alloc::vec![1, 4, 16, 32, 64, 128, 256],
+            msm_sizes: alloc::vec![32, 64, 128, 256, 512],
+            rounds: 3,
+        }
+    }
+}
+
+/// How long batch verification took with one group size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct Measurement {
+    /// The group size.
+    pub group_size: usize,
+    /// The average time it took to verify each signature in nanoseconds.
+    pub nanos_per_signature: u64,
+}
+
+/// How long multi-scalar multiplication took with each algorithm for one number of points.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct MsmMeasurement {
+    /// The number of points.
+    pub points: usize,
+    /// How long the interleaved wNAF algorithm took in nanoseconds.
+    pub wnaf_nanos: u64,
+    /// How long Pippenger's algorithm took in nanoseconds.
+    pub pippenger_nanos: u64,
+}
+
+/// The parameters [`calibrate`] picked for this machine.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct Calibration {
+    /// The fastest group size.
+    pub group_size: usize,
+    /// The smallest group size that was faster per signature than checking signatures one by one
+    /// (`None` if none of them were).
+    pub batch_threshold: Option<usize>,
+    /// Every measurement in the order of [`CalibrationOptions::group_sizes`].
+    pub measurements: Vec<Measurement>,
+    /// The multi-scalar multiplication parameters. The Pippenger threshold is one less than the
+    /// smallest number of points at which Pippenger's algorithm was faster (or the default if it
+    /// never was).
+    pub msm: MsmContext,
+    /// Every multi-scalar multiplication measurement in order of the number of points.
+    pub msm_measurements: Vec<MsmMeasurement>,
+}
+
+impl Calibration {
+    /// The default [`BatchStrategy`] with the calibrated group size and multi-scalar
+    /// multiplication parameters.
+    pub fn strategy(&self) -> BatchStrategy {
+        BatchStrategy {
+            group_size: self.group_size,
+            msm: self.msm,
+            ..Default::default()
+        }
+    }
+
+    /// A verifier using [`strategy`](Self::strategy).
+    pub fn verifier<H: Digest<OutputSize = U32> + Tag + Default + Clone>(
+        &self,
+    ) -> BatchVerifier<H> {
+        BatchVerifier::new(self.strategy())
+    }
+
+    /// Whether `n` signatures are worth checking as a batch rather than one by one.
+    pub fn should_batch(&self, n: usize) -> bool {
+        self.batch_threshold
+            .map_or(false, |threshold| n >= threshold)
+    }
+}
+
+/// Measures multi-scalar multiplication with each of `options.msm_sizes` and batch verification
+/// with each of `options.group_sizes` and picks the fastest parameters.
+///
+/// # Panics
+///
+/// If `options.group_sizes` is empty, either of `options.group_sizes` or `options.msm_sizes`
+/// contains 0 or `options.rounds` is 0.
+pub fn calibrate<H: Digest<OutputSize = U32> + Tag + Default + Clone>(
+    options: &CalibrationOptions,
+) -> Calibration {
+    assert!(
+        !options.group_sizes.is_empty(),
+        "there must be a group size to try"
+    );
+    assert!(options.rounds > 0, "there must be at least one round");
+    let (msm, msm_measurements) = calibrate_msm::<H>(&options.msm_sizes, options.rounds);
+    let schnorr = Schnorr::<H, Deterministic<H>>::default();
+    let message = Message::<Public>::plain("schnorr_fun/calibrate", b"calibrate");
+    let n = options.group_sizes.iter().copied().max().unwrap_or(1);
+    let items = (1..=n as u32)
+        .map(|i| {
+            let keypair = schnorr.new_keypair(Scalar::from(i).non_zero().expect("i > 0"));
+            (
+                keypair.public_key(),
+                message,
+                schnorr.sign(&keypair, message),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let measurements = options
+        .group_sizes
+        .iter()
+        .map(|&group_size| {
+            let verifier = BatchVerifier::<H>::new(BatchStrategy {
+                group_size,
+                msm,
+                ..Default::default()
+            });
+            let start = Instant::now();
+            for _ in 0..options.rounds {
+                let report = verifier.verify(items.iter().cloned());
+                debug_assert!(report.is_valid());
+            }
+            let elapsed = start.elapsed().as_nanos() / (options.rounds * items.len()) as u128;
+            Measurement {
+                group_size,
+                nanos_per_signature: u64::try_from(elapsed).unwrap_or(u64::MAX),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let fastest = measurements
+        .iter()
+        .min_by_key(|measurement| measurement.nanos_per_signature)
+        .expect("there is at least one measurement");
+    let individual = measurements
+        .iter()
+        .find(|measurement| measurement.group_size == 1)
+        .map(|measurement| measurement.nanos_per_signature);
+    let batch_threshold = measurements
+        .iter()
+        .filter(|measurement| {
+            measurement.group_size > 1
+                && individual.map_or(true, |individual| {
+                    measurement.nanos_per_signature < individual
+                })
+        })
+        .map(|measurement| measurement.group_size)
+        .min();
+
+    Calibration {
+        group_size: fastest.group_size,
+        batch_threshold,
+        measurements,
+        msm,
+        msm_measurements,
+    }
+}
+
+/// Times multi-scalar multiplication with each algorithm to find where Pippenger's gets faster.
+fn calibrate_msm<H: Digest<OutputSize = U32> + Default>(
+    sizes: &[usize],
+    rounds: usize,
+) -> (MsmContext, Vec<MsmMeasurement>) {
+    assert!(sizes.iter().all(|&n| n > 0), "msm sizes must be at least 1");
+    let mut sizes = sizes.to_vec();
+    sizes.sort_unstable();
+    sizes.dedup();
+    let n = sizes.last().copied().unwrap_or(0);
+    // full size scalars so the timings are realistic
+    let scalars = (0..n as u32)
+        .map(|i| {
+            Scalar::from_hash(
+                H::default()
+                    .add(b"schnorr_fun/calibrate".as_slice())
+                    .add(i.to_be_bytes()),
+            )
+            .public()
+        })
+        .collect::<Vec<_>>();
+    let points = scalars
+        .iter()
+        .map(|scalar| g!(scalar * G).normalize())
+        .collect::<Vec<_>>();
+    let time = |n: usize, pippenger_threshold: usize| {
+        let msm = MsmContext {
+            pippenger_threshold,
+        };
+        let start = Instant::now();
+        for _ in 0..rounds {
+            let result = msm.point_scalar_dot_product_vartime(&scalars[..n], &points[..n]);
+            debug_assert_eq!(
+                result,
+                op::point_scalar_dot_product_vartime(&scalars[..n], &points[..n])
+            );
+        }
+        let elapsed = start.elapsed().as_nanos() / rounds as u128;
+        u64::try_from(elapsed).unwrap_or(u64::MAX)
+    };
+    let measurements = sizes
+        .iter()
+        .map(|&points| MsmMeasurement {
+            points,
+            wnaf_nanos: time(points, usize::MAX),
+            pippenger_nanos: time(points, 0),
+        })
+        .collect::<Vec<_>>();
+    let pippenger_threshold = measurements
+        .iter()
+        .find(|measurement| measurement.pippenger_nanos < measurement.wnaf_nanos)
+        .map_or(op::DEFAULT_PIPPENGER_THRESHOLD, |measurement| {
+            measurement.points - 1
+        });
+    (
+        MsmContext {
+            pippenger_threshold,
+        },
+        measurements,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+
+    #[test]
+    fn picks_a_measured_group_size() {
+        let calibration = calibrate::<Sha256>(&CalibrationOptions {
+            group_sizes: alloc::vec![1, 2, 16],
+            msm_sizes: alloc::vec![64, 8],
+            rounds: 1,
+        });
+        assert_eq!(calibration.measurements.len(), 3);
+        let fastest = calibration
+            .measurements
+            .iter()
+            .map(|measurement| measurement.nanos_per_signature)
+            .min();
+        let chosen = calibration
+            .measurements
+            .iter()
+            .find(|measurement| measurement.group_size == calibration.group_size)
+            .map(|measurement| measurement.nanos_per_signature);
+        assert_eq!(chosen, fastest);
+        assert_eq!(calibration.strategy().group_size, calibration.group_size);
+        assert_eq!(calibration.strategy().msm, calibration.msm);
+        assert_eq!(
+            calibration
+                .msm_measurements
+                .iter()
+                .map(|measurement| measurement.points)
+                .collect::<Vec<_>>(),
+            alloc::vec![8, 64]
+        );
+        let first_pippenger_win = calibration
+            .msm_measurements
+            .iter()
+            .find(|measurement| measurement.pippenger_nanos < measurement.wnaf_nanos);
+        assert_eq!(
+            calibration.msm.pippenger_threshold,
+            first_pippenger_win.map_or(op::DEFAULT_PIPPENGER_THRESHOLD, |measurement| {
+                measurement.points - 1
+            })
+        );
+        match calibration.batch_threshold {
+            Some(threshold) => {
+                assert!(calibration.should_batch(threshold));
+                assert!(!calibration.should_batch(threshold - 1));
+            }
+            None => assert!(!calibration.should_batch(1000)),
+        }
+    }
+}
@@ -0,0 +1,611 @@
+//! Canonical CBOR encodings of the interactive protocol messages.
+//!
+//! `serde` leaves the wire format up to whichever serializer you pick so two implementations that
+//! both "use serde" can easily disagree on the bytes. This module fixes a single deterministic
+//! [CBOR] encoding for every message that is sent between parties in [`musig`] and [`frost`] so
+//! that implementations in other languages can produce and check byte-identical messages.
+//!
+//! # Encoding rules
+//!
+//! Messages are encoded following the _core deterministic encoding requirements_ of
+//! [RFC 8949 §4.2.1]:
+//!
+//! - integers and lengths always use the shortest possible head
+//! - indefinite length items are never used
+//! - map keys are sorted by the bytewise lexicographic order of their encodings and are unique
+//!
+//! Decoding is strict: anything that violates these rules (or has bytes left over) is rejected
+//! with a [`CborError`] so there is exactly one valid encoding of each message. Arrays and maps of
+//! points or nonces are never empty in the schema below so empty ones are rejected too.
+//!
+//! # Schema
+//!
+//! In [CDDL]:
+//!
+//! ```text
+//! scalar          = bstr .size 32  ; big-endian, less than the curve order
+//! point           = bstr .size 33  ; SEC1 compressed (33 zero bytes for the zero point)
+//! signature       = bstr .size 64  ; BIP340 (x-only R || s)
+//! nonce           = bstr .size 66  ; two points R1 || R2
+//! party-index     = scalar         ; non-zero
+//!
+//! ; MuSig
+//! musig-nonce            = nonce
+//! musig-partial-sig      = scalar
+//! musig-partial-sig-blame = [index: uint, partial-sig: scalar, nonces: [+ nonce],
+//!                            attestation: signature]
+//!
+//! ; FROST key generation
+//! frost-keygen-broadcast = [point-poly: [+ point], proof-of-possession: signature]
+//! frost-secret-shares    = { * party-index => scalar }
+//! frost-keygen-share-blame = [accused: party-index, receiver: party-index,
+//!                             point-poly: [+ point], secret-share: scalar]
+//!
+//! ; FROST signing
+//! frost-nonce            = nonce
+//! frost-nonces           = { + party-index => nonce }
+//! frost-signature-share  = scalar
+//! frost-signature-share-blame = [index: party-index, signature-share: scalar,
+//!                                nonces: frost-nonces, attestation: signature]
+//! ```
+//!
+//! The Rust type for each message is:
+//!
+//! | message                       | type                                    |
+//! |-------------------------------|-----------------------------------------|
+//! | `musig-nonce`, `frost-nonce`  | [`binonce::Nonce`]                      |
+//! | `musig-partial-sig`, `frost-signature-share` | `Scalar<Public, Zero>`   |
+//! | `musig-partial-sig-blame`     | [`musig::PartialSignatureBlame`]        |
+//! | `frost-keygen-broadcast`      | `(Vec<Point>, Signature)`               |
+//! | `frost-secret-shares`         | `BTreeMap<PartyIndex, Scalar<Secret, Zero>>` |
+//! | `frost-keygen-share-blame`    | [`frost::KeyGenShareBlame`]             |
+//! | `frost-nonces`                | `BTreeMap<PartyIndex, Nonce>`           |
+//! | `frost-signature-share-blame` | [`frost::SignatureShareBlame`]          |
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{binonce::NonceKeyPair, cbor};
+//! let nonce = NonceKeyPair::random(&mut rand::thread_rng()).public();
+//! let bytes = cbor::to_vec(&nonce);
+//! assert_eq!(bytes.len(), 2 + 66);
+//! assert_eq!(cbor::from_slice::<schnorr_fun::binonce::Nonce>(&bytes), Ok(nonce));
+//! ```
+//!
+//! [CBOR]: https://www.rfc-editor.org/rfc/rfc8949.html
+//! [RFC 8949 §4.2.1]: https://www.rfc-editor.org/rfc/rfc8949.html#section-4.2.1
+//! [CDDL]: https://www.rfc-editor.org/rfc/rfc8610.html
+//! [`musig`]: crate::musig
+//! [`frost`]: crate::frost
+//! [`binonce::Nonce`]: crate::binonce::Nonce
+//! [`musig::PartialSignatureBlame`]: crate::musig::PartialSignatureBlame
+//! [`frost::KeyGenShareBlame`]: crate::frost::KeyGenShareBlame
+//! [`frost::SignatureShareBlame`]: crate::frost::SignatureShareBlame
+use crate::{
+    binonce::Nonce,
+    frost::{KeyGenShareBlame, SignatureShareBlame},
+    fun::{marker::*, Point, Scalar},
+    musig::PartialSignatureBlame,
+    Signature,
+};
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::fmt;
+
+const MAJOR_UINT: u8 = 0;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_MAP: u8 = 5;
+
+/// A type with a canonical CBOR encoding.
+///
+/// See the [module documentation](crate::cbor) for the encoding of each type. You'll usually want
+/// [`to_vec`] and [`from_slice`] rather than calling these methods directly.
+pub trait CanonicalCbor: Sized {
+    /// Appends the encoding of `self` to `out`.
+    fn encode_cbor(&self, out: &mut Vec<u8>);
+    /// Reads a value from the front of `decoder`.
+    fn decode_cbor(decoder: &mut Decoder<'_>) -> Result<Self, CborError>;
+    /// Whether an array of this type or a map with values of this type must have at least one
+    /// element. Decoding an empty one fails with [`CborError::Empty`].
+    const NON_EMPTY: bool = false;
+}
+
+/// Encode `value` as canonical CBOR.
+pub fn to_vec<T: CanonicalCbor>(value: &T) -> Vec<u8> {
+    let mut out = vec![];
+    value.encode_cbor(&mut out);
+    out
+}
+
+/// Decode a value from canonical CBOR.
+///
+/// The whole of `bytes` must be a single canonically encoded value.
+pub fn from_slice<T: CanonicalCbor>(bytes: &[u8]) -> Result<T, CborError> {
+    let mut decoder = Decoder::new(bytes);
+    let value = T::decode_cbor(&mut decoder)?;
+    if !decoder.is_empty() {
+        return Err(CborError::TrailingBytes);
+    }
+    Ok(value)
+}
+
+/// Error decoding canonical CBOR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CborError {
+    /// The input ended in the middle of an item.
+    UnexpectedEnd,
+    /// There were bytes left over after the value was decoded.
+    TrailingBytes,
+    /// An item had a different major type to what the schema requires.
+    UnexpectedType {
+        /// The major type the schema requires.
+        expected: u8,
+        /// The major type that was found.
+        got: u8,
+    },
+    /// An item had the wrong length for its position in the schema.
+    WrongLength {
+        /// The length the schema requires.
+        expected: usize,
+        /// The length that was found.
+        got: usize,
+    },
+    /// An array or map that the schema requires to be non-empty was empty.
+    Empty,
+    /// The encoding isn't canonical (non-minimal head, indefinite length or unsorted/duplicate
+    /// map keys).
+    NonCanonical,
+    /// The bytes don't encode a valid scalar, point, signature or nonce.
+    InvalidValue,
+}
+
+impl fmt::Display for CborError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CborError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            CborError::TrailingBytes => write!(f, "trailing bytes after value"),
+            CborError::UnexpectedType { expected, got } => {
+                write!(f, "expected CBOR major type {} but got {}", expected, got)
+            }
+            CborError::WrongLength { expected, got } => {
+                write!(f, "expected length {} but got {}", expected, got)
+            }
+            CborError::Empty => write!(f, "array or map must not be empty"),
+            CborError::NonCanonical => write!(f, "encoding is not canonical"),
+            CborError::InvalidValue => write!(f, "bytes do not encode a valid value"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CborError {}
+
+/// Reads canonical CBOR items from a byte slice.
+#[derive(Debug, Clone)]
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    /// Create a decoder reading from the start of `bytes`.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Whether all the input has been read.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], CborError> {
+        if self.bytes.len() < len {
+            return Err(CborError::UnexpectedEnd);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    /// Reads an item head with major type `major` and returns its argument.
+    ///
+    /// Errors if the argument wasn't encoded in the smallest possible number of bytes.
+    pub fn read_head(&mut self, major: u8) -> Result<u64, CborError> {
+        let initial = self.take(1)?[0];
+        let got = initial >> 5;
+        if got != major {
+            return Err(CborError::UnexpectedType {
+                expected: major,
+                got,
+            });
+        }
+        let (value, min) = match initial & 0x1f {
+            info @ 0..=23 => return Ok(info as u64),
+            24 => (self.take(1)?[0] as u64, 24),
+            25 => (
+                u16::from_be_bytes(self.take(2)?.try_into().unwrap()) as u64,
+                1 << 8,
+            ),
+            26 => (
+                u32::from_be_bytes(self.take(4)?.try_into().unwrap()) as u64,
+                1 << 16,
+            ),
+            27 => (
+                u64::from_be_bytes(self.take(8)?.try_into().unwrap()),
+                1 << 32,
+            ),
+            // reserved values and indefinite lengths
+            _ => return Err(CborError::NonCanonical),
+        };
+        if value < min {
+            return Err(CborError::NonCanonical);
+        }
+        Ok(value)
+    }
+
+    fn read_len(&mut self, major: u8) -> Result<usize, CborError> {
+        let len = self.read_head(major)?;
+        // a length longer than the input can't be valid so there's no point going further
+        if len > self.bytes.len() as u64 {
+            return Err(CborError::UnexpectedEnd);
+        }
+        Ok(len as usize)
+    }
+
+    /// Reads the length of an array.
+    pub fn read_array_len(&mut self) -> Result<usize, CborError> {
+        self.read_len(MAJOR_ARRAY)
+    }
+
+    /// Reads an array header and checks it has `expected` elements.
+    pub fn read_array_exact(&mut self, expected: usize) -> Result<(), CborError> {
+        let got = self.read_array_len()?;
+        if got != expected {
+            return Err(CborError::WrongLength { expected, got });
+        }
+        Ok(())
+    }
+
+    /// Reads the number of entries in a map.
+    pub fn read_map_len(&mut self) -> Result<usize, CborError> {
+        self.read_len(MAJOR_MAP)
+    }
+
+    /// Reads a byte string of exactly `N` bytes.
+    pub fn read_byte_array<const N: usize>(&mut self) -> Result<[u8; N], CborError> {
+        let got = self.read_len(MAJOR_BYTES)?;
+        if got != N {
+            return Err(CborError::WrongLength { expected: N, got });
+        }
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+}
+
+/// Writes the head of an item using the shortest encoding of `value`.
+fn write_head(out: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    if value < 24 {
+        out.push(major | value as u8);
+    } else if value <= u8::MAX as u64 {
+        out.push(major | 24);
+        out.push(value as u8);
+    } else if value <= u16::MAX as u64 {
+        out.push(major | 25);
+        out.extend_from_slice(&(value as u16).to_be_bytes());
+    } else if value <= u32::MAX as u64 {
+        out.push(major | 26);
+        out.extend_from_slice(&(value as u32).to_be_bytes());
+    } else {
+        out.push(major | 27);
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_head(out, MAJOR_BYTES, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+impl CanonicalCbor for u64 {
+    fn encode_cbor(&self, out: &mut Vec<u8>) {
+        write_head(out, MAJOR_UINT, *self);
+    }
+
+    fn decode_cbor(decoder: &mut Decoder<'_>) -> Result<Self, CborError> {
+        decoder.read_head(MAJOR_UINT)
+    }
+}
+
+impl CanonicalCbor for usize {
+    fn encode_cbor(&self, out: &mut Vec<u8>) {
+        (*self as u64).encode_cbor(out)
+    }
+
+    fn decode_cbor(decoder: &mut Decoder<'_>) -> Result<Self, CborError> {
+        usize::try_from(u64::decode_cbor(decoder)?).map_err(|_| CborError::InvalidValue)
+    }
+}
+
+impl<S, Z: ZeroChoice> CanonicalCbor for Scalar<S, Z> {
+    fn encode_cbor(&self, out: &mut Vec<u8>) {
+        write_bytes(out, self.to_secret_bytes().expose_secret())
+    }
+
+    fn decode_cbor(decoder: &mut Decoder<'_>) -> Result<Self, CborError> {
+        Scalar::from_bytes(decoder.read_byte_array()?).ok_or(CborError::InvalidValue)
+    }
+}
+
+impl<Z: ZeroChoice> CanonicalCbor for Point<Normal, Public, Z> {
+    fn encode_cbor(&self, out: &mut Vec<u8>) {
+        write_bytes(out, &self.to_bytes())
+    }
+
+    fn decode_cbor(decoder: &mut Decoder<'_>) -> Result<Self, CborError> {
+        Point::from_bytes(decoder.read_byte_array()?).ok_or(CborError::InvalidValue)
+    }
+
+    // points only come in polynomials
+    const NON_EMPTY: bool = true;
+}
+
+impl CanonicalCbor for Signature {
+    fn encode_cbor(&self, out: &mut Vec<u8>) {
+        write_bytes(out, &self.to_bytes())
+    }
+
+    fn decode_cbor(decoder: &mut Decoder<'_>) -> Result<Self, CborError> {
+        Signature::from_bytes(decoder.read_byte_array()?).ok_or(CborError::InvalidValue)
+    }
+}
+
+impl<Z: ZeroChoice> CanonicalCbor for Nonce<Z> {
+    fn encode_cbor(&self, out: &mut Vec<u8>) {
+        write_bytes(out, &self.to_bytes())
+    }
+
+    fn decode_cbor(decoder: &mut Decoder<'_>) -> Result<Self, CborError> {
+        Nonce::from_bytes(decoder.read_byte_array()?).ok_or(CborError::InvalidValue)
+    }
+
+    // nonces only come in the nonces of a signing session
+    const NON_EMPTY: bool = true;
+}
+
+impl<T: CanonicalCbor> CanonicalCbor for Vec<T> {
+    fn encode_cbor(&self, out: &mut Vec<u8>) {
+        write_head(out, MAJOR_ARRAY, self.len() as u64);
+        for item in self {
+            item.encode_cbor(out);
+        }
+    }
+
+    fn decode_cbor(decoder: &mut Decoder<'_>) -> Result<Self, CborError> {
+        let len = decoder.read_array_len()?;
+        if len == 0 && T::NON_EMPTY {
+            return Err(CborError::Empty);
+        }
+        (0..len).map(|_| T::decode_cbor(decoder)).collect()
+    }
+}
+
+impl<A: CanonicalCbor, B: CanonicalCbor> CanonicalCbor for (A, B) {
+    fn encode_cbor(&self, out: &mut Vec<u8>) {
+        write_head(out, MAJOR_ARRAY, 2);
+        self.0.encode_cbor(out);
+        self.1.encode_cbor(out);
+    }
+
+    fn decode_cbor(decoder: &mut Decoder<'_>) -> Result<Self, CborError> {
+        decoder.read_array_exact(2)?;
+        Ok((A::decode_cbor(decoder)?, B::decode_cbor(decoder)?))
+    }
+}
+
+impl<K: CanonicalCbor + Ord, V: CanonicalCbor> CanonicalCbor for BTreeMap<K, V> {
+    fn encode_cbor(&self, out: &mut Vec<u8>) {
+        // Canonical order is by the encoding of the key which need not agree with `K`'s `Ord`.
+        let mut entries = self
+            .iter()
+            .map(|(key, value)| (to_vec(key), value))
+            .collect::<Vec<_>>();
+        entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        write_head(out, MAJOR_MAP, entries.len() as u64);
+        for (key, value) in entries {
+            out.extend_from_slice(&key);
+            value.encode_cbor(out);
+        }
+    }
+
+    fn decode_cbor(decoder: &mut Decoder<'_>) -> Result<Self, CborError> {
+        let len = decoder.read_map_len()?;
+        if len == 0 && V::NON_EMPTY {
+            return Err(CborError::Empty);
+        }
+        let mut map = BTreeMap::new();
+        let mut prev_key: Option<&[u8]> = None;
+        for _ in 0..len {
+            let before = decoder.bytes;
+            let key = K::decode_cbor(decoder)?;
+            let key_bytes = &before[..before.len() - decoder.bytes.len()];
+            if let Some(prev_key) = prev_key {
+                if prev_key >= key_bytes {
+                    return Err(CborError::NonCanonical);
+                }
+            }
+            prev_key = Some(key_bytes);
+            let value = V::decode_cbor(decoder)?;
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+}
+
+impl CanonicalCbor for PartialSignatureBlame {
+    fn encode_cbor(&self, out: &mut Vec<u8>) {
+        write_head(out, MAJOR_ARRAY, 4);
+        self.index.encode_cbor(out);
+        self.partial_sig.encode_cbor(out);
+        self.nonces.encode_cbor(out);
+        self.attestation.encode_cbor(out);
+    }
+
+    fn decode_cbor(decoder: &mut Decoder<'_>) -> Result<Self, CborError> {
+        decoder.read_array_exact(4)?;
+        Ok(PartialSignatureBlame {
+            index: CanonicalCbor::decode_cbor(decoder)?,
+            partial_sig: CanonicalCbor::decode_cbor(decoder)?,
+            nonces: CanonicalCbor::decode_cbor(decoder)?,
+            attestation: CanonicalCbor::decode_cbor(decoder)?,
+        })
+    }
+}
+
+impl CanonicalCbor for KeyGenShareBlame {
+    fn encode_cbor(&self, out: &mut Vec<u8>) {
+        write_head(out, MAJOR_ARRAY, 4);
+        self.accused.encode_cbor(out);
+        self.receiver.encode_cbor(out);
+        self.point_poly.encode_cbor(out);
+        self.secret_share.encode_cbor(out);
+    }
+
+    fn decode_cbor(decoder: &mut Decoder<'_>) -> Result<Self, CborError> {
+        decoder.read_array_exact(4)?;
+        Ok(KeyGenShareBlame {
+            accused: CanonicalCbor::decode_cbor(decoder)?,
+            receiver: CanonicalCbor::decode_cbor(decoder)?,
+            point_poly: CanonicalCbor::decode_cbor(decoder)?,
+            secret_share: CanonicalCbor::decode_cbor(decoder)?,
+        })
+    }
+}
+
+impl CanonicalCbor for SignatureShareBlame {
+    fn encode_cbor(&self, out: &mut Vec<u8>) {
+        write_head(out, MAJOR_ARRAY, 4);
+        self.index.encode_cbor(out);
+        self.signature_share.encode_cbor(out);
+        self.nonces.encode_cbor(out);
+        self.attestation.encode_cbor(out);
+    }
+
+    fn decode_cbor(decoder: &mut Decoder<'_>) -> Result<Self, CborError> {
+        decoder.read_array_exact(4)?;
+        Ok(SignatureShareBlame {
+            index: CanonicalCbor::decode_cbor(decoder)?,
+            signature_share: CanonicalCbor::decode_cbor(decoder)?,
+            nonces: CanonicalCbor::decode_cbor(decoder)?,
+            attestation: CanonicalCbor::decode_cbor(decoder)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frost::PartyIndex;
+
+    #[test]
+    fn heads_are_minimal() {
+        for (value, expected) in [
+            (0u64, &[0x00][..]),
+            (23, &[0x17]),
+            (24, &[0x18, 24]),
+            (255, &[0x18, 0xff]),
+            (256, &[0x19, 0x01, 0x00]),
+            (65536, &[0x1a, 0x00, 0x01, 0x00, 0x00]),
+            (1 << 32, &[0x1b, 0, 0, 0, 1, 0, 0, 0, 0]),
+        ] {
+            assert_eq!(to_vec(&value), expected);
+            assert_eq!(from_slice::<u64>(expected), Ok(value));
+        }
+        assert_eq!(from_slice::<u64>(&[0x18, 23]), Err(CborError::NonCanonical));
+        assert_eq!(
+            from_slice::<u64>(&[0x19, 0x00, 0xff]),
+            Err(CborError::NonCanonical)
+        );
+        // indefinite length array
+        assert_eq!(
+            from_slice::<Vec<u64>>(&[0x9f, 0x00, 0xff]),
+            Err(CborError::NonCanonical)
+        );
+        assert_eq!(
+            from_slice::<u64>(&[0x00, 0x00]),
+            Err(CborError::TrailingBytes)
+        );
+    }
+
+    #[test]
+    fn map_keys_must_be_sorted_and_unique() {
+        let one = Scalar::<Public, Zero>::from(1);
+        let two = Scalar::<Public, Zero>::from(2);
+        let map = BTreeMap::from_iter([(two, 0u64), (one, 0u64)]);
+        let encoded = to_vec(&map);
+        assert_eq!(from_slice(&encoded), Ok(map));
+
+        let entry = |scalar: Scalar<Public, Zero>| {
+            let mut entry = to_vec(&scalar);
+            entry.push(0x00);
+            entry
+        };
+        let mut unsorted = vec![0xa2];
+        unsorted.extend(entry(two));
+        unsorted.extend(entry(one));
+        assert_eq!(
+            from_slice::<BTreeMap<Scalar<Public, Zero>, u64>>(&unsorted),
+            Err(CborError::NonCanonical)
+        );
+
+        let mut duplicate = vec![0xa2];
+        duplicate.extend(entry(one));
+        duplicate.extend(entry(one));
+        assert_eq!(
+            from_slice::<BTreeMap<Scalar<Public, Zero>, u64>>(&duplicate),
+            Err(CborError::NonCanonical)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        let mut bad_scalar = vec![0x58, 32];
+        bad_scalar.extend([0xff; 32]);
+        assert_eq!(
+            from_slice::<Scalar<Public, Zero>>(&bad_scalar),
+            Err(CborError::InvalidValue)
+        );
+        let mut short = vec![0x58, 31];
+        short.extend([0x01; 31]);
+        assert_eq!(
+            from_slice::<Scalar<Public, Zero>>(&short),
+            Err(CborError::WrongLength {
+                expected: 32,
+                got: 31
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_empty_where_schema_requires_elements() {
+        let signature = to_vec(&Signature {
+            R: Point::<Normal>::generator().into_point_with_even_y().0,
+            s: Scalar::zero(),
+        });
+        let mut keygen_broadcast = vec![0x82, 0x80];
+        keygen_broadcast.extend(&signature);
+        assert_eq!(
+            from_slice::<(Vec<Point>, Signature)>(&keygen_broadcast),
+            Err(CborError::Empty)
+        );
+        assert_eq!(
+            from_slice::<BTreeMap<PartyIndex, Nonce>>(&[0xa0]),
+            Err(CborError::Empty)
+        );
+        assert_eq!(from_slice::<Vec<Nonce>>(&[0x80]), Err(CborError::Empty));
+        // a party may get no secret shares
+        assert_eq!(
+            from_slice::<BTreeMap<PartyIndex, Scalar<Secret, Zero>>>(&[0xa0]),
+            Ok(BTreeMap::new())
+        );
+        assert_eq!(from_slice::<Vec<u64>>(&[0x80]), Ok(vec![]));
+    }
+}
@@ -0,0 +1,284 @@
+//! Certificates where one key vouches for another.
+//!
+//! A [`Certificate`] is the statement "`issuer` certifies `subject` for `purpose` until
+//! `expires_at`" signed by the issuer. They are the building block of federation membership lists
+//! and onboarding new signers: a coordinator key certifies each member's key and anyone who trusts
+//! the coordinator can check that a key belongs to a member.
+//!
+//! The subject can be any public key (not just a BIP340 one). `expires_at` is a time in whatever
+//! unit the application uses (e.g. UNIX seconds or a block height) and the certificate is valid
+//! while the current time is less than it.
+//!
+//! The statement the issuer signs is the canonical encoding of everything but the signature (see
+//! [`Certificate::to_bytes`]) so there is exactly one encoding of each certificate.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     cert::{Certificate, CertError},
+//!     fun::{Point, Scalar},
+//! };
+//! let schnorr = schnorr_fun::test_instance!();
+//! let coordinator = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let member = Point::random(&mut rand::thread_rng());
+//! let certificate = schnorr
+//!     .certify(&coordinator, member, "federation-member", 1_700_000_000)
+//!     .unwrap();
+//!
+//! // anyone who trusts the coordinator can check a member's certificate
+//! let certificate = Certificate::from_bytes(&certificate.to_bytes()).unwrap();
+//! let issuer = coordinator.public_key();
+//! assert_eq!(
+//!     schnorr.verify_certificate(&certificate, &issuer, "federation-member", 1_600_000_000),
+//!     Ok(member)
+//! );
+//! assert_eq!(
+//!     schnorr.verify_certificate(&certificate, &issuer, "federation-member", 1_800_000_000),
+//!     Err(CertError::Expired)
+//! );
+//! ```
+use crate::{Message, Schnorr, Signature};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    marker::*,
+    nonce::NonceGen,
+    KeyPair, Point,
+};
+
+/// The application tag of the [`Message`] a certificate's statement is signed as.
+pub const CERT_APP_TAG: &str = "schnorr_fun/cert";
+
+/// The longest `purpose` a certificate can have in bytes.
+pub const MAX_PURPOSE_LEN: usize = 255;
+
+const VERSION: u8 = 0;
+
+/// A signed statement that `issuer` certifies `subject` for `purpose` until `expires_at`.
+///
+/// See the [module documentation](crate::cert).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Certificate {
+    /// The key vouching for the subject.
+    pub issuer: Point<EvenY>,
+    /// The key being certified.
+    pub subject: Point,
+    /// What the subject is certified for.
+    pub purpose: String,
+    /// The time from which the certificate is no longer valid.
+    pub expires_at: u64,
+    /// The issuer's signature on the statement.
+    pub signature: Signature,
+}
+
+impl Certificate {
+    /// The canonical encoding of the certificate.
+    ///
+    /// It is a version byte (`0`), the 32 byte issuer, the 33 byte subject, `expires_at` as 8 big
+    /// endian bytes, the length of `purpose` as a byte, `purpose` and finally the 64 byte
+    /// signature. The signature is on everything that comes before it.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes =
+            statement_bytes(&self.issuer, &self.subject, &self.purpose, self.expires_at);
+        bytes.extend_from_slice(&self.signature.to_bytes());
+        bytes
+    }
+
+    /// Decodes a certificate from its [canonical encoding](Self::to_bytes).
+    ///
+    /// This doesn't check the signature.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CertError> {
+        let (version, rest) = bytes.split_first().ok_or(CertError::Malformed)?;
+        if *version != VERSION || rest.len() < 32 + 33 + 8 + 1 + 64 {
+            return Err(CertError::Malformed);
+        }
+        let (issuer, rest) = rest.split_at(32);
+        let (subject, rest) = rest.split_at(33);
+        let (expires_at, rest) = rest.split_at(8);
+        let (purpose_len, rest) = rest.split_first().expect("checked length");
+        if rest.len() != *purpose_len as usize + 64 {
+            return Err(CertError::Malformed);
+        }
+        let (purpose, signature) = rest.split_at(*purpose_len as usize);
+        Ok(Certificate {
+            issuer: Point::from_xonly_bytes(issuer.try_into().expect("32 bytes"))
+                .ok_or(CertError::Malformed)?,
+            subject: Point::from_bytes(subject.try_into().expect("33 bytes"))
+                .ok_or(CertError::Malformed)?,
+            purpose: String::from_utf8(purpose.to_vec()).map_err(|_| CertError::Malformed)?,
+            expires_at: u64::from_be_bytes(expires_at.try_into().expect("8 bytes")),
+            signature: Signature::from_bytes(signature.try_into().expect("64 bytes"))
+                .ok_or(CertError::Malformed)?,
+        })
+    }
+}
+
+fn statement_bytes(
+    issuer: &Point<EvenY>,
+    subject: &Point,
+    purpose: &str,
+    expires_at: u64,
+) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(1 + 32 + 33 + 8 + 1 + purpose.len() + 64);
+    bytes.push(VERSION);
+    bytes.extend_from_slice(&issuer.to_xonly_bytes());
+    bytes.extend_from_slice(&subject.to_bytes());
+    bytes.extend_from_slice(&expires_at.to_be_bytes());
+    bytes.push(purpose.len() as u8);
+    bytes.extend_from_slice(purpose.as_bytes());
+    bytes
+}
+
+impl<CH: Digest<OutputSize = U32> + Clone, NG: NonceGen> Schnorr<CH, NG> {
+    /// Creates a certificate that `issuer` certifies `subject` for `purpose` until `expires_at`.
+    ///
+    /// Returns [`CertError::PurposeTooLong`] if `purpose` is longer than [`MAX_PURPOSE_LEN`]
+    /// bytes.
+    pub fn certify(
+        &self,
+        issuer: &KeyPair<EvenY>,
+        subject: Point,
+        purpose: &str,
+        expires_at: u64,
+    ) -> Result<Certificate, CertError> {
+        if purpose.len() > MAX_PURPOSE_LEN {
+            return Err(CertError::PurposeTooLong);
+        }
+        let statement = statement_bytes(&issuer.public_key(), &subject, purpose, expires_at);
+        let signature = self.sign(issuer, Message::<Public>::plain(CERT_APP_TAG, &statement));
+        Ok(Certificate {
+            issuer: issuer.public_key(),
+            subject,
+            purpose: purpose.into(),
+            expires_at,
+            signature,
+        })
+    }
+}
+
+impl<CH: Digest<OutputSize = U32> + Clone, NG> Schnorr<CH, NG> {
+    /// Checks that `certificate` was issued by `issuer` for `purpose` and hasn't expired at `now`
+    /// returning the certified key.
+    pub fn verify_certificate(
+        &self,
+        certificate: &Certificate,
+        issuer: &Point<EvenY>,
+        purpose: &str,
+        now: u64,
+    ) -> Result<Point, CertError> {
+        if certificate.issuer != *issuer {
+            return Err(CertError::WrongIssuer);
+        }
+        if certificate.purpose != purpose {
+            return Err(CertError::WrongPurpose);
+        }
+        if certificate.purpose.len() > MAX_PURPOSE_LEN {
+            return Err(CertError::PurposeTooLong);
+        }
+        let statement = statement_bytes(
+            &certificate.issuer,
+            &certificate.subject,
+            &certificate.purpose,
+            certificate.expires_at,
+        );
+        if !self.verify(
+            issuer,
+            Message::<Public>::plain(CERT_APP_TAG, &statement),
+            &certificate.signature,
+        ) {
+            return Err(CertError::InvalidSignature);
+        }
+        if now >= certificate.expires_at {
+            return Err(CertError::Expired);
+        }
+        Ok(certificate.subject)
+    }
+}
+
+/// Error returned when creating, decoding or verifying a [`Certificate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CertError {
+    /// The purpose is longer than [`MAX_PURPOSE_LEN`] bytes.
+    PurposeTooLong,
+    /// The bytes aren't a canonically encoded certificate.
+    Malformed,
+    /// The certificate was issued by a different key.
+    WrongIssuer,
+    /// The certificate is for a different purpose.
+    WrongPurpose,
+    /// The issuer's signature is invalid.
+    InvalidSignature,
+    /// The certificate has expired.
+    Expired,
+}
+
+impl fmt::Display for CertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CertError::PurposeTooLong => write!(
+                f,
+                "certificate purpose is longer than {MAX_PURPOSE_LEN} bytes"
+            ),
+            CertError::Malformed => write!(f, "malformed certificate"),
+            CertError::WrongIssuer => write!(f, "certificate was issued by a different key"),
+            CertError::WrongPurpose => write!(f, "certificate is for a different purpose"),
+            CertError::InvalidSignature => write!(f, "certificate signature is invalid"),
+            CertError::Expired => write!(f, "certificate has expired"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CertError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use secp256kfun::Scalar;
+
+    #[test]
+    fn certificates_are_bound_to_their_statement() {
+        let schnorr = crate::test_instance!();
+        let issuer = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let other_issuer = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let subject = Point::random(&mut rand::thread_rng());
+        let certificate = schnorr.certify(&issuer, subject, "signer", 10).unwrap();
+        let verify = |certificate: &Certificate, issuer: &KeyPair<EvenY>, purpose, now| {
+            schnorr.verify_certificate(certificate, &issuer.public_key(), purpose, now)
+        };
+
+        assert_eq!(verify(&certificate, &issuer, "signer", 9), Ok(subject));
+        assert_eq!(
+            verify(&certificate, &issuer, "signer", 10),
+            Err(CertError::Expired)
+        );
+        assert_eq!(
+            verify(&certificate, &issuer, "admin", 9),
+            Err(CertError::WrongPurpose)
+        );
+        assert_eq!(
+            verify(&certificate, &other_issuer, "signer", 9),
+            Err(CertError::WrongIssuer)
+        );
+
+        let mut extended = certificate.clone();
+        extended.expires_at = 20;
+        assert_eq!(
+            verify(&extended, &issuer, "signer", 9),
+            Err(CertError::InvalidSignature)
+        );
+
+        let bytes = certificate.to_bytes();
+        assert_eq!(Certificate::from_bytes(&bytes), Ok(certificate));
+        assert_eq!(
+            Certificate::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(CertError::Malformed)
+        );
+        assert_eq!(
+            schnorr.certify(&issuer, subject, &"x".repeat(MAX_PURPOSE_LEN + 1), 10),
+            Err(CertError::PurposeTooLong)
+        );
+    }
+}
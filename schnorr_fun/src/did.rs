@@ -0,0 +1,221 @@
+//! `did:key` and multibase encodings of secp256k1 public keys.
+//!
+//! Decentralized identity stacks refer to keys by their [multicodec] `secp256k1-pub` encoding
+//! (the varint `0xe7` followed by the 33-byte compressed point) written in base58btc [multibase]
+//! (a leading `z`). Prefixed with `did:key:` this is a [`did:key`] identifier and on its own it is
+//! the `publicKeyMultibase` of a DID document verification method.
+//!
+//! A BIP340 (x-only) public key is encoded as the point with an even y-coordinate so the same key
+//! can be used for Bitcoin and DID applications. Encoding a [`Point<EvenY>`] gives the same result
+//! as encoding it as an ordinary point. When decoding, [`xonly_from_did_key`] and
+//! [`xonly_from_multibase`] reject keys with an odd y-coordinate since the x-only key with the
+//! same x-coordinate is a different key (its negation).
+//!
+//! For JWKs use `ecdsa_fun`'s `jose` module which encodes the same points.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{did, fun::Scalar};
+//! let schnorr = schnorr_fun::test_instance!();
+//! let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let did = did::to_did_key(&keypair.public_key());
+//! assert!(did.starts_with("did:key:zQ3s"));
+//! assert_eq!(did::xonly_from_did_key(&did), Ok(keypair.public_key()));
+//! ```
+//!
+//! [multicodec]: https://github.com/multiformats/multicodec
+//! [multibase]: https://github.com/multiformats/multibase
+//! [`did:key`]: https://w3c-ccg.github.io/did-key-spec/
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+use secp256kfun::{marker::*, Point};
+
+/// The varint encoding of the `secp256k1-pub` multicodec (`0xe7`).
+const SECP256K1_PUB: [u8; 2] = [0xe7, 0x01];
+const DID_KEY_PREFIX: &str = "did:key:";
+const BASE58BTC: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Error returned when a `did:key` or multibase key can't be decoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DidKeyError {
+    /// The string isn't a `did:key` or base58btc multibase string.
+    Malformed,
+    /// The multicodec isn't `secp256k1-pub`.
+    UnsupportedKeyType,
+    /// The bytes aren't a valid compressed point.
+    InvalidPoint,
+    /// An x-only key was expected but the point has an odd y-coordinate.
+    OddY,
+}
+
+impl fmt::Display for DidKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DidKeyError::Malformed => write!(f, "not a base58btc did:key or multibase key"),
+            DidKeyError::UnsupportedKeyType => write!(f, "key is not a secp256k1 public key"),
+            DidKeyError::InvalidPoint => write!(f, "key is not a valid secp256k1 point"),
+            DidKeyError::OddY => write!(f, "key has an odd y-coordinate so it isn't x-only"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DidKeyError {}
+
+fn base58_encode(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+    // little endian base 58 digits
+    let mut digits = Vec::<u8>::with_capacity(bytes.len() * 138 / 100 + 1);
+    for byte in &bytes[zeros..] {
+        let mut carry = *byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    core::iter::repeat('1')
+        .take(zeros)
+        .chain(
+            digits
+                .iter()
+                .rev()
+                .map(|digit| BASE58BTC[*digit as usize] as char),
+        )
+        .collect()
+}
+
+fn base58_decode(encoded: &str) -> Option<Vec<u8>> {
+    let zeros = encoded.bytes().take_while(|c| *c == b'1').count();
+    // little endian bytes
+    let mut bytes = Vec::<u8>::with_capacity(encoded.len());
+    for c in encoded.bytes().skip(zeros) {
+        let mut carry = BASE58BTC.iter().position(|b| *b == c)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    bytes.extend(core::iter::repeat(0).take(zeros));
+    bytes.reverse();
+    Some(bytes)
+}
+
+/// Encodes `public_key` as a base58btc multibase `secp256k1-pub` key (e.g. for
+/// `publicKeyMultibase`).
+pub fn to_multibase<T: Normalized>(public_key: &Point<T, impl Secrecy>) -> String {
+    let mut bytes = Vec::with_capacity(35);
+    bytes.extend_from_slice(&SECP256K1_PUB);
+    bytes.extend_from_slice(&public_key.to_bytes());
+    let mut multibase = String::from("z");
+    multibase.push_str(&base58_encode(&bytes));
+    multibase
+}
+
+/// Decodes a base58btc multibase `secp256k1-pub` key.
+pub fn from_multibase(multibase: &str) -> Result<Point, DidKeyError> {
+    let encoded = multibase.strip_prefix('z').ok_or(DidKeyError::Malformed)?;
+    let bytes = base58_decode(encoded).ok_or(DidKeyError::Malformed)?;
+    let point = bytes
+        .strip_prefix(&SECP256K1_PUB[..])
+        .ok_or(DidKeyError::UnsupportedKeyType)?;
+    let point = <[u8; 33]>::try_from(point).map_err(|_| DidKeyError::InvalidPoint)?;
+    Point::from_bytes(point).ok_or(DidKeyError::InvalidPoint)
+}
+
+/// Decodes a base58btc multibase `secp256k1-pub` key that must have an even y-coordinate.
+pub fn xonly_from_multibase(multibase: &str) -> Result<Point<EvenY>, DidKeyError> {
+    into_xonly(from_multibase(multibase)?)
+}
+
+/// Encodes `public_key` as a `did:key`.
+pub fn to_did_key<T: Normalized>(public_key: &Point<T, impl Secrecy>) -> String {
+    let mut did = String::from(DID_KEY_PREFIX);
+    did.push_str(&to_multibase(public_key));
+    did
+}
+
+/// Decodes a `did:key` with a `secp256k1-pub` key.
+///
+/// A DID URL of the key's verification method (`did:key:z...#z...`) is also accepted as long as
+/// the fragment is the same key.
+pub fn from_did_key(did: &str) -> Result<Point, DidKeyError> {
+    let multibase = did
+        .strip_prefix(DID_KEY_PREFIX)
+        .ok_or(DidKeyError::Malformed)?;
+    let multibase = match multibase.split_once('#') {
+        Some((multibase, fragment)) if fragment == multibase => multibase,
+        Some(_) => return Err(DidKeyError::Malformed),
+        None => multibase,
+    };
+    from_multibase(multibase)
+}
+
+/// Decodes a `did:key` with a `secp256k1-pub` key that must have an even y-coordinate.
+pub fn xonly_from_did_key(did: &str) -> Result<Point<EvenY>, DidKeyError> {
+    into_xonly(from_did_key(did)?)
+}
+
+fn into_xonly(point: Point) -> Result<Point<EvenY>, DidKeyError> {
+    match point.into_point_with_even_y() {
+        (point, false) => Ok(point),
+        (_, true) => Err(DidKeyError::OddY),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn base58_vectors() {
+        for (bytes, encoded) in [
+            (&b""[..], ""),
+            (b"Hello World!", "2NEpo7TZRRrLZSi2U"),
+            (&[0, 0, 0x28, 0x7f, 0xb4, 0xcd], "11233QC4"),
+        ] {
+            assert_eq!(base58_encode(bytes), encoded);
+            assert_eq!(base58_decode(encoded).as_deref(), Some(bytes));
+        }
+        assert_eq!(base58_decode("0OIl"), None);
+    }
+
+    #[test]
+    fn did_key_round_trip() {
+        let point = Point::random(&mut rand::thread_rng());
+        let did = to_did_key(&point);
+        assert_eq!(from_did_key(&did), Ok(point));
+        let fragment = format!("{}#{}", did, to_multibase(&point));
+        assert_eq!(from_did_key(&fragment), Ok(point));
+        assert_eq!(
+            from_did_key(&format!("{did}#key-1")),
+            Err(DidKeyError::Malformed)
+        );
+
+        let (xonly, needs_negation) = point.into_point_with_even_y();
+        assert_eq!(to_did_key(&xonly), to_did_key(&xonly.normalize()));
+        assert_eq!(xonly_from_did_key(&to_did_key(&xonly)), Ok(xonly));
+        assert_eq!(
+            xonly_from_did_key(&to_did_key(&-xonly)),
+            Err(DidKeyError::OddY)
+        );
+        assert_eq!(xonly_from_did_key(&did).is_ok(), !needs_negation);
+
+        assert!(from_did_key("did:key:zQ3shokFTS3brHcDQrn82RUDfCZESWL1ZdCEJwekUDPQiYBme").is_ok());
+        // an ed25519-pub key
+        assert_eq!(
+            from_did_key("did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK"),
+            Err(DidKeyError::UnsupportedKeyType)
+        );
+    }
+}
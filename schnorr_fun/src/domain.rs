@@ -0,0 +1,266 @@
+//! Keys, messages and signatures tagged with the network or domain they belong to.
+//!
+//! Applications that handle both testnet and mainnet (or several deployments of the same
+//! protocol) can mix up keys and end up signing a mainnet payload with a key that was only meant
+//! for testing. Wrapping them in [`DomainKeyPair`], [`DomainPublicKey`], [`DomainMessage`] and
+//! [`DomainSignature`] makes the domain part of the type so [`Schnorr::sign_in_domain`] and
+//! [`Schnorr::verify_in_domain`] won't compile if the domains don't match:
+//!
+//! ```compile_fail
+//! use schnorr_fun::{
+//!     domain::{DomainKeyPair, DomainMessage, Mainnet, Testnet},
+//!     fun::{marker::*, Scalar},
+//!     Message,
+//! };
+//! let schnorr = schnorr_fun::test_instance!();
+//! let keypair =
+//!     DomainKeyPair::<Testnet>::new(schnorr.new_keypair(Scalar::random(&mut rand::thread_rng())));
+//! let payload = DomainMessage::<Mainnet>::new(Message::<Public>::plain("my-app", b"pay"));
+//! schnorr.sign_in_domain(&keypair, payload);
+//! ```
+//!
+//! The domain is only a compile time tag. It is **not** mixed into the signature which is an
+//! ordinary BIP340 signature. Each wrapper's `new` is where you assert what domain something
+//! belongs to so keep these at the edges of your application (e.g. where keys are loaded or
+//! payloads are built for a particular network).
+//!
+//! [`Mainnet`], [`Testnet`], [`Signet`] and [`Regtest`] are provided. Implement [`Domain`] for your
+//! own types for other deployments.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     domain::{DomainKeyPair, DomainMessage, Testnet},
+//!     fun::{marker::*, Scalar},
+//!     Message,
+//! };
+//! let schnorr = schnorr_fun::test_instance!();
+//! let keypair =
+//!     DomainKeyPair::<Testnet>::new(schnorr.new_keypair(Scalar::random(&mut rand::thread_rng())));
+//! let payload = DomainMessage::<Testnet>::new(Message::<Public>::plain("my-app", b"pay"));
+//! let signature = schnorr.sign_in_domain(&keypair, payload);
+//! assert!(schnorr.verify_in_domain(&keypair.public_key(), payload, &signature));
+//! ```
+use crate::{Message, Schnorr, Signature};
+use core::{fmt::Debug, marker::PhantomData};
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    marker::*,
+    nonce::NonceGen,
+    KeyPair, Point,
+};
+
+/// A network or deployment that keys, messages and signatures can belong to.
+pub trait Domain: Copy + Debug + Default + PartialEq + Eq + 'static {
+    /// A human readable name for the domain e.g. for logging.
+    const NAME: &'static str;
+}
+
+/// Bitcoin mainnet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Mainnet;
+
+/// Bitcoin testnet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Testnet;
+
+/// Bitcoin signet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Signet;
+
+/// Bitcoin regtest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Regtest;
+
+impl Domain for Mainnet {
+    const NAME: &'static str = "mainnet";
+}
+
+impl Domain for Testnet {
+    const NAME: &'static str = "testnet";
+}
+
+impl Domain for Signet {
+    const NAME: &'static str = "signet";
+}
+
+impl Domain for Regtest {
+    const NAME: &'static str = "regtest";
+}
+
+/// A keypair that may only sign in domain `D`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DomainKeyPair<D> {
+    keypair: KeyPair<EvenY>,
+    domain: PhantomData<D>,
+}
+
+impl<D: Domain> DomainKeyPair<D> {
+    /// Asserts that `keypair` belongs to `D`.
+    pub fn new(keypair: KeyPair<EvenY>) -> Self {
+        Self {
+            keypair,
+            domain: PhantomData,
+        }
+    }
+
+    /// The untagged keypair.
+    pub fn keypair(&self) -> &KeyPair<EvenY> {
+        &self.keypair
+    }
+
+    /// The public key tagged with the same domain.
+    pub fn public_key(&self) -> DomainPublicKey<D> {
+        DomainPublicKey::new(self.keypair.public_key())
+    }
+}
+
+/// A public key that signatures are only checked against in domain `D`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde", transparent, bound = "")
+)]
+pub struct DomainPublicKey<D> {
+    public_key: Point<EvenY>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    domain: PhantomData<D>,
+}
+
+impl<D: Domain> DomainPublicKey<D> {
+    /// Asserts that `public_key` belongs to `D`.
+    pub fn new(public_key: Point<EvenY>) -> Self {
+        Self {
+            public_key,
+            domain: PhantomData,
+        }
+    }
+
+    /// The untagged public key.
+    pub fn public_key(&self) -> Point<EvenY> {
+        self.public_key
+    }
+}
+
+/// A message that may only be signed in domain `D`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DomainMessage<'a, D, S = Public> {
+    message: Message<'a, S>,
+    domain: PhantomData<D>,
+}
+
+impl<'a, D: Domain, S: Secrecy> DomainMessage<'a, D, S> {
+    /// Asserts that `message` is a payload for `D`.
+    pub fn new(message: Message<'a, S>) -> Self {
+        Self {
+            message,
+            domain: PhantomData,
+        }
+    }
+
+    /// The untagged message.
+    pub fn message(&self) -> Message<'a, S> {
+        self.message
+    }
+}
+
+/// A signature made in domain `D`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde", transparent, bound = "")
+)]
+pub struct DomainSignature<D> {
+    signature: Signature,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    domain: PhantomData<D>,
+}
+
+impl<D: Domain> DomainSignature<D> {
+    /// Asserts that `signature` was made in `D` (e.g. when it has been received for a `D` payload).
+    pub fn new(signature: Signature) -> Self {
+        Self {
+            signature,
+            domain: PhantomData,
+        }
+    }
+
+    /// The untagged signature.
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Converts into the untagged signature.
+    pub fn into_signature(self) -> Signature {
+        self.signature
+    }
+}
+
+impl<CH, NG> Schnorr<CH, NG>
+where
+    CH: Digest<OutputSize = U32> + Clone,
+    NG: NonceGen,
+{
+    /// Signs `message` with `keypair` where both must belong to the same domain.
+    ///
+    /// See the [module documentation](crate::domain).
+    pub fn sign_in_domain<D: Domain>(
+        &self,
+        keypair: &DomainKeyPair<D>,
+        message: DomainMessage<'_, D, impl Secrecy>,
+    ) -> DomainSignature<D> {
+        DomainSignature::new(self.sign(&keypair.keypair, message.message))
+    }
+}
+
+impl<CH, NG> Schnorr<CH, NG>
+where
+    CH: Digest<OutputSize = U32> + Clone,
+{
+    /// Verifies `signature` on `message` under `public_key` where all must belong to the same
+    /// domain.
+    #[must_use]
+    pub fn verify_in_domain<D: Domain>(
+        &self,
+        public_key: &DomainPublicKey<D>,
+        message: DomainMessage<'_, D, impl Secrecy>,
+        signature: &DomainSignature<D>,
+    ) -> bool {
+        self.verify(
+            &public_key.public_key,
+            message.message,
+            &signature.signature,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use secp256kfun::Scalar;
+
+    #[test]
+    fn domain_tagged_signing() {
+        let schnorr = crate::test_instance!();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let message = Message::<Public>::plain("test", b"domain");
+        let signet_keypair = DomainKeyPair::<Signet>::new(keypair.clone());
+        let signature = schnorr.sign_in_domain(&signet_keypair, DomainMessage::new(message));
+        // the domain doesn't change the signature
+        assert_eq!(signature.signature(), &schnorr.sign(&keypair, message));
+        assert!(schnorr.verify_in_domain(
+            &signet_keypair.public_key(),
+            DomainMessage::new(message),
+            &signature
+        ));
+        assert!(!schnorr.verify_in_domain(
+            &signet_keypair.public_key(),
+            DomainMessage::new(Message::<Public>::plain("test", b"other")),
+            &signature
+        ));
+        assert_eq!(Signet::NAME, "signet");
+    }
+}
@@ -169,28 +169,83 @@
 //! Note that if a key generation session fails you should always start a fresh session with a
 //! different session id (but you can use the same nonce_gen).
 //!
+//! ## Preprocessed nonces
+//!
+//! To cut a round out of signing, signers can publish a batch of nonce commitments ahead of time
+//! with [`Frost::preprocess_nonces`]. A signing request then just has to say which index of the
+//! batch to use. See [`PreprocessedNonces`] for how to make sure a preprocessed nonce is never
+//! used twice.
+//!
+//! ## Persisting sessions
+//!
+//! With the `serde` feature the state a party needs between rounds can be stored and loaded again
+//! after a restart: the [`KeyGen`] between the two rounds of key generation and the [`SignSession`]
+//! and [`Nonce`]s between the nonce and signing rounds. These encodings are stable: a [`KeyGen`]
+//! starts with a version number and the encodings of the others are checked against golden files
+//! in the tests so they can't change by accident.
+//!
+//! All of those are public. The secret state is the [`NonceKeyPair`] of a session and your secret
+//! share and has to be stored as carefully as the share itself (e.g. with [`keystore`]). A
+//! secret nonce must never be loaded and used again after a signature share has been made with
+//! it, which the [`journal`] takes care of for you.
+//!
 //! [FROST]: <https://eprint.iacr.org/2020/852.pdf>
 //! [secp256k1-zkp]: <https://github.com/ElementsProject/secp256k1-zkp/pull/138>
 //! [Security of Multi- and Threshold Signatures]: <https://eprint.iacr.org/2021/1375.pdf>
 //! [`musig`]: crate::musig
 //! [`Scalar`]: crate::fun::Scalar
-use core::num::NonZeroU32;
+//! [`keystore`]: crate::keystore
+//! [`journal`]: crate::journal
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub mod driver;
+pub mod dry_run;
+pub mod encpedpop;
+pub mod hierarchical;
+pub mod migration;
+pub mod public_key_package;
+pub mod repair;
+pub mod replay;
+pub mod rfc9591;
+pub mod sign_request;
+pub mod taproot;
+pub mod weighted;
+
+use core::{convert::Infallible, num::NonZeroU32};
 
 pub use crate::binonce::{Nonce, NonceKeyPair};
-use crate::{Message, Schnorr, Signature};
-use alloc::{collections::BTreeMap, vec::Vec};
+use crate::{signing, Message, Schnorr, Signature};
+use alloc::{
+    collections::{btree_map, BTreeMap, BTreeSet},
+    vec::Vec,
+};
+pub use public_key_package::FrostPublicKeyPackage;
 use secp256kfun::{
+    bip32::{Xpub, BIP328_CHAIN_CODE},
     derive_nonce_rng,
-    digest::{generic_array::typenum::U32, Digest},
+    digest::{
+        crypto_common::BlockSizeUser,
+        generic_array::typenum::{U32, U64},
+        Digest,
+    },
     g,
     hash::{HashAdd, Tag},
     marker::*,
     nonce::{self, NonceGen},
-    poly,
+    op, poly,
     rand_core::{RngCore, SeedableRng},
-    s, Point, Scalar, G,
+    s, Point, Scalar, SmallScalar, G,
 };
 
+/// The message tag used by [`Frost::certify_keygen`].
+const CERTIFICATION_TAG: &str = "frost-keygen-cert";
+/// The message tag used by [`Frost::prove_share_possession`].
+const SHARE_POSSESSION_TAG: &str = "frost-share-possession";
+/// The message tag used by [`Frost::attest_signature_share`].
+const SHARE_ATTESTATION_TAG: &str = "frost-share-attestation";
+/// The tag hashed in front of the point polynomial by [`FrostKey::fingerprint`].
+const FINGERPRINT_TAG: &str = "frost-key-fingerprint";
+
 /// The index of a party's secret share.
 ///
 /// This index and its secret share define a point on the joint secret polynomial.
@@ -201,9 +256,151 @@ use secp256kfun::{
 /// Other applications may desire to use indicies corresponding to pre-existing keys or identifiers.
 /// See [`share_backup`] for backup sizes.
 ///
+/// Functions that take a single index accept an [`IntoPartyIndex`] so you can pass `1`, `2`, ...
+/// directly.
+///
 /// [`share_backup`]: crate::share_backup
 pub type PartyIndex = Scalar<Public, NonZero>;
 
+/// A small integer identifying a party that maps canonically to a [`PartyIndex`].
+///
+/// A `PartyId` can't be zero so you can't accidentally give someone the share at index `0` (which
+/// is the joint secret itself). Use [`PartyId::unique_indices`] to catch parties being given the
+/// same id.
+///
+/// # Example
+///
+/// ```
+/// use schnorr_fun::{frost::PartyId, fun::s};
+/// assert_eq!(PartyId::new(0), None);
+/// let ids = [1, 2, 3].map(|id| PartyId::new(id).unwrap());
+/// let indices = PartyId::unique_indices(ids).unwrap();
+/// assert!(indices.contains(&s!(2).public()));
+/// assert!(PartyId::unique_indices([ids[0], ids[1], ids[0]]).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct PartyId(NonZeroU32);
+
+impl PartyId {
+    /// Creates a party id. Returns `None` if `id` is zero.
+    pub fn new(id: u32) -> Option<Self> {
+        NonZeroU32::new(id).map(Self)
+    }
+
+    /// The id as an integer.
+    pub fn get(self) -> u32 {
+        self.0.get()
+    }
+
+    /// The index of the party's secret share.
+    pub fn to_index(self) -> PartyIndex {
+        Scalar::from_non_zero_u32(self.0).public()
+    }
+
+    /// Converts a [`PartyIndex`] back into a `PartyId`.
+    ///
+    /// Returns `None` if the index isn't one that can be created from a `PartyId`.
+    pub fn from_index(index: PartyIndex) -> Option<Self> {
+        Self::new(SmallScalar::<{ u32::MAX }>::from_scalar(&index)?.get())
+    }
+
+    /// Maps each of `ids` to its [`PartyIndex`] checking that no id appears twice.
+    pub fn unique_indices(
+        ids: impl IntoIterator<Item = PartyId>,
+    ) -> Result<BTreeSet<PartyIndex>, DuplicatePartyId> {
+        let mut indices = BTreeSet::new();
+        for id in ids {
+            if !indices.insert(id.to_index()) {
+                return Err(DuplicatePartyId(id));
+            }
+        }
+        Ok(indices)
+    }
+}
+
+impl From<PartyId> for PartyIndex {
+    fn from(id: PartyId) -> Self {
+        id.to_index()
+    }
+}
+
+impl core::fmt::Display for PartyId {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Error returned by [`PartyId::unique_indices`] when two parties have the same id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DuplicatePartyId(pub PartyId);
+
+impl core::fmt::Display for DuplicatePartyId {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "party id {} was used more than once", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DuplicatePartyId {}
+
+/// Anything that identifies a party's [`PartyIndex`] so the FROST API can take plain integers.
+///
+/// An integer like `1` or a [`PartyId`] maps to [`PartyId::to_index`] (which is also the small
+/// index form of a [`share_backup`]). A [`PartyIndex`] is taken as is for applications that use
+/// arbitrary scalars as indices.
+///
+/// ```
+/// use schnorr_fun::{frost::IntoPartyIndex, fun::s};
+/// assert_eq!(3.into_party_index(), s!(3).public());
+/// assert_eq!(s!(3).public().into_party_index(), s!(3).public());
+/// ```
+///
+/// ## Panics
+///
+/// Converting the integer `0` panics since it isn't a valid index.
+///
+/// [`share_backup`]: crate::share_backup
+pub trait IntoPartyIndex {
+    /// The index of the party.
+    fn into_party_index(self) -> PartyIndex;
+}
+
+impl IntoPartyIndex for PartyIndex {
+    fn into_party_index(self) -> PartyIndex {
+        self
+    }
+}
+
+impl IntoPartyIndex for &PartyIndex {
+    fn into_party_index(self) -> PartyIndex {
+        *self
+    }
+}
+
+impl IntoPartyIndex for PartyId {
+    fn into_party_index(self) -> PartyIndex {
+        self.to_index()
+    }
+}
+
+impl IntoPartyIndex for u32 {
+    fn into_party_index(self) -> PartyIndex {
+        PartyId::new(self)
+            .expect("a party index can't be zero")
+            .to_index()
+    }
+}
+
 /// The FROST context.
 ///
 /// Type parameters:
@@ -218,6 +415,10 @@ pub struct Frost<H, NG> {
     binding_hash: H,
     /// The hash used to generate the `keygen_id`
     keygen_id_hash: H,
+    /// The hash used to generate the keygen transcript hash
+    keygen_transcript_hash: H,
+    /// The hash used to generate the coefficients for batch share verification
+    share_batch_hash: H,
     /// Nonce generator.
     /// Usually a tagged clone of the schnorr nonce generator.
     nonce_gen: NG,
@@ -241,6 +442,26 @@ impl<H, NG> Frost<H, NG> {
         NonceKeyPair::random(nonce_rng)
     }
 
+    /// Generate a batch of `count` nonces to publish ahead of signing.
+    ///
+    /// ⚠ You must use a CAREFULLY CHOSEN nonce rng, see [`Frost::seed_nonce_rng`]. The session id
+    /// you use to seed it must be unique to the batch.
+    ///
+    /// See [`PreprocessedNonces`].
+    pub fn preprocess_nonces<R: RngCore>(
+        &self,
+        nonce_rng: &mut R,
+        count: usize,
+    ) -> PreprocessedNonces {
+        let nonces = (0..count)
+            .map(|_| self.gen_nonce(nonce_rng))
+            .collect::<Vec<_>>();
+        PreprocessedNonces {
+            commitments: nonces.iter().map(NonceKeyPair::public).collect(),
+            nonces: nonces.into_iter().map(Some).collect(),
+        }
+    }
+
     /// Get the [`NonceGen`] that this frost instance is using in [`Frost::seed_nonce_rng`].
     ///
     /// [`NonceGen`]: secp256kfun::nonce::NonceGen
@@ -283,6 +504,8 @@ where
         Self {
             binding_hash: H::default().tag(b"frost/binding"),
             keygen_id_hash: H::default().tag(b"frost/keygenid"),
+            keygen_transcript_hash: H::default().tag(b"frost/keygentranscript"),
+            share_batch_hash: H::default().tag(b"frost/sharebatch"),
             nonce_gen: schnorr.nonce_gen().clone().tag(b"frost"),
             schnorr,
         }
@@ -291,20 +514,110 @@ where
 
 /// A KeyGen (distributed key generation) session
 ///
-/// Created using [`Frost::new_keygen`]
+/// Created using [`Frost::new_keygen`]. With the `serde` feature it can be stored between the
+/// rounds of key generation. It's encoded as a version number and the point polynomial of each
+/// party and everything else is recomputed (and checked) when it's decoded.
 ///
 /// [`Frost::new_keygen`]
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct KeyGen {
     frost_key: FrostKey<Normal>,
     point_polys: BTreeMap<PartyIndex, Vec<Point>>,
 }
 
+impl core::fmt::Debug for KeyGen {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("KeyGen")
+            .field("participants", &self.point_polys.keys())
+            .field("threshold", &self.frost_key.threshold())
+            .field("public_key", &self.frost_key.public_key())
+            .finish()
+    }
+}
+
 impl KeyGen {
+    fn from_point_polys(
+        point_polys: BTreeMap<PartyIndex, Vec<Point>>,
+    ) -> Result<Self, NewKeyGenError> {
+        let len_first_poly = point_polys
+            .iter()
+            .next()
+            .map(|(_, poly)| poly.len())
+            .ok_or(NewKeyGenError::NotEnoughParties)?;
+        {
+            if let Some((i, _)) = point_polys
+                .iter()
+                .find(|(_, point_poly)| point_poly.len() != len_first_poly)
+            {
+                return Err(NewKeyGenError::PolyDifferentLength(*i));
+            }
+
+            // Number of parties is less than the length of polynomials specifying the threshold
+            if point_polys.len() < len_first_poly {
+                return Err(NewKeyGenError::NotEnoughParties);
+            }
+        }
+
+        let mut joint_poly = (0..len_first_poly)
+            .map(|_| Point::<NonNormal, Public, _>::zero())
+            .collect::<Vec<_>>();
+
+        for poly in point_polys.values() {
+            op::pairwise_add_points(&mut joint_poly, poly);
+        }
+
+        let public_key = joint_poly[0]
+            .normalize()
+            .non_zero()
+            .ok_or(NewKeyGenError::ZeroFrostKey)?;
+
+        Ok(KeyGen {
+            point_polys,
+            frost_key: FrostKey {
+                tweaked_public_key: public_key,
+                point_polynomial: joint_poly
+                    .into_iter()
+                    .map(|coef| coef.normalize())
+                    .collect(),
+                tweak: Scalar::zero(),
+                needs_negation: false,
+                generation: KeyGeneration::default(),
+            },
+        })
+    }
+
     /// Return the number of parties in the KeyGen
     pub fn n_parties(&self) -> usize {
         self.point_polys.len()
     }
+
+    /// Produce a [`KeyGenShareBlame`] against `accused` if the `secret_share` they sent to us
+    /// doesn't match their point polynomial.
+    ///
+    /// You'd call this after [`Frost::finish_keygen`] returns [`FinishKeyGenError::InvalidShare`].
+    ///
+    /// Returns `None` if `accused` isn't part of the key generation or if the share is actually
+    /// valid.
+    pub fn blame_share(
+        &self,
+        accused: impl IntoPartyIndex,
+        my_index: impl IntoPartyIndex,
+        secret_share: Scalar<Secret, Zero>,
+    ) -> Option<KeyGenShareBlame> {
+        let accused = accused.into_party_index();
+        let my_index = my_index.into_party_index();
+        let blame = KeyGenShareBlame {
+            accused,
+            receiver: my_index,
+            point_poly: self.point_polys.get(&accused)?.clone(),
+            secret_share,
+        };
+        if blame.verify() {
+            Some(blame)
+        } else {
+            None
+        }
+    }
 }
 
 /// First round keygen errors
@@ -332,6 +645,38 @@ impl core::fmt::Display for NewKeyGenError {
 #[cfg(feature = "std")]
 impl std::error::Error for NewKeyGenError {}
 
+/// The version of the encoding of a [`KeyGen`].
+#[cfg(feature = "serde")]
+const KEYGEN_ENCODING_VERSION: u8 = 0;
+
+#[cfg(feature = "serde")]
+impl crate::fun::serde::Serialize for KeyGen {
+    fn serialize<S: crate::fun::serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        (KEYGEN_ENCODING_VERSION, &self.point_polys).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> crate::fun::serde::Deserialize<'de> for KeyGen {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: secp256kfun::serde::Deserializer<'de>,
+    {
+        use crate::fun::serde::de::Error;
+        let (version, point_polys) =
+            <(u8, BTreeMap<PartyIndex, Vec<Point>>)>::deserialize(deserializer)?;
+        if version != KEYGEN_ENCODING_VERSION {
+            return Err(D::Error::custom(format_args!(
+                "unsupported KeyGen encoding version {version}"
+            )));
+        }
+        KeyGen::from_point_polys(point_polys).map_err(D::Error::custom)
+    }
+}
+
 /// Second round KeyGen errors
 #[derive(Debug, Clone)]
 pub enum FinishKeyGenError {
@@ -364,6 +709,61 @@ impl core::fmt::Display for FinishKeyGenError {
 #[cfg(feature = "std")]
 impl std::error::Error for FinishKeyGenError {}
 
+fn share_possession_message<T: Copy + PointType>(
+    frost_key: &FrostKey<T>,
+    index: PartyIndex,
+    challenge: &[u8],
+) -> Vec<u8> {
+    let mut message_data = frost_key.point_polynomial[0].to_bytes().to_vec();
+    message_data.extend_from_slice(&index.to_bytes());
+    message_data.extend_from_slice(challenge);
+    message_data
+}
+
+fn share_attestation_message(
+    session: &SignSession,
+    index: PartyIndex,
+    signature_share: Scalar<Public, Zero>,
+) -> Vec<u8> {
+    // the challenge commits to the message and the aggregate nonce, the nonces to who else signed
+    let mut message_data = index.to_bytes().to_vec();
+    message_data.extend_from_slice(&signature_share.to_bytes());
+    message_data.extend_from_slice(&session.challenge.to_bytes());
+    for (index, nonce) in &session.nonces {
+        message_data.extend_from_slice(&index.to_bytes());
+        message_data.extend_from_slice(&nonce.to_bytes());
+    }
+    message_data
+}
+
+/// Error returned by [`Frost::verify_certifications`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum CertificationError {
+    /// The party didn't provide a certification
+    Missing(PartyIndex),
+    /// The party's certification was invalid
+    Invalid(PartyIndex),
+    /// A certification was provided for a party that isn't part of the key generation
+    UnknownParty(PartyIndex),
+}
+
+impl core::fmt::Display for CertificationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        use CertificationError::*;
+        match self {
+            Missing(i) => write!(f, "party {i} did not certify the key generation"),
+            Invalid(i) => write!(f, "the certification from party {i} was invalid"),
+            UnknownParty(i) => write!(
+                f,
+                "a certification was provided for party {i} who is not part of the key generation"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CertificationError {}
+
 /// A FROST key
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct FrostKey<T: PointType> {
@@ -375,11 +775,67 @@ pub struct FrostKey<T: PointType> {
     /// The public point polynomial that defines the access structure to the FROST key.
     point_polynomial: Vec<Point<Normal, Public, Zero>>,
     /// The tweak applied to this frost key, tracks the aggregate tweak.
-    tweak: Scalar<Public, Zero>,
+    pub(crate) tweak: Scalar<Public, Zero>,
     /// Whether the secret keys need to be negated during signing (only used for EvenY keys).
-    needs_negation: bool,
+    pub(crate) needs_negation: bool,
+    /// Which generation of shares the key's point polynomial belongs to.
+    generation: KeyGeneration,
+}
+
+/// Which generation of secret shares a [`FrostKey`] belongs to.
+///
+/// Refreshing or resharing a FROST key gives the parties new secret shares of the same secret so
+/// the new key has the same public key but a different point polynomial. Old shares don't work with
+/// the new polynomial (and shouldn't be kept around) so operational tooling needs to know which
+/// generation a share and its key are from. Keys from key generation are generation `0` with no
+/// parent. [`FrostKey::next_generation`] creates the key of the next generation which records the
+/// [`fingerprint`] of the key it replaced.
+///
+/// The generation is stored along with the share by [`keystore::save_frost_share`]. Make sure you
+/// are signing with the current generation with [`FrostKey::check_generation`].
+///
+/// [`fingerprint`]: FrostKey::fingerprint
+/// [`keystore::save_frost_share`]: crate::keystore::save_frost_share
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct KeyGeneration {
+    /// How many times the shares have been refreshed or reshared since key generation.
+    pub counter: u32,
+    /// The [`FrostKey::fingerprint`] of the previous generation's key.
+    pub parent: Option<[u8; 8]>,
+}
+
+/// Error returned by [`FrostKey::check_generation`] when a key isn't from the current generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaleGeneration {
+    /// The generation that should have been used.
+    pub current: KeyGeneration,
+    /// The generation of the key.
+    pub got: KeyGeneration,
+}
+
+impl core::fmt::Display for StaleGeneration {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "the key is from share generation {} but the current generation is {}",
+            self.got.counter, self.current.counter
+        )
+    }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for StaleGeneration {}
+
 impl<T: Copy + PointType> FrostKey<T> {
     /// The public key with all tweaks applied
     pub fn public_key(&self) -> Point<T> {
@@ -389,8 +845,8 @@ impl<T: Copy + PointType> FrostKey<T> {
     /// The verification shares of each party in the key.
     ///
     /// The verification share is the image of their secret share.
-    pub fn verification_share(&self, index: &PartyIndex) -> Point<NonNormal, Public, Zero> {
-        poly::point::eval(&self.point_polynomial, *index)
+    pub fn verification_share(&self, index: impl IntoPartyIndex) -> Point<NonNormal, Public, Zero> {
+        poly::point::eval(&self.point_polynomial, index.into_party_index())
     }
 
     /// The threshold number of participants required in a signing coalition to produce a valid signature.
@@ -398,6 +854,72 @@ impl<T: Copy + PointType> FrostKey<T> {
         self.point_polynomial.len()
     }
 
+    /// Which generation of shares the key belongs to.
+    pub fn generation(&self) -> KeyGeneration {
+        self.generation
+    }
+
+    /// Sets the generation of the key (e.g. when restoring it from your own storage).
+    pub fn with_generation(mut self, generation: KeyGeneration) -> Self {
+        self.generation = generation;
+        self
+    }
+
+    /// A short identifier of the key's point polynomial.
+    ///
+    /// Unlike the public key this changes every time the shares are refreshed or reshared so it
+    /// identifies a particular generation of the key. Tweaks don't change it.
+    pub fn fingerprint<H: Default + Digest<OutputSize = U32>>(&self) -> [u8; 8] {
+        let hash = H::default()
+            .add(FINGERPRINT_TAG)
+            .add(&self.point_polynomial[..])
+            .finalize();
+        let mut fingerprint = [0u8; 8];
+        fingerprint.copy_from_slice(&hash[..8]);
+        fingerprint
+    }
+
+    /// Creates the key of the next generation after the shares have been refreshed or reshared to
+    /// `point_polynomial`.
+    ///
+    /// The new key keeps the tweaks of this one and records this key's [`fingerprint`] as its
+    /// parent. Returns `None` if `point_polynomial` isn't a polynomial for the same secret.
+    ///
+    /// [`fingerprint`]: Self::fingerprint
+    pub fn next_generation<H: Default + Digest<OutputSize = U32>>(
+        &self,
+        point_polynomial: Vec<Point<Normal, Public, Zero>>,
+    ) -> Option<Self> {
+        if point_polynomial.first()? != self.point_polynomial.first()? {
+            return None;
+        }
+        Some(FrostKey {
+            tweaked_public_key: self.tweaked_public_key,
+            point_polynomial,
+            tweak: self.tweak,
+            needs_negation: self.needs_negation,
+            generation: KeyGeneration {
+                counter: self.generation.counter + 1,
+                parent: Some(self.fingerprint::<H>()),
+            },
+        })
+    }
+
+    /// Checks that the key is from the `current` generation of shares.
+    ///
+    /// Call this before signing so that a signer holding a share from a generation that has been
+    /// refreshed away refuses to sign instead of producing an invalid signature share.
+    pub fn check_generation(&self, current: KeyGeneration) -> Result<(), StaleGeneration> {
+        if self.generation == current {
+            Ok(())
+        } else {
+            Err(StaleGeneration {
+                current,
+                got: self.generation,
+            })
+        }
+    }
+
     /// The public image of the key's polynomial on the elliptic curve.
     ///
     /// Note: the first coefficient (index `0`) is guaranteed to be non-zero but the coefficients
@@ -422,6 +944,7 @@ impl FrostKey<Normal> {
             point_polynomial: self.point_polynomial,
             tweak,
             needs_negation,
+            generation: self.generation,
         }
     }
 
@@ -451,9 +974,36 @@ impl FrostKey<Normal> {
             point_polynomial: self.point_polynomial,
             tweak,
             needs_negation: self.needs_negation,
+            generation: self.generation,
         })
     }
 
+    /// The key as an extended public key with the [BIP328] chain code.
+    ///
+    /// FROST has no convention of its own so this uses the MuSig one: the chain code is the
+    /// constant [`BIP328_CHAIN_CODE`] so every party derives the same children without having to
+    /// agree on one. Export this from the untweaked key.
+    ///
+    /// [BIP328]: https://bips.xyz/328
+    pub fn xpub(&self) -> Xpub {
+        Xpub::new(self.tweaked_public_key, BIP328_CHAIN_CODE)
+    }
+
+    /// Derives the unhardened child key at `path` from [`xpub`] so that the parties can sign under
+    /// it.
+    ///
+    /// Returns the child key along with its extended public key. `H` must be SHA512 to match other
+    /// BIP32 implementations. Returns `None` if any index is hardened or a child is invalid.
+    ///
+    /// [`xpub`]: Self::xpub
+    pub fn derive_bip32<H>(self, path: &[u32]) -> Option<(Self, Xpub)>
+    where
+        H: BlockSizeUser + Digest<OutputSize = U64> + Default + Clone,
+    {
+        let (child, tweak) = self.xpub().derive_path::<H>(path)?;
+        Some((self.tweak(tweak)?, child))
+    }
+
     /// Put the `FrostKey` into a form that can be encoded/decode.
     ///
     /// Note this encoding **ignores the tweaks that have been applied to the `FrostKey`**. To
@@ -468,6 +1018,22 @@ impl FrostKey<Normal> {
     pub fn decode(encoded_frost_key: EncodedFrostKey) -> Self {
         Self::from(encoded_frost_key)
     }
+
+    /// Create an untweaked `FrostKey` from its point polynomial.
+    ///
+    /// Returns `None` if the first coefficient is zero.
+    pub(crate) fn from_point_polynomial(
+        point_polynomial: Vec<Point<Normal, Public, Zero>>,
+    ) -> Option<Self> {
+        let public_key = point_polynomial.first()?.non_zero()?;
+        Some(FrostKey {
+            tweaked_public_key: public_key,
+            point_polynomial,
+            tweak: Scalar::zero(),
+            needs_negation: false,
+            generation: KeyGeneration::default(),
+        })
+    }
 }
 
 impl FrostKey<EvenY> {
@@ -497,6 +1063,7 @@ impl FrostKey<EvenY> {
             point_polynomial: self.point_polynomial,
             needs_negation,
             tweak: new_tweak,
+            generation: self.generation,
         })
     }
 }
@@ -536,6 +1103,51 @@ impl<H: Digest<OutputSize = U32> + Clone, NG: NonceGen> Frost<H, NG> {
         self.schnorr.sign(&key_pair, message)
     }
 
+    /// Certify that we agree on the outcome of the key generation.
+    ///
+    /// This is a Schnorr signature over the [`keygen_transcript`] under our verification share
+    /// (i.e. with `secret_share` as the secret key). Once every party has broadcast their
+    /// certification anyone can check with [`verify_certifications`] that all the parties ended up
+    /// with the same key and parameters and that each of them really holds a share of it.
+    ///
+    /// [`keygen_transcript`]: Frost::keygen_transcript
+    /// [`verify_certifications`]: Frost::verify_certifications
+    pub fn certify_keygen(&self, keygen: &KeyGen, secret_share: &Scalar) -> Signature {
+        let transcript = self.keygen_transcript(keygen);
+        let keypair = self.schnorr.new_keypair(*secret_share);
+        self.schnorr.sign(
+            &keypair,
+            Message::<Public>::plain(CERTIFICATION_TAG, &transcript),
+        )
+    }
+
+    /// Prove that we hold the secret share at `index` of `frost_key`.
+    ///
+    /// This is a Schnorr signature under the verification share at `index` over the key, the index
+    /// and a `challenge`. A new custodian can use this to show the other parties (or an auditor)
+    /// that they've received their share correctly. The `challenge` should be chosen by whoever
+    /// is checking the proof (e.g. a random nonce or the date of the audit) so old proofs can't be
+    /// replayed.
+    ///
+    /// Check the proof with [`verify_share_possession`].
+    ///
+    /// [`verify_share_possession`]: Frost::verify_share_possession
+    pub fn prove_share_possession<T: Copy + PointType>(
+        &self,
+        frost_key: &FrostKey<T>,
+        index: impl IntoPartyIndex,
+        secret_share: &Scalar,
+        challenge: &[u8],
+    ) -> Signature {
+        let index = index.into_party_index();
+        let message_data = share_possession_message(frost_key, index, challenge);
+        let keypair = self.schnorr.new_keypair(*secret_share);
+        self.schnorr.sign(
+            &keypair,
+            Message::<Public>::plain(SHARE_POSSESSION_TAG, &message_data),
+        )
+    }
+
     /// Seed a random number generator to be used for FROST nonces.
     ///
     /// ** ⚠ WARNING ⚠**: This method is unstable and easy to use incorrectly. The seed it uses for
@@ -655,15 +1267,38 @@ impl<H: Digest<OutputSize = U32> + Clone, NG: NonceGen> Frost<H, NG> {
 
         (frost_key.unwrap(), secret_shares)
     }
-}
 
-impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
-    /// Generate an id for the key generation by hashing the party indicies and their point
-    /// polynomials
-    pub fn keygen_id(&self, keygen: &KeyGen) -> [u8; 32] {
-        let mut keygen_hash = self.keygen_id_hash.clone();
-        keygen_hash.update((keygen.point_polys.len() as u32).to_be_bytes());
-        for (index, poly) in &keygen.point_polys {
+    /// Attest to the `signature_share` we produced for `session`.
+    ///
+    /// The attestation is a signature with our secret share over the signature share and every
+    /// nonce in the session. Send it along with the signature share so that if the coordinator
+    /// finds the share invalid they can produce a [`SignatureShareBlame`] that a third party can
+    /// check. Without it anyone could produce a blame against us by making up a session with
+    /// nonces we never sent.
+    pub fn attest_signature_share(
+        &self,
+        session: &SignSession,
+        my_index: impl IntoPartyIndex,
+        secret_share: &Scalar,
+        signature_share: Scalar<Public, Zero>,
+    ) -> Signature {
+        let message_data =
+            share_attestation_message(session, my_index.into_party_index(), signature_share);
+        let keypair = self.schnorr.new_keypair(*secret_share);
+        self.schnorr.sign(
+            &keypair,
+            Message::<Public>::plain(SHARE_ATTESTATION_TAG, &message_data),
+        )
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
+    /// Generate an id for the key generation by hashing the party indicies and their point
+    /// polynomials
+    pub fn keygen_id(&self, keygen: &KeyGen) -> [u8; 32] {
+        let mut keygen_hash = self.keygen_id_hash.clone();
+        keygen_hash.update((keygen.point_polys.len() as u32).to_be_bytes());
+        for (index, poly) in &keygen.point_polys {
             keygen_hash.update(index.to_bytes());
             for point in poly {
                 keygen_hash.update(point.to_bytes());
@@ -672,6 +1307,156 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
         keygen_hash.finalize().into()
     }
 
+    /// Check that `secret_share` is the share at `index` of `frost_key`.
+    ///
+    /// This only needs the joint key's public polynomial rather than every party's commitments
+    /// from the key generation. Note that it can't tell you if the polynomial itself was generated
+    /// properly.
+    pub fn verify_share_against_key<T: Copy + PointType>(
+        &self,
+        frost_key: &FrostKey<T>,
+        index: impl IntoPartyIndex,
+        secret_share: &Scalar<impl Secrecy, impl ZeroChoice>,
+    ) -> bool {
+        let index = index.into_party_index();
+        g!(secret_share * G) == frost_key.verification_share(index)
+    }
+
+    /// Check many secret shares against the point polynomials they were created from at once.
+    ///
+    /// Each item is a point polynomial, the index the share is for and the share. This is
+    /// equivalent to checking `g!(share * G) == poly::point::eval(poly, index)` for every item but
+    /// does a single multi-scalar multiplication over a random linear combination of the checks
+    /// which is much faster when there are many of them (e.g. verifying the shares from each party
+    /// in a large key generation).
+    ///
+    /// The coefficients of the linear combination are derived by hashing all of the items so no
+    /// randomness is needed. If this returns `false` you have to check the shares individually to
+    /// find out which are invalid.
+    pub fn verify_shares_batch<'a>(
+        &self,
+        shares: impl IntoIterator<Item = (&'a [Point], PartyIndex, Scalar<Secret, Zero>)>,
+    ) -> bool {
+        let shares = shares.into_iter().collect::<Vec<_>>();
+        if shares.is_empty() {
+            return true;
+        }
+        let mut batch_hash = self.share_batch_hash.clone();
+        for (poly, index, share) in &shares {
+            batch_hash = batch_hash
+                .add((poly.len() as u32).to_be_bytes())
+                .add(*poly)
+                .add(index)
+                .add(share);
+        }
+
+        let mut scalars = Vec::with_capacity(shares.iter().map(|(poly, ..)| poly.len()).sum());
+        let mut points = Vec::with_capacity(scalars.capacity());
+        let mut share_sum = s!(0);
+        for (i, (poly, index, share)) in shares.iter().enumerate() {
+            let coefficient =
+                Scalar::from_hash(batch_hash.clone().add((i as u32).to_be_bytes())).public();
+            share_sum += s!(coefficient * share);
+            let mut index_pow = s!(-coefficient).public();
+            for point in poly.iter() {
+                scalars.push(index_pow);
+                points.push(*point);
+                index_pow = s!(index_pow * index).public();
+            }
+        }
+
+        g!(share_sum * G + scalars .* points).is_zero()
+    }
+
+    /// Verify a proof from [`prove_share_possession`] that the party at `index` holds their
+    /// secret share of `frost_key`.
+    ///
+    /// [`prove_share_possession`]: Frost::prove_share_possession
+    pub fn verify_share_possession<T: Copy + PointType>(
+        &self,
+        frost_key: &FrostKey<T>,
+        index: impl IntoPartyIndex,
+        challenge: &[u8],
+        proof: &Signature,
+    ) -> bool {
+        let index = index.into_party_index();
+        let verification_share = match frost_key.verification_share(index).normalize().non_zero() {
+            Some(verification_share) => verification_share.into_point_with_even_y().0,
+            None => return false,
+        };
+        let message_data = share_possession_message(frost_key, index, challenge);
+        self.schnorr.verify(
+            &verification_share,
+            Message::<Public>::plain(SHARE_POSSESSION_TAG, &message_data),
+            proof,
+        )
+    }
+
+    /// Hash everything the parties need to agree on after a key generation.
+    ///
+    /// This commits to the [`keygen_id`] (every party's point polynomial), the threshold, the
+    /// party indices and the resulting joint point polynomial. It's what parties sign with
+    /// [`certify_keygen`].
+    ///
+    /// [`keygen_id`]: Frost::keygen_id
+    /// [`certify_keygen`]: Frost::certify_keygen
+    pub fn keygen_transcript(&self, keygen: &KeyGen) -> [u8; 32] {
+        let mut transcript_hash = self.keygen_transcript_hash.clone();
+        transcript_hash.update(self.keygen_id(keygen));
+        transcript_hash.update((keygen.frost_key.threshold() as u32).to_be_bytes());
+        transcript_hash.update((keygen.point_polys.len() as u32).to_be_bytes());
+        for index in keygen.point_polys.keys() {
+            transcript_hash.update(index.to_bytes());
+        }
+        for point in &keygen.frost_key.point_polynomial {
+            transcript_hash.update(point.to_bytes());
+        }
+        transcript_hash.finalize().into()
+    }
+
+    /// Check that every party in `keygen` has certified its outcome with [`certify_keygen`].
+    ///
+    /// An auditor who has the point polynomials from the key generation can recreate the
+    /// [`KeyGen`] with [`new_keygen`] and then use this to confirm that all the parties agreed on
+    /// the same key and parameters.
+    ///
+    /// [`certify_keygen`]: Frost::certify_keygen
+    /// [`new_keygen`]: Frost::new_keygen
+    pub fn verify_certifications(
+        &self,
+        keygen: &KeyGen,
+        certifications: &BTreeMap<PartyIndex, Signature>,
+    ) -> Result<(), CertificationError> {
+        if let Some(unknown) = certifications
+            .keys()
+            .find(|index| !keygen.point_polys.contains_key(index))
+        {
+            return Err(CertificationError::UnknownParty(*unknown));
+        }
+        let transcript = self.keygen_transcript(keygen);
+        let message = Message::<Public>::plain(CERTIFICATION_TAG, &transcript);
+        for index in keygen.point_polys.keys() {
+            let certification = certifications
+                .get(index)
+                .ok_or(CertificationError::Missing(*index))?;
+            let verification_share = keygen
+                .frost_key
+                .verification_share(index)
+                .normalize()
+                .non_zero()
+                .ok_or(CertificationError::Invalid(*index))?
+                .into_point_with_even_y()
+                .0;
+            if !self
+                .schnorr
+                .verify(&verification_share, message, certification)
+            {
+                return Err(CertificationError::Invalid(*index));
+            }
+        }
+        Ok(())
+    }
+
     /// Collect all the public polynomials commitments into a [`KeyGen`] to produce a [`FrostKey`].
     ///
     /// It is crucial that at least one of these polynomials was not adversarially produced
@@ -703,52 +1488,7 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
                 debug_assert_eq!(_existing, poly::scalar::to_point_poly(scalar_poly.as_ref()));
             }
         }
-        let len_first_poly = point_polys
-            .iter()
-            .next()
-            .map(|(_, poly)| poly.len())
-            .ok_or(NewKeyGenError::NotEnoughParties)?;
-        {
-            if let Some((i, _)) = point_polys
-                .iter()
-                .find(|(_, point_poly)| point_poly.len() != len_first_poly)
-            {
-                return Err(NewKeyGenError::PolyDifferentLength(*i));
-            }
-
-            // Number of parties is less than the length of polynomials specifying the threshold
-            if point_polys.len() < len_first_poly {
-                return Err(NewKeyGenError::NotEnoughParties);
-            }
-        }
-
-        let mut joint_poly = (0..len_first_poly)
-            .map(|_| Point::<NonNormal, Public, _>::zero())
-            .collect::<Vec<_>>();
-
-        for poly in point_polys.values() {
-            for i in 0..len_first_poly {
-                joint_poly[i] += poly[i];
-            }
-        }
-
-        let public_key = joint_poly[0]
-            .normalize()
-            .non_zero()
-            .ok_or(NewKeyGenError::ZeroFrostKey)?;
-
-        Ok(KeyGen {
-            point_polys,
-            frost_key: FrostKey {
-                tweaked_public_key: public_key,
-                point_polynomial: joint_poly
-                    .into_iter()
-                    .map(|coef| coef.normalize())
-                    .collect(),
-                tweak: Scalar::zero(),
-                needs_negation: false,
-            },
-        })
+        KeyGen::from_point_polys(point_polys)
     }
 
     /// Verify a key generation without being a key-owning party
@@ -790,10 +1530,11 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
     pub fn finish_keygen(
         &self,
         keygen: KeyGen,
-        my_index: PartyIndex,
+        my_index: impl IntoPartyIndex,
         secret_shares: BTreeMap<PartyIndex, (Scalar<Secret, Zero>, Signature)>,
         proof_of_possession_msg: Message,
     ) -> Result<(Scalar, FrostKey<Normal>), FinishKeyGenError> {
+        let my_index = my_index.into_party_index();
         let mut total_secret_share = s!(0);
 
         for (party_index, poly) in &keygen.point_polys {
@@ -808,12 +1549,21 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
             {
                 return Err(FinishKeyGenError::InvalidProofOfPossession(*party_index));
             }
+            total_secret_share += secret_share;
+        }
 
-            let expected_public_share = poly::point::eval(poly, my_index);
-            if g!(secret_share * G) != expected_public_share {
-                return Err(FinishKeyGenError::InvalidShare(*party_index));
+        let shares = keygen
+            .point_polys
+            .iter()
+            .map(|(party_index, poly)| (&poly[..], my_index, secret_shares[party_index].0));
+        if !self.verify_shares_batch(shares) {
+            // find out who sent the invalid share
+            for (party_index, poly) in &keygen.point_polys {
+                let expected_public_share = poly::point::eval(poly, my_index);
+                if g!({ secret_shares[party_index].0 } * G) != expected_public_share {
+                    return Err(FinishKeyGenError::InvalidShare(*party_index));
+                }
             }
-            total_secret_share += secret_share;
         }
 
         let total_secret_share = total_secret_share.non_zero().expect(
@@ -839,44 +1589,79 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
         nonces: BTreeMap<PartyIndex, Nonce>,
         message: Message,
     ) -> SignSession {
-        let nonce_map = nonces;
+        if nonces.len() < frost_key.threshold() {
+            panic!("nonces' length was less than the threshold");
+        }
+
+        let agg_nonce = Nonce::aggregate(nonces.values().copied());
+        let binding_coeff = signing::frost_binding_coeff(
+            &self.binding_hash,
+            &agg_nonce,
+            frost_key.public_key(),
+            message,
+        );
+        let agg_nonce = g!(agg_nonce.0[0] + binding_coeff * agg_nonce.0[1]);
+        let binding_coeffs = nonces.keys().map(|index| (*index, binding_coeff)).collect();
+
+        self._start_sign_session(frost_key, nonces, message, agg_nonce, binding_coeffs)
+    }
 
-        if nonce_map.len() < frost_key.threshold() {
+    /// Start a FROST signing session with a binding factor for each signer computed as in [RFC
+    /// 9591].
+    ///
+    /// This is like [`start_sign_session`] except for how the nonces are bound so all the signers
+    /// need to use the same one. See the [`rfc9591`] module.
+    ///
+    /// # Panics
+    ///
+    /// If the number of nonces is less than the threshold.
+    ///
+    /// [RFC 9591]: https://www.rfc-editor.org/rfc/rfc9591.html
+    /// [`start_sign_session`]: Self::start_sign_session
+    pub fn start_sign_session_rfc9591(
+        &self,
+        frost_key: &FrostKey<EvenY>,
+        nonces: BTreeMap<PartyIndex, Nonce>,
+        message: Message,
+    ) -> SignSession
+    where
+        H: BlockSizeUser,
+    {
+        if nonces.len() < frost_key.threshold() {
             panic!("nonces' length was less than the threshold");
         }
 
-        let agg_nonce = nonce_map
-            .iter()
-            .fold([Point::zero(); 2], |acc, (_, nonce)| {
-                [g!(acc[0] + nonce.0[0]), g!(acc[1] + nonce.0[1])]
-            });
-
-        let agg_nonce = [agg_nonce[0].normalize(), agg_nonce[1].normalize()];
-
-        let binding_coeff = Scalar::from_hash(
-            self.binding_hash
-                .clone()
-                .add(agg_nonce[0])
-                .add(agg_nonce[1])
-                .add(frost_key.public_key())
-                .add(message),
+        let binding_coeffs =
+            rfc9591::binding_factors::<H>(frost_key.public_key(), &nonces, message);
+        let agg_nonce = signing::rfc9591_group_commitment(
+            nonces
+                .iter()
+                .map(|(index, nonce)| (*nonce, binding_coeffs[index])),
         );
-        let (agg_nonce, nonces_need_negation) = g!(agg_nonce[0] + binding_coeff * agg_nonce[1])
-            .normalize()
-            .non_zero()
-            .unwrap_or(Point::generator())
-            .into_point_with_even_y();
+
+        self._start_sign_session(frost_key, nonces, message, agg_nonce, binding_coeffs)
+    }
+
+    fn _start_sign_session(
+        &self,
+        frost_key: &FrostKey<EvenY>,
+        nonces: BTreeMap<PartyIndex, Nonce>,
+        message: Message,
+        agg_nonce: Point<NonNormal, Public, Zero>,
+        binding_coeffs: BTreeMap<PartyIndex, Scalar<Public, Zero>>,
+    ) -> SignSession {
+        let (agg_nonce, nonces_need_negation) = signing::session_nonce(agg_nonce);
 
         let challenge = self
             .schnorr
             .challenge(&agg_nonce, &frost_key.public_key(), message);
 
         SignSession {
-            binding_coeff,
+            binding_coeffs,
             nonces_need_negation,
             agg_nonce,
             challenge,
-            nonces: nonce_map,
+            nonces,
         }
     }
 
@@ -894,11 +1679,63 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
         &self,
         frost_key: &FrostKey<EvenY>,
         session: &SignSession,
+        my_index: impl IntoPartyIndex,
+        secret_share: &Scalar,
+        secret_nonce: NonceKeyPair,
+    ) -> Scalar<Public, Zero> {
+        let my_index = my_index.into_party_index();
+        let lambda = poly::eval_basis_poly_at_0(my_index, session.nonces.keys());
+        self._sign(
+            frost_key,
+            session,
+            lambda,
+            my_index,
+            secret_share,
+            secret_nonce,
+        )
+    }
+
+    /// Like [`sign`] but takes the lagrange coefficient from `basis` rather than computing it.
+    ///
+    /// When the same signers sign many messages create the [`LagrangeBasis`] for them once with
+    /// [`SignSession::lagrange_basis`] and use it for all of them.
+    ///
+    /// ## Panics
+    ///
+    /// Same as [`sign`] and if `basis` isn't for the signers of `session`.
+    ///
+    /// [`sign`]: Self::sign
+    /// [`LagrangeBasis`]: secp256kfun::poly::LagrangeBasis
+    pub fn sign_with_basis(
+        &self,
+        frost_key: &FrostKey<EvenY>,
+        session: &SignSession,
+        basis: &poly::LagrangeBasis,
+        my_index: impl IntoPartyIndex,
+        secret_share: &Scalar,
+        secret_nonce: NonceKeyPair,
+    ) -> Scalar<Public, Zero> {
+        let my_index = my_index.into_party_index();
+        let lambda = session.coefficient_from_basis(basis, my_index);
+        self._sign(
+            frost_key,
+            session,
+            lambda,
+            my_index,
+            secret_share,
+            secret_nonce,
+        )
+    }
+
+    fn _sign(
+        &self,
+        frost_key: &FrostKey<EvenY>,
+        session: &SignSession,
+        lambda: Scalar<Public>,
         my_index: PartyIndex,
         secret_share: &Scalar,
         secret_nonce: NonceKeyPair,
     ) -> Scalar<Public, Zero> {
-        let mut lambda = poly::eval_basis_poly_at_0(my_index, session.nonces.keys());
         assert_eq!(
             *session
                 .nonces
@@ -907,15 +1744,15 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
             secret_nonce.public(),
             "secret nonce didn't match previously provided public nonce"
         );
-        lambda.conditional_negate(frost_key.needs_negation);
-        let [mut r1, mut r2] = secret_nonce.secret;
-        r1.conditional_negate(session.nonces_need_negation);
-        r2.conditional_negate(session.nonces_need_negation);
-
-        let b = &session.binding_coeff;
-        let x = secret_share;
-        let c = &session.challenge;
-        s!(r1 + (r2 * b) + lambda * x * c).public()
+        signing::sign(
+            &session.challenge,
+            &session.binding_coeffs[&my_index],
+            lambda,
+            frost_key.needs_negation,
+            session.nonces_need_negation,
+            secret_share,
+            secret_nonce,
+        )
     }
 
     /// Verify a partial signature for a participant at `index` (from zero).
@@ -927,23 +1764,156 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
         &self,
         frost_key: &FrostKey<EvenY>,
         session: &SignSession,
+        index: impl IntoPartyIndex,
+        signature_share: Scalar<Public, Zero>,
+    ) -> bool {
+        let index = index.into_party_index();
+        let lambda = poly::eval_basis_poly_at_0(index, session.nonces.keys());
+        self._verify_signature_share(frost_key, session, lambda, index, signature_share)
+    }
+
+    /// Like [`verify_signature_share`] but takes the lagrange coefficient from `basis` rather than
+    /// computing it.
+    ///
+    /// ## Panics
+    ///
+    /// If `basis` isn't for the signers of `session`.
+    ///
+    /// [`verify_signature_share`]: Self::verify_signature_share
+    pub fn verify_signature_share_with_basis(
+        &self,
+        frost_key: &FrostKey<EvenY>,
+        session: &SignSession,
+        basis: &poly::LagrangeBasis,
+        index: impl IntoPartyIndex,
+        signature_share: Scalar<Public, Zero>,
+    ) -> bool {
+        let index = index.into_party_index();
+        let lambda = session.coefficient_from_basis(basis, index);
+        self._verify_signature_share(frost_key, session, lambda, index, signature_share)
+    }
+
+    fn _verify_signature_share(
+        &self,
+        frost_key: &FrostKey<EvenY>,
+        session: &SignSession,
+        lambda: Scalar<Public>,
         index: PartyIndex,
         signature_share: Scalar<Public, Zero>,
     ) -> bool {
-        let s = signature_share;
-        let mut lambda = poly::eval_basis_poly_at_0(index, session.nonces.keys());
-        lambda.conditional_negate(frost_key.needs_negation);
-        let c = &session.challenge;
-        let b = &session.binding_coeff;
-        let X = frost_key.verification_share(&index);
-        let [R1, R2] = session
+        let nonce = session
             .nonces
             .get(&index)
-            .expect("verifying party index that is not part of frost signing coalition")
-            .0;
-        let R1 = R1.conditional_negate(session.nonces_need_negation);
-        let R2 = R2.conditional_negate(session.nonces_need_negation);
-        g!(R1 + b * R2 + (c * lambda) * X - s * G).is_zero()
+            .expect("verifying party index that is not part of frost signing coalition");
+        signing::verify(
+            &session.challenge,
+            &session.binding_coeffs[&index],
+            lambda,
+            frost_key.needs_negation,
+            session.nonces_need_negation,
+            &frost_key.verification_share(index),
+            nonce,
+            signature_share,
+        )
+    }
+
+    /// Check the attestation the party at `index` sent with their `signature_share`.
+    ///
+    /// See [`attest_signature_share`] for what it attests to.
+    ///
+    /// [`attest_signature_share`]: Self::attest_signature_share
+    pub fn verify_signature_share_attestation(
+        &self,
+        frost_key: &FrostKey<EvenY>,
+        session: &SignSession,
+        index: impl IntoPartyIndex,
+        signature_share: Scalar<Public, Zero>,
+        attestation: &Signature,
+    ) -> bool {
+        let index = index.into_party_index();
+        let verification_share = match frost_key.verification_share(index).normalize().non_zero() {
+            Some(verification_share) => verification_share.into_point_with_even_y().0,
+            None => return false,
+        };
+        let message_data = share_attestation_message(session, index, signature_share);
+        self.schnorr.verify(
+            &verification_share,
+            Message::<Public>::plain(SHARE_ATTESTATION_TAG, &message_data),
+            attestation,
+        )
+    }
+
+    /// Produce a [`SignatureShareBlame`] against the party at `index` if `signature_share` is
+    /// invalid.
+    ///
+    /// The `attestation` is the one the accused sent along with their signature share (see
+    /// [`attest_signature_share`]). Without it nothing stops us from blaming an honest party by
+    /// swapping out their nonce for one they never sent.
+    ///
+    /// Returns `None` if the signature share is actually valid or if the `attestation` isn't valid
+    /// for it. In the latter case you can't prove anything but you should still exclude the party.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is not part of the `session`.
+    ///
+    /// [`attest_signature_share`]: Self::attest_signature_share
+    pub fn blame_signature_share(
+        &self,
+        frost_key: &FrostKey<EvenY>,
+        session: &SignSession,
+        index: impl IntoPartyIndex,
+        signature_share: Scalar<Public, Zero>,
+        attestation: Signature,
+    ) -> Option<SignatureShareBlame> {
+        let index = index.into_party_index();
+        if self.verify_signature_share(frost_key, session, index, signature_share)
+            || !self.verify_signature_share_attestation(
+                frost_key,
+                session,
+                index,
+                signature_share,
+                &attestation,
+            )
+        {
+            return None;
+        }
+        Some(SignatureShareBlame {
+            index,
+            signature_share,
+            nonces: session.nonces.clone(),
+            attestation,
+        })
+    }
+
+    /// Check a [`SignatureShareBlame`] as a third party.
+    ///
+    /// The session is recomputed from `frost_key`, `message` and the nonces in the blame so
+    /// nothing from the signing session needs to be trusted. The accused's attestation binds them
+    /// to that session so the blame can only be valid if they really sent the invalid signature
+    /// share for it. This only works for sessions started with
+    /// [`start_sign_session`](Self::start_sign_session).
+    ///
+    /// ## Return value
+    ///
+    /// Returns `true` if the blamed signature share really is invalid.
+    pub fn verify_signature_share_blame(
+        &self,
+        frost_key: &FrostKey<EvenY>,
+        message: Message,
+        blame: &SignatureShareBlame,
+    ) -> bool {
+        if blame.nonces.len() < frost_key.threshold() || !blame.nonces.contains_key(&blame.index) {
+            return false;
+        }
+        let session = self.start_sign_session(frost_key, blame.nonces.clone(), message);
+        self.verify_signature_share_attestation(
+            frost_key,
+            &session,
+            blame.index,
+            blame.signature_share,
+            &blame.attestation,
+        ) && !self.verify_signature_share(frost_key, &session, blame.index, blame.signature_share)
     }
 
     /// Combine a vector of signatures shares into an aggregate signature.
@@ -961,42 +1931,457 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
         session: &SignSession,
         signature_shares: Vec<Scalar<Public, Zero>>,
     ) -> Signature {
-        let ck = s!(session.challenge * frost_key.tweak);
-        let sum_s = signature_shares
-            .into_iter()
-            .reduce(|acc, partial_sig| s!(acc + partial_sig).public())
-            .unwrap_or(Scalar::zero());
         Signature {
             R: session.agg_nonce,
-            s: s!(sum_s + ck).public(),
+            s: signing::combine(&session.challenge, &frost_key.tweak, signature_shares),
+        }
+    }
+
+    /// Combines the signature shares from every signer in `session` into a signature and if it's
+    /// invalid finds out which signers are to blame.
+    ///
+    /// The combined signature is checked first which is much cheaper than checking each signature
+    /// share with [`verify_signature_share`]. Only if it's invalid (or a signer's share is missing)
+    /// are the signature shares checked individually so the [`BlameList`] has every signer that
+    /// misbehaved. If the signature shares arrive one at a time use a
+    /// [`SignatureShareAggregator`] instead.
+    ///
+    /// [`verify_signature_share`]: Self::verify_signature_share
+    pub fn verify_and_combine_signature_shares(
+        &self,
+        frost_key: &FrostKey<EvenY>,
+        session: &SignSession,
+        signature_shares: &BTreeMap<PartyIndex, Scalar<Public, Zero>>,
+    ) -> Result<Signature, BlameList> {
+        let mut faults = signature_shares
+            .keys()
+            .filter(|index| !session.nonces.contains_key(index))
+            .map(|index| SignatureShareError::NotSigner(*index))
+            .collect::<Vec<_>>();
+        faults.extend(
+            session
+                .participants()
+                .filter(|index| !signature_shares.contains_key(index))
+                .map(SignatureShareError::Missing),
+        );
+
+        if faults.is_empty() {
+            let signature = self.combine_signature_shares(
+                frost_key,
+                session,
+                signature_shares.values().copied().collect(),
+            );
+            let (R, s, c, X) = (
+                &signature.R,
+                &signature.s,
+                &session.challenge,
+                frost_key.public_key(),
+            );
+            if g!(R + c * X - s * G).is_zero() {
+                return Ok(signature);
+            }
+        }
+
+        let basis = session.lagrange_basis();
+        faults.extend(
+            session
+                .participants()
+                .filter_map(|index| Some((index, *signature_shares.get(&index)?)))
+                .filter(|(index, signature_share)| {
+                    !self.verify_signature_share_with_basis(
+                        frost_key,
+                        session,
+                        &basis,
+                        *index,
+                        *signature_share,
+                    )
+                })
+                .map(|(index, _)| SignatureShareError::Invalid(index)),
+        );
+        Err(BlameList { faults })
+    }
+
+    /// Start combining signature shares one at a time as they arrive.
+    ///
+    /// See [`SignatureShareAggregator`].
+    pub fn signature_share_aggregator<'a>(
+        &'a self,
+        frost_key: &'a FrostKey<EvenY>,
+        session: &'a SignSession,
+    ) -> SignatureShareAggregator<'a, H, NG> {
+        SignatureShareAggregator {
+            frost: self,
+            frost_key,
+            session,
+            signature_shares: BTreeMap::new(),
         }
     }
 }
 
-/// A FROST signing session
+/// Verifies signature shares as they arrive and combines them once there is one from every signer.
 ///
-/// Created using [`Frost::start_sign_session`].
+/// Created with [`Frost::signature_share_aggregator`]. An invalid signature share is caught as soon
+/// as it's [`add`]ed so you know who to blame without waiting for the others. Adding the same
+/// signature share again is a no-op so redelivered messages can be passed straight in.
 ///
-/// [`Frost::start_sign_session`]
-#[derive(Clone, Debug, PartialEq)]
-#[cfg_attr(
-    feature = "bincode",
-    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
-    bincode(crate = "crate::fun::bincode")
-)]
-#[cfg_attr(
-    feature = "serde",
-    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
-    serde(crate = "crate::fun::serde")
+/// [`add`]: Self::add
+pub struct SignatureShareAggregator<'a, H, NG> {
+    frost: &'a Frost<H, NG>,
+    frost_key: &'a FrostKey<EvenY>,
+    session: &'a SignSession,
+    signature_shares: BTreeMap<PartyIndex, Scalar<Public, Zero>>,
+}
+
+impl<'a, H: Digest<OutputSize = U32> + Clone, NG> SignatureShareAggregator<'a, H, NG> {
+    /// Verifies and adds the signature share from the signer at `index`.
+    ///
+    /// Adding the signature share already added for `index` does nothing. If it's invalid or
+    /// conflicts with the one already added it is not added and the aggregator can still be used.
+    pub fn add(
+        &mut self,
+        index: impl IntoPartyIndex,
+        signature_share: Scalar<Public, Zero>,
+    ) -> Result<(), SignatureShareError> {
+        let index = index.into_party_index();
+        if !self.session.nonces.contains_key(&index) {
+            return Err(SignatureShareError::NotSigner(index));
+        }
+        match self.signature_shares.get(&index) {
+            Some(existing) if *existing == signature_share => return Ok(()),
+            Some(_) => return Err(SignatureShareError::Conflicting(index)),
+            None => {}
+        }
+        if !self
+            .frost
+            .verify_signature_share(self.frost_key, self.session, index, signature_share)
+        {
+            return Err(SignatureShareError::Invalid(index));
+        }
+        self.signature_shares.insert(index, signature_share);
+        Ok(())
+    }
+
+    /// The signers whose signature shares haven't been added yet.
+    pub fn missing(&self) -> impl Iterator<Item = PartyIndex> + '_ {
+        self.session
+            .participants()
+            .filter(|index| !self.signature_shares.contains_key(index))
+    }
+
+    /// Combines the signature shares into a signature.
+    ///
+    /// Returns an error if a signature share hasn't been added for every signer.
+    pub fn finish(self) -> Result<Signature, SignatureShareError> {
+        if let Some(index) = self.missing().next() {
+            return Err(SignatureShareError::Missing(index));
+        }
+        Ok(self.frost.combine_signature_shares(
+            self.frost_key,
+            self.session,
+            self.signature_shares.into_values().collect(),
+        ))
+    }
+}
+
+/// Error returned by a [`SignatureShareAggregator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureShareError {
+    /// The party isn't one of the signers in the session.
+    NotSigner(PartyIndex),
+    /// A different signature share from the signer has already been added.
+    Conflicting(PartyIndex),
+    /// The signer's signature share is invalid.
+    Invalid(PartyIndex),
+    /// The signer's signature share hasn't been added.
+    Missing(PartyIndex),
+}
+
+impl core::fmt::Display for SignatureShareError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        use SignatureShareError::*;
+        match self {
+            NotSigner(index) => write!(f, "party {index} is not a signer in the session"),
+            Conflicting(index) => write!(
+                f,
+                "already have a different signature share from party {index}"
+            ),
+            Invalid(index) => write!(f, "party {index} sent an invalid signature share"),
+            Missing(index) => write!(f, "missing a signature share from party {index}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SignatureShareError {}
+
+/// Every fault found by [`Frost::verify_and_combine_signature_shares`].
+///
+/// Evict the [`culprits`] from the signing group (or at least from the next session) and try
+/// again.
+///
+/// [`culprits`]: Self::culprits
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlameList {
+    /// What was wrong with each signature share that was at fault.
+    pub faults: Vec<SignatureShareError>,
+}
+
+impl BlameList {
+    /// The parties whose signature shares were missing or invalid (or who weren't signers at
+    /// all).
+    pub fn culprits(&self) -> impl Iterator<Item = PartyIndex> + '_ {
+        self.faults.iter().map(|fault| match fault {
+            SignatureShareError::NotSigner(index)
+            | SignatureShareError::Conflicting(index)
+            | SignatureShareError::Invalid(index)
+            | SignatureShareError::Missing(index) => *index,
+        })
+    }
+}
+
+impl core::fmt::Display for BlameList {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "combining the signature shares failed: ")?;
+        for (i, fault) in self.faults.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{fault}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BlameList {}
+
+/// Remembers the signature shares a signer has computed so handling a redelivered sign request is
+/// idempotent.
+///
+/// Networks redeliver messages. Signing the same session again would need the nonce that was
+/// consumed the first time so [`get_or_sign`] returns the cached signature share instead. The cache
+/// is keyed by the application's session id (e.g. the one passed to [`Frost::seed_nonce_rng`]) and
+/// a request under a cached session id for a different session is an error rather than a second
+/// signature with the same nonce.
+///
+/// # Example
+///
+/// ```
+/// use schnorr_fun::{frost, Message};
+/// use std::collections::BTreeMap;
+/// let frost = frost::new_with_deterministic_nonces::<sha2::Sha256>();
+/// let (frost_key, shares) = frost.simulate_keygen(1, 1, &mut rand::thread_rng());
+/// let frost_key = frost_key.into_xonly_key();
+/// let (my_index, my_share) = shares.into_iter().next().unwrap();
+/// let nonce = frost.gen_nonce(&mut rand::thread_rng());
+/// let session = frost.start_sign_session(
+///     &frost_key,
+///     BTreeMap::from_iter([(my_index, nonce.public())]),
+///     Message::plain("my-app", b"hello"),
+/// );
+/// let mut cache = frost::SignatureShareCache::default();
+/// let share = cache.get_or_sign(*b"session-1", &session, || {
+///     frost.sign(&frost_key, &session, my_index, &my_share, nonce)
+/// })?;
+/// // the sign request is redelivered but the nonce is gone
+/// let again = cache.get_or_sign(*b"session-1", &session, || unreachable!())?;
+/// assert_eq!(share, again);
+/// # Ok::<(), frost::ConflictingSession>(())
+/// ```
+///
+/// [`get_or_sign`]: Self::get_or_sign
+#[derive(Clone, Debug)]
+pub struct SignatureShareCache<K> {
+    shares: BTreeMap<K, CachedShare>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct CachedShare {
+    agg_nonce: Point<EvenY>,
+    challenge: Scalar<Public, Zero>,
+    signature_share: Scalar<Public, Zero>,
+}
+
+impl<K> Default for SignatureShareCache<K> {
+    fn default() -> Self {
+        Self {
+            shares: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K: Ord> SignatureShareCache<K> {
+    /// Returns the signature share cached for `session_id` or calls `sign` and caches what it
+    /// returns.
+    ///
+    /// Returns an error without calling `sign` if a share was cached for `session_id` under a
+    /// different session.
+    pub fn get_or_sign(
+        &mut self,
+        session_id: K,
+        session: &SignSession,
+        sign: impl FnOnce() -> Scalar<Public, Zero>,
+    ) -> Result<Scalar<Public, Zero>, ConflictingSession> {
+        match self.shares.entry(session_id) {
+            btree_map::Entry::Occupied(entry) => {
+                let cached = entry.get();
+                if cached.agg_nonce != session.agg_nonce || cached.challenge != session.challenge {
+                    return Err(ConflictingSession);
+                }
+                Ok(cached.signature_share)
+            }
+            btree_map::Entry::Vacant(entry) => {
+                let signature_share = sign();
+                entry.insert(CachedShare {
+                    agg_nonce: session.agg_nonce,
+                    challenge: session.challenge,
+                    signature_share,
+                });
+                Ok(signature_share)
+            }
+        }
+    }
+
+    /// The signature share cached for `session_id` if there is one.
+    pub fn get(&self, session_id: &K) -> Option<Scalar<Public, Zero>> {
+        self.shares
+            .get(session_id)
+            .map(|cached| cached.signature_share)
+    }
+
+    /// Forgets the signature share cached for `session_id` e.g. once the signature is complete.
+    pub fn remove(&mut self, session_id: &K) -> Option<Scalar<Public, Zero>> {
+        self.shares
+            .remove(session_id)
+            .map(|cached| cached.signature_share)
+    }
+
+    /// The number of cached signature shares.
+    pub fn len(&self) -> usize {
+        self.shares.len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.shares.is_empty()
+    }
+}
+
+/// Error returned by [`SignatureShareCache::get_or_sign`] when the session id was already used to
+/// sign a different session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConflictingSession;
+
+impl core::fmt::Display for ConflictingSession {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "the session id was already used to sign a different session"
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConflictingSession {}
+
+/// A message broadcast by a signer during a signing ceremony.
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub enum SigningMessage {
+    /// The signer's public nonce (the first round).
+    Nonce(Nonce),
+    /// The signer's signature share (the second round).
+    SignatureShare(Scalar<Public, Zero>),
+}
+
+impl SigningMessage {
+    /// The length of the longest encoding of a signing message.
+    ///
+    /// Network layers can use this to allocate a fixed buffer and drop anything longer unread.
+    pub const MAX_SIZE: usize = 1 + Nonce::<NonZero>::SIZE;
+
+    /// Encodes the message as a type byte (`0` for a nonce and `1` for a signature share)
+    /// followed by the nonce or signature share.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::MAX_SIZE);
+        match self {
+            SigningMessage::Nonce(nonce) => {
+                bytes.push(0);
+                bytes.extend_from_slice(&nonce.to_bytes());
+            }
+            SigningMessage::SignatureShare(share) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&share.to_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Decodes a message from [`to_bytes`](Self::to_bytes).
+    ///
+    /// Returns `None` without looking at the contents if `bytes` is longer than
+    /// [`MAX_SIZE`](Self::MAX_SIZE).
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() > Self::MAX_SIZE {
+            return None;
+        }
+        let (kind, rest) = bytes.split_first()?;
+        match kind {
+            0 => Some(SigningMessage::Nonce(Nonce::from_bytes(
+                rest.try_into().ok()?,
+            )?)),
+            1 => Some(SigningMessage::SignatureShare(Scalar::from_bytes(
+                rest.try_into().ok()?,
+            )?)),
+            _ => None,
+        }
+    }
+}
+
+/// A FROST signing session
+///
+/// Created using [`Frost::start_sign_session`].
+///
+/// [`Frost::start_sign_session`]
+#[derive(Clone, PartialEq)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
 )]
 pub struct SignSession {
-    binding_coeff: Scalar,
+    binding_coeffs: BTreeMap<PartyIndex, Scalar<Public, Zero>>,
     nonces_need_negation: bool,
     agg_nonce: Point<EvenY>,
     challenge: Scalar<Public, Zero>,
     nonces: BTreeMap<PartyIndex, Nonce>,
 }
 
+impl core::fmt::Debug for SignSession {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("SignSession")
+            .field("participants", &self.nonces.keys())
+            .field("agg_nonce", &self.agg_nonce)
+            .field("challenge", &self.challenge)
+            .finish()
+    }
+}
+
 impl SignSession {
     /// Fetch the participant indices for this signing session.
     ///
@@ -1006,6 +2391,348 @@ impl SignSession {
     pub fn participants(&self) -> impl DoubleEndedIterator<Item = PartyIndex> + '_ {
         self.nonces.keys().copied()
     }
+
+    /// The lagrange coefficients of the participants.
+    ///
+    /// Pass this to [`Frost::sign_with_basis`] and [`Frost::verify_signature_share_with_basis`]
+    /// in later sessions with the same participants to avoid computing them again.
+    pub fn lagrange_basis(&self) -> poly::LagrangeBasis {
+        poly::LagrangeBasis::new(self.participants())
+    }
+
+    fn coefficient_from_basis(
+        &self,
+        basis: &poly::LagrangeBasis,
+        index: PartyIndex,
+    ) -> Scalar<Public> {
+        assert!(
+            basis.is_for(self.nonces.keys()),
+            "lagrange basis was not for the participants of the session"
+        );
+        basis.coefficient(&index).expect("index was not in session")
+    }
+}
+
+/// Proof that a party produced an invalid signature share.
+///
+/// An arbiter that knows the [`FrostKey`] and the message being signed can check it with
+/// [`Frost::verify_signature_share_blame`] without any session secrets. The accused party's
+/// attestation (see [`Frost::attest_signature_share`]) shows that they sent the signature share for
+/// a session with exactly these nonces.
+///
+/// Created with [`Frost::blame_signature_share`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct SignatureShareBlame {
+    /// The index of the accused party.
+    pub index: PartyIndex,
+    /// The invalid signature share.
+    pub signature_share: Scalar<Public, Zero>,
+    /// The nonces of the signing session.
+    pub nonces: BTreeMap<PartyIndex, Nonce>,
+    /// The accused party's attestation to the signature share and nonces.
+    pub attestation: Signature,
+}
+
+/// Proof that a party sent a secret share during key generation that doesn't match their
+/// polynomial commitment.
+///
+/// It can be checked by anyone with [`KeyGenShareBlame::verify`]. Unlike [`SignatureShareBlame`] it
+/// doesn't prove that the accused party actually sent the share or the point polynomial so these
+/// should be signed by their sender.
+///
+/// ⚠ The proof contains the secret share the accused party sent to `receiver`. That share is
+/// useless once the key generation has been aborted but you should never produce one for a key
+/// generation session you intend to keep using.
+///
+/// Created with [`KeyGen::blame_share`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct KeyGenShareBlame {
+    /// The index of the accused party.
+    pub accused: PartyIndex,
+    /// The index of the party that received the bad share.
+    pub receiver: PartyIndex,
+    /// The point polynomial the accused party committed to.
+    pub point_poly: Vec<Point>,
+    /// The secret share the accused party sent to the receiver.
+    pub secret_share: Scalar<Secret, Zero>,
+}
+
+impl KeyGenShareBlame {
+    /// Checks the proof.
+    ///
+    /// ## Return value
+    ///
+    /// Returns `true` if the secret share really is inconsistent with the point polynomial.
+    pub fn verify(&self) -> bool {
+        let expected_public_share = poly::point::eval(&self.point_poly, self.receiver);
+        g!(self.secret_share * G) != expected_public_share
+    }
+}
+
+/// A batch of nonces generated ahead of time so that signing only needs a single round.
+///
+/// In the preprocessing variant of FROST each signer publishes the [`commitments`] of a batch of
+/// nonces before the message is known. A later signing request names the index of the nonce within
+/// the batch that each signer should use. The coordinator can then start a [`SignSession`]
+/// straight away using the already published commitments and each signer replies with their
+/// signature share.
+///
+/// The secret nonces have to be stored somewhere until they are used so this type can be
+/// serialized. A nonce must **never** be used twice so you have to take secret nonces out with
+/// [`take`] which records that the index has been consumed in a [`ConsumedNonces`] tracker and
+/// removes the secret nonce from the batch. If the batch is stored persistently the tracker must
+/// be too. Serializing the batch copies its secret nonces so a serialized batch must **never** be
+/// restored from an older snapshot unless its [`ConsumedNonces`] tracker is restored with it and
+/// still records every nonce taken since the snapshot (a tracker rolled back along with the batch
+/// doesn't help). Otherwise rolling back the batch hands out nonces that have already been used.
+///
+/// Create one with [`Frost::preprocess_nonces`].
+///
+/// [`commitments`]: Self::commitments
+/// [`take`]: Self::take
+#[derive(Debug)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct PreprocessedNonces {
+    commitments: Vec<Nonce>,
+    nonces: Vec<Option<NonceKeyPair>>,
+}
+
+#[cfg(feature = "zeroize")]
+impl crate::fun::zeroize::Zeroize for PreprocessedNonces {
+    /// Overwrites the secret nonces leaving every nonce in the batch consumed.
+    fn zeroize(&mut self) {
+        for nonce in &mut self.nonces {
+            nonce.zeroize();
+        }
+    }
+}
+
+// `Drop` is implemented with or without `zeroize` so that enabling it doesn't change the API
+impl Drop for PreprocessedNonces {
+    fn drop(&mut self) {
+        #[cfg(feature = "zeroize")]
+        crate::fun::zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl crate::fun::zeroize::ZeroizeOnDrop for PreprocessedNonces {}
+
+impl PreprocessedNonces {
+    /// The number of nonces in the batch (including consumed ones).
+    pub fn len(&self) -> usize {
+        self.commitments.len()
+    }
+
+    /// Whether the batch is empty.
+    pub fn is_empty(&self) -> bool {
+        self.commitments.is_empty()
+    }
+
+    /// The public nonces of the batch in order. These are what you publish ahead of time.
+    pub fn commitments(&self) -> Vec<Nonce> {
+        self.commitments.clone()
+    }
+
+    /// Take the secret nonce at `index` out of the batch to sign with it.
+    ///
+    /// `index` is marked as consumed in `consumed` *before* the nonce is returned so it can never
+    /// be handed out again by the same tracker. The nonce is removed from the batch so it can't be
+    /// handed out again even with a different tracker.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if `index` is not in the batch, if it has already been consumed or if the
+    /// tracker failed to record that it has been consumed. In the last case the nonce is left in
+    /// the batch and can be taken once the tracker works again.
+    pub fn take<C: ConsumedNonces>(
+        &mut self,
+        index: u32,
+        consumed: &mut C,
+    ) -> Result<NonceKeyPair, TakeNonceError<C::Error>> {
+        let slot = self
+            .nonces
+            .get_mut(index as usize)
+            .ok_or(TakeNonceError::OutOfRange(index))?;
+        if consumed.is_consumed(index) || slot.is_none() {
+            return Err(TakeNonceError::AlreadyConsumed(index));
+        }
+        consumed
+            .mark_consumed(index)
+            .map_err(TakeNonceError::Tracker)?;
+        Ok(slot.take().expect("checked above"))
+    }
+}
+
+/// Tracks which nonces of a [`PreprocessedNonces`] batch have already been used.
+///
+/// If you persist a batch of preprocessed nonces you must persist the tracker alongside it and
+/// [`mark_consumed`] must not return `Ok` until the consumption has been durably recorded.
+/// Otherwise a crash could lead to the same nonce being used to sign two different messages which
+/// leaks your secret share.
+///
+/// [`mark_consumed`]: Self::mark_consumed
+pub trait ConsumedNonces {
+    /// The error returned when the consumption can't be recorded.
+    type Error;
+    /// Whether the nonce at `index` has been consumed.
+    fn is_consumed(&self, index: u32) -> bool;
+    /// Durably record that the nonce at `index` has been consumed.
+    ///
+    /// If this returns an error [`PreprocessedNonces::take`] won't hand out the nonce.
+    fn mark_consumed(&mut self, index: u32) -> Result<(), Self::Error>;
+}
+
+/// An in-memory tracker (useful when the batch isn't persisted).
+impl ConsumedNonces for BTreeSet<u32> {
+    type Error = Infallible;
+
+    fn is_consumed(&self, index: u32) -> bool {
+        self.contains(&index)
+    }
+
+    fn mark_consumed(&mut self, index: u32) -> Result<(), Self::Error> {
+        self.insert(index);
+        Ok(())
+    }
+}
+
+impl<T: ConsumedNonces> ConsumedNonces for &mut T {
+    type Error = T::Error;
+
+    fn is_consumed(&self, index: u32) -> bool {
+        T::is_consumed(self, index)
+    }
+
+    fn mark_consumed(&mut self, index: u32) -> Result<(), Self::Error> {
+        T::mark_consumed(self, index)
+    }
+}
+
+/// Error returned by [`PreprocessedNonces::take`].
+///
+/// `E` is the [`ConsumedNonces::Error`] of the tracker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TakeNonceError<E = Infallible> {
+    /// There is no nonce at this index in the batch.
+    OutOfRange(u32),
+    /// The nonce at this index has already been used.
+    AlreadyConsumed(u32),
+    /// The tracker failed to record that the nonce has been consumed.
+    Tracker(E),
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for TakeNonceError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        use TakeNonceError::*;
+        match self {
+            OutOfRange(i) => write!(f, "there is no preprocessed nonce at index {i}"),
+            AlreadyConsumed(i) => write!(
+                f,
+                "the preprocessed nonce at index {i} has already been used"
+            ),
+            Tracker(error) => write!(f, "failed to record the nonce as consumed: {error}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: core::fmt::Debug + core::fmt::Display> std::error::Error for TakeNonceError<E> {}
+
+/// Splits an existing secret key into `n_parties` FROST shares any `threshold` of which can sign.
+///
+/// This is for turning a single signer wallet into a threshold one without moving its coins: the
+/// returned package's public key is `secret_key * G` so it's the key the coins are already locked
+/// to (after [`into_xonly_key`] for BIP340 outputs). The key owner acts as a trusted dealer so
+/// they must hand each share to its party over a secure channel and then delete `secret_key`.
+/// Until they do the key isn't really threshold: anyone who copied it can still spend alone.
+///
+/// The parties are indexed from `1` to `n_parties` like [`Frost::simulate_keygen`].
+///
+/// # Panics
+///
+/// If `threshold` is zero or greater than `n_parties`.
+///
+/// # Example
+///
+/// ```
+/// use schnorr_fun::{
+///     frost,
+///     fun::{g, poly, Scalar, G},
+/// };
+/// let secret_key = Scalar::random(&mut rand::thread_rng());
+/// let (package, shares) = frost::split_existing_key(&secret_key, 2, 3, &mut rand::thread_rng());
+/// assert_eq!(package.public_key(), g!(secret_key * G).normalize());
+/// assert_eq!(package.threshold(), 2);
+/// // any two shares recover the original key
+/// let two = shares.iter().take(2).map(|(index, share)| (*index, *share)).collect();
+/// assert_eq!(poly::scalar::interpolate_and_eval_poly_at_0(two), secret_key);
+/// ```
+///
+/// [`into_xonly_key`]: FrostKey::into_xonly_key
+pub fn split_existing_key(
+    secret_key: &Scalar,
+    threshold: usize,
+    n_parties: usize,
+    rng: &mut impl RngCore,
+) -> (FrostPublicKeyPackage, BTreeMap<PartyIndex, Scalar>) {
+    assert!(
+        threshold > 0 && threshold <= n_parties,
+        "threshold must be between 1 and the number of parties"
+    );
+    let mut scalar_poly = poly::scalar::generate(threshold, rng);
+    scalar_poly[0] = *secret_key;
+    let point_poly = poly::scalar::to_point_poly(&scalar_poly)
+        .into_iter()
+        .map(|coefficient| coefficient.mark_zero())
+        .collect();
+    let frost_key =
+        FrostKey::from_point_polynomial(point_poly).expect("the secret key is not zero");
+    let shares = (1..=n_parties)
+        .map(|i| {
+            let index =
+                Scalar::from_non_zero_u32(NonZeroU32::new(i as u32).expect("we start at 1"))
+                    .public();
+            let share = poly::scalar::eval(&scalar_poly, index)
+                .non_zero()
+                .expect("computationally unreachable");
+            (index, share)
+        })
+        .collect::<BTreeMap<_, _>>();
+    #[cfg(feature = "zeroize")]
+    crate::fun::zeroize::Zeroize::zeroize(&mut scalar_poly);
+    let package = FrostPublicKeyPackage::new(&frost_key, shares.keys().copied())
+        .expect("there is a share for every party");
+    (package, shares)
 }
 
 /// Constructor for a Frost instance using deterministic nonce generation.
@@ -1136,6 +2863,7 @@ impl From<EncodedFrostKey> for FrostKey<Normal> {
             point_polynomial: from.point_polynomial,
             tweak: Scalar::zero(),
             needs_negation: false,
+            generation: KeyGeneration::default(),
         }
     }
 }
@@ -1154,6 +2882,49 @@ mod test {
     use super::*;
     use sha2::Sha256;
 
+    #[test]
+    fn key_generations() {
+        let frost = new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, _shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        assert_eq!(frost_key.generation(), KeyGeneration::default());
+
+        let mut refreshed_poly = frost_key.point_polynomial();
+        refreshed_poly[1] = Point::random(&mut rand::thread_rng()).mark_zero();
+        let tweaked_key = frost_key.clone().tweak(s!(42)).unwrap();
+        assert_eq!(
+            tweaked_key.fingerprint::<Sha256>(),
+            frost_key.fingerprint::<Sha256>()
+        );
+        let next_key = tweaked_key
+            .next_generation::<Sha256>(refreshed_poly.clone())
+            .unwrap();
+        assert_eq!(next_key.public_key(), tweaked_key.public_key());
+        assert_ne!(
+            next_key.fingerprint::<Sha256>(),
+            frost_key.fingerprint::<Sha256>()
+        );
+        assert_eq!(
+            next_key.generation(),
+            KeyGeneration {
+                counter: 1,
+                parent: Some(frost_key.fingerprint::<Sha256>()),
+            }
+        );
+        assert_eq!(next_key.check_generation(next_key.generation()), Ok(()));
+        assert_eq!(
+            frost_key.check_generation(next_key.generation()),
+            Err(StaleGeneration {
+                current: next_key.generation(),
+                got: KeyGeneration::default(),
+            })
+        );
+
+        refreshed_poly[0] = Point::random(&mut rand::thread_rng()).mark_zero();
+        assert!(frost_key
+            .next_generation::<Sha256>(refreshed_poly)
+            .is_none());
+    }
+
     #[test]
     fn zero_agg_nonce_results_in_G() {
         let frost = new_with_deterministic_nonces::<Sha256>();
@@ -1170,4 +2941,716 @@ mod test {
 
         assert_eq!(session.agg_nonce, *G);
     }
+
+    #[test]
+    fn blame_proofs() {
+        let frost = new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        let frost_key = frost_key.into_xonly_key();
+        let message = Message::<Public>::plain("test", b"blame");
+        let secret_nonces = shares
+            .keys()
+            .take(2)
+            .map(|index| (*index, NonceKeyPair::random(&mut rand::thread_rng())))
+            .collect::<BTreeMap<_, _>>();
+        let session = frost.start_sign_session(
+            &frost_key,
+            secret_nonces
+                .iter()
+                .map(|(index, nonce)| (*index, nonce.public()))
+                .collect(),
+            message,
+        );
+        let (honest_index, cheat_index) = (s!(1).public(), s!(2).public());
+        let honest = frost.sign(
+            &frost_key,
+            &session,
+            honest_index,
+            &shares[&honest_index],
+            secret_nonces[&honest_index].clone(),
+        );
+        let honest_attestation =
+            frost.attest_signature_share(&session, honest_index, &shares[&honest_index], honest);
+        assert_eq!(
+            frost.blame_signature_share(
+                &frost_key,
+                &session,
+                honest_index,
+                honest,
+                honest_attestation.clone()
+            ),
+            None
+        );
+        let cheat_attestation =
+            frost.attest_signature_share(&session, cheat_index, &shares[&cheat_index], honest);
+        assert_eq!(
+            frost.blame_signature_share(
+                &frost_key,
+                &session,
+                cheat_index,
+                honest,
+                honest_attestation
+            ),
+            None,
+            "can't blame without the accused's attestation"
+        );
+        let blame = frost
+            .blame_signature_share(&frost_key, &session, cheat_index, honest, cheat_attestation)
+            .unwrap();
+        assert!(frost.verify_signature_share_blame(&frost_key, message, &blame));
+        let false_blame = SignatureShareBlame {
+            index: honest_index,
+            ..blame.clone()
+        };
+        assert!(!frost.verify_signature_share_blame(&frost_key, message, &false_blame));
+    }
+
+    #[test]
+    fn blame_cant_frame_with_substituted_nonce() {
+        let frost = new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        let frost_key = frost_key.into_xonly_key();
+        let message = Message::<Public>::plain("test", b"blame");
+        let secret_nonces = shares
+            .keys()
+            .take(2)
+            .map(|index| (*index, NonceKeyPair::random(&mut rand::thread_rng())))
+            .collect::<BTreeMap<_, _>>();
+        let nonces = secret_nonces
+            .iter()
+            .map(|(index, nonce)| (*index, nonce.public()))
+            .collect::<BTreeMap<_, _>>();
+        let session = frost.start_sign_session(&frost_key, nonces.clone(), message);
+        let index = s!(1).public();
+        let share = frost.sign(
+            &frost_key,
+            &session,
+            index,
+            &shares[&index],
+            secret_nonces[&index].clone(),
+        );
+        let attestation = frost.attest_signature_share(&session, index, &shares[&index], share);
+
+        let mut substituted = nonces;
+        substituted.insert(
+            index,
+            NonceKeyPair::random(&mut rand::thread_rng()).public(),
+        );
+        let framed = SignatureShareBlame {
+            index,
+            signature_share: share,
+            nonces: substituted,
+            attestation,
+        };
+        assert!(!frost.verify_signature_share_blame(&frost_key, message, &framed));
+
+        let my_poly = poly::scalar::generate(2, &mut rand::thread_rng());
+        let other_poly = poly::scalar::generate(2, &mut rand::thread_rng());
+        let (my_index, other_index) = (s!(1).public(), s!(2).public());
+        let keygen = frost
+            .new_keygen(
+                BTreeMap::from_iter([(other_index, poly::scalar::to_point_poly(&other_poly))]),
+                &BTreeMap::from_iter([(my_index, &my_poly)]),
+            )
+            .unwrap();
+        let good_share = frost.create_share(&other_poly, my_index);
+        assert_eq!(keygen.blame_share(other_index, my_index, good_share), None);
+        let bad_share = s!(good_share + 1);
+        let blame = keygen
+            .blame_share(other_index, my_index, bad_share)
+            .unwrap();
+        assert!(blame.verify());
+    }
+
+    #[test]
+    fn derive_bip32_child_signs() {
+        use sha2::Sha512;
+        let frost = new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        let xpub = frost_key.xpub();
+        let (child, child_xpub) = frost_key.derive_bip32::<Sha512>(&[1, 2]).unwrap();
+        assert_eq!(child.public_key(), child_xpub.public_key);
+        assert_eq!(xpub.derive_path::<Sha512>(&[1, 2]).unwrap().0, child_xpub);
+
+        let child = child.into_xonly_key();
+        let message = Message::<Public>::plain("test", b"child");
+        let secret_nonces = shares
+            .keys()
+            .take(2)
+            .map(|index| (*index, NonceKeyPair::random(&mut rand::thread_rng())))
+            .collect::<BTreeMap<_, _>>();
+        let session = frost.start_sign_session(
+            &child,
+            secret_nonces
+                .iter()
+                .map(|(index, nonce)| (*index, nonce.public()))
+                .collect(),
+            message,
+        );
+        let signature_shares = secret_nonces
+            .iter()
+            .map(|(index, nonce)| {
+                frost.sign(&child, &session, *index, &shares[index], nonce.clone())
+            })
+            .collect();
+        let signature = frost.combine_signature_shares(&child, &session, signature_shares);
+        assert!(frost
+            .schnorr
+            .verify(&child.public_key(), message, &signature));
+    }
+
+    #[test]
+    fn split_existing_key_signs() {
+        let frost = new_with_deterministic_nonces::<Sha256>();
+        let keypair = secp256kfun::KeyPair::<EvenY>::new(Scalar::random(&mut rand::thread_rng()));
+        let (package, shares) =
+            split_existing_key(keypair.secret_key(), 2, 3, &mut rand::thread_rng());
+        assert_eq!(shares.len(), 3);
+        let frost_key = package.frost_key().into_xonly_key();
+        assert_eq!(frost_key.public_key(), keypair.public_key());
+
+        let message = Message::<Public>::plain("test", b"upgraded");
+        let secret_nonces = shares
+            .keys()
+            .skip(1)
+            .map(|index| (*index, NonceKeyPair::random(&mut rand::thread_rng())))
+            .collect::<BTreeMap<_, _>>();
+        let session = frost.start_sign_session(
+            &frost_key,
+            secret_nonces
+                .iter()
+                .map(|(index, nonce)| (*index, nonce.public()))
+                .collect(),
+            message,
+        );
+        let signature_shares = secret_nonces
+            .iter()
+            .map(|(index, nonce)| {
+                let share = frost.sign(&frost_key, &session, *index, &shares[index], nonce.clone());
+                assert!(frost.verify_signature_share(&frost_key, &session, *index, share));
+                share
+            })
+            .collect();
+        let signature = frost.combine_signature_shares(&frost_key, &session, signature_shares);
+        assert!(frost
+            .schnorr
+            .verify(&keypair.public_key(), message, &signature));
+    }
+
+    #[test]
+    fn signing_with_lagrange_basis() {
+        let frost = new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        let frost_key = frost_key.into_xonly_key();
+        let signers = shares.keys().copied().take(2).collect::<Vec<_>>();
+        let mut basis = None;
+        for message in [b"first", b"later"] {
+            let message = Message::<Public>::plain("test", message);
+            let secret_nonces = signers
+                .iter()
+                .map(|index| (*index, NonceKeyPair::random(&mut rand::thread_rng())))
+                .collect::<BTreeMap<_, _>>();
+            let session = frost.start_sign_session(
+                &frost_key,
+                secret_nonces
+                    .iter()
+                    .map(|(index, nonce)| (*index, nonce.public()))
+                    .collect(),
+                message,
+            );
+            let basis = basis.get_or_insert_with(|| session.lagrange_basis());
+            let signature_shares = secret_nonces
+                .into_iter()
+                .map(|(index, secret_nonce)| {
+                    let share = frost.sign_with_basis(
+                        &frost_key,
+                        &session,
+                        basis,
+                        index,
+                        &shares[&index],
+                        secret_nonce,
+                    );
+                    assert!(frost.verify_signature_share(&frost_key, &session, index, share));
+                    assert!(frost.verify_signature_share_with_basis(
+                        &frost_key, &session, basis, index, share
+                    ));
+                    share
+                })
+                .collect();
+            let signature = frost.combine_signature_shares(&frost_key, &session, signature_shares);
+            assert!(frost
+                .schnorr
+                .verify(&frost_key.public_key(), message, &signature));
+        }
+    }
+
+    #[test]
+    fn aggregate_signature_shares_one_at_a_time() {
+        let frost = new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        let frost_key = frost_key.into_xonly_key();
+        let message = Message::<Public>::plain("test", b"aggregator");
+        let secret_nonces = shares
+            .keys()
+            .take(2)
+            .map(|index| (*index, NonceKeyPair::random(&mut rand::thread_rng())))
+            .collect::<BTreeMap<_, _>>();
+        let session = frost.start_sign_session(
+            &frost_key,
+            secret_nonces
+                .iter()
+                .map(|(index, nonce)| (*index, nonce.public()))
+                .collect(),
+            message,
+        );
+        let signature_shares = secret_nonces
+            .into_iter()
+            .map(|(index, secret_nonce)| {
+                let share = frost.sign(&frost_key, &session, index, &shares[&index], secret_nonce);
+                (index, share)
+            })
+            .collect::<Vec<_>>();
+        let (first, second) = (signature_shares[0], signature_shares[1]);
+
+        let mut aggregator = frost.signature_share_aggregator(&frost_key, &session);
+        assert_eq!(
+            aggregator.add(s!(3).public(), first.1),
+            Err(SignatureShareError::NotSigner(s!(3).public()))
+        );
+        assert_eq!(
+            aggregator.add(second.0, first.1),
+            Err(SignatureShareError::Invalid(second.0))
+        );
+        assert_eq!(aggregator.add(first.0, first.1), Ok(()));
+        assert_eq!(aggregator.add(first.0, first.1), Ok(()));
+        assert_eq!(
+            aggregator.add(first.0, second.1),
+            Err(SignatureShareError::Conflicting(first.0))
+        );
+        assert_eq!(aggregator.missing().collect::<Vec<_>>(), vec![second.0]);
+        assert_eq!(aggregator.add(second.0, second.1), Ok(()));
+        let signature = aggregator.finish().unwrap();
+        assert!(frost
+            .schnorr
+            .verify(&frost_key.public_key(), message, &signature));
+
+        let aggregator = frost.signature_share_aggregator(&frost_key, &session);
+        assert_eq!(
+            aggregator.finish(),
+            Err(SignatureShareError::Missing(first.0))
+        );
+    }
+
+    #[test]
+    fn verify_and_combine_blames_signers() {
+        let frost = new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, shares) = frost.simulate_keygen(3, 4, &mut rand::thread_rng());
+        let frost_key = frost_key.into_xonly_key();
+        let message = Message::<Public>::plain("test", b"blame list");
+        let secret_nonces = shares
+            .keys()
+            .take(3)
+            .map(|index| (*index, NonceKeyPair::random(&mut rand::thread_rng())))
+            .collect::<BTreeMap<_, _>>();
+        let session = frost.start_sign_session(
+            &frost_key,
+            secret_nonces
+                .iter()
+                .map(|(index, nonce)| (*index, nonce.public()))
+                .collect(),
+            message,
+        );
+        let signature_shares = secret_nonces
+            .into_iter()
+            .map(|(index, secret_nonce)| {
+                let share = frost.sign(&frost_key, &session, index, &shares[&index], secret_nonce);
+                (index, share)
+            })
+            .collect::<BTreeMap<_, _>>();
+        let signature = frost
+            .verify_and_combine_signature_shares(&frost_key, &session, &signature_shares)
+            .unwrap();
+        assert!(frost
+            .schnorr
+            .verify(&frost_key.public_key(), message, &signature));
+
+        let (one, two, three, four) = (
+            1.into_party_index(),
+            2.into_party_index(),
+            3.into_party_index(),
+            4.into_party_index(),
+        );
+        let mut bad_shares = signature_shares.clone();
+        *bad_shares.get_mut(&one).unwrap() = s!(42).public().mark_zero();
+        *bad_shares.get_mut(&three).unwrap() = signature_shares[&two];
+        let blame = frost
+            .verify_and_combine_signature_shares(&frost_key, &session, &bad_shares)
+            .unwrap_err();
+        assert_eq!(blame.culprits().collect::<Vec<_>>(), vec![one, three]);
+
+        let mut missing_and_extra = signature_shares.clone();
+        missing_and_extra.remove(&two);
+        missing_and_extra.insert(four, signature_shares[&one]);
+        let blame = frost
+            .verify_and_combine_signature_shares(&frost_key, &session, &missing_and_extra)
+            .unwrap_err();
+        assert_eq!(
+            blame.faults,
+            vec![
+                SignatureShareError::NotSigner(four),
+                SignatureShareError::Missing(two)
+            ]
+        );
+    }
+
+    #[test]
+    fn signature_share_cache_is_idempotent() {
+        let frost = new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, shares) = frost.simulate_keygen(1, 2, &mut rand::thread_rng());
+        let frost_key = frost_key.into_xonly_key();
+        let (index, share) = shares.into_iter().next().unwrap();
+        let start = |message| {
+            let nonce = frost.gen_nonce(&mut rand::thread_rng());
+            let session = frost.start_sign_session(
+                &frost_key,
+                BTreeMap::from_iter([(index, nonce.public())]),
+                Message::<Public>::plain("test", message),
+            );
+            (session, nonce)
+        };
+        let (session, nonce) = start(b"cache");
+        let mut cache = SignatureShareCache::default();
+        let signature_share = cache
+            .get_or_sign(1u64, &session, || {
+                frost.sign(&frost_key, &session, index, &share, nonce)
+            })
+            .unwrap();
+        assert!(frost.verify_signature_share(&frost_key, &session, index, signature_share));
+        assert_eq!(
+            cache.get_or_sign(1, &session, || panic!("signed twice")),
+            Ok(signature_share)
+        );
+        let (other_session, _) = start(b"other");
+        assert_eq!(
+            cache.get_or_sign(1, &other_session, || panic!("signed a conflicting session")),
+            Err(ConflictingSession)
+        );
+        assert_eq!(cache.remove(&1), Some(signature_share));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn preprocessed_nonces_are_only_used_once() {
+        let frost = new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        let frost_key = frost_key.into_xonly_key();
+        let indexes = [s!(1).public(), s!(2).public()];
+
+        // every signer publishes a batch of commitments ahead of time
+        let mut batches = indexes
+            .iter()
+            .map(|index| {
+                let mut nonce_rng: rand_chacha::ChaCha20Rng =
+                    frost.seed_nonce_rng(&frost_key, &shares[index], b"batch-0");
+                (*index, frost.preprocess_nonces(&mut nonce_rng, 4))
+            })
+            .collect::<BTreeMap<_, _>>();
+        let published = batches
+            .iter()
+            .map(|(index, batch)| (*index, batch.commitments()))
+            .collect::<BTreeMap<_, _>>();
+        let mut consumed = indexes
+            .iter()
+            .map(|index| (*index, BTreeSet::new()))
+            .collect::<BTreeMap<_, _>>();
+
+        for nonce_index in [2u32, 0] {
+            let message = Message::<Public>::plain("test", b"preprocessed");
+            let nonces = published
+                .iter()
+                .map(|(index, commitments)| (*index, commitments[nonce_index as usize]))
+                .collect();
+            let session = frost.start_sign_session(&frost_key, nonces, message);
+            let sig_shares = indexes
+                .iter()
+                .map(|index| {
+                    let tracker = consumed.get_mut(index).unwrap();
+                    let nonce = batches
+                        .get_mut(index)
+                        .unwrap()
+                        .take(nonce_index, tracker)
+                        .unwrap();
+                    let sig_share = frost.sign(&frost_key, &session, *index, &shares[index], nonce);
+                    assert!(frost.verify_signature_share(&frost_key, &session, *index, sig_share));
+                    sig_share
+                })
+                .collect();
+            let signature = frost.combine_signature_shares(&frost_key, &session, sig_shares);
+            assert!(frost
+                .schnorr
+                .verify(&frost_key.public_key(), message, &signature));
+        }
+
+        let batch = batches.get_mut(&indexes[0]).unwrap();
+        let tracker = consumed.get_mut(&indexes[0]).unwrap();
+        assert_eq!(
+            batch.take(2, tracker),
+            Err(TakeNonceError::AlreadyConsumed(2))
+        );
+        assert_eq!(batch.take(4, tracker), Err(TakeNonceError::OutOfRange(4)));
+        assert!(batch.take(1, tracker).is_ok());
+        // the nonce is gone from the batch even if the tracker is lost
+        assert_eq!(
+            batch.take(1, &mut BTreeSet::new()),
+            Err(TakeNonceError::AlreadyConsumed(1))
+        );
+        assert_eq!(batch.commitments(), published[&indexes[0]]);
+    }
+
+    #[test]
+    fn preprocessed_nonce_is_kept_when_tracker_fails() {
+        struct FailingTracker;
+        impl ConsumedNonces for FailingTracker {
+            type Error = &'static str;
+            fn is_consumed(&self, _index: u32) -> bool {
+                false
+            }
+            fn mark_consumed(&mut self, _index: u32) -> Result<(), Self::Error> {
+                Err("disk full")
+            }
+        }
+
+        let frost = new_with_deterministic_nonces::<Sha256>();
+        let mut batch = frost.preprocess_nonces(&mut rand::thread_rng(), 1);
+        assert_eq!(
+            batch.take(0, &mut FailingTracker),
+            Err(TakeNonceError::Tracker("disk full"))
+        );
+        assert!(batch.take(0, &mut BTreeSet::new()).is_ok());
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn zeroize_secret_shares_and_nonces() {
+        use crate::fun::zeroize::{Zeroize, Zeroizing};
+        let frost = new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, shares) = frost.simulate_keygen(1, 1, &mut rand::thread_rng());
+        let frost_key = frost_key.into_xonly_key();
+        // secret shares are `Copy` so they have to be put in a `Zeroizing` to be cleared on drop
+        let (my_index, secret_share) = shares
+            .into_iter()
+            .map(|(index, share)| (index, Zeroizing::new(share)))
+            .next()
+            .unwrap();
+
+        let mut nonces = frost.preprocess_nonces(&mut rand::thread_rng(), 2);
+        let nonce = nonces.take(0, &mut BTreeSet::new()).unwrap();
+        let message = Message::<Public>::plain("test", b"zeroize");
+        let session = frost.start_sign_session(
+            &frost_key,
+            BTreeMap::from_iter([(my_index, nonce.public())]),
+            message,
+        );
+        let signature_share = frost.sign(&frost_key, &session, my_index, &secret_share, nonce);
+        assert!(frost.verify_signature_share(&frost_key, &session, my_index, signature_share));
+
+        nonces.zeroize();
+        assert_eq!(nonces.len(), 2);
+        assert_eq!(
+            nonces.take(1, &mut BTreeSet::new()),
+            Err(TakeNonceError::AlreadyConsumed(1))
+        );
+    }
+    #[test]
+    fn keygen_certifications() {
+        let frost = new_with_deterministic_nonces::<Sha256>();
+        let scalar_polys = (1..=3u32)
+            .map(|i| {
+                (
+                    Scalar::<Public, Zero>::from(i).non_zero().unwrap(),
+                    poly::scalar::generate(2, &mut rand::thread_rng()),
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        let keygen = frost.new_keygen(Default::default(), &scalar_polys).unwrap();
+        let mut certifications = scalar_polys
+            .keys()
+            .map(|index| {
+                let secret_share = scalar_polys
+                    .values()
+                    .fold(
+                        s!(0),
+                        |acc, poly| s!(acc + frost.create_share(poly, *index)),
+                    )
+                    .non_zero()
+                    .unwrap();
+                (*index, frost.certify_keygen(&keygen, &secret_share))
+            })
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(
+            frost.verify_certifications(&keygen, &certifications),
+            Ok(())
+        );
+
+        let (first, second) = (s!(1).public(), s!(2).public());
+        let second_cert = certifications[&second].clone();
+        let first_cert = certifications.insert(first, second_cert).unwrap();
+        assert_eq!(
+            frost.verify_certifications(&keygen, &certifications),
+            Err(CertificationError::Invalid(first))
+        );
+        certifications.remove(&first);
+        assert_eq!(
+            frost.verify_certifications(&keygen, &certifications),
+            Err(CertificationError::Missing(first))
+        );
+        certifications.insert(first, first_cert.clone());
+        certifications.insert(s!(4).public(), first_cert);
+        assert_eq!(
+            frost.verify_certifications(&keygen, &certifications),
+            Err(CertificationError::UnknownParty(s!(4).public()))
+        );
+
+        // a different key generation has a different transcript
+        let other_polys = scalar_polys
+            .keys()
+            .map(|index| (*index, poly::scalar::generate(2, &mut rand::thread_rng())))
+            .collect::<BTreeMap<_, _>>();
+        let other_keygen = frost.new_keygen(Default::default(), &other_polys).unwrap();
+        assert_ne!(
+            frost.keygen_transcript(&keygen),
+            frost.keygen_transcript(&other_keygen)
+        );
+    }
+
+    #[test]
+    fn share_possession() {
+        let frost = new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        let frost_key = frost_key.into_xonly_key();
+        let (index, other_index) = (s!(1).public(), s!(2).public());
+        let share = shares[&index];
+        assert!(frost.verify_share_against_key(&frost_key, index, &share));
+        assert!(!frost.verify_share_against_key(&frost_key, other_index, &share));
+
+        let proof = frost.prove_share_possession(&frost_key, index, &share, b"audit-1");
+        assert!(frost.verify_share_possession(&frost_key, index, b"audit-1", &proof));
+        assert!(!frost.verify_share_possession(&frost_key, index, b"audit-2", &proof));
+        assert!(!frost.verify_share_possession(&frost_key, other_index, b"audit-1", &proof));
+    }
+
+    #[test]
+    fn party_id_index_mapping() {
+        assert_eq!(PartyId::new(0), None);
+        for id in [1, 2, 255, 256, u32::MAX] {
+            let party_id = PartyId::new(id).unwrap();
+            assert_eq!(party_id.to_index(), Scalar::<Public, Zero>::from(id));
+            assert_eq!(PartyId::from_index(party_id.to_index()), Some(party_id));
+        }
+        let big = Scalar::<Public, Zero>::from(u32::MAX);
+        let big = s!(big + 1).public().non_zero().unwrap();
+        assert_eq!(PartyId::from_index(big), None);
+        let id = PartyId::new(7).unwrap();
+        assert_eq!(
+            PartyId::unique_indices([PartyId::new(1).unwrap(), id, id]),
+            Err(DuplicatePartyId(id))
+        );
+    }
+
+    #[test]
+    fn integer_indices_sign() {
+        let frost = new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        let frost_key = frost_key.into_xonly_key();
+        assert_eq!(
+            g!(shares[&3.into_party_index()] * G),
+            frost_key.verification_share(3)
+        );
+
+        let message = Message::<Public>::plain("test", b"integers");
+        let nonce1 = NonceKeyPair::random(&mut rand::thread_rng());
+        let nonce3 = NonceKeyPair::random(&mut rand::thread_rng());
+        let session = frost.start_sign_session(
+            &frost_key,
+            BTreeMap::from_iter([
+                (1.into_party_index(), nonce1.public()),
+                (3.into_party_index(), nonce3.public()),
+            ]),
+            message,
+        );
+        let share1 = frost.sign(
+            &frost_key,
+            &session,
+            1,
+            &shares[&1.into_party_index()],
+            nonce1,
+        );
+        let share3 = frost.sign(
+            &frost_key,
+            &session,
+            PartyId::new(3).unwrap(),
+            &shares[&3.into_party_index()],
+            nonce3,
+        );
+        assert!(frost.verify_signature_share(&frost_key, &session, 3, share3));
+        assert!(!frost.verify_signature_share(&frost_key, &session, 1, share3));
+
+        let mut aggregator = frost.signature_share_aggregator(&frost_key, &session);
+        aggregator.add(1, share1).unwrap();
+        aggregator.add(3, share3).unwrap();
+        let signature = aggregator.finish().unwrap();
+        assert!(frost
+            .schnorr
+            .verify(&frost_key.public_key(), message, &signature));
+    }
+
+    #[test]
+    fn batch_share_verification() {
+        let frost = new_with_deterministic_nonces::<Sha256>();
+        let index = s!(3).public();
+        let scalar_polys = (0..10)
+            .map(|_| poly::scalar::generate(4, &mut rand::thread_rng()))
+            .collect::<Vec<_>>();
+        let point_polys = scalar_polys
+            .iter()
+            .map(|scalar_poly| poly::scalar::to_point_poly(scalar_poly))
+            .collect::<Vec<_>>();
+        let mut shares = scalar_polys
+            .iter()
+            .map(|scalar_poly| poly::scalar::eval(scalar_poly, index))
+            .collect::<Vec<_>>();
+        let batch = |shares: &[Scalar<Secret, Zero>]| {
+            frost.verify_shares_batch(
+                point_polys
+                    .iter()
+                    .zip(shares)
+                    .map(|(point_poly, share)| (&point_poly[..], index, *share)),
+            )
+        };
+        assert!(batch(&shares));
+        assert!(frost.verify_shares_batch(core::iter::empty()));
+        shares[7] += s!(1);
+        assert!(!batch(&shares));
+    }
+
+    #[test]
+    fn signing_message_encoding_is_bounded() {
+        let frost = new_with_deterministic_nonces::<Sha256>();
+        let nonce = SigningMessage::Nonce(frost.gen_nonce(&mut rand::thread_rng()).public());
+        let share = SigningMessage::SignatureShare(
+            Scalar::random(&mut rand::thread_rng()).public().mark_zero(),
+        );
+        for message in [nonce, share] {
+            let bytes = message.to_bytes();
+            assert!(bytes.len() <= SigningMessage::MAX_SIZE);
+            assert_eq!(SigningMessage::from_bytes(&bytes), Some(message));
+            assert_eq!(SigningMessage::from_bytes(&bytes[..bytes.len() - 1]), None);
+        }
+        assert_eq!(
+            SigningMessage::from_bytes(&[0u8; SigningMessage::MAX_SIZE + 1]),
+            None
+        );
+        assert_eq!(SigningMessage::from_bytes(&[2u8; 33]), None);
+        assert_eq!(SigningMessage::from_bytes(&[]), None);
+    }
 }
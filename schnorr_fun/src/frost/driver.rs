@@ -0,0 +1,392 @@
+//! An async driver for a party in a FROST signing ceremony.
+//!
+//! [`run_frost_signing`] takes care of the round sequencing for one signer: it broadcasts the
+//! signer's nonce, waits for the nonces of the other signers, broadcasts its signature share and
+//! then waits for the other shares, checking each one before combining them into the signature.
+//! You only need to implement [`Transport`] to move [`SigningMessage`]s between the signers (e.g.
+//! over a websocket or a message queue).
+//!
+//! The driver doesn't depend on any async runtime. [`Transport`] methods return boxed futures so
+//! it works with any executor (and the MSRV).
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     frost::{
+//!         driver::{run_frost_signing, SigningCeremony, SigningError, Transport},
+//!         FrostKey, PartyIndex,
+//!     },
+//!     fun::{marker::*, Scalar},
+//!     Message, Signature,
+//! };
+//! use std::collections::BTreeSet;
+//!
+//! async fn sign_hello<T: Transport>(
+//!     transport: &mut T,
+//!     frost_key: FrostKey<Normal>,
+//!     my_index: PartyIndex,
+//!     secret_share: Scalar,
+//!     signers: BTreeSet<PartyIndex>,
+//! ) -> Result<Signature, SigningError<T::Error>> {
+//!     let frost = schnorr_fun::frost::new_with_synthetic_nonces::<sha2::Sha256, rand::rngs::ThreadRng>();
+//!     let nonce = frost.gen_nonce(&mut rand::thread_rng());
+//!     run_frost_signing(
+//!         &frost,
+//!         transport,
+//!         SigningCeremony {
+//!             frost_key: frost_key.into_xonly_key(),
+//!             my_index,
+//!             secret_share,
+//!             signers,
+//!             message: Message::plain("my-app", b"hello"),
+//!             nonce,
+//!         },
+//!     )
+//!     .await
+//! }
+//! ```
+pub use super::SigningMessage;
+use super::{Frost, FrostKey, NonceKeyPair, PartyIndex};
+use crate::{Message, Signature};
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+use core::{fmt, future::Future, pin::Pin};
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    marker::*,
+    Scalar,
+};
+
+/// The future returned by [`Transport`] methods.
+pub type TransportFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Moves [`SigningMessage`]s between the signers of a ceremony.
+pub trait Transport {
+    /// The error returned when a message can't be sent or received.
+    type Error;
+
+    /// Sends `message` to every other signer.
+    fn send(&mut self, message: SigningMessage) -> TransportFuture<'_, Result<(), Self::Error>>;
+
+    /// Waits for the next message from another signer and returns it along with the sender.
+    fn recv(&mut self) -> TransportFuture<'_, Result<(PartyIndex, SigningMessage), Self::Error>>;
+}
+
+/// What a signer needs to take part in a signing ceremony.
+///
+/// Its `Debug` output leaves out the secret share and nonce.
+pub struct SigningCeremony<'a> {
+    /// The key being signed under.
+    pub frost_key: FrostKey<EvenY>,
+    /// The index of the signer running the ceremony.
+    pub my_index: PartyIndex,
+    /// Their secret share.
+    pub secret_share: Scalar,
+    /// Every signer in the ceremony including this one.
+    pub signers: BTreeSet<PartyIndex>,
+    /// The message being signed.
+    pub message: Message<'a>,
+    /// The signer's nonce for this ceremony. Never use it for anything else.
+    pub nonce: NonceKeyPair,
+}
+
+impl fmt::Debug for SigningCeremony<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SigningCeremony")
+            .field("frost_key", &self.frost_key)
+            .field("my_index", &self.my_index)
+            .field("signers", &self.signers)
+            .field("message", &self.message)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Runs one signer's side of a FROST signing ceremony over `transport`.
+///
+/// Messages may arrive in any order e.g. a fast signer's signature share may arrive before the
+/// last nonce. A message that is delivered more than once is ignored but a signer sending a
+/// different message in a round it has already sent one for is an error. It returns once a valid
+/// signature share has been received from every other signer.
+///
+/// # Panics
+///
+/// If `my_index` is not one of the `signers` or there are fewer signers than the threshold.
+pub async fn run_frost_signing<H, NG, T>(
+    frost: &Frost<H, NG>,
+    transport: &mut T,
+    ceremony: SigningCeremony<'_>,
+) -> Result<Signature, SigningError<T::Error>>
+where
+    H: Digest<OutputSize = U32> + Clone,
+    T: Transport,
+{
+    let SigningCeremony {
+        frost_key,
+        my_index,
+        secret_share,
+        signers,
+        message,
+        nonce,
+    } = ceremony;
+    assert!(
+        signers.contains(&my_index),
+        "my_index must be one of the signers"
+    );
+    assert!(
+        signers.len() >= frost_key.threshold(),
+        "there must be at least threshold signers"
+    );
+
+    transport
+        .send(SigningMessage::Nonce(nonce.public()))
+        .await
+        .map_err(SigningError::Transport)?;
+
+    let mut nonces = BTreeMap::from_iter([(my_index, nonce.public())]);
+    let mut shares = BTreeMap::new();
+    while nonces.len() < signers.len() {
+        let (from, message) = transport.recv().await.map_err(SigningError::Transport)?;
+        if from == my_index || !signers.contains(&from) {
+            return Err(SigningError::UnexpectedSender(from));
+        }
+        match message {
+            SigningMessage::Nonce(nonce) => insert_idempotent(&mut nonces, from, nonce)?,
+            SigningMessage::SignatureShare(share) => insert_idempotent(&mut shares, from, share)?,
+        }
+    }
+
+    let session = frost.start_sign_session(&frost_key, nonces.clone(), message);
+    let my_share = frost.sign(&frost_key, &session, my_index, &secret_share, nonce);
+    transport
+        .send(SigningMessage::SignatureShare(my_share))
+        .await
+        .map_err(SigningError::Transport)?;
+
+    while shares.len() < signers.len() - 1 {
+        let (from, message) = transport.recv().await.map_err(SigningError::Transport)?;
+        if from == my_index || !signers.contains(&from) {
+            return Err(SigningError::UnexpectedSender(from));
+        }
+        match message {
+            SigningMessage::Nonce(nonce) => insert_idempotent(&mut nonces, from, nonce)?,
+            SigningMessage::SignatureShare(share) => insert_idempotent(&mut shares, from, share)?,
+        }
+    }
+
+    let mut signature_shares = Vec::with_capacity(signers.len());
+    signature_shares.push(my_share);
+    for (from, share) in shares {
+        if !frost.verify_signature_share(&frost_key, &session, from, share) {
+            return Err(SigningError::InvalidSignatureShare(from));
+        }
+        signature_shares.push(share);
+    }
+
+    Ok(frost.combine_signature_shares(&frost_key, &session, signature_shares))
+}
+
+/// Records a signer's message for a round ignoring redeliveries of the same message.
+fn insert_idempotent<T: PartialEq, E>(
+    received: &mut BTreeMap<PartyIndex, T>,
+    from: PartyIndex,
+    message: T,
+) -> Result<(), SigningError<E>> {
+    match received.get(&from) {
+        Some(existing) if *existing == message => Ok(()),
+        Some(_) => Err(SigningError::Conflicting(from)),
+        None => {
+            received.insert(from, message);
+            Ok(())
+        }
+    }
+}
+
+/// Error returned by [`run_frost_signing`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SigningError<E> {
+    /// The transport failed.
+    Transport(E),
+    /// A message was received from a party that isn't one of the other signers.
+    UnexpectedSender(PartyIndex),
+    /// The signer sent two different messages in the same round.
+    Conflicting(PartyIndex),
+    /// The signer sent an invalid signature share.
+    InvalidSignatureShare(PartyIndex),
+}
+
+impl<E: fmt::Display> fmt::Display for SigningError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SigningError::Transport(error) => write!(f, "transport error: {error}"),
+            SigningError::UnexpectedSender(party) => {
+                write!(f, "received a message from party {party} who isn't signing")
+            }
+            SigningError::Conflicting(party) => {
+                write!(f, "party {party} sent conflicting messages in a round")
+            }
+            SigningError::InvalidSignatureShare(party) => {
+                write!(f, "party {party} sent an invalid signature share")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: fmt::Debug + fmt::Display> std::error::Error for SigningError<E> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::task::{Context, Poll};
+    use sha2::Sha256;
+    use std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+        task::Wake,
+    };
+
+    type Inboxes = Arc<Mutex<BTreeMap<PartyIndex, VecDeque<(PartyIndex, SigningMessage)>>>>;
+
+    struct MemoryTransport {
+        me: PartyIndex,
+        inboxes: Inboxes,
+        /// How many times each message is delivered.
+        copies: usize,
+    }
+
+    struct Recv(MemoryTransport);
+
+    impl Future for Recv {
+        type Output = Result<(PartyIndex, SigningMessage), ()>;
+
+        fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let mut inboxes = self.0.inboxes.lock().unwrap();
+            match inboxes.get_mut(&self.0.me).unwrap().pop_front() {
+                Some(message) => Poll::Ready(Ok(message)),
+                None => Poll::Pending,
+            }
+        }
+    }
+
+    impl Transport for MemoryTransport {
+        type Error = ();
+
+        fn send(&mut self, message: SigningMessage) -> TransportFuture<'_, Result<(), ()>> {
+            for (party, inbox) in self.inboxes.lock().unwrap().iter_mut() {
+                if *party != self.me {
+                    for _ in 0..self.copies {
+                        inbox.push_back((self.me, message.clone()));
+                    }
+                }
+            }
+            Box::pin(async { Ok(()) })
+        }
+
+        fn recv(&mut self) -> TransportFuture<'_, Result<(PartyIndex, SigningMessage), ()>> {
+            Box::pin(Recv(MemoryTransport {
+                me: self.me,
+                inboxes: self.inboxes.clone(),
+                copies: self.copies,
+            }))
+        }
+    }
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    fn run_ceremony(copies: usize) {
+        let frost = super::super::new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, secret_shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        let frost_key = frost_key.into_xonly_key();
+        let message = Message::<Public>::plain("test", b"driver");
+        let signers = secret_shares
+            .keys()
+            .copied()
+            .take(2)
+            .collect::<BTreeSet<_>>();
+        let inboxes: Inboxes = Arc::new(Mutex::new(
+            signers
+                .iter()
+                .map(|signer| (*signer, VecDeque::new()))
+                .collect(),
+        ));
+        let mut transports = signers
+            .iter()
+            .map(|signer| MemoryTransport {
+                me: *signer,
+                inboxes: inboxes.clone(),
+                copies,
+            })
+            .collect::<Vec<_>>();
+        let mut ceremonies = transports
+            .iter_mut()
+            .map(|transport| {
+                let me = transport.me;
+                Box::pin(run_frost_signing(
+                    &frost,
+                    transport,
+                    SigningCeremony {
+                        frost_key: frost_key.clone(),
+                        my_index: me,
+                        secret_share: secret_shares[&me],
+                        signers: signers.clone(),
+                        message,
+                        nonce: frost.gen_nonce(&mut rand::thread_rng()),
+                    },
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        let waker = Arc::new(NoopWaker).into();
+        let mut cx = Context::from_waker(&waker);
+        let mut signatures = vec![None; ceremonies.len()];
+        while signatures.iter().any(Option::is_none) {
+            for (ceremony, signature) in ceremonies.iter_mut().zip(&mut signatures) {
+                if signature.is_none() {
+                    if let Poll::Ready(result) = ceremony.as_mut().poll(&mut cx) {
+                        *signature = Some(result.unwrap());
+                    }
+                }
+            }
+        }
+
+        let signature = signatures[0].clone().unwrap();
+        assert_eq!(signatures[1], Some(signature.clone()));
+        assert!(frost
+            .schnorr
+            .verify(&frost_key.public_key(), message, &signature));
+    }
+
+    #[test]
+    fn signers_agree_on_signature() {
+        run_ceremony(1);
+    }
+
+    #[test]
+    fn redelivered_messages_are_ignored() {
+        run_ceremony(3);
+    }
+
+    #[test]
+    fn conflicting_messages_are_rejected() {
+        let frost = super::super::new_with_deterministic_nonces::<Sha256>();
+        let mut nonces = BTreeMap::new();
+        let from = secp256kfun::s!(1).public();
+        let nonce = frost.gen_nonce(&mut rand::thread_rng()).public();
+        let other = frost.gen_nonce(&mut rand::thread_rng()).public();
+        assert_eq!(insert_idempotent::<_, ()>(&mut nonces, from, nonce), Ok(()));
+        assert_eq!(insert_idempotent::<_, ()>(&mut nonces, from, nonce), Ok(()));
+        assert_eq!(
+            insert_idempotent::<_, ()>(&mut nonces, from, other),
+            Err(SigningError::Conflicting(from))
+        );
+        assert_eq!(nonces[&from], nonce);
+    }
+}
@@ -0,0 +1,606 @@
+//! In-process "dry runs" of a FROST key generation and signing ceremony.
+//!
+//! [`run`] plays every party in a ceremony locally but sends each message through a [`Transport`]
+//! and only lets parties act on what the transport delivers to them. [`LoopbackTransport`] is a
+//! known-good reference transport. Wrap your own message routing code in a [`Transport`] to check
+//! it gets every message to the right party before going distributed, then compare the
+//! [`Transcript`] with one from the reference.
+//!
+//! **⚠ WARNING ⚠**: The transcript contains every party's secret shares. Only use this for testing.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     frost::{self, dry_run},
+//!     Message,
+//! };
+//! let frost = frost::new_with_deterministic_nonces::<sha2::Sha256>();
+//! let mut transport = dry_run::LoopbackTransport::default();
+//! let transcript = dry_run::run(
+//!     &frost,
+//!     2,
+//!     3,
+//!     Message::plain("my-app", b"dry run"),
+//!     &mut transport,
+//!     &mut rand::thread_rng(),
+//! )
+//! .unwrap();
+//! // 3 polynomials, 3 shares from each party, 2 nonces and 2 signature shares
+//! assert_eq!(transcript.messages.len(), 3 + 3 * 3 + 2 + 2);
+//! ```
+use super::{FinishKeyGenError, Frost, FrostKey, NewKeyGenError, Nonce, PartyIndex};
+use crate::{Message, Signature};
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::{fmt, num::NonZeroU32};
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    marker::*,
+    nonce::NonceGen,
+    poly,
+    rand_core::RngCore,
+    Point, Scalar,
+};
+
+/// A round of the ceremony.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Round {
+    /// Each party broadcasts the image of their key generation polynomial.
+    KeyGenPolys,
+    /// Each party sends every other party a secret share and a proof of possession.
+    KeyGenShares,
+    /// The signers broadcast their public nonces.
+    Nonces,
+    /// The signers broadcast their signature shares.
+    SignatureShares,
+}
+
+/// The contents of a message.
+///
+/// The secret share of [`Payload::KeyGenShare`] is left out of its `Debug` output.
+#[derive(Clone, PartialEq)]
+pub enum Payload {
+    /// A public key generation polynomial.
+    KeyGenPoly(Vec<Point>),
+    /// A secret share along with the sender's proof of possession.
+    KeyGenShare(Scalar<Secret, Zero>, Signature),
+    /// A public nonce.
+    Nonce(Nonce),
+    /// A signature share.
+    SignatureShare(Scalar<Public, Zero>),
+}
+
+impl fmt::Debug for Payload {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Payload::KeyGenPoly(poly) => f.debug_tuple("KeyGenPoly").field(poly).finish(),
+            Payload::KeyGenShare(_, pop) => f
+                .debug_tuple("KeyGenShare")
+                .field(&format_args!(".."))
+                .field(pop)
+                .finish(),
+            Payload::Nonce(nonce) => f.debug_tuple("Nonce").field(nonce).finish(),
+            Payload::SignatureShare(share) => f.debug_tuple("SignatureShare").field(share).finish(),
+        }
+    }
+}
+
+impl Payload {
+    /// The round the payload is sent in.
+    pub fn round(&self) -> Round {
+        match self {
+            Payload::KeyGenPoly(_) => Round::KeyGenPolys,
+            Payload::KeyGenShare(..) => Round::KeyGenShares,
+            Payload::Nonce(_) => Round::Nonces,
+            Payload::SignatureShare(_) => Round::SignatureShares,
+        }
+    }
+}
+
+/// Who a message is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recipient {
+    /// Every other party.
+    Broadcast,
+    /// A single party.
+    Party(PartyIndex),
+}
+
+/// A message sent by a party during the ceremony.
+#[derive(Clone, PartialEq)]
+pub struct RoutedMessage {
+    /// The sender.
+    pub from: PartyIndex,
+    /// Who the message is for.
+    pub to: Recipient,
+    /// The contents.
+    pub payload: Payload,
+}
+
+impl fmt::Debug for RoutedMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RoutedMessage")
+            .field("round", &self.payload.round())
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .field("payload", &self.payload)
+            .finish()
+    }
+}
+
+impl RoutedMessage {
+    /// Whether `party` should receive the message.
+    pub fn is_for(&self, party: PartyIndex) -> bool {
+        match self.to {
+            Recipient::Broadcast => self.from != party,
+            Recipient::Party(to) => to == party,
+        }
+    }
+}
+
+/// Moves messages between the parties in a dry run.
+pub trait Transport {
+    /// Sends a message.
+    fn send(&mut self, message: RoutedMessage);
+    /// Returns the messages for `party` that have been sent since it last received.
+    fn receive(&mut self, party: PartyIndex) -> Vec<RoutedMessage>;
+}
+
+/// A reference [`Transport`] that delivers every message to exactly the parties it is for.
+#[derive(Debug, Clone, Default)]
+pub struct LoopbackTransport {
+    sent: Vec<RoutedMessage>,
+    received: BTreeMap<PartyIndex, usize>,
+}
+
+impl Transport for LoopbackTransport {
+    fn send(&mut self, message: RoutedMessage) {
+        self.sent.push(message);
+    }
+
+    fn receive(&mut self, party: PartyIndex) -> Vec<RoutedMessage> {
+        let cursor = self.received.entry(party).or_default();
+        let messages = self.sent[*cursor..]
+            .iter()
+            .filter(|message| message.is_for(party))
+            .cloned()
+            .collect();
+        *cursor = self.sent.len();
+        messages
+    }
+}
+
+/// The record of a successful dry run.
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    /// Every message in the order it was sent.
+    pub messages: Vec<RoutedMessage>,
+    /// The key every party ended up with.
+    pub frost_key: FrostKey<Normal>,
+    /// The signers.
+    pub signers: Vec<PartyIndex>,
+    /// The signature the signers produced.
+    pub signature: Signature,
+}
+
+impl Transcript {
+    /// The messages sent during `round`.
+    pub fn round(&self, round: Round) -> impl Iterator<Item = &RoutedMessage> + '_ {
+        self.messages
+            .iter()
+            .filter(move |message| message.payload.round() == round)
+    }
+}
+
+/// Runs a key generation for `n_parties` with `threshold` followed by the first `threshold`
+/// parties signing `message`, routing every message through `transport`.
+///
+/// The parties have the indices `1..=n_parties`.
+///
+/// # Panics
+///
+/// If `threshold` is zero or greater than `n_parties`.
+pub fn run<H, NG>(
+    frost: &Frost<H, NG>,
+    threshold: usize,
+    n_parties: usize,
+    message: Message,
+    transport: &mut impl Transport,
+    rng: &mut impl RngCore,
+) -> Result<Transcript, DryRunError>
+where
+    H: Digest<OutputSize = U32> + Clone,
+    NG: NonceGen,
+{
+    assert!(
+        threshold > 0 && threshold <= n_parties,
+        "threshold must be between 1 and the number of parties"
+    );
+    let parties = (1..=n_parties)
+        .map(|i| {
+            Scalar::from_non_zero_u32(NonZeroU32::new(i as u32).expect("starts at 1")).public()
+        })
+        .collect::<Vec<_>>();
+    let mut messages = Vec::new();
+    let mut send = |transport: &mut dyn Transport, message: RoutedMessage| {
+        messages.push(message.clone());
+        transport.send(message);
+    };
+
+    let scalar_polys = parties
+        .iter()
+        .map(|party| (*party, poly::scalar::generate(threshold, rng)))
+        .collect::<BTreeMap<_, _>>();
+    for (party, scalar_poly) in &scalar_polys {
+        send(
+            transport,
+            RoutedMessage {
+                from: *party,
+                to: Recipient::Broadcast,
+                payload: Payload::KeyGenPoly(poly::scalar::to_point_poly(scalar_poly)),
+            },
+        );
+    }
+
+    let mut keygens = BTreeMap::new();
+    for (party, scalar_poly) in &scalar_polys {
+        let point_polys = receive(transport, *party, Round::KeyGenPolys, n_parties - 1)?
+            .into_iter()
+            .map(|(from, payload)| match payload {
+                Payload::KeyGenPoly(point_poly) => (from, point_poly),
+                _ => unreachable!("filtered by round"),
+            })
+            .collect();
+        let keygen = frost
+            .new_keygen(point_polys, &BTreeMap::from_iter([(*party, scalar_poly)]))
+            .map_err(|error| DryRunError::NewKeyGen(*party, error))?;
+        keygens.insert(*party, keygen);
+    }
+
+    for (party, keygen) in &keygens {
+        let (shares, pop) =
+            frost.create_shares_and_pop(keygen, &scalar_polys[party], Message::<Public>::empty());
+        for (to, share) in shares {
+            send(
+                transport,
+                RoutedMessage {
+                    from: *party,
+                    to: Recipient::Party(to),
+                    payload: Payload::KeyGenShare(share, pop.clone()),
+                },
+            );
+        }
+    }
+
+    let mut secret_shares = BTreeMap::new();
+    let mut frost_key: Option<FrostKey<Normal>> = None;
+    for (party, keygen) in keygens {
+        let received_shares = receive(transport, party, Round::KeyGenShares, n_parties)?
+            .into_iter()
+            .map(|(from, payload)| match payload {
+                Payload::KeyGenShare(share, pop) => (from, (share, pop)),
+                _ => unreachable!("filtered by round"),
+            })
+            .collect();
+        let (secret_share, party_frost_key) = frost
+            .finish_keygen(keygen, party, received_shares, Message::<Public>::empty())
+            .map_err(|error| DryRunError::FinishKeyGen(party, error))?;
+        match &frost_key {
+            Some(frost_key) if *frost_key != party_frost_key => {
+                return Err(DryRunError::KeysDisagree(party))
+            }
+            _ => frost_key = Some(party_frost_key),
+        }
+        secret_shares.insert(party, secret_share);
+    }
+    let frost_key = frost_key.expect("there is at least one party");
+    let xonly_frost_key = frost_key.clone().into_xonly_key();
+
+    let signers = parties[..threshold].to_vec();
+    let mut secret_nonces = BTreeMap::new();
+    for signer in &signers {
+        let nonce = frost.gen_nonce(rng);
+        send(
+            transport,
+            RoutedMessage {
+                from: *signer,
+                to: Recipient::Broadcast,
+                payload: Payload::Nonce(nonce.public()),
+            },
+        );
+        secret_nonces.insert(*signer, nonce);
+    }
+
+    let mut sessions = BTreeMap::new();
+    for (signer, secret_nonce) in secret_nonces {
+        let mut nonces: BTreeMap<_, _> = receive(transport, signer, Round::Nonces, threshold - 1)?
+            .into_iter()
+            .map(|(from, payload)| match payload {
+                Payload::Nonce(nonce) => (from, nonce),
+                _ => unreachable!("filtered by round"),
+            })
+            .collect();
+        nonces.insert(signer, secret_nonce.public());
+        let session = frost.start_sign_session(&xonly_frost_key, nonces, message);
+        sessions.insert(signer, (session, secret_nonce));
+    }
+
+    let mut signed_sessions = BTreeMap::new();
+    for (signer, (session, secret_nonce)) in sessions {
+        let signature_share = frost.sign(
+            &xonly_frost_key,
+            &session,
+            signer,
+            &secret_shares[&signer],
+            secret_nonce,
+        );
+        send(
+            transport,
+            RoutedMessage {
+                from: signer,
+                to: Recipient::Broadcast,
+                payload: Payload::SignatureShare(signature_share),
+            },
+        );
+        signed_sessions.insert(signer, (session, signature_share));
+    }
+
+    let mut signature: Option<Signature> = None;
+    for (signer, (session, my_signature_share)) in signed_sessions {
+        let mut signature_shares = vec![my_signature_share];
+        for (from, payload) in receive(transport, signer, Round::SignatureShares, threshold - 1)? {
+            let signature_share = match payload {
+                Payload::SignatureShare(signature_share) => signature_share,
+                _ => unreachable!("filtered by round"),
+            };
+            if !frost.verify_signature_share(&xonly_frost_key, &session, from, signature_share) {
+                return Err(DryRunError::InvalidSignatureShare(from));
+            }
+            signature_shares.push(signature_share);
+        }
+        let signer_signature =
+            frost.combine_signature_shares(&xonly_frost_key, &session, signature_shares);
+        if !frost
+            .schnorr
+            .verify(&xonly_frost_key.public_key(), message, &signer_signature)
+        {
+            return Err(DryRunError::InvalidSignature(signer));
+        }
+        signature = Some(signer_signature);
+    }
+
+    Ok(Transcript {
+        messages,
+        frost_key,
+        signers,
+        signature: signature.expect("there is at least one signer"),
+    })
+}
+
+fn receive(
+    transport: &mut impl Transport,
+    party: PartyIndex,
+    round: Round,
+    expected: usize,
+) -> Result<Vec<(PartyIndex, Payload)>, DryRunError> {
+    let mut received = BTreeMap::new();
+    for message in transport.receive(party) {
+        if !message.is_for(party) {
+            return Err(DryRunError::Misdelivered {
+                party,
+                from: message.from,
+                to: message.to,
+            });
+        }
+        if message.payload.round() != round {
+            return Err(DryRunError::UnexpectedRound {
+                party,
+                from: message.from,
+                round: message.payload.round(),
+            });
+        }
+        let from = message.from;
+        if received.insert(from, message.payload).is_some() {
+            return Err(DryRunError::Duplicate { party, from, round });
+        }
+    }
+    if received.len() != expected {
+        return Err(DryRunError::MissingMessages {
+            party,
+            round,
+            expected,
+            got: received.len(),
+        });
+    }
+    Ok(received.into_iter().collect())
+}
+
+/// Error returned by [`run`] when the transport or the ceremony misbehaves.
+#[derive(Debug, Clone)]
+pub enum DryRunError {
+    /// The party wasn't delivered all the messages it needed for a round.
+    MissingMessages {
+        /// The party missing messages.
+        party: PartyIndex,
+        /// The round.
+        round: Round,
+        /// How many messages the party should have received.
+        expected: usize,
+        /// How many it received.
+        got: usize,
+    },
+    /// The party was delivered a message that wasn't for it.
+    Misdelivered {
+        /// The party that received the message.
+        party: PartyIndex,
+        /// The sender of the message.
+        from: PartyIndex,
+        /// Who the message was for.
+        to: Recipient,
+    },
+    /// The party was delivered a message from a different round than the one it's in.
+    UnexpectedRound {
+        /// The party that received the message.
+        party: PartyIndex,
+        /// The sender of the message.
+        from: PartyIndex,
+        /// The round the message was from.
+        round: Round,
+    },
+    /// The party was delivered more than one message from the same sender in a round.
+    Duplicate {
+        /// The party that received the messages.
+        party: PartyIndex,
+        /// The sender of the messages.
+        from: PartyIndex,
+        /// The round.
+        round: Round,
+    },
+    /// The party couldn't start the key generation.
+    NewKeyGen(PartyIndex, NewKeyGenError),
+    /// The party couldn't finish the key generation.
+    FinishKeyGen(PartyIndex, FinishKeyGenError),
+    /// The party ended up with a different key to the others.
+    KeysDisagree(PartyIndex),
+    /// The party sent an invalid signature share.
+    InvalidSignatureShare(PartyIndex),
+    /// The signature the party combined was invalid.
+    InvalidSignature(PartyIndex),
+}
+
+impl fmt::Display for DryRunError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use DryRunError::*;
+        match self {
+            MissingMessages {
+                party,
+                round,
+                expected,
+                got,
+            } => write!(
+                f,
+                "party {party} expected {expected} messages in round {round:?} but got {got}"
+            ),
+            Misdelivered { party, from, to } => write!(
+                f,
+                "party {party} was delivered a message from party {from} that was for {to:?}"
+            ),
+            UnexpectedRound { party, from, round } => write!(
+                f,
+                "party {party} was delivered a {round:?} message from party {from} in the wrong round"
+            ),
+            Duplicate { party, from, round } => write!(
+                f,
+                "party {party} was delivered more than one {round:?} message from party {from}"
+            ),
+            NewKeyGen(party, error) => write!(f, "party {party} couldn't start keygen: {error}"),
+            FinishKeyGen(party, error) => {
+                write!(f, "party {party} couldn't finish keygen: {error}")
+            }
+            KeysDisagree(party) => write!(f, "party {party} ended up with a different key"),
+            InvalidSignatureShare(party) => {
+                write!(f, "party {party} sent an invalid signature share")
+            }
+            InvalidSignature(party) => write!(f, "party {party} combined an invalid signature"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DryRunError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+
+    /// Drops the secret shares sent by one of the parties.
+    #[derive(Default)]
+    struct LossyTransport(LoopbackTransport);
+
+    impl Transport for LossyTransport {
+        fn send(&mut self, message: RoutedMessage) {
+            if message.payload.round() != Round::KeyGenShares || message.from != lossy_party() {
+                self.0.send(message)
+            }
+        }
+
+        fn receive(&mut self, party: PartyIndex) -> Vec<RoutedMessage> {
+            self.0.receive(party)
+        }
+    }
+
+    fn lossy_party() -> PartyIndex {
+        Scalar::<Public, Zero>::from(2).non_zero().unwrap()
+    }
+
+    #[test]
+    fn dry_run() {
+        let frost = super::super::new_with_deterministic_nonces::<Sha256>();
+        let message = Message::<Public>::plain("test", b"dry run");
+        let transcript = run(
+            &frost,
+            3,
+            4,
+            message,
+            &mut LoopbackTransport::default(),
+            &mut rand::thread_rng(),
+        )
+        .unwrap();
+        assert_eq!(transcript.round(Round::KeyGenPolys).count(), 4);
+        assert_eq!(transcript.round(Round::KeyGenShares).count(), 16);
+        assert_eq!(transcript.round(Round::Nonces).count(), 3);
+        assert_eq!(transcript.signers.len(), 3);
+        assert!(frost.schnorr.verify(
+            &transcript.frost_key.into_xonly_key().public_key(),
+            message,
+            &transcript.signature
+        ));
+
+        let error = run(
+            &frost,
+            2,
+            3,
+            message,
+            &mut LossyTransport::default(),
+            &mut rand::thread_rng(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            DryRunError::MissingMessages {
+                round: Round::KeyGenShares,
+                expected: 3,
+                got: 2,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn debug_redacts_secret_shares() {
+        let frost = super::super::new_with_deterministic_nonces::<Sha256>();
+        let message = Message::<Public>::plain("test", b"dry run");
+        let transcript = run(
+            &frost,
+            2,
+            2,
+            message,
+            &mut LoopbackTransport::default(),
+            &mut rand::thread_rng(),
+        )
+        .unwrap();
+        for message in &transcript.messages {
+            let debug = format!("{:?}", message);
+            assert!(debug.starts_with(&format!(
+                "RoutedMessage {{ round: {:?}",
+                message.payload.round()
+            )));
+            if let Payload::KeyGenShare(secret_share, _) = &message.payload {
+                let secret_hex = format!("{}", secret_share);
+                assert!(!debug.contains(&secret_hex));
+                assert!(!format!("{:#?}", message).contains(&secret_hex));
+            }
+        }
+    }
+}
@@ -0,0 +1,531 @@
+//! Key generation over an untrusted message board with encrypted share delivery.
+//!
+//! [`Frost::finish_keygen`] needs each party's secret share sent to its recipient over a private
+//! channel. In EncPedPop each party has a long lived encryption key instead and every contributor
+//! encrypts the shares inside its [`Contribution`] so the whole key generation is one round of
+//! public messages. A coordinator (or a message board) only relays contributions, it never sees a
+//! share and can't change a contribution without it being detected.
+//!
+//! 1. The parties agree on a unique `session_id` and each other's encryption keys.
+//! 2. Each party creates its [`Contribution`] with [`Frost::encpedpop_contribute`] and posts it.
+//! 3. Once all the contributions are posted anyone can check them and compute the [`KeyGen`] with
+//!    [`Frost::encpedpop_keygen`].
+//! 4. Each party decrypts its shares with [`Frost::encpedpop_finish`].
+//!
+//! The contributor's proof-of-possession signs over the session id, the polynomial and the
+//! ciphertexts so a share that doesn't decrypt to the right value is the fault of the contributor
+//! (or of a wrong encryption key). A coordinator can still show different parties
+//! different sets of contributions so the parties should [certify] the key generation before
+//! using the key.
+//!
+//! ## Encryption
+//!
+//! Each share is encrypted to its recipient's encryption key with [`secp256kfun::ecies`]. The
+//! associated data is the session id and the indices of the contributor and the recipient so a
+//! ciphertext can't be moved to another session or recipient. The decrypted share is also checked
+//! against the contributor's polynomial.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     frost::{self, IntoPartyIndex},
+//!     fun::{g, Scalar, G},
+//! };
+//! use std::collections::BTreeMap;
+//! let frost = frost::new_with_deterministic_nonces::<sha2::Sha256>();
+//! let decryption_keys = (1..=3)
+//!     .map(|i| (i.into_party_index(), Scalar::random(&mut rand::thread_rng())))
+//!     .collect::<BTreeMap<_, _>>();
+//! let encryption_keys = decryption_keys
+//!     .iter()
+//!     .map(|(index, key)| (*index, g!(key * G).normalize()))
+//!     .collect::<BTreeMap<_, _>>();
+//! let session_id = b"board-members-2026";
+//! // each party posts their contribution to the board
+//! let contributions = encryption_keys
+//!     .keys()
+//!     .map(|index| {
+//!         let contribution = frost.encpedpop_contribute(
+//!             session_id,
+//!             index,
+//!             2,
+//!             &encryption_keys,
+//!             &mut rand::thread_rng(),
+//!         );
+//!         (*index, contribution)
+//!     })
+//!     .collect::<BTreeMap<_, _>>();
+//! // each party reads all of them back and finishes
+//! for (index, decryption_key) in &decryption_keys {
+//!     let my_contribution = &contributions[index];
+//!     let keygen = frost.encpedpop_keygen(session_id, &contributions).unwrap();
+//!     let (secret_share, frost_key) = frost
+//!         .encpedpop_finish(
+//!             session_id,
+//!             keygen,
+//!             &contributions,
+//!             my_contribution,
+//!             index,
+//!             decryption_key,
+//!         )
+//!         .unwrap();
+//!     assert_eq!(g!(secret_share * G), frost_key.verification_share(index));
+//! }
+//! ```
+//!
+//! [certify]: Frost::certify_keygen
+use super::{Frost, FrostKey, IntoPartyIndex, KeyGen, NewKeyGenError, PartyIndex};
+use crate::{Message, Signature};
+use alloc::{collections::BTreeMap, vec::Vec};
+use secp256kfun::{
+    digest::{crypto_common::BlockSizeUser, generic_array::typenum::U32, Digest},
+    ecies, g,
+    marker::*,
+    nonce::NonceGen,
+    poly,
+    rand_core::RngCore,
+    s, Point, Scalar, G,
+};
+
+const POP_TAG: &str = "frost-encpedpop-pop";
+
+/// A party's public message in an EncPedPop key generation.
+///
+/// Created with [`Frost::encpedpop_contribute`]. See the [module
+/// documentation](crate::frost::encpedpop).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct Contribution {
+    /// The commitment to the contributor's key generation polynomial.
+    pub point_poly: Vec<Point>,
+    /// The share for each party encrypted to their encryption key with [`ecies::encrypt`].
+    pub encrypted_shares: BTreeMap<PartyIndex, Vec<u8>>,
+    /// The contributor's proof-of-possession over everything else in the contribution.
+    pub pop: Signature,
+}
+
+/// Error returned by [`Frost::encpedpop_keygen`] and [`Frost::encpedpop_finish`].
+#[derive(Debug, Clone)]
+pub enum EncPedPopError {
+    /// The polynomials in the contributions don't form a valid key generation.
+    NewKeyGen(NewKeyGenError),
+    /// The proof-of-possession in the contribution from this party was invalid.
+    InvalidProofOfPossession(PartyIndex),
+    /// The contribution from this party doesn't have exactly one share for each party.
+    WrongRecipients(PartyIndex),
+    /// There was no contribution from this party.
+    MissingContribution(PartyIndex),
+    /// The share from this party didn't decrypt to the evaluation of their polynomial at our index.
+    InvalidShare(PartyIndex),
+    /// The contribution at our index isn't the one we created.
+    ContributionReplaced,
+}
+
+impl core::fmt::Display for EncPedPopError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        use EncPedPopError::*;
+        match self {
+            NewKeyGen(e) => write!(f, "{e}"),
+            InvalidProofOfPossession(i) => write!(
+                f,
+                "the proof-of-possession in the contribution from party {i} was invalid"
+            ),
+            WrongRecipients(i) => write!(
+                f,
+                "the contribution from party {i} didn't have a share for exactly each party"
+            ),
+            MissingContribution(i) => write!(f, "there was no contribution from party {i}"),
+            InvalidShare(i) => write!(
+                f,
+                "the share from party {i} didn't decrypt to the evaluation of their polynomial \
+                at our index. Check that our index and decryption key are correct"
+            ),
+            ContributionReplaced => write!(f, "our contribution was replaced with another one"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EncPedPopError {}
+
+impl From<NewKeyGenError> for EncPedPopError {
+    fn from(e: NewKeyGenError) -> Self {
+        EncPedPopError::NewKeyGen(e)
+    }
+}
+
+fn pop_message_data(
+    session_id: &[u8],
+    contributor: PartyIndex,
+    point_poly: &[Point],
+    encrypted_shares: &BTreeMap<PartyIndex, Vec<u8>>,
+) -> Vec<u8> {
+    let mut data = (session_id.len() as u64).to_be_bytes().to_vec();
+    data.extend_from_slice(session_id);
+    data.extend_from_slice(&contributor.to_bytes());
+    data.extend_from_slice(&(point_poly.len() as u32).to_be_bytes());
+    for point in point_poly {
+        data.extend_from_slice(&point.to_bytes());
+    }
+    for (index, ciphertext) in encrypted_shares {
+        data.extend_from_slice(&index.to_bytes());
+        data.extend_from_slice(&(ciphertext.len() as u32).to_be_bytes());
+        data.extend_from_slice(ciphertext);
+    }
+    data
+}
+
+fn share_associated_data(
+    session_id: &[u8],
+    contributor: PartyIndex,
+    recipient: PartyIndex,
+) -> Vec<u8> {
+    let mut data = (session_id.len() as u64).to_be_bytes().to_vec();
+    data.extend_from_slice(session_id);
+    data.extend_from_slice(&contributor.to_bytes());
+    data.extend_from_slice(&recipient.to_bytes());
+    data
+}
+
+impl<H, NG> Frost<H, NG>
+where
+    H: BlockSizeUser + Digest<OutputSize = U32> + Clone + Default,
+    NG: NonceGen,
+{
+    /// Creates our [`Contribution`] to a `threshold`-of-n key generation between the owners of
+    /// `encryption_keys`.
+    ///
+    /// `session_id` must be unique to this key generation and agreed on by all the parties. The
+    /// contribution is the only thing we need to keep from this step: pass it to
+    /// [`encpedpop_finish`] as `my_contribution`.
+    ///
+    /// # Panics
+    ///
+    /// If `threshold` is `0`.
+    ///
+    /// [`encpedpop_finish`]: Frost::encpedpop_finish
+    pub fn encpedpop_contribute(
+        &self,
+        session_id: &[u8],
+        my_index: impl IntoPartyIndex,
+        threshold: usize,
+        encryption_keys: &BTreeMap<PartyIndex, Point>,
+        rng: &mut impl RngCore,
+    ) -> Contribution {
+        assert!(threshold > 0, "threshold must be at least 1");
+        let my_index = my_index.into_party_index();
+        #[cfg_attr(not(feature = "zeroize"), allow(unused_mut))]
+        let mut scalar_poly = poly::scalar::generate(threshold, rng);
+        let point_poly = poly::scalar::to_point_poly(&scalar_poly);
+        let encrypted_shares = encryption_keys
+            .iter()
+            .map(|(index, encryption_key)| {
+                let share = self.create_share(&scalar_poly, *index).to_secret_bytes();
+                let ciphertext = ecies::encrypt::<H>(
+                    encryption_key,
+                    &share_associated_data(session_id, my_index, *index),
+                    share.expose_secret(),
+                    rng,
+                );
+                (*index, ciphertext)
+            })
+            .collect();
+        let pop_data = pop_message_data(session_id, my_index, &point_poly, &encrypted_shares);
+        let pop = self
+            .create_proof_of_possession(&scalar_poly, Message::<Public>::plain(POP_TAG, &pop_data));
+        #[cfg(feature = "zeroize")]
+        crate::fun::zeroize::Zeroize::zeroize(&mut scalar_poly);
+
+        Contribution {
+            point_poly,
+            encrypted_shares,
+            pop,
+        }
+    }
+}
+
+impl<H, NG> Frost<H, NG>
+where
+    H: BlockSizeUser + Digest<OutputSize = U32> + Clone + Default,
+{
+    /// Checks every contribution and computes the [`KeyGen`] from them.
+    ///
+    /// Each contribution's proof-of-possession must be valid and it must have a share for exactly
+    /// the parties that contributed. This doesn't need any secrets so the coordinator can use it
+    /// to reject bad contributions before relaying them.
+    pub fn encpedpop_keygen(
+        &self,
+        session_id: &[u8],
+        contributions: &BTreeMap<PartyIndex, Contribution>,
+    ) -> Result<KeyGen, EncPedPopError> {
+        for (contributor, contribution) in contributions {
+            if !contribution
+                .encrypted_shares
+                .keys()
+                .eq(contributions.keys())
+            {
+                return Err(EncPedPopError::WrongRecipients(*contributor));
+            }
+            let pop_valid = contribution.point_poly.first().map_or(false, |first| {
+                let pop_data = pop_message_data(
+                    session_id,
+                    *contributor,
+                    &contribution.point_poly,
+                    &contribution.encrypted_shares,
+                );
+                let (even_poly_point, _) = first.into_point_with_even_y();
+                self.schnorr.verify(
+                    &even_poly_point,
+                    Message::<Public>::plain(POP_TAG, &pop_data),
+                    &contribution.pop,
+                )
+            });
+            if !pop_valid {
+                return Err(EncPedPopError::InvalidProofOfPossession(*contributor));
+            }
+        }
+
+        let point_polys = contributions
+            .iter()
+            .map(|(contributor, contribution)| (*contributor, contribution.point_poly.clone()))
+            .collect();
+        Ok(self.new_keygen(point_polys, &BTreeMap::<PartyIndex, Vec<Scalar>>::new())?)
+    }
+
+    /// Decrypts our shares from `contributions` and combines them into our long-lived secret
+    /// share.
+    ///
+    /// `keygen` and `session_id` must be the ones passed to [`encpedpop_keygen`] on the same
+    /// `contributions` and
+    /// `my_contribution` is what [`encpedpop_contribute`] gave us. Checking that our own
+    /// contribution wasn't replaced protects against a coordinator that substitutes a polynomial
+    /// it controls for ours (like `local_secret_polys` does in [`new_keygen`]).
+    ///
+    /// # Return value
+    ///
+    /// Your secret share and the [`FrostKey`]
+    ///
+    /// [`encpedpop_keygen`]: Frost::encpedpop_keygen
+    /// [`encpedpop_contribute`]: Frost::encpedpop_contribute
+    /// [`new_keygen`]: Frost::new_keygen
+    #[allow(clippy::too_many_arguments)]
+    pub fn encpedpop_finish(
+        &self,
+        session_id: &[u8],
+        keygen: KeyGen,
+        contributions: &BTreeMap<PartyIndex, Contribution>,
+        my_contribution: &Contribution,
+        my_index: impl IntoPartyIndex,
+        decryption_key: &Scalar,
+    ) -> Result<(Scalar, FrostKey<Normal>), EncPedPopError> {
+        let my_index = my_index.into_party_index();
+        if contributions.get(&my_index) != Some(my_contribution) {
+            return Err(EncPedPopError::ContributionReplaced);
+        }
+
+        let mut secret_shares = BTreeMap::new();
+        for contributor in keygen.point_polys.keys() {
+            let contribution = contributions
+                .get(contributor)
+                .ok_or(EncPedPopError::MissingContribution(*contributor))?;
+            let ciphertext = contribution
+                .encrypted_shares
+                .get(&my_index)
+                .ok_or(EncPedPopError::WrongRecipients(*contributor))?;
+            let plaintext = ecies::decrypt::<H>(
+                decryption_key,
+                &share_associated_data(session_id, *contributor, my_index),
+                ciphertext,
+            )
+            .map_err(|_| EncPedPopError::InvalidShare(*contributor))?;
+            let secret_share = Scalar::<Secret, Zero>::from_slice(&plaintext)
+                .ok_or(EncPedPopError::InvalidShare(*contributor))?;
+            secret_shares.insert(*contributor, secret_share);
+        }
+
+        let shares = keygen
+            .point_polys
+            .iter()
+            .map(|(contributor, poly)| (&poly[..], my_index, secret_shares[contributor]));
+        if !self.verify_shares_batch(shares) {
+            for (contributor, poly) in &keygen.point_polys {
+                let expected_public_share = poly::point::eval(poly, my_index);
+                if g!({ secret_shares[contributor] } * G) != expected_public_share {
+                    return Err(EncPedPopError::InvalidShare(*contributor));
+                }
+            }
+        }
+
+        let total_secret_share = secret_shares
+            .values()
+            .fold(s!(0), |total, share| s!(total + share))
+            .non_zero()
+            .expect(
+                "since verification shares are non-zero, the total secret share cannot be zero",
+            );
+
+        Ok((total_secret_share, keygen.frost_key))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frost;
+    use sha2::Sha256;
+
+    #[test]
+    fn encpedpop_end_to_end() {
+        let frost = frost::new_with_deterministic_nonces::<Sha256>();
+        let session_id = b"test-session";
+        let decryption_keys = (1..=4)
+            .map(|i| {
+                (
+                    i.into_party_index(),
+                    Scalar::random(&mut rand::thread_rng()),
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        let encryption_keys = decryption_keys
+            .iter()
+            .map(|(index, key)| (*index, g!(key * G).normalize()))
+            .collect::<BTreeMap<_, _>>();
+        let contributions = encryption_keys
+            .keys()
+            .map(|index| {
+                (
+                    *index,
+                    frost.encpedpop_contribute(
+                        session_id,
+                        index,
+                        3,
+                        &encryption_keys,
+                        &mut rand::thread_rng(),
+                    ),
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let keygen = frost.encpedpop_keygen(session_id, &contributions).unwrap();
+        let mut secret_shares = BTreeMap::new();
+        for (index, decryption_key) in &decryption_keys {
+            let (secret_share, frost_key) = frost
+                .encpedpop_finish(
+                    session_id,
+                    keygen.clone(),
+                    &contributions,
+                    &contributions[index],
+                    index,
+                    decryption_key,
+                )
+                .unwrap();
+            assert_eq!(frost_key.threshold(), 3);
+            assert_eq!(g!(secret_share * G), frost_key.verification_share(index));
+            secret_shares.insert(*index, secret_share);
+        }
+        let certifications = secret_shares
+            .values()
+            .zip(secret_shares.keys())
+            .map(|(share, index)| (*index, frost.certify_keygen(&keygen, share)))
+            .collect();
+        assert!(frost
+            .verify_certifications(&keygen, &certifications)
+            .is_ok());
+
+        let alice = s!(1).public();
+        let bob = s!(2).public();
+        // the pop is over a different session
+        assert!(matches!(
+            frost.encpedpop_keygen(b"other-session", &contributions),
+            Err(EncPedPopError::InvalidProofOfPossession(_))
+        ));
+        // the relay tampers with a ciphertext
+        let mut tampered = contributions.clone();
+        tampered
+            .get_mut(&bob)
+            .unwrap()
+            .encrypted_shares
+            .get_mut(&alice)
+            .unwrap()[0] ^= 1;
+        assert!(matches!(
+            frost.encpedpop_keygen(session_id, &tampered),
+            Err(EncPedPopError::InvalidProofOfPossession(i)) if i == bob
+        ));
+        // the relay drops a share
+        let mut dropped = contributions.clone();
+        dropped
+            .get_mut(&bob)
+            .unwrap()
+            .encrypted_shares
+            .remove(&alice);
+        assert!(matches!(
+            frost.encpedpop_keygen(session_id, &dropped),
+            Err(EncPedPopError::WrongRecipients(i)) if i == bob
+        ));
+        // the relay replaces alice's contribution with its own
+        let mut replaced = contributions.clone();
+        replaced.insert(
+            alice,
+            frost.encpedpop_contribute(
+                session_id,
+                alice,
+                3,
+                &encryption_keys,
+                &mut rand::thread_rng(),
+            ),
+        );
+        let replaced_keygen = frost.encpedpop_keygen(session_id, &replaced).unwrap();
+        assert!(matches!(
+            frost.encpedpop_finish(
+                session_id,
+                replaced_keygen,
+                &replaced,
+                &contributions[&alice],
+                alice,
+                &decryption_keys[&alice]
+            ),
+            Err(EncPedPopError::ContributionReplaced)
+        ));
+        // decrypting with the wrong key
+        assert!(matches!(
+            frost.encpedpop_finish(
+                session_id,
+                keygen.clone(),
+                &contributions,
+                &contributions[&alice],
+                alice,
+                &decryption_keys[&bob]
+            ),
+            Err(EncPedPopError::InvalidShare(_))
+        ));
+        // a ciphertext moved to another recipient doesn't decrypt
+        let mut moved = contributions.clone();
+        let ciphertext = moved[&bob].encrypted_shares[&bob].clone();
+        moved
+            .get_mut(&bob)
+            .unwrap()
+            .encrypted_shares
+            .insert(alice, ciphertext);
+        assert!(matches!(
+            frost.encpedpop_finish(
+                session_id,
+                keygen,
+                &moved,
+                &contributions[&alice],
+                alice,
+                &decryption_keys[&alice]
+            ),
+            Err(EncPedPopError::InvalidShare(i)) if i == bob
+        ));
+    }
+}
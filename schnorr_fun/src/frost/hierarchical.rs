@@ -0,0 +1,475 @@
+//! Hierarchical FROST where a party of the key is itself a threshold group.
+//!
+//! Custody policies often follow an org chart: "two of the three departments have to approve and
+//! a department approves when two of its four officers do". Here the FROST key is an ordinary
+//! `t`-of-`n` key between the top-level parties (the departments) and each top-level party deals
+//! its secret share out to its members as a `t'`-of-`m` [`SubGroup`] with [`SubGroup::deal`]. The
+//! group's point polynomial is checked against its verification share in the FROST key so members
+//! can tell they hold shares of the right thing.
+//!
+//! When signing, the members of a group that take part each generate an ordinary nonce and the
+//! group starts a [`GroupSession`] with them. The group's nonce (the sum of its members' nonces)
+//! is what goes into the top-level [`SignSession`]. Each member signs with
+//! [`Frost::sign_as_group_member`] and [`GroupSession::combine`] adds their shares up into the
+//! group's signature share which is checked and combined like any other with
+//! [`Frost::verify_signature_share`] and [`Frost::combine_signature_shares`]. The top-level
+//! coordinator doesn't need to know the group is a group.
+//!
+//! Summing the members' nonces is how [MuSig2] aggregates nonces and has the same security since
+//! the binding coefficient of the group's nonce in the top-level session commits to it.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     frost::{self, hierarchical::SubGroup},
+//!     fun::{marker::*, Scalar},
+//!     Message,
+//! };
+//! use std::collections::BTreeMap;
+//! let frost = frost::new_with_deterministic_nonces::<sha2::Sha256>();
+//! // 2-of-3 departments
+//! let (frost_key, mut department_shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+//! let frost_key = frost_key.into_xonly_key();
+//! // the first department deals its share to 2-of-4 officers
+//! let (department, department_share) = department_shares.pop_first().unwrap();
+//! let officers = (1..=4).map(|i| Scalar::<Public, Zero>::from(i).non_zero().unwrap());
+//! let (group, officer_shares) = SubGroup::deal(
+//!     &frost_key,
+//!     department,
+//!     &department_share,
+//!     2,
+//!     officers,
+//!     &mut rand::thread_rng(),
+//! )
+//! .unwrap();
+//!
+//! // two officers and the second department sign
+//! let signing_officers = officer_shares.into_iter().take(2).collect::<BTreeMap<_, _>>();
+//! let officer_nonces = signing_officers
+//!     .keys()
+//!     .map(|officer| (*officer, frost.gen_nonce(&mut rand::thread_rng())))
+//!     .collect::<BTreeMap<_, _>>();
+//! let group_session = group
+//!     .start_session(
+//!         officer_nonces
+//!             .iter()
+//!             .map(|(officer, nonce)| (*officer, nonce.public()))
+//!             .collect(),
+//!     )
+//!     .unwrap();
+//! let (other_department, other_share) = department_shares.pop_first().unwrap();
+//! let other_nonce = frost.gen_nonce(&mut rand::thread_rng());
+//! let message = Message::plain("my-org", b"move funds");
+//! let session = frost.start_sign_session(
+//!     &frost_key,
+//!     BTreeMap::from_iter([
+//!         (department, group_session.nonce()),
+//!         (other_department, other_nonce.public()),
+//!     ]),
+//!     message,
+//! );
+//! let officer_shares = signing_officers
+//!     .iter()
+//!     .map(|(officer, secret_share)| {
+//!         frost.sign_as_group_member(
+//!             &frost_key,
+//!             &session,
+//!             &group_session,
+//!             *officer,
+//!             secret_share,
+//!             officer_nonces[officer].clone(),
+//!         )
+//!     })
+//!     .collect();
+//! let department_signature_share = group_session.combine(officer_shares);
+//! assert!(frost.verify_signature_share(&frost_key, &session, department, department_signature_share));
+//! let other_signature_share = frost.sign(&frost_key, &session, other_department, &other_share, other_nonce);
+//! let signature = frost.combine_signature_shares(
+//!     &frost_key,
+//!     &session,
+//!     vec![department_signature_share, other_signature_share],
+//! );
+//! assert!(frost.schnorr.verify(&frost_key.public_key(), message, &signature));
+//! ```
+//!
+//! [MuSig2]: https://eprint.iacr.org/2020/1261
+use super::{Frost, FrostKey, Nonce, NonceKeyPair, PartyIndex, SignSession};
+use alloc::{collections::BTreeMap, vec::Vec};
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    g,
+    marker::*,
+    poly,
+    rand_core::RngCore,
+    s, Point, Scalar, G,
+};
+
+/// A top-level party of a FROST key whose secret share is shared out between its members.
+///
+/// See the [module documentation](crate::frost::hierarchical).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct SubGroup {
+    index: PartyIndex,
+    point_polynomial: Vec<Point<Normal, Public, Zero>>,
+}
+
+impl SubGroup {
+    /// Creates the group for the top-level party at `index` of `frost_key` from the point
+    /// polynomial its share was dealt with.
+    ///
+    /// Returns `None` if the polynomial isn't for the party's verification share.
+    pub fn new<T: PointType + Copy>(
+        frost_key: &FrostKey<T>,
+        index: PartyIndex,
+        point_polynomial: Vec<Point<Normal, Public, Zero>>,
+    ) -> Option<Self> {
+        if *point_polynomial.first()? != frost_key.verification_share(index) {
+            return None;
+        }
+        Some(Self {
+            index,
+            point_polynomial,
+        })
+    }
+
+    /// Deals the secret share of the top-level party at `index` out to `members` so that any
+    /// `threshold` of them can sign for it.
+    ///
+    /// This is run by whoever holds the party's share (who should delete it afterwards). Each
+    /// member's share has to be sent to them privately and they should check it with
+    /// [`verify_member_share`]. Returns `None` if `secret_share` isn't the party's share, the
+    /// threshold is zero or there are fewer members than the threshold.
+    ///
+    /// [`verify_member_share`]: Self::verify_member_share
+    pub fn deal<T: PointType + Copy>(
+        frost_key: &FrostKey<T>,
+        index: PartyIndex,
+        secret_share: &Scalar,
+        threshold: usize,
+        members: impl IntoIterator<Item = PartyIndex>,
+        rng: &mut impl RngCore,
+    ) -> Option<(Self, BTreeMap<PartyIndex, Scalar>)> {
+        let members = members.into_iter().collect::<Vec<_>>();
+        if threshold == 0 || members.len() < threshold {
+            return None;
+        }
+        let mut scalar_poly = poly::scalar::generate(threshold, rng);
+        scalar_poly[0] = *secret_share;
+        let point_polynomial = poly::scalar::to_point_poly(&scalar_poly)
+            .into_iter()
+            .map(|coefficient| coefficient.mark_zero())
+            .collect();
+        let dealt = Self::new(frost_key, index, point_polynomial).map(|group| {
+            let shares = members
+                .into_iter()
+                .map(|member| {
+                    let share = poly::scalar::eval(&scalar_poly, member)
+                        .non_zero()
+                        .expect("computationally unreachable");
+                    (member, share)
+                })
+                .collect();
+            (group, shares)
+        });
+        #[cfg(feature = "zeroize")]
+        crate::fun::zeroize::Zeroize::zeroize(&mut scalar_poly);
+        dealt
+    }
+
+    /// The index of the group in the top-level FROST key.
+    pub fn index(&self) -> PartyIndex {
+        self.index
+    }
+
+    /// The number of members needed to sign for the group.
+    pub fn threshold(&self) -> usize {
+        self.point_polynomial.len()
+    }
+
+    /// The public polynomial the group's share was dealt with.
+    pub fn point_polynomial(&self) -> &[Point<Normal, Public, Zero>] {
+        &self.point_polynomial
+    }
+
+    /// The image of the secret share of the member at `member`.
+    pub fn verification_share(&self, member: PartyIndex) -> Point<NonNormal, Public, Zero> {
+        poly::point::eval(&self.point_polynomial, member)
+    }
+
+    /// Checks that `secret_share` is the share of the member at `member`.
+    pub fn verify_member_share(&self, member: PartyIndex, secret_share: &Scalar) -> bool {
+        self.verification_share(member) == g!(secret_share * G)
+    }
+
+    /// Starts signing for the group with the members that sent `member_nonces`.
+    ///
+    /// Returns `None` if there are fewer than [`threshold`] members or (with negligible
+    /// probability) their nonces sum to zero.
+    ///
+    /// [`threshold`]: Self::threshold
+    pub fn start_session(
+        &self,
+        member_nonces: BTreeMap<PartyIndex, Nonce>,
+    ) -> Option<GroupSession> {
+        if member_nonces.len() < self.threshold() {
+            return None;
+        }
+        let nonce = Nonce::aggregate(member_nonces.values().copied())
+            .non_identity()
+            .ok()?;
+        Some(GroupSession {
+            group: self.clone(),
+            member_nonces,
+            nonce,
+        })
+    }
+}
+
+/// The members of a [`SubGroup`] that are signing for it in a signing session.
+///
+/// Created with [`SubGroup::start_session`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroupSession {
+    group: SubGroup,
+    member_nonces: BTreeMap<PartyIndex, Nonce>,
+    nonce: Nonce,
+}
+
+impl GroupSession {
+    /// The group's nonce to use for it in the top-level [`SignSession`].
+    pub fn nonce(&self) -> Nonce {
+        self.nonce
+    }
+
+    /// The group the session is for.
+    pub fn group(&self) -> &SubGroup {
+        &self.group
+    }
+
+    /// The members signing for the group.
+    pub fn members(&self) -> impl DoubleEndedIterator<Item = PartyIndex> + '_ {
+        self.member_nonces.keys().copied()
+    }
+
+    /// Adds up the members' signature shares into the group's signature share.
+    ///
+    /// This doesn't check the shares. Use [`Frost::verify_group_member_share`] to find out which
+    /// member is to blame if the result is invalid.
+    pub fn combine(&self, member_shares: Vec<Scalar<Public, Zero>>) -> Scalar<Public, Zero> {
+        member_shares
+            .into_iter()
+            .fold(Scalar::zero(), |acc, share| s!(acc + share).public())
+    }
+
+    fn lagrange_coefficient(&self, member: PartyIndex) -> Scalar<Public> {
+        poly::eval_basis_poly_at_0(member, self.member_nonces.keys())
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
+    /// Creates the signature share of the group member at `member`.
+    ///
+    /// ## Panics
+    ///
+    /// If the group isn't part of the `session` with the group session's nonce or `secret_nonce`
+    /// isn't the member's nonce in the `group_session`.
+    pub fn sign_as_group_member(
+        &self,
+        frost_key: &FrostKey<EvenY>,
+        session: &SignSession,
+        group_session: &GroupSession,
+        member: PartyIndex,
+        secret_share: &Scalar,
+        secret_nonce: NonceKeyPair,
+    ) -> Scalar<Public, Zero> {
+        let index = group_session.group.index;
+        assert_eq!(
+            session.nonces.get(&index),
+            Some(&group_session.nonce),
+            "the group's nonce in the session didn't match the group session"
+        );
+        assert_eq!(
+            group_session.member_nonces.get(&member),
+            Some(&secret_nonce.public()),
+            "secret nonce didn't match the member's nonce in the group session"
+        );
+        let lambda = poly::eval_basis_poly_at_0(index, session.nonces.keys());
+        let mut lambda = s!(lambda * { group_session.lagrange_coefficient(member) }).public();
+        lambda.conditional_negate(frost_key.needs_negation);
+        let [mut r1, mut r2] = secret_nonce.into_secrets();
+        r1.conditional_negate(session.nonces_need_negation);
+        r2.conditional_negate(session.nonces_need_negation);
+        let b = &session.binding_coeffs[&index];
+        let c = &session.challenge;
+        let y = secret_share;
+        let share = s!(r1 + r2 * b + lambda * y * c).public();
+        #[cfg(feature = "zeroize")]
+        {
+            use crate::fun::zeroize::Zeroize;
+            r1.zeroize();
+            r2.zeroize();
+        }
+        share
+    }
+
+    /// Verifies the signature share of the group member at `member`.
+    ///
+    /// ## Panics
+    ///
+    /// If the group or the member aren't part of the sessions.
+    pub fn verify_group_member_share(
+        &self,
+        frost_key: &FrostKey<EvenY>,
+        session: &SignSession,
+        group_session: &GroupSession,
+        member: PartyIndex,
+        signature_share: Scalar<Public, Zero>,
+    ) -> bool {
+        let index = group_session.group.index;
+        let lambda = poly::eval_basis_poly_at_0(index, session.nonces.keys());
+        let mut lambda = s!(lambda * { group_session.lagrange_coefficient(member) }).public();
+        lambda.conditional_negate(frost_key.needs_negation);
+        let [R1, R2] = group_session
+            .member_nonces
+            .get(&member)
+            .expect("member is not signing in the group session")
+            .0;
+        let R1 = R1.conditional_negate(session.nonces_need_negation);
+        let R2 = R2.conditional_negate(session.nonces_need_negation);
+        let b = &session.binding_coeffs[&index];
+        let c = &session.challenge;
+        let Y = group_session.group.verification_share(member);
+        g!(R1 + b * R2 + (c * lambda) * Y - signature_share * G).is_zero()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Message;
+    use sha2::Sha256;
+
+    #[test]
+    fn two_groups_sign() {
+        let frost = crate::frost::new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, shares) = frost.simulate_keygen(2, 2, &mut rand::thread_rng());
+        let frost_key = frost_key.into_xonly_key();
+        let members = || (1..=3).map(|i| Scalar::<Public, Zero>::from(i).non_zero().unwrap());
+        let groups = shares
+            .iter()
+            .map(|(index, share)| {
+                SubGroup::deal(
+                    &frost_key,
+                    *index,
+                    share,
+                    2,
+                    members(),
+                    &mut rand::thread_rng(),
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        for (group, member_shares) in &groups {
+            assert!(member_shares
+                .iter()
+                .all(|(member, share)| group.verify_member_share(*member, share)));
+            assert_eq!(
+                SubGroup::new(&frost_key, group.index(), group.point_polynomial().to_vec()),
+                Some(group.clone())
+            );
+        }
+        let (index, share) = shares.iter().next().unwrap();
+        assert!(SubGroup::deal(
+            &frost_key,
+            *index,
+            &s!(share + 1).non_zero().unwrap(),
+            2,
+            members(),
+            &mut rand::thread_rng()
+        )
+        .is_none());
+
+        // members 1 and 3 sign in the first group and 2 and 3 in the second
+        let signing = [[0, 2], [1, 2]];
+        let mut group_sessions = vec![];
+        let mut secrets = vec![];
+        for ((group, member_shares), signing) in groups.iter().zip(signing) {
+            let member_secrets = signing
+                .iter()
+                .map(|i| {
+                    let (member, share) = member_shares.iter().nth(*i).unwrap();
+                    (*member, (*share, frost.gen_nonce(&mut rand::thread_rng())))
+                })
+                .collect::<BTreeMap<_, _>>();
+            let group_session = group
+                .start_session(
+                    member_secrets
+                        .iter()
+                        .map(|(member, (_, nonce))| (*member, nonce.public()))
+                        .collect(),
+                )
+                .unwrap();
+            group_sessions.push(group_session);
+            secrets.push(member_secrets);
+        }
+        let message = Message::<Public>::plain("test", b"hierarchical");
+        let session = frost.start_sign_session(
+            &frost_key,
+            group_sessions
+                .iter()
+                .map(|group_session| (group_session.group().index(), group_session.nonce()))
+                .collect(),
+            message,
+        );
+        let mut group_shares = vec![];
+        for (group_session, member_secrets) in group_sessions.iter().zip(secrets) {
+            let member_shares = member_secrets
+                .into_iter()
+                .map(|(member, (share, nonce))| {
+                    let signature_share = frost.sign_as_group_member(
+                        &frost_key,
+                        &session,
+                        group_session,
+                        member,
+                        &share,
+                        nonce,
+                    );
+                    assert!(frost.verify_group_member_share(
+                        &frost_key,
+                        &session,
+                        group_session,
+                        member,
+                        signature_share
+                    ));
+                    signature_share
+                })
+                .collect::<Vec<_>>();
+            let bad_share = s!({ member_shares[0] } + 1).public();
+            assert!(!frost.verify_group_member_share(
+                &frost_key,
+                &session,
+                group_session,
+                group_session.members().next().unwrap(),
+                bad_share,
+            ));
+            let group_share = group_session.combine(member_shares);
+            assert!(frost.verify_signature_share(
+                &frost_key,
+                &session,
+                group_session.group().index(),
+                group_share
+            ));
+            group_shares.push(group_share);
+        }
+        let signature = frost.combine_signature_shares(&frost_key, &session, group_shares);
+        assert!(frost
+            .schnorr
+            .verify(&frost_key.public_key(), message, &signature));
+    }
+}
@@ -0,0 +1,335 @@
+//! Attesting that a new sharing of a FROST key controls the same key as the old one.
+//!
+//! After the shares of a key are refreshed or reshared (or a single signer key is
+//! [split](crate::frost::split_existing_key)) auditors and wallets that track the key want evidence
+//! that the new [`FrostPublicKeyPackage`] is for the same key and that its shares actually work.
+//! Comparing the public keys is the trivial part. A [`MigrationAttestation`] adds a signature by a
+//! quorum of the **new** shares on a [`MigrationStatement`] naming the [`fingerprint`] of both
+//! sharings. Each signature share is checked against the new package's verification shares so it
+//! can't have been made with the old shares.
+//!
+//! The signers run an ordinary signing session on [`MigrationStatement::message`] and whoever
+//! collects the signature shares bundles them with the nonces into the attestation.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     binonce::NonceKeyPair,
+//!     frost::{
+//!         self,
+//!         migration::{MigrationAttestation, MigrationStatement},
+//!         FrostPublicKeyPackage,
+//!     },
+//!     fun::Scalar,
+//! };
+//! use std::collections::BTreeMap;
+//! let frost = frost::new_with_deterministic_nonces::<sha2::Sha256>();
+//! let secret_key = Scalar::random(&mut rand::thread_rng());
+//! let (old, _) = frost::split_existing_key(&secret_key, 2, 3, &mut rand::thread_rng());
+//! let (new, new_shares) = frost::split_existing_key(&secret_key, 3, 5, &mut rand::thread_rng());
+//!
+//! let statement = MigrationStatement::new::<sha2::Sha256>(&old, &new).unwrap();
+//! let bytes = statement.to_bytes();
+//! let message = MigrationStatement::message(&bytes);
+//! let frost_key = new.frost_key().into_xonly_key();
+//! let signers = new_shares.iter().take(3).collect::<Vec<_>>();
+//! let secret_nonces = signers
+//!     .iter()
+//!     .map(|(index, _)| (**index, NonceKeyPair::random(&mut rand::thread_rng())))
+//!     .collect::<BTreeMap<_, _>>();
+//! let nonces = secret_nonces
+//!     .iter()
+//!     .map(|(index, nonce)| (*index, nonce.public()))
+//!     .collect::<BTreeMap<_, _>>();
+//! let session = frost.start_sign_session(&frost_key, nonces.clone(), message);
+//! let signature_shares = signers
+//!     .iter()
+//!     .map(|(index, share)| {
+//!         let nonce = secret_nonces[*index].clone();
+//!         (**index, frost.sign(&frost_key, &session, **index, share, nonce))
+//!     })
+//!     .collect();
+//! let attestation = MigrationAttestation {
+//!     statement,
+//!     nonces,
+//!     signature_shares,
+//! };
+//! // an auditor with only the two public packages
+//! assert!(attestation.verify(&frost, &old, &new).is_ok());
+//! ```
+//!
+//! [`fingerprint`]: crate::frost::FrostKey::fingerprint
+use super::{Frost, FrostPublicKeyPackage, Nonce, PartyIndex};
+use crate::{Message, Signature};
+use alloc::collections::BTreeMap;
+use core::fmt;
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    marker::*,
+    Point, Scalar,
+};
+
+/// The application tag of the message the new shares sign.
+pub const MIGRATION_APP_TAG: &str = "frost/migration";
+
+/// What a [`MigrationAttestation`] attests to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct MigrationStatement {
+    /// The joint public key both sharings control.
+    pub public_key: Point,
+    /// The fingerprint of the old sharing.
+    pub old_fingerprint: [u8; 8],
+    /// The fingerprint of the new sharing.
+    pub new_fingerprint: [u8; 8],
+}
+
+impl MigrationStatement {
+    /// The length of [`to_bytes`](Self::to_bytes).
+    pub const SIZE: usize = 33 + 8 + 8;
+
+    /// The statement that `new` controls the same key as `old`.
+    ///
+    /// Returns [`MigrationError::DifferentPublicKey`] if they don't.
+    pub fn new<H: Default + Digest<OutputSize = U32>>(
+        old: &FrostPublicKeyPackage,
+        new: &FrostPublicKeyPackage,
+    ) -> Result<Self, MigrationError> {
+        if old.public_key() != new.public_key() {
+            return Err(MigrationError::DifferentPublicKey);
+        }
+        Ok(Self {
+            public_key: new.public_key(),
+            old_fingerprint: old.frost_key().fingerprint::<H>(),
+            new_fingerprint: new.frost_key().fingerprint::<H>(),
+        })
+    }
+
+    /// The public key followed by the old and new fingerprints.
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[..33].copy_from_slice(&self.public_key.to_bytes());
+        bytes[33..41].copy_from_slice(&self.old_fingerprint);
+        bytes[41..].copy_from_slice(&self.new_fingerprint);
+        bytes
+    }
+
+    /// The message the new shares sign given the statement's [`to_bytes`](Self::to_bytes).
+    pub fn message(bytes: &[u8; Self::SIZE]) -> Message<'_, Public> {
+        Message::plain(MIGRATION_APP_TAG, bytes)
+    }
+}
+
+/// A quorum of a key's new shares signing a [`MigrationStatement`].
+///
+/// See the [module documentation](crate::frost::migration).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct MigrationAttestation {
+    /// What is being attested to.
+    pub statement: MigrationStatement,
+    /// The public nonce of each signer.
+    pub nonces: BTreeMap<PartyIndex, Nonce>,
+    /// The signature share of each signer.
+    pub signature_shares: BTreeMap<PartyIndex, Scalar<Public, Zero>>,
+}
+
+impl MigrationAttestation {
+    /// Checks the attestation that `new` controls the same key as `old`.
+    ///
+    /// On success returns the signature the shares combine to which is a BIP340 signature on
+    /// [`MigrationStatement::message`] under the joint key.
+    pub fn verify<H: Default + Digest<OutputSize = U32> + Clone, NG>(
+        &self,
+        frost: &Frost<H, NG>,
+        old: &FrostPublicKeyPackage,
+        new: &FrostPublicKeyPackage,
+    ) -> Result<Signature, MigrationError> {
+        if MigrationStatement::new::<H>(old, new)? != self.statement {
+            return Err(MigrationError::StatementMismatch);
+        }
+        if self.nonces.len() < new.threshold() {
+            return Err(MigrationError::NotEnoughSigners);
+        }
+        if let Some(index) = self
+            .nonces
+            .keys()
+            .chain(self.signature_shares.keys())
+            .find(|index| new.verification_share(**index).is_none())
+        {
+            return Err(MigrationError::UnknownSigner(*index));
+        }
+        if let Some(index) = self
+            .nonces
+            .keys()
+            .find(|index| !self.signature_shares.contains_key(index))
+        {
+            return Err(MigrationError::MissingSignatureShare(*index));
+        }
+
+        let frost_key = new.frost_key().into_xonly_key();
+        let bytes = self.statement.to_bytes();
+        let message = MigrationStatement::message(&bytes);
+        let session = frost.start_sign_session(&frost_key, self.nonces.clone(), message);
+        for (index, signature_share) in &self.signature_shares {
+            if !self.nonces.contains_key(index)
+                || !frost.verify_signature_share(&frost_key, &session, *index, *signature_share)
+            {
+                return Err(MigrationError::InvalidSignatureShare(*index));
+            }
+        }
+        let signature = frost.combine_signature_shares(
+            &frost_key,
+            &session,
+            self.signature_shares.values().copied().collect(),
+        );
+        if !frost
+            .schnorr
+            .verify(&frost_key.public_key(), message, &signature)
+        {
+            return Err(MigrationError::InvalidSignature);
+        }
+        Ok(signature)
+    }
+}
+
+/// Why a [`MigrationAttestation`] is invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MigrationError {
+    /// The old and new packages have different public keys.
+    DifferentPublicKey,
+    /// The statement isn't about the given packages.
+    StatementMismatch,
+    /// Fewer than the new threshold of parties signed.
+    NotEnoughSigners,
+    /// A signer isn't a party of the new package.
+    UnknownSigner(PartyIndex),
+    /// A signer sent a nonce but no signature share.
+    MissingSignatureShare(PartyIndex),
+    /// A signature share doesn't verify against the signer's new verification share.
+    InvalidSignatureShare(PartyIndex),
+    /// The signature shares don't combine into a valid signature.
+    InvalidSignature,
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::DifferentPublicKey => {
+                write!(f, "the old and new sharings are of different keys")
+            }
+            MigrationError::StatementMismatch => {
+                write!(f, "the statement isn't about these sharings")
+            }
+            MigrationError::NotEnoughSigners => {
+                write!(f, "fewer than the threshold of the new sharing signed")
+            }
+            MigrationError::UnknownSigner(index) => {
+                write!(f, "party {} isn't in the new sharing", index)
+            }
+            MigrationError::MissingSignatureShare(index) => {
+                write!(f, "party {} didn't send a signature share", index)
+            }
+            MigrationError::InvalidSignatureShare(index) => {
+                write!(f, "the signature share of party {} is invalid", index)
+            }
+            MigrationError::InvalidSignature => write!(f, "the signature is invalid"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MigrationError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{binonce::NonceKeyPair, frost};
+    use sha2::Sha256;
+
+    #[test]
+    fn attest_migration() {
+        let frost = frost::new_with_deterministic_nonces::<Sha256>();
+        let secret_key = Scalar::random(&mut rand::thread_rng());
+        let (old, old_shares) =
+            frost::split_existing_key(&secret_key, 2, 3, &mut rand::thread_rng());
+        let (new, new_shares) =
+            frost::split_existing_key(&secret_key, 2, 4, &mut rand::thread_rng());
+        let (other, _) = frost::split_existing_key(
+            &Scalar::random(&mut rand::thread_rng()),
+            2,
+            3,
+            &mut rand::thread_rng(),
+        );
+        assert_eq!(
+            MigrationStatement::new::<Sha256>(&old, &other),
+            Err(MigrationError::DifferentPublicKey)
+        );
+
+        let statement = MigrationStatement::new::<Sha256>(&old, &new).unwrap();
+        let sign_with = |shares: &BTreeMap<PartyIndex, Scalar>, package: &FrostPublicKeyPackage| {
+            let frost_key = package.frost_key().into_xonly_key();
+            let bytes = statement.to_bytes();
+            let message = MigrationStatement::message(&bytes);
+            let secret_nonces = shares
+                .keys()
+                .take(2)
+                .map(|index| (*index, NonceKeyPair::random(&mut rand::thread_rng())))
+                .collect::<BTreeMap<_, _>>();
+            let nonces = secret_nonces
+                .iter()
+                .map(|(index, nonce)| (*index, nonce.public()))
+                .collect::<BTreeMap<_, _>>();
+            let session = frost.start_sign_session(&frost_key, nonces.clone(), message);
+            let signature_shares = secret_nonces
+                .into_iter()
+                .map(|(index, nonce)| {
+                    let share = frost.sign(&frost_key, &session, index, &shares[&index], nonce);
+                    (index, share)
+                })
+                .collect();
+            MigrationAttestation {
+                statement,
+                nonces,
+                signature_shares,
+            }
+        };
+
+        let attestation = sign_with(&new_shares, &new);
+        let signature = attestation.verify(&frost, &old, &new).unwrap();
+        let bytes = statement.to_bytes();
+        assert!(frost.schnorr.verify(
+            &new.frost_key().into_xonly_key().public_key(),
+            MigrationStatement::message(&bytes),
+            &signature
+        ));
+        assert_eq!(
+            attestation.verify(&frost, &new, &old),
+            Err(MigrationError::StatementMismatch)
+        );
+
+        // the old shares can make the same signature but not the same signature shares
+        let forged = sign_with(&old_shares, &old);
+        assert!(matches!(
+            forged.verify(&frost, &old, &new),
+            Err(MigrationError::InvalidSignatureShare(_))
+        ));
+
+        let mut missing = attestation.clone();
+        let first = *missing.signature_shares.keys().next().unwrap();
+        missing.signature_shares.remove(&first);
+        assert_eq!(
+            missing.verify(&frost, &old, &new),
+            Err(MigrationError::MissingSignatureShare(first))
+        );
+    }
+}
@@ -0,0 +1,314 @@
+//! Everything a watch-only observer of a FROST key needs in one place.
+//!
+//! A coordinator, auditor or monitoring service that doesn't hold a share still wants to check
+//! the signature shares the signers send and keep track of who is taking part in signing. A
+//! [`FrostPublicKeyPackage`] bundles the joint public key, the threshold and each party's
+//! verification share (the public image of their secret share) so it can be handed to such an
+//! observer without any secret material. With the `serde` feature it serializes and the
+//! verification shares are checked against the joint key when it is deserialized.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     frost::{self, FrostPublicKeyPackage},
+//!     Message,
+//! };
+//! use rand_chacha::ChaCha20Rng;
+//! use std::collections::BTreeMap;
+//! let frost = frost::new_with_deterministic_nonces::<sha2::Sha256>();
+//! let (frost_key, secret_shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+//! let package = FrostPublicKeyPackage::new(&frost_key, secret_shares.keys().copied()).unwrap();
+//!
+//! // the observer only has the package
+//! let frost_key = package.frost_key().into_xonly_key();
+//! let message = Message::plain("test", b"watch only");
+//! let signers = secret_shares.iter().take(2).collect::<Vec<_>>();
+//! let nonces = signers
+//!     .iter()
+//!     .map(|(index, secret_share)| {
+//!         let mut nonce_rng: ChaCha20Rng =
+//!             frost.seed_nonce_rng(&frost_key, secret_share, b"session");
+//!         (**index, frost.gen_nonce(&mut nonce_rng))
+//!     })
+//!     .collect::<BTreeMap<_, _>>();
+//! let session = frost.start_sign_session(
+//!     &frost_key,
+//!     nonces.iter().map(|(index, nonce)| (*index, nonce.public())).collect(),
+//!     message,
+//! );
+//! assert_eq!(package.missing_signers(&session).count(), 1);
+//! for (index, secret_share) in signers {
+//!     let signature_share = frost.sign(
+//!         &frost_key,
+//!         &session,
+//!         *index,
+//!         secret_share,
+//!         nonces[index].clone(),
+//!     );
+//!     assert!(frost.verify_signature_share(&frost_key, &session, *index, signature_share));
+//! }
+//! ```
+use super::{FrostKey, KeyGeneration, PartyIndex, SignSession};
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::fmt;
+use secp256kfun::{marker::*, poly, Point};
+
+/// The public parts of a FROST key for parties that verify but don't sign.
+///
+/// See the [module documentation](crate::frost::public_key_package).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrostPublicKeyPackage {
+    verification_shares: BTreeMap<PartyIndex, Point<Normal, Public, Zero>>,
+    frost_key: FrostKey<Normal>,
+}
+
+impl FrostPublicKeyPackage {
+    /// Creates the package for the parties `participants` of `frost_key`.
+    ///
+    /// Like [`EncodedFrostKey`] this doesn't include any tweaks applied to `frost_key`. It does
+    /// include its [`KeyGeneration`].
+    ///
+    /// [`EncodedFrostKey`]: crate::frost::EncodedFrostKey
+    pub fn new<T: PointType + Copy>(
+        frost_key: &FrostKey<T>,
+        participants: impl IntoIterator<Item = PartyIndex>,
+    ) -> Result<Self, PublicKeyPackageError> {
+        let verification_shares = participants
+            .into_iter()
+            .map(|index| (index, frost_key.verification_share(index).normalize()))
+            .collect::<BTreeMap<_, _>>();
+        if verification_shares.len() < frost_key.threshold() {
+            return Err(PublicKeyPackageError::NotEnoughParticipants);
+        }
+        let frost_key = FrostKey::from(super::EncodedFrostKey::from(frost_key.clone()))
+            .with_generation(frost_key.generation());
+        Ok(Self {
+            verification_shares,
+            frost_key,
+        })
+    }
+
+    /// Creates the package from the joint public key, threshold and verification shares.
+    ///
+    /// The verification shares must all lie on a polynomial of degree exactly `threshold - 1` that
+    /// is the public key at `0`. That requires at least `threshold` of them.
+    pub fn from_verification_shares(
+        public_key: Point,
+        threshold: usize,
+        verification_shares: BTreeMap<PartyIndex, Point<Normal, Public, Zero>>,
+        generation: KeyGeneration,
+    ) -> Result<Self, PublicKeyPackageError> {
+        if threshold == 0 || verification_shares.len() < threshold {
+            return Err(PublicKeyPackageError::NotEnoughParticipants);
+        }
+        let points = verification_shares
+            .iter()
+            .take(threshold)
+            .map(|(index, share)| Some((index.mark_zero(), share.non_zero()?)))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(PublicKeyPackageError::InvalidVerificationShares)?;
+        let point_polynomial = if threshold == 1 {
+            // interpolating a single point gives an empty polynomial rather than a constant one
+            points.iter().map(|(_, share)| share.mark_zero()).collect()
+        } else {
+            poly::point::interpolate(points)
+                .into_iter()
+                .map(|coefficient| coefficient.normalize())
+                .collect::<Vec<_>>()
+        };
+        // a zero last coefficient means the shares are really on a lower degree polynomial
+        if point_polynomial.first() != Some(&public_key.mark_zero())
+            || point_polynomial
+                .last()
+                .map_or(true, |coefficient| coefficient.is_zero())
+        {
+            return Err(PublicKeyPackageError::InvalidVerificationShares);
+        }
+        let frost_key =
+            FrostKey::from(super::EncodedFrostKey { point_polynomial }).with_generation(generation);
+        let consistent = verification_shares
+            .iter()
+            .skip(threshold)
+            .all(|(index, share)| frost_key.verification_share(index) == *share);
+        if !consistent {
+            return Err(PublicKeyPackageError::InvalidVerificationShares);
+        }
+        Ok(Self {
+            verification_shares,
+            frost_key,
+        })
+    }
+
+    /// The joint public key (without any tweaks).
+    pub fn public_key(&self) -> Point {
+        self.frost_key.public_key()
+    }
+
+    /// The number of parties needed to sign.
+    pub fn threshold(&self) -> usize {
+        self.frost_key.threshold()
+    }
+
+    /// The generation of shares the package is for.
+    pub fn generation(&self) -> KeyGeneration {
+        self.frost_key.generation()
+    }
+
+    /// The indices of the parties in the package.
+    pub fn participants(&self) -> impl DoubleEndedIterator<Item = PartyIndex> + '_ {
+        self.verification_shares.keys().copied()
+    }
+
+    /// The verification share of the party at `index` if it is in the package.
+    pub fn verification_share(&self, index: PartyIndex) -> Option<Point<Normal, Public, Zero>> {
+        self.verification_shares.get(&index).copied()
+    }
+
+    /// The verification shares of every party in the package.
+    pub fn verification_shares(&self) -> &BTreeMap<PartyIndex, Point<Normal, Public, Zero>> {
+        &self.verification_shares
+    }
+
+    /// The FROST key to pass to [`Frost::verify_signature_share`] (after converting it with
+    /// [`into_xonly_key`] and applying any tweaks).
+    ///
+    /// [`Frost::verify_signature_share`]: crate::frost::Frost::verify_signature_share
+    /// [`into_xonly_key`]: FrostKey::into_xonly_key
+    pub fn frost_key(&self) -> FrostKey<Normal> {
+        self.frost_key.clone()
+    }
+
+    /// The parties in the package that aren't signing in `session`.
+    pub fn missing_signers<'a>(
+        &'a self,
+        session: &'a SignSession,
+    ) -> impl Iterator<Item = PartyIndex> + 'a {
+        self.participants()
+            .filter(move |index| !session.participants().any(|signer| signer == *index))
+    }
+}
+
+/// Error creating a [`FrostPublicKeyPackage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PublicKeyPackageError {
+    /// There were fewer participants than the threshold.
+    NotEnoughParticipants,
+    /// The verification shares aren't shares of the public key.
+    InvalidVerificationShares,
+}
+
+impl fmt::Display for PublicKeyPackageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use PublicKeyPackageError::*;
+        match self {
+            NotEnoughParticipants => write!(f, "there are fewer participants than the threshold"),
+            InvalidVerificationShares => {
+                write!(
+                    f,
+                    "the verification shares are not shares of the public key"
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PublicKeyPackageError {}
+
+#[cfg(feature = "serde")]
+#[derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize)]
+#[serde(crate = "crate::fun::serde")]
+struct Repr {
+    public_key: Point,
+    threshold: usize,
+    verification_shares: BTreeMap<PartyIndex, Point<Normal, Public, Zero>>,
+    generation: KeyGeneration,
+}
+
+#[cfg(feature = "serde")]
+impl crate::fun::serde::Serialize for FrostPublicKeyPackage {
+    fn serialize<S: crate::fun::serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Repr {
+            public_key: self.public_key(),
+            threshold: self.threshold(),
+            verification_shares: self.verification_shares.clone(),
+            generation: self.generation(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> crate::fun::serde::Deserialize<'de> for FrostPublicKeyPackage {
+    fn deserialize<D: crate::fun::serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let repr = Repr::deserialize(deserializer)?;
+        Self::from_verification_shares(
+            repr.public_key,
+            repr.threshold,
+            repr.verification_shares,
+            repr.generation,
+        )
+        .map_err(crate::fun::serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+
+    #[test]
+    fn from_verification_shares_checks_shares() {
+        let frost = crate::frost::new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, shares) = frost.simulate_keygen(2, 4, &mut rand::thread_rng());
+        let package = FrostPublicKeyPackage::new(&frost_key, shares.keys().copied()).unwrap();
+        assert_eq!(package.frost_key(), frost_key);
+        assert_eq!(package.participants().count(), 4);
+
+        let rebuilt = FrostPublicKeyPackage::from_verification_shares(
+            package.public_key(),
+            package.threshold(),
+            package.verification_shares().clone(),
+            package.generation(),
+        );
+        assert_eq!(rebuilt, Ok(package.clone()));
+
+        let mut bad_shares = package.verification_shares().clone();
+        let last = *bad_shares.keys().last().unwrap();
+        bad_shares.insert(last, Point::random(&mut rand::thread_rng()).mark_zero());
+        assert_eq!(
+            FrostPublicKeyPackage::from_verification_shares(
+                package.public_key(),
+                2,
+                bad_shares,
+                package.generation(),
+            ),
+            Err(PublicKeyPackageError::InvalidVerificationShares)
+        );
+        assert_eq!(
+            FrostPublicKeyPackage::new(&frost_key, shares.keys().copied().take(1)),
+            Err(PublicKeyPackageError::NotEnoughParticipants)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_roundtrip() {
+        let frost = crate::frost::new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        let package = FrostPublicKeyPackage::new(&frost_key, shares.keys().copied()).unwrap();
+        let mut json = serde_json::to_value(&package).unwrap();
+        assert_eq!(
+            serde_json::from_value::<FrostPublicKeyPackage>(json.clone()).unwrap(),
+            package
+        );
+        json["threshold"] = 3.into();
+        assert!(serde_json::from_value::<FrostPublicKeyPackage>(json).is_err());
+    }
+}
@@ -0,0 +1,293 @@
+//! Repairing a lost share or enrolling a new party without changing the key.
+//!
+//! If a party loses their secret share (or a new party should be added to the key) `threshold` or
+//! more existing parties, the *helpers*, can compute the share at the *target* index for them. The
+//! share is the evaluation of the key's polynomial at the target index so the public key, the
+//! threshold and everyone else's shares stay the same. No one learns anything about anyone else's
+//! share or about the joint secret.
+//!
+//! The protocol is the repairable threshold scheme of [Laing and Stinson]:
+//!
+//! 1. Each helper `i` computes `λ_i(target) * s_i` where `λ_i(target)` is their lagrange coefficient
+//!    at the target index, splits it into random deltas with [`helper_deltas`] and sends one
+//!    delta to each helper (themselves included) over a private channel.
+//! 2. Each helper adds up the deltas they received with [`combine_deltas`] and sends the sum to the
+//!    party at the target index over a private channel.
+//! 3. The party at the target index adds up the sums with [`recover_share`] which checks the
+//!    share against the target's verification share.
+//!
+//! Each helper only ever sees random deltas so the helpers can't learn each other's shares unless
+//! they all collude (in which case they could just sign anyway). Enrolling a new party doesn't
+//! change the [`FrostKey`] but any [`FrostPublicKeyPackage`] has to be recreated to include them.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     frost::{self, repair, IntoPartyIndex},
+//!     fun::{g, G},
+//! };
+//! use std::collections::{BTreeMap, BTreeSet};
+//! let (package, mut shares) = frost::split_existing_key(
+//!     &schnorr_fun::fun::Scalar::random(&mut rand::thread_rng()),
+//!     2,
+//!     3,
+//!     &mut rand::thread_rng(),
+//! );
+//! let frost_key = package.frost_key();
+//! // party 3 lost their share and parties 1 and 2 help them get it back
+//! let target = 3.into_party_index();
+//! let lost_share = shares.remove(&target).unwrap();
+//! let helpers = shares.keys().copied().collect::<BTreeSet<_>>();
+//! let mut received = BTreeMap::<_, Vec<_>>::new();
+//! for (helper, secret_share) in &shares {
+//!     let deltas = repair::helper_deltas(
+//!         &frost_key,
+//!         &helpers,
+//!         helper,
+//!         secret_share,
+//!         target,
+//!         &mut rand::thread_rng(),
+//!     )
+//!     .unwrap();
+//!     for (receiver, delta) in deltas {
+//!         received.entry(receiver).or_default().push(delta);
+//!     }
+//! }
+//! let sums = received.into_values().map(repair::combine_deltas);
+//! let repaired_share = repair::recover_share(&frost_key, target, sums).unwrap();
+//! assert_eq!(repaired_share, lost_share);
+//! ```
+//!
+//! [Laing and Stinson]: https://eprint.iacr.org/2017/1155
+//! [`FrostPublicKeyPackage`]: crate::frost::FrostPublicKeyPackage
+use super::{FrostKey, IntoPartyIndex, PartyIndex};
+use alloc::collections::{BTreeMap, BTreeSet};
+use secp256kfun::{g, marker::*, rand_core::RngCore, s, Scalar, G};
+
+/// Error returned by the functions in [`repair`](crate::frost::repair).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairError {
+    /// There were fewer helpers than the threshold of the key.
+    NotEnoughHelpers,
+    /// The target index is one of the helpers.
+    TargetIsHelper,
+    /// The index passed as ours isn't one of the helpers.
+    NotAHelper,
+    /// The recovered share doesn't match the target's verification share.
+    InvalidShare,
+}
+
+impl core::fmt::Display for RepairError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        use RepairError::*;
+        match self {
+            NotEnoughHelpers => write!(f, "there were fewer helpers than the threshold"),
+            TargetIsHelper => write!(f, "the target of the repair can't be one of the helpers"),
+            NotAHelper => write!(f, "our index wasn't one of the helpers"),
+            InvalidShare => write!(
+                f,
+                "the recovered share didn't match the verification share. One of the helpers \
+                sent a wrong value or didn't use the same set of helpers"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RepairError {}
+
+/// The lagrange coefficient of `x_i` for interpolating the polynomial at `target` from `x_ms`.
+fn eval_basis_poly_at_target(
+    target: PartyIndex,
+    x_i: PartyIndex,
+    x_ms: &BTreeSet<PartyIndex>,
+) -> Scalar<Public> {
+    let (numerator, denominator) = x_ms.iter().filter(|x_m| **x_m != x_i).fold(
+        (Scalar::<Public>::one(), Scalar::<Public>::one()),
+        |(numerator, denominator), x_m| {
+            let target_difference = s!(target - x_m)
+                .public()
+                .non_zero()
+                .expect("the target isn't a helper");
+            let difference = s!(x_i - x_m)
+                .public()
+                .non_zero()
+                .expect("the helpers are unique");
+            (
+                s!(numerator * target_difference).public(),
+                s!(denominator * difference).public(),
+            )
+        },
+    );
+    s!(numerator / denominator).public()
+}
+
+/// Step 1 for the helper at `my_index`: splits our part of the share at `target` into a random
+/// delta for each of the `helpers`.
+///
+/// Send each delta to the helper it's for (keep your own) over a private channel. The deltas are
+/// random each time so only call this once per repair.
+pub fn helper_deltas<T: Copy + PointType>(
+    frost_key: &FrostKey<T>,
+    helpers: &BTreeSet<PartyIndex>,
+    my_index: impl IntoPartyIndex,
+    secret_share: &Scalar,
+    target: impl IntoPartyIndex,
+    rng: &mut impl RngCore,
+) -> Result<BTreeMap<PartyIndex, Scalar<Secret, Zero>>, RepairError> {
+    let my_index = my_index.into_party_index();
+    let target = target.into_party_index();
+    if helpers.len() < frost_key.threshold() {
+        return Err(RepairError::NotEnoughHelpers);
+    }
+    if helpers.contains(&target) {
+        return Err(RepairError::TargetIsHelper);
+    }
+    if !helpers.contains(&my_index) {
+        return Err(RepairError::NotAHelper);
+    }
+
+    let lambda = eval_basis_poly_at_target(target, my_index, helpers);
+    let mut remaining = s!(lambda * secret_share).mark_zero();
+    let mut deltas = helpers
+        .iter()
+        .skip(1)
+        .map(|helper| {
+            let delta = Scalar::random(rng).mark_zero();
+            remaining -= delta;
+            (*helper, delta)
+        })
+        .collect::<BTreeMap<_, _>>();
+    let first = *helpers.iter().next().expect("there is at least one helper");
+    deltas.insert(first, remaining);
+    Ok(deltas)
+}
+
+/// Step 2 for each helper: adds up the deltas received from every helper (including our own).
+///
+/// Send the sum to the party at the target index over a private channel.
+pub fn combine_deltas(
+    deltas: impl IntoIterator<Item = Scalar<Secret, Zero>>,
+) -> Scalar<Secret, Zero> {
+    deltas
+        .into_iter()
+        .fold(Scalar::zero(), |sum, delta| s!(sum + delta))
+}
+
+/// Step 3 for the party at `target`: adds up the sums from every helper into our secret share.
+///
+/// The share is checked against the verification share of `target` in `frost_key`.
+pub fn recover_share<T: Copy + PointType>(
+    frost_key: &FrostKey<T>,
+    target: impl IntoPartyIndex,
+    sums: impl IntoIterator<Item = Scalar<Secret, Zero>>,
+) -> Result<Scalar, RepairError> {
+    let secret_share = combine_deltas(sums);
+    if g!(secret_share * G) != frost_key.verification_share(target) {
+        return Err(RepairError::InvalidShare);
+    }
+    secret_share.non_zero().ok_or(RepairError::InvalidShare)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frost;
+    use alloc::vec::Vec;
+    use secp256kfun::poly;
+
+    fn repair(
+        frost_key: &FrostKey<Normal>,
+        shares: &BTreeMap<PartyIndex, Scalar>,
+        target: PartyIndex,
+    ) -> Result<Scalar, RepairError> {
+        let helpers = shares.keys().copied().collect::<BTreeSet<_>>();
+        let mut received = BTreeMap::<_, Vec<_>>::new();
+        for (helper, secret_share) in shares {
+            for (receiver, delta) in helper_deltas(
+                frost_key,
+                &helpers,
+                helper,
+                secret_share,
+                target,
+                &mut rand::thread_rng(),
+            )? {
+                received.entry(receiver).or_default().push(delta);
+            }
+        }
+        recover_share(
+            frost_key,
+            target,
+            received.into_values().map(combine_deltas),
+        )
+    }
+
+    #[test]
+    fn repair_and_enroll() {
+        let secret_key = Scalar::random(&mut rand::thread_rng());
+        let (package, mut shares) =
+            frost::split_existing_key(&secret_key, 3, 5, &mut rand::thread_rng());
+        let frost_key = package.frost_key();
+
+        // repair with exactly threshold helpers
+        let lost = 2.into_party_index();
+        let lost_share = shares.remove(&lost).unwrap();
+        let helpers = [1, 3, 5]
+            .map(|i| (i.into_party_index(), shares[&i.into_party_index()]))
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(repair(&frost_key, &helpers, lost), Ok(lost_share));
+        // and with more than threshold
+        assert_eq!(repair(&frost_key, &shares, lost), Ok(lost_share));
+
+        // enroll a new party and they can recover the key with two others
+        let new = 6.into_party_index();
+        let new_share = repair(&frost_key, &helpers, new).unwrap();
+        assert_eq!(g!(new_share * G), frost_key.verification_share(new));
+        let quorum = [(new, new_share), (lost, lost_share)]
+            .into_iter()
+            .chain(shares.iter().take(1).map(|(i, s)| (*i, *s)))
+            .collect();
+        assert_eq!(
+            poly::scalar::interpolate_and_eval_poly_at_0(quorum),
+            secret_key
+        );
+
+        let two_helpers = helpers.iter().take(2).map(|(i, s)| (*i, *s)).collect();
+        assert_eq!(
+            repair(&frost_key, &two_helpers, lost),
+            Err(RepairError::NotEnoughHelpers)
+        );
+        let helper_set = helpers.keys().copied().collect::<BTreeSet<_>>();
+        let one = 1.into_party_index();
+        assert_eq!(
+            helper_deltas(
+                &frost_key,
+                &helper_set,
+                one,
+                &helpers[&one],
+                one,
+                &mut rand::thread_rng()
+            ),
+            Err(RepairError::TargetIsHelper)
+        );
+        assert_eq!(
+            helper_deltas(
+                &frost_key,
+                &helper_set,
+                lost,
+                &lost_share,
+                new,
+                &mut rand::thread_rng()
+            ),
+            Err(RepairError::NotAHelper)
+        );
+        // a helper sending a wrong sum is caught
+        let bad_sums = [s!(1).mark_zero(), s!(2).mark_zero(), s!(3).mark_zero()];
+        assert_eq!(
+            recover_share(&frost_key, lost, bad_sums),
+            Err(RepairError::InvalidShare)
+        );
+    }
+}
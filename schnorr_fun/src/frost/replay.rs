@@ -0,0 +1,316 @@
+//! Offline re-verification of recorded FROST signing ceremonies.
+//!
+//! A coordinator or signing service can record every [`SigningMessage`] of a ceremony in the order
+//! it received them. [`replay_signing`] checks that log end-to-end after the fact: that every
+//! message came from a signer, no one sent a round twice, every signer's nonce and signature share
+//! is there, each share is valid and that they combine into a valid signature (the one that was
+//! published if it is given). The error says which step of the log would have failed and who was
+//! responsible which is what you need for a post-incident audit.
+//!
+//! The log only contains public data so it is safe to store.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     frost::{self, replay::{replay_signing, ReplayError}, SigningMessage},
+//!     fun::Scalar,
+//!     Message,
+//! };
+//! use std::collections::BTreeMap;
+//! let frost = frost::new_with_deterministic_nonces::<sha2::Sha256>();
+//! let (frost_key, secret_shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+//! let frost_key = frost_key.into_xonly_key();
+//! let message = Message::plain("my-app", b"audit me");
+//! let signers = secret_shares.into_iter().take(2).collect::<BTreeMap<_, _>>();
+//! let nonces = signers
+//!     .keys()
+//!     .map(|index| (*index, frost.gen_nonce(&mut rand::thread_rng())))
+//!     .collect::<BTreeMap<_, _>>();
+//! let session = frost.start_sign_session(
+//!     &frost_key,
+//!     nonces.iter().map(|(index, nonce)| (*index, nonce.public())).collect(),
+//!     message,
+//! );
+//! // what the coordinator recorded
+//! let mut log = nonces
+//!     .iter()
+//!     .map(|(index, nonce)| (*index, SigningMessage::Nonce(nonce.public())))
+//!     .collect::<Vec<_>>();
+//! for (index, secret_share) in &signers {
+//!     let share = frost.sign(&frost_key, &session, *index, secret_share, nonces[index].clone());
+//!     log.push((*index, SigningMessage::SignatureShare(share)));
+//! }
+//! let signers = signers.keys().copied().collect();
+//! let signature = replay_signing(&frost, &frost_key, &signers, message, &log, None).unwrap();
+//!
+//! // a tampered share is pinned to the step it was logged at
+//! let (index, _) = log[3];
+//! log[3] = (index, SigningMessage::SignatureShare(Scalar::zero()));
+//! assert_eq!(
+//!     replay_signing(&frost, &frost_key, &signers, message, &log, Some(&signature)),
+//!     Err(ReplayError::InvalidSignatureShare { step: 3, from: index })
+//! );
+//! ```
+use super::{Frost, FrostKey, PartyIndex, SigningMessage};
+use crate::{Message, Signature};
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+use core::fmt;
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    marker::*,
+};
+
+/// Re-verifies the recorded `log` of a signing ceremony by `signers` on `message` under
+/// `frost_key`.
+///
+/// `log` is every message of the ceremony (including each signer's own) along with its sender in
+/// the order they were recorded. Signature shares may appear before the last nonce. If
+/// `signature` is given it must be the signature the shares combine to.
+///
+/// Returns the signature the ceremony produced.
+pub fn replay_signing<H: Digest<OutputSize = U32> + Clone, NG>(
+    frost: &Frost<H, NG>,
+    frost_key: &FrostKey<EvenY>,
+    signers: &BTreeSet<PartyIndex>,
+    message: Message,
+    log: &[(PartyIndex, SigningMessage)],
+    signature: Option<&Signature>,
+) -> Result<Signature, ReplayError> {
+    if signers.len() < frost_key.threshold() {
+        return Err(ReplayError::NotEnoughSigners);
+    }
+    let mut nonces = BTreeMap::new();
+    let mut shares = BTreeMap::new();
+    for (step, (from, signing_message)) in log.iter().enumerate() {
+        let from = *from;
+        if !signers.contains(&from) {
+            return Err(ReplayError::UnexpectedSender { step, from });
+        }
+        let duplicate = match signing_message {
+            SigningMessage::Nonce(nonce) => nonces.insert(from, *nonce).is_some(),
+            SigningMessage::SignatureShare(share) => shares.insert(from, (step, *share)).is_some(),
+        };
+        if duplicate {
+            return Err(ReplayError::Duplicate { step, from });
+        }
+    }
+    if let Some(missing) = signers.iter().find(|signer| !nonces.contains_key(signer)) {
+        return Err(ReplayError::MissingNonce(*missing));
+    }
+
+    let session = frost.start_sign_session(frost_key, nonces, message);
+    let mut signature_shares = Vec::with_capacity(signers.len());
+    // check the shares in the order they were logged so the first bad one is blamed
+    let mut logged_shares = shares.iter().collect::<Vec<_>>();
+    logged_shares.sort_by_key(|(_, (step, _))| *step);
+    for (from, (step, share)) in logged_shares {
+        if !frost.verify_signature_share(frost_key, &session, *from, *share) {
+            return Err(ReplayError::InvalidSignatureShare {
+                step: *step,
+                from: *from,
+            });
+        }
+        signature_shares.push(*share);
+    }
+    if let Some(missing) = signers.iter().find(|signer| !shares.contains_key(signer)) {
+        return Err(ReplayError::MissingSignatureShare(*missing));
+    }
+
+    let combined = frost.combine_signature_shares(frost_key, &session, signature_shares);
+    if !frost
+        .schnorr
+        .verify(&frost_key.public_key(), message, &combined)
+    {
+        return Err(ReplayError::InvalidSignature);
+    }
+    if signature.map_or(false, |signature| *signature != combined) {
+        return Err(ReplayError::SignatureMismatch);
+    }
+    Ok(combined)
+}
+
+/// The step of a recorded signing ceremony that failed. Returned by [`replay_signing`].
+///
+/// `step` is the position of the offending message in the log.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayError {
+    /// There are fewer signers than the threshold of the key.
+    NotEnoughSigners,
+    /// A message was recorded from a party that isn't one of the signers.
+    UnexpectedSender {
+        /// The position of the message in the log.
+        step: usize,
+        /// Who sent it.
+        from: PartyIndex,
+    },
+    /// A signer sent more than one message in a round.
+    Duplicate {
+        /// The position of the second message in the log.
+        step: usize,
+        /// Who sent it.
+        from: PartyIndex,
+    },
+    /// A signer's nonce isn't in the log.
+    MissingNonce(PartyIndex),
+    /// A signer's signature share is invalid.
+    InvalidSignatureShare {
+        /// The position of the share in the log.
+        step: usize,
+        /// Who sent it.
+        from: PartyIndex,
+    },
+    /// A signer's signature share isn't in the log.
+    MissingSignatureShare(PartyIndex),
+    /// The shares are valid but don't combine into a valid signature. This can only happen if the
+    /// key is inconsistent with the signers' verification shares.
+    InvalidSignature,
+    /// The shares combine into a valid signature but not the one that was given.
+    SignatureMismatch,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::NotEnoughSigners => {
+                write!(f, "there are fewer signers than the threshold")
+            }
+            ReplayError::UnexpectedSender { step, from } => {
+                write!(f, "step {step}: party {from} isn't one of the signers")
+            }
+            ReplayError::Duplicate { step, from } => {
+                write!(
+                    f,
+                    "step {step}: party {from} sent more than one message in a round"
+                )
+            }
+            ReplayError::MissingNonce(party) => write!(f, "party {party} never sent a nonce"),
+            ReplayError::InvalidSignatureShare { step, from } => {
+                write!(
+                    f,
+                    "step {step}: party {from} sent an invalid signature share"
+                )
+            }
+            ReplayError::MissingSignatureShare(party) => {
+                write!(f, "party {party} never sent a signature share")
+            }
+            ReplayError::InvalidSignature => {
+                write!(
+                    f,
+                    "the signature shares don't combine into a valid signature"
+                )
+            }
+            ReplayError::SignatureMismatch => {
+                write!(f, "the signature shares combine into a different signature")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ReplayError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frost::new_with_deterministic_nonces;
+    use sha2::Sha256;
+
+    #[test]
+    fn replay_blames_the_failing_step() {
+        let frost = new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, secret_shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        let frost_key = frost_key.into_xonly_key();
+        let message = Message::<Public>::plain("test", b"replay");
+        let mut parties = secret_shares.into_iter();
+        let signing = parties.by_ref().take(2).collect::<BTreeMap<_, _>>();
+        let (outsider, _) = parties.next().unwrap();
+        let nonces = signing
+            .keys()
+            .map(|index| (*index, frost.gen_nonce(&mut rand::thread_rng())))
+            .collect::<BTreeMap<_, _>>();
+        let session = frost.start_sign_session(
+            &frost_key,
+            nonces
+                .iter()
+                .map(|(index, nonce)| (*index, nonce.public()))
+                .collect(),
+            message,
+        );
+        // the first signer's share arrives before the second signer's nonce
+        let (first, second) = {
+            let mut signers = signing.keys().copied();
+            (signers.next().unwrap(), signers.next().unwrap())
+        };
+        let share = |index: PartyIndex| {
+            SigningMessage::SignatureShare(frost.sign(
+                &frost_key,
+                &session,
+                index,
+                &signing[&index],
+                nonces[&index].clone(),
+            ))
+        };
+        let log = vec![
+            (first, SigningMessage::Nonce(nonces[&first].public())),
+            (first, share(first)),
+            (second, SigningMessage::Nonce(nonces[&second].public())),
+            (second, share(second)),
+        ];
+        let signers = signing.keys().copied().collect::<BTreeSet<_>>();
+        let replay = |log: &[(PartyIndex, SigningMessage)], signature: Option<&Signature>| {
+            replay_signing(&frost, &frost_key, &signers, message, log, signature)
+        };
+
+        let signature = replay(&log, None).unwrap();
+        assert!(frost
+            .schnorr
+            .verify(&frost_key.public_key(), message, &signature));
+        assert_eq!(replay(&log, Some(&signature)), Ok(signature.clone()));
+        assert_eq!(
+            replay(&log, Some(&Signature::random(&mut rand::thread_rng()))),
+            Err(ReplayError::SignatureMismatch)
+        );
+        assert_eq!(
+            replay(&log[..3], None),
+            Err(ReplayError::MissingSignatureShare(second))
+        );
+        assert_eq!(
+            replay(&log[1..], None),
+            Err(ReplayError::MissingNonce(first))
+        );
+
+        let mut duplicated = log.clone();
+        duplicated.insert(2, log[1].clone());
+        assert_eq!(
+            replay(&duplicated, None),
+            Err(ReplayError::Duplicate {
+                step: 2,
+                from: first
+            })
+        );
+
+        let mut with_outsider = log.clone();
+        with_outsider.push((outsider, log[3].1.clone()));
+        assert_eq!(
+            replay(&with_outsider, None),
+            Err(ReplayError::UnexpectedSender {
+                step: 4,
+                from: outsider
+            })
+        );
+
+        let mut swapped = log.clone();
+        swapped[1].1 = log[3].1.clone();
+        assert_eq!(
+            replay(&swapped, None),
+            Err(ReplayError::InvalidSignatureShare {
+                step: 1,
+                from: first
+            })
+        );
+    }
+}
@@ -0,0 +1,545 @@
+//! The `FROST(secp256k1, SHA-256)` ciphersuite of [RFC 9591] (the IETF FROST specification).
+//!
+//! There are two ways to use it:
+//!
+//! 1. [`Frost::start_sign_session_rfc9591`] only takes the RFC's nonce binding and still produces
+//!    [BIP340] signatures.
+//! 2. The functions in this module implement the whole ciphersuite including its challenge
+//!    derivation and [`Signature`] format for interoperating with FROST implementations outside of
+//!    Bitcoin. They work with a [`FrostKey<Normal>`] since the RFC doesn't require the
+//!    key or the group commitment to have an even y-coordinate.
+//!
+//! [`Frost::start_sign_session`] binds every signer's nonce with a single coefficient. RFC 9591
+//! instead gives each signer `i` its own binding factor:
+//!
+//! ```text
+//! rho_i = H1(SerializePublicKey(group_public_key) || H4(msg) || H5(encode_group_commitment_list(commitments)) || SerializeScalar(i))
+//! ```
+//!
+//! and the group commitment is `R = Σ (D_i + rho_i * E_i)` where `D_i` and `E_i` are the first
+//! (hiding) and second (binding) points of the signer's [`Nonce`]. [`binding_factors`] computes
+//! them with the exact hashes and encodings of the RFC. The identifier of each signer is its
+//! [`PartyIndex`] and a [`Message`] with an application tag is hashed in its [BIP340] form so use
+//! [`Message::raw`] to interoperate.
+//!
+//! The hash type parameter `H` must be SHA-256 to get the RFC's ciphersuite.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     frost::{self, rfc9591},
+//!     Message,
+//! };
+//! use sha2::Sha256;
+//! use std::collections::BTreeMap;
+//! let frost = frost::new_with_deterministic_nonces::<Sha256>();
+//! let (frost_key, secret_shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+//! let message = Message::raw(b"test");
+//! let signers = secret_shares.into_iter().take(2).collect::<BTreeMap<_, _>>();
+//! let nonces = signers
+//!     .iter()
+//!     .map(|(index, secret_share)| {
+//!         let nonce = rfc9591::gen_nonce::<Sha256>(secret_share, &mut rand::thread_rng());
+//!         (*index, nonce)
+//!     })
+//!     .collect::<BTreeMap<_, _>>();
+//! let session = rfc9591::start_sign_session::<Sha256>(
+//!     &frost_key,
+//!     nonces.iter().map(|(index, nonce)| (*index, nonce.public())).collect(),
+//!     message,
+//! );
+//! let shares = signers
+//!     .iter()
+//!     .map(|(index, secret_share)| {
+//!         let share = rfc9591::sign(&session, *index, secret_share, nonces[index].clone());
+//!         assert!(rfc9591::verify_signature_share(&frost_key, &session, *index, share));
+//!         share
+//!     })
+//!     .collect();
+//! let signature = rfc9591::combine_signature_shares(&frost_key, &session, shares);
+//! assert!(rfc9591::verify::<Sha256>(&frost_key.public_key(), message, &signature));
+//! ```
+//!
+//! [RFC 9591]: https://www.rfc-editor.org/rfc/rfc9591.html
+//! [BIP340]: https://bips.xyz/340
+//! [`Frost::start_sign_session`]: super::Frost::start_sign_session
+//! [`Frost::start_sign_session_rfc9591`]: super::Frost::start_sign_session_rfc9591
+//! [`FrostKey<Normal>`]: super::FrostKey
+use super::{FrostKey, Nonce, NonceKeyPair, PartyIndex};
+use crate::{
+    signing::{self, hash_to_field},
+    Message,
+};
+use alloc::{collections::BTreeMap, vec::Vec};
+use secp256kfun::{
+    digest::{crypto_common::BlockSizeUser, generic_array::typenum::U32, Digest},
+    g,
+    marker::*,
+    poly,
+    rand_core::RngCore,
+    Point, Scalar, G,
+};
+
+/// The context string of the `FROST(secp256k1, SHA-256)` ciphersuite.
+pub const CONTEXT_STRING: &[u8] = signing::RFC9591_CONTEXT_STRING;
+
+/// Computes the binding factor of each signer in `nonces` for signing `message` under
+/// `group_public_key`.
+///
+/// `H` must be SHA-256 to get the binding factors of the RFC.
+pub fn binding_factors<H: Digest<OutputSize = U32> + BlockSizeUser>(
+    group_public_key: Point<impl Normalized>,
+    nonces: &BTreeMap<PartyIndex, Nonce>,
+    message: Message,
+) -> BTreeMap<PartyIndex, Scalar<Public, Zero>> {
+    let prefix = signing::rfc9591_binding_prefix::<H>(
+        group_public_key,
+        nonces.iter().map(|(index, nonce)| (*index, *nonce)),
+        message,
+    );
+    nonces
+        .keys()
+        .map(|index| {
+            (
+                *index,
+                signing::rfc9591_binding_factor::<H>(&prefix, *index),
+            )
+        })
+        .collect()
+}
+
+/// Encodes the signers' nonces as the RFC's `encode_group_commitment_list` does.
+///
+/// Each signer is encoded as its 32-byte identifier followed by its two compressed nonce points.
+pub fn encode_group_commitment_list(nonces: &BTreeMap<PartyIndex, Nonce>) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(nonces.len() * (32 + 33 + 33));
+    for (index, nonce) in nonces {
+        encoded.extend_from_slice(&index.to_bytes());
+        encoded.extend_from_slice(&nonce.0[0].to_bytes());
+        encoded.extend_from_slice(&nonce.0[1].to_bytes());
+    }
+    encoded
+}
+
+/// Generates a signer's nonce as the RFC's `nonce_generate` does from 32 random bytes and their
+/// secret share.
+pub fn gen_nonce<H: Digest<OutputSize = U32> + BlockSizeUser>(
+    secret_share: &Scalar,
+    rng: &mut impl RngCore,
+) -> NonceKeyPair {
+    let secret = secret_share.to_secret_bytes();
+    let [hiding, binding] = [(); 2].map(|_| {
+        let mut random_bytes = [0u8; 32];
+        rng.fill_bytes(&mut random_bytes);
+        hash_to_field::<H>(b"nonce", &[&random_bytes, secret.expose_secret()])
+            .secret()
+            .non_zero()
+            .expect("computationally unreachable")
+    });
+    NonceKeyPair::from_secrets([hiding, binding])
+}
+
+/// Computes the RFC's challenge `H2(SerializeElement(R) || SerializePublicKey(public_key) || msg)`.
+pub fn challenge<H: Digest<OutputSize = U32> + BlockSizeUser>(
+    R: &Point,
+    public_key: &Point,
+    message: Message,
+) -> Scalar<Public, Zero> {
+    let mut input = Vec::with_capacity(33 + 33 + message.len());
+    input.extend_from_slice(&R.to_bytes());
+    input.extend_from_slice(&public_key.to_bytes());
+    if let Some(app_tag) = message.app_tag {
+        let mut padded_tag = [0u8; 64];
+        padded_tag[..app_tag.len()].copy_from_slice(app_tag.as_bytes());
+        input.extend_from_slice(&padded_tag);
+    }
+    input.extend_from_slice(message.bytes.as_inner());
+    hash_to_field::<H>(b"chal", &[&input])
+}
+
+/// A signature in the format of the RFC.
+///
+/// Unlike a [BIP340] signature the group commitment `R` may have an odd y-coordinate.
+///
+/// [BIP340]: https://bips.xyz/340
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct Signature {
+    /// The group commitment.
+    pub R: Point,
+    /// The aggregate response.
+    pub z: Scalar<Public, Zero>,
+}
+
+impl Signature {
+    /// Serializes the signature as the compressed `R` followed by `z`.
+    pub fn to_bytes(&self) -> [u8; 65] {
+        let mut bytes = [0u8; 65];
+        bytes[..33].copy_from_slice(&self.R.to_bytes());
+        bytes[33..].copy_from_slice(&self.z.to_bytes());
+        bytes
+    }
+
+    /// Deserializes a signature from the output of [`to_bytes`](Self::to_bytes).
+    ///
+    /// Returns `None` if `R` isn't a valid point or `z` isn't less than the curve order.
+    pub fn from_bytes(bytes: [u8; 65]) -> Option<Self> {
+        let R = Point::from_slice(&bytes[..33])?;
+        let z = Scalar::from_slice(&bytes[33..])?;
+        Some(Self { R, z })
+    }
+}
+
+/// A signing session of the RFC's ciphersuite.
+///
+/// Created with [`start_sign_session`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct SignSession {
+    binding_factors: BTreeMap<PartyIndex, Scalar<Public, Zero>>,
+    group_commitment: Point,
+    challenge: Scalar<Public, Zero>,
+    nonces: BTreeMap<PartyIndex, Nonce>,
+}
+
+impl SignSession {
+    /// The participant indices of the signers.
+    pub fn participants(&self) -> impl DoubleEndedIterator<Item = PartyIndex> + '_ {
+        self.nonces.keys().copied()
+    }
+
+    /// The binding factor of each signer.
+    pub fn binding_factors(&self) -> &BTreeMap<PartyIndex, Scalar<Public, Zero>> {
+        &self.binding_factors
+    }
+
+    /// The group commitment `R`.
+    pub fn group_commitment(&self) -> Point {
+        self.group_commitment
+    }
+}
+
+/// Starts a signing session of the RFC's ciphersuite.
+///
+/// Like [`Frost::start_sign_session`] all signers must agree on the `nonces` beforehand.
+///
+/// # Panics
+///
+/// If the number of nonces is less than the threshold.
+///
+/// [`Frost::start_sign_session`]: super::Frost::start_sign_session
+pub fn start_sign_session<H: Digest<OutputSize = U32> + BlockSizeUser>(
+    frost_key: &FrostKey<Normal>,
+    nonces: BTreeMap<PartyIndex, Nonce>,
+    message: Message,
+) -> SignSession {
+    if nonces.len() < frost_key.threshold() {
+        panic!("nonces' length was less than the threshold");
+    }
+    let binding_factors = binding_factors::<H>(frost_key.public_key(), &nonces, message);
+    let group_commitment = signing::rfc9591_group_commitment(
+        nonces
+            .iter()
+            .map(|(index, nonce)| (*nonce, binding_factors[index])),
+    )
+    .normalize()
+    .non_zero()
+    // the RFC would fail here but this is computationally unreachable
+    .unwrap_or(Point::generator());
+    let challenge = challenge::<H>(&group_commitment, &frost_key.public_key(), message);
+    SignSession {
+        binding_factors,
+        group_commitment,
+        challenge,
+        nonces,
+    }
+}
+
+/// Creates the signature share of the signer at `my_index`.
+///
+/// # Panics
+///
+/// If `secret_nonce` doesn't match the signer's nonce in the `session`.
+pub fn sign(
+    session: &SignSession,
+    my_index: PartyIndex,
+    secret_share: &Scalar,
+    secret_nonce: NonceKeyPair,
+) -> Scalar<Public, Zero> {
+    assert_eq!(
+        *session
+            .nonces
+            .get(&my_index)
+            .expect("my_index was not in session"),
+        secret_nonce.public(),
+        "secret nonce didn't match previously provided public nonce"
+    );
+    let lambda = poly::eval_basis_poly_at_0(my_index, session.nonces.keys());
+    // the RFC's keys and group commitments are never negated
+    signing::sign(
+        &session.challenge,
+        &session.binding_factors[&my_index],
+        lambda,
+        false,
+        false,
+        secret_share,
+        secret_nonce,
+    )
+}
+
+/// Verifies the signature share of the signer at `index`.
+///
+/// # Panics
+///
+/// If `index` is not part of the `session`.
+pub fn verify_signature_share(
+    frost_key: &FrostKey<Normal>,
+    session: &SignSession,
+    index: PartyIndex,
+    signature_share: Scalar<Public, Zero>,
+) -> bool {
+    let lambda = poly::eval_basis_poly_at_0(index, session.nonces.keys());
+    let nonce = session
+        .nonces
+        .get(&index)
+        .expect("verifying party index that is not part of frost signing coalition");
+    signing::verify(
+        &session.challenge,
+        &session.binding_factors[&index],
+        lambda,
+        false,
+        false,
+        &frost_key.verification_share(index),
+        nonce,
+        signature_share,
+    )
+}
+
+/// Combines the signature shares into a [`Signature`].
+///
+/// Like [`Frost::combine_signature_shares`] this doesn't check the shares.
+///
+/// [`Frost::combine_signature_shares`]: super::Frost::combine_signature_shares
+pub fn combine_signature_shares(
+    frost_key: &FrostKey<Normal>,
+    session: &SignSession,
+    signature_shares: Vec<Scalar<Public, Zero>>,
+) -> Signature {
+    Signature {
+        R: session.group_commitment,
+        z: signing::combine(&session.challenge, &frost_key.tweak, signature_shares),
+    }
+}
+
+/// Verifies a [`Signature`] on `message` under `public_key` as the RFC does.
+pub fn verify<H: Digest<OutputSize = U32> + BlockSizeUser>(
+    public_key: &Point,
+    message: Message,
+    signature: &Signature,
+) -> bool {
+    let Signature { R, z } = signature;
+    let c = challenge::<H>(R, public_key, message);
+    g!(R + c * public_key - z * G).is_zero()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frost;
+    use alloc::vec;
+    use secp256kfun::{hash::HashAdd, hex, s};
+    use sha2::Sha256;
+
+    fn scalar(hex: &str) -> Scalar<Public, Zero> {
+        Scalar::from_bytes(hex::decode_array(hex).unwrap()).unwrap()
+    }
+
+    // from the FROST(secp256k1, SHA-256) test vectors in RFC 9591 appendix E.5
+    #[test]
+    fn rfc9591_test_vectors() {
+        let participant_share =
+            scalar("08f89ffe80ac94dcb920c26f3f46140bfc7f95b493f8310f5fc1ea2b01f4254c");
+        let nonces = [
+            (
+                "7ea5ed09af19f6ff21040c07ec2d2adbd35b759da5a401d4c99dd26b82391cb2",
+                "841d3a6450d7580b4da83c8e618414d0f024391f2aeb511d7579224420aa81f0",
+                "03c699af97d26bb4d3f05232ec5e1938c12f1e6ae97643c8f8f11c9820303f1904",
+            ),
+            (
+                "47acab018f116020c10cb9b9abdc7ac10aae1b48ca6e36dc15acb6ec9be5cdc5",
+                "8d2624f532af631377f33cf44b5ac5f849067cae2eacb88680a31e77c79b5a80",
+                "02fa2aaccd51b948c9dc1a325d77226e98a5a3fe65fe9ba213761a60123040a45e",
+            ),
+        ];
+        for (randomness, nonce, commitment) in nonces {
+            let randomness = hex::decode_array::<32>(randomness).unwrap();
+            let derived =
+                hash_to_field::<Sha256>(b"nonce", &[&randomness, &participant_share.to_bytes()]);
+            assert_eq!(derived, scalar(nonce));
+            assert_eq!(
+                hex::encode(&g!(derived * G).normalize().to_bytes()),
+                commitment
+            );
+        }
+
+        let H4 = Sha256::new()
+            .add(CONTEXT_STRING)
+            .add(b"msg")
+            .add(Message::<Public>::raw(b"test"))
+            .finalize();
+        let binding_factor_input = hex::decode(
+            "02f37c34b66ced1fb51c34a90bdae006901f10625cc06c4f64663b0eae87d87b4fff9b5210ffbb3c07a73a7c8935be4a8c62cf015f6cf7ade6efac09a6513540fc3f5a816aaebc2114a811a415d7a55db7c5cbc1cf27183e79dd9def941b5d48010000000000000000000000000000000000000000000000000000000000000001",
+        )
+        .unwrap();
+        assert_eq!(H4.as_slice(), &binding_factor_input[33..65]);
+        let rho_1 = scalar("3e08fe561e075c653cbfd46908a10e7637c70c74f0a77d5fd45d1a750c739ec6");
+        assert_eq!(
+            hash_to_field::<Sha256>(b"rho", &[&binding_factor_input]),
+            rho_1
+        );
+
+        // the group key from the dealer's polynomial
+        let group_secret_key =
+            scalar("0d004150d27c3bf2a42f312683d35fac7394b1e9e318249c1bfe7f0795a83114");
+        let coefficient =
+            scalar("fbf85eadae3058ea14f19148bb72b45e4399c0b16028acaf0395c9b03c823579");
+        let frost_key = FrostKey::from_point_polynomial(vec![
+            g!(group_secret_key * G).normalize(),
+            g!(coefficient * G).normalize(),
+        ])
+        .unwrap();
+        assert_eq!(
+            hex::encode(&frost_key.public_key().to_bytes()),
+            "02f37c34b66ced1fb51c34a90bdae006901f10625cc06c4f64663b0eae87d87b4f"
+        );
+        let (p1, p3) = (s!(1).public(), s!(3).public());
+        let share_3 = scalar("00e95d59dd0d46b0e303e500b62b7ccb0e555d49f5b849f5e748c071da8c0dbc");
+        assert_eq!(frost_key.verification_share(p1), g!(participant_share * G));
+        assert_eq!(frost_key.verification_share(p3), g!(share_3 * G));
+
+        let expected_signature = Signature::from_bytes(hex::decode_array(
+            "0205b6d04d3774c8929413e3c76024d54149c372d57aae62574ed74319b5ea14d0c65dde8492a7471437e6c2fe3da49b90d23f642b5c6dbe7e36089f096dd97324",
+        ).unwrap())
+        .unwrap();
+        let group_commitment = expected_signature.R;
+        let message = Message::<Public>::raw(b"test");
+        let c = challenge::<Sha256>(&group_commitment, &frost_key.public_key(), message);
+        assert_eq!(
+            c,
+            scalar("7af30c3d74ab6346121058e2ad02da1803829fb1d0b9a95d12cfea8031ebb26d")
+        );
+
+        // the signature shares. We only have P1's nonces so P3 is checked against its part of the
+        // group commitment.
+        let [(_, hiding, _), (_, binding, _)] = nonces;
+        let nonce_1 = NonceKeyPair::from_secrets(
+            [scalar(hiding), scalar(binding)].map(|nonce| nonce.secret().non_zero().unwrap()),
+        );
+        let session = SignSession {
+            binding_factors: BTreeMap::from_iter([(p1, rho_1)]),
+            group_commitment,
+            challenge: c,
+            // P3's entry is only used for P1's lagrange coefficient
+            nonces: BTreeMap::from_iter([(p1, nonce_1.public()), (p3, nonce_1.public())]),
+        };
+        let share_1 = scalar("c4fce1775a1e141fb579944166eab0d65eefe7b98d480a569bbbfcb14f91c197");
+        let signature_share_1 = sign(
+            &session,
+            p1,
+            &participant_share.secret().non_zero().unwrap(),
+            nonce_1.clone(),
+        );
+        assert_eq!(signature_share_1, share_1);
+        assert!(verify_signature_share(&frost_key, &session, p1, share_1));
+
+        let share_3_sig =
+            scalar("0160fd0d388932f4826d2ebcd6b9eaba734f7c71cf25b4279a4ca2581e47b18d");
+        let [D_1, E_1] = nonce_1.public().0;
+        let lambda_3 = poly::eval_basis_poly_at_0(p3, [p1, p3].iter());
+        assert_eq!(
+            g!(share_3_sig * G),
+            g!(group_commitment - (D_1 + rho_1 * E_1)
+                + (lambda_3 * c) * { frost_key.verification_share(p3) })
+        );
+
+        let signature = combine_signature_shares(&frost_key, &session, vec![share_1, share_3_sig]);
+        assert_eq!(signature, expected_signature);
+        assert!(verify::<Sha256>(
+            &frost_key.public_key(),
+            message,
+            &signature
+        ));
+    }
+
+    #[test]
+    fn rfc9591_signing() {
+        let frost = frost::new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, secret_shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        // the RFC doesn't care about the parity of the key or R
+        let frost_key = frost_key
+            .tweak(Scalar::random(&mut rand::thread_rng()))
+            .unwrap();
+        let message = Message::<Public>::raw(b"test");
+        let signers = secret_shares
+            .into_iter()
+            .skip(1)
+            .collect::<BTreeMap<_, _>>();
+        let nonces = signers
+            .iter()
+            .map(|(index, secret_share)| {
+                (
+                    *index,
+                    gen_nonce::<Sha256>(secret_share, &mut rand::thread_rng()),
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        let session = start_sign_session::<Sha256>(
+            &frost_key,
+            nonces
+                .iter()
+                .map(|(index, nonce)| (*index, nonce.public()))
+                .collect(),
+            message,
+        );
+        let mut shares = vec![];
+        for (index, secret_share) in &signers {
+            let share = sign(&session, *index, secret_share, nonces[index].clone());
+            assert!(verify_signature_share(&frost_key, &session, *index, share));
+            assert!(!verify_signature_share(
+                &frost_key,
+                &session,
+                *index,
+                s!(share + 1).public()
+            ));
+            shares.push(share);
+        }
+        let signature = combine_signature_shares(&frost_key, &session, shares);
+        let public_key = frost_key.public_key();
+        assert!(verify::<Sha256>(&public_key, message, &signature));
+        assert!(!verify::<Sha256>(
+            &public_key,
+            Message::raw(b"tset"),
+            &signature
+        ));
+        assert_eq!(Signature::from_bytes(signature.to_bytes()), Some(signature));
+    }
+}
@@ -0,0 +1,426 @@
+//! A self-describing envelope for asking air-gapped FROST signers to sign.
+//!
+//! This crate doesn't parse transactions so a signer is only ever given a 32-byte message hash.
+//! On its own that doesn't let a signer show a human what they are approving. A [`SignRequest`]
+//! carries everything else a signer needs to decide:
+//!
+//! - the message hash and the [`SignScheme`] it is signed with,
+//! - the chain of [`KeyTweak`]s from the FROST key to the key that signs (e.g. a BIP32 derivation
+//!   followed by a taproot tweak) so the signer can check and display the key,
+//! - free form policy metadata (e.g. `"wallet" => "vault"`, `"amount" => "0.5 BTC"`),
+//! - the hash of a human readable description which is sent alongside the request so the signer
+//!   can check it with [`SignRequest::check_description`] before displaying it.
+//!
+//! The request has a canonical encoding ([`SignRequest::to_bytes`]) that is what gets moved
+//! between the coordinator and the signers (e.g. by QR code) and a canonical hash
+//! ([`SignRequest::id`]). Every signer that approves the same id is approving exactly the same
+//! thing. Using the id as the session id when seeding nonces (see [`Frost::seed_nonce_rng`])
+//! binds the nonces to the request.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     frost::{
+//!         self,
+//!         sign_request::{KeyTweak, SignRequest, SignScheme},
+//!     },
+//!     fun::{marker::*, Scalar},
+//! };
+//! use sha2::Sha256;
+//! let frost = frost::new_with_deterministic_nonces::<Sha256>();
+//! let (frost_key, _) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+//! let description = "Send 0.5 BTC to bc1q...";
+//! let mut request = SignRequest::new(
+//!     SignScheme::Bip340,
+//!     [42u8; 32],
+//!     SignRequest::description_hash::<Sha256>(description.as_bytes()),
+//! );
+//! request
+//!     .tweaks
+//!     .push(KeyTweak::Plain(Scalar::<Public, _>::from(7u32)));
+//! request
+//!     .policy
+//!     .insert("wallet".into(), "vault".into());
+//! let bytes = request.to_bytes();
+//!
+//! // on the air-gapped signer
+//! let request = SignRequest::from_bytes(&bytes).unwrap();
+//! assert!(request.check_description::<Sha256>(description.as_bytes()));
+//! let signing_key = request.apply_tweaks(frost_key.clone()).unwrap();
+//! // ...show the user the description, policy and signing key then sign `request.message()`
+//! // under `signing_key` with nonces seeded from `request.id::<Sha256>()`
+//! ```
+//!
+//! [`Frost::seed_nonce_rng`]: crate::frost::Frost::seed_nonce_rng
+use super::FrostKey;
+use crate::Message;
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    hash::{HashAdd, Tag},
+    marker::*,
+    Scalar,
+};
+
+/// How the message hash of a [`SignRequest`] is signed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+#[non_exhaustive]
+pub enum SignScheme {
+    /// A [BIP340] signature on the message hash as a raw message (e.g. a taproot sighash).
+    ///
+    /// [BIP340]: https://bips.xyz/340
+    Bip340,
+}
+
+impl SignScheme {
+    fn to_byte(self) -> u8 {
+        match self {
+            SignScheme::Bip340 => 0,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(SignScheme::Bip340),
+            _ => None,
+        }
+    }
+}
+
+/// A step from the FROST key to the key that signs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub enum KeyTweak {
+    /// A tweak applied with [`FrostKey::tweak`] before the key is made x-only (e.g. an unhardened
+    /// BIP32 derivation step).
+    Plain(Scalar<Public, Zero>),
+    /// A tweak applied with [`FrostKey::tweak`] after the key is made x-only (e.g. a taproot
+    /// tweak).
+    XOnly(Scalar<Public, Zero>),
+}
+
+/// Everything a signer needs to know to approve a signature.
+///
+/// See the [module documentation](crate::frost::sign_request).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct SignRequest {
+    /// How the message hash is signed.
+    pub scheme: SignScheme,
+    /// The 32-byte hash to sign.
+    pub message_hash: [u8; 32],
+    /// The tweaks from the FROST key to the signing key in the order they are applied.
+    pub tweaks: Vec<KeyTweak>,
+    /// Policy metadata for the signer to display or check.
+    pub policy: BTreeMap<String, String>,
+    /// The hash of the human readable description (see [`SignRequest::description_hash`]).
+    pub description_hash: [u8; 32],
+}
+
+impl SignRequest {
+    /// The most [`tweaks`](Self::tweaks) a request can have.
+    pub const MAX_TWEAKS: usize = 16;
+    /// The most [`policy`](Self::policy) entries a request can have.
+    pub const MAX_POLICY_ENTRIES: usize = 32;
+    /// The longest (in bytes) a policy key or value can be.
+    pub const MAX_POLICY_STRING_LEN: usize = 256;
+    /// The length of the longest encoding of a request that is [within the
+    /// limits](Self::is_within_limits).
+    ///
+    /// Network layers can use this to allocate a fixed buffer and drop anything longer unread.
+    pub const MAX_SIZE: usize = 1
+        + 32
+        + 4
+        + Self::MAX_TWEAKS * (1 + 32)
+        + 4
+        + Self::MAX_POLICY_ENTRIES * 2 * (4 + Self::MAX_POLICY_STRING_LEN)
+        + 32;
+
+    /// A request with no tweaks or policy metadata.
+    pub fn new(scheme: SignScheme, message_hash: [u8; 32], description_hash: [u8; 32]) -> Self {
+        Self {
+            scheme,
+            message_hash,
+            tweaks: Vec::new(),
+            policy: BTreeMap::new(),
+            description_hash,
+        }
+    }
+
+    /// The hash of a human readable description of what is being signed.
+    pub fn description_hash<H: Tag + Digest<OutputSize = U32> + Default>(
+        description: &[u8],
+    ) -> [u8; 32] {
+        H::default()
+            .tag(b"frost/sign-request/description")
+            .add(description)
+            .finalize()
+            .into()
+    }
+
+    /// Whether `description` is the one the request commits to.
+    pub fn check_description<H: Tag + Digest<OutputSize = U32> + Default>(
+        &self,
+        description: &[u8],
+    ) -> bool {
+        Self::description_hash::<H>(description) == self.description_hash
+    }
+
+    /// The canonical hash of the request.
+    pub fn id<H: Tag + Digest<OutputSize = U32> + Default>(&self) -> [u8; 32] {
+        H::default()
+            .tag(b"frost/sign-request")
+            .add(self.to_bytes().as_slice())
+            .finalize()
+            .into()
+    }
+
+    /// The message to sign.
+    pub fn message(&self) -> Message<'_, Public> {
+        match self.scheme {
+            SignScheme::Bip340 => Message::raw(&self.message_hash),
+        }
+    }
+
+    /// Applies the [`tweaks`](Self::tweaks) to `frost_key` to get the key that signs.
+    ///
+    /// Returns `None` if a [`KeyTweak::Plain`] comes after a [`KeyTweak::XOnly`] or a tweak
+    /// results in the zero point.
+    pub fn apply_tweaks(&self, frost_key: FrostKey<Normal>) -> Option<FrostKey<EvenY>> {
+        let n_plain = self
+            .tweaks
+            .iter()
+            .take_while(|tweak| matches!(tweak, KeyTweak::Plain(_)))
+            .count();
+        let (plain, xonly) = self.tweaks.split_at(n_plain);
+        let frost_key = plain.iter().try_fold(frost_key, |key, tweak| match tweak {
+            KeyTweak::Plain(tweak) => key.tweak(*tweak),
+            KeyTweak::XOnly(_) => unreachable!("only plain tweaks are before the split"),
+        })?;
+        xonly
+            .iter()
+            .try_fold(frost_key.into_xonly_key(), |key, tweak| match tweak {
+                KeyTweak::XOnly(tweak) => key.tweak(*tweak),
+                KeyTweak::Plain(_) => None,
+            })
+    }
+
+    /// Whether the request has no more than [`MAX_TWEAKS`] tweaks and [`MAX_POLICY_ENTRIES`]
+    /// policy entries each no longer than [`MAX_POLICY_STRING_LEN`].
+    ///
+    /// Only requests within the limits can be decoded with [`from_bytes`](Self::from_bytes).
+    ///
+    /// [`MAX_TWEAKS`]: Self::MAX_TWEAKS
+    /// [`MAX_POLICY_ENTRIES`]: Self::MAX_POLICY_ENTRIES
+    /// [`MAX_POLICY_STRING_LEN`]: Self::MAX_POLICY_STRING_LEN
+    pub fn is_within_limits(&self) -> bool {
+        self.tweaks.len() <= Self::MAX_TWEAKS
+            && self.policy.len() <= Self::MAX_POLICY_ENTRIES
+            && self.policy.iter().all(|(key, value)| {
+                key.len() <= Self::MAX_POLICY_STRING_LEN
+                    && value.len() <= Self::MAX_POLICY_STRING_LEN
+            })
+    }
+
+    /// The canonical encoding of the request.
+    ///
+    /// This is the scheme byte, the message hash, the number of tweaks (4 bytes big-endian)
+    /// followed by each tweak as a kind byte (`0` for plain and `1` for x-only) and its scalar,
+    /// the number of policy entries followed by each key and value (sorted by key) length
+    /// prefixed with 4 bytes and finally the description hash.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.push(self.scheme.to_byte());
+        bytes.extend_from_slice(&self.message_hash);
+        bytes.extend_from_slice(&(self.tweaks.len() as u32).to_be_bytes());
+        for tweak in &self.tweaks {
+            let (kind, scalar) = match tweak {
+                KeyTweak::Plain(scalar) => (0, scalar),
+                KeyTweak::XOnly(scalar) => (1, scalar),
+            };
+            bytes.push(kind);
+            bytes.extend_from_slice(&scalar.to_bytes());
+        }
+        bytes.extend_from_slice(&(self.policy.len() as u32).to_be_bytes());
+        for (key, value) in &self.policy {
+            for string in [key, value] {
+                bytes.extend_from_slice(&(string.len() as u32).to_be_bytes());
+                bytes.extend_from_slice(string.as_bytes());
+            }
+        }
+        bytes.extend_from_slice(&self.description_hash);
+        bytes
+    }
+
+    /// Decodes a request from its [canonical encoding](Self::to_bytes).
+    ///
+    /// Returns `None` if the bytes aren't exactly a canonical encoding (including if the policy
+    /// keys aren't sorted and unique) or the request isn't [within the
+    /// limits](Self::is_within_limits). Anything longer than [`MAX_SIZE`](Self::MAX_SIZE) is
+    /// rejected before it is parsed.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() > Self::MAX_SIZE {
+            return None;
+        }
+        let mut reader = Reader(bytes);
+        let scheme = SignScheme::from_byte(reader.take::<1>()?[0])?;
+        let message_hash = reader.take::<32>()?;
+        let n_tweaks = reader.take_len(Self::MAX_TWEAKS)?;
+        let tweaks = (0..n_tweaks)
+            .map(|_| {
+                let kind = reader.take::<1>()?[0];
+                let scalar = Scalar::from_bytes(reader.take::<32>()?)?;
+                match kind {
+                    0 => Some(KeyTweak::Plain(scalar)),
+                    1 => Some(KeyTweak::XOnly(scalar)),
+                    _ => None,
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let n_policy = reader.take_len(Self::MAX_POLICY_ENTRIES)?;
+        let mut policy = BTreeMap::new();
+        let mut last_key: Option<String> = None;
+        for _ in 0..n_policy {
+            let key = reader.take_string(Self::MAX_POLICY_STRING_LEN)?;
+            let value = reader.take_string(Self::MAX_POLICY_STRING_LEN)?;
+            if last_key.map_or(false, |last_key| last_key >= key) {
+                return None;
+            }
+            last_key = Some(key.clone());
+            policy.insert(key, value);
+        }
+        let description_hash = reader.take::<32>()?;
+        if !reader.0.is_empty() {
+            return None;
+        }
+        Some(Self {
+            scheme,
+            message_hash,
+            tweaks,
+            policy,
+            description_hash,
+        })
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        if self.0.len() < N {
+            return None;
+        }
+        let (taken, rest) = self.0.split_at(N);
+        self.0 = rest;
+        Some(taken.try_into().expect("N bytes"))
+    }
+
+    fn take_len(&mut self, max: usize) -> Option<usize> {
+        let len = u32::from_be_bytes(self.take::<4>()?) as usize;
+        // every item takes at least one byte so this stops huge allocations
+        if len > self.0.len() || len > max {
+            return None;
+        }
+        Some(len)
+    }
+
+    fn take_string(&mut self, max_len: usize) -> Option<String> {
+        let len = self.take_len(max_len)?;
+        let (string, rest) = self.0.split_at(len);
+        self.0 = rest;
+        String::from_utf8(string.to_vec()).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+
+    #[test]
+    fn sign_request_roundtrip_and_tweaks() {
+        let frost = crate::frost::new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, _) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        let mut request = SignRequest::new(
+            SignScheme::Bip340,
+            [1u8; 32],
+            SignRequest::description_hash::<Sha256>(b"pay alice"),
+        );
+        let plain = Scalar::<Public, _>::from(3u32);
+        let xonly = Scalar::<Public, _>::from(5u32);
+        request.tweaks = vec![KeyTweak::Plain(plain), KeyTweak::XOnly(xonly)];
+        request.policy.insert("wallet".into(), "vault".into());
+        request.policy.insert("amount".into(), "1000 sats".into());
+
+        let bytes = request.to_bytes();
+        assert_eq!(SignRequest::from_bytes(&bytes), Some(request.clone()));
+        assert_eq!(SignRequest::from_bytes(&bytes[..bytes.len() - 1]), None);
+        assert!(request.check_description::<Sha256>(b"pay alice"));
+        assert!(!request.check_description::<Sha256>(b"pay mallory"));
+
+        let signing_key = request.apply_tweaks(frost_key.clone()).unwrap();
+        let expected = frost_key
+            .clone()
+            .tweak(plain)
+            .unwrap()
+            .into_xonly_key()
+            .tweak(xonly)
+            .unwrap();
+        assert_eq!(signing_key.public_key(), expected.public_key());
+
+        let mut changed = request.clone();
+        changed.policy.insert("amount".into(), "2000 sats".into());
+        assert_ne!(changed.id::<Sha256>(), request.id::<Sha256>());
+
+        let mut out_of_order = request.clone();
+        out_of_order.tweaks.reverse();
+        assert!(out_of_order.apply_tweaks(frost_key).is_none());
+    }
+
+    #[test]
+    fn sign_request_limits() {
+        let mut request = SignRequest::new(SignScheme::Bip340, [1u8; 32], [2u8; 32]);
+        request.tweaks = vec![KeyTweak::Plain(Scalar::zero()); SignRequest::MAX_TWEAKS];
+        for i in 0..SignRequest::MAX_POLICY_ENTRIES {
+            let key = format!("{:0width$}", i, width = SignRequest::MAX_POLICY_STRING_LEN);
+            request.policy.insert(key.clone(), key);
+        }
+        assert!(request.is_within_limits());
+        let bytes = request.to_bytes();
+        assert_eq!(bytes.len(), SignRequest::MAX_SIZE);
+        assert_eq!(SignRequest::from_bytes(&bytes), Some(request.clone()));
+
+        let mut too_many_tweaks = request.clone();
+        too_many_tweaks.tweaks.push(KeyTweak::Plain(Scalar::zero()));
+        too_many_tweaks.policy.clear();
+        assert!(!too_many_tweaks.is_within_limits());
+        assert!(too_many_tweaks.to_bytes().len() < SignRequest::MAX_SIZE);
+        assert_eq!(SignRequest::from_bytes(&too_many_tweaks.to_bytes()), None);
+
+        let mut long_value = SignRequest::new(SignScheme::Bip340, [1u8; 32], [2u8; 32]);
+        long_value.policy.insert(
+            "memo".into(),
+            "x".repeat(SignRequest::MAX_POLICY_STRING_LEN + 1),
+        );
+        assert!(!long_value.is_within_limits());
+        assert_eq!(SignRequest::from_bytes(&long_value.to_bytes()), None);
+
+        let mut oversized = bytes;
+        oversized.push(0);
+        assert_eq!(SignRequest::from_bytes(&oversized), None);
+    }
+}
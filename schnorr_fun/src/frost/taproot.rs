@@ -0,0 +1,482 @@
+//! Taproot outputs for a FROST key with a script path fallback.
+//!
+//! A FROST key on its own is a single point of failure for liveness: if fewer than a threshold of
+//! parties remain the coins are stuck. [`spend_info`] puts the FROST key in the key path of a
+//! taproot output and commits to [`Fallback`] scripts in the script path, for example an n-of-n
+//! of every party or a recovery key that can spend on its own after a timelock.
+//!
+//! The keys the parties use in the fallback scripts must **not** come from their secret shares.
+//! Any threshold of parties can reconstruct every secret share so leaf keys derived from them
+//! would turn the n-of-n fallback back into a t-of-n. Instead each party brings a key `P_i` of
+//! their own that isn't shared with anyone (and that they have to back up separately from their
+//! share). Party `i`'s leaf key is
+//!
+//! ```text
+//! t_i = H("frost/taproot/leaf-key" || X || i || P_i)
+//! L_i = P_i + t_i * G
+//! ```
+//!
+//! where `X` is the FROST public key. The tweak means the same `P_i` can be used with many FROST
+//! keys without the leaf keys being linkable. Party `i` signs for it with [`leaf_keypair`] using
+//! the secret key of `P_i`. Anyone who knows the FROST key and every `P_i` can check the whole
+//! output including the fallback.
+//!
+//! The script tree is built from the fallbacks in the order they are given by pairing up
+//! adjacent nodes at each level (an odd node out is carried up to the next level). Everything is
+//! only compatible with [BIP341] if `H` is SHA256.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     frost::{
+//!         self,
+//!         taproot::{self, Fallback},
+//!     },
+//!     fun::{marker::*, Scalar},
+//!     Message,
+//! };
+//! use sha2::Sha256;
+//! use std::collections::BTreeMap;
+//! let frost = frost::new_with_deterministic_nonces::<Sha256>();
+//! let (frost_key, secret_shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+//! // each party's own key which has nothing to do with their share
+//! let party_secrets = secret_shares
+//!     .keys()
+//!     .map(|index| (*index, Scalar::random(&mut rand::thread_rng())))
+//!     .collect::<BTreeMap<_, _>>();
+//! let party_keys = party_secrets
+//!     .iter()
+//!     .map(|(index, secret)| (*index, frost.schnorr.new_keypair(*secret).public_key().normalize()))
+//!     .collect();
+//! let recovery_key = frost
+//!     .schnorr
+//!     .new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let spend_info = taproot::spend_info::<Sha256>(
+//!     &frost_key,
+//!     &party_keys,
+//!     &[
+//!         Fallback::NOfN,
+//!         Fallback::TimelockedRecovery {
+//!             recovery_key: recovery_key.public_key(),
+//!             blocks: 52_560,
+//!         },
+//!     ],
+//! );
+//! // sign on the key path with `spend_info.output_key` or on the script path with leaf keys:
+//! let (index, party_secret) = party_secrets.iter().next().unwrap();
+//! let leaf_keypair = taproot::leaf_keypair::<Sha256>(&frost_key, *index, party_secret);
+//! assert_eq!(leaf_keypair.public_key(), spend_info.leaf_keys[index]);
+//! ```
+//!
+//! [BIP341]: https://bips.xyz/341
+use super::{FrostKey, PartyIndex};
+use alloc::{collections::BTreeMap, vec::Vec};
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    g,
+    hash::{HashAdd, Tag},
+    marker::*,
+    s, KeyPair, Point, Scalar, G,
+};
+
+/// The leaf version of tapscript.
+pub const TAPSCRIPT_LEAF_VERSION: u8 = 0xc0;
+
+const OP_0: u8 = 0x00;
+const OP_1: u8 = 0x51;
+const OP_DROP: u8 = 0x75;
+const OP_CHECKSIGVERIFY: u8 = 0xad;
+const OP_CHECKSIG: u8 = 0xac;
+const OP_CHECKSEQUENCEVERIFY: u8 = 0xb2;
+
+/// A script path spending condition to commit to alongside the FROST key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fallback {
+    /// Every party signs with their leaf key.
+    ///
+    /// The script is `<L_1> OP_CHECKSIGVERIFY ... <L_n> OP_CHECKSIG` in order of party index so
+    /// the witness has the signatures in reverse order.
+    NOfN,
+    /// `recovery_key` can spend alone once the output is `blocks` blocks old.
+    ///
+    /// The script is `<blocks> OP_CHECKSEQUENCEVERIFY OP_DROP <recovery_key> OP_CHECKSIG`.
+    TimelockedRecovery {
+        /// The key that can spend after the timelock.
+        recovery_key: Point<EvenY>,
+        /// The relative timelock in blocks.
+        blocks: u16,
+    },
+}
+
+/// A leaf of the script tree along with what's needed to spend it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TapLeaf {
+    /// The fallback this leaf is for.
+    pub fallback: Fallback,
+    /// The tapscript of the leaf.
+    pub script: Vec<u8>,
+    /// The tapleaf hash of the script.
+    pub leaf_hash: [u8; 32],
+    /// The control block to put at the end of the witness when spending the leaf.
+    pub control_block: Vec<u8>,
+}
+
+/// Everything about a taproot output with a FROST key path and fallback script path.
+///
+/// Created with [`spend_info`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaprootSpendInfo {
+    /// The internal key: the FROST key as an x-only key.
+    pub internal_key: FrostKey<EvenY>,
+    /// The output key: the internal key tweaked with the script tree. Sign with this on the key
+    /// path. Its public key is what goes in the output.
+    pub output_key: FrostKey<EvenY>,
+    /// The root of the script tree or `None` if there were no fallbacks.
+    pub merkle_root: Option<[u8; 32]>,
+    /// The leaves of the script tree in the order of the fallbacks.
+    pub leaves: Vec<TapLeaf>,
+    /// The leaf key of each party.
+    pub leaf_keys: BTreeMap<PartyIndex, Point<EvenY>>,
+}
+
+fn leaf_key_tweak<H: Tag + Default + Digest<OutputSize = U32> + Clone>(
+    frost_key: &FrostKey<Normal>,
+    index: PartyIndex,
+    party_key: Point,
+) -> Scalar<Public, Zero> {
+    Scalar::from_hash(
+        H::default()
+            .tag(b"frost/taproot/leaf-key")
+            .add(frost_key.public_key())
+            .add(index)
+            .add(party_key),
+    )
+    .public()
+    .mark_zero()
+}
+
+/// The leaf key of party `index` whose own (unshared) key is `party_key`.
+///
+/// See the [module documentation](crate::frost::taproot) for how it's derived.
+pub fn leaf_key<H: Tag + Default + Digest<OutputSize = U32> + Clone>(
+    frost_key: &FrostKey<Normal>,
+    index: PartyIndex,
+    party_key: Point,
+) -> Point<EvenY> {
+    let tweak = leaf_key_tweak::<H>(frost_key, index, party_key);
+    g!(party_key + tweak * G)
+        .normalize()
+        .non_zero()
+        .expect("computationally unreachable")
+        .into_point_with_even_y()
+        .0
+}
+
+/// The keypair for party `index`'s leaf key from the secret key of their own key.
+///
+/// ⚠ `party_secret` must be a key only party `index` knows. Never pass your secret share.
+pub fn leaf_keypair<H: Tag + Default + Digest<OutputSize = U32> + Clone>(
+    frost_key: &FrostKey<Normal>,
+    index: PartyIndex,
+    party_secret: &Scalar,
+) -> KeyPair<EvenY> {
+    let party_key = g!(party_secret * G).normalize();
+    let tweak = leaf_key_tweak::<H>(frost_key, index, party_key);
+    let secret_key = s!(party_secret + tweak)
+        .non_zero()
+        .expect("computationally unreachable");
+    KeyPair::<EvenY>::new(secret_key)
+}
+
+/// Builds the taproot output for `frost_key` with `fallbacks` in the script path.
+///
+/// `party_keys` has the own key of each party that takes part in the fallbacks.
+pub fn spend_info<H: Tag + Default + Digest<OutputSize = U32> + Clone>(
+    frost_key: &FrostKey<Normal>,
+    party_keys: &BTreeMap<PartyIndex, Point>,
+    fallbacks: &[Fallback],
+) -> TaprootSpendInfo {
+    let leaf_keys = party_keys
+        .iter()
+        .map(|(index, party_key)| (*index, leaf_key::<H>(frost_key, *index, *party_key)))
+        .collect::<BTreeMap<_, _>>();
+    let scripts = fallbacks
+        .iter()
+        .map(|fallback| fallback_script(fallback, &leaf_keys))
+        .collect::<Vec<_>>();
+    let leaf_hashes = scripts
+        .iter()
+        .map(|script| tap_leaf_hash::<H>(script))
+        .collect::<Vec<_>>();
+
+    // each node of the current level is its hash and the leaves under it
+    let mut level = leaf_hashes
+        .iter()
+        .enumerate()
+        .map(|(i, leaf_hash)| (*leaf_hash, vec![i]))
+        .collect::<Vec<_>>();
+    let mut merkle_paths = vec![Vec::<[u8; 32]>::new(); leaf_hashes.len()];
+    while level.len() > 1 {
+        let mut next_level = Vec::with_capacity((level.len() + 1) / 2);
+        let mut nodes = level.into_iter();
+        while let Some((left, mut left_leaves)) = nodes.next() {
+            match nodes.next() {
+                Some((right, right_leaves)) => {
+                    for leaf in &left_leaves {
+                        merkle_paths[*leaf].push(right);
+                    }
+                    for leaf in &right_leaves {
+                        merkle_paths[*leaf].push(left);
+                    }
+                    left_leaves.extend(right_leaves);
+                    next_level.push((tap_branch_hash::<H>(left, right), left_leaves));
+                }
+                None => next_level.push((left, left_leaves)),
+            }
+        }
+        level = next_level;
+    }
+    let merkle_root = level.first().map(|(root, _)| *root);
+
+    let internal_key = frost_key.clone().into_xonly_key();
+    let mut tap_tweak_hash = H::default()
+        .tag(b"TapTweak")
+        .add(internal_key.public_key().to_xonly_bytes());
+    if let Some(merkle_root) = merkle_root {
+        tap_tweak_hash = tap_tweak_hash.add(merkle_root);
+    }
+    let tap_tweak = Scalar::<Secret, NonZero>::from_hash(tap_tweak_hash);
+    let output_key_is_odd = !g!({ internal_key.public_key() } + tap_tweak * G)
+        .normalize()
+        .non_zero()
+        .expect("computationally unreachable")
+        .is_y_even();
+    let output_key = internal_key
+        .clone()
+        .tweak(tap_tweak)
+        .expect("computationally unreachable");
+
+    let leaves = fallbacks
+        .iter()
+        .zip(scripts)
+        .zip(leaf_hashes)
+        .zip(merkle_paths)
+        .map(|(((fallback, script), leaf_hash), merkle_path)| {
+            let mut control_block = Vec::with_capacity(33 + 32 * merkle_path.len());
+            control_block.push(TAPSCRIPT_LEAF_VERSION | output_key_is_odd as u8);
+            control_block.extend(internal_key.public_key().to_xonly_bytes());
+            for node in merkle_path {
+                control_block.extend(node);
+            }
+            TapLeaf {
+                fallback: *fallback,
+                script,
+                leaf_hash,
+                control_block,
+            }
+        })
+        .collect();
+
+    TaprootSpendInfo {
+        internal_key,
+        output_key,
+        merkle_root,
+        leaves,
+        leaf_keys,
+    }
+}
+
+fn fallback_script(fallback: &Fallback, leaf_keys: &BTreeMap<PartyIndex, Point<EvenY>>) -> Vec<u8> {
+    let mut script = vec![];
+    match fallback {
+        Fallback::NOfN => {
+            for (i, leaf_key) in leaf_keys.values().enumerate() {
+                if i > 0 {
+                    script.push(OP_CHECKSIGVERIFY);
+                }
+                push_xonly_key(&mut script, leaf_key);
+            }
+            script.push(OP_CHECKSIG);
+        }
+        Fallback::TimelockedRecovery {
+            recovery_key,
+            blocks,
+        } => {
+            push_number(&mut script, *blocks);
+            script.push(OP_CHECKSEQUENCEVERIFY);
+            script.push(OP_DROP);
+            push_xonly_key(&mut script, recovery_key);
+            script.push(OP_CHECKSIG);
+        }
+    }
+    script
+}
+
+fn push_xonly_key(script: &mut Vec<u8>, key: &Point<EvenY>) {
+    script.push(32);
+    script.extend(key.to_xonly_bytes());
+}
+
+/// Pushes `n` as a minimally encoded script number.
+fn push_number(script: &mut Vec<u8>, n: u16) {
+    match n {
+        0 => script.push(OP_0),
+        1..=16 => script.push(OP_1 + (n as u8 - 1)),
+        _ => {
+            let mut bytes = n.to_le_bytes().to_vec();
+            if bytes[1] == 0 {
+                bytes.pop();
+            }
+            // the top bit is the sign bit
+            if bytes[bytes.len() - 1] & 0x80 != 0 {
+                bytes.push(0);
+            }
+            script.push(bytes.len() as u8);
+            script.extend(bytes);
+        }
+    }
+}
+
+fn tap_leaf_hash<H: Tag + Default + Digest<OutputSize = U32> + Clone>(script: &[u8]) -> [u8; 32] {
+    let mut hash = H::default().tag(b"TapLeaf");
+    hash.update([TAPSCRIPT_LEAF_VERSION]);
+    // compact size length prefix
+    let len = script.len();
+    if len < 0xfd {
+        hash.update([len as u8]);
+    } else {
+        hash.update([0xfd]);
+        hash.update((len as u16).to_le_bytes());
+    }
+    hash.update(script);
+    hash.finalize().into()
+}
+
+fn tap_branch_hash<H: Tag + Default + Digest<OutputSize = U32> + Clone>(
+    a: [u8; 32],
+    b: [u8; 32],
+) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    H::default()
+        .tag(b"TapBranch")
+        .add(left)
+        .add(right)
+        .finalize()
+        .into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{frost, Message};
+    use sha2::Sha256;
+
+    #[test]
+    fn script_numbers() {
+        let encode = |n| {
+            let mut script = vec![];
+            push_number(&mut script, n);
+            script
+        };
+        assert_eq!(encode(0), [0x00]);
+        assert_eq!(encode(16), [0x60]);
+        assert_eq!(encode(17), [0x01, 0x11]);
+        assert_eq!(encode(0x80), [0x02, 0x80, 0x00]);
+        assert_eq!(encode(144), [0x02, 0x90, 0x00]);
+        assert_eq!(encode(0x7fff), [0x02, 0xff, 0x7f]);
+        assert_eq!(encode(0xffff), [0x03, 0xff, 0xff, 0x00]);
+    }
+
+    #[test]
+    fn fallback_tree_is_consistent() {
+        let frost = frost::new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, secret_shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        let party_secrets = secret_shares
+            .keys()
+            .map(|index| (*index, Scalar::random(&mut rand::thread_rng())))
+            .collect::<BTreeMap<_, _>>();
+        let parties = party_secrets
+            .iter()
+            .map(|(index, secret)| (*index, g!(secret * G).normalize()))
+            .collect::<BTreeMap<_, _>>();
+        let recovery_key = frost
+            .schnorr
+            .new_keypair(Scalar::random(&mut rand::thread_rng()))
+            .public_key();
+        let recovery = Fallback::TimelockedRecovery {
+            recovery_key,
+            blocks: 1000,
+        };
+
+        let key_path_only = spend_info::<Sha256>(&frost_key, &parties, &[]);
+        assert_eq!(key_path_only.merkle_root, None);
+        assert!(key_path_only.leaves.is_empty());
+
+        for n_leaves in 1..=5 {
+            let fallbacks = (0..n_leaves)
+                .map(|i| match i % 2 {
+                    0 => Fallback::NOfN,
+                    _ => Fallback::TimelockedRecovery {
+                        recovery_key,
+                        blocks: i,
+                    },
+                })
+                .collect::<Vec<_>>();
+            let info = spend_info::<Sha256>(&frost_key, &parties, &fallbacks);
+            // it's deterministic
+            assert_eq!(info, spend_info::<Sha256>(&frost_key, &parties, &fallbacks));
+            let output_key = info.output_key.public_key();
+            for leaf in &info.leaves {
+                // walk the control block back up to the output key
+                let control_block = &leaf.control_block;
+                assert_eq!(control_block.len() % 32, 1);
+                let mut node = leaf.leaf_hash;
+                for sibling in control_block[33..].chunks(32) {
+                    node = tap_branch_hash::<Sha256>(node, sibling.try_into().unwrap());
+                }
+                assert_eq!(Some(node), info.merkle_root);
+                let internal_key =
+                    Point::<EvenY>::from_xonly_bytes(control_block[1..33].try_into().unwrap())
+                        .unwrap();
+                let tap_tweak = Scalar::<Secret, NonZero>::from_hash(
+                    Sha256::default()
+                        .tag(b"TapTweak")
+                        .add(internal_key.to_xonly_bytes())
+                        .add(node),
+                );
+                let expected = g!(internal_key + tap_tweak * G)
+                    .normalize()
+                    .non_zero()
+                    .unwrap();
+                assert_eq!(expected.to_xonly_bytes(), output_key.to_xonly_bytes());
+                assert_eq!(
+                    control_block[0],
+                    TAPSCRIPT_LEAF_VERSION | !expected.is_y_even() as u8
+                );
+            }
+        }
+
+        let spend_info = spend_info::<Sha256>(&frost_key, &parties, &[Fallback::NOfN, recovery]);
+        assert_eq!(
+            spend_info.leaves[1].script[..4],
+            [0x02, 0xe8, 0x03, OP_CHECKSEQUENCEVERIFY]
+        );
+        let n_of_n = &spend_info.leaves[0].script;
+        assert_eq!(n_of_n.len(), 3 * 33 + 3);
+        let message = Message::<Public>::plain("test", b"fallback");
+        for (index, party_secret) in &party_secrets {
+            let leaf_keypair = leaf_keypair::<Sha256>(&frost_key, *index, party_secret);
+            let leaf_key = spend_info.leaf_keys[index];
+            assert_eq!(leaf_keypair.public_key(), leaf_key);
+            // a threshold of parties can rebuild everyone's share but not their leaf keys
+            let from_share = KeyPair::<EvenY>::new(secret_shares[index]).public_key();
+            assert_ne!(leaf_key, from_share);
+            assert!(n_of_n
+                .windows(32)
+                .any(|window| window == leaf_key.to_xonly_bytes()));
+            let signature = frost.schnorr.sign(&leaf_keypair, message);
+            assert!(frost.schnorr.verify(&leaf_key, message, &signature));
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Weighted FROST where a party can hold several shares.
+//!
+//! In a weighted threshold scheme parties don't count equally: a board member with two votes
+//! should count as two signers. This is done with ordinary FROST by giving that party two share
+//! indices. The set of indices a party holds is their [`IndexBundle`] and its size is their
+//! weight. The threshold of the key is then a threshold of total weight.
+//!
+//! Key generation is run as usual with every index of every bundle as a party (each party creates
+//! the shares and proofs of possession for all of their indices). When signing a party generates a
+//! nonce for each of their indices with [`Frost::gen_bundle_nonces`] and all of them go into the
+//! session. [`Frost::sign_bundle`] then produces a single signature share for the whole bundle so
+//! the party still looks like one signer to the coordinator. The coordinator checks it with
+//! [`Frost::verify_bundle_signature_share`] and combines the bundle shares with
+//! [`Frost::combine_signature_shares`] like any others.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     frost::{self, weighted::IndexBundle},
+//!     Message,
+//! };
+//! let frost = frost::new_with_deterministic_nonces::<sha2::Sha256>();
+//! // alice has two votes, bob and carol have one each and three votes are needed
+//! let bundles = IndexBundle::assign(&[2, 1, 1]);
+//! let (frost_key, mut secret_shares) = frost.simulate_keygen(3, 4, &mut rand::thread_rng());
+//! let frost_key = frost_key.into_xonly_key();
+//! let (alice, bob) = (&bundles[0], &bundles[1]);
+//! let alice_shares = alice.take_shares(&mut secret_shares).unwrap();
+//! let bob_shares = bob.take_shares(&mut secret_shares).unwrap();
+//!
+//! let alice_nonces = frost.gen_bundle_nonces(alice, &mut rand::thread_rng());
+//! let bob_nonces = frost.gen_bundle_nonces(bob, &mut rand::thread_rng());
+//! let message = Message::plain("my-board", b"approve budget");
+//! let session = frost.start_sign_session(
+//!     &frost_key,
+//!     alice_nonces
+//!         .iter()
+//!         .chain(&bob_nonces)
+//!         .map(|(index, nonce)| (*index, nonce.public()))
+//!         .collect(),
+//!     message,
+//! );
+//! let alice_share = frost.sign_bundle(&frost_key, &session, &alice_shares, alice_nonces);
+//! let bob_share = frost.sign_bundle(&frost_key, &session, &bob_shares, bob_nonces);
+//! assert!(frost.verify_bundle_signature_share(&frost_key, &session, alice, alice_share));
+//! assert!(frost.verify_bundle_signature_share(&frost_key, &session, bob, bob_share));
+//! let signature =
+//!     frost.combine_signature_shares(&frost_key, &session, vec![alice_share, bob_share]);
+//! assert!(frost
+//!     .schnorr
+//!     .verify(&frost_key.public_key(), message, &signature));
+//! ```
+use super::{Frost, FrostKey, NonceKeyPair, PartyIndex, SignSession};
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    g,
+    marker::*,
+    poly,
+    rand_core::RngCore,
+    s, Point, Scalar, G,
+};
+
+/// The share indices held by a single party in weighted FROST.
+///
+/// See the [module documentation](crate::frost::weighted).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct IndexBundle {
+    indices: BTreeSet<PartyIndex>,
+}
+
+impl IndexBundle {
+    /// Creates a bundle of `indices`.
+    ///
+    /// Returns `None` if there are no indices.
+    pub fn new(indices: impl IntoIterator<Item = PartyIndex>) -> Option<Self> {
+        let indices = indices.into_iter().collect::<BTreeSet<_>>();
+        if indices.is_empty() {
+            return None;
+        }
+        Some(Self { indices })
+    }
+
+    /// Assigns consecutive indices `1, 2, ...` to parties with `weights`.
+    ///
+    /// The first party gets the first `weights[0]` indices and so on. The parties to pass to
+    /// key generation are the indices `1..=weights.iter().sum()`.
+    ///
+    /// ## Panics
+    ///
+    /// If a weight is zero.
+    pub fn assign(weights: &[u32]) -> Vec<Self> {
+        let mut next = 1u32;
+        weights
+            .iter()
+            .map(|weight| {
+                assert_ne!(*weight, 0, "a party must have a weight of at least one");
+                let indices = (next..next + weight).map(|index| {
+                    Scalar::<Public, Zero>::from(index)
+                        .non_zero()
+                        .expect("starts at one")
+                });
+                next += weight;
+                Self::new(indices).expect("weight is not zero")
+            })
+            .collect()
+    }
+
+    /// The indices in the bundle.
+    pub fn indices(&self) -> impl DoubleEndedIterator<Item = PartyIndex> + '_ {
+        self.indices.iter().copied()
+    }
+
+    /// The weight of the bundle (the number of indices it has).
+    pub fn weight(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Whether `index` is in the bundle.
+    pub fn contains(&self, index: PartyIndex) -> bool {
+        self.indices.contains(&index)
+    }
+
+    /// Removes the shares of the bundle's indices from `shares` and returns them.
+    ///
+    /// Useful for handing out the result of [`Frost::simulate_keygen`] to weighted parties. Returns
+    /// `None` (and leaves `shares` unchanged) if a share is missing.
+    pub fn take_shares(
+        &self,
+        shares: &mut BTreeMap<PartyIndex, Scalar>,
+    ) -> Option<BTreeMap<PartyIndex, Scalar>> {
+        if !self.indices().all(|index| shares.contains_key(&index)) {
+            return None;
+        }
+        Some(
+            self.indices()
+                .map(|index| (index, shares.remove(&index).expect("checked")))
+                .collect(),
+        )
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
+    /// Generates a nonce for each index of `bundle`.
+    ///
+    /// ⚠ Like [`Frost::gen_nonce`] this must only be used once and the rng must be chosen
+    /// carefully (see [`Frost::seed_nonce_rng`]).
+    pub fn gen_bundle_nonces<R: RngCore>(
+        &self,
+        bundle: &IndexBundle,
+        nonce_rng: &mut R,
+    ) -> BTreeMap<PartyIndex, NonceKeyPair> {
+        bundle
+            .indices()
+            .map(|index| (index, NonceKeyPair::random(nonce_rng)))
+            .collect()
+    }
+
+    /// Creates a single signature share for a bundle from the secret shares and secret nonces of
+    /// each of its indices.
+    ///
+    /// ## Panics
+    ///
+    /// If `secret_shares` and `secret_nonces` don't have the same indices or if a nonce doesn't
+    /// match the one in the `session` (like [`Frost::sign`]).
+    pub fn sign_bundle(
+        &self,
+        frost_key: &FrostKey<EvenY>,
+        session: &SignSession,
+        secret_shares: &BTreeMap<PartyIndex, Scalar>,
+        secret_nonces: BTreeMap<PartyIndex, NonceKeyPair>,
+    ) -> Scalar<Public, Zero> {
+        assert!(
+            secret_shares.keys().eq(secret_nonces.keys()),
+            "the secret shares and nonces must be for the same indices"
+        );
+        secret_nonces
+            .into_iter()
+            .map(|(index, secret_nonce)| {
+                let lambda = poly::eval_basis_poly_at_0(index, session.nonces.keys());
+                self._sign(
+                    frost_key,
+                    session,
+                    lambda,
+                    index,
+                    &secret_shares[&index],
+                    secret_nonce,
+                )
+            })
+            .fold(Scalar::zero(), |acc, share| s!(acc + share).public())
+    }
+
+    /// Verifies a signature share created by [`sign_bundle`] for `bundle`.
+    ///
+    /// ## Panics
+    ///
+    /// If an index of `bundle` is not part of the `session`.
+    ///
+    /// [`sign_bundle`]: Self::sign_bundle
+    pub fn verify_bundle_signature_share(
+        &self,
+        frost_key: &FrostKey<EvenY>,
+        session: &SignSession,
+        bundle: &IndexBundle,
+        signature_share: Scalar<Public, Zero>,
+    ) -> bool {
+        let c = &session.challenge;
+        let expected =
+            bundle
+                .indices()
+                .fold(Point::<NonNormal, Public, Zero>::zero(), |acc, index| {
+                    let mut lambda = poly::eval_basis_poly_at_0(index, session.nonces.keys());
+                    lambda.conditional_negate(frost_key.needs_negation);
+                    let b = &session.binding_coeffs[&index];
+                    let X = frost_key.verification_share(index);
+                    let [R1, R2] = session
+                        .nonces
+                        .get(&index)
+                        .expect("verifying party index that is not part of frost signing coalition")
+                        .0;
+                    let R1 = R1.conditional_negate(session.nonces_need_negation);
+                    let R2 = R2.conditional_negate(session.nonces_need_negation);
+                    g!(acc + R1 + b * R2 + (c * lambda) * X)
+                });
+        g!(expected - signature_share * G).is_zero()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Message;
+    use sha2::Sha256;
+
+    #[test]
+    fn weighted_signing() {
+        let frost = crate::frost::new_with_deterministic_nonces::<Sha256>();
+        let bundles = IndexBundle::assign(&[3, 1, 2]);
+        assert_eq!(
+            bundles.iter().map(IndexBundle::weight).collect::<Vec<_>>(),
+            vec![3, 1, 2]
+        );
+        let (frost_key, mut secret_shares) = frost.simulate_keygen(4, 6, &mut rand::thread_rng());
+        let frost_key = frost_key.into_xonly_key();
+        let shares = bundles
+            .iter()
+            .map(|bundle| bundle.take_shares(&mut secret_shares).unwrap())
+            .collect::<Vec<_>>();
+        assert!(secret_shares.is_empty());
+
+        // the parties with weights 3 and 1 sign
+        let message = Message::<Public>::plain("test", b"weighted");
+        let nonces = [&bundles[0], &bundles[1]]
+            .map(|bundle| frost.gen_bundle_nonces(bundle, &mut rand::thread_rng()));
+        let session = frost.start_sign_session(
+            &frost_key,
+            nonces
+                .iter()
+                .flatten()
+                .map(|(index, nonce)| (*index, nonce.public()))
+                .collect(),
+            message,
+        );
+        let [nonces0, nonces1] = nonces;
+        let share0 = frost.sign_bundle(&frost_key, &session, &shares[0], nonces0);
+        let share1 = frost.sign_bundle(&frost_key, &session, &shares[1], nonces1);
+        assert!(frost.verify_bundle_signature_share(&frost_key, &session, &bundles[0], share0));
+        assert!(frost.verify_bundle_signature_share(&frost_key, &session, &bundles[1], share1));
+        assert!(!frost.verify_bundle_signature_share(&frost_key, &session, &bundles[0], share1));
+        let signature = frost.combine_signature_shares(&frost_key, &session, vec![share0, share1]);
+        assert!(frost
+            .schnorr
+            .verify(&frost_key.public_key(), message, &signature));
+    }
+}
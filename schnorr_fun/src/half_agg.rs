@@ -0,0 +1,355 @@
+//! Non-interactive half-aggregation of BIP340 signatures.
+//!
+//! Anyone who has signatures `(R_i, s_i)` on messages `m_i` under keys `P_i` can replace them with
+//! a single [`HalfAggSig`] of the nonces `R_1, ..., R_u` and one scalar `s = Σ z_i * s_i`. That's
+//! `32 * u + 32` bytes rather than `64 * u`. The `z_i` are derived by hashing the signatures,
+//! keys and messages so that the aggregate can't be opened back up into individual signatures or
+//! rearranged. Verifying the aggregate is about as fast as batch verifying the signatures.
+//!
+//! The scheme follows the [half-aggregation BIP draft] but it is **not** compatible with it. The
+//! draft only has fixed 32-byte messages while here a [`Message`] can have any length (and an
+//! application tag) so each message is prefixed with its length when hashed into the `z_i`.
+//! Aggregates can be extended with more signatures with [`Schnorr::inc_aggregate`] without having
+//! the original signatures.
+//!
+//! ⚠ The aggregate only proves that the signatures existed; you can't get any one of them back
+//! out. Don't use it where a verifier needs an individual signature (e.g. to forward it).
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     fun::{marker::*, Scalar},
+//!     Message,
+//! };
+//! let schnorr = schnorr_fun::test_instance!();
+//! let keypairs = (0..3)
+//!     .map(|_| schnorr.new_keypair(Scalar::random(&mut rand::thread_rng())))
+//!     .collect::<Vec<_>>();
+//! let public_keys = keypairs.iter().map(|kp| kp.public_key()).collect::<Vec<_>>();
+//! let messages = [b"gossip 1", b"gossip 2", b"gossip 3"].map(|m| Message::<Public>::raw(m));
+//! let signatures = keypairs
+//!     .iter()
+//!     .zip(messages)
+//!     .map(|(keypair, message)| schnorr.sign(keypair, message))
+//!     .collect::<Vec<_>>();
+//! let aggregate = schnorr.aggregate(&public_keys, &messages, &signatures);
+//! assert_eq!(aggregate.to_bytes().len(), 32 * 3 + 32);
+//! assert!(schnorr.verify_half_agg(&public_keys, &messages, &aggregate));
+//! assert!(!schnorr.verify_half_agg(&public_keys[..2], &messages[..2], &aggregate));
+//! ```
+//!
+//! [half-aggregation BIP draft]: https://github.com/BlockstreamResearch/cross-input-aggregation/blob/master/half-aggregation.mediawiki
+use crate::{
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        hash::{HashAdd, Tag},
+        marker::*,
+        op, s, Point, Scalar, G,
+    },
+    Message, Schnorr, Signature,
+};
+use alloc::vec::Vec;
+
+/// The most signatures that can be aggregated together.
+pub const MAX_SIGNATURES: usize = 1 << 16;
+
+/// A half-aggregate of BIP340 signatures created with [`Schnorr::aggregate`].
+///
+/// See the [module documentation](crate::half_agg).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct HalfAggSig {
+    /// The nonces of the signatures in order.
+    pub Rs: Vec<Point<EvenY>>,
+    /// The aggregated `s` values.
+    pub s: Scalar<Public, Zero>,
+}
+
+impl HalfAggSig {
+    /// The aggregate of no signatures.
+    pub fn empty() -> Self {
+        Self {
+            Rs: Vec::new(),
+            s: Scalar::zero(),
+        }
+    }
+
+    /// The number of signatures in the aggregate.
+    pub fn len(&self) -> usize {
+        self.Rs.len()
+    }
+
+    /// Whether the aggregate contains no signatures.
+    pub fn is_empty(&self) -> bool {
+        self.Rs.is_empty()
+    }
+
+    /// Serializes the aggregate as the x-coordinates of the nonces followed by `s`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(32 * self.Rs.len() + 32);
+        for R in &self.Rs {
+            bytes.extend_from_slice(&R.to_xonly_bytes());
+        }
+        bytes.extend_from_slice(&self.s.to_bytes());
+        bytes
+    }
+
+    /// Deserializes an aggregate from [`to_bytes`](Self::to_bytes).
+    ///
+    /// Returns `None` if the length isn't a multiple of 32, a nonce isn't on the curve, `s` isn't
+    /// less than the curve order or there are more than [`MAX_SIGNATURES`] nonces.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() % 32 != 0 || bytes.is_empty() || bytes.len() / 32 - 1 > MAX_SIGNATURES {
+            return None;
+        }
+        let (nonces, s) = bytes.split_at(bytes.len() - 32);
+        let Rs = nonces
+            .chunks(32)
+            .map(|R| Point::<EvenY>::from_xonly_bytes(R.try_into().unwrap()))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self {
+            Rs,
+            s: Scalar::from_slice(s)?,
+        })
+    }
+}
+
+impl<NG, H: Digest<OutputSize = U32> + Tag + Default + Clone> Schnorr<H, NG> {
+    /// The randomizers `z_i` for each signature. The first is always 1.
+    ///
+    /// Messages are length prefixed since they don't have a fixed length.
+    fn half_agg_randomizers<'a, 'm: 'a>(
+        &self,
+        Rs: &[Point<EvenY>],
+        public_keys: impl IntoIterator<Item = &'a Point<EvenY>>,
+        messages: impl IntoIterator<Item = &'a Message<'m, Public>>,
+    ) -> Vec<Scalar<Public, Zero>> {
+        let mut hash = H::default().tag(b"HalfAgg/randomizer");
+        Rs.iter()
+            .zip(public_keys)
+            .zip(messages)
+            .enumerate()
+            .map(|(i, ((R, public_key), message))| {
+                hash = hash
+                    .clone()
+                    .add(R)
+                    .add(public_key)
+                    .add((message.len() as u64).to_be_bytes())
+                    .add(*message);
+                if i == 0 {
+                    Scalar::one().mark_zero()
+                } else {
+                    Scalar::from_hash(hash.clone()).public().mark_zero()
+                }
+            })
+            .collect()
+    }
+
+    /// Aggregates `signatures` on `messages` under `public_keys` into a [`HalfAggSig`].
+    ///
+    /// The signatures aren't checked. If any is invalid so is the aggregate. See the [module
+    /// documentation](crate::half_agg).
+    ///
+    /// # Panics
+    ///
+    /// If the slices have different lengths or there are more than [`MAX_SIGNATURES`].
+    pub fn aggregate(
+        &self,
+        public_keys: &[Point<EvenY>],
+        messages: &[Message<'_, Public>],
+        signatures: &[Signature],
+    ) -> HalfAggSig {
+        self.inc_aggregate(
+            &HalfAggSig::empty(),
+            &[],
+            &[],
+            public_keys,
+            messages,
+            signatures,
+        )
+        .expect("aggregating onto the empty aggregate")
+    }
+
+    /// Adds `signatures` to `aggregate` which is the aggregate of signatures on
+    /// `aggregated_messages` under `aggregated_public_keys`.
+    ///
+    /// The result is the same as aggregating all the signatures at once. Returns `None` if
+    /// `aggregate` has a different number of signatures than `aggregated_public_keys`.
+    ///
+    /// # Panics
+    ///
+    /// If the slices of keys, messages and signatures have different lengths or there would be
+    /// more than [`MAX_SIGNATURES`].
+    pub fn inc_aggregate(
+        &self,
+        aggregate: &HalfAggSig,
+        aggregated_public_keys: &[Point<EvenY>],
+        aggregated_messages: &[Message<'_, Public>],
+        public_keys: &[Point<EvenY>],
+        messages: &[Message<'_, Public>],
+        signatures: &[Signature],
+    ) -> Option<HalfAggSig> {
+        assert_eq!(aggregated_public_keys.len(), aggregated_messages.len());
+        assert!(public_keys.len() == messages.len() && messages.len() == signatures.len());
+        assert!(
+            aggregate.len() + signatures.len() <= MAX_SIGNATURES,
+            "too many signatures to aggregate"
+        );
+        if aggregate.len() != aggregated_public_keys.len() {
+            return None;
+        }
+        let mut Rs = aggregate.Rs.clone();
+        Rs.extend(signatures.iter().map(|signature| signature.R));
+        let zs = self.half_agg_randomizers(
+            &Rs,
+            aggregated_public_keys.iter().chain(public_keys),
+            aggregated_messages.iter().chain(messages),
+        );
+        let mut s = aggregate.s;
+        for (z, signature) in zs.into_iter().skip(aggregate.len()).zip(signatures) {
+            s = s!(s + z * signature.s).public();
+        }
+        Some(HalfAggSig { Rs, s })
+    }
+
+    /// Verifies a half-aggregate of signatures on `messages` under `public_keys`.
+    ///
+    /// The keys and messages must be in the same order as the signatures were aggregated.
+    #[must_use]
+    pub fn verify_half_agg(
+        &self,
+        public_keys: &[Point<EvenY>],
+        messages: &[Message<'_, Public>],
+        aggregate: &HalfAggSig,
+    ) -> bool {
+        let n = aggregate.len();
+        if public_keys.len() != n || messages.len() != n || n > MAX_SIGNATURES {
+            return false;
+        }
+        let zs = self.half_agg_randomizers(&aggregate.Rs, public_keys, messages);
+        let mut scalars = Vec::with_capacity(2 * n + 1);
+        let mut points = Vec::with_capacity(2 * n + 1);
+        // s * G = Σ z_i * (R_i + e_i * P_i)
+        for (z, ((R, public_key), message)) in zs
+            .into_iter()
+            .zip(aggregate.Rs.iter().zip(public_keys).zip(messages))
+        {
+            let e = self.challenge(R, public_key, *message);
+            scalars.push(z);
+            points.push(R.normalize());
+            scalars.push(s!(z * e).public());
+            points.push(public_key.normalize());
+        }
+        scalars.push(s!(-{ aggregate.s }).public());
+        points.push(G.normalize());
+        op::point_scalar_dot_product_vartime(&scalars, &points).is_zero()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fun::KeyPair;
+
+    fn signed(
+        n: usize,
+    ) -> (
+        Vec<Point<EvenY>>,
+        Vec<Message<'static, Public>>,
+        Vec<Signature>,
+    ) {
+        let schnorr = crate::test_instance!();
+        let messages = [b"zero", b"one!", b"two!", b"thre", b"four"]
+            .map(|m| Message::<Public>::raw(m))
+            .to_vec();
+        let keypairs = (0..n)
+            .map(|_| KeyPair::<EvenY>::new(Scalar::random(&mut rand::thread_rng())))
+            .collect::<Vec<_>>();
+        let signatures = keypairs
+            .iter()
+            .zip(&messages)
+            .map(|(keypair, message)| schnorr.sign(keypair, *message))
+            .collect();
+        (
+            keypairs.iter().map(|kp| kp.public_key()).collect(),
+            messages[..n].to_vec(),
+            signatures,
+        )
+    }
+
+    #[test]
+    fn aggregate_and_verify() {
+        let schnorr = crate::test_instance!();
+        let (public_keys, messages, signatures) = signed(5);
+        let aggregate = schnorr.aggregate(&public_keys, &messages, &signatures);
+        assert!(schnorr.verify_half_agg(&public_keys, &messages, &aggregate));
+        assert_eq!(
+            HalfAggSig::from_bytes(&aggregate.to_bytes()),
+            Some(aggregate.clone())
+        );
+
+        // incremental aggregation gives the same aggregate
+        let first = schnorr.aggregate(&public_keys[..2], &messages[..2], &signatures[..2]);
+        let rest = schnorr
+            .inc_aggregate(
+                &first,
+                &public_keys[..2],
+                &messages[..2],
+                &public_keys[2..],
+                &messages[2..],
+                &signatures[2..],
+            )
+            .unwrap();
+        assert_eq!(rest, aggregate);
+        assert_eq!(
+            schnorr.inc_aggregate(
+                &first,
+                &public_keys[..1],
+                &messages[..1],
+                &public_keys[2..],
+                &messages[2..],
+                &signatures[2..],
+            ),
+            None
+        );
+
+        // the order matters
+        let mut swapped_keys = public_keys.clone();
+        let mut swapped_messages = messages.clone();
+        swapped_keys.swap(1, 2);
+        swapped_messages.swap(1, 2);
+        assert!(!schnorr.verify_half_agg(&swapped_keys, &swapped_messages, &aggregate));
+        let mut swapped = aggregate.clone();
+        swapped.Rs.swap(1, 2);
+        assert!(!schnorr.verify_half_agg(&swapped_keys, &swapped_messages, &swapped));
+
+        let mut wrong_messages = messages.clone();
+        wrong_messages[4] = Message::<Public>::raw(b"five");
+        assert!(!schnorr.verify_half_agg(&public_keys, &wrong_messages, &aggregate));
+
+        // one invalid signature makes the aggregate invalid
+        let mut bad_signatures = signatures;
+        bad_signatures[3].s = s!({ bad_signatures[3].s } + 1).public();
+        let bad = schnorr.aggregate(&public_keys, &messages, &bad_signatures);
+        assert!(!schnorr.verify_half_agg(&public_keys, &messages, &bad));
+    }
+
+    #[test]
+    fn empty_and_single() {
+        let schnorr = crate::test_instance!();
+        let empty = HalfAggSig::empty();
+        assert!(schnorr.verify_half_agg(&[], &[], &empty));
+        assert_eq!(HalfAggSig::from_bytes(&empty.to_bytes()), Some(empty));
+        assert_eq!(HalfAggSig::from_bytes(&[]), None);
+
+        // the aggregate of one signature is the signature
+        let (public_keys, messages, signatures) = signed(1);
+        let aggregate = schnorr.aggregate(&public_keys, &messages, &signatures);
+        assert_eq!(aggregate.to_bytes(), signatures[0].to_bytes().to_vec());
+    }
+}
@@ -0,0 +1,77 @@
+//! ☢ Hazardous materials ☢
+//!
+//! Low-level signing functions that let the caller choose the nonce.
+//!
+//! **⚠ DO NOT USE THESE UNLESS YOU KNOW EXACTLY WHAT YOU ARE DOING ⚠**. If the same nonce is ever
+//! used to sign two different messages (or the same message under a different key or challenge),
+//! or if the nonce is even slightly biased or predictable, the secret key can be extracted from
+//! the resulting signatures. The ordinary [`Schnorr::sign`] derives nonces with a [`NonceGen`]
+//! precisely so you don't have to think about this.
+//!
+//! These exist for protocols where the nonce is produced externally (e.g. by an MPC protocol or a
+//! deterministic derivation that can't be expressed as a [`NonceGen`]).
+//!
+//! [`NonceGen`]: crate::nonce::NonceGen
+use crate::{
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        marker::*,
+        KeyPair, Scalar,
+    },
+    Message, Schnorr, Signature,
+};
+
+/// Create a BIP340 style Schnorr signature using `nonce` as the secret nonce.
+///
+/// The challenge is computed with the challenge hash of `schnorr`. If `nonce * G` has an odd
+/// y-coordinate the nonce is negated (as required by [BIP-340]) so the signature's `R` is always
+/// `±nonce * G`.
+///
+/// **⚠ The `nonce` must be uniformly random, secret and never used again. See the [module
+/// documentation](crate::hazmat).**
+///
+/// # Example
+///
+/// ```
+/// use schnorr_fun::{fun::{marker::*, Scalar}, hazmat, Message};
+/// # let schnorr = schnorr_fun::test_instance!();
+/// let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+/// let message = Message::<Public>::plain("my-app", b"nonce from somewhere else");
+/// // ⚠ this nonce came from an external protocol that guarantees it is fresh
+/// let nonce = Scalar::random(&mut rand::thread_rng());
+/// let signature = hazmat::sign_with_nonce(&schnorr, &keypair, nonce, message);
+/// assert!(schnorr.verify(&keypair.public_key(), message, &signature));
+/// ```
+///
+/// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+pub fn sign_with_nonce<CH, NG>(
+    schnorr: &Schnorr<CH, NG>,
+    keypair: &KeyPair<EvenY>,
+    nonce: Scalar,
+    message: Message<'_, impl Secrecy>,
+) -> Signature
+where
+    CH: Digest<OutputSize = U32> + Clone,
+{
+    schnorr.sign_with_nonce(keypair, nonce, message)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fun::{g, G};
+
+    #[test]
+    fn sign_with_external_nonce() {
+        let schnorr = crate::test_instance!();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let message = Message::<Public>::plain("test", b"hazmat");
+        let nonce = Scalar::random(&mut rand::thread_rng());
+        let signature = sign_with_nonce(&schnorr, &keypair, nonce, message);
+        assert!(schnorr.verify(&keypair.public_key(), message, &signature));
+        assert_eq!(
+            g!(nonce * G).normalize().to_xonly_bytes(),
+            signature.R.to_xonly_bytes()
+        );
+    }
+}
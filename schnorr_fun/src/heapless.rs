@@ -0,0 +1,18 @@
+//! FROST and MuSig signing without an allocator.
+//!
+//! The [`frost`](crate::frost) and [`musig`](crate::musig) modules keep keys, nonces and shares in
+//! `Vec`s and `BTreeMap`s so they need the `alloc` feature. The versions here use arrays whose
+//! lengths are const generics instead so that a signer with no allocator (e.g. a microcontroller
+//! in a hardware wallet) can run the whole signing path:
+//!
+//! - [`frost::FrostKey<T, THRESHOLD>`] holds the point polynomial of a key with threshold
+//!   `THRESHOLD` and [`frost::SignSession<N>`] the nonces of `N` signers.
+//! - [`musig::AggKey<T, N>`] is an aggregate of `N` keys and [`musig::SignSession<N>`] holds their
+//!   nonces.
+//!
+//! They compute exactly the same signature shares, partial signatures and signatures as the
+//! `alloc` versions so an embedded signer can sign with a coordinator that uses the `alloc` APIs.
+//! Key generation isn't covered: do it with the `alloc` APIs (or on a machine with an allocator)
+//! and load the resulting point polynomial onto the signer.
+pub mod frost;
+pub mod musig;
@@ -0,0 +1,623 @@
+//! FROST signing without an allocator.
+//!
+//! This is the signing half of [`crate::frost`] with the point polynomial of the key and the
+//! nonces of the signers kept in arrays. A [`FrostKey<T, THRESHOLD>`] has a `THRESHOLD` long point
+//! polynomial and a [`SignSession<N>`] is for `N` signers. Sessions are started with either the
+//! binding coefficient of [`crate::frost::Frost::start_sign_session`] or the per-signer binding
+//! factors of [`crate::frost::Frost::start_sign_session_rfc9591`]. The arithmetic is shared with
+//! [`crate::frost`] so the two compute the same sessions and signature shares.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     binonce::NonceKeyPair,
+//!     frost,
+//!     fun::{marker::*, nonce::Deterministic, Point, Scalar},
+//!     heapless::frost::{Frost, FrostKey},
+//!     Message,
+//! };
+//! use sha2::Sha256;
+//! let mut rng = rand::thread_rng();
+//! // key generation happens somewhere with an allocator
+//! let (alloc_key, shares) = frost::new_with_deterministic_nonces::<Sha256>()
+//!     .simulate_keygen(2, 3, &mut rng);
+//! let point_polynomial: [Point<Normal, Public, Zero>; 2] =
+//!     alloc_key.point_polynomial().try_into().unwrap();
+//!
+//! // and the signers only need the point polynomial and their share
+//! let frost = Frost::<Sha256, Deterministic<Sha256>>::default();
+//! let frost_key = FrostKey::from_point_polynomial(point_polynomial)
+//!     .unwrap()
+//!     .into_xonly_key();
+//! let signers = [1u32, 3].map(|i| {
+//!     let index = Scalar::<Public, Zero>::from(i).non_zero().unwrap();
+//!     (index, shares[&index], NonceKeyPair::random(&mut rand::thread_rng()))
+//! });
+//! let message = Message::<Public>::plain("my-app", b"hello");
+//! let session = frost.start_sign_session(
+//!     &frost_key,
+//!     signers.clone().map(|(index, _, nonce)| (index, nonce.public())),
+//!     message,
+//! );
+//! let signature_shares = signers.map(|(index, share, nonce)| {
+//!     let signature_share = frost.sign(&frost_key, &session, index, &share, nonce);
+//!     assert!(frost.verify_signature_share(&frost_key, &session, index, signature_share));
+//!     signature_share
+//! });
+//! let signature = frost.combine_signature_shares(&frost_key, &session, signature_shares);
+//! assert!(frost
+//!     .schnorr
+//!     .verify(&frost_key.public_key(), message, &signature));
+//! ```
+use crate::{
+    binonce::{Nonce, NonceKeyPair},
+    fun::{
+        digest::{crypto_common::BlockSizeUser, generic_array::typenum::U32, Digest},
+        g,
+        hash::Tag,
+        marker::*,
+        poly, s, Point, Scalar, G,
+    },
+    signing, Message, Schnorr, Signature,
+};
+
+/// The index of a party's share. The same as [`crate::frost::PartyIndex`].
+pub type PartyIndex = signing::PartyIndex;
+
+/// The FROST signing context.
+///
+/// It binds nonces the same way as [`crate::frost::Frost`] so the two can be used in the same
+/// signing session.
+#[derive(Clone)]
+pub struct Frost<H, NG> {
+    /// The instance of the Schnorr signature scheme.
+    pub schnorr: Schnorr<H, NG>,
+    /// The hash used to generate the nonce binding coefficient when signing.
+    binding_hash: H,
+}
+
+impl<H: Tag + Default, NG> Frost<H, NG> {
+    /// Creates the FROST context from a Schnorr context.
+    pub fn new(schnorr: Schnorr<H, NG>) -> Self {
+        Self {
+            schnorr,
+            binding_hash: H::default().tag(b"frost/binding"),
+        }
+    }
+}
+
+impl<H, NG> Default for Frost<H, NG>
+where
+    H: Default + Tag + Digest<OutputSize = U32>,
+    NG: Default + Tag,
+{
+    fn default() -> Self {
+        Frost::new(Schnorr::default())
+    }
+}
+
+/// A FROST key with a point polynomial of length `THRESHOLD`.
+///
+/// Like [`crate::frost::FrostKey`] but without a `Vec`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrostKey<T: PointType, const THRESHOLD: usize> {
+    /// The joint public key with the tweaks applied.
+    tweaked_public_key: Point<T>,
+    /// The public point polynomial that defines the access structure to the FROST key.
+    point_polynomial: [Point<Normal, Public, Zero>; THRESHOLD],
+    /// The tweak applied to this frost key, tracks the aggregate tweak.
+    tweak: Scalar<Public, Zero>,
+    /// Whether the secret keys need to be negated during signing (only used for EvenY keys).
+    needs_negation: bool,
+}
+
+impl<T: Copy + PointType, const THRESHOLD: usize> FrostKey<T, THRESHOLD> {
+    /// The public key with all tweaks applied
+    pub fn public_key(&self) -> Point<T> {
+        self.tweaked_public_key
+    }
+
+    /// The verification share of the party at `index` (the image of their secret share).
+    pub fn verification_share(&self, index: PartyIndex) -> Point<NonNormal, Public, Zero> {
+        poly::point::eval(&self.point_polynomial, index)
+    }
+
+    /// The threshold number of participants required in a signing coalition to produce a valid
+    /// signature.
+    pub fn threshold(&self) -> usize {
+        THRESHOLD
+    }
+
+    /// The public image of the key's polynomial on the elliptic curve.
+    pub fn point_polynomial(&self) -> [Point<Normal, Public, Zero>; THRESHOLD] {
+        self.point_polynomial
+    }
+}
+
+impl<const THRESHOLD: usize> FrostKey<Normal, THRESHOLD> {
+    /// Creates an untweaked key from its point polynomial.
+    ///
+    /// Returns `None` if the first coefficient is zero (or `THRESHOLD` is `0`).
+    pub fn from_point_polynomial(
+        point_polynomial: [Point<Normal, Public, Zero>; THRESHOLD],
+    ) -> Option<Self> {
+        let public_key = point_polynomial.first()?.non_zero()?;
+        Some(FrostKey {
+            tweaked_public_key: public_key,
+            point_polynomial,
+            tweak: Scalar::zero(),
+            needs_negation: false,
+        })
+    }
+
+    /// Converts the key into a BIP340 key. See [`crate::frost::FrostKey::into_xonly_key`].
+    pub fn into_xonly_key(self) -> FrostKey<EvenY, THRESHOLD> {
+        let (tweaked_public_key, needs_negation) = self.public_key().into_point_with_even_y();
+        let mut tweak = self.tweak;
+        tweak.conditional_negate(needs_negation);
+        FrostKey {
+            tweaked_public_key,
+            point_polynomial: self.point_polynomial,
+            tweak,
+            needs_negation,
+        }
+    }
+
+    /// Applies a plain tweak to the public key. See [`crate::frost::FrostKey::tweak`].
+    ///
+    /// Returns `None` if the tweak is the negation of the secret key.
+    pub fn tweak(self, tweak: Scalar<impl Secrecy, impl ZeroChoice>) -> Option<Self> {
+        let tweaked_public_key = g!(self.tweaked_public_key + tweak * G)
+            .normalize()
+            .non_zero()?;
+        let tweak = s!(self.tweak + tweak).public();
+
+        Some(FrostKey {
+            tweaked_public_key,
+            point_polynomial: self.point_polynomial,
+            tweak,
+            needs_negation: self.needs_negation,
+        })
+    }
+}
+
+impl<const THRESHOLD: usize> FrostKey<EvenY, THRESHOLD> {
+    /// Applies an "x-only" tweak to the public key e.g. a taproot commitment. See
+    /// [`crate::frost::FrostKey::tweak`].
+    ///
+    /// Returns `None` if the tweak is the negation of the secret key.
+    pub fn tweak(self, tweak: Scalar<impl Secrecy, impl ZeroChoice>) -> Option<Self> {
+        let (new_public_key, needs_negation) = g!(self.tweaked_public_key + tweak * G)
+            .normalize()
+            .non_zero()?
+            .into_point_with_even_y();
+        let mut new_tweak = s!(self.tweak + tweak).public();
+        new_tweak.conditional_negate(needs_negation);
+        let needs_negation = self.needs_negation ^ needs_negation;
+
+        Some(Self {
+            tweaked_public_key: new_public_key,
+            point_polynomial: self.point_polynomial,
+            needs_negation,
+            tweak: new_tweak,
+        })
+    }
+}
+
+/// Error returned when converting a [`crate::frost::FrostKey`] into a [`FrostKey`] with a
+/// different threshold.
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThresholdMismatch {
+    /// The threshold of the [`FrostKey`] type.
+    pub expected: usize,
+    /// The threshold of the key that was converted.
+    pub got: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl core::fmt::Display for ThresholdMismatch {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "expected a key with threshold {} but it had threshold {}",
+            self.expected, self.got
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ThresholdMismatch {}
+
+/// Keeps the tweaks of the key.
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl<T: Copy + PointType, const THRESHOLD: usize> TryFrom<&crate::frost::FrostKey<T>>
+    for FrostKey<T, THRESHOLD>
+{
+    type Error = ThresholdMismatch;
+
+    fn try_from(frost_key: &crate::frost::FrostKey<T>) -> Result<Self, Self::Error> {
+        let point_polynomial =
+            frost_key
+                .point_polynomial()
+                .try_into()
+                .map_err(|_| ThresholdMismatch {
+                    expected: THRESHOLD,
+                    got: frost_key.threshold(),
+                })?;
+        Ok(FrostKey {
+            tweaked_public_key: frost_key.public_key(),
+            point_polynomial,
+            tweak: frost_key.tweak,
+            needs_negation: frost_key.needs_negation,
+        })
+    }
+}
+
+/// A FROST signing session for `N` signers.
+///
+/// Created by [`Frost::start_sign_session`] or [`Frost::start_sign_session_rfc9591`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SignSession<const N: usize> {
+    nonces: [(PartyIndex, Nonce); N],
+    binding_coeffs: [Scalar<Public, Zero>; N],
+    agg_nonce: Point<EvenY>,
+    nonces_need_negation: bool,
+    challenge: Scalar<Public, Zero>,
+}
+
+impl<const N: usize> SignSession<N> {
+    /// The indices of the signers in the order they were given.
+    pub fn participants(&self) -> impl Iterator<Item = PartyIndex> + '_ {
+        self.nonces.iter().map(|(index, _)| *index)
+    }
+
+    /// The aggregate nonce (the `R` of the final signature).
+    pub fn agg_nonce(&self) -> Point<EvenY> {
+        self.agg_nonce
+    }
+
+    /// The nonce and binding coefficient of the signer at `index`.
+    fn signer(&self, index: PartyIndex) -> Option<(&Nonce, &Scalar<Public, Zero>)> {
+        let position = self
+            .nonces
+            .iter()
+            .position(|(signer, _)| *signer == index)?;
+        Some((&self.nonces[position].1, &self.binding_coeffs[position]))
+    }
+
+    fn lagrange_coefficient(&self, index: PartyIndex) -> Scalar<Public> {
+        poly::eval_basis_poly_at_0(index, self.nonces.iter().map(|(index, _)| index))
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
+    /// Starts a signing session with the public nonces of each signer.
+    ///
+    /// Every signer has to start it with the same nonces (in any order). The session is the same
+    /// as the one [`crate::frost::Frost::start_sign_session`] would start.
+    ///
+    /// # Panics
+    ///
+    /// If there are fewer than `THRESHOLD` signers or a signer is listed twice.
+    pub fn start_sign_session<const THRESHOLD: usize, const N: usize>(
+        &self,
+        frost_key: &FrostKey<EvenY, THRESHOLD>,
+        nonces: [(PartyIndex, Nonce); N],
+        message: Message,
+    ) -> SignSession<N> {
+        check_signers::<THRESHOLD, N>(&nonces);
+
+        let agg_nonce = Nonce::aggregate(nonces.iter().map(|(_, nonce)| *nonce));
+        let binding_coeff = signing::frost_binding_coeff(
+            &self.binding_hash,
+            &agg_nonce,
+            frost_key.public_key(),
+            message,
+        );
+        let agg_nonce = g!(agg_nonce.0[0] + binding_coeff * agg_nonce.0[1]);
+
+        self._start_sign_session(frost_key, nonces, message, agg_nonce, [binding_coeff; N])
+    }
+
+    /// Starts a signing session with a binding factor for each signer computed as in RFC 9591.
+    ///
+    /// The session is the same as the one [`crate::frost::Frost::start_sign_session_rfc9591`]
+    /// would start. All the signers need to use the same kind of session.
+    ///
+    /// # Panics
+    ///
+    /// If there are fewer than `THRESHOLD` signers or a signer is listed twice.
+    pub fn start_sign_session_rfc9591<const THRESHOLD: usize, const N: usize>(
+        &self,
+        frost_key: &FrostKey<EvenY, THRESHOLD>,
+        nonces: [(PartyIndex, Nonce); N],
+        message: Message,
+    ) -> SignSession<N>
+    where
+        H: BlockSizeUser,
+    {
+        check_signers::<THRESHOLD, N>(&nonces);
+
+        // the RFC encodes the nonces in the order of the signers' indices
+        let mut sorted_nonces = nonces;
+        sorted_nonces.sort_unstable_by_key(|(index, _)| *index);
+        let prefix =
+            signing::rfc9591_binding_prefix::<H>(frost_key.public_key(), sorted_nonces, message);
+        let binding_coeffs =
+            nonces.map(|(index, _)| signing::rfc9591_binding_factor::<H>(&prefix, index));
+        let agg_nonce = signing::rfc9591_group_commitment(
+            nonces
+                .iter()
+                .zip(&binding_coeffs)
+                .map(|((_, nonce), rho)| (*nonce, *rho)),
+        );
+
+        self._start_sign_session(frost_key, nonces, message, agg_nonce, binding_coeffs)
+    }
+
+    fn _start_sign_session<const THRESHOLD: usize, const N: usize>(
+        &self,
+        frost_key: &FrostKey<EvenY, THRESHOLD>,
+        nonces: [(PartyIndex, Nonce); N],
+        message: Message,
+        agg_nonce: Point<NonNormal, Public, Zero>,
+        binding_coeffs: [Scalar<Public, Zero>; N],
+    ) -> SignSession<N> {
+        let (agg_nonce, nonces_need_negation) = signing::session_nonce(agg_nonce);
+
+        let challenge = self
+            .schnorr
+            .challenge(&agg_nonce, &frost_key.public_key(), message);
+
+        SignSession {
+            nonces,
+            binding_coeffs,
+            agg_nonce,
+            nonces_need_negation,
+            challenge,
+        }
+    }
+
+    /// Creates a signature share with the secret share at `my_index`.
+    ///
+    /// # Panics
+    ///
+    /// If `my_index` isn't a signer in `session` or `secret_nonce` isn't the nonce it started the
+    /// session with.
+    pub fn sign<const THRESHOLD: usize, const N: usize>(
+        &self,
+        frost_key: &FrostKey<EvenY, THRESHOLD>,
+        session: &SignSession<N>,
+        my_index: PartyIndex,
+        secret_share: &Scalar,
+        secret_nonce: NonceKeyPair,
+    ) -> Scalar<Public, Zero> {
+        let (nonce, binding_coeff) = session
+            .signer(my_index)
+            .expect("my_index was not in session");
+        assert_eq!(
+            *nonce,
+            secret_nonce.public(),
+            "secret nonce didn't match previously provided public nonce"
+        );
+        signing::sign(
+            &session.challenge,
+            binding_coeff,
+            session.lagrange_coefficient(my_index),
+            frost_key.needs_negation,
+            session.nonces_need_negation,
+            secret_share,
+            secret_nonce,
+        )
+    }
+
+    /// Checks the signature share from the signer at `index`.
+    ///
+    /// # Panics
+    ///
+    /// If `index` isn't a signer in `session`.
+    #[must_use]
+    pub fn verify_signature_share<const THRESHOLD: usize, const N: usize>(
+        &self,
+        frost_key: &FrostKey<EvenY, THRESHOLD>,
+        session: &SignSession<N>,
+        index: PartyIndex,
+        signature_share: Scalar<Public, Zero>,
+    ) -> bool {
+        let (nonce, binding_coeff) = session
+            .signer(index)
+            .expect("verifying party index that is not part of frost signing coalition");
+        signing::verify(
+            &session.challenge,
+            binding_coeff,
+            session.lagrange_coefficient(index),
+            frost_key.needs_negation,
+            session.nonces_need_negation,
+            &frost_key.verification_share(index),
+            nonce,
+            signature_share,
+        )
+    }
+
+    /// Combines the signature shares into a signature.
+    ///
+    /// This doesn't check the signature shares. Check each of them with
+    /// [`verify_signature_share`](Self::verify_signature_share) or verify the signature.
+    pub fn combine_signature_shares<const THRESHOLD: usize, const N: usize>(
+        &self,
+        frost_key: &FrostKey<EvenY, THRESHOLD>,
+        session: &SignSession<N>,
+        signature_shares: [Scalar<Public, Zero>; N],
+    ) -> Signature {
+        Signature {
+            R: session.agg_nonce,
+            s: signing::combine(&session.challenge, &frost_key.tweak, signature_shares),
+        }
+    }
+}
+
+fn check_signers<const THRESHOLD: usize, const N: usize>(nonces: &[(PartyIndex, Nonce); N]) {
+    assert!(
+        N >= THRESHOLD,
+        "there were fewer signers than the threshold"
+    );
+    for (i, (index, _)) in nonces.iter().enumerate() {
+        assert!(
+            nonces[..i].iter().all(|(other, _)| other != index),
+            "signer {index} was listed twice",
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{frost as alloc_frost, fun::nonce::Deterministic};
+    use alloc::collections::BTreeMap;
+    use sha2::Sha256;
+
+    #[test]
+    fn matches_alloc_frost() {
+        let alloc_frost = alloc_frost::new_with_deterministic_nonces::<Sha256>();
+        let frost = Frost::<Sha256, Deterministic<Sha256>>::default();
+        let (alloc_key, shares) = alloc_frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        let alloc_key = alloc_key
+            .tweak(Scalar::random(&mut rand::thread_rng()))
+            .unwrap()
+            .into_xonly_key()
+            .tweak(Scalar::random(&mut rand::thread_rng()))
+            .unwrap();
+        let frost_key = FrostKey::<EvenY, 2>::try_from(&alloc_key).unwrap();
+        assert_eq!(frost_key.public_key(), alloc_key.public_key());
+        assert_eq!(
+            FrostKey::<EvenY, 3>::try_from(&alloc_key),
+            Err(ThresholdMismatch {
+                expected: 3,
+                got: 2
+            })
+        );
+
+        let message = Message::<Public>::plain("test", b"heapless");
+        let signers = [3, 1].map(|i| {
+            let index = Scalar::<Public, Zero>::from(i).non_zero().unwrap();
+            (
+                index,
+                shares[&index],
+                NonceKeyPair::random(&mut rand::thread_rng()),
+            )
+        });
+        let nonces = signers
+            .clone()
+            .map(|(index, _, nonce)| (index, nonce.public()));
+        let session = frost.start_sign_session(&frost_key, nonces, message);
+        let alloc_session = alloc_frost.start_sign_session(
+            &alloc_key,
+            nonces.into_iter().collect::<BTreeMap<_, _>>(),
+            message,
+        );
+
+        let signature_shares = signers.clone().map(|(index, share, nonce)| {
+            let signature_share = frost.sign(&frost_key, &session, index, &share, nonce.clone());
+            assert_eq!(
+                signature_share,
+                alloc_frost.sign(&alloc_key, &alloc_session, index, &share, nonce)
+            );
+            assert!(frost.verify_signature_share(&frost_key, &session, index, signature_share));
+            assert!(!frost.verify_signature_share(
+                &frost_key,
+                &session,
+                signers[0].0,
+                s!(signature_share + 1).public()
+            ));
+            signature_share
+        });
+        let signature = frost.combine_signature_shares(&frost_key, &session, signature_shares);
+        assert_eq!(
+            signature,
+            alloc_frost.combine_signature_shares(
+                &alloc_key,
+                &alloc_session,
+                signature_shares.to_vec()
+            )
+        );
+        assert!(frost
+            .schnorr
+            .verify(&frost_key.public_key(), message, &signature));
+    }
+
+    #[test]
+    fn matches_alloc_frost_rfc9591() {
+        let alloc_frost = alloc_frost::new_with_deterministic_nonces::<Sha256>();
+        let frost = Frost::<Sha256, Deterministic<Sha256>>::default();
+        let (alloc_key, shares) = alloc_frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        let alloc_key = alloc_key.into_xonly_key();
+        let frost_key = FrostKey::<EvenY, 2>::try_from(&alloc_key).unwrap();
+
+        let message = Message::<Public>::plain("test", b"heapless rfc9591");
+        // out of order so the nonces have to be sorted for the binding factors
+        let signers = [3, 1, 2].map(|i| {
+            let index = Scalar::<Public, Zero>::from(i).non_zero().unwrap();
+            (
+                index,
+                shares[&index],
+                NonceKeyPair::random(&mut rand::thread_rng()),
+            )
+        });
+        let nonces = signers
+            .clone()
+            .map(|(index, _, nonce)| (index, nonce.public()));
+        let session = frost.start_sign_session_rfc9591(&frost_key, nonces, message);
+        let alloc_session = alloc_frost.start_sign_session_rfc9591(
+            &alloc_key,
+            nonces.into_iter().collect::<BTreeMap<_, _>>(),
+            message,
+        );
+        assert_ne!(
+            session.agg_nonce(),
+            frost
+                .start_sign_session(&frost_key, nonces, message)
+                .agg_nonce()
+        );
+
+        let signature_shares = signers.clone().map(|(index, share, nonce)| {
+            let signature_share = frost.sign(&frost_key, &session, index, &share, nonce.clone());
+            assert_eq!(
+                signature_share,
+                alloc_frost.sign(&alloc_key, &alloc_session, index, &share, nonce)
+            );
+            assert!(frost.verify_signature_share(&frost_key, &session, index, signature_share));
+            signature_share
+        });
+        let signature = frost.combine_signature_shares(&frost_key, &session, signature_shares);
+        assert_eq!(
+            signature,
+            alloc_frost.combine_signature_shares(
+                &alloc_key,
+                &alloc_session,
+                signature_shares.to_vec()
+            )
+        );
+        assert!(frost
+            .schnorr
+            .verify(&frost_key.public_key(), message, &signature));
+    }
+
+    #[test]
+    #[should_panic(expected = "fewer signers than the threshold")]
+    fn too_few_signers() {
+        let frost = Frost::<Sha256, Deterministic<Sha256>>::default();
+        let (alloc_key, _) = alloc_frost::new_with_deterministic_nonces::<Sha256>()
+            .simulate_keygen(2, 3, &mut rand::thread_rng());
+        let frost_key = FrostKey::<EvenY, 2>::try_from(&alloc_key.into_xonly_key()).unwrap();
+        let nonce = NonceKeyPair::random(&mut rand::thread_rng()).public();
+        let _ = frost.start_sign_session(
+            &frost_key,
+            [(Scalar::<Public, Zero>::from(1).non_zero().unwrap(), nonce)],
+            Message::<Public>::raw(b"test"),
+        );
+    }
+}
@@ -0,0 +1,392 @@
+//! MuSig2 signing without an allocator.
+//!
+//! This is [`crate::musig`] with the keys and nonces of the `N` signers kept in arrays. An
+//! [`AggKey<T, N>`] is the aggregate of `N` keys and a [`SignSession<N>`] holds their nonces. Only
+//! ordinary (not adaptor) signing sessions are supported. The arithmetic is shared with
+//! [`crate::musig`] so the two compute the same keys, sessions and partial signatures.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     binonce::NonceKeyPair,
+//!     fun::{marker::*, nonce::Deterministic, KeyPair, Scalar},
+//!     heapless::musig::MuSig,
+//!     Message,
+//! };
+//! use sha2::Sha256;
+//! let musig = MuSig::<Sha256, Deterministic<Sha256>>::default();
+//! let keypairs = [(); 3].map(|_| KeyPair::<Normal>::new(Scalar::random(&mut rand::thread_rng())));
+//! let agg_key = musig
+//!     .new_agg_key(keypairs.clone().map(|keypair| keypair.public_key()))
+//!     .into_xonly_key();
+//! let nonces = [(); 3].map(|_| NonceKeyPair::random(&mut rand::thread_rng()));
+//! let message = Message::<Public>::plain("my-app", b"hello");
+//! let session = musig.start_sign_session(&agg_key, nonces.clone().map(|nonce| nonce.public()), message);
+//! let mut i = 0;
+//! let partial_sigs = nonces.map(|nonce| {
+//!     let partial_sig = musig.sign(&agg_key, &session, i, &keypairs[i], nonce);
+//!     assert!(musig.verify_partial_signature(&agg_key, &session, i, partial_sig));
+//!     i += 1;
+//!     partial_sig
+//! });
+//! let signature = musig.combine_partial_signatures(&agg_key, &session, partial_sigs);
+//! assert!(musig
+//!     .schnorr
+//!     .verify(&agg_key.agg_public_key(), message, &signature));
+//! ```
+use crate::{
+    binonce::{Nonce, NonceKeyPair},
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        g,
+        hash::Tag,
+        marker::*,
+        s, KeyPair, Point, Scalar, G,
+    },
+    signing, Message, Schnorr, Signature,
+};
+
+/// The MuSig signing context.
+///
+/// It aggregates keys and nonces the same way as [`crate::musig::MuSig`] so the two can be used in
+/// the same signing session.
+#[derive(Clone)]
+pub struct MuSig<H, NG> {
+    /// The hash used to compress the key list to 32 bytes.
+    pk_hash: H,
+    /// The hash used to generate each key's coefficient.
+    coeff_hash: H,
+    /// The hash used to generate the nonce coefficients.
+    nonce_coeff_hash: H,
+    /// The instance of the underlying Schnorr context.
+    pub schnorr: Schnorr<H, NG>,
+}
+
+impl<H: Tag + Default, NG> MuSig<H, NG> {
+    /// Creates the MuSig context from a Schnorr context.
+    pub fn new(schnorr: Schnorr<H, NG>) -> Self {
+        Self {
+            pk_hash: H::default().tag(b"KeyAgg list"),
+            coeff_hash: H::default().tag(b"KeyAgg coefficient"),
+            nonce_coeff_hash: H::default().tag(b"MuSig/noncecoef"),
+            schnorr,
+        }
+    }
+}
+
+impl<H, NG> Default for MuSig<H, NG>
+where
+    H: Tag + Default,
+    NG: Default + Tag,
+{
+    fn default() -> Self {
+        MuSig::new(Schnorr::<H, NG>::default())
+    }
+}
+
+/// `N` keys aggregated into a single key.
+///
+/// Like [`crate::musig::AggKey`] but without a `Vec`. Created with [`MuSig::new_agg_key`].
+#[derive(Clone, Copy, Debug)]
+pub struct AggKey<T, const N: usize> {
+    /// The keys involved in the key aggregation.
+    keys: [Point; N],
+    /// The coefficients of each key
+    coefs: [Scalar<Public>; N],
+    /// Whether the secret keys needs to be negated when signing
+    needs_negation: bool,
+    /// The aggregate key
+    agg_key: Point<T>,
+    /// The tweak on the aggregate key
+    tweak: Scalar<Public, Zero>,
+}
+
+impl<T: Copy, const N: usize> AggKey<T, N> {
+    /// The aggregate key.
+    ///
+    /// Call [`into_xonly_key`](AggKey::into_xonly_key) before using it as a BIP340 key.
+    pub fn agg_public_key(&self) -> Point<T> {
+        self.agg_key
+    }
+
+    /// The public keys of each party in the aggregate key.
+    pub fn keys(&self) -> [Point; N] {
+        self.keys
+    }
+}
+
+impl<const N: usize> AggKey<Normal, N> {
+    /// Converts the key into a BIP340 key. See [`crate::musig::AggKey::into_xonly_key`].
+    pub fn into_xonly_key(self) -> AggKey<EvenY, N> {
+        let (agg_key, needs_negation) = self.agg_key.into_point_with_even_y();
+        let mut tweak = self.tweak;
+        tweak.conditional_negate(needs_negation);
+        AggKey {
+            keys: self.keys,
+            coefs: self.coefs,
+            needs_negation,
+            tweak,
+            agg_key,
+        }
+    }
+
+    /// Adds a plain tweak to the aggregate key. See [`crate::musig::AggKey::tweak`].
+    ///
+    /// Returns `None` if the tweak is the negation of the aggregate secret key.
+    pub fn tweak(self, tweak: Scalar<impl Secrecy, impl ZeroChoice>) -> Option<Self> {
+        let agg_key = g!(self.agg_key + tweak * G).normalize().non_zero()?;
+        let tweak = s!(self.tweak + tweak).public();
+
+        Some(AggKey {
+            keys: self.keys,
+            coefs: self.coefs,
+            needs_negation: false,
+            agg_key,
+            tweak,
+        })
+    }
+}
+
+impl<const N: usize> AggKey<EvenY, N> {
+    /// Applies an "x-only" tweak to the aggregate key e.g. a taproot commitment. See
+    /// [`crate::musig::AggKey::tweak`].
+    ///
+    /// Returns `None` if the tweak is the negation of the aggregate secret key.
+    pub fn tweak(self, tweak: Scalar<impl Secrecy, impl ZeroChoice>) -> Option<Self> {
+        let (new_agg_key, needs_negation) = g!(self.agg_key + tweak * G)
+            .normalize()
+            .non_zero()?
+            .into_point_with_even_y();
+        let mut new_tweak = s!(self.tweak + tweak).public();
+        new_tweak.conditional_negate(needs_negation);
+        let needs_negation = self.needs_negation ^ needs_negation;
+
+        Some(Self {
+            keys: self.keys,
+            coefs: self.coefs,
+            needs_negation,
+            tweak: new_tweak,
+            agg_key: new_agg_key,
+        })
+    }
+}
+
+/// A MuSig signing session for `N` signers.
+///
+/// Created by [`MuSig::start_sign_session`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SignSession<const N: usize> {
+    b: Scalar<Public, Zero>,
+    c: Scalar<Public, Zero>,
+    public_nonces: [Nonce; N],
+    R: Point<EvenY>,
+    nonce_needs_negation: bool,
+}
+
+impl<const N: usize> SignSession<N> {
+    /// The aggregate nonce (the `R` of the final signature).
+    pub fn agg_nonce(&self) -> Point<EvenY> {
+        self.R
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Clone, NG> MuSig<H, NG> {
+    /// Aggregates `keys` into a single key.
+    ///
+    /// The keys have to be in the same order for every party. The key is the same as the one
+    /// [`crate::musig::MuSig::new_agg_key`] creates.
+    ///
+    /// # Panics
+    ///
+    /// If `N` is `0`.
+    pub fn new_agg_key<const N: usize>(&self, keys: [Point; N]) -> AggKey<Normal, N> {
+        let mut coefs_iter = signing::musig_key_coeffs(&self.pk_hash, &self.coeff_hash, &keys);
+        let coefs = [(); N].map(|_| coefs_iter.next().expect("one coefficient per key"));
+
+        let agg_key = g!(&coefs .* &keys)
+            .non_zero().expect("computationally unreachable: linear combination of hash randomised points cannot add to zero");
+
+        AggKey {
+            keys,
+            coefs,
+            agg_key: agg_key.normalize(),
+            tweak: Scalar::zero(),
+            needs_negation: false,
+        }
+    }
+
+    /// Starts a signing session.
+    ///
+    /// The `i`th nonce must be from the party with the `i`th key in `agg_key`. The session is the
+    /// same as the one [`crate::musig::MuSig::start_sign_session`] would start.
+    pub fn start_sign_session<const N: usize>(
+        &self,
+        agg_key: &AggKey<EvenY, N>,
+        nonces: [Nonce; N],
+        message: Message<'_, Public>,
+    ) -> SignSession<N> {
+        let agg_Rs = Nonce::aggregate(nonces);
+
+        let b = signing::musig_binding_coeff(
+            &self.nonce_coeff_hash,
+            &agg_Rs,
+            agg_key.agg_public_key(),
+            message,
+        );
+
+        let (R, nonce_needs_negation) = signing::session_nonce(g!(agg_Rs.0[0] + b * agg_Rs.0[1]));
+
+        let mut public_nonces = nonces;
+        for R_i in &mut public_nonces {
+            R_i.conditional_negate(nonce_needs_negation);
+        }
+
+        let c = self
+            .schnorr
+            .challenge(&R, &agg_key.agg_public_key(), message);
+
+        SignSession {
+            b,
+            c,
+            public_nonces,
+            R,
+            nonce_needs_negation,
+        }
+    }
+
+    /// Creates a partial signature with the key at `my_index`.
+    ///
+    /// # Panics
+    ///
+    /// If the key at `my_index` isn't `keypair`'s.
+    pub fn sign<const N: usize>(
+        &self,
+        agg_key: &AggKey<EvenY, N>,
+        session: &SignSession<N>,
+        my_index: usize,
+        keypair: &KeyPair,
+        local_secret_nonce: NonceKeyPair,
+    ) -> Scalar<Public, Zero> {
+        assert_eq!(
+            keypair.public_key(),
+            agg_key.keys[my_index],
+            "key at index {my_index} didn't match",
+        );
+        signing::sign(
+            &session.c,
+            &session.b,
+            agg_key.coefs[my_index],
+            agg_key.needs_negation,
+            session.nonce_needs_negation,
+            keypair.secret_key(),
+            local_secret_nonce,
+        )
+    }
+
+    /// Checks the partial signature from the party with the key at `index`.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is `N` or more.
+    #[must_use]
+    pub fn verify_partial_signature<const N: usize>(
+        &self,
+        agg_key: &AggKey<EvenY, N>,
+        session: &SignSession<N>,
+        index: usize,
+        partial_sig: Scalar<Public, Zero>,
+    ) -> bool {
+        // the session's nonces have already been negated
+        signing::verify(
+            &session.c,
+            &session.b,
+            agg_key.coefs[index],
+            agg_key.needs_negation,
+            false,
+            &agg_key.keys[index],
+            &session.public_nonces[index],
+            partial_sig,
+        )
+    }
+
+    /// Combines the partial signatures into a signature.
+    ///
+    /// This doesn't check the partial signatures. Check each of them with
+    /// [`verify_partial_signature`](Self::verify_partial_signature) or verify the signature.
+    pub fn combine_partial_signatures<const N: usize>(
+        &self,
+        agg_key: &AggKey<EvenY, N>,
+        session: &SignSession<N>,
+        partial_sigs: [Scalar<Public, Zero>; N],
+    ) -> Signature {
+        Signature {
+            R: session.R,
+            s: signing::combine(&session.c, &agg_key.tweak, partial_sigs),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{fun::nonce::Deterministic, musig as alloc_musig};
+    use sha2::Sha256;
+
+    #[test]
+    fn matches_alloc_musig() {
+        let alloc_musig = alloc_musig::new_with_deterministic_nonces::<Sha256>();
+        let musig = MuSig::<Sha256, Deterministic<Sha256>>::default();
+        let keypairs =
+            [(); 3].map(|_| alloc_musig.new_keypair(Scalar::random(&mut rand::thread_rng())));
+        let keys = keypairs.clone().map(|keypair| keypair.public_key());
+        let plain_tweak = Scalar::random(&mut rand::thread_rng());
+        let xonly_tweak = Scalar::random(&mut rand::thread_rng());
+        let agg_key = musig
+            .new_agg_key(keys)
+            .tweak(plain_tweak)
+            .unwrap()
+            .into_xonly_key()
+            .tweak(xonly_tweak)
+            .unwrap();
+        let alloc_agg_key = alloc_musig
+            .new_agg_key(keys.to_vec())
+            .tweak(plain_tweak)
+            .unwrap()
+            .into_xonly_key()
+            .tweak(xonly_tweak)
+            .unwrap();
+        assert_eq!(agg_key.agg_public_key(), alloc_agg_key.agg_public_key());
+
+        let message = Message::<Public>::plain("test", b"heapless");
+        let nonces = [(); 3].map(|_| NonceKeyPair::random(&mut rand::thread_rng()));
+        let public_nonces = nonces.clone().map(|nonce| nonce.public());
+        let session = musig.start_sign_session(&agg_key, public_nonces, message);
+        let alloc_session =
+            alloc_musig.start_sign_session(&alloc_agg_key, public_nonces.to_vec(), message);
+
+        let mut partial_sigs = [Scalar::<Public, Zero>::zero(); 3];
+        for (i, nonce) in nonces.into_iter().enumerate() {
+            partial_sigs[i] = musig.sign(&agg_key, &session, i, &keypairs[i], nonce.clone());
+            assert_eq!(
+                partial_sigs[i],
+                alloc_musig.sign(&alloc_agg_key, &alloc_session, i, &keypairs[i], nonce)
+            );
+            assert!(musig.verify_partial_signature(&agg_key, &session, i, partial_sigs[i]));
+            assert!(!musig.verify_partial_signature(
+                &agg_key,
+                &session,
+                (i + 1) % 3,
+                partial_sigs[i]
+            ));
+        }
+        let signature = musig.combine_partial_signatures(&agg_key, &session, partial_sigs);
+        assert_eq!(
+            signature,
+            alloc_musig.combine_partial_signatures(&alloc_agg_key, &alloc_session, partial_sigs)
+        );
+        assert!(musig
+            .schnorr
+            .verify(&agg_key.agg_public_key(), message, &signature));
+    }
+}
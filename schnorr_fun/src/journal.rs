@@ -0,0 +1,775 @@
+//! Crash recovery for FROST and MuSig signers by journaling each round.
+//!
+//! A signer that crashes between rounds has two bad options without a record of what it did:
+//! abort the ceremony, or generate a fresh nonce and risk signing two different sessions with
+//! nonces that leak its secret. A [`JournaledSigner`] appends a [`SessionEvent`] to a
+//! [`SessionJournal`] **before** acting on it (before its nonce or signature share is sent and
+//! before a received message is used) and rebuilds its state by replaying the journal in
+//! [`JournaledSigner::open`]. After a restart it hands out the same nonce, never signs a second
+//! time with it and ignores redelivered messages. The nonce is journaled along with the key and
+//! message of the session so it can't be used to sign anything else.
+//!
+//! The journal holds the signer's secret nonce until it has signed so it must be stored as
+//! carefully as the secret share. Once a signature share is recorded the secret nonce is never
+//! used again even if the journal still contains it.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{frost, journal::JournaledSigner, Message};
+//! let frost = frost::new_with_deterministic_nonces::<sha2::Sha256>();
+//! let (frost_key, secret_shares) = frost.simulate_keygen(2, 2, &mut rand::thread_rng());
+//! let frost_key = frost_key.into_xonly_key();
+//! let message = Message::plain("my-app", b"survive a crash");
+//! let signers = secret_shares.keys().copied().collect::<std::collections::BTreeSet<_>>();
+//! let (a, b) = (*signers.iter().next().unwrap(), *signers.iter().next_back().unwrap());
+//!
+//! let mut journal_a = vec![];
+//! let mut alice = JournaledSigner::open(&mut journal_a, a, signers.clone())?;
+//! let mut bob = JournaledSigner::open(vec![], b, signers.clone())?;
+//! let public_key = frost_key.public_key();
+//! let alice_nonce = alice.nonce(public_key, message, || frost.gen_nonce(&mut rand::thread_rng()))?;
+//! let bob_nonce = bob.nonce(public_key, message, || frost.gen_nonce(&mut rand::thread_rng()))?;
+//! alice.receive_nonce(b, bob_nonce)?;
+//! drop(alice); // crash!
+//!
+//! let mut alice = JournaledSigner::open(&mut journal_a, a, signers)?;
+//! // the same nonce comes back rather than a new one
+//! assert_eq!(alice.nonce(public_key, message, || unreachable!())?, alice_nonce);
+//! // a redelivered nonce is ignored
+//! alice.receive_nonce(b, bob_nonce)?;
+//! bob.receive_nonce(a, alice_nonce)?;
+//! alice.sign(&frost, &frost_key, message, &secret_shares[&a])?;
+//! let bob_share = bob.sign(&frost, &frost_key, message, &secret_shares[&b])?;
+//! alice.receive_signature_share(b, bob_share)?;
+//! let signature = alice.combine(&frost, &frost_key, message)?;
+//! assert!(frost.schnorr.verify(&frost_key.public_key(), message, &signature));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+use crate::{
+    binonce::{Nonce, NonceKeyPair},
+    frost::{self, Frost, FrostKey, PartyIndex, SignatureShareError},
+    musig::{AggKey, MuSig, PartialSignatureError},
+    Message, Signature,
+};
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+use core::{convert::Infallible, fmt};
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    marker::*,
+    KeyPair, Point, Scalar,
+};
+
+/// Durable storage for the events of one signing session.
+///
+/// Implement this over whatever storage survives a restart (a file, a database row). `append`
+/// must not return until the event is durable.
+pub trait SessionJournal<E> {
+    /// The error returned when the storage fails.
+    type Error;
+
+    /// Durably appends `event` to the journal.
+    fn append(&mut self, event: &E) -> Result<(), Self::Error>;
+
+    /// Every event in the journal in the order they were appended.
+    fn events(&self) -> Result<Vec<E>, Self::Error>;
+}
+
+/// An in-memory journal (useful for testing).
+impl<E: Clone> SessionJournal<E> for Vec<E> {
+    type Error = Infallible;
+
+    fn append(&mut self, event: &E) -> Result<(), Self::Error> {
+        self.push(event.clone());
+        Ok(())
+    }
+
+    fn events(&self) -> Result<Vec<E>, Self::Error> {
+        Ok(self.clone())
+    }
+}
+
+impl<E, J: SessionJournal<E>> SessionJournal<E> for &mut J {
+    type Error = J::Error;
+
+    fn append(&mut self, event: &E) -> Result<(), Self::Error> {
+        (**self).append(event)
+    }
+
+    fn events(&self) -> Result<Vec<E>, Self::Error> {
+        (**self).events()
+    }
+}
+
+/// Something that happened in a signing session from the point of view of one signer.
+///
+/// `I` is how signers are identified: [`PartyIndex`] for FROST and the position of the signer's
+/// key for MuSig.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+#[allow(clippy::large_enum_variant)]
+pub enum SessionEvent<I> {
+    /// The signer generated its nonce for signing `message` under `public_key`. Contains the secret
+    /// nonce!
+    NonceGenerated {
+        /// The secret nonce.
+        nonce: NonceKeyPair,
+        /// The key being signed for.
+        public_key: Point<EvenY>,
+        /// The message being signed as it goes into the challenge hash: the application tag padded
+        /// to 64 bytes (if there is one) followed by the message bytes.
+        message: Vec<u8>,
+    },
+    /// Another signer's nonce was received.
+    Nonce(I, Nonce),
+    /// Another signer's signature share was received.
+    SignatureShare(I, Scalar<Public, Zero>),
+    /// The signer produced its signature share consuming its nonce.
+    Signed(Scalar<Public, Zero>),
+}
+
+/// One signer's side of a signing session that is journaled so it can be recovered after a crash.
+///
+/// See the [module documentation](crate::journal).
+#[derive(Debug)]
+pub struct JournaledSigner<I, J> {
+    journal: J,
+    my_index: I,
+    signers: BTreeSet<I>,
+    my_nonce: Option<NonceKeyPair>,
+    session: Option<(Point<EvenY>, Vec<u8>)>,
+    nonces: BTreeMap<I, Nonce>,
+    signature_shares: BTreeMap<I, Scalar<Public, Zero>>,
+}
+
+impl<I: Ord + Copy, J: SessionJournal<SessionEvent<I>>> JournaledSigner<I, J> {
+    /// Opens the session of `my_index` with `signers` rebuilding its state from `journal`.
+    ///
+    /// A new session is just one with an empty journal.
+    ///
+    /// # Panics
+    ///
+    /// If `my_index` is not one of the `signers`.
+    pub fn open(
+        journal: J,
+        my_index: I,
+        signers: BTreeSet<I>,
+    ) -> Result<Self, JournalError<I, J::Error>> {
+        assert!(
+            signers.contains(&my_index),
+            "my_index must be one of the signers"
+        );
+        let events = journal.events().map_err(JournalError::Journal)?;
+        let mut signer = Self {
+            journal,
+            my_index,
+            signers,
+            my_nonce: None,
+            session: None,
+            nonces: BTreeMap::new(),
+            signature_shares: BTreeMap::new(),
+        };
+        for event in events {
+            if signer.check(&event)? {
+                signer.apply(event);
+            }
+        }
+        Ok(signer)
+    }
+
+    /// Whether `event` changes the state. Redelivered events don't and conflicting ones are errors.
+    fn check(&self, event: &SessionEvent<I>) -> Result<bool, JournalError<I, J::Error>> {
+        let (from, existing_matches) = match event {
+            SessionEvent::NonceGenerated {
+                nonce,
+                public_key,
+                message,
+            } => (
+                self.my_index,
+                self.nonces.get(&self.my_index).map(|existing| {
+                    *existing == nonce.public()
+                        && self.session.as_ref() == Some(&(*public_key, message.clone()))
+                }),
+            ),
+            SessionEvent::Signed(share) => {
+                if !self.nonces.contains_key(&self.my_index) {
+                    return Err(JournalError::NoNonce);
+                }
+                (
+                    self.my_index,
+                    self.signature_shares
+                        .get(&self.my_index)
+                        .map(|existing| existing == share),
+                )
+            }
+            SessionEvent::Nonce(from, nonce) => {
+                self.check_sender(*from)?;
+                (
+                    *from,
+                    self.nonces.get(from).map(|existing| existing == nonce),
+                )
+            }
+            SessionEvent::SignatureShare(from, share) => {
+                self.check_sender(*from)?;
+                (
+                    *from,
+                    self.signature_shares
+                        .get(from)
+                        .map(|existing| existing == share),
+                )
+            }
+        };
+        match existing_matches {
+            None => Ok(true),
+            Some(true) => Ok(false),
+            Some(false) => Err(JournalError::Conflicting(from)),
+        }
+    }
+
+    fn check_sender(&self, from: I) -> Result<(), JournalError<I, J::Error>> {
+        if from == self.my_index || !self.signers.contains(&from) {
+            return Err(JournalError::UnexpectedSender(from));
+        }
+        Ok(())
+    }
+
+    fn apply(&mut self, event: SessionEvent<I>) {
+        match event {
+            SessionEvent::NonceGenerated {
+                nonce,
+                public_key,
+                message,
+            } => {
+                self.nonces.insert(self.my_index, nonce.public());
+                self.my_nonce = Some(nonce);
+                self.session = Some((public_key, message));
+            }
+            SessionEvent::Nonce(from, nonce) => {
+                self.nonces.insert(from, nonce);
+            }
+            SessionEvent::SignatureShare(from, share) => {
+                self.signature_shares.insert(from, share);
+            }
+            SessionEvent::Signed(share) => {
+                self.signature_shares.insert(self.my_index, share);
+                self.my_nonce = None;
+            }
+        }
+    }
+
+    fn record(&mut self, event: SessionEvent<I>) -> Result<(), JournalError<I, J::Error>> {
+        if self.check(&event)? {
+            self.journal.append(&event).map_err(JournalError::Journal)?;
+            self.apply(event);
+        }
+        Ok(())
+    }
+
+    /// The signer's public nonce to send to the others for signing `message` under `public_key`.
+    ///
+    /// `gen_nonce` is only called (and its nonce journaled with `public_key` and `message`) the first
+    /// time. After that the journaled nonce is returned as long as `public_key` and `message` are
+    /// the same as they were then.
+    pub fn nonce(
+        &mut self,
+        public_key: Point<EvenY>,
+        message: Message,
+        gen_nonce: impl FnOnce() -> NonceKeyPair,
+    ) -> Result<Nonce, JournalError<I, J::Error>> {
+        if let Some(nonce) = self.nonces.get(&self.my_index).copied() {
+            self.check_session(public_key, message)?;
+            return Ok(nonce);
+        }
+        let nonce = gen_nonce();
+        let public = nonce.public();
+        self.record(SessionEvent::NonceGenerated {
+            nonce,
+            public_key,
+            message: message_bytes(message),
+        })?;
+        Ok(public)
+    }
+
+    /// Checks that `public_key` and `message` are what the nonce was journaled with.
+    fn check_session(
+        &self,
+        public_key: Point<EvenY>,
+        message: Message,
+    ) -> Result<(), JournalError<I, J::Error>> {
+        match &self.session {
+            Some(session) if *session != (public_key, message_bytes(message)) => {
+                Err(JournalError::Conflicting(self.my_index))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Records the nonce sent by `from`.
+    ///
+    /// Receiving the same nonce again does nothing.
+    pub fn receive_nonce(
+        &mut self,
+        from: I,
+        nonce: Nonce,
+    ) -> Result<(), JournalError<I, J::Error>> {
+        self.record(SessionEvent::Nonce(from, nonce))
+    }
+
+    /// Records the signature share sent by `from`. It is checked in `combine`.
+    ///
+    /// Receiving the same signature share again does nothing.
+    pub fn receive_signature_share(
+        &mut self,
+        from: I,
+        signature_share: Scalar<Public, Zero>,
+    ) -> Result<(), JournalError<I, J::Error>> {
+        self.record(SessionEvent::SignatureShare(from, signature_share))
+    }
+
+    /// The nonces received so far including the signer's own.
+    pub fn nonces(&self) -> &BTreeMap<I, Nonce> {
+        &self.nonces
+    }
+
+    /// The signature shares received so far including the signer's own once it has signed.
+    pub fn signature_shares(&self) -> &BTreeMap<I, Scalar<Public, Zero>> {
+        &self.signature_shares
+    }
+
+    /// The signer's own signature share if it has signed.
+    pub fn my_signature_share(&self) -> Option<Scalar<Public, Zero>> {
+        self.signature_shares.get(&self.my_index).copied()
+    }
+
+    /// The signers whose nonces haven't been received yet.
+    pub fn missing_nonces(&self) -> impl Iterator<Item = I> + '_ {
+        self.signers
+            .iter()
+            .copied()
+            .filter(|signer| !self.nonces.contains_key(signer))
+    }
+
+    /// Returns the journal.
+    pub fn into_journal(self) -> J {
+        self.journal
+    }
+
+    /// The nonce to sign with once every signer's nonce has been received.
+    fn signing_nonce(&self) -> Result<NonceKeyPair, JournalError<I, J::Error>> {
+        if let Some(missing) = self.missing_nonces().next() {
+            return Err(if missing == self.my_index {
+                JournalError::NoNonce
+            } else {
+                JournalError::MissingNonce(missing)
+            });
+        }
+        Ok(self
+            .my_nonce
+            .clone()
+            .expect("the nonce is only taken once the signature share is recorded"))
+    }
+}
+
+impl<J: SessionJournal<SessionEvent<PartyIndex>>> JournaledSigner<PartyIndex, J> {
+    /// Produces (and journals) the signer's FROST signature share once every signer's nonce has
+    /// been received.
+    ///
+    /// If the signer has already signed the journaled signature share is returned. `frost_key` and
+    /// `message` must be the ones the nonce was generated for.
+    ///
+    /// # Panics
+    ///
+    /// If there are fewer signers than the threshold of `frost_key`.
+    pub fn sign<H: Digest<OutputSize = U32> + Clone, NG>(
+        &mut self,
+        frost: &Frost<H, NG>,
+        frost_key: &FrostKey<EvenY>,
+        message: Message,
+        secret_share: &Scalar,
+    ) -> Result<Scalar<Public, Zero>, JournalError<PartyIndex, J::Error>> {
+        self.check_session(frost_key.public_key(), message)?;
+        if let Some(signature_share) = self.my_signature_share() {
+            return Ok(signature_share);
+        }
+        let nonce = self.signing_nonce()?;
+        let session = frost.start_sign_session(frost_key, self.nonces.clone(), message);
+        let signature_share = frost.sign(frost_key, &session, self.my_index, secret_share, nonce);
+        self.record(SessionEvent::Signed(signature_share))?;
+        Ok(signature_share)
+    }
+
+    /// Checks the signature shares and combines them into the signature.
+    pub fn combine<H: Digest<OutputSize = U32> + Clone, NG>(
+        &self,
+        frost: &Frost<H, NG>,
+        frost_key: &FrostKey<EvenY>,
+        message: Message,
+    ) -> Result<Signature, JournalError<PartyIndex, J::Error>> {
+        self.check_session(frost_key.public_key(), message)?;
+        if let Some(missing) = self.missing_nonces().next() {
+            return Err(JournalError::MissingNonce(missing));
+        }
+        let session = frost.start_sign_session(frost_key, self.nonces.clone(), message);
+        let mut aggregator = frost.signature_share_aggregator(frost_key, &session);
+        let to_journal_error = |error| match error {
+            SignatureShareError::Missing(index) => JournalError::MissingSignatureShare(index),
+            SignatureShareError::NotSigner(index) => JournalError::UnexpectedSender(index),
+            SignatureShareError::Conflicting(index) => JournalError::Conflicting(index),
+            SignatureShareError::Invalid(index) => JournalError::InvalidSignatureShare(index),
+        };
+        for (index, signature_share) in &self.signature_shares {
+            aggregator
+                .add(*index, *signature_share)
+                .map_err(to_journal_error)?;
+        }
+        aggregator.finish().map_err(to_journal_error)
+    }
+
+    /// The [`SigningMessage`](frost::SigningMessage) version of `receive_nonce` and
+    /// `receive_signature_share` for use with a FROST transport.
+    pub fn receive(
+        &mut self,
+        from: PartyIndex,
+        message: frost::SigningMessage,
+    ) -> Result<(), JournalError<PartyIndex, J::Error>> {
+        match message {
+            frost::SigningMessage::Nonce(nonce) => self.receive_nonce(from, nonce),
+            frost::SigningMessage::SignatureShare(share) => {
+                self.receive_signature_share(from, share)
+            }
+        }
+    }
+}
+
+impl<J: SessionJournal<SessionEvent<usize>>> JournaledSigner<usize, J> {
+    /// Produces (and journals) the signer's MuSig partial signature once every signer's nonce has
+    /// been received.
+    ///
+    /// The signers must be the positions of every key in `agg_key`. If the signer has already
+    /// signed the journaled partial signature is returned. `agg_key` and `message` must be the ones
+    /// the nonce was generated for.
+    ///
+    /// # Panics
+    ///
+    /// If the signers don't match the keys of `agg_key` or `keypair` isn't the signer's key.
+    pub fn sign<H: Digest<OutputSize = U32> + Clone, NG>(
+        &mut self,
+        musig: &MuSig<H, NG>,
+        agg_key: &AggKey<EvenY>,
+        message: Message,
+        keypair: &KeyPair,
+    ) -> Result<Scalar<Public, Zero>, JournalError<usize, J::Error>> {
+        self.check_session(agg_key.agg_public_key(), message)?;
+        if let Some(partial_signature) = self.my_signature_share() {
+            return Ok(partial_signature);
+        }
+        let nonce = self.signing_nonce()?;
+        let session =
+            musig.start_sign_session(agg_key, self.nonces.values().copied().collect(), message);
+        let partial_signature = musig.sign(agg_key, &session, self.my_index, keypair, nonce);
+        self.record(SessionEvent::Signed(partial_signature))?;
+        Ok(partial_signature)
+    }
+
+    /// Checks the partial signatures and combines them into the signature.
+    pub fn combine<H: Digest<OutputSize = U32> + Clone, NG>(
+        &self,
+        musig: &MuSig<H, NG>,
+        agg_key: &AggKey<EvenY>,
+        message: Message,
+    ) -> Result<Signature, JournalError<usize, J::Error>> {
+        self.check_session(agg_key.agg_public_key(), message)?;
+        if let Some(missing) = self.missing_nonces().next() {
+            return Err(JournalError::MissingNonce(missing));
+        }
+        let session =
+            musig.start_sign_session(agg_key, self.nonces.values().copied().collect(), message);
+        let mut aggregator = musig.partial_signature_aggregator(agg_key, &session);
+        let to_journal_error = |error| match error {
+            PartialSignatureError::Missing(index) => JournalError::MissingSignatureShare(index),
+            PartialSignatureError::UnknownIndex(index) => JournalError::UnexpectedSender(index),
+            PartialSignatureError::Conflicting(index) => JournalError::Conflicting(index),
+            PartialSignatureError::Invalid(index) => JournalError::InvalidSignatureShare(index),
+        };
+        for (index, partial_signature) in &self.signature_shares {
+            aggregator
+                .add(*index, *partial_signature)
+                .map_err(to_journal_error)?;
+        }
+        aggregator.finish().map_err(to_journal_error)
+    }
+}
+
+/// How [`SessionEvent::NonceGenerated`] records a message.
+fn message_bytes(message: Message) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(message.len());
+    if let Some(app_tag) = message.app_tag {
+        bytes.extend_from_slice(app_tag.as_bytes());
+        bytes.resize(64, 0);
+    }
+    bytes.extend_from_slice(message.bytes.into());
+    bytes
+}
+
+/// Error returned by a [`JournaledSigner`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum JournalError<I, E> {
+    /// The journal failed.
+    Journal(E),
+    /// A message was received from a party that isn't one of the other signers.
+    UnexpectedSender(I),
+    /// The party sent something different to what was already recorded for them. For the signer
+    /// itself this means it was asked to sign a different key or message to the one its nonce was
+    /// generated for.
+    Conflicting(I),
+    /// The signer tried to sign before generating its nonce.
+    NoNonce,
+    /// The nonce of the party hasn't been received.
+    MissingNonce(I),
+    /// The signature share of the party hasn't been received.
+    MissingSignatureShare(I),
+    /// The party's signature share is invalid.
+    InvalidSignatureShare(I),
+}
+
+impl<I: fmt::Display, E: fmt::Display> fmt::Display for JournalError<I, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JournalError::Journal(error) => write!(f, "journal error: {error}"),
+            JournalError::UnexpectedSender(party) => {
+                write!(f, "received a message from party {party} who isn't signing")
+            }
+            JournalError::Conflicting(party) => {
+                write!(f, "party {party} sent conflicting messages in a round")
+            }
+            JournalError::NoNonce => write!(f, "the signer hasn't generated its nonce"),
+            JournalError::MissingNonce(party) => {
+                write!(f, "haven't received the nonce of party {party}")
+            }
+            JournalError::MissingSignatureShare(party) => {
+                write!(f, "haven't received the signature share of party {party}")
+            }
+            JournalError::InvalidSignatureShare(party) => {
+                write!(f, "party {party} sent an invalid signature share")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<I: fmt::Debug + fmt::Display, E: fmt::Debug + fmt::Display> std::error::Error
+    for JournalError<I, E>
+{
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::musig;
+    use sha2::Sha256;
+
+    #[test]
+    fn frost_signer_recovers_after_signing() {
+        let frost = frost::new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, secret_shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        let frost_key = frost_key.into_xonly_key();
+        let message = Message::<Public>::plain("test", b"journal");
+        let signers = secret_shares
+            .keys()
+            .copied()
+            .take(2)
+            .collect::<BTreeSet<_>>();
+        let (a, b) = (
+            *signers.iter().next().unwrap(),
+            *signers.iter().next_back().unwrap(),
+        );
+        let outsider = *secret_shares.keys().last().unwrap();
+
+        let mut journal = vec![];
+        let mut alice = JournaledSigner::open(&mut journal, a, signers.clone()).unwrap();
+        let mut bob = JournaledSigner::open(vec![], b, signers.clone()).unwrap();
+        assert_eq!(
+            alice.sign(&frost, &frost_key, message, &secret_shares[&a]),
+            Err(JournalError::NoNonce)
+        );
+        let public_key = frost_key.public_key();
+        let alice_nonce = alice
+            .nonce(public_key, message, || {
+                frost.gen_nonce(&mut rand::thread_rng())
+            })
+            .unwrap();
+        let bob_nonce = bob
+            .nonce(public_key, message, || {
+                frost.gen_nonce(&mut rand::thread_rng())
+            })
+            .unwrap();
+        assert_eq!(
+            alice.receive_nonce(outsider, bob_nonce),
+            Err(JournalError::UnexpectedSender(outsider))
+        );
+        assert_eq!(
+            alice.sign(&frost, &frost_key, message, &secret_shares[&a]),
+            Err(JournalError::MissingNonce(b))
+        );
+        alice.receive_nonce(b, bob_nonce).unwrap();
+        assert_eq!(
+            alice.receive_nonce(b, alice_nonce),
+            Err(JournalError::Conflicting(b))
+        );
+        let alice_share = alice
+            .sign(&frost, &frost_key, message, &secret_shares[&a])
+            .unwrap();
+        drop(alice);
+        // the journal doesn't grow with redeliveries or rejected messages
+        assert_eq!(journal.len(), 3);
+
+        let mut alice = JournaledSigner::open(&mut journal, a, signers).unwrap();
+        assert_eq!(alice.my_signature_share(), Some(alice_share));
+        assert!(alice.my_nonce.is_none());
+        assert_eq!(
+            alice.sign(&frost, &frost_key, message, &secret_shares[&a]),
+            Ok(alice_share)
+        );
+        bob.receive(a, frost::SigningMessage::Nonce(alice_nonce))
+            .unwrap();
+        let bob_share = bob
+            .sign(&frost, &frost_key, message, &secret_shares[&b])
+            .unwrap();
+        assert_eq!(
+            alice.combine(&frost, &frost_key, message),
+            Err(JournalError::MissingSignatureShare(b))
+        );
+        alice.receive_signature_share(b, alice_share).unwrap();
+        assert_eq!(
+            alice.combine(&frost, &frost_key, message),
+            Err(JournalError::InvalidSignatureShare(b))
+        );
+        assert_eq!(bob.my_signature_share(), Some(bob_share));
+        bob.receive_signature_share(a, alice_share).unwrap();
+        let signature = bob.combine(&frost, &frost_key, message).unwrap();
+        assert!(frost
+            .schnorr
+            .verify(&frost_key.public_key(), message, &signature));
+    }
+
+    #[test]
+    fn musig_signer_recovers_between_rounds() {
+        let musig = musig::new_with_deterministic_nonces::<Sha256>();
+        let keypairs = [0u8, 1].map(|_| musig.new_keypair(Scalar::random(&mut rand::thread_rng())));
+        let agg_key = musig
+            .new_agg_key(
+                keypairs
+                    .iter()
+                    .map(|keypair| keypair.public_key())
+                    .collect(),
+            )
+            .into_xonly_key();
+        let message = Message::<Public>::plain("test", b"journal");
+        let signers = BTreeSet::from_iter([0, 1]);
+
+        let mut journal = vec![];
+        let mut signer0 = JournaledSigner::open(&mut journal, 0, signers.clone()).unwrap();
+        let mut signer1 = JournaledSigner::open(vec![], 1, signers.clone()).unwrap();
+        let public_key = agg_key.agg_public_key();
+        let nonce0 = signer0
+            .nonce(public_key, message, || {
+                musig.gen_nonce(&mut rand::thread_rng())
+            })
+            .unwrap();
+        let nonce1 = signer1
+            .nonce(public_key, message, || {
+                musig.gen_nonce(&mut rand::thread_rng())
+            })
+            .unwrap();
+        signer0.receive_nonce(1, nonce1).unwrap();
+        drop(signer0);
+
+        let mut signer0 = JournaledSigner::open(&mut journal, 0, signers).unwrap();
+        assert_eq!(
+            signer0.nonce(public_key, message, || unreachable!()),
+            Ok(nonce0)
+        );
+        signer1.receive_nonce(0, nonce0).unwrap();
+        let partial0 = signer0
+            .sign(&musig, &agg_key, message, &keypairs[0])
+            .unwrap();
+        let partial1 = signer1
+            .sign(&musig, &agg_key, message, &keypairs[1])
+            .unwrap();
+        signer0.receive_signature_share(1, partial1).unwrap();
+        signer0.receive_signature_share(1, partial1).unwrap();
+        signer1.receive_signature_share(0, partial0).unwrap();
+        let signature = signer0.combine(&musig, &agg_key, message).unwrap();
+        assert!(signer1.combine(&musig, &agg_key, message) == Ok(signature.clone()));
+        assert!(musig
+            .schnorr
+            .verify(&agg_key.agg_public_key(), message, &signature));
+    }
+
+    #[test]
+    fn nonce_is_bound_to_key_and_message() {
+        let frost = frost::new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, secret_shares) = frost.simulate_keygen(2, 2, &mut rand::thread_rng());
+        let frost_key = frost_key.into_xonly_key();
+        let other_key = frost
+            .simulate_keygen(2, 2, &mut rand::thread_rng())
+            .0
+            .into_xonly_key();
+        let message = Message::<Public>::plain("test", b"journal");
+        let other_message = Message::<Public>::plain("test", b"something else");
+        let signers = secret_shares.keys().copied().collect::<BTreeSet<_>>();
+        let (a, b) = (
+            *signers.iter().next().unwrap(),
+            *signers.iter().next_back().unwrap(),
+        );
+
+        let mut journal = vec![];
+        let mut alice = JournaledSigner::open(&mut journal, a, signers.clone()).unwrap();
+        let public_key = frost_key.public_key();
+        alice
+            .nonce(public_key, message, || {
+                frost.gen_nonce(&mut rand::thread_rng())
+            })
+            .unwrap();
+        let bob_nonce = frost.gen_nonce(&mut rand::thread_rng()).public();
+        alice.receive_nonce(b, bob_nonce).unwrap();
+        let alice_share = alice
+            .sign(&frost, &frost_key, message, &secret_shares[&a])
+            .unwrap();
+        drop(alice);
+
+        let mut alice = JournaledSigner::open(&mut journal, a, signers).unwrap();
+        assert_eq!(
+            alice.nonce(public_key, other_message, || unreachable!()),
+            Err(JournalError::Conflicting(a))
+        );
+        assert_eq!(
+            alice.nonce(other_key.public_key(), message, || unreachable!()),
+            Err(JournalError::Conflicting(a))
+        );
+        assert_eq!(
+            alice.sign(&frost, &frost_key, other_message, &secret_shares[&a]),
+            Err(JournalError::Conflicting(a))
+        );
+        assert_eq!(
+            alice.combine(&frost, &frost_key, other_message),
+            Err(JournalError::Conflicting(a))
+        );
+        assert_eq!(
+            alice.sign(&frost, &frost_key, message, &secret_shares[&a]),
+            Ok(alice_share)
+        );
+        assert_eq!(
+            message_bytes(Message::<Public>::raw(b"raw")),
+            b"raw".to_vec()
+        );
+        assert_eq!(message_bytes(message).len(), message.len());
+    }
+}
@@ -0,0 +1,440 @@
+//! Password encrypted key files.
+//!
+//! [`save_keypair`] and [`save_frost_share`] encrypt a key with a password into a self-describing
+//! file that [`load_keypair`] and [`load_frost_share`] can read back. The format is:
+//!
+//! ```text
+//! header = "SFKS" || version (1 byte) || kind (1 byte) || iterations (u32 BE) || salt (16 bytes)
+//! file   = header || ciphertext || mac (32 bytes)
+//! ```
+//!
+//! The key is derived from the password with [PBKDF2] using HMAC-`H` and the contents are
+//! encrypted and authenticated with [`ecies::seal`] with the header as the associated data:
+//!
+//! ```text
+//! k = PBKDF2-HMAC-H(password, salt, iterations, 32)
+//! ciphertext || mac = ecies::seal(k, header, contents)
+//! ```
+//!
+//! Since the salt is random the key is only ever used for one file. The iterations must be between
+//! [`KdfParams::MIN_ITERATIONS`] and [`KdfParams::MAX_ITERATIONS`] so that a file can't make
+//! loading it take forever (or skip the KDF).
+//!
+//! The `kind` byte is `1` for a keypair whose contents are the 32 byte secret key and `3` for a
+//! FROST share whose contents are the 32 byte party index, the 32 byte secret share, the
+//! [`KeyGeneration`] of the key and then each 33 byte coefficient of the (untweaked) point
+//! polynomial of the FROST key. The generation is its counter (u32 BE), a byte that is `1` if it
+//! has a parent and `0` if not and the 8 byte parent fingerprint (zeros if there is none).
+//!
+//! With the `zeroize` feature the decrypted contents and the key derived from the password are
+//! overwritten once they have been used.
+//!
+//! [`KeyGeneration`]: crate::frost::KeyGeneration
+//! [PBKDF2]: https://datatracker.ietf.org/doc/html/rfc8018#section-5.2
+//! [`ecies::seal`]: secp256kfun::ecies::seal
+//!
+//! The KDF is not memory-hard so use a strong password (or a random one kept in a secrets
+//! manager) and at least the default number of iterations.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     fun::{marker::*, KeyPair, Scalar},
+//!     keystore,
+//! };
+//! use sha2::Sha256;
+//! let keypair = KeyPair::<Normal>::new(Scalar::random(&mut rand::thread_rng()));
+//! let params = keystore::KdfParams::default();
+//! # let params = keystore::KdfParams::new(keystore::KdfParams::MIN_ITERATIONS).unwrap();
+//! let file = keystore::save_keypair::<Sha256>(
+//!     &keypair,
+//!     b"correct horse battery staple",
+//!     params,
+//!     &mut rand::thread_rng(),
+//! );
+//! let loaded = keystore::load_keypair::<Sha256>(&file, b"correct horse battery staple").unwrap();
+//! assert_eq!(loaded, keypair);
+//! assert_eq!(
+//!     keystore::load_keypair::<Sha256>(&file, b"wrong"),
+//!     Err(keystore::KeystoreError::WrongPassword)
+//! );
+//! ```
+use crate::frost::{FrostKey, KeyGeneration, PartyIndex};
+use alloc::vec::Vec;
+use core::fmt;
+use secp256kfun::{
+    digest::{crypto_common::BlockSizeUser, generic_array::typenum::U32, Digest},
+    ecies, g,
+    hash::pbkdf2,
+    marker::*,
+    rand_core::RngCore,
+    KeyPair, Point, Scalar, G,
+};
+
+const MAGIC: [u8; 4] = *b"SFKS";
+const VERSION: u8 = 1;
+const KIND_KEYPAIR: u8 = 1;
+const KIND_FROST_SHARE: u8 = 3;
+const GENERATION_LEN: usize = 4 + 1 + 8;
+const HEADER_LEN: usize = 4 + 1 + 1 + 4 + 16;
+
+/// The parameters for deriving the encryption key from the password.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    iterations: u32,
+}
+
+impl KdfParams {
+    /// The fewest PBKDF2 iterations a file can use.
+    ///
+    /// This only rules out files that barely use the KDF at all. Use the [`Default`] unless you
+    /// really can't afford it.
+    pub const MIN_ITERATIONS: u32 = 10_000;
+    /// The most PBKDF2 iterations a file can use.
+    pub const MAX_ITERATIONS: u32 = 10_000_000;
+
+    /// Creates the parameters for `iterations` of PBKDF2.
+    ///
+    /// Returns `None` if `iterations` is not between [`MIN_ITERATIONS`] and [`MAX_ITERATIONS`].
+    ///
+    /// [`MIN_ITERATIONS`]: Self::MIN_ITERATIONS
+    /// [`MAX_ITERATIONS`]: Self::MAX_ITERATIONS
+    pub fn new(iterations: u32) -> Option<Self> {
+        if (Self::MIN_ITERATIONS..=Self::MAX_ITERATIONS).contains(&iterations) {
+            Some(Self { iterations })
+        } else {
+            None
+        }
+    }
+
+    /// How many iterations of PBKDF2 are used.
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+}
+
+impl Default for KdfParams {
+    /// 600,000 iterations (what OWASP recommends for PBKDF2-HMAC-SHA256).
+    fn default() -> Self {
+        Self {
+            iterations: 600_000,
+        }
+    }
+}
+
+/// Encrypts `keypair` with `password`.
+///
+/// See the [module documentation](crate::keystore) for the format.
+pub fn save_keypair<H: BlockSizeUser + Digest<OutputSize = U32> + Default>(
+    keypair: &KeyPair<impl PointType>,
+    password: &[u8],
+    params: KdfParams,
+    rng: &mut impl RngCore,
+) -> Vec<u8> {
+    seal::<H>(
+        KIND_KEYPAIR,
+        keypair
+            .secret_key()
+            .to_secret_bytes()
+            .expose_secret()
+            .to_vec(),
+        password,
+        params,
+        rng,
+    )
+}
+
+/// Decrypts a keypair saved with [`save_keypair`].
+pub fn load_keypair<H: BlockSizeUser + Digest<OutputSize = U32> + Default>(
+    file: &[u8],
+    password: &[u8],
+) -> Result<KeyPair, KeystoreError> {
+    #[cfg_attr(not(feature = "zeroize"), allow(unused_mut))]
+    let mut contents = open::<H>(KIND_KEYPAIR, file, password)?;
+    let secret_key = Scalar::from_slice(&contents).ok_or(KeystoreError::InvalidContents);
+    #[cfg(feature = "zeroize")]
+    secp256kfun::zeroize::Zeroize::zeroize(&mut contents);
+    Ok(KeyPair::<Normal>::new(secret_key?))
+}
+
+/// Encrypts the secret share at `index` of `frost_key` with `password`.
+///
+/// Only the point polynomial and generation of `frost_key` are stored so any tweaks aren't saved.
+///
+/// See the [module documentation](crate::keystore) for the format.
+pub fn save_frost_share<H: BlockSizeUser + Digest<OutputSize = U32> + Default>(
+    frost_key: &FrostKey<impl PointType>,
+    index: PartyIndex,
+    secret_share: &Scalar,
+    password: &[u8],
+    params: KdfParams,
+    rng: &mut impl RngCore,
+) -> Vec<u8> {
+    let point_polynomial = frost_key.point_polynomial();
+    let generation = frost_key.generation();
+    let mut contents = Vec::with_capacity(64 + GENERATION_LEN + 33 * point_polynomial.len());
+    contents.extend(index.to_bytes());
+    contents.extend(secret_share.to_secret_bytes().expose_secret());
+    contents.extend(generation.counter.to_be_bytes());
+    contents.push(generation.parent.is_some() as u8);
+    contents.extend(generation.parent.unwrap_or_default());
+    for coefficient in point_polynomial {
+        contents.extend(coefficient.to_bytes());
+    }
+    seal::<H>(KIND_FROST_SHARE, contents, password, params, rng)
+}
+
+/// Decrypts a FROST share saved with [`save_frost_share`].
+///
+/// Returns the (untweaked) FROST key with its [`KeyGeneration`] along with the party index and
+/// secret share. The share is checked against the key.
+pub fn load_frost_share<H: BlockSizeUser + Digest<OutputSize = U32> + Default>(
+    file: &[u8],
+    password: &[u8],
+) -> Result<(FrostKey<Normal>, PartyIndex, Scalar), KeystoreError> {
+    #[cfg_attr(not(feature = "zeroize"), allow(unused_mut))]
+    let mut contents = open::<H>(KIND_FROST_SHARE, file, password)?;
+    let frost_share = frost_share_from_contents(&contents);
+    #[cfg(feature = "zeroize")]
+    secp256kfun::zeroize::Zeroize::zeroize(&mut contents);
+    frost_share
+}
+
+fn frost_share_from_contents(
+    contents: &[u8],
+) -> Result<(FrostKey<Normal>, PartyIndex, Scalar), KeystoreError> {
+    let poly_start = 64 + GENERATION_LEN;
+    if contents.len() < poly_start + 33 || (contents.len() - poly_start) % 33 != 0 {
+        return Err(KeystoreError::InvalidContents);
+    }
+    let generation = &contents[64..poly_start];
+    let mut counter = [0u8; 4];
+    counter.copy_from_slice(&generation[..4]);
+    let mut parent = [0u8; 8];
+    parent.copy_from_slice(&generation[5..]);
+    let parent = match generation[4] {
+        0 if parent == [0u8; 8] => None,
+        1 => Some(parent),
+        _ => return Err(KeystoreError::InvalidContents),
+    };
+    let generation = KeyGeneration {
+        counter: u32::from_be_bytes(counter),
+        parent,
+    };
+    let index = PartyIndex::from_slice(&contents[..32]).ok_or(KeystoreError::InvalidContents)?;
+    let secret_share =
+        Scalar::from_slice(&contents[32..64]).ok_or(KeystoreError::InvalidContents)?;
+    let point_polynomial = contents[poly_start..]
+        .chunks(33)
+        .map(Point::<Normal, Public, Zero>::from_slice)
+        .collect::<Option<Vec<_>>>()
+        .ok_or(KeystoreError::InvalidContents)?;
+    let frost_key = FrostKey::from_point_polynomial(point_polynomial)
+        .ok_or(KeystoreError::InvalidContents)?
+        .with_generation(generation);
+    if frost_key.verification_share(index) != g!(secret_share * G) {
+        return Err(KeystoreError::InvalidContents);
+    }
+    Ok((frost_key, index, secret_share))
+}
+
+fn derive_key<H: BlockSizeUser + Digest<OutputSize = U32> + Default>(
+    password: &[u8],
+    salt: &[u8],
+    params: KdfParams,
+) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2::<H>(password, salt, params.iterations, &mut key);
+    key
+}
+
+fn seal<H: BlockSizeUser + Digest<OutputSize = U32> + Default>(
+    kind: u8,
+    #[cfg_attr(not(feature = "zeroize"), allow(unused_mut))] mut contents: Vec<u8>,
+    password: &[u8],
+    params: KdfParams,
+    rng: &mut impl RngCore,
+) -> Vec<u8> {
+    let mut salt = [0u8; 16];
+    rng.fill_bytes(&mut salt);
+    let mut file = Vec::with_capacity(HEADER_LEN + contents.len() + 32);
+    file.extend(MAGIC);
+    file.push(VERSION);
+    file.push(kind);
+    file.extend(params.iterations.to_be_bytes());
+    file.extend(salt);
+
+    #[cfg_attr(not(feature = "zeroize"), allow(unused_mut))]
+    let mut key = derive_key::<H>(password, &salt, params);
+    let ciphertext = ecies::seal::<H>(&key, &file, &contents);
+    #[cfg(feature = "zeroize")]
+    {
+        use secp256kfun::zeroize::Zeroize;
+        key.zeroize();
+        contents.zeroize();
+    }
+    file.extend(ciphertext);
+    file
+}
+
+/// Decrypts a file that must be of `kind` and returns its contents.
+///
+/// With the `zeroize` feature the caller must zeroize the contents once it has read them.
+fn open<H: BlockSizeUser + Digest<OutputSize = U32> + Default>(
+    kind: u8,
+    file: &[u8],
+    password: &[u8],
+) -> Result<Vec<u8>, KeystoreError> {
+    if file.len() < HEADER_LEN + 32 || file[..4] != MAGIC {
+        return Err(KeystoreError::NotAKeystore);
+    }
+    if file[4] != VERSION {
+        return Err(KeystoreError::UnsupportedVersion(file[4]));
+    }
+    if file[5] != kind {
+        return Err(KeystoreError::WrongKind(file[5]));
+    }
+    let mut iterations = [0u8; 4];
+    iterations.copy_from_slice(&file[6..10]);
+    let iterations = u32::from_be_bytes(iterations);
+    let params = KdfParams::new(iterations).ok_or(KeystoreError::InvalidIterations(iterations))?;
+    let (header, ciphertext) = file.split_at(HEADER_LEN);
+
+    #[cfg_attr(not(feature = "zeroize"), allow(unused_mut))]
+    let mut key = derive_key::<H>(password, &header[10..], params);
+    let contents = ecies::open::<H>(&key, header, ciphertext);
+    #[cfg(feature = "zeroize")]
+    secp256kfun::zeroize::Zeroize::zeroize(&mut key);
+    contents.map_err(|_| KeystoreError::WrongPassword)
+}
+
+/// Error loading a key file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeystoreError {
+    /// The file isn't a key file.
+    NotAKeystore,
+    /// The file has a version this library doesn't know about.
+    UnsupportedVersion(u8),
+    /// The file holds a different kind of key.
+    WrongKind(u8),
+    /// The file asks for a number of KDF iterations outside of the allowed range.
+    InvalidIterations(u32),
+    /// The password is wrong or the file has been tampered with.
+    WrongPassword,
+    /// The file decrypted but its contents aren't a valid key.
+    InvalidContents,
+}
+
+impl fmt::Display for KeystoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use KeystoreError::*;
+        match self {
+            NotAKeystore => write!(f, "not a key file"),
+            UnsupportedVersion(version) => write!(f, "unsupported key file version {version}"),
+            WrongKind(kind) => write!(f, "key file holds a different kind of key ({kind})"),
+            InvalidIterations(iterations) => write!(
+                f,
+                "key file uses {iterations} KDF iterations which is outside of the allowed range"
+            ),
+            WrongPassword => write!(f, "wrong password or the key file has been tampered with"),
+            InvalidContents => write!(f, "key file contents are invalid"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for KeystoreError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+
+    const PARAMS: KdfParams = KdfParams {
+        iterations: KdfParams::MIN_ITERATIONS,
+    };
+
+    #[test]
+    fn frost_share_round_trip() {
+        let frost = crate::frost::new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        let (index, secret_share) = shares.into_iter().next().unwrap();
+        let file = save_frost_share::<Sha256>(
+            &frost_key,
+            index,
+            &secret_share,
+            b"password",
+            PARAMS,
+            &mut rand::thread_rng(),
+        );
+        assert_eq!(
+            load_frost_share::<Sha256>(&file, b"password"),
+            Ok((frost_key, index, secret_share))
+        );
+        assert_eq!(
+            load_keypair::<Sha256>(&file, b"password"),
+            Err(KeystoreError::WrongKind(KIND_FROST_SHARE))
+        );
+
+        let mut tampered = file.clone();
+        tampered[HEADER_LEN] ^= 1;
+        assert_eq!(
+            load_frost_share::<Sha256>(&tampered, b"password"),
+            Err(KeystoreError::WrongPassword)
+        );
+        let mut more_iterations = file.clone();
+        more_iterations[9] += 1;
+        assert_eq!(
+            load_frost_share::<Sha256>(&more_iterations, b"password"),
+            Err(KeystoreError::WrongPassword)
+        );
+        let mut no_iterations = file.clone();
+        no_iterations[6..10].copy_from_slice(&[0u8; 4]);
+        assert_eq!(
+            load_frost_share::<Sha256>(&no_iterations, b"password"),
+            Err(KeystoreError::InvalidIterations(0))
+        );
+        let mut future_version = file;
+        future_version[4] = 2;
+        assert_eq!(
+            load_frost_share::<Sha256>(&future_version, b"password"),
+            Err(KeystoreError::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            load_frost_share::<Sha256>(b"not a keystore", b"password"),
+            Err(KeystoreError::NotAKeystore)
+        );
+    }
+
+    #[test]
+    fn kdf_params_range() {
+        assert_eq!(KdfParams::new(0), None);
+        assert_eq!(KdfParams::new(KdfParams::MIN_ITERATIONS - 1), None);
+        assert_eq!(KdfParams::new(KdfParams::MIN_ITERATIONS), Some(PARAMS));
+        assert!(KdfParams::new(KdfParams::MAX_ITERATIONS).is_some());
+        assert_eq!(KdfParams::new(u32::MAX), None);
+        assert!(KdfParams::new(KdfParams::default().iterations()).is_some());
+    }
+
+    #[test]
+    fn frost_share_generations() {
+        let frost = crate::frost::new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        let (index, secret_share) = shares.into_iter().next().unwrap();
+        let frost_key = frost_key.with_generation(KeyGeneration {
+            counter: 7,
+            parent: Some([3u8; 8]),
+        });
+        let file = save_frost_share::<Sha256>(
+            &frost_key,
+            index,
+            &secret_share,
+            b"password",
+            PARAMS,
+            &mut rand::thread_rng(),
+        );
+        let (loaded_key, _, _) = load_frost_share::<Sha256>(&file, b"password").unwrap();
+        assert_eq!(loaded_key.generation(), frost_key.generation());
+    }
+}
@@ -0,0 +1,610 @@
+//! Labeled JSON export of the public parts of a FROST key.
+//!
+//! Wallet coordinators and people reviewing a setup want to see the joint key, who the parties
+//! are and which keys have been derived from it without having to link this crate.
+//! [`LabelBundle`] writes these out as [BIP329] style JSON lines (one object per line, each with a
+//! `type`, a `ref` and a `label`) and reads them back strictly.
+//!
+//! # Format
+//!
+//! ```text
+//! {"type":"frost_key","ref":<point>,"label":<string>,"threshold":<uint>,"point_polynomial":[<point>,...]}
+//! {"type":"frost_party","ref":<scalar>,"label":<string>,"verification_share":<point>}
+//! {"type":"frost_derivation","ref":<point>,"label":<string>,"path":<string>,"tweak":<scalar>}
+//! ```
+//!
+//! - `<point>` is a 33 byte compressed point (33 zero bytes for the zero point) and `<scalar>` is
+//!   a 32 byte big-endian scalar, both in lowercase hex.
+//! - There is exactly one `frost_key` record and it comes first. Its `ref` is the joint public key
+//!   (the first coefficient of the polynomial).
+//! - The `ref` of a `frost_party` is the party's index.
+//! - The `ref` of a `frost_derivation` is the joint public key plus `tweak * G`. `path` is free-form
+//!   text (e.g. a BIP32 path) recording where `tweak` came from.
+//!
+//! Records are exported with their fields in the order above, no insignificant whitespace and a
+//! newline after each record. The importer accepts fields in any order but otherwise rejects
+//! anything it doesn't understand: unknown record types, unknown, missing or duplicate fields,
+//! blank lines and non-canonical hex. It also rejects records that don't agree with the key: the
+//! threshold must be the length of the polynomial, each verification share must be the polynomial
+//! evaluated at the party's index and each derivation's `ref` must be the tweaked key. A bundle
+//! that imports is therefore internally consistent but its labels are only as trustworthy as
+//! whoever wrote them.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     frost,
+//!     fun::{marker::*, Scalar},
+//!     labels::{Derivation, LabelBundle},
+//! };
+//! # let frost = frost::new_with_deterministic_nonces::<sha2::Sha256>();
+//! # let (frost_key, secret_shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+//! let bundle = LabelBundle {
+//!     frost_key,
+//!     label: "treasury".into(),
+//!     parties: secret_shares
+//!         .into_keys()
+//!         .zip(["alice", "bob", "carol"])
+//!         .map(|(index, name)| (index, name.into()))
+//!         .collect(),
+//!     derivations: vec![Derivation {
+//!         label: "deposit 0".into(),
+//!         path: "m/0/0".into(),
+//!         tweak: Scalar::random(&mut rand::thread_rng()).public().mark_zero(),
+//!     }],
+//! };
+//! let jsonl = bundle.to_jsonl();
+//! assert_eq!(jsonl.lines().count(), 5);
+//! assert_eq!(LabelBundle::from_jsonl(&jsonl), Ok(bundle));
+//! ```
+//!
+//! [BIP329]: https://github.com/bitcoin/bips/blob/master/bip-0329.mediawiki
+use crate::{
+    frost::{FrostKey, PartyIndex},
+    fun::{g, hex, marker::*, Point, Scalar, G},
+};
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::fmt::{self, Write};
+use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+use serde_json::Value;
+
+/// The public parts of a FROST key along with human readable labels.
+///
+/// See the [module documentation](crate::labels) for the format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelBundle {
+    /// The key. Tweaks applied to it are **not** exported. Record them in `derivations` instead.
+    pub frost_key: FrostKey<Normal>,
+    /// The label of the key.
+    pub label: String,
+    /// The label of each party.
+    pub parties: BTreeMap<PartyIndex, String>,
+    /// The keys derived from the key.
+    pub derivations: Vec<Derivation>,
+}
+
+/// A key derived from a [`LabelBundle`]'s key by adding `tweak * G` to it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Derivation {
+    /// The label of the derived key.
+    pub label: String,
+    /// Free-form description of where `tweak` came from e.g. a BIP32 path.
+    pub path: String,
+    /// The tweak that was added to the joint public key.
+    pub tweak: Scalar<Public, Zero>,
+}
+
+impl Derivation {
+    /// The derived public key given the untweaked joint public key.
+    pub fn public_key(&self, joint_public_key: Point) -> Point<Normal, Public, Zero> {
+        g!(joint_public_key + self.tweak * G).normalize()
+    }
+}
+
+impl LabelBundle {
+    /// Exports the bundle as JSON lines.
+    pub fn to_jsonl(&self) -> String {
+        let point_polynomial = self.frost_key.point_polynomial();
+        let joint_public_key = point_polynomial[0]
+            .non_zero()
+            .expect("first coefficient is non-zero");
+        let mut out = String::new();
+
+        out.push_str("{\"type\":\"frost_key\",\"ref\":");
+        push_hex(&mut out, joint_public_key.to_bytes().as_ref());
+        out.push_str(",\"label\":");
+        push_string(&mut out, &self.label);
+        write!(out, ",\"threshold\":{}", point_polynomial.len()).unwrap();
+        out.push_str(",\"point_polynomial\":[");
+        for (i, coefficient) in point_polynomial.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            push_hex(&mut out, coefficient.to_bytes().as_ref());
+        }
+        out.push_str("]}\n");
+
+        for (index, label) in &self.parties {
+            out.push_str("{\"type\":\"frost_party\",\"ref\":");
+            push_hex(&mut out, index.to_bytes().as_ref());
+            out.push_str(",\"label\":");
+            push_string(&mut out, label);
+            out.push_str(",\"verification_share\":");
+            let verification_share = self.frost_key.verification_share(index).normalize();
+            push_hex(&mut out, verification_share.to_bytes().as_ref());
+            out.push_str("}\n");
+        }
+
+        for derivation in &self.derivations {
+            out.push_str("{\"type\":\"frost_derivation\",\"ref\":");
+            let public_key = derivation.public_key(joint_public_key);
+            push_hex(&mut out, public_key.to_bytes().as_ref());
+            out.push_str(",\"label\":");
+            push_string(&mut out, &derivation.label);
+            out.push_str(",\"path\":");
+            push_string(&mut out, &derivation.path);
+            out.push_str(",\"tweak\":");
+            push_hex(&mut out, derivation.tweak.to_bytes().as_ref());
+            out.push_str("}\n");
+        }
+
+        out
+    }
+
+    /// Imports a bundle from JSON lines.
+    ///
+    /// The trailing newline after the last record is optional.
+    pub fn from_jsonl(jsonl: &str) -> Result<Self, LabelImportError> {
+        let mut lines = jsonl.strip_suffix('\n').unwrap_or(jsonl).split('\n');
+        let mut key_record = Record::parse(1, lines.next().unwrap_or(""))?;
+        if key_record.record_type != "frost_key" {
+            return Err(LabelImportError::MissingKey);
+        }
+        let public_key = key_record.take_point("ref")?;
+        let label = key_record.take_string("label")?;
+        let threshold = key_record.take_uint("threshold")?;
+        let point_polynomial = key_record
+            .take_array("point_polynomial")?
+            .iter()
+            .map(|coefficient| decode_point(coefficient).ok_or(key_record.invalid_value()))
+            .collect::<Result<Vec<_>, _>>()?;
+        key_record.finish()?;
+        if point_polynomial.first() != Some(&public_key)
+            || threshold != point_polynomial.len() as u64
+        {
+            return Err(LabelImportError::Inconsistent { line: 1 });
+        }
+        let frost_key = FrostKey::from_point_polynomial(point_polynomial)
+            .ok_or(LabelImportError::Inconsistent { line: 1 })?;
+        let joint_public_key = frost_key.public_key();
+
+        let mut parties = BTreeMap::new();
+        let mut derivations = vec![];
+        for (i, line) in lines.enumerate() {
+            let line_number = i + 2;
+            let mut record = Record::parse(line_number, line)?;
+            match record.record_type.as_str() {
+                "frost_key" => return Err(LabelImportError::DuplicateKey { line: line_number }),
+                "frost_party" => {
+                    let index = record.take_scalar("ref")?;
+                    let index = index.non_zero().ok_or(record.invalid_value())?;
+                    let label = record.take_string("label")?;
+                    let verification_share = record.take_point("verification_share")?;
+                    record.finish()?;
+                    if frost_key.verification_share(index).normalize() != verification_share {
+                        return Err(LabelImportError::Inconsistent { line: line_number });
+                    }
+                    if parties.insert(index, label).is_some() {
+                        return Err(LabelImportError::DuplicateParty { line: line_number });
+                    }
+                }
+                "frost_derivation" => {
+                    let public_key = record.take_point("ref")?;
+                    let derivation = Derivation {
+                        label: record.take_string("label")?,
+                        path: record.take_string("path")?,
+                        tweak: record.take_scalar("tweak")?,
+                    };
+                    record.finish()?;
+                    if derivation.public_key(joint_public_key) != public_key {
+                        return Err(LabelImportError::Inconsistent { line: line_number });
+                    }
+                    derivations.push(derivation);
+                }
+                _ => return Err(LabelImportError::UnknownType { line: line_number }),
+            }
+        }
+
+        Ok(LabelBundle {
+            frost_key,
+            label,
+            parties,
+            derivations,
+        })
+    }
+}
+
+/// Error importing a [`LabelBundle`].
+///
+/// Lines are numbered from `1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelImportError {
+    /// The line isn't a JSON object.
+    Syntax {
+        /// The line of the error.
+        line: usize,
+    },
+    /// The first record isn't a `frost_key`.
+    MissingKey,
+    /// There was a second `frost_key` record.
+    DuplicateKey {
+        /// The line of the error.
+        line: usize,
+    },
+    /// The record's `type` isn't one of the types in the format.
+    UnknownType {
+        /// The line of the error.
+        line: usize,
+    },
+    /// The record has a field that isn't in the format for its type.
+    UnknownField {
+        /// The line of the error.
+        line: usize,
+    },
+    /// The record is missing a field.
+    MissingField {
+        /// The line of the error.
+        line: usize,
+        /// The name of the missing field.
+        field: &'static str,
+    },
+    /// The record has the same field twice.
+    DuplicateField {
+        /// The line of the error.
+        line: usize,
+    },
+    /// A field has the wrong JSON type or doesn't encode a valid point or scalar.
+    InvalidValue {
+        /// The line of the error.
+        line: usize,
+    },
+    /// The record doesn't agree with the key.
+    Inconsistent {
+        /// The line of the error.
+        line: usize,
+    },
+    /// There was a second `frost_party` record for the same party.
+    DuplicateParty {
+        /// The line of the error.
+        line: usize,
+    },
+}
+
+impl fmt::Display for LabelImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use LabelImportError::*;
+        match self {
+            Syntax { line } => write!(f, "line {} is not a valid JSON record", line),
+            MissingKey => write!(f, "the first record is not a frost_key"),
+            DuplicateKey { line } => write!(f, "line {} is a second frost_key record", line),
+            UnknownType { line } => write!(f, "line {} has an unknown record type", line),
+            UnknownField { line } => write!(f, "line {} has an unknown field", line),
+            MissingField { line, field } => {
+                write!(f, "line {} is missing the field {}", line, field)
+            }
+            DuplicateField { line } => write!(f, "line {} has a duplicate field", line),
+            InvalidValue { line } => write!(f, "line {} has an invalid field value", line),
+            Inconsistent { line } => write!(f, "line {} does not match the key", line),
+            DuplicateParty { line } => write!(f, "line {} labels a party a second time", line),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LabelImportError {}
+
+/// The fields of a JSON object in the order they appear, duplicates included.
+struct Fields(Vec<(String, Value)>);
+
+impl<'de> Deserialize<'de> for Fields {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FieldsVisitor;
+
+        impl<'de> Visitor<'de> for FieldsVisitor {
+            type Value = Fields;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a JSON object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Fields, A::Error> {
+                let mut fields = vec![];
+                while let Some(field) = map.next_entry()? {
+                    fields.push(field);
+                }
+                Ok(Fields(fields))
+            }
+        }
+
+        deserializer.deserialize_map(FieldsVisitor)
+    }
+}
+
+/// A parsed line whose fields are removed as they are read.
+struct Record {
+    line: usize,
+    record_type: String,
+    fields: Vec<(String, Value)>,
+}
+
+impl Record {
+    fn parse(line: usize, text: &str) -> Result<Self, LabelImportError> {
+        let Fields(mut fields) =
+            serde_json::from_str(text).map_err(|_| LabelImportError::Syntax { line })?;
+        for (i, (name, _)) in fields.iter().enumerate() {
+            if fields[..i].iter().any(|(other, _)| other == name) {
+                return Err(LabelImportError::DuplicateField { line });
+            }
+        }
+        let record_type = match fields.iter().position(|(name, _)| name == "type") {
+            Some(i) => match fields.remove(i).1 {
+                Value::String(record_type) => record_type,
+                _ => return Err(LabelImportError::InvalidValue { line }),
+            },
+            None => {
+                return Err(LabelImportError::MissingField {
+                    line,
+                    field: "type",
+                })
+            }
+        };
+        Ok(Record {
+            line,
+            record_type,
+            fields,
+        })
+    }
+
+    fn invalid_value(&self) -> LabelImportError {
+        LabelImportError::InvalidValue { line: self.line }
+    }
+
+    fn take(&mut self, field: &'static str) -> Result<Value, LabelImportError> {
+        match self.fields.iter().position(|(name, _)| name == field) {
+            Some(i) => Ok(self.fields.remove(i).1),
+            None => Err(LabelImportError::MissingField {
+                line: self.line,
+                field,
+            }),
+        }
+    }
+
+    fn take_string(&mut self, field: &'static str) -> Result<String, LabelImportError> {
+        match self.take(field)? {
+            Value::String(string) => Ok(string),
+            _ => Err(self.invalid_value()),
+        }
+    }
+
+    fn take_uint(&mut self, field: &'static str) -> Result<u64, LabelImportError> {
+        match self.take(field)? {
+            Value::Number(number) => number.as_u64().ok_or(self.invalid_value()),
+            _ => Err(self.invalid_value()),
+        }
+    }
+
+    fn take_array(&mut self, field: &'static str) -> Result<Vec<String>, LabelImportError> {
+        match self.take(field)? {
+            Value::Array(array) => array
+                .into_iter()
+                .map(|element| match element {
+                    Value::String(string) => Ok(string),
+                    _ => Err(self.invalid_value()),
+                })
+                .collect(),
+            _ => Err(self.invalid_value()),
+        }
+    }
+
+    fn take_point(
+        &mut self,
+        field: &'static str,
+    ) -> Result<Point<Normal, Public, Zero>, LabelImportError> {
+        let string = self.take_string(field)?;
+        decode_point(&string).ok_or(self.invalid_value())
+    }
+
+    fn take_scalar(
+        &mut self,
+        field: &'static str,
+    ) -> Result<Scalar<Public, Zero>, LabelImportError> {
+        let string = self.take_string(field)?;
+        decode_hex(&string)
+            .and_then(Scalar::from_bytes)
+            .map(|scalar: Scalar<Secret, Zero>| scalar.public())
+            .ok_or(self.invalid_value())
+    }
+
+    fn finish(self) -> Result<(), LabelImportError> {
+        if self.fields.is_empty() {
+            Ok(())
+        } else {
+            Err(LabelImportError::UnknownField { line: self.line })
+        }
+    }
+}
+
+fn decode_hex<const N: usize>(string: &str) -> Option<[u8; N]> {
+    if !string
+        .bytes()
+        .all(|c| matches!(c, b'0'..=b'9' | b'a'..=b'f'))
+    {
+        return None;
+    }
+    hex::decode_array(string).ok()
+}
+
+fn decode_point(string: &str) -> Option<Point<Normal, Public, Zero>> {
+    decode_hex(string).and_then(Point::from_bytes)
+}
+
+fn push_hex(out: &mut String, bytes: &[u8]) {
+    out.push('"');
+    out.push_str(&hex::encode(bytes));
+    out.push('"');
+}
+
+fn push_string(out: &mut String, string: &str) {
+    out.push_str(&serde_json::to_string(string).expect("strings always serialize"));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frost;
+    use alloc::string::ToString;
+    use sha2::Sha256;
+
+    fn bundle() -> LabelBundle {
+        let frost = frost::new_with_deterministic_nonces::<Sha256>();
+        let (frost_key, _) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+        LabelBundle {
+            frost_key,
+            label: "cold \"storage\"\n\u{1} ✓".into(),
+            parties: (1..=3)
+                .map(|i| {
+                    (
+                        Scalar::from_non_zero_u32(i.try_into().unwrap()).public(),
+                        i.to_string(),
+                    )
+                })
+                .collect(),
+            derivations: vec![
+                Derivation {
+                    label: "receive".into(),
+                    path: "m/0/1".into(),
+                    tweak: Scalar::random(&mut rand::thread_rng()).public().mark_zero(),
+                },
+                Derivation {
+                    label: "untweaked".into(),
+                    path: "".into(),
+                    tweak: Scalar::zero(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let bundle = bundle();
+        let jsonl = bundle.to_jsonl();
+        assert!(jsonl.ends_with('\n'));
+        assert!(jsonl.contains(r#""label":"cold \"storage\"\n\u0001 ✓""#));
+        assert_eq!(LabelBundle::from_jsonl(&jsonl), Ok(bundle.clone()));
+        assert_eq!(
+            LabelBundle::from_jsonl(jsonl.trim_end()),
+            Ok(bundle.clone())
+        );
+
+        // field order and whitespace don't matter
+        let mut lines = jsonl.lines().map(String::from).collect::<Vec<_>>();
+        lines[1] = lines[1]
+            .replacen("{\"type\":\"frost_party\",", "{ ", 1)
+            .replacen('}', " ,\t\"type\" : \"frost_party\" }", 1);
+        let reordered = lines.join("\n");
+        assert_eq!(LabelBundle::from_jsonl(&reordered), Ok(bundle));
+    }
+
+    #[test]
+    fn strict_import() {
+        use LabelImportError::*;
+        let bundle = bundle();
+        let jsonl = bundle.to_jsonl();
+        let lines = jsonl.lines().collect::<Vec<_>>();
+        let with_line = |i: usize, replacement: &str| {
+            let mut lines = lines.clone();
+            lines[i] = replacement;
+            lines.join("\n")
+        };
+        let edit_line =
+            |i: usize, from: &str, to: &str| with_line(i, &lines[i].replacen(from, to, 1));
+
+        assert_eq!(LabelBundle::from_jsonl(""), Err(Syntax { line: 1 }));
+        assert_eq!(
+            LabelBundle::from_jsonl(&lines[1..].join("\n")),
+            Err(MissingKey)
+        );
+        assert_eq!(
+            LabelBundle::from_jsonl(&with_line(3, lines[0])),
+            Err(DuplicateKey { line: 4 })
+        );
+        assert_eq!(
+            LabelBundle::from_jsonl(&with_line(3, lines[2])),
+            Err(DuplicateParty { line: 4 })
+        );
+        assert_eq!(
+            LabelBundle::from_jsonl(&edit_line(1, "frost_party", "frost_partner")),
+            Err(UnknownType { line: 2 })
+        );
+        assert_eq!(
+            LabelBundle::from_jsonl(&edit_line(1, "}", ",\"origin\":\"\"}")),
+            Err(UnknownField { line: 2 })
+        );
+        assert_eq!(
+            LabelBundle::from_jsonl(&edit_line(1, "\"label\"", "\"name\"")),
+            Err(MissingField {
+                line: 2,
+                field: "label"
+            })
+        );
+        assert_eq!(
+            LabelBundle::from_jsonl(&edit_line(1, "}", ",\"label\":\"\"}")),
+            Err(DuplicateField { line: 2 })
+        );
+        assert_eq!(
+            LabelBundle::from_jsonl(&edit_line(0, "\"threshold\":2", "\"threshold\":3")),
+            Err(Inconsistent { line: 1 })
+        );
+        assert_eq!(
+            LabelBundle::from_jsonl(&edit_line(0, "\"threshold\":2", "\"threshold\":02")),
+            Err(Syntax { line: 1 })
+        );
+        assert_eq!(
+            LabelBundle::from_jsonl(&edit_line(0, "\"threshold\":2", "\"threshold\":\"2\"")),
+            Err(InvalidValue { line: 1 })
+        );
+        let tweak = hex::encode(&bundle.derivations[0].tweak.to_bytes());
+        assert_eq!(
+            LabelBundle::from_jsonl(&edit_line(4, &tweak, &tweak.to_uppercase())),
+            Err(InvalidValue { line: 5 })
+        );
+        let changed_tweak = edit_line(4, &tweak, &hex::encode(&[0u8; 32]));
+        assert_eq!(
+            LabelBundle::from_jsonl(&changed_tweak),
+            Err(Inconsistent { line: 5 })
+        );
+        let share = |i: u32| {
+            let index = Scalar::from_non_zero_u32(i.try_into().unwrap()).public();
+            hex::encode(
+                &bundle
+                    .frost_key
+                    .verification_share(index)
+                    .normalize()
+                    .to_bytes(),
+            )
+        };
+        assert_eq!(
+            LabelBundle::from_jsonl(&edit_line(2, &share(2), &share(1))),
+            Err(Inconsistent { line: 3 })
+        );
+        assert_eq!(
+            LabelBundle::from_jsonl(&format!("{}\n", jsonl)),
+            Err(Syntax { line: 7 })
+        );
+        assert_eq!(
+            LabelBundle::from_jsonl(&edit_line(1, "\"label\":\"1\"", "\"label\":\"\\ud800\"")),
+            Err(Syntax { line: 2 })
+        );
+    }
+}
@@ -26,14 +26,95 @@ pub mod musig;
 #[cfg(feature = "alloc")]
 pub mod frost;
 
+#[cfg(feature = "alloc")]
+pub mod batch;
+
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub mod half_agg;
+
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub mod beacon;
+
+#[cfg(feature = "calibrate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "calibrate")))]
+pub mod calibrate;
+
+#[cfg(feature = "alloc")]
+pub mod self_test;
+
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub mod policy;
+
 /// bech32m secret share backup scheme
 #[cfg(feature = "share_backup")]
 pub mod share_backup;
 
+#[cfg(feature = "wallets")]
+#[cfg_attr(docsrs, doc(cfg(feature = "wallets")))]
+pub mod wallets;
+
+#[cfg(feature = "cbor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+pub mod cbor;
+
+#[cfg(feature = "labels")]
+#[cfg_attr(docsrs, doc(cfg(feature = "labels")))]
+pub mod labels;
+
+#[cfg(feature = "keystore")]
+#[cfg_attr(docsrs, doc(cfg(feature = "keystore")))]
+pub mod keystore;
+
+#[cfg(feature = "did")]
+#[cfg_attr(docsrs, doc(cfg(feature = "did")))]
+pub mod did;
+
+#[cfg(feature = "cert")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cert")))]
+pub mod cert;
+
+#[cfg(feature = "audit")]
+#[cfg_attr(docsrs, doc(cfg(feature = "audit")))]
+pub mod audit;
+
+/// Low-level signing with caller supplied nonces. ☢
+#[cfg(feature = "hazmat")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hazmat")))]
+pub mod hazmat;
+
+pub mod prelude;
+
 mod signature;
 pub use signature::Signature;
+mod signing;
 pub mod adaptor;
+pub mod anti_exfil;
+pub mod attested_keygen;
+pub mod blind;
+pub mod domain;
+pub mod heapless;
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub mod journal;
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub mod nonce_audit;
+pub mod presign;
+pub mod public_inputs;
+#[cfg(feature = "unstable")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable")))]
+pub mod recoverable;
+pub mod remote;
+pub mod rerandomize;
 mod schnorr;
+pub mod sighash;
+pub mod sign_to_contract;
+pub mod taproot;
+pub mod verifier;
+pub mod vrf;
 pub use schnorr::*;
 mod message;
 pub use message::*;
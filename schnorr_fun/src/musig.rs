@@ -69,21 +69,43 @@
 //! However, we go "off-spec" in a few places especially with regards to nonce generation where we provide our own APIs (that
 //! at the time of writing are subject to change).
 //!
+//! ## Persisting sessions
+//!
+//! With the `serde` feature a [`SignSession`] and the public [`Nonce`]s can be stored between the
+//! nonce and signing rounds. Their encodings are checked against golden files in the tests so
+//! they can't change by accident. The [`AggKey`] isn't stored, recreate it from the keys. The
+//! [`NonceKeyPair`] is secret and must be stored as carefully as the secret key it's used with.
+//! It must never be loaded and used again after a partial signature has been made with it.
+//!
 //! [the excellent paper]: https://eprint.iacr.org/2020/1261.pdf
 //! [secp256k1-zkp]: https://github.com/ElementsProject/secp256k1-zkp/pull/131
+
+pub mod key_blinding;
+pub mod session_manager;
+pub mod session_salt;
+pub mod sig_backup;
+
 pub use crate::binonce::{Nonce, NonceKeyPair};
-use crate::{adaptor::EncryptedSignature, Message, Schnorr, Signature};
+use crate::{adaptor::EncryptedSignature, signing, Message, Schnorr, Signature};
 use alloc::vec::Vec;
 use secp256kfun::{
-    digest::{generic_array::typenum::U32, Digest},
+    bip32::{Xpub, BIP328_CHAIN_CODE},
+    digest::{
+        crypto_common::BlockSizeUser,
+        generic_array::typenum::{U32, U64},
+        Digest,
+    },
     g,
-    hash::{HashAdd, Tag},
+    hash::Tag,
     marker::*,
     nonce::{self, NoNonces, NonceGen},
     rand_core::{RngCore, SeedableRng},
     s, KeyPair, Point, Scalar, G,
 };
 
+/// The message tag used by [`MuSig::attest_partial_signature`].
+const PARTIAL_SIG_ATTESTATION_TAG: &str = "musig-partial-sig-attestation";
+
 /// The MuSig context.
 pub struct MuSig<H, NG> {
     /// The hash used to compress the key list to 32 bytes.
@@ -92,6 +114,12 @@ pub struct MuSig<H, NG> {
     coeff_hash: H,
     /// The hash used to generate the nonce coefficients.
     nonce_coeff_hash: H,
+    /// The hash used to derive key blinding factors.
+    key_blind_hash: H,
+    /// The hash used to commit to session salt contributions.
+    salt_commit_hash: H,
+    /// The hash used to combine salt contributions into the session salt.
+    session_salt_hash: H,
     /// The instance of the underlying Schnorr context.
     pub schnorr: Schnorr<H, NG>,
     /// The nonce generator used to
@@ -134,6 +162,9 @@ where
             pk_hash: H::default().tag(b"KeyAgg list"),
             coeff_hash: H::default().tag(b"KeyAgg coefficient"),
             nonce_coeff_hash: H::default().tag(b"MuSig/noncecoef"),
+            key_blind_hash: H::default().tag(b"MuSig/keyblind"),
+            salt_commit_hash: H::default().tag(b"MuSig/saltcommit"),
+            session_salt_hash: H::default().tag(b"MuSig/sessionsalt"),
             nonce_gen: schnorr.nonce_gen().clone().tag(b"MuSig"),
             schnorr,
         }
@@ -236,6 +267,33 @@ impl AggKey<Normal> {
             tweak,
         })
     }
+
+    /// The aggregate key as an extended public key with the [BIP328] chain code.
+    ///
+    /// Export this from the untweaked aggregate key. Anyone with it (e.g. a watch-only wallet with
+    /// a descriptor) can derive the same children as [`derive_bip32`] does.
+    ///
+    /// [BIP328]: https://bips.xyz/328
+    /// [`derive_bip32`]: Self::derive_bip32
+    pub fn xpub(&self) -> Xpub {
+        Xpub::new(self.agg_key, BIP328_CHAIN_CODE)
+    }
+
+    /// Derives the unhardened child aggregate key at `path` from [`xpub`] so that the original
+    /// signers can sign under it.
+    ///
+    /// Returns the child key along with its extended public key. Every participant that derives
+    /// the same `path` from the same aggregate key gets the same child. `H` must be SHA512 to match
+    /// other BIP32 implementations. Returns `None` if any index is hardened or a child is invalid.
+    ///
+    /// [`xpub`]: Self::xpub
+    pub fn derive_bip32<H>(self, path: &[u32]) -> Option<(Self, Xpub)>
+    where
+        H: BlockSizeUser + Digest<OutputSize = U64> + Default + Clone,
+    {
+        let (child, tweak) = self.xpub().derive_path::<H>(path)?;
+        Some((self.tweak(tweak)?, child))
+    }
 }
 
 // /// A [`AggKey`] that has been converted into a [BIP340] x-only key.
@@ -290,27 +348,8 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> MuSig<H, NG> {
     /// let agg_key = musig.new_agg_key(vec![their_public_key, my_public_key]);
     /// ```
     pub fn new_agg_key(&self, keys: Vec<Point>) -> AggKey<Normal> {
-        let coeff_hash = {
-            let L = self.pk_hash.clone().add(&keys[..]).finalize();
-            self.coeff_hash.clone().add(L.as_slice())
-        };
-
-        let mut second = None;
-        let coefs = keys
-            .iter()
-            .map(|key| {
-                // This is the logic for IsSecond from appendix B of the MuSig2 paper
-                if second.is_none() && key != &keys[0] {
-                    second = Some(key);
-                }
-                if second != Some(key) {
-                    Scalar::from_hash(coeff_hash.clone().add(key))
-                } else {
-                    Scalar::one()
-                }
-                .public()
-            })
-            .collect::<Vec<_>>();
+        let coefs =
+            signing::musig_key_coeffs(&self.pk_hash, &self.coeff_hash, &keys).collect::<Vec<_>>();
 
         let agg_key = g!(&coefs .* &keys)
             .non_zero().expect("computationally unreachable: linear combination of hash randomised points cannot add to zero");
@@ -323,6 +362,203 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> MuSig<H, NG> {
             needs_negation: false,
         }
     }
+
+    /// Recomputes an aggregate key from its constituent `keys` and `tweaks` and checks it against
+    /// `claimed_key`.
+    ///
+    /// This lets a third party (e.g. a custodian's auditor) check that a key (or the output key of
+    /// a taproot address) really belongs to a claimed set of signers. The returned [`AggKeyAudit`]
+    /// records each step of the aggregation and its [`Display`] implementation prints them as a
+    /// human-readable audit trail.
+    ///
+    /// The `tweaks` are applied in order in the same way that [`AggKey::tweak`],
+    /// [`AggKey::into_xonly_key`] and the x-only [`AggKey::tweak`] would apply them. Once the key
+    /// has been converted to x-only every following tweak is an x-only tweak.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use schnorr_fun::{
+    ///     fun::{marker::*, Scalar},
+    ///     musig::{self, KeyTweak},
+    /// };
+    /// let musig = musig::new_with_deterministic_nonces::<sha2::Sha256>();
+    /// # let keys = (0..3).map(|_| musig.new_keypair(Scalar::random(&mut rand::thread_rng())).public_key()).collect::<Vec<_>>();
+    /// let taproot_tweak = Scalar::<Public, Zero>::from(42);
+    /// let agg_key = musig
+    ///     .new_agg_key(keys.clone())
+    ///     .into_xonly_key()
+    ///     .tweak(taproot_tweak)
+    ///     .unwrap();
+    /// let audit = musig.audit_agg_key(
+    ///     keys,
+    ///     &[KeyTweak::XOnly, KeyTweak::Tweak(taproot_tweak)],
+    ///     agg_key.agg_public_key(),
+    /// );
+    /// assert!(audit.matches());
+    /// println!("{}", audit);
+    /// ```
+    ///
+    /// [`Display`]: core::fmt::Display
+    pub fn audit_agg_key<T: Normalized>(
+        &self,
+        keys: Vec<Point>,
+        tweaks: &[KeyTweak],
+        claimed_key: Point<T>,
+    ) -> AggKeyAudit {
+        let (steps, computed_key) = self.recompute_agg_key(keys, tweaks);
+        AggKeyAudit {
+            steps,
+            claimed_key: claimed_key.to_bytes(),
+            matches: computed_key == Some(claimed_key.to_bytes()),
+            computed_key,
+        }
+    }
+
+    fn recompute_agg_key(
+        &self,
+        keys: Vec<Point>,
+        tweaks: &[KeyTweak],
+    ) -> (Vec<AuditStep>, Option<[u8; 33]>) {
+        let agg_key = self.new_agg_key(keys);
+        let mut steps = agg_key
+            .keys
+            .iter()
+            .zip(&agg_key.coefs)
+            .enumerate()
+            .map(|(index, (&key, &coefficient))| AuditStep::Key {
+                index,
+                key,
+                coefficient,
+            })
+            .collect::<Vec<_>>();
+        steps.push(AuditStep::Aggregate {
+            key: agg_key.agg_public_key(),
+        });
+
+        enum State {
+            Normal(AggKey<Normal>),
+            XOnly(AggKey<EvenY>),
+        }
+
+        let mut state = Some(State::Normal(agg_key));
+        for tweak in tweaks {
+            state = match (state, *tweak) {
+                (None, _) => None,
+                (Some(State::Normal(agg_key)), KeyTweak::XOnly) => {
+                    let agg_key = agg_key.into_xonly_key();
+                    steps.push(AuditStep::XOnly {
+                        key: agg_key.agg_public_key(),
+                        negated: agg_key.needs_negation,
+                    });
+                    Some(State::XOnly(agg_key))
+                }
+                (Some(State::XOnly(agg_key)), KeyTweak::XOnly) => {
+                    steps.push(AuditStep::XOnly {
+                        key: agg_key.agg_public_key(),
+                        negated: false,
+                    });
+                    Some(State::XOnly(agg_key))
+                }
+                (Some(State::Normal(agg_key)), KeyTweak::Tweak(tweak)) => {
+                    match agg_key.tweak(tweak) {
+                        Some(agg_key) => {
+                            steps.push(AuditStep::Tweak {
+                                tweak,
+                                key: agg_key.agg_public_key().normalize(),
+                            });
+                            Some(State::Normal(agg_key))
+                        }
+                        None => {
+                            steps.push(AuditStep::InvalidTweak { tweak });
+                            None
+                        }
+                    }
+                }
+                (Some(State::XOnly(agg_key)), KeyTweak::Tweak(tweak)) => {
+                    let was_negated = agg_key.needs_negation;
+                    match agg_key.tweak(tweak) {
+                        Some(agg_key) => {
+                            steps.push(AuditStep::XOnlyTweak {
+                                tweak,
+                                key: agg_key.agg_public_key(),
+                                negated: agg_key.needs_negation != was_negated,
+                            });
+                            Some(State::XOnly(agg_key))
+                        }
+                        None => {
+                            steps.push(AuditStep::InvalidTweak { tweak });
+                            None
+                        }
+                    }
+                }
+            };
+        }
+
+        let computed_key = state.map(|state| match state {
+            State::Normal(agg_key) => agg_key.agg_public_key().to_bytes(),
+            State::XOnly(agg_key) => agg_key.agg_public_key().to_bytes(),
+        });
+
+        (steps, computed_key)
+    }
+
+    /// Records what an aggregate key is derived from so it can be checked again later with
+    /// [`check_provenance`](Self::check_provenance).
+    ///
+    /// Returns `None` if one of the `tweaks` is invalid.
+    pub fn record_provenance(
+        &self,
+        keys: Vec<Point>,
+        tweaks: Vec<KeyTweak>,
+    ) -> Option<AggKeyProvenance> {
+        let (_, computed_key) = self.recompute_agg_key(keys.clone(), &tweaks);
+        let recorded_key = Point::from_bytes(computed_key?)?;
+        Some(AggKeyProvenance {
+            keys,
+            tweaks,
+            recorded_key,
+        })
+    }
+
+    /// Recomputes every key in `records` and compares it to the recorded key.
+    ///
+    /// Run this against the stored provenance of every key you depend on before deploying a new
+    /// version of this library to make sure the upgrade doesn't change any key (and therefore any
+    /// address).
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use schnorr_fun::{
+    ///     fun::{marker::*, Point, Scalar},
+    ///     musig::{self, KeyTweak},
+    /// };
+    /// let musig = musig::new_with_deterministic_nonces::<sha2::Sha256>();
+    /// let keys = vec![
+    ///     Point::random(&mut rand::thread_rng()),
+    ///     Point::random(&mut rand::thread_rng()),
+    /// ];
+    /// let taproot_tweak = Scalar::<Public, Zero>::from(42);
+    /// // when the key is created
+    /// let provenance = musig
+    ///     .record_provenance(keys, vec![KeyTweak::XOnly, KeyTweak::Tweak(taproot_tweak)])
+    ///     .unwrap();
+    /// // ...store the provenance (e.g. with serde) and then after upgrading
+    /// let report = musig.check_provenance(&[provenance]);
+    /// assert!(report.all_match());
+    /// println!("{}", report);
+    /// ```
+    pub fn check_provenance(&self, records: &[AggKeyProvenance]) -> ProvenanceReport {
+        ProvenanceReport {
+            audits: records
+                .iter()
+                .map(|record| {
+                    self.audit_agg_key(record.keys.clone(), &record.tweaks, record.recorded_key)
+                })
+                .collect(),
+        }
+    }
 }
 
 impl<H, NG> MuSig<H, NG>
@@ -376,6 +612,231 @@ where
         );
         rng
     }
+
+    /// Attest to the `partial_sig` we produced for `session`.
+    ///
+    /// The attestation is a signature with our `keypair` over the partial signature and every nonce
+    /// in the session. Send it along with the partial signature so that if it turns out to be
+    /// invalid a [`PartialSignatureBlame`] can be produced that a third party can check. Without it
+    /// anyone could produce a blame against us by making up a session with nonces we never sent.
+    pub fn attest_partial_signature(
+        &self,
+        session: &SignSession,
+        my_index: usize,
+        keypair: &KeyPair,
+        partial_sig: Scalar<Public, Zero>,
+    ) -> Signature {
+        let message_data = partial_sig_attestation_message(session, my_index, partial_sig);
+        let keypair = self.schnorr.new_keypair(*keypair.secret_key());
+        self.schnorr.sign(
+            &keypair,
+            Message::<Public>::plain(PARTIAL_SIG_ATTESTATION_TAG, &message_data),
+        )
+    }
+}
+
+/// A tweak applied to an aggregate key for [`MuSig::audit_agg_key`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub enum KeyTweak {
+    /// Add `tweak * G` to the key like [`AggKey::tweak`] (or the x-only [`AggKey::tweak`] if the
+    /// key has already been converted to x-only).
+    Tweak(Scalar<Public, Zero>),
+    /// Convert the key to x-only like [`AggKey::into_xonly_key`].
+    XOnly,
+}
+
+/// A step in recomputing an aggregate key recorded by [`MuSig::audit_agg_key`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuditStep {
+    /// A constituent key and the coefficient it is multiplied by in the aggregation.
+    Key {
+        /// The position of the key in the list of keys.
+        index: usize,
+        /// The constituent key.
+        key: Point,
+        /// The key aggregation coefficient.
+        coefficient: Scalar<Public>,
+    },
+    /// The sum of the constituent keys multiplied by their coefficients.
+    Aggregate {
+        /// The untweaked aggregate key.
+        key: Point,
+    },
+    /// An ordinary tweak was added to the key.
+    Tweak {
+        /// The tweak.
+        tweak: Scalar<Public, Zero>,
+        /// The key after the tweak.
+        key: Point,
+    },
+    /// The key was converted to x-only.
+    XOnly {
+        /// The x-only key.
+        key: Point<EvenY>,
+        /// Whether the key had to be negated to have an even y-coordinate.
+        negated: bool,
+    },
+    /// An x-only tweak was added to the key.
+    XOnlyTweak {
+        /// The tweak.
+        tweak: Scalar<Public, Zero>,
+        /// The x-only key after the tweak.
+        key: Point<EvenY>,
+        /// Whether the tweaked key had to be negated to have an even y-coordinate.
+        negated: bool,
+    },
+    /// The tweak was the negation of the key so the result was the point at infinity. No further
+    /// steps were applied.
+    InvalidTweak {
+        /// The tweak.
+        tweak: Scalar<Public, Zero>,
+    },
+}
+
+impl core::fmt::Display for AuditStep {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let negated = |negated: bool| if negated { " (negated)" } else { "" };
+        match self {
+            AuditStep::Key {
+                index,
+                key,
+                coefficient,
+            } => write!(f, "key {}: {} with coefficient {}", index, key, coefficient),
+            AuditStep::Aggregate { key } => write!(f, "aggregate key: {}", key),
+            AuditStep::Tweak { tweak, key } => write!(f, "tweak by {}: {}", tweak, key),
+            AuditStep::XOnly { key, negated: n } => {
+                write!(f, "x-only key: {}{}", key, negated(*n))
+            }
+            AuditStep::XOnlyTweak {
+                tweak,
+                key,
+                negated: n,
+            } => write!(f, "x-only tweak by {}: {}{}", tweak, key, negated(*n)),
+            AuditStep::InvalidTweak { tweak } => {
+                write!(
+                    f,
+                    "tweak by {}: INVALID (result is the point at infinity)",
+                    tweak
+                )
+            }
+        }
+    }
+}
+
+/// The result of [`MuSig::audit_agg_key`].
+///
+/// Its [`Display`] implementation writes out every step of the aggregation one per line followed
+/// by the claimed key and whether it matched.
+///
+/// [`Display`]: core::fmt::Display
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggKeyAudit {
+    steps: Vec<AuditStep>,
+    claimed_key: [u8; 33],
+    matches: bool,
+    computed_key: Option<[u8; 33]>,
+}
+
+impl AggKeyAudit {
+    /// Whether the recomputed key is the claimed key.
+    pub fn matches(&self) -> bool {
+        self.matches
+    }
+
+    /// The steps taken to recompute the key.
+    pub fn steps(&self) -> &[AuditStep] {
+        &self.steps
+    }
+
+    /// The encoding of the recomputed key (`None` if a tweak was invalid).
+    pub fn computed_key(&self) -> Option<[u8; 33]> {
+        self.computed_key
+    }
+}
+
+impl core::fmt::Display for AggKeyAudit {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "{}", step)?;
+        }
+        write!(f, "claimed key: ")?;
+        for byte in self.claimed_key {
+            write!(f, "{:02x}", byte)?;
+        }
+        writeln!(f)?;
+        write!(
+            f,
+            "result: {}",
+            if self.matches { "MATCH" } else { "MISMATCH" }
+        )
+    }
+}
+
+/// What an aggregate key was derived from, recorded with [`MuSig::record_provenance`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct AggKeyProvenance {
+    /// The constituent keys in the order they were aggregated.
+    pub keys: Vec<Point>,
+    /// The tweaks applied to the aggregate key in order.
+    pub tweaks: Vec<KeyTweak>,
+    /// The key that was derived when the provenance was recorded.
+    pub recorded_key: Point,
+}
+
+/// The result of [`MuSig::check_provenance`].
+///
+/// Its [`Display`] implementation summarizes how many keys were checked and writes out the full
+/// audit of every key that didn't match.
+///
+/// [`Display`]: core::fmt::Display
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProvenanceReport {
+    audits: Vec<AggKeyAudit>,
+}
+
+impl ProvenanceReport {
+    /// Whether every recomputed key matched its recorded key.
+    pub fn all_match(&self) -> bool {
+        self.audits.iter().all(AggKeyAudit::matches)
+    }
+
+    /// The audit of each record in the order they were checked.
+    pub fn audits(&self) -> &[AggKeyAudit] {
+        &self.audits
+    }
+
+    /// The index and audit of every record whose key didn't match.
+    pub fn mismatches(&self) -> impl Iterator<Item = (usize, &AggKeyAudit)> + '_ {
+        self.audits
+            .iter()
+            .enumerate()
+            .filter(|(_, audit)| !audit.matches())
+    }
+}
+
+impl core::fmt::Display for ProvenanceReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "checked {} keys: {} mismatches",
+            self.audits.len(),
+            self.mismatches().count()
+        )?;
+        for (index, audit) in self.mismatches() {
+            write!(f, "\n\nrecord {}:\n{}", index, audit)?;
+        }
+        Ok(())
+    }
 }
 
 /// Marker type for indicating the [`SignSession`] is being used to create an ordinary Schnorr
@@ -418,7 +879,7 @@ pub struct Adaptor {
 /// [`start_sign_session`]: MuSig::start_sign_session
 /// [`start_encrypted_sign_session`]: MuSig::start_encrypted_sign_session
 /// [`sign`]: MuSig::sign
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Clone, PartialEq)]
 #[cfg_attr(
     feature = "serde",
     derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
@@ -438,10 +899,73 @@ pub struct SignSession<T = Ordinary> {
     signing_type: T,
 }
 
+impl<T: core::fmt::Debug> core::fmt::Debug for SignSession<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("SignSession")
+            .field("n_signers", &self.public_nonces.len())
+            .field("agg_nonce", &self.R)
+            .field("challenge", &self.c)
+            .field("signing_type", &self.signing_type)
+            .finish()
+    }
+}
+
+/// Proof that a party produced an invalid partial signature in an ordinary signing session.
+///
+/// An arbiter that knows the [`AggKey`] and the message being signed can check it with
+/// [`MuSig::verify_partial_signature_blame`] without any session secrets. The accused party's
+/// attestation (see [`MuSig::attest_partial_signature`]) shows that they sent the partial signature
+/// for a session with exactly these nonces.
+///
+/// Created with [`MuSig::blame_partial_signature`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+pub struct PartialSignatureBlame {
+    /// The index of the accused party's key in the [`AggKey`].
+    pub index: usize,
+    /// The invalid partial signature.
+    pub partial_sig: Scalar<Public, Zero>,
+    /// The public nonces of every party in the session (as they were passed to
+    /// [`MuSig::start_sign_session`]).
+    pub nonces: Vec<Nonce>,
+    /// The accused party's attestation to the partial signature and nonces.
+    pub attestation: Signature,
+}
+
+fn partial_sig_attestation_message<T>(
+    session: &SignSession<T>,
+    index: usize,
+    partial_sig: Scalar<Public, Zero>,
+) -> Vec<u8> {
+    // the challenge commits to the message and the aggregate nonce, the nonces to who else signed
+    let mut message_data = (index as u32).to_be_bytes().to_vec();
+    message_data.extend_from_slice(&partial_sig.to_bytes());
+    message_data.extend_from_slice(&session.c.to_bytes());
+    for nonce in &session.public_nonces {
+        message_data.extend_from_slice(&nonce.to_bytes());
+    }
+    message_data
+}
+
 impl<H: Digest<OutputSize = U32> + Clone, NG> MuSig<H, NG> {
     /// Start a signing session.
     ///
-    /// You must provide the public nonces for this signing session in the correct order.
+    /// You must provide the public nonces for this signing session in the correct order i.e. the
+    /// `i`th nonce must be from the party with the `i`th key in `agg_key`. The order only
+    /// determines whose partial signature is checked against which nonce. The aggregate nonce
+    /// (and so the session) is the same for any order since it is computed with
+    /// [`Nonce::aggregate`] as specified in [BIP327].
+    ///
+    /// [BIP327]: https://github.com/bitcoin/bips/blob/master/bip-0327.mediawiki
     ///
     /// ## Return Value
     ///
@@ -530,30 +1054,17 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> MuSig<H, NG> {
         bool,
     ) {
         let mut Rs = nonces;
-        let agg_Rs = Rs.iter().fold([Point::zero(); 2], |acc, nonce| {
-            [g!(acc[0] + nonce.0[0]), g!(acc[1] + nonce.0[1])]
-        });
-        let agg_Rs = Nonce::<Zero>([
-            g!(agg_Rs[0] + encryption_key).normalize(),
-            agg_Rs[1].normalize(),
-        ]);
-
-        let b = {
-            let H = self.nonce_coeff_hash.clone();
-            Scalar::from_hash(
-                H.add(agg_Rs.to_bytes())
-                    .add(agg_key.agg_public_key())
-                    .add(message),
-            )
-        }
-        .public()
-        .mark_zero();
+        let agg_Rs = Nonce::aggregate(Rs.iter().copied());
+        let agg_Rs = Nonce::<Zero>([g!(agg_Rs.0[0] + encryption_key).normalize(), agg_Rs.0[1]]);
 
-        let (R, r_needs_negation) = g!(agg_Rs.0[0] + b * agg_Rs.0[1])
-            .normalize()
-            .non_zero()
-            .unwrap_or(Point::generator())
-            .into_point_with_even_y();
+        let b = signing::musig_binding_coeff(
+            &self.nonce_coeff_hash,
+            &agg_Rs,
+            agg_key.agg_public_key(),
+            message,
+        );
+
+        let (R, r_needs_negation) = signing::session_nonce(g!(agg_Rs.0[0] + b * agg_Rs.0[1]));
 
         for R_i in &mut Rs {
             R_i.conditional_negate(r_needs_negation);
@@ -580,16 +1091,15 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> MuSig<H, NG> {
             agg_key.keys().nth(my_index).unwrap(),
             "key at index {my_index} didn't match",
         );
-        let c = session.c;
-        let b = session.b;
-        let x_i = keypair.secret_key();
-        let mut a = agg_key.coefs[my_index];
-
-        a.conditional_negate(agg_key.needs_negation);
-        let [mut r1, mut r2] = local_secret_nonce.secret;
-        r1.conditional_negate(session.nonce_needs_negation);
-        r2.conditional_negate(session.nonce_needs_negation);
-        s!(c * a * x_i + r1 + b * r2).public()
+        signing::sign(
+            &session.c,
+            &session.b,
+            agg_key.coefs[my_index],
+            agg_key.needs_negation,
+            session.nonce_needs_negation,
+            keypair.secret_key(),
+            local_secret_nonce,
+        )
     }
 
     #[must_use]
@@ -607,19 +1117,120 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> MuSig<H, NG> {
         index: usize,
         partial_sig: Scalar<Public, Zero>,
     ) -> bool {
-        let c = session.c;
-        let b = session.b;
-        let s_i = &partial_sig;
-        let a = agg_key.coefs[index];
-
-        let X_i = agg_key
-            .keys()
-            .nth(index)
-            .unwrap()
-            .conditional_negate(agg_key.needs_negation);
+        // the session's nonces have already been negated
+        signing::verify(
+            &session.c,
+            &session.b,
+            agg_key.coefs[index],
+            agg_key.needs_negation,
+            false,
+            &agg_key.keys[index],
+            &session.public_nonces[index],
+            partial_sig,
+        )
+    }
 
-        let [R1, R2] = &session.public_nonces[index].0;
-        g!((c * a) * X_i + R1 + b * R2 - s_i * G).is_zero()
+    /// Check the attestation the party at `index` sent with their `partial_sig`.
+    ///
+    /// See [`attest_partial_signature`] for what it attests to.
+    ///
+    /// [`attest_partial_signature`]: Self::attest_partial_signature
+    ///
+    /// # Panics
+    ///
+    /// Panics when `index` is equal to or greater than the number of keys in the agg_key.
+    pub fn verify_partial_signature_attestation(
+        &self,
+        agg_key: &AggKey<EvenY>,
+        session: &SignSession,
+        index: usize,
+        partial_sig: Scalar<Public, Zero>,
+        attestation: &Signature,
+    ) -> bool {
+        let key = agg_key.keys().nth(index).unwrap();
+        let message_data = partial_sig_attestation_message(session, index, partial_sig);
+        self.schnorr.verify(
+            &key.into_point_with_even_y().0,
+            Message::<Public>::plain(PARTIAL_SIG_ATTESTATION_TAG, &message_data),
+            attestation,
+        )
+    }
+
+    /// Produce a [`PartialSignatureBlame`] against the party at `index` if `partial_sig` is
+    /// invalid.
+    ///
+    /// The `attestation` is the one the accused sent along with their partial signature (see
+    /// [`attest_partial_signature`]). Without it nothing stops us from blaming an honest party by
+    /// swapping out their nonce for one they never sent.
+    ///
+    /// Returns `None` if the partial signature is actually valid or if the `attestation` isn't
+    /// valid for it. In the latter case you can't prove anything but you should still exclude the
+    /// party.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `index` is equal to or greater than the number of keys in the agg_key.
+    ///
+    /// [`attest_partial_signature`]: Self::attest_partial_signature
+    pub fn blame_partial_signature(
+        &self,
+        agg_key: &AggKey<EvenY>,
+        session: &SignSession,
+        index: usize,
+        partial_sig: Scalar<Public, Zero>,
+        attestation: Signature,
+    ) -> Option<PartialSignatureBlame> {
+        if self.verify_partial_signature(agg_key, session, index, partial_sig)
+            || !self.verify_partial_signature_attestation(
+                agg_key,
+                session,
+                index,
+                partial_sig,
+                &attestation,
+            )
+        {
+            return None;
+        }
+        let mut nonces = session.public_nonces.clone();
+        for nonce in &mut nonces {
+            nonce.conditional_negate(session.nonce_needs_negation);
+        }
+        Some(PartialSignatureBlame {
+            index,
+            partial_sig,
+            nonces,
+            attestation,
+        })
+    }
+
+    /// Check a [`PartialSignatureBlame`] as a third party.
+    ///
+    /// The session is recomputed from `agg_key`, `message` and the nonces in the blame so nothing
+    /// from the signing session needs to be trusted. The accused's attestation binds them to that
+    /// session so the blame can only be valid if they really sent the invalid partial signature for
+    /// it.
+    ///
+    /// ## Return value
+    ///
+    /// Returns `true` if the blamed partial signature really is invalid.
+    pub fn verify_partial_signature_blame(
+        &self,
+        agg_key: &AggKey<EvenY>,
+        message: Message<'_, Public>,
+        blame: &PartialSignatureBlame,
+    ) -> bool {
+        let n_keys = agg_key.keys().count();
+        if blame.nonces.len() != n_keys || blame.index >= n_keys {
+            return false;
+        }
+        let session = self.start_sign_session(agg_key, blame.nonces.clone(), message);
+        self.verify_partial_signature_attestation(
+            agg_key,
+            &session,
+            blame.index,
+            blame.partial_sig,
+            &blame.attestation,
+        ) && !self.verify_partial_signature(agg_key, &session, blame.index, blame.partial_sig)
     }
 
     /// Combines all the partial signatures into a single `Signature`.
@@ -666,17 +1277,167 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> MuSig<H, NG> {
         session: &SignSession<T>,
         partial_sigs: impl IntoIterator<Item = Scalar<Public, Zero>>,
     ) -> (Point<EvenY>, Scalar<Public, Zero>) {
-        let sum_s = partial_sigs
-            .into_iter()
-            .reduce(|acc, s| s!(acc + s).public())
-            .unwrap_or(Scalar::zero());
+        (
+            session.R,
+            signing::combine(&session.c, &agg_key.tweak, partial_sigs),
+        )
+    }
 
-        let s = s!(sum_s + agg_key.tweak * session.c).public();
+    /// Start combining partial signatures one at a time as they arrive.
+    ///
+    /// See [`PartialSignatureAggregator`].
+    pub fn partial_signature_aggregator<'a, T>(
+        &'a self,
+        agg_key: &'a AggKey<EvenY>,
+        session: &'a SignSession<T>,
+    ) -> PartialSignatureAggregator<'a, H, NG, T> {
+        PartialSignatureAggregator {
+            musig: self,
+            agg_key,
+            session,
+            partial_sigs: vec![None; session.public_nonces.len()],
+        }
+    }
+}
 
-        (session.R, s)
+/// Verifies partial signatures as they arrive and combines them once there is one from every party.
+///
+/// Created with [`MuSig::partial_signature_aggregator`]. An invalid partial signature is caught as
+/// soon as it's [`add`]ed so you know who to blame without waiting for the others. Adding the same
+/// partial signature again is a no-op so redelivered messages can be passed straight in.
+///
+/// # Example
+///
+/// ```
+/// use schnorr_fun::{fun::Scalar, musig, Message};
+/// let musig = musig::new_with_deterministic_nonces::<sha2::Sha256>();
+/// let keypair = musig.new_keypair(Scalar::random(&mut rand::thread_rng()));
+/// let agg_key = musig
+///     .new_agg_key(vec![keypair.public_key()])
+///     .into_xonly_key();
+/// let nonce = musig::NonceKeyPair::random(&mut rand::thread_rng());
+/// let message = Message::plain("my-app", b"hello");
+/// let session = musig.start_sign_session(&agg_key, vec![nonce.public()], message);
+/// let partial_sig = musig.sign(&agg_key, &session, 0, &keypair, nonce);
+/// let mut aggregator = musig.partial_signature_aggregator(&agg_key, &session);
+/// aggregator.add(0, partial_sig)?;
+/// let signature = aggregator.finish()?;
+/// assert!(musig
+///     .schnorr
+///     .verify(&agg_key.agg_public_key(), message, &signature));
+/// # Ok::<(), musig::PartialSignatureError>(())
+/// ```
+///
+/// [`add`]: Self::add
+pub struct PartialSignatureAggregator<'a, H, NG, T = Ordinary> {
+    musig: &'a MuSig<H, NG>,
+    agg_key: &'a AggKey<EvenY>,
+    session: &'a SignSession<T>,
+    partial_sigs: Vec<Option<Scalar<Public, Zero>>>,
+}
+
+impl<'a, H: Digest<OutputSize = U32> + Clone, NG, T> PartialSignatureAggregator<'a, H, NG, T> {
+    /// Verifies and adds the partial signature from the party at `index`.
+    ///
+    /// Adding the partial signature already added for `index` does nothing. If it's invalid or
+    /// conflicts with the one already added it is not added and the aggregator can still be used.
+    pub fn add(
+        &mut self,
+        index: usize,
+        partial_sig: Scalar<Public, Zero>,
+    ) -> Result<(), PartialSignatureError> {
+        match self.partial_sigs.get(index) {
+            None => return Err(PartialSignatureError::UnknownIndex(index)),
+            Some(Some(existing)) if *existing == partial_sig => return Ok(()),
+            Some(Some(_)) => return Err(PartialSignatureError::Conflicting(index)),
+            Some(None) => {}
+        }
+        if !self
+            .musig
+            .verify_partial_signature(self.agg_key, self.session, index, partial_sig)
+        {
+            return Err(PartialSignatureError::Invalid(index));
+        }
+        self.partial_sigs[index] = Some(partial_sig);
+        Ok(())
+    }
+
+    /// The indices of the parties whose partial signatures haven't been added yet.
+    pub fn missing(&self) -> impl Iterator<Item = usize> + '_ {
+        self.partial_sigs
+            .iter()
+            .enumerate()
+            .filter(|(_, partial_sig)| partial_sig.is_none())
+            .map(|(index, _)| index)
+    }
+
+    fn _finish(&self) -> Result<(Point<EvenY>, Scalar<Public, Zero>), PartialSignatureError> {
+        if let Some(index) = self.missing().next() {
+            return Err(PartialSignatureError::Missing(index));
+        }
+        Ok(self.musig._combine_partial_signatures(
+            self.agg_key,
+            self.session,
+            self.partial_sigs.iter().flatten().copied(),
+        ))
     }
 }
 
+impl<'a, H: Digest<OutputSize = U32> + Clone, NG> PartialSignatureAggregator<'a, H, NG, Ordinary> {
+    /// Combines the partial signatures into a signature.
+    ///
+    /// Returns an error if a partial signature hasn't been added for every party.
+    pub fn finish(self) -> Result<Signature, PartialSignatureError> {
+        let (R, s) = self._finish()?;
+        Ok(Signature { R, s })
+    }
+}
+
+impl<'a, H: Digest<OutputSize = U32> + Clone, NG> PartialSignatureAggregator<'a, H, NG, Adaptor> {
+    /// Combines the partial encrypted signatures into an encrypted signature.
+    ///
+    /// Returns an error if a partial encrypted signature hasn't been added for every party.
+    pub fn finish(self) -> Result<EncryptedSignature, PartialSignatureError> {
+        let (R, s_hat) = self._finish()?;
+        Ok(EncryptedSignature {
+            R,
+            s_hat,
+            needs_negation: self.session.signing_type.y_needs_negation,
+        })
+    }
+}
+
+/// Error returned by a [`PartialSignatureAggregator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartialSignatureError {
+    /// There is no party at the index in the session.
+    UnknownIndex(usize),
+    /// A different partial signature from the party has already been added.
+    Conflicting(usize),
+    /// The party's partial signature is invalid.
+    Invalid(usize),
+    /// The party's partial signature hasn't been added.
+    Missing(usize),
+}
+
+impl core::fmt::Display for PartialSignatureError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        use PartialSignatureError::*;
+        match self {
+            UnknownIndex(index) => write!(f, "there is no party at index {index} in the session"),
+            Conflicting(index) => write!(
+                f,
+                "already have a different partial signature from party {index}"
+            ),
+            Invalid(index) => write!(f, "party {index} sent an invalid partial signature"),
+            Missing(index) => write!(f, "missing a partial signature from party {index}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PartialSignatureError {}
+
 /// Constructor for a MuSig instance using deterministic nonce generation.
 ///
 /// If you use deterministic nonce generation you will have to provide a unique session id to every
@@ -732,6 +1493,228 @@ mod test {
     use secp256kfun::proptest::{option, prelude::*};
     use sha2::Sha256;
 
+    #[test]
+    fn aggregate_partial_signatures_one_at_a_time() {
+        let musig = new_with_deterministic_nonces::<Sha256>();
+        let keypairs = [(); 2].map(|_| musig.new_keypair(Scalar::random(&mut rand::thread_rng())));
+        let agg_key = musig
+            .new_agg_key(
+                keypairs
+                    .iter()
+                    .map(|keypair| keypair.public_key())
+                    .collect(),
+            )
+            .into_xonly_key();
+        let nonces = [(); 2].map(|_| NonceKeyPair::random(&mut rand::thread_rng()));
+        let message = Message::<Public>::plain("test", b"aggregator");
+        let session = musig.start_sign_session(
+            &agg_key,
+            nonces.iter().map(|nonce| nonce.public()).collect(),
+            message,
+        );
+        let partial_sigs =
+            [0, 1].map(|i| musig.sign(&agg_key, &session, i, &keypairs[i], nonces[i].clone()));
+
+        let mut aggregator = musig.partial_signature_aggregator(&agg_key, &session);
+        assert_eq!(
+            aggregator.add(2, partial_sigs[0]),
+            Err(PartialSignatureError::UnknownIndex(2))
+        );
+        assert_eq!(
+            aggregator.add(1, partial_sigs[0]),
+            Err(PartialSignatureError::Invalid(1))
+        );
+        assert_eq!(aggregator.add(1, partial_sigs[1]), Ok(()));
+        assert_eq!(aggregator.add(1, partial_sigs[1]), Ok(()));
+        assert_eq!(
+            aggregator.add(1, partial_sigs[0]),
+            Err(PartialSignatureError::Conflicting(1))
+        );
+        assert_eq!(aggregator.missing().collect::<Vec<_>>(), vec![0]);
+        assert_eq!(aggregator.add(0, partial_sigs[0]), Ok(()));
+        let signature = aggregator.finish().unwrap();
+        assert!(musig
+            .schnorr
+            .verify(&agg_key.agg_public_key(), message, &signature));
+        assert_eq!(
+            musig
+                .partial_signature_aggregator(&agg_key, &session)
+                .finish(),
+            Err(PartialSignatureError::Missing(0))
+        );
+    }
+
+    #[test]
+    fn derive_bip32_child_signs() {
+        use sha2::Sha512;
+        let musig = new_with_deterministic_nonces::<Sha256>();
+        let keypairs = [(); 2].map(|_| musig.new_keypair(Scalar::random(&mut rand::thread_rng())));
+        let agg_key = musig.new_agg_key(
+            keypairs
+                .iter()
+                .map(|keypair| keypair.public_key())
+                .collect(),
+        );
+        let xpub = agg_key.xpub();
+        assert_eq!(xpub.chain_code, BIP328_CHAIN_CODE);
+        let (child, child_xpub) = agg_key.clone().derive_bip32::<Sha512>(&[0, 3]).unwrap();
+        assert_eq!(child.agg_public_key(), child_xpub.public_key);
+        // a watch-only wallet with the xpub derives the same child
+        assert_eq!(
+            xpub.derive_child::<Sha512>(0)
+                .and_then(|xpub| xpub.derive_child::<Sha512>(3)),
+            Some(child_xpub)
+        );
+        assert!(agg_key
+            .derive_bip32::<Sha512>(&[crate::fun::bip32::HARDENED])
+            .is_none());
+
+        let child = child.into_xonly_key();
+        let nonces = [(); 2].map(|_| NonceKeyPair::random(&mut rand::thread_rng()));
+        let message = Message::<Public>::plain("test", b"child");
+        let session = musig.start_sign_session(
+            &child,
+            nonces.iter().map(|nonce| nonce.public()).collect(),
+            message,
+        );
+        let mut aggregator = musig.partial_signature_aggregator(&child, &session);
+        for i in 0..2 {
+            let partial_sig = musig.sign(&child, &session, i, &keypairs[i], nonces[i].clone());
+            assert_eq!(aggregator.add(i, partial_sig), Ok(()));
+        }
+        let signature = aggregator.finish().unwrap();
+        assert!(musig
+            .schnorr
+            .verify(&child.agg_public_key(), message, &signature));
+    }
+
+    #[test]
+    fn blame_invalid_partial_signature() {
+        let musig = new_with_deterministic_nonces::<Sha256>();
+        let keypairs = [
+            musig.new_keypair(Scalar::random(&mut rand::thread_rng())),
+            musig.new_keypair(Scalar::random(&mut rand::thread_rng())),
+        ];
+        let agg_key = musig
+            .new_agg_key(keypairs.iter().map(|kp| kp.public_key()).collect())
+            .into_xonly_key();
+        let secret_nonces = [
+            NonceKeyPair::random(&mut rand::thread_rng()),
+            NonceKeyPair::random(&mut rand::thread_rng()),
+        ];
+        let message = Message::<Public>::plain("test", b"blame");
+        let session = musig.start_sign_session(
+            &agg_key,
+            secret_nonces.iter().map(|n| n.public()).collect(),
+            message,
+        );
+        let honest = musig.sign(
+            &agg_key,
+            &session,
+            0,
+            &keypairs[0],
+            secret_nonces[0].clone(),
+        );
+        let cheat = s!(honest + 1).public();
+        let honest_attestation = musig.attest_partial_signature(&session, 0, &keypairs[0], honest);
+        let cheat_attestation = musig.attest_partial_signature(&session, 1, &keypairs[1], cheat);
+
+        assert_eq!(
+            musig.blame_partial_signature(
+                &agg_key,
+                &session,
+                0,
+                honest,
+                honest_attestation.clone()
+            ),
+            None
+        );
+        assert_eq!(
+            musig.blame_partial_signature(&agg_key, &session, 1, cheat, honest_attestation.clone()),
+            None,
+            "can't blame without the accused's attestation"
+        );
+        let blame = musig
+            .blame_partial_signature(&agg_key, &session, 1, cheat, cheat_attestation)
+            .unwrap();
+        assert!(musig.verify_partial_signature_blame(&agg_key, message, &blame));
+
+        let false_blame = PartialSignatureBlame {
+            index: 0,
+            partial_sig: honest,
+            nonces: blame.nonces.clone(),
+            attestation: honest_attestation.clone(),
+        };
+        assert!(!musig.verify_partial_signature_blame(&agg_key, message, &false_blame));
+
+        let mut substituted = blame.nonces.clone();
+        substituted[0] = NonceKeyPair::random(&mut rand::thread_rng()).public();
+        let framed = PartialSignatureBlame {
+            index: 0,
+            partial_sig: honest,
+            nonces: substituted,
+            attestation: honest_attestation,
+        };
+        assert!(!musig.verify_partial_signature_blame(&agg_key, message, &framed));
+    }
+
+    #[test]
+    fn audit_agg_key_path() {
+        use alloc::string::ToString;
+        let musig = new_with_deterministic_nonces::<Sha256>();
+        let keys = (0..3)
+            .map(|_| Point::random(&mut rand::thread_rng()))
+            .collect::<Vec<_>>();
+        let bip32_tweak = Scalar::random(&mut rand::thread_rng()).public().mark_zero();
+        let taproot_tweak = Scalar::random(&mut rand::thread_rng()).public().mark_zero();
+        let agg_key = musig
+            .new_agg_key(keys.clone())
+            .tweak(bip32_tweak)
+            .unwrap()
+            .into_xonly_key()
+            .tweak(taproot_tweak)
+            .unwrap();
+        let tweaks = [
+            KeyTweak::Tweak(bip32_tweak),
+            KeyTweak::XOnly,
+            KeyTweak::Tweak(taproot_tweak),
+        ];
+
+        let audit = musig.audit_agg_key(keys.clone(), &tweaks, agg_key.agg_public_key());
+        assert!(audit.matches());
+        assert_eq!(audit.steps().len(), keys.len() + 1 + tweaks.len());
+        assert!(audit.to_string().ends_with("result: MATCH"));
+
+        let mut reordered = keys.clone();
+        reordered.swap(0, 1);
+        let audit = musig.audit_agg_key(reordered, &tweaks, agg_key.agg_public_key());
+        assert!(!audit.matches());
+        assert!(audit.to_string().ends_with("result: MISMATCH"));
+
+        let audit = musig.audit_agg_key(keys.clone(), &tweaks[..2], agg_key.agg_public_key());
+        assert!(!audit.matches());
+
+        let provenance = musig.record_provenance(keys, tweaks.to_vec()).unwrap();
+        assert_eq!(
+            provenance.recorded_key,
+            agg_key.agg_public_key().normalize()
+        );
+        let mut changed = provenance.clone();
+        changed.tweaks.pop();
+        let report = musig.check_provenance(&[provenance, changed]);
+        assert!(!report.all_match());
+        assert_eq!(
+            report
+                .mismatches()
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>(),
+            vec![1]
+        );
+        assert!(report
+            .to_string()
+            .starts_with("checked 2 keys: 1 mismatches\n\nrecord 1:\n"));
+    }
+
     proptest! {
         #[test]
         fn proptest_sign_verify(sk1 in any::<Scalar>(),
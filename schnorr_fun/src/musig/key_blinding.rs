@@ -0,0 +1,250 @@
+//! Per-session blinded participant keys.
+//!
+//! A participant who takes part in many aggregations (e.g. as a member of several federations)
+//! reveals that they are the same participant every time they contribute the same public key. With
+//! [`MuSig::blind_keypair`] they can instead contribute a fresh key for each session:
+//!
+//! ```text
+//! t = H("MuSig/keyblind" || x || X || len(session_id) || session_id)
+//! X' = X + t * G
+//! ```
+//!
+//! The other parties can't tell `X'` apart from a random key but the participant can later show an
+//! auditor that `X'` belongs to them by handing over a [`KeyLinkage`] which reveals `t` for that
+//! session only. Since `t` is derived from the secret key and the session id it doesn't need to be
+//! stored.
+//!
+//! Revealing `t` alone wouldn't prove anything: anyone can pick a `t` and claim `X + t * G` is a
+//! blinded `X`. So the linkage also contains a signature by the long-term key over `X'` and the
+//! session id which only the owner of `X` can produce. [`MuSig::verify_key_linkage`] checks both.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{fun::Scalar, musig};
+//! let musig = musig::new_with_deterministic_nonces::<sha2::Sha256>();
+//! let keypair = musig.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let blinded = musig.blind_keypair(&keypair, b"federation-1/session-7");
+//! # let other = musig.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let agg_key = musig.new_agg_key(vec![blinded.keypair().public_key(), other.public_key()]);
+//! // sign with blinded.keypair() as usual. Later, prove the key was ours to an auditor:
+//! let linkage = blinded.linkage();
+//! assert!(musig.verify_key_linkage(&linkage));
+//! assert_eq!(linkage.public_key, keypair.public_key());
+//! assert_eq!(linkage.blinded_key, agg_key.keys().next().unwrap());
+//! ```
+use super::MuSig;
+use crate::{Message, Signature};
+use alloc::vec::Vec;
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    g,
+    hash::HashAdd,
+    marker::*,
+    nonce::NonceGen,
+    s, KeyPair, Point, Scalar, G,
+};
+
+const KEY_LINKAGE_TAG: &str = "MuSig/key-linkage";
+
+fn key_linkage_message(blinded_key: Point, session_id: &[u8]) -> Vec<u8> {
+    let mut data = blinded_key.to_bytes().to_vec();
+    data.extend_from_slice(&(session_id.len() as u64).to_be_bytes());
+    data.extend_from_slice(session_id);
+    data
+}
+
+/// A keypair blinded for a single session along with what is needed to link it to the original.
+///
+/// Created with [`MuSig::blind_keypair`].
+///
+/// Its `Debug` output only shows the blinded key and session id so that logging it doesn't link
+/// the blinded key to the original one or leak the secret key.
+#[derive(Clone)]
+pub struct BlindedKeyPair {
+    keypair: KeyPair,
+    public_key: Point,
+    session_id: Vec<u8>,
+    blinding_factor: Scalar<Secret, Zero>,
+    signature: Signature,
+}
+
+impl core::fmt::Debug for BlindedKeyPair {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("BlindedKeyPair")
+            .field("blinded_key", &self.keypair.public_key())
+            .field("session_id", &secp256kfun::hex::encode(&self.session_id))
+            .finish()
+    }
+}
+
+impl BlindedKeyPair {
+    /// The blinded keypair to use in the session in place of the original one.
+    pub fn keypair(&self) -> &KeyPair {
+        &self.keypair
+    }
+
+    /// The proof that the blinded key belongs to the owner of the original key.
+    ///
+    /// Only give this to someone who you want to be able to link the two keys.
+    pub fn linkage(&self) -> KeyLinkage {
+        KeyLinkage {
+            public_key: self.public_key,
+            blinded_key: self.keypair.public_key(),
+            session_id: self.session_id.clone(),
+            blinding_factor: self.blinding_factor.public(),
+            signature: self.signature.clone(),
+        }
+    }
+}
+
+/// Proof that a blinded key is controlled by the owner of a long-term key.
+///
+/// Since the secret key of the blinded key is the original secret key plus the blinding factor
+/// whoever controls one controls the other. The signature shows the owner of the long-term key
+/// blinded it for this session. Check it with [`MuSig::verify_key_linkage`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct KeyLinkage {
+    /// The long-term public key.
+    pub public_key: Point,
+    /// The key used in the session.
+    pub blinded_key: Point,
+    /// The session the key was blinded for.
+    pub session_id: Vec<u8>,
+    /// The difference between the secret keys of the two keys.
+    pub blinding_factor: Scalar<Public, Zero>,
+    /// A signature by `public_key` over `blinded_key` and `session_id`.
+    pub signature: Signature,
+}
+
+impl<H: Digest<OutputSize = U32> + Clone, NG> MuSig<H, NG> {
+    /// Checks that `linkage.blinded_key` is `linkage.public_key` blinded by the blinding factor and
+    /// that the owner of `public_key` signed the blinded key and session id.
+    #[must_use]
+    pub fn verify_key_linkage(&self, linkage: &KeyLinkage) -> bool {
+        let message_data = key_linkage_message(linkage.blinded_key, &linkage.session_id);
+        g!(linkage.public_key + linkage.blinding_factor * G) == linkage.blinded_key
+            && self.schnorr.verify(
+                &linkage.public_key.into_point_with_even_y().0,
+                Message::<Public>::plain(KEY_LINKAGE_TAG, &message_data),
+                &linkage.signature,
+            )
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Clone, NG: NonceGen> MuSig<H, NG> {
+    /// Blinds `keypair` for the session `session_id`.
+    ///
+    /// The same keypair and session id always give the same blinded keypair. Use a different
+    /// session id for every aggregation you don't want linked to the others.
+    pub fn blind_keypair(&self, keypair: &KeyPair, session_id: &[u8]) -> BlindedKeyPair {
+        let blinding_factor = Scalar::from_hash(
+            self.key_blind_hash
+                .clone()
+                .add(keypair.secret_key())
+                .add(keypair.public_key())
+                .add((session_id.len() as u64).to_be_bytes())
+                .add(session_id),
+        );
+        let blinded_secret = s!(keypair.secret_key() + blinding_factor)
+            .non_zero()
+            .expect("computationally unreachable");
+        let blinded_keypair = KeyPair::<Normal>::new(blinded_secret);
+        let message_data = key_linkage_message(blinded_keypair.public_key(), session_id);
+        let signature = self.schnorr.sign(
+            &self.schnorr.new_keypair(*keypair.secret_key()),
+            Message::<Public>::plain(KEY_LINKAGE_TAG, &message_data),
+        );
+        BlindedKeyPair {
+            keypair: blinded_keypair,
+            public_key: keypair.public_key(),
+            session_id: session_id.to_vec(),
+            blinding_factor: blinding_factor.mark_zero(),
+            signature,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Message;
+    use sha2::Sha256;
+
+    #[test]
+    fn blinded_keys_sign_and_link() {
+        let musig = crate::musig::new_with_deterministic_nonces::<Sha256>();
+        let keypair = musig.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let blinded = musig.blind_keypair(&keypair, b"session-1");
+        assert_eq!(
+            musig.blind_keypair(&keypair, b"session-1").keypair(),
+            blinded.keypair()
+        );
+        let other_session = musig.blind_keypair(&keypair, b"session-2");
+        assert_ne!(
+            other_session.keypair().public_key(),
+            blinded.keypair().public_key()
+        );
+        assert_ne!(blinded.keypair().public_key(), keypair.public_key());
+
+        let agg_key = musig
+            .new_agg_key(vec![blinded.keypair().public_key()])
+            .into_xonly_key();
+        let mut nonce_rng: rand_chacha::ChaCha20Rng =
+            musig.seed_nonce_rng(&agg_key, blinded.keypair().secret_key(), b"session-1");
+        let nonce = musig.gen_nonce(&mut nonce_rng);
+        let message = Message::<Public>::plain("test", b"blinded");
+        let session = musig.start_sign_session(&agg_key, vec![nonce.public()], message);
+        let partial_sig = musig.sign(&agg_key, &session, 0, blinded.keypair(), nonce);
+        let signature = musig.combine_partial_signatures(&agg_key, &session, [partial_sig]);
+        assert!(musig
+            .schnorr
+            .verify(&agg_key.agg_public_key(), message, &signature));
+
+        let linkage = blinded.linkage();
+        assert!(musig.verify_key_linkage(&linkage));
+        let mut wrong = linkage.clone();
+        wrong.blinded_key = other_session.keypair().public_key();
+        assert!(!musig.verify_key_linkage(&wrong));
+        let mut wrong = linkage.clone();
+        wrong.session_id = b"session-2".to_vec();
+        assert!(!musig.verify_key_linkage(&wrong));
+    }
+
+    #[test]
+    fn linkage_cant_be_forged_without_the_secret_key() {
+        let musig = crate::musig::new_with_deterministic_nonces::<Sha256>();
+        let victim = musig.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let attacker = musig.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        // pick a blinding factor for the victim's key and sign with our own key instead
+        let blinding_factor = Scalar::random(&mut rand::thread_rng()).public().mark_zero();
+        let blinded_key = g!(victim.public_key() + blinding_factor * G)
+            .normalize()
+            .non_zero()
+            .unwrap();
+        let forged = KeyLinkage {
+            public_key: victim.public_key(),
+            blinded_key,
+            session_id: b"session-1".to_vec(),
+            blinding_factor,
+            signature: musig.schnorr.sign(
+                &musig.schnorr.new_keypair(*attacker.secret_key()),
+                Message::<Public>::plain(
+                    KEY_LINKAGE_TAG,
+                    &key_linkage_message(blinded_key, b"session-1"),
+                ),
+            ),
+        };
+        assert!(!musig.verify_key_linkage(&forged));
+    }
+}
@@ -0,0 +1,343 @@
+//! Limits on concurrently open MuSig signing sessions.
+//!
+//! MuSig2 is secure when a signer has many sessions open at once, but only because each signer
+//! contributes two nonces per session. Protocols that are misused so they degenerate to something
+//! MuSig1-like (e.g. only one nonce is effectively used, or nonces are committed to after seeing
+//! the other parties') are vulnerable to Wagner's algorithm and the [ROS attack]: a malicious
+//! co-signer who can get you to open enough sessions at the same time can forge a signature on a
+//! message you never agreed to.
+//!
+//! A [`SessionManager`] holds your secret nonces and stands between your application and
+//! [`MuSig::sign`]. For each of your keys it enforces:
+//!
+//! - a maximum number of sessions that can be open at once;
+//! - optionally, that sessions are signed in the order they were opened;
+//! - optionally, that the maximum shrinks each time a session is abandoned without being signed (and
+//!   grows back as sessions are completed). Abandoning sessions is exactly what an attacker needs
+//!   you to do to collect enough of them.
+//!
+//! Since the manager owns the secret nonces it also guarantees that each nonce is used at most once.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     binonce::NonceKeyPair,
+//!     fun::Scalar,
+//!     musig::{
+//!         self,
+//!         session_manager::{SessionLimitError, SessionLimits, SessionManager},
+//!     },
+//!     Message,
+//! };
+//! let musig = musig::new_with_deterministic_nonces::<sha2::Sha256>();
+//! let keypair = musig.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let agg_key = musig.new_agg_key(vec![keypair.public_key()]).into_xonly_key();
+//! let mut manager = SessionManager::new(SessionLimits {
+//!     max_concurrent: 1,
+//!     ..Default::default()
+//! });
+//! let (session_id, public_nonce) = manager
+//!     .open_session(
+//!         keypair.public_key(),
+//!         NonceKeyPair::random(&mut rand::thread_rng()),
+//!     )
+//!     .unwrap();
+//! // can't open another until the first one is finished
+//! assert_eq!(
+//!     manager.open_session(
+//!         keypair.public_key(),
+//!         NonceKeyPair::random(&mut rand::thread_rng())
+//!     ),
+//!     Err(SessionLimitError::TooManySessions { limit: 1 })
+//! );
+//! let message = Message::<schnorr_fun::fun::marker::Public>::plain("my-app", b"hello");
+//! let session = musig.start_sign_session(&agg_key, vec![public_nonce], message);
+//! let partial_sig = manager
+//!     .sign(&musig, &agg_key, &session, 0, &keypair, session_id)
+//!     .unwrap();
+//! ```
+//!
+//! [ROS attack]: https://eprint.iacr.org/2020/945
+//! [`MuSig::sign`]: crate::musig::MuSig::sign
+use super::{AggKey, MuSig, SignSession};
+use crate::binonce::{Nonce, NonceKeyPair};
+use alloc::collections::BTreeMap;
+use core::fmt;
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    marker::*,
+    KeyPair, Point, Scalar,
+};
+
+/// The limits a [`SessionManager`] enforces for each key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionLimits {
+    /// The maximum number of sessions a key can have open at once.
+    pub max_concurrent: usize,
+    /// Whether sessions have to be signed (or abandoned) in the order they were opened.
+    pub in_order: bool,
+    /// Whether abandoning a session lowers the key's limit by one (to no lower than one) until a
+    /// session is signed again.
+    pub adaptive: bool,
+}
+
+impl SessionLimits {
+    /// Only allow one session per key to be open at a time.
+    pub fn sequential() -> Self {
+        Self {
+            max_concurrent: 1,
+            in_order: true,
+            adaptive: false,
+        }
+    }
+}
+
+impl Default for SessionLimits {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 8,
+            in_order: false,
+            adaptive: true,
+        }
+    }
+}
+
+/// Identifies a session opened with [`SessionManager::open_session`].
+///
+/// Ids increase in the order sessions are opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SessionId(u64);
+
+#[derive(Debug)]
+struct KeySessions {
+    limit: usize,
+    open: BTreeMap<SessionId, NonceKeyPair>,
+}
+
+/// Tracks the open signing sessions for each of our keys and holds their secret nonces.
+///
+/// See the [module documentation](crate::musig::session_manager).
+#[derive(Debug)]
+pub struct SessionManager {
+    limits: SessionLimits,
+    next_id: u64,
+    keys: BTreeMap<Point, KeySessions>,
+}
+
+impl SessionManager {
+    /// Creates a manager that enforces `limits` for every key.
+    ///
+    /// # Panics
+    ///
+    /// If `limits.max_concurrent` is zero.
+    pub fn new(limits: SessionLimits) -> Self {
+        assert!(
+            limits.max_concurrent > 0,
+            "must allow at least one session at a time"
+        );
+        Self {
+            limits,
+            next_id: 0,
+            keys: BTreeMap::new(),
+        }
+    }
+
+    /// The limits the manager was created with.
+    pub fn limits(&self) -> SessionLimits {
+        self.limits
+    }
+
+    /// Opens a new session that will use `nonce` to sign for `public_key`.
+    ///
+    /// Returns the id of the session and the public nonce to send to the other parties.
+    pub fn open_session(
+        &mut self,
+        public_key: Point,
+        nonce: NonceKeyPair,
+    ) -> Result<(SessionId, Nonce), SessionLimitError> {
+        let max_concurrent = self.limits.max_concurrent;
+        let sessions = self.keys.entry(public_key).or_insert_with(|| KeySessions {
+            limit: max_concurrent,
+            open: BTreeMap::new(),
+        });
+        if sessions.open.len() >= sessions.limit {
+            return Err(SessionLimitError::TooManySessions {
+                limit: sessions.limit,
+            });
+        }
+        let id = SessionId(self.next_id);
+        self.next_id += 1;
+        let public_nonce = nonce.public();
+        sessions.open.insert(id, nonce);
+        Ok((id, public_nonce))
+    }
+
+    /// Signs `session` with the nonce from the session `id`, closing it.
+    ///
+    /// This is [`MuSig::sign`] except the secret nonce comes from the manager.
+    #[allow(clippy::too_many_arguments)]
+    pub fn sign<H: Digest<OutputSize = U32> + Clone, NG, T>(
+        &mut self,
+        musig: &MuSig<H, NG>,
+        agg_key: &AggKey<EvenY>,
+        session: &SignSession<T>,
+        my_index: usize,
+        keypair: &KeyPair,
+        id: SessionId,
+    ) -> Result<Scalar<Public, Zero>, SessionLimitError> {
+        let nonce = self.close(keypair.public_key(), id)?;
+        let sessions = self
+            .keys
+            .get_mut(&keypair.public_key())
+            .expect("close checked the key exists");
+        sessions.limit = (sessions.limit + 1).min(self.limits.max_concurrent);
+        Ok(musig.sign(agg_key, session, my_index, keypair, nonce))
+    }
+
+    /// Abandons the session `id` without signing and throws away its secret nonce.
+    ///
+    /// If the limits are [`adaptive`] this lowers the number of sessions `public_key` can have
+    /// open.
+    ///
+    /// [`adaptive`]: SessionLimits::adaptive
+    pub fn abandon(&mut self, public_key: Point, id: SessionId) -> Result<(), SessionLimitError> {
+        let _ = self.close(public_key, id)?;
+        if self.limits.adaptive {
+            let sessions = self
+                .keys
+                .get_mut(&public_key)
+                .expect("close checked the key exists");
+            sessions.limit = sessions.limit.saturating_sub(1).max(1);
+        }
+        Ok(())
+    }
+
+    /// The number of sessions `public_key` currently has open.
+    pub fn open_sessions(&self, public_key: Point) -> usize {
+        self.keys
+            .get(&public_key)
+            .map(|sessions| sessions.open.len())
+            .unwrap_or(0)
+    }
+
+    /// The number of sessions `public_key` is currently allowed to have open.
+    pub fn limit(&self, public_key: Point) -> usize {
+        self.keys
+            .get(&public_key)
+            .map(|sessions| sessions.limit)
+            .unwrap_or(self.limits.max_concurrent)
+    }
+
+    fn close(
+        &mut self,
+        public_key: Point,
+        id: SessionId,
+    ) -> Result<NonceKeyPair, SessionLimitError> {
+        let sessions = self
+            .keys
+            .get_mut(&public_key)
+            .ok_or(SessionLimitError::UnknownSession(id))?;
+        let oldest = *sessions
+            .open
+            .keys()
+            .next()
+            .ok_or(SessionLimitError::UnknownSession(id))?;
+        if !sessions.open.contains_key(&id) {
+            return Err(SessionLimitError::UnknownSession(id));
+        }
+        if self.limits.in_order && oldest != id {
+            return Err(SessionLimitError::OutOfOrder { expected: oldest });
+        }
+        Ok(sessions.open.remove(&id).expect("checked above"))
+    }
+}
+
+/// Error returned by a [`SessionManager`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionLimitError {
+    /// The key already has as many sessions open as it is allowed.
+    TooManySessions {
+        /// How many sessions the key is allowed to have open.
+        limit: usize,
+    },
+    /// The session isn't open for the key (it may have already been signed or abandoned).
+    UnknownSession(SessionId),
+    /// Sessions must be closed in order and there is an older session still open.
+    OutOfOrder {
+        /// The session that has to be closed next.
+        expected: SessionId,
+    },
+}
+
+impl fmt::Display for SessionLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use SessionLimitError::*;
+        match self {
+            TooManySessions { limit } => write!(
+                f,
+                "key already has the maximum of {limit} signing sessions open"
+            ),
+            UnknownSession(id) => write!(f, "session {} is not open for this key", id.0),
+            OutOfOrder { expected } => write!(
+                f,
+                "sessions must be closed in order and session {} is still open",
+                expected.0
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SessionLimitError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Message;
+    use sha2::Sha256;
+
+    #[test]
+    fn session_limits() {
+        let musig = crate::musig::new_with_deterministic_nonces::<Sha256>();
+        let keypair = musig.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let public_key = keypair.public_key();
+        let agg_key = musig.new_agg_key(vec![public_key]).into_xonly_key();
+        let mut manager = SessionManager::new(SessionLimits {
+            max_concurrent: 3,
+            in_order: true,
+            adaptive: true,
+        });
+        let mut open =
+            || manager.open_session(public_key, NonceKeyPair::random(&mut rand::thread_rng()));
+        let sessions = [open(), open(), open()].map(Result::unwrap);
+        assert_eq!(open(), Err(SessionLimitError::TooManySessions { limit: 3 }));
+
+        let message = Message::<Public>::plain("test", b"session-limits");
+        let sign_session = musig.start_sign_session(&agg_key, vec![sessions[1].1], message);
+        assert_eq!(
+            manager.sign(&musig, &agg_key, &sign_session, 0, &keypair, sessions[1].0),
+            Err(SessionLimitError::OutOfOrder {
+                expected: sessions[0].0
+            })
+        );
+        manager.abandon(public_key, sessions[0].0).unwrap();
+        assert_eq!(manager.limit(public_key), 2);
+        assert_eq!(manager.open_sessions(public_key), 2);
+        assert_eq!(
+            manager.abandon(public_key, sessions[0].0),
+            Err(SessionLimitError::UnknownSession(sessions[0].0))
+        );
+
+        let partial_sig = manager
+            .sign(&musig, &agg_key, &sign_session, 0, &keypair, sessions[1].0)
+            .unwrap();
+        assert!(musig.verify_partial_signature(&agg_key, &sign_session, 0, partial_sig));
+        assert_eq!(manager.limit(public_key), 3);
+        assert_eq!(
+            manager.sign(&musig, &agg_key, &sign_session, 0, &keypair, sessions[1].0),
+            Err(SessionLimitError::UnknownSession(sessions[1].0))
+        );
+        assert_eq!(manager.open_sessions(public_key), 1);
+    }
+}
@@ -0,0 +1,302 @@
+//! An optional pre-round that mixes jointly generated randomness into every signer's nonces.
+//!
+//! With deterministic nonces a signer's nonce only depends on their key, the aggregate key and the
+//! session id. If an untrusted coordinator chooses session ids (or the application gets them
+//! wrong) a signer can be made to reuse a nonce. Before the nonce round the signers can run a
+//! commit-and-reveal round to agree on a session salt that none of them could choose:
+//!
+//! 1. Each signer picks a random salt with [`MuSig::gen_salt`] and sends its [`SaltCommitment`].
+//! 2. Once a signer has everyone's commitment they [`reveal`] their salt.
+//! 3. Everyone checks the salts against the commitments and combines them with
+//!    [`MuSig::session_salt`].
+//!
+//! Each signer then seeds their nonce rng with [`MuSig::seed_salted_nonce_rng`]. Since the
+//! session salt depends on the signer's own fresh salt the nonce can't repeat even if the session
+//! id does. The commitments are bound to the aggregate key and the signer's index so they can't
+//! be replayed by another signer or in another aggregation.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{fun::Scalar, musig};
+//! let musig = musig::new_with_deterministic_nonces::<sha2::Sha256>();
+//! let keypairs = [0; 2].map(|_| musig.new_keypair(Scalar::random(&mut rand::thread_rng())));
+//! let agg_key = musig
+//!     .new_agg_key(keypairs.iter().map(|keypair| keypair.public_key()).collect())
+//!     .into_xonly_key();
+//! let contributions = [0, 1].map(|i| musig.gen_salt(&agg_key, i, &mut rand::thread_rng()));
+//! let commitments = contributions
+//!     .iter()
+//!     .map(|contribution| contribution.commitment())
+//!     .collect::<Vec<_>>();
+//! let salts = contributions
+//!     .into_iter()
+//!     .map(|contribution| contribution.reveal(&commitments).unwrap())
+//!     .collect::<Vec<_>>();
+//! let session_salt = musig.session_salt(&agg_key, &commitments, &salts).unwrap();
+//! let mut nonce_rng: rand_chacha::ChaCha20Rng = musig.seed_salted_nonce_rng(
+//!     &agg_key,
+//!     keypairs[0].secret_key(),
+//!     b"session-1",
+//!     &session_salt,
+//! );
+//! let nonce = musig.gen_nonce(&mut nonce_rng);
+//! ```
+//!
+//! [`reveal`]: SaltContribution::reveal
+use super::{AggKey, MuSig};
+use core::fmt;
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    hash::HashAdd,
+    marker::*,
+    nonce::NonceGen,
+    rand_core::{RngCore, SeedableRng},
+    Scalar,
+};
+
+/// A signer's commitment to their salt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct SaltCommitment(pub [u8; 32]);
+
+/// A signer's salt before it has been revealed.
+///
+/// Created with [`MuSig::gen_salt`].
+#[derive(Clone)]
+pub struct SaltContribution {
+    index: usize,
+    n_signers: usize,
+    salt: [u8; 32],
+    commitment: SaltCommitment,
+}
+
+impl fmt::Debug for SaltContribution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SaltContribution")
+            .field("index", &self.index)
+            .field("commitment", &self.commitment)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SaltContribution {
+    /// The commitment to send to the other signers.
+    pub fn commitment(&self) -> SaltCommitment {
+        self.commitment
+    }
+
+    /// Reveals the salt once every signer's commitment has been received.
+    ///
+    /// `commitments` are in the order of the keys in the aggregate key. This checks our own
+    /// commitment is in our place and that no one else sent a copy of it.
+    pub fn reveal(self, commitments: &[SaltCommitment]) -> Result<[u8; 32], SessionSaltError> {
+        if commitments.len() != self.n_signers {
+            return Err(SessionSaltError::WrongNumberOfSigners);
+        }
+        for (index, commitment) in commitments.iter().enumerate() {
+            if (index == self.index) != (*commitment == self.commitment) {
+                return Err(SessionSaltError::InvalidSalt { index });
+            }
+        }
+        Ok(self.salt)
+    }
+}
+
+/// The salt all of the signers agreed on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SessionSalt([u8; 32]);
+
+impl SessionSalt {
+    /// The 32 bytes of the salt.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+/// Error agreeing on a [`SessionSalt`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SessionSaltError {
+    /// There wasn't a commitment and salt for every signer.
+    WrongNumberOfSigners,
+    /// The signer at `index` revealed a salt that doesn't match their commitment (or sent our
+    /// commitment as their own).
+    InvalidSalt {
+        /// The index of the signer.
+        index: usize,
+    },
+}
+
+impl fmt::Display for SessionSaltError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SessionSaltError::WrongNumberOfSigners => {
+                write!(f, "there wasn't a commitment and salt for every signer")
+            }
+            SessionSaltError::InvalidSalt { index } => {
+                write!(f, "the salt of signer {} is invalid", index)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SessionSaltError {}
+
+impl<H: Digest<OutputSize = U32> + Clone, NG> MuSig<H, NG> {
+    /// The commitment of the signer at `index` of `agg_key` to `salt`.
+    pub fn salt_commitment(
+        &self,
+        agg_key: &AggKey<impl Normalized>,
+        index: usize,
+        salt: &[u8; 32],
+    ) -> SaltCommitment {
+        SaltCommitment(
+            self.salt_commit_hash
+                .clone()
+                .add(agg_key.agg_public_key().to_xonly_bytes())
+                .add((index as u32).to_be_bytes())
+                .add(salt)
+                .finalize()
+                .into(),
+        )
+    }
+
+    /// Picks a fresh salt for the signer at `index` of `agg_key` and commits to it.
+    pub fn gen_salt(
+        &self,
+        agg_key: &AggKey<impl Normalized>,
+        index: usize,
+        rng: &mut impl RngCore,
+    ) -> SaltContribution {
+        let mut salt = [0u8; 32];
+        rng.fill_bytes(&mut salt);
+        SaltContribution {
+            index,
+            n_signers: agg_key.keys().count(),
+            salt,
+            commitment: self.salt_commitment(agg_key, index, &salt),
+        }
+    }
+
+    /// Checks every signer's salt against their commitment and combines them into the session
+    /// salt.
+    ///
+    /// `commitments` and `salts` are in the order of the keys in `agg_key`.
+    pub fn session_salt(
+        &self,
+        agg_key: &AggKey<impl Normalized>,
+        commitments: &[SaltCommitment],
+        salts: &[[u8; 32]],
+    ) -> Result<SessionSalt, SessionSaltError> {
+        let n_signers = agg_key.keys().count();
+        if commitments.len() != n_signers || salts.len() != n_signers {
+            return Err(SessionSaltError::WrongNumberOfSigners);
+        }
+        let mut hash = self
+            .session_salt_hash
+            .clone()
+            .add(agg_key.agg_public_key().to_xonly_bytes());
+        for (index, (commitment, salt)) in commitments.iter().zip(salts).enumerate() {
+            if self.salt_commitment(agg_key, index, salt) != *commitment {
+                return Err(SessionSaltError::InvalidSalt { index });
+            }
+            hash = hash.add(salt);
+        }
+        Ok(SessionSalt(hash.finalize().into()))
+    }
+}
+
+impl<H, NG> MuSig<H, NG>
+where
+    H: Digest<OutputSize = U32> + Clone,
+    NG: NonceGen,
+{
+    /// Seeds a nonce rng like [`MuSig::seed_nonce_rng`] that also commits to the `session_salt`.
+    ///
+    /// The same warnings apply: the seed may change between minor versions and `secret` must be
+    /// the key you sign with.
+    pub fn seed_salted_nonce_rng<R: SeedableRng<Seed = [u8; 32]>>(
+        &self,
+        agg_key: &AggKey<impl Normalized>,
+        secret: &Scalar,
+        session_id: &[u8],
+        session_salt: &SessionSalt,
+    ) -> R {
+        let sid_len = (session_id.len() as u64).to_be_bytes();
+        let pk_bytes = agg_key.agg_public_key().to_xonly_bytes();
+        secp256kfun::derive_nonce_rng!(
+            nonce_gen => self.nonce_gen(),
+            secret => &secret,
+            public => [pk_bytes, session_salt.0, sid_len, session_id],
+            seedable_rng => R
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec::Vec;
+    use rand_chacha::ChaCha20Rng;
+    use sha2::Sha256;
+
+    #[test]
+    fn session_salt_round() {
+        let musig = crate::musig::new_with_deterministic_nonces::<Sha256>();
+        let keypairs = [0; 3].map(|_| musig.new_keypair(Scalar::random(&mut rand::thread_rng())));
+        let agg_key = musig.new_agg_key(keypairs.iter().map(|kp| kp.public_key()).collect());
+        let contributions = [0, 1, 2].map(|i| musig.gen_salt(&agg_key, i, &mut rand::thread_rng()));
+        let commitments = contributions
+            .iter()
+            .map(SaltContribution::commitment)
+            .collect::<Vec<_>>();
+
+        // a signer who copies someone's commitment is caught before anyone reveals
+        let mut copied = commitments.clone();
+        copied[2] = copied[0];
+        assert_eq!(
+            contributions[0].clone().reveal(&copied),
+            Err(SessionSaltError::InvalidSalt { index: 2 })
+        );
+        assert_eq!(
+            contributions[0].clone().reveal(&commitments[..2]),
+            Err(SessionSaltError::WrongNumberOfSigners)
+        );
+
+        let salts = contributions
+            .into_iter()
+            .map(|contribution| contribution.reveal(&commitments).unwrap())
+            .collect::<Vec<_>>();
+        let session_salt = musig.session_salt(&agg_key, &commitments, &salts).unwrap();
+        let mut bad_salts = salts.clone();
+        bad_salts[1][0] ^= 1;
+        assert_eq!(
+            musig.session_salt(&agg_key, &commitments, &bad_salts),
+            Err(SessionSaltError::InvalidSalt { index: 1 })
+        );
+
+        // the same session id gives different nonces with a different salt
+        let secret = keypairs[0].secret_key();
+        let nonce = |rng: &mut ChaCha20Rng| musig.gen_nonce(rng).public();
+        let salted =
+            nonce(&mut musig.seed_salted_nonce_rng(&agg_key, secret, b"id", &session_salt));
+        assert_eq!(
+            salted,
+            nonce(&mut musig.seed_salted_nonce_rng(&agg_key, secret, b"id", &session_salt))
+        );
+        let other_salt = SessionSalt([7u8; 32]);
+        assert_ne!(
+            salted,
+            nonce(&mut musig.seed_salted_nonce_rng(&agg_key, secret, b"id", &other_salt))
+        );
+        assert_ne!(
+            salted,
+            nonce(&mut musig.seed_nonce_rng(&agg_key, secret, b"id"))
+        );
+    }
+}
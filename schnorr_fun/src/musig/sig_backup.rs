@@ -0,0 +1,289 @@
+//! Handing over pre-signed partial signatures for specific signing sessions to a watchtower.
+//!
+//! A signer who may be offline when a pre-agreed transaction (e.g. a justice transaction) needs
+//! to be completed can run the nonce round and sign it ahead of time and give the partial
+//! signature to a watchtower. Each partial signature is sealed in a [`PartialSignatureBackup`] that
+//! only the watchtower can open and that commits to the session id and the signer's index. After
+//! opening it with [`MuSig::open_partial_signature_backup`] the watchtower has checked it's a
+//! valid partial signature for the session and can combine it with the others when the time comes.
+//!
+//! The watchtower only ever learns a partial signature on the pre-agreed message. Unlike handing
+//! over a secret nonce this reveals nothing about the signer's secret key and doesn't let the
+//! watchtower sign anything else. The backup works the same for [`Adaptor`] sessions where the
+//! sealed value is the partial encrypted signature.
+//!
+//! The partial signature is encrypted to the watchtower's public key with [`secp256kfun::ecies`]
+//! with the session id and signer index as associated data.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     binonce::NonceKeyPair,
+//!     fun::{g, marker::*, Scalar, G},
+//!     musig, Message,
+//! };
+//! let musig = musig::new_with_deterministic_nonces::<sha2::Sha256>();
+//! let keypair = musig.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let agg_key = musig.new_agg_key(vec![keypair.public_key()]).into_xonly_key();
+//! let nonce = NonceKeyPair::random(&mut rand::thread_rng());
+//! let message = Message::<Public>::plain("my-app", b"justice transaction");
+//! let session = musig.start_sign_session(&agg_key, vec![nonce.public()], message);
+//! let watchtower_secret = Scalar::random(&mut rand::thread_rng());
+//! let watchtower = g!(watchtower_secret * G).normalize();
+//! let backup = musig.backup_partial_signature(
+//!     &agg_key,
+//!     &session,
+//!     0,
+//!     &keypair,
+//!     nonce,
+//!     b"justice-tx-commitment-42",
+//!     &watchtower,
+//!     &mut rand::thread_rng(),
+//! );
+//! // send the backup to the watchtower who knows the session
+//! let partial_sig = musig
+//!     .open_partial_signature_backup(&agg_key, &session, &backup, &watchtower_secret)
+//!     .unwrap();
+//! let signature = musig.combine_partial_signatures(&agg_key, &session, [partial_sig]);
+//! assert!(musig
+//!     .schnorr
+//!     .verify(&agg_key.agg_public_key(), message, &signature));
+//! ```
+//!
+//! [`Adaptor`]: super::Adaptor
+use super::{AggKey, MuSig, SignSession};
+use crate::binonce::NonceKeyPair;
+use alloc::vec::Vec;
+use core::fmt;
+use secp256kfun::{
+    digest::{crypto_common::BlockSizeUser, generic_array::typenum::U32, Digest},
+    ecies,
+    marker::*,
+    rand_core::RngCore,
+    KeyPair, Point, Scalar,
+};
+
+/// A partial signature for a single session encrypted to a watchtower.
+///
+/// See the [module documentation](crate::musig::sig_backup).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct PartialSignatureBackup {
+    /// The session the partial signature is for.
+    pub session_id: Vec<u8>,
+    /// The index of the signer's key in the [`AggKey`].
+    pub index: usize,
+    /// The partial signature encrypted with [`ecies::encrypt`].
+    pub ciphertext: Vec<u8>,
+}
+
+impl PartialSignatureBackup {
+    /// Encrypts `partial_sig` from the signer at `index` for `session_id` so that only the owner of
+    /// `watchtower` can open it.
+    pub fn seal<H: BlockSizeUser + Digest<OutputSize = U32> + Default>(
+        session_id: &[u8],
+        index: usize,
+        partial_sig: Scalar<Public, Zero>,
+        watchtower: &Point,
+        rng: &mut impl RngCore,
+    ) -> Self {
+        let ciphertext = ecies::encrypt::<H>(
+            watchtower,
+            &associated_data(session_id, index),
+            &partial_sig.to_bytes(),
+            rng,
+        );
+        Self {
+            session_id: session_id.to_vec(),
+            index,
+            ciphertext,
+        }
+    }
+
+    /// Decrypts the partial signature with the watchtower's secret key.
+    ///
+    /// This doesn't check the partial signature. Use [`MuSig::open_partial_signature_backup`] to do
+    /// that as well.
+    pub fn open<H: BlockSizeUser + Digest<OutputSize = U32> + Default>(
+        &self,
+        watchtower_secret: &Scalar,
+    ) -> Result<Scalar<Public, Zero>, SigBackupError> {
+        let plaintext = ecies::decrypt::<H>(
+            watchtower_secret,
+            &associated_data(&self.session_id, self.index),
+            &self.ciphertext,
+        )
+        .map_err(SigBackupError::Decrypt)?;
+        let bytes: [u8; 32] = plaintext
+            .try_into()
+            .map_err(|_| SigBackupError::InvalidPartialSignature)?;
+        Scalar::from_bytes(bytes).ok_or(SigBackupError::InvalidPartialSignature)
+    }
+}
+
+fn associated_data(session_id: &[u8], index: usize) -> Vec<u8> {
+    let mut data = (session_id.len() as u64).to_be_bytes().to_vec();
+    data.extend_from_slice(session_id);
+    data.extend_from_slice(&(index as u32).to_be_bytes());
+    data
+}
+
+impl<H, NG> MuSig<H, NG>
+where
+    H: BlockSizeUser + Digest<OutputSize = U32> + Clone + Default,
+{
+    /// Signs in `session` and seals the partial signature in a [`PartialSignatureBackup`] for
+    /// `watchtower`.
+    ///
+    /// `session_id` is only used to identify the backup. It's authenticated along with the index
+    /// so the watchtower can't be tricked into filing it under a different session.
+    #[allow(clippy::too_many_arguments)]
+    pub fn backup_partial_signature<T>(
+        &self,
+        agg_key: &AggKey<EvenY>,
+        session: &SignSession<T>,
+        my_index: usize,
+        keypair: &KeyPair,
+        secret_nonce: NonceKeyPair,
+        session_id: &[u8],
+        watchtower: &Point,
+        rng: &mut impl RngCore,
+    ) -> PartialSignatureBackup {
+        let partial_sig = self.sign(agg_key, session, my_index, keypair, secret_nonce);
+        PartialSignatureBackup::seal::<H>(session_id, my_index, partial_sig, watchtower, rng)
+    }
+
+    /// Opens a [`PartialSignatureBackup`] and checks the partial signature is valid for `session`.
+    pub fn open_partial_signature_backup<T>(
+        &self,
+        agg_key: &AggKey<EvenY>,
+        session: &SignSession<T>,
+        backup: &PartialSignatureBackup,
+        watchtower_secret: &Scalar,
+    ) -> Result<Scalar<Public, Zero>, SigBackupError> {
+        if backup.index >= agg_key.keys().count() {
+            return Err(SigBackupError::InvalidPartialSignature);
+        }
+        let partial_sig = backup.open::<H>(watchtower_secret)?;
+        if !self.verify_partial_signature(agg_key, session, backup.index, partial_sig) {
+            return Err(SigBackupError::InvalidPartialSignature);
+        }
+        Ok(partial_sig)
+    }
+}
+
+/// Error opening a [`PartialSignatureBackup`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SigBackupError {
+    /// The backup was not encrypted to the key or has been tampered with.
+    Decrypt(ecies::DecryptError),
+    /// The backup doesn't contain a valid partial signature for the session.
+    InvalidPartialSignature,
+}
+
+impl fmt::Display for SigBackupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SigBackupError::Decrypt(e) => write!(f, "couldn't decrypt the backup: {}", e),
+            SigBackupError::InvalidPartialSignature => write!(
+                f,
+                "the backup does not contain a valid partial signature for the session"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SigBackupError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{binonce::NonceKeyPair, Message};
+    use secp256kfun::{g, G};
+    use sha2::Sha256;
+
+    #[test]
+    fn partial_signature_backup_round_trip() {
+        let musig = crate::musig::new_with_deterministic_nonces::<Sha256>();
+        let keypairs = [
+            musig.new_keypair(Scalar::random(&mut rand::thread_rng())),
+            musig.new_keypair(Scalar::random(&mut rand::thread_rng())),
+        ];
+        let agg_key = musig
+            .new_agg_key(keypairs.iter().map(|kp| kp.public_key()).collect())
+            .into_xonly_key();
+        let nonces = [
+            NonceKeyPair::random(&mut rand::thread_rng()),
+            NonceKeyPair::random(&mut rand::thread_rng()),
+        ];
+        let message = Message::<Public>::plain("test", b"justice");
+        let session = musig.start_sign_session(
+            &agg_key,
+            nonces.iter().map(NonceKeyPair::public).collect(),
+            message,
+        );
+        let watchtower_secret = Scalar::random(&mut rand::thread_rng());
+        let watchtower = g!(watchtower_secret * G).normalize();
+        let backup = musig.backup_partial_signature(
+            &agg_key,
+            &session,
+            1,
+            &keypairs[1],
+            nonces[1].clone(),
+            b"session-1",
+            &watchtower,
+            &mut rand::thread_rng(),
+        );
+        let partial_sig = musig
+            .open_partial_signature_backup(&agg_key, &session, &backup, &watchtower_secret)
+            .unwrap();
+        let mine = musig.sign(&agg_key, &session, 0, &keypairs[0], nonces[0].clone());
+        let signature = musig.combine_partial_signatures(&agg_key, &session, [mine, partial_sig]);
+        assert!(musig
+            .schnorr
+            .verify(&agg_key.agg_public_key(), message, &signature));
+
+        assert_eq!(
+            backup.open::<Sha256>(&Scalar::random(&mut rand::thread_rng())),
+            Err(SigBackupError::Decrypt(ecies::DecryptError::InvalidTag))
+        );
+        let mut swapped = backup.clone();
+        swapped.session_id = b"session-2".to_vec();
+        assert_eq!(
+            swapped.open::<Sha256>(&watchtower_secret),
+            Err(SigBackupError::Decrypt(ecies::DecryptError::InvalidTag))
+        );
+        let mut wrong_index = backup.clone();
+        wrong_index.index = 0;
+        assert_eq!(
+            wrong_index.open::<Sha256>(&watchtower_secret),
+            Err(SigBackupError::Decrypt(ecies::DecryptError::InvalidTag))
+        );
+
+        let other_session = musig.start_sign_session(
+            &agg_key,
+            nonces.iter().map(NonceKeyPair::public).collect(),
+            Message::<Public>::plain("test", b"something else"),
+        );
+        assert_eq!(
+            musig.open_partial_signature_backup(
+                &agg_key,
+                &other_session,
+                &backup,
+                &watchtower_secret
+            ),
+            Err(SigBackupError::InvalidPartialSignature)
+        );
+    }
+}
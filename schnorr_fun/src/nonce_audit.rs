@@ -0,0 +1,331 @@
+//! Checking an archive of signatures for nonce reuse.
+//!
+//! Two signatures by the same key with the same `R` on different messages reveal the secret key.
+//! A custodian that keeps every signature it has produced can show it never did this by running
+//! the archive through a [`NonceAudit`] in two streaming passes:
+//!
+//! 1. [`NonceAudit::ingest`] each record. A bloom filter of `(public key, R)` pairs flags the
+//!    records whose pair may have been seen before. This needs a fixed amount of memory however
+//!    large the archive is (about 10 bits per expected record).
+//! 2. [`NonceAudit::confirm`] with the archive again. Only the flagged pairs are kept and counted
+//!    exactly so bloom filter false positives are discarded and each real repeat is listed with the
+//!    position of every record that used it.
+//!
+//! The [`NonceAuditReport`] distinguishes a signature that was archived twice (same `R` and same
+//! challenge) from real nonce reuse (same `R` and different challenges) which means the key is
+//! exposed. [`audit_nonce_reuse`] does both passes over an archive that can be iterated twice.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     fun::{marker::*, s, Point, Scalar, G},
+//!     nonce_audit::audit_nonce_reuse,
+//!     Message, Signature,
+//! };
+//! let schnorr = schnorr_fun::Schnorr::<sha2::Sha256>::verify_only();
+//! let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! // a badly broken signer that uses the same nonce every time
+//! let mut r = Scalar::random(&mut rand::thread_rng());
+//! let R = Point::even_y_from_scalar_mul(G, &mut r);
+//! let archive = [b"one".as_slice(), b"two", b"three"]
+//!     .iter()
+//!     .map(|message| {
+//!         let message = Message::<Public>::plain("my-app", message);
+//!         let c = schnorr.challenge(&R, &keypair.public_key(), message);
+//!         let s = s!(r + c * keypair.secret_key()).public();
+//!         (keypair.public_key(), message, Signature { R, s })
+//!     })
+//!     .collect::<Vec<_>>();
+//! let report = audit_nonce_reuse(&schnorr, archive.iter().cloned(), archive.len());
+//! assert!(!report.is_clean());
+//! assert_eq!(report.reused_nonces()[0].positions(), &[0, 1, 2]);
+//! println!("{}", report);
+//! ```
+use crate::{Message, Schnorr, Signature};
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    marker::*,
+    Point, Scalar,
+};
+
+/// The number of bloom filter bits per expected record (about a 1% false positive rate).
+const BITS_PER_RECORD: usize = 10;
+/// The number of bloom filter bits set for each `(public key, R)` pair.
+const N_HASHES: u64 = 7;
+
+type NoncePair = ([u8; 32], [u8; 32]);
+
+/// The state of a two pass nonce reuse audit.
+///
+/// See the [module documentation](crate::nonce_audit).
+#[derive(Clone, Debug)]
+pub struct NonceAudit {
+    filter: Vec<u64>,
+    records: usize,
+    candidates: BTreeSet<NoncePair>,
+}
+
+impl NonceAudit {
+    /// Starts an audit sizing the bloom filter for `expected_records`.
+    ///
+    /// Underestimating only means more false positives to discard in the second pass.
+    pub fn new(expected_records: usize) -> Self {
+        let words = (expected_records.max(1) * BITS_PER_RECORD + 63) / 64;
+        Self {
+            filter: vec![0; words],
+            records: 0,
+            candidates: BTreeSet::new(),
+        }
+    }
+
+    fn bit_indices(&self, pair: &NoncePair) -> impl Iterator<Item = usize> {
+        // R is the output of a hash (or should be!) so its bytes are already uniformly random
+        let word = |i: usize| {
+            u64::from_be_bytes(pair.1[i..i + 8].try_into().expect("8 bytes"))
+                ^ u64::from_be_bytes(pair.0[i..i + 8].try_into().expect("8 bytes"))
+        };
+        let (h1, h2) = (word(0), word(8) | 1);
+        let n_bits = self.filter.len() as u64 * 64;
+        (0..N_HASHES).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % n_bits) as usize)
+    }
+
+    /// The first pass: adds the record of `signature` by `public_key`.
+    pub fn ingest(&mut self, public_key: &Point<EvenY>, signature: &Signature) {
+        let pair = (public_key.to_xonly_bytes(), signature.R.to_xonly_bytes());
+        let mut seen = true;
+        for bit in self.bit_indices(&pair).collect::<Vec<_>>() {
+            let (word, mask) = (bit / 64, 1u64 << (bit % 64));
+            seen &= self.filter[word] & mask != 0;
+            self.filter[word] |= mask;
+        }
+        if seen {
+            self.candidates.insert(pair);
+        }
+        self.records += 1;
+    }
+
+    /// The number of records ingested.
+    pub fn records(&self) -> usize {
+        self.records
+    }
+
+    /// The number of `(public key, R)` pairs flagged for confirmation so far.
+    pub fn candidates(&self) -> usize {
+        self.candidates.len()
+    }
+
+    /// The second pass: goes through the same `records` in the same order keeping only the
+    /// flagged ones and produces the report.
+    pub fn confirm<'a, H: Digest<OutputSize = U32> + Clone, NG>(
+        self,
+        schnorr: &Schnorr<H, NG>,
+        records: impl IntoIterator<Item = (Point<EvenY>, Message<'a>, Signature)>,
+    ) -> NonceAuditReport {
+        let mut groups = BTreeMap::<NoncePair, Vec<(usize, Scalar<Public, Zero>)>>::new();
+        for (position, (public_key, message, signature)) in records.into_iter().enumerate() {
+            let pair = (public_key.to_xonly_bytes(), signature.R.to_xonly_bytes());
+            if self.candidates.contains(&pair) {
+                let challenge = schnorr.challenge(&signature.R, &public_key, message);
+                groups.entry(pair).or_default().push((position, challenge));
+            }
+        }
+
+        let mut false_positives = self.candidates.len() - groups.len();
+        let mut reused_nonces = Vec::new();
+        for ((public_key, R), uses) in groups {
+            if uses.len() < 2 {
+                false_positives += 1;
+                continue;
+            }
+            let distinct_challenges = uses
+                .iter()
+                .map(|(_, challenge)| challenge.to_bytes())
+                .collect::<BTreeSet<_>>()
+                .len();
+            reused_nonces.push(ReusedNonce {
+                public_key: Point::from_xonly_bytes(public_key).expect("was a valid point"),
+                R: Point::from_xonly_bytes(R).expect("was a valid point"),
+                positions: uses.into_iter().map(|(position, _)| position).collect(),
+                distinct_challenges,
+            });
+        }
+        NonceAuditReport {
+            records: self.records,
+            false_positives,
+            reused_nonces,
+        }
+    }
+}
+
+/// Runs both passes of a [`NonceAudit`] over `records` (which is iterated twice).
+pub fn audit_nonce_reuse<'a, H: Digest<OutputSize = U32> + Clone, NG, I>(
+    schnorr: &Schnorr<H, NG>,
+    records: I,
+    expected_records: usize,
+) -> NonceAuditReport
+where
+    I: IntoIterator<Item = (Point<EvenY>, Message<'a>, Signature)> + Clone,
+{
+    let mut audit = NonceAudit::new(expected_records);
+    for (public_key, _, signature) in records.clone() {
+        audit.ingest(&public_key, &signature);
+    }
+    audit.confirm(schnorr, records)
+}
+
+/// An `R` that appears in more than one record for the same key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReusedNonce {
+    /// The key that signed.
+    pub public_key: Point<EvenY>,
+    /// The nonce that was repeated.
+    pub R: Point<EvenY>,
+    positions: Vec<usize>,
+    distinct_challenges: usize,
+}
+
+impl ReusedNonce {
+    /// The position in the archive of every record using the nonce.
+    pub fn positions(&self) -> &[usize] {
+        &self.positions
+    }
+
+    /// Whether the nonce signed different challenges which means the secret key can be computed
+    /// from the signatures. If not, the records are copies of the same signature.
+    pub fn exposes_key(&self) -> bool {
+        self.distinct_challenges > 1
+    }
+}
+
+/// The result of a [`NonceAudit`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct NonceAuditReport {
+    records: usize,
+    false_positives: usize,
+    reused_nonces: Vec<ReusedNonce>,
+}
+
+impl NonceAuditReport {
+    /// The number of records audited.
+    pub fn records(&self) -> usize {
+        self.records
+    }
+
+    /// The number of bloom filter false positives the second pass discarded.
+    pub fn false_positives(&self) -> usize {
+        self.false_positives
+    }
+
+    /// Every repeated nonce ordered by public key and `R`.
+    pub fn reused_nonces(&self) -> &[ReusedNonce] {
+        &self.reused_nonces
+    }
+
+    /// Whether no key signed different messages with the same nonce (duplicate copies of a
+    /// signature are allowed).
+    pub fn is_clean(&self) -> bool {
+        !self.reused_nonces.iter().any(ReusedNonce::exposes_key)
+    }
+}
+
+impl fmt::Display for NonceAuditReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "nonce reuse audit of {} records: {}",
+            self.records,
+            if self.is_clean() {
+                "clean"
+            } else {
+                "KEYS EXPOSED"
+            }
+        )?;
+        for reused in &self.reused_nonces {
+            writeln!(
+                f,
+                "{} key {} reused R {} at records {:?}",
+                if reused.exposes_key() {
+                    "EXPOSED"
+                } else {
+                    "duplicate"
+                },
+                reused.public_key,
+                reused.R,
+                reused.positions
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+    use secp256kfun::{g, G};
+
+    #[test]
+    fn finds_reuse_among_many_signatures() {
+        let schnorr = Schnorr::<sha2::Sha256, crate::nonce::Deterministic<sha2::Sha256>>::default();
+        let keypairs =
+            [0u8; 3].map(|_| schnorr.new_keypair(Scalar::random(&mut rand::thread_rng())));
+        let messages = (0..200u32).map(|i| i.to_be_bytes()).collect::<Vec<_>>();
+        let mut archive = messages
+            .iter()
+            .enumerate()
+            .map(|(i, message)| {
+                let keypair = &keypairs[i % 3];
+                let message = Message::<Public>::raw(message);
+                (
+                    keypair.public_key(),
+                    message,
+                    schnorr.sign(keypair, message),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let report = audit_nonce_reuse(&schnorr, archive.iter().cloned(), archive.len());
+        assert!(report.is_clean());
+        assert!(report.reused_nonces().is_empty());
+        assert_eq!(report.records(), 200);
+
+        // a copy of a signature is reported but doesn't expose the key
+        archive.push(archive[5].clone());
+        // a forged reuse: same R as record 7, a different message and made up s
+        let (public_key, _, signature) = archive[7].clone();
+        let other = Message::<Public>::raw(b"other");
+        archive.push((
+            public_key,
+            other,
+            Signature {
+                R: signature.R,
+                s: Scalar::random(&mut rand::thread_rng()).public().mark_zero(),
+            },
+        ));
+        // the same R by a different key isn't reuse
+        archive.push((keypairs[0].public_key(), other, signature.clone()));
+        assert_ne!(keypairs[0].public_key(), public_key);
+
+        // a tiny filter so there are false positives to throw away
+        let report = audit_nonce_reuse(&schnorr, archive.iter().cloned(), 8);
+        assert!(report.false_positives() > 0);
+        assert!(!report.is_clean());
+        let mut reused = report.reused_nonces().to_vec();
+        reused.sort_by_key(|reused| reused.positions()[0]);
+        assert_eq!(reused.len(), 2);
+        assert_eq!(reused[0].positions(), &[5, 200]);
+        assert!(!reused[0].exposes_key());
+        assert_eq!(reused[1].positions(), &[7, 201]);
+        assert!(reused[1].exposes_key());
+        assert_eq!(reused[1].R, signature.R);
+        assert_eq!(g!(keypairs[1].secret_key() * G), reused[1].public_key);
+        assert!(report.to_string().contains("KEYS EXPOSED"));
+    }
+}
@@ -0,0 +1,366 @@
+//! A signer that checks every message against a set of policies before signing it.
+//!
+//! A signing server that holds a key is only as safe as the code that decides what it signs. As
+//! defense in depth [`PolicySigner`] refuses to sign unless every one of its [`Policy`]s allows
+//! it. There are policies for rate limits ([`RateLimit`]), allowed application tags
+//! ([`AllowedAppTags`]), allowed message prefixes ([`AllowedPrefixes`]) and deny lists
+//! ([`DenyList`]) and any closure taking the message and the time is a policy too.
+//!
+//! [`PolicySigner::sign`] checks every policy before signing and only records the signature with
+//! them (e.g. counting it towards a rate limit) once they have all allowed it. It takes `&mut self`
+//! so signing is atomic with respect to the policies: put the signer behind a mutex to share it
+//! between threads.
+//!
+//! There is no clock in `no_std` so the caller passes the current time to each call. The
+//! policies here don't care about the unit as long as it's the same one used to configure them
+//! (e.g. seconds since the UNIX epoch).
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     fun::{marker::*, Scalar},
+//!     policy::{AllowedAppTags, PolicyError, PolicySigner, RateLimit},
+//!     Message,
+//! };
+//! let schnorr = schnorr_fun::test_instance!();
+//! let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let mut signer = PolicySigner::new(schnorr, keypair)
+//!     .with_policy(AllowedAppTags::new(["my-app/withdrawal"]))
+//!     // at most 2 signatures an hour
+//!     .with_policy(RateLimit::new(2, 60 * 60));
+//! let now = 1_700_000_000;
+//! let withdrawal = Message::<Public>::plain("my-app/withdrawal", b"send 1 BTC to alice");
+//! assert!(signer.sign(withdrawal, now).is_ok());
+//! assert!(signer.sign(withdrawal, now + 1).is_ok());
+//! assert_eq!(
+//!     signer.sign(withdrawal, now + 2),
+//!     Err(PolicyError::RateLimited {
+//!         retry_at: now + 60 * 60
+//!     })
+//! );
+//! let other = Message::<Public>::plain("other-app", b"anything");
+//! assert_eq!(
+//!     signer.sign(other, now + 60 * 60),
+//!     Err(PolicyError::AppTagNotAllowed)
+//! );
+//! ```
+use crate::{fun::KeyPair, Message, Schnorr, Signature};
+use alloc::{
+    boxed::Box,
+    collections::{BTreeSet, VecDeque},
+    vec::Vec,
+};
+use core::fmt;
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    marker::*,
+    nonce::NonceGen,
+    Point,
+};
+
+/// Why a policy refused to sign a message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolicyError {
+    /// Too many messages have been signed recently.
+    RateLimited {
+        /// The earliest time a message could be signed again.
+        retry_at: u64,
+    },
+    /// The message doesn't have an allowed application tag.
+    AppTagNotAllowed,
+    /// The message doesn't start with an allowed prefix.
+    PrefixNotAllowed,
+    /// The message is on a deny list.
+    Denied,
+    /// A custom policy refused to sign.
+    Custom(&'static str),
+}
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PolicyError::RateLimited { retry_at } => {
+                write!(f, "rate limited until {}", retry_at)
+            }
+            PolicyError::AppTagNotAllowed => write!(f, "the message's app tag is not allowed"),
+            PolicyError::PrefixNotAllowed => write!(f, "the message's prefix is not allowed"),
+            PolicyError::Denied => write!(f, "the message is on a deny list"),
+            PolicyError::Custom(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PolicyError {}
+
+/// A rule about which messages may be signed and when.
+pub trait Policy {
+    /// Checks whether `message` may be signed at time `now`.
+    fn check(&self, message: Message<'_, Public>, now: u64) -> Result<(), PolicyError>;
+
+    /// Records that `message` was signed at time `now`.
+    ///
+    /// This is only called after every policy of the signer allowed the message.
+    fn record(&mut self, _message: Message<'_, Public>, _now: u64) {}
+}
+
+impl<F> Policy for F
+where
+    F: Fn(Message<'_, Public>, u64) -> Result<(), PolicyError>,
+{
+    fn check(&self, message: Message<'_, Public>, now: u64) -> Result<(), PolicyError> {
+        self(message, now)
+    }
+}
+
+/// Allows at most `max` signatures in any window of `period` time units.
+#[derive(Clone, Debug)]
+pub struct RateLimit {
+    max: usize,
+    period: u64,
+    signed_at: VecDeque<u64>,
+}
+
+impl RateLimit {
+    /// Allows at most `max` signatures in any window of `period`.
+    pub fn new(max: usize, period: u64) -> Self {
+        Self {
+            max,
+            period,
+            signed_at: VecDeque::new(),
+        }
+    }
+
+    fn in_window(&self, now: u64) -> impl Iterator<Item = &u64> {
+        let period = self.period;
+        self.signed_at
+            .iter()
+            .filter(move |&&time| now.saturating_sub(time) < period)
+    }
+}
+
+impl Policy for RateLimit {
+    fn check(&self, _message: Message<'_, Public>, now: u64) -> Result<(), PolicyError> {
+        let in_window = self.in_window(now).count();
+        if in_window < self.max {
+            return Ok(());
+        }
+        // the signature that has to drop out of the window before signing again
+        let oldest = self
+            .in_window(now)
+            .nth(in_window - self.max)
+            .copied()
+            .unwrap_or(now);
+        Err(PolicyError::RateLimited {
+            retry_at: oldest.saturating_add(self.period),
+        })
+    }
+
+    fn record(&mut self, _message: Message<'_, Public>, now: u64) {
+        while self
+            .signed_at
+            .front()
+            .map_or(false, |&time| now.saturating_sub(time) >= self.period)
+        {
+            self.signed_at.pop_front();
+        }
+        self.signed_at.push_back(now);
+    }
+}
+
+/// Only allows messages created with [`Message::plain`] with one of the given application tags.
+#[derive(Clone, Debug)]
+pub struct AllowedAppTags(BTreeSet<&'static str>);
+
+impl AllowedAppTags {
+    /// Allows messages with any of `app_tags`.
+    pub fn new(app_tags: impl IntoIterator<Item = &'static str>) -> Self {
+        Self(app_tags.into_iter().collect())
+    }
+}
+
+impl Policy for AllowedAppTags {
+    fn check(&self, message: Message<'_, Public>, _now: u64) -> Result<(), PolicyError> {
+        match message.app_tag {
+            Some(app_tag) if self.0.contains(app_tag) => Ok(()),
+            _ => Err(PolicyError::AppTagNotAllowed),
+        }
+    }
+}
+
+/// Only allows messages whose bytes (not including the application tag) start with one of the
+/// given prefixes.
+#[derive(Clone, Debug)]
+pub struct AllowedPrefixes(Vec<Vec<u8>>);
+
+impl AllowedPrefixes {
+    /// Allows messages starting with any of `prefixes`.
+    pub fn new<P: AsRef<[u8]>>(prefixes: impl IntoIterator<Item = P>) -> Self {
+        Self(
+            prefixes
+                .into_iter()
+                .map(|prefix| prefix.as_ref().to_vec())
+                .collect(),
+        )
+    }
+}
+
+impl Policy for AllowedPrefixes {
+    fn check(&self, message: Message<'_, Public>, _now: u64) -> Result<(), PolicyError> {
+        let bytes = message.bytes.as_inner();
+        if self.0.iter().any(|prefix| bytes.starts_with(prefix)) {
+            Ok(())
+        } else {
+            Err(PolicyError::PrefixNotAllowed)
+        }
+    }
+}
+
+/// Refuses to sign any of a set of messages (compared by their bytes, not including the
+/// application tag).
+#[derive(Clone, Debug, Default)]
+pub struct DenyList(BTreeSet<Vec<u8>>);
+
+impl DenyList {
+    /// Denies all of `messages`.
+    pub fn new<M: AsRef<[u8]>>(messages: impl IntoIterator<Item = M>) -> Self {
+        Self(
+            messages
+                .into_iter()
+                .map(|message| message.as_ref().to_vec())
+                .collect(),
+        )
+    }
+
+    /// Adds `message` to the deny list.
+    pub fn deny(&mut self, message: &[u8]) {
+        self.0.insert(message.to_vec());
+    }
+}
+
+impl Policy for DenyList {
+    fn check(&self, message: Message<'_, Public>, _now: u64) -> Result<(), PolicyError> {
+        if self.0.contains(message.bytes.as_inner()) {
+            Err(PolicyError::Denied)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A signer that only signs messages all of its policies allow.
+///
+/// See the [module documentation](crate::policy).
+pub struct PolicySigner<CH, NG> {
+    schnorr: Schnorr<CH, NG>,
+    keypair: KeyPair<EvenY>,
+    policies: Vec<Box<dyn Policy + Send>>,
+}
+
+impl<CH, NG> fmt::Debug for PolicySigner<CH, NG> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PolicySigner")
+            .field("public_key", &self.keypair.public_key())
+            .field("n_policies", &self.policies.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<CH, NG> PolicySigner<CH, NG>
+where
+    CH: Digest<OutputSize = U32> + Clone,
+    NG: NonceGen,
+{
+    /// A signer for `keypair` with no policies (so it signs everything).
+    pub fn new(schnorr: Schnorr<CH, NG>, keypair: KeyPair<EvenY>) -> Self {
+        Self {
+            schnorr,
+            keypair,
+            policies: Vec::new(),
+        }
+    }
+
+    /// Adds `policy` to the signer.
+    pub fn with_policy(mut self, policy: impl Policy + Send + 'static) -> Self {
+        self.add_policy(policy);
+        self
+    }
+
+    /// Adds `policy` to the signer.
+    pub fn add_policy(&mut self, policy: impl Policy + Send + 'static) {
+        self.policies.push(Box::new(policy));
+    }
+
+    /// The public key of the signer.
+    pub fn public_key(&self) -> Point<EvenY> {
+        self.keypair.public_key()
+    }
+
+    /// Signs `message` at time `now` if every policy allows it.
+    ///
+    /// The first policy (in the order they were added) to refuse determines the error.
+    pub fn sign(
+        &mut self,
+        message: Message<'_, Public>,
+        now: u64,
+    ) -> Result<Signature, PolicyError> {
+        for policy in &self.policies {
+            policy.check(message, now)?;
+        }
+        for policy in &mut self.policies {
+            policy.record(message, now);
+        }
+        Ok(self.schnorr.sign(&self.keypair, message))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fun::Scalar;
+
+    #[test]
+    fn policies_are_checked_before_recording() {
+        let schnorr = crate::test_instance!();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let mut signer = PolicySigner::new(schnorr.clone(), keypair)
+            .with_policy(RateLimit::new(2, 10))
+            .with_policy(AllowedPrefixes::new([b"pay:"]))
+            .with_policy(DenyList::new([b"pay:mallory"]))
+            .with_policy(|_message: Message<'_, Public>, now: u64| {
+                if now % 2 == 0 {
+                    Ok(())
+                } else {
+                    Err(PolicyError::Custom("odd times are closed"))
+                }
+            });
+        let alice = Message::<Public>::raw(b"pay:alice");
+        let signature = signer.sign(alice, 0).unwrap();
+        assert!(schnorr.verify(&signer.public_key(), alice, &signature));
+
+        // refused messages don't count towards the rate limit
+        assert_eq!(
+            signer.sign(Message::raw(b"pay:mallory"), 2),
+            Err(PolicyError::Denied)
+        );
+        assert_eq!(
+            signer.sign(Message::raw(b"transfer"), 2),
+            Err(PolicyError::PrefixNotAllowed)
+        );
+        assert_eq!(
+            signer.sign(alice, 3),
+            Err(PolicyError::Custom("odd times are closed"))
+        );
+        assert!(signer.sign(alice, 4).is_ok());
+        assert_eq!(
+            signer.sign(alice, 6),
+            Err(PolicyError::RateLimited { retry_at: 10 })
+        );
+        assert!(signer.sign(alice, 10).is_ok());
+        assert_eq!(
+            signer.sign(alice, 12),
+            Err(PolicyError::RateLimited { retry_at: 14 })
+        );
+    }
+}
@@ -0,0 +1,22 @@
+//! The stable core of `schnorr_fun` in one import.
+//!
+//! This includes everything in [`secp256kfun::prelude`] along with the BIP340 signature scheme
+//! and the adaptor signature traits.
+//!
+//! ```
+//! use schnorr_fun::prelude::*;
+//! let schnorr = Schnorr::<sha2::Sha256, Deterministic<sha2::Sha256>>::default();
+//! let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let message = Message::<Public>::plain("my-app", b"hello");
+//! let signature = schnorr.sign(&keypair, message);
+//! assert!(schnorr.verify(&keypair.public_key(), message, &signature));
+//! ```
+//!
+//! Like [`secp256kfun::prelude`] items are only removed or renamed in a breaking release.
+//!
+//! [`secp256kfun::prelude`]: crate::fun::prelude
+pub use crate::{
+    adaptor::{Adaptor, EncryptedSign, EncryptedSignature},
+    fun::prelude::*,
+    Message, Schnorr, Signature,
+};
@@ -0,0 +1,383 @@
+//! Signatures whose nonces are fixed ahead of time for each state of a protocol.
+//!
+//! In LN-symmetry (eltoo) style protocols the update and settlement transactions for each state
+//! are presigned and the counterparty wants to know the signature nonce `R` for a state before the
+//! signature exists (e.g. so it can be committed to in a script or used to predict the signature
+//! of an adaptor). [`StateNonces`] derives a nonce for every state number from a secret seed so
+//! the public nonces can be handed out in advance with [`public_nonce`] and
+//! [`sign`]/[`encrypted_sign`] later produce signatures that use exactly that nonce. The
+//! counterparty checks them with [`verify_presigned`] and [`verify_presigned_encrypted`].
+//!
+//! # Security notes
+//!
+//! - A state's nonce must only ever sign **one** message. Signing two different messages (or a
+//!   signature and an encrypted signature) with the same nonce reveals the secret key.
+//!   `StateNonces` refuses to sign a state lower than the last one it signed or a different
+//!   message for the same state. It can only do this if you persist [`last_signed`] and restore
+//!   it with [`set_last_signed`] after a restart. Protocols that need several signatures per
+//!   state have to give each of them its own state number (e.g. `2n` and `2n + 1`).
+//! - The nonces are derived from the secret seed rather than by adding a public tweak for the
+//!   state to one nonce. With public tweaks two signatures on different states would be enough to
+//!   solve for the secret key.
+//!
+//! [`public_nonce`]: StateNonces::public_nonce
+//! [`sign`]: StateNonces::sign
+//! [`encrypted_sign`]: StateNonces::encrypted_sign
+//! [`last_signed`]: StateNonces::last_signed
+//! [`set_last_signed`]: StateNonces::set_last_signed
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     adaptor::Adaptor,
+//!     fun::{marker::*, Scalar},
+//!     presign::{verify_presigned, verify_presigned_encrypted, StateNonces},
+//!     Message,
+//! };
+//! let schnorr = schnorr_fun::test_instance!();
+//! let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let mut nonces = StateNonces::<sha2::Sha256>::new([42u8; 32]);
+//! // the nonce for state 7 is handed out before anything is signed
+//! let R_7 = nonces.public_nonce(&keypair, 7);
+//! let update = Message::<Public>::plain("update", b"state 7");
+//! let signature = nonces.sign(&schnorr, &keypair, 7, update).unwrap();
+//! assert!(verify_presigned(&schnorr, &keypair.public_key(), &R_7, update, &signature));
+//! // signing something else for state 7 would leak the key so it's refused
+//! let other = Message::<Public>::plain("update", b"other state 7");
+//! assert!(nonces.sign(&schnorr, &keypair, 7, other).is_err());
+//!
+//! // the settlement for state 8 is encrypted under an adaptor point
+//! let decryption_key = Scalar::random(&mut rand::thread_rng());
+//! let encryption_key = schnorr.encryption_key_for(&decryption_key);
+//! let R_8 = nonces.public_nonce(&keypair, 8);
+//! let settle = Message::<Public>::plain("settle", b"state 8");
+//! let encrypted = nonces
+//!     .encrypted_sign(&schnorr, &keypair, 8, &encryption_key, settle)
+//!     .unwrap();
+//! assert!(verify_presigned_encrypted(
+//!     &schnorr,
+//!     &keypair.public_key(),
+//!     &R_8,
+//!     &encryption_key,
+//!     settle,
+//!     &encrypted
+//! ));
+//! ```
+use crate::{adaptor::EncryptedSignature, Message, Schnorr, Signature};
+use core::fmt;
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    g,
+    hash::{HashAdd, Tag},
+    marker::*,
+    s, KeyPair, Point, Scalar, G,
+};
+
+/// The last state a [`StateNonces`] signed and the challenge of what it signed.
+///
+/// Persist this after every signature (see the [module documentation](crate::presign)).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct SignedState {
+    /// The state number.
+    pub state: u64,
+    /// The signature challenge. Signing the same message again gives the same challenge.
+    pub challenge: [u8; 32],
+}
+
+/// Error returned when signing a state would reuse a nonce.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PresignError {
+    /// A later state has already been signed.
+    StateBehind {
+        /// The state that was asked to be signed.
+        state: u64,
+        /// The last state that was signed.
+        last: u64,
+    },
+    /// The state has already been used to sign a different message.
+    StateAlreadySigned {
+        /// The state that was asked to be signed.
+        state: u64,
+    },
+}
+
+impl fmt::Display for PresignError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PresignError::StateBehind { state, last } => write!(
+                f,
+                "state {} can't be signed because state {} already has been",
+                state, last
+            ),
+            PresignError::StateAlreadySigned { state } => {
+                write!(f, "state {} has already signed a different message", state)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PresignError {}
+
+/// Derives a nonce for each state number and signs with it.
+///
+/// It isn't `Clone` since two copies could sign different messages for the same state.
+///
+/// See the [module documentation](crate::presign).
+pub struct StateNonces<H> {
+    nonce_hash: H,
+    seed: [u8; 32],
+    last_signed: Option<SignedState>,
+}
+
+// `Drop` is implemented with or without `zeroize` so that enabling it doesn't change the API
+impl<H> Drop for StateNonces<H> {
+    fn drop(&mut self) {
+        #[cfg(feature = "zeroize")]
+        crate::fun::zeroize::Zeroize::zeroize(&mut self.seed);
+    }
+}
+
+impl<H> fmt::Debug for StateNonces<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StateNonces")
+            .field("seed", &"<redacted>")
+            .field("last_signed", &self.last_signed)
+            .finish()
+    }
+}
+
+impl<H: Tag + Default> StateNonces<H> {
+    /// Creates the nonces for a protocol instance (e.g. a channel) from a secret `seed`.
+    ///
+    /// Use a fresh random seed for every instance and keep it as secret as the signing key.
+    pub fn new(seed: [u8; 32]) -> Self {
+        Self {
+            nonce_hash: H::default().tag(b"schnorr_fun/presign/nonce"),
+            seed,
+            last_signed: None,
+        }
+    }
+}
+
+impl<H> StateNonces<H> {
+    /// The last state that was signed (if any).
+    pub fn last_signed(&self) -> Option<SignedState> {
+        self.last_signed
+    }
+
+    /// Restores [`last_signed`](Self::last_signed) after a restart.
+    pub fn set_last_signed(&mut self, last_signed: Option<SignedState>) {
+        self.last_signed = last_signed;
+    }
+
+    fn check_state(&self, state: u64, challenge: [u8; 32]) -> Result<(), PresignError> {
+        match self.last_signed {
+            Some(last) if state < last.state => Err(PresignError::StateBehind {
+                state,
+                last: last.state,
+            }),
+            Some(last) if state == last.state && challenge != last.challenge => {
+                Err(PresignError::StateAlreadySigned { state })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Clone> StateNonces<H> {
+    /// The secret nonce for `state` negated so its image has an even y-coordinate.
+    fn secret_nonce(&self, keypair: &KeyPair<EvenY>, state: u64) -> (Scalar, Point<EvenY>) {
+        let mut r = Scalar::from_hash(
+            self.nonce_hash
+                .clone()
+                .add(&self.seed[..])
+                .add(keypair.public_key())
+                .add(&state.to_be_bytes()[..]),
+        );
+        let R = Point::even_y_from_scalar_mul(G, &mut r);
+        (r, R)
+    }
+
+    /// The public nonce that signatures for `state` under `keypair` will have.
+    pub fn public_nonce(&self, keypair: &KeyPair<EvenY>, state: u64) -> Point<EvenY> {
+        self.secret_nonce(keypair, state).1
+    }
+
+    /// Signs `message` for `state` with the state's nonce.
+    ///
+    /// The signature's `R` is [`public_nonce`](Self::public_nonce). Signing the same message for
+    /// the same state again gives the same signature.
+    pub fn sign<CH: Digest<OutputSize = U32> + Clone, NG>(
+        &mut self,
+        schnorr: &Schnorr<CH, NG>,
+        keypair: &KeyPair<EvenY>,
+        state: u64,
+        message: Message<'_, impl Secrecy>,
+    ) -> Result<Signature, PresignError> {
+        let (x, X) = keypair.as_tuple();
+        let (r, R) = self.secret_nonce(keypair, state);
+        let c = schnorr.challenge(&R, &X, message);
+        let challenge = c.public().to_bytes();
+        self.check_state(state, challenge)?;
+        self.last_signed = Some(SignedState { state, challenge });
+        let s = s!(r + c * x).public();
+        Ok(Signature { R, s })
+    }
+
+    /// Signs `message` for `state` encrypted under `encryption_key` (an adaptor signature).
+    ///
+    /// The nonce of the decrypted signature is `public_nonce + encryption_key` (negated if it has
+    /// an odd y-coordinate) so the counterparty can predict it before the signature exists.
+    pub fn encrypted_sign<CH: Digest<OutputSize = U32> + Clone, NG>(
+        &mut self,
+        schnorr: &Schnorr<CH, NG>,
+        keypair: &KeyPair<EvenY>,
+        state: u64,
+        encryption_key: &Point,
+        message: Message<'_, impl Secrecy>,
+    ) -> Result<EncryptedSignature, PresignError> {
+        let (x, X) = keypair.as_tuple();
+        let (mut r, R_hat) = self.secret_nonce(keypair, state);
+        let (R, needs_negation) = encrypted_nonce(&R_hat, encryption_key)
+            .expect("computationally unreachable: the nonce is derived independently of Y");
+        r.conditional_negate(needs_negation);
+        let c = schnorr.challenge(&R, &X, message);
+        let challenge = c.public().to_bytes();
+        self.check_state(state, challenge)?;
+        self.last_signed = Some(SignedState { state, challenge });
+        let s_hat = s!(r + c * x).public();
+        Ok(EncryptedSignature {
+            R,
+            s_hat,
+            needs_negation,
+        })
+    }
+}
+
+/// The nonce a signature presigned with `public_nonce` and encrypted under `encryption_key` will
+/// have once it is decrypted and whether the decryption key has to be negated.
+///
+/// Returns `None` if `public_nonce + encryption_key` is zero.
+pub fn encrypted_nonce(
+    public_nonce: &Point<EvenY>,
+    encryption_key: &Point,
+) -> Option<(Point<EvenY>, bool)> {
+    Some(
+        g!(public_nonce + encryption_key)
+            .normalize()
+            .non_zero()?
+            .into_point_with_even_y(),
+    )
+}
+
+/// Checks that `signature` is a valid signature on `message` under `public_key` with the nonce
+/// `public_nonce` that was handed out ahead of time.
+#[must_use]
+pub fn verify_presigned<CH: Digest<OutputSize = U32> + Clone, NG>(
+    schnorr: &Schnorr<CH, NG>,
+    public_key: &Point<EvenY>,
+    public_nonce: &Point<EvenY>,
+    message: Message<'_, Public>,
+    signature: &Signature,
+) -> bool {
+    signature.R == *public_nonce && schnorr.verify(public_key, message, signature)
+}
+
+/// Checks that `encrypted_signature` is a valid encrypted signature on `message` under
+/// `public_key` and `encryption_key` made with the nonce `public_nonce` that was handed out ahead
+/// of time.
+#[must_use]
+pub fn verify_presigned_encrypted<CH: Digest<OutputSize = U32> + Clone, NG>(
+    schnorr: &Schnorr<CH, NG>,
+    public_key: &Point<EvenY>,
+    public_nonce: &Point<EvenY>,
+    encryption_key: &Point,
+    message: Message<'_, Public>,
+    encrypted_signature: &EncryptedSignature,
+) -> bool {
+    use crate::adaptor::Adaptor;
+    encrypted_nonce(public_nonce, encryption_key)
+        == Some((encrypted_signature.R, encrypted_signature.needs_negation))
+        && schnorr.verify_encrypted_signature(
+            public_key,
+            encryption_key,
+            message,
+            encrypted_signature,
+        )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adaptor::Adaptor;
+    use sha2::Sha256;
+
+    #[test]
+    fn presigned_states() {
+        let schnorr = crate::test_instance!();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let mut nonces = StateNonces::<Sha256>::new([1u8; 32]);
+        let message = Message::<Public>::plain("test", b"state 3");
+
+        let R_3 = nonces.public_nonce(&keypair, 3);
+        assert_ne!(R_3, nonces.public_nonce(&keypair, 4));
+        let signature = nonces.sign(&schnorr, &keypair, 3, message).unwrap();
+        assert!(verify_presigned(
+            &schnorr,
+            &keypair.public_key(),
+            &R_3,
+            message,
+            &signature
+        ));
+        assert!(!verify_presigned(
+            &schnorr,
+            &keypair.public_key(),
+            &nonces.public_nonce(&keypair, 4),
+            message,
+            &signature
+        ));
+        // signing the same thing again is fine
+        assert!(nonces.sign(&schnorr, &keypair, 3, message).ok() == Some(signature));
+
+        let decryption_key = Scalar::random(&mut rand::thread_rng());
+        let encryption_key = schnorr.encryption_key_for(&decryption_key);
+        assert_eq!(
+            nonces.encrypted_sign(&schnorr, &keypair, 3, &encryption_key, message),
+            Err(PresignError::StateAlreadySigned { state: 3 })
+        );
+        let encrypted = nonces
+            .encrypted_sign(&schnorr, &keypair, 5, &encryption_key, message)
+            .unwrap();
+        let R_5 = nonces.public_nonce(&keypair, 5);
+        assert!(verify_presigned_encrypted(
+            &schnorr,
+            &keypair.public_key(),
+            &R_5,
+            &encryption_key,
+            message,
+            &encrypted
+        ));
+        let decrypted = schnorr.decrypt_signature(decryption_key, encrypted);
+        assert_eq!(
+            Some(decrypted.R),
+            encrypted_nonce(&R_5, &encryption_key).map(|(R, _)| R)
+        );
+        assert!(schnorr.verify(&keypair.public_key(), message, &decrypted));
+
+        assert!(matches!(
+            nonces.sign(&schnorr, &keypair, 4, message),
+            Err(PresignError::StateBehind { state: 4, last: 5 })
+        ));
+        // after a restart the record has to be restored to keep refusing
+        let mut restored = StateNonces::<Sha256>::new([1u8; 32]);
+        restored.set_last_signed(nonces.last_signed());
+        assert!(restored.sign(&schnorr, &keypair, 5, message).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+//! The terms of the verification equation of a signature as plain data.
+//!
+//! A zero-knowledge circuit that proves "I know a valid signature under this key" has to do the
+//! same arithmetic as [`Schnorr::verify`]. [`VerificationEquation`] exports every term of it so the
+//! circuit builder can mirror (and test against) this crate's math:
+//!
+//! ```text
+//! R = s * G + (-c) * X    where    c = H(R_x || X_x || m)
+//! ```
+//!
+//! `H` is the challenge hash of the [`Schnorr`] instance (for [BIP340] the
+//! `"BIP0340/challenge"` tagged SHA256) and `m` is the message as it is hashed (see
+//! [`VerificationEquation::challenge_preimage`]).
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     fun::{marker::*, Scalar, G},
+//!     Message,
+//! };
+//! let schnorr = schnorr_fun::test_instance!();
+//! let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let message = Message::<Public>::plain("my-app", b"prove I know a signature on this");
+//! let signature = schnorr.sign(&keypair, message);
+//! let equation = schnorr.verification_equation(&keypair.public_key(), message, &signature);
+//! let [(s, base), (minus_c, X)] = equation.terms();
+//! assert_eq!(base, G.normalize());
+//! assert_eq!(X, keypair.public_key().normalize());
+//! assert!(equation.holds());
+//! ```
+//!
+//! [BIP340]: https://bips.xyz/340
+use crate::{
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        g,
+        marker::*,
+        Point, Scalar, G,
+    },
+    Message, Schnorr, Signature,
+};
+
+/// The terms of the verification equation of a Schnorr signature.
+///
+/// See the [module documentation](crate::public_inputs).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerificationEquation {
+    /// The public key `X`.
+    pub public_key: Point<EvenY>,
+    /// The signature's nonce `R`.
+    pub R: Point<EvenY>,
+    /// The signature's `s`.
+    pub s: Scalar<Public, Zero>,
+    /// The challenge `c`.
+    pub challenge: Scalar<Public, Zero>,
+}
+
+impl VerificationEquation {
+    /// The scalar and base point pairs that sum to `R`: `[(s, G), (-c, X)]`.
+    pub fn terms(&self) -> [(Scalar<Public, Zero>, Point); 2] {
+        [
+            (self.s, G.normalize()),
+            (-self.challenge, self.public_key.normalize()),
+        ]
+    }
+
+    /// The sum of the [`terms`](Self::terms).
+    pub fn implied_R(&self) -> Point<Normal, Public, Zero> {
+        g!(self.s * G - self.challenge * self.public_key).normalize()
+    }
+
+    /// Whether the equation holds, i.e. the signature is valid.
+    ///
+    /// This doesn't check that the challenge is the hash of its preimage.
+    pub fn holds(&self) -> bool {
+        self.implied_R() == self.R
+    }
+
+    /// The bytes that are hashed to get the challenge: `R_x || X_x` followed by the message as it
+    /// is hashed (a message with an application tag starts with the tag padded to 64 bytes).
+    ///
+    /// For [BIP340] instances the hash is the `"BIP0340/challenge"` tagged hash so its first two
+    /// blocks of input (the tag prefix) aren't included.
+    ///
+    /// [BIP340]: https://bips.xyz/340
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn challenge_preimage(&self, message: Message<'_, Public>) -> alloc::vec::Vec<u8> {
+        let mut preimage = alloc::vec::Vec::with_capacity(64 + message.len());
+        preimage.extend_from_slice(&self.R.to_xonly_bytes());
+        preimage.extend_from_slice(&self.public_key.to_xonly_bytes());
+        if let Some(app_tag) = message.app_tag {
+            let mut padded_tag = [0u8; 64];
+            padded_tag[..app_tag.len()].copy_from_slice(app_tag.as_bytes());
+            preimage.extend_from_slice(&padded_tag);
+        }
+        preimage.extend_from_slice(message.bytes.as_inner());
+        preimage
+    }
+}
+
+impl<NG, CH: Digest<OutputSize = U32> + Clone> Schnorr<CH, NG> {
+    /// Exports the terms of the verification equation of `signature` on `message` under
+    /// `public_key`.
+    ///
+    /// The terms are returned whether or not the signature is valid. Check with
+    /// [`VerificationEquation::holds`].
+    pub fn verification_equation(
+        &self,
+        public_key: &Point<EvenY>,
+        message: Message<'_, Public>,
+        signature: &Signature,
+    ) -> VerificationEquation {
+        VerificationEquation {
+            public_key: *public_key,
+            R: signature.R,
+            s: signature.s,
+            challenge: self.challenge(&signature.R, public_key, message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fun::hash::Tag;
+    use sha2::Sha256;
+
+    #[test]
+    fn verification_equation_mirrors_verify() {
+        let schnorr = crate::test_instance!();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let message = Message::<Public>::plain("test", b"circuit");
+        let signature = schnorr.sign(&keypair, message);
+        let equation = schnorr.verification_equation(&keypair.public_key(), message, &signature);
+        assert!(equation.holds());
+        let [(s, base), (minus_c, X)] = equation.terms();
+        assert_eq!(g!(s * base + minus_c * X), equation.R);
+
+        let preimage = equation.challenge_preimage(message);
+        let challenge = Scalar::from_hash(
+            Sha256::default()
+                .tag(b"BIP0340/challenge")
+                .chain_update(&preimage),
+        );
+        assert_eq!(challenge, equation.challenge);
+
+        let other = Message::<Public>::plain("test", b"other");
+        assert!(!schnorr
+            .verification_equation(&keypair.public_key(), other, &signature)
+            .holds());
+    }
+}
@@ -0,0 +1,211 @@
+//! Experimental key-recovery Schnorr signatures. **Not BIP340**.
+//!
+//! On links where every byte counts (e.g. low power radio) sending a 32-byte public key with each
+//! 64-byte signature is expensive. In this variant the challenge doesn't commit to the public key
+//! (`c = H(R || m)` instead of `H(R || X || m)`) so the verifier can recover the signer's key from
+//! the signature: `X = c⁻¹(s * G - R)`. The verifier then checks the recovered key against the
+//! one it expects (or looks it up in the set of keys it knows about) so the key never has to be
+//! sent.
+//!
+//! # Security notes
+//!
+//! This is not the BIP340 scheme and its signatures are not valid BIP340 signatures (or the other
+//! way around). Leaving the key out of the challenge has consequences:
+//!
+//! - **Any** `(R, s)` recovers to some key so [`recover`] on its own proves nothing. A signature
+//!   is only meaningful once the recovered key is compared to a key the verifier already trusts
+//!   (use [`verify`]).
+//! - **Related keys are malleable.** Anyone who sees a signature under `X` can turn it into a
+//!   signature on the same message under `X + t * G` for any `t` they like. Don't use it with keys
+//!   derived by adding public tweaks (e.g. BIP32 non-hardened derivation or Taproot tweaks) unless
+//!   the verifier only accepts one key per message.
+//! - Bind messages to their context. The message should include the link or device it is meant
+//!   for (e.g. with [`Message::plain`]) since there is no key in the challenge to do it.
+//!
+//! [`recover`]: RecoverableSchnorr::recover
+//! [`verify`]: RecoverableSchnorr::verify
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     fun::{marker::*, KeyPair, Scalar},
+//!     nonce, recoverable::RecoverableSchnorr, Message,
+//! };
+//! let schnorr = RecoverableSchnorr::<sha2::Sha256, _>::new(
+//!     nonce::Deterministic::<sha2::Sha256>::default(),
+//! );
+//! let keypair = KeyPair::<Normal>::new(Scalar::random(&mut rand::thread_rng()));
+//! let message = Message::<Public>::plain("sensor-7", b"temperature=21.5");
+//! let signature = schnorr.sign(&keypair, message);
+//! // only the 64 signature bytes go over the link
+//! let bytes = signature.to_bytes();
+//! let received = schnorr_fun::recoverable::RecoverableSignature::from_bytes(bytes).unwrap();
+//! assert_eq!(schnorr.recover(message, &received), Some(keypair.public_key()));
+//! assert!(schnorr.verify(&keypair.public_key(), message, &received));
+//! ```
+use crate::{Message, Signature};
+use secp256kfun::{
+    derive_nonce,
+    digest::{generic_array::typenum::U32, Digest},
+    g,
+    hash::{HashAdd, Tag},
+    marker::*,
+    nonce::NonceGen,
+    s, KeyPair, Point, Scalar, G,
+};
+
+/// A key-recovery Schnorr signature. See the [module documentation](crate::recoverable).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct RecoverableSignature {
+    /// The signature's public nonce.
+    pub R: Point<EvenY>,
+    /// The challenge response.
+    pub s: Scalar<Public, Zero>,
+}
+
+impl RecoverableSignature {
+    /// Serializes the signature as the 32-byte x-only nonce followed by the 32-byte response.
+    pub fn to_bytes(&self) -> [u8; 64] {
+        Signature {
+            R: self.R,
+            s: self.s,
+        }
+        .to_bytes()
+    }
+
+    /// Deserializes a signature from [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: [u8; 64]) -> Option<Self> {
+        let Signature { R, s } = Signature::from_bytes(bytes)?;
+        Some(Self { R, s })
+    }
+}
+
+/// Signs and recovers [`RecoverableSignature`]s.
+#[derive(Clone, Debug)]
+pub struct RecoverableSchnorr<H, NG> {
+    nonce_gen: NG,
+    challenge_hash: H,
+}
+
+impl<H: Tag + Default, NG: Tag> RecoverableSchnorr<H, NG> {
+    /// Creates an instance deriving nonces with `nonce_gen`.
+    pub fn new(nonce_gen: NG) -> Self {
+        Self {
+            nonce_gen: nonce_gen.tag(b"schnorr_fun/recoverable"),
+            challenge_hash: H::default().tag(b"schnorr_fun/recoverable/challenge"),
+        }
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Clone, NG> RecoverableSchnorr<H, NG> {
+    /// The challenge `H(R || m)` (the public key is deliberately left out).
+    pub fn challenge<S: Secrecy>(
+        &self,
+        R: &Point<EvenY, impl Secrecy>,
+        message: Message<'_, S>,
+    ) -> Scalar<S, Zero> {
+        Scalar::from_hash(self.challenge_hash.clone().add(R).add(message))
+            .mark_zero()
+            .set_secrecy::<S>()
+    }
+
+    /// Recovers the public key that `signature` is a signature on `message` under.
+    ///
+    /// Every signature recovers to some key so this doesn't mean anything until the key is
+    /// compared to one you trust. Returns `None` if no key can be recovered (the challenge or the
+    /// key would be zero).
+    pub fn recover(
+        &self,
+        message: Message<'_, Public>,
+        signature: &RecoverableSignature,
+    ) -> Option<Point> {
+        let RecoverableSignature { R, s } = signature;
+        let c_inv = self.challenge(R, message).non_zero()?.invert();
+        g!(c_inv * (s * G - R)).normalize().non_zero()
+    }
+
+    /// Checks that `signature` is a signature on `message` under `public_key`.
+    #[must_use]
+    pub fn verify(
+        &self,
+        public_key: &Point,
+        message: Message<'_, Public>,
+        signature: &RecoverableSignature,
+    ) -> bool {
+        let RecoverableSignature { R, s } = signature;
+        let c = self.challenge(R, message);
+        g!(s * G - c * public_key) == *R
+    }
+}
+
+impl<H, NG> RecoverableSchnorr<H, NG>
+where
+    H: Digest<OutputSize = U32> + Clone,
+    NG: NonceGen,
+{
+    /// Signs `message` so that the verifier can recover `keypair`'s public key.
+    pub fn sign(
+        &self,
+        keypair: &KeyPair<Normal>,
+        message: Message<'_, impl Secrecy>,
+    ) -> RecoverableSignature {
+        let (x, X) = keypair.as_tuple();
+        let mut r = derive_nonce!(
+            nonce_gen => &self.nonce_gen,
+            secret => x,
+            public => [X, message]
+        );
+        let R = Point::even_y_from_scalar_mul(G, &mut r);
+        let c = self.challenge(&R, message);
+        let s = s!(r + c * x).public();
+        RecoverableSignature { R, s }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::nonce::Deterministic;
+    use sha2::Sha256;
+
+    #[test]
+    fn sign_recover_verify() {
+        let schnorr = RecoverableSchnorr::<Sha256, _>::new(Deterministic::<Sha256>::default());
+        let keypair = KeyPair::<Normal>::new(Scalar::random(&mut rand::thread_rng()));
+        let message = Message::<Public>::plain("test", b"hello");
+        let signature = schnorr.sign(&keypair, message);
+        assert_eq!(
+            schnorr.recover(message, &signature),
+            Some(keypair.public_key())
+        );
+        assert!(schnorr.verify(&keypair.public_key(), message, &signature));
+        assert_eq!(
+            RecoverableSignature::from_bytes(signature.to_bytes()),
+            Some(signature.clone())
+        );
+
+        // a different message recovers to some other key
+        let other = Message::<Public>::plain("test", b"goodbye");
+        assert_ne!(
+            schnorr.recover(other, &signature),
+            Some(keypair.public_key())
+        );
+        assert!(!schnorr.verify(&keypair.public_key(), other, &signature));
+
+        // the related key malleability described in the security notes
+        let t = Scalar::random(&mut rand::thread_rng());
+        let c = schnorr.challenge(&signature.R, message);
+        let tweaked = RecoverableSignature {
+            R: signature.R,
+            s: s!(signature.s + c * t).public(),
+        };
+        let tweaked_key = g!(keypair.public_key() + t * G).normalize();
+        assert!(schnorr.verify(&tweaked_key.non_zero().unwrap(), message, &tweaked));
+    }
+}
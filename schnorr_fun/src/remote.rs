@@ -0,0 +1,262 @@
+//! Signing with a key held by an external device or service.
+//!
+//! Hardware signers, HSMs and air-gapped machines keep the secret key (and the secret nonce) to
+//! themselves so they can only be asked to do the two secret operations of a signature: generate a
+//! nonce and compute the final scalar `s`. Implement [`RemoteNonceGen`] and [`RemoteSigner`] to
+//! forward these to the device and [`Schnorr::sign_remote`] does everything else. It checks the
+//! signature that the device produced so a faulty or malicious device can't hand back an invalid
+//! signature.
+//!
+//! The device is sent the message in a [`SignRequest`] and computes the BIP340 challenge itself
+//! from the message, its own nonce and its own key. It must never sign a challenge it's handed:
+//! a host that can choose the challenges of several concurrent sessions can combine the answers
+//! into a signature on a message the device never saw (the ROS attack). Since the device has the
+//! message it can also show the user what they're signing.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     fun::{g, marker::*, Point, Scalar, G},
+//!     remote::{RemoteNonceGen, RemoteSigner, SignRequest},
+//!     Message,
+//! };
+//! use std::collections::BTreeMap;
+//!
+//! // a stand-in for a real device
+//! struct Device {
+//!     secret_key: Scalar,
+//!     nonces: BTreeMap<Vec<u8>, Scalar>,
+//! }
+//!
+//! impl RemoteNonceGen for Device {
+//!     type Error = &'static str;
+//!
+//!     fn gen_nonce(&mut self, session_id: &[u8]) -> Result<Point, Self::Error> {
+//!         let r = Scalar::random(&mut rand::thread_rng());
+//!         let R = g!(r * G).normalize();
+//!         self.nonces.insert(session_id.to_vec(), r);
+//!         Ok(R)
+//!     }
+//! }
+//!
+//! impl RemoteSigner for Device {
+//!     fn public_key(&mut self) -> Result<Point, Self::Error> {
+//!         Ok(g!(self.secret_key * G).normalize())
+//!     }
+//!
+//!     fn sign(
+//!         &mut self,
+//!         session_id: &[u8],
+//!         request: SignRequest<'_>,
+//!     ) -> Result<Scalar<Public, Zero>, Self::Error> {
+//!         // a real device would show the user request.message and ask for approval here
+//!         // the nonce must never be used twice
+//!         let r = self.nonces.remove(session_id).ok_or("unknown session")?;
+//!         let schnorr = schnorr_fun::test_instance!();
+//!         Ok(request.respond(&schnorr, &r, &self.secret_key))
+//!     }
+//! }
+//!
+//! let schnorr = schnorr_fun::test_instance!();
+//! let mut device = Device {
+//!     secret_key: Scalar::random(&mut rand::thread_rng()),
+//!     nonces: Default::default(),
+//! };
+//! let message = Message::<Public>::plain("my-app", b"hello");
+//! let signature = schnorr.sign_remote(&mut device, b"session-1", message).unwrap();
+//! let (public_key, _) = device.public_key().unwrap().into_point_with_even_y();
+//! assert!(schnorr.verify(&public_key, message, &signature));
+//! ```
+use crate::{Message, Schnorr, Signature};
+use core::fmt;
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    g,
+    marker::*,
+    s, KeyPair, Point, Scalar, G,
+};
+
+/// A device that generates and keeps the secret nonces for signatures.
+pub trait RemoteNonceGen {
+    /// The error returned when the device can't be reached or refuses a request.
+    type Error;
+
+    /// Asks the device to generate a fresh secret nonce `r` for the session and return `R = r * G`.
+    ///
+    /// The device must remember `r` until it is asked to [`sign`](RemoteSigner::sign) for
+    /// `session_id` and must never use it for another session.
+    fn gen_nonce(&mut self, session_id: &[u8]) -> Result<Point, Self::Error>;
+}
+
+/// A device that holds a secret key and can complete signatures with the nonces it generated.
+pub trait RemoteSigner: RemoteNonceGen {
+    /// Asks the device for the public key `X = x * G` of the key it signs with.
+    fn public_key(&mut self) -> Result<Point, Self::Error>;
+
+    /// Asks the device to sign the request's message with the session's nonce and forget the
+    /// nonce.
+    ///
+    /// The device should compute the signature's `s` with [`SignRequest::respond`] (or get the
+    /// same result some other way) so that the challenge is derived from the message rather than
+    /// taken from the host. It must refuse to sign twice for the same session since that reveals
+    /// the secret key.
+    fn sign(
+        &mut self,
+        session_id: &[u8],
+        request: SignRequest<'_>,
+    ) -> Result<Scalar<Public, Zero>, Self::Error>;
+}
+
+/// What a [`RemoteSigner`] is asked to sign.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SignRequest<'a> {
+    /// The message to sign.
+    pub message: Message<'a, Public>,
+}
+
+impl SignRequest<'_> {
+    /// Computes the `s` of the BIP340 signature on the message with the nonce `r` and key `x`.
+    ///
+    /// The nonce, key and challenge are all derived here from `r`, `x` and the message so the
+    /// host has no say in what gets signed.
+    pub fn respond<CH: Digest<OutputSize = U32> + Clone, NG>(
+        &self,
+        schnorr: &Schnorr<CH, NG>,
+        r: &Scalar,
+        x: &Scalar,
+    ) -> Scalar<Public, Zero> {
+        let keypair = KeyPair::<EvenY>::new(*x);
+        let (x, X) = keypair.as_tuple();
+        let mut r = *r;
+        let (R, negate_nonce) = g!(r * G).normalize().into_point_with_even_y();
+        r.conditional_negate(negate_nonce);
+        let c = schnorr.challenge(&R, &X, self.message);
+        s!(r + c * x).public()
+    }
+}
+
+impl<CH: Digest<OutputSize = U32> + Clone, NG> Schnorr<CH, NG> {
+    /// Signs `message` with the key held by `signer`.
+    ///
+    /// `session_id` identifies the signature to the device so it can find the nonce again. It must
+    /// be unique for each signature (e.g. a counter or random bytes).
+    ///
+    /// The signature the device produces is verified before it is returned. See the [module
+    /// documentation](crate::remote).
+    pub fn sign_remote<S: RemoteSigner>(
+        &self,
+        signer: &mut S,
+        session_id: &[u8],
+        message: Message<'_, Public>,
+    ) -> Result<Signature, RemoteSignError<S::Error>> {
+        let (X, _) = signer
+            .public_key()
+            .map_err(RemoteSignError::Device)?
+            .into_point_with_even_y();
+        let (R, _) = signer
+            .gen_nonce(session_id)
+            .map_err(RemoteSignError::Device)?
+            .into_point_with_even_y();
+        let s = signer
+            .sign(session_id, SignRequest { message })
+            .map_err(RemoteSignError::Device)?;
+        let signature = Signature { R, s };
+
+        if !self.verify(&X, message, &signature) {
+            return Err(RemoteSignError::InvalidSignature);
+        }
+
+        Ok(signature)
+    }
+}
+
+/// Error returned by [`Schnorr::sign_remote`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteSignError<E> {
+    /// The device returned an error.
+    Device(E),
+    /// The device returned an `s` that doesn't make a valid signature.
+    InvalidSignature,
+}
+
+impl<E: fmt::Display> fmt::Display for RemoteSignError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RemoteSignError::Device(error) => write!(f, "remote signer error: {error}"),
+            RemoteSignError::InvalidSignature => {
+                write!(f, "remote signer produced an invalid signature")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: fmt::Debug + fmt::Display> std::error::Error for RemoteSignError<E> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use secp256kfun::{g, G};
+
+    struct OneShotDevice {
+        x: Scalar,
+        r: Option<Scalar>,
+        tamper: bool,
+    }
+
+    impl RemoteNonceGen for OneShotDevice {
+        type Error = ();
+
+        fn gen_nonce(&mut self, _session_id: &[u8]) -> Result<Point, ()> {
+            let r = Scalar::random(&mut rand::thread_rng());
+            self.r = Some(r);
+            Ok(g!(r * G).normalize())
+        }
+    }
+
+    impl RemoteSigner for OneShotDevice {
+        fn public_key(&mut self) -> Result<Point, ()> {
+            Ok(g!(self.x * G).normalize())
+        }
+
+        fn sign(
+            &mut self,
+            _session_id: &[u8],
+            request: SignRequest<'_>,
+        ) -> Result<Scalar<Public, Zero>, ()> {
+            let schnorr = crate::test_instance!();
+            let s = request.respond(&schnorr, &self.r.take().ok_or(())?, &self.x);
+            Ok(if self.tamper { s!(s + 1).public() } else { s })
+        }
+    }
+
+    #[test]
+    fn remote_signing_handles_parity_and_checks_device() {
+        let schnorr = crate::test_instance!();
+        let message = Message::<Public>::plain("test", b"remote");
+        // enough signatures for every combination of nonce and key parity with high probability
+        for _ in 0..16 {
+            let mut device = OneShotDevice {
+                x: Scalar::random(&mut rand::thread_rng()),
+                r: None,
+                tamper: false,
+            };
+            let signature = schnorr.sign_remote(&mut device, b"id", message).unwrap();
+            let (X, _) = device.public_key().unwrap().into_point_with_even_y();
+            assert!(schnorr.verify(&X, message, &signature));
+            // the nonce is gone
+            assert_eq!(device.sign(b"id", SignRequest { message }), Err(()));
+        }
+
+        let mut device = OneShotDevice {
+            x: Scalar::random(&mut rand::thread_rng()),
+            r: None,
+            tamper: true,
+        };
+        assert_eq!(
+            schnorr.sign_remote(&mut device, b"id", message),
+            Err(RemoteSignError::InvalidSignature)
+        );
+    }
+}
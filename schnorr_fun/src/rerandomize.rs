@@ -0,0 +1,219 @@
+//! Signing under rerandomized keys for unlinkable presentations.
+//!
+//! A key `X` can be rerandomized to `Y = X + r * G` (with the y-coordinate made even). Signatures
+//! under `Y` are ordinary BIP340 signatures that can't be linked to `X` by anyone who doesn't
+//! know `r`. This is useful for credentials: a holder whose key `X` is known to an issuer can
+//! present a fresh key each time and only reveal the link when they want to.
+//!
+//! The link can be shown in two ways:
+//!
+//! - Reveal the randomness `r`. Anyone can check it with [`check_rerandomization`].
+//! - Without revealing `r`, prove knowledge of the discrete logarithm of `Y - X` with
+//!   [`Schnorr::prove_rerandomization`] which is checked with [`Schnorr::verify_rerandomization`].
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     fun::{marker::*, Scalar},
+//!     rerandomize::{check_rerandomization, RerandomizedKey},
+//!     Message,
+//! };
+//! let schnorr = schnorr_fun::test_instance!();
+//! let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let rerandomized = RerandomizedKey::random(&keypair, &mut rand::thread_rng());
+//! let message = Message::<Public>::plain("my-app", b"presentation");
+//! let signature = schnorr.sign(rerandomized.keypair(), message);
+//! assert!(schnorr.verify(&rerandomized.public_key(), message, &signature));
+//!
+//! // show the link by revealing the randomness
+//! assert!(check_rerandomization(
+//!     &keypair.public_key(),
+//!     &rerandomized.public_key(),
+//!     rerandomized.randomness()
+//! ));
+//! // or without revealing it
+//! let proof = schnorr.prove_rerandomization(&keypair, &rerandomized, b"session-1");
+//! assert!(schnorr.verify_rerandomization(
+//!     &keypair.public_key(),
+//!     &rerandomized.public_key(),
+//!     b"session-1",
+//!     &proof
+//! ));
+//! ```
+use crate::{Message, Schnorr, Signature};
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    g,
+    marker::*,
+    nonce::NonceGen,
+    rand_core::RngCore,
+    s, KeyPair, Point, Scalar, G,
+};
+
+/// The application tag of the [`Message`] signed by a rerandomization proof.
+pub const RERANDOMIZATION_APP_TAG: &str = "schnorr_fun/rerandomization";
+
+/// A keypair rerandomized from another one along with the randomness that links them.
+///
+/// Its `Debug` output only shows the rerandomized public key so logging it doesn't leak the
+/// secret key or link it to the original key.
+#[derive(Clone, PartialEq, Eq)]
+pub struct RerandomizedKey {
+    keypair: KeyPair<EvenY>,
+    randomness: Scalar,
+    /// The discrete logarithm of the rerandomized key minus the original key.
+    link: Scalar,
+}
+
+impl core::fmt::Debug for RerandomizedKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("RerandomizedKey")
+            .field("public_key", &self.public_key())
+            .finish_non_exhaustive()
+    }
+}
+
+impl RerandomizedKey {
+    /// Rerandomizes `keypair` with `randomness`.
+    ///
+    /// Returns `None` if the rerandomized key would be zero or the same as the original key.
+    pub fn new(keypair: &KeyPair<EvenY>, randomness: Scalar) -> Option<Self> {
+        let secret_key = s!(keypair.secret_key() + randomness).non_zero()?;
+        let rerandomized = KeyPair::<EvenY>::new(secret_key);
+        let link = s!(rerandomized.secret_key() - keypair.secret_key()).non_zero()?;
+        Some(Self {
+            keypair: rerandomized,
+            randomness,
+            link,
+        })
+    }
+
+    /// Rerandomizes `keypair` with fresh randomness from `rng`.
+    pub fn random(keypair: &KeyPair<EvenY>, rng: &mut impl RngCore) -> Self {
+        Self::new(keypair, Scalar::random(rng)).expect("computationally unreachable")
+    }
+
+    /// The rerandomized keypair to sign with.
+    pub fn keypair(&self) -> &KeyPair<EvenY> {
+        &self.keypair
+    }
+
+    /// The rerandomized public key.
+    pub fn public_key(&self) -> Point<EvenY> {
+        self.keypair.public_key()
+    }
+
+    /// The randomness `r` that was added to the original key.
+    ///
+    /// Revealing this links the rerandomized key to the original.
+    pub fn randomness(&self) -> &Scalar {
+        &self.randomness
+    }
+}
+
+/// Checks that `rerandomized` is `original` rerandomized with `randomness`.
+#[must_use]
+pub fn check_rerandomization(
+    original: &Point<EvenY, impl Secrecy>,
+    rerandomized: &Point<EvenY, impl Secrecy>,
+    randomness: &Scalar<impl Secrecy, impl ZeroChoice>,
+) -> bool {
+    match g!(original + randomness * G).normalize().non_zero() {
+        Some(implied) => implied.eq_up_to_parity(rerandomized),
+        None => false,
+    }
+}
+
+impl<CH: Digest<OutputSize = U32> + Clone, NG: NonceGen> Schnorr<CH, NG> {
+    /// Proves that `rerandomized` was rerandomized from `original` without revealing the
+    /// randomness.
+    ///
+    /// The proof is a signature on `context` under the difference of the two public keys.
+    /// `context` should be unique to the session so the proof can't be replayed.
+    pub fn prove_rerandomization(
+        &self,
+        original: &KeyPair<EvenY>,
+        rerandomized: &RerandomizedKey,
+        context: &[u8],
+    ) -> Signature {
+        debug_assert_eq!(
+            g!(rerandomized.link * G + original.public_key()),
+            rerandomized.public_key()
+        );
+        let link = KeyPair::<EvenY>::new(rerandomized.link);
+        self.sign(
+            &link,
+            Message::<Public>::plain(RERANDOMIZATION_APP_TAG, context),
+        )
+    }
+}
+
+impl<CH: Digest<OutputSize = U32> + Clone, NG> Schnorr<CH, NG> {
+    /// Verifies a proof from [`prove_rerandomization`] that `rerandomized` was rerandomized from
+    /// `original`.
+    ///
+    /// [`prove_rerandomization`]: Self::prove_rerandomization
+    #[must_use]
+    pub fn verify_rerandomization(
+        &self,
+        original: &Point<EvenY, impl Secrecy>,
+        rerandomized: &Point<EvenY, impl Secrecy>,
+        context: &[u8],
+        proof: &Signature,
+    ) -> bool {
+        let link = match g!(rerandomized - original).normalize().non_zero() {
+            Some(link) => link.into_point_with_even_y().0,
+            None => return false,
+        };
+        self.verify(
+            &link,
+            Message::<Public>::plain(RERANDOMIZATION_APP_TAG, context),
+            proof,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rerandomization_links_only_the_right_keys() {
+        let schnorr = crate::test_instance!();
+        // enough keys to hit both parities of the rerandomized key with high probability
+        for _ in 0..8 {
+            let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+            let other = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+            let rerandomized = RerandomizedKey::random(&keypair, &mut rand::thread_rng());
+            let (X, Y) = (keypair.public_key(), rerandomized.public_key());
+
+            assert!(check_rerandomization(&X, &Y, rerandomized.randomness()));
+            assert!(!check_rerandomization(
+                &other.public_key(),
+                &Y,
+                rerandomized.randomness()
+            ));
+
+            let proof = schnorr.prove_rerandomization(&keypair, &rerandomized, b"one");
+            assert!(schnorr.verify_rerandomization(&X, &Y, b"one", &proof));
+            assert!(!schnorr.verify_rerandomization(&X, &Y, b"two", &proof));
+            assert!(!schnorr.verify_rerandomization(&other.public_key(), &Y, b"one", &proof));
+        }
+
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let cancelling = s!(-keypair.secret_key());
+        assert_eq!(RerandomizedKey::new(&keypair, cancelling), None);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn debug_is_redacted() {
+        let keypair = KeyPair::<EvenY>::new(Scalar::random(&mut rand::thread_rng()));
+        let rerandomized = RerandomizedKey::random(&keypair, &mut rand::thread_rng());
+        let debug = format!("{:?}", rerandomized);
+        assert!(debug.contains(&format!("{}", rerandomized.public_key())));
+        assert!(!debug.contains(&format!("{}", rerandomized.keypair().secret_key())));
+        assert!(!debug.contains(&format!("{}", rerandomized.randomness())));
+    }
+}
@@ -8,7 +8,9 @@ use crate::{
         hash::{HashAdd, Tag},
         marker::*,
         nonce::NonceGen,
-        s, KeyPair, Point, Scalar, G,
+        op, s,
+        signature::SignatureScheme,
+        KeyPair, Point, Scalar, G,
     },
     Message, Signature,
 };
@@ -134,17 +136,49 @@ where
     pub fn sign(&self, keypair: &KeyPair<EvenY>, message: Message<'_, impl Secrecy>) -> Signature {
         let (x, X) = keypair.as_tuple();
 
-        let mut r = derive_nonce!(
+        let r = derive_nonce!(
             nonce_gen => self.nonce_gen(),
             secret => x,
             public => [X, message]
         );
 
-        let R = Point::even_y_from_scalar_mul(G, &mut r);
-        let c = self.challenge(&R, &X, message);
-        let s = s!(r + c * x).public();
+        self.sign_with_nonce(keypair, r, message)
+    }
 
-        Signature { R, s }
+    /// Signs each of a set of related messages returning the signatures in the same order.
+    ///
+    /// This is for signing several variants of the same thing at once e.g. the signature messages
+    /// of versions of a transaction that pay different fees so that it can be fee bumped later
+    /// without the key. Each nonce is derived from the key and its own message exactly as in
+    /// [`sign`](Self::sign) so different messages always get different nonces. Signing the same
+    /// set again only gives the same signatures if the [`NonceGen`] is deterministic (e.g.
+    /// [`nonce::Deterministic`]). With a synthetic one each call gives fresh signatures.
+    ///
+    /// [`nonce::Deterministic`]: crate::nonce::Deterministic
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use schnorr_fun::{
+    /// #     Message,
+    /// #     fun::{marker::*, Scalar},
+    /// # };
+    /// # let schnorr = schnorr_fun::test_instance!();
+    /// let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+    /// // the signature messages for each fee rate
+    /// let sighashes = [[1u8; 32], [2u8; 32], [3u8; 32]];
+    /// let messages = [0, 1, 2].map(|i| Message::<Public>::raw(&sighashes[i]));
+    /// let signatures = schnorr.sign_batch(&keypair, messages);
+    /// for (message, signature) in messages.iter().zip(&signatures) {
+    ///     assert!(schnorr.verify(&keypair.public_key(), *message, signature));
+    /// }
+    /// ```
+    pub fn sign_batch<S: Secrecy, const N: usize>(
+        &self,
+        keypair: &KeyPair<EvenY>,
+        messages: [Message<'_, S>; N],
+    ) -> [Signature; N] {
+        messages.map(|message| self.sign(keypair, message))
     }
 }
 
@@ -154,6 +188,24 @@ impl<NG, CH: Digest<OutputSize = U32> + Clone> Schnorr<CH, NG> {
         self.challenge_hash.clone()
     }
 
+    /// Sign a message with a nonce chosen by the caller.
+    ///
+    /// The nonce's public image may have an odd y-coordinate in which case it is negated before
+    /// use. This is not exposed publicly except through the `hazmat` module.
+    pub(crate) fn sign_with_nonce(
+        &self,
+        keypair: &KeyPair<EvenY>,
+        mut r: Scalar,
+        message: Message<'_, impl Secrecy>,
+    ) -> Signature {
+        let (x, X) = keypair.as_tuple();
+        let R = Point::even_y_from_scalar_mul(G, &mut r);
+        let c = self.challenge(&R, &X, message);
+        let s = s!(r + c * x).public();
+
+        Signature { R, s }
+    }
+
     /// Convieninece method for creating a new signing [`KeyPair<EvenY>`]
     ///
     /// [`KeyPair<EvenY>`]: crate::fun::KeyPair
@@ -207,6 +259,12 @@ impl<NG, CH: Digest<OutputSize = U32> + Clone> Schnorr<CH, NG> {
 
     /// Verifies a signature on a message under a given public key.
     ///
+    /// Whether this runs in constant time depends on the [`Secrecy`] of the arguments. If the
+    /// public key, message and signature are all marked [`Public`] (the usual case) it uses the
+    /// same variable time algorithm as [`verify_vartime`]. If any of them are marked [`Secret`] it
+    /// runs in constant time instead.
+    ///
+    /// [`verify_vartime`]: Self::verify_vartime
     /// # Example
     ///
     /// ```
@@ -221,7 +279,7 @@ impl<NG, CH: Digest<OutputSize = U32> + Clone> Schnorr<CH, NG> {
     /// let public_key = Point::<EvenY, Public>::from_str("d69c3509bb99e412e68b0fe8544e72837dfa30746d8be2aa65975f29d22dc7b9").unwrap();
     /// let signature = Signature::<Public>::from_str("00000000000000000000003b78ce563f89a0ed9414f5aa28ad0d96d6795f9c6376afb1548af603b3eb45c9f8207dee1060cb71c04e80f593060b07d28308d7f4").unwrap();
     /// let message = hex::decode("4df3c3f68fcc83b27e9d42c90431a72499f17875c81a599b566c9889b9696703").unwrap();
-    /// assert!(schnorr.verify(&public_key, Message::<Secret>::raw(&message), &signature));
+    /// assert!(schnorr.verify(&public_key, Message::<Public>::raw(&message), &signature));
     ///
     /// // We could also say the message is secret if we want to use a constant time algorithm to verify the signature.
     /// assert!(schnorr.verify(&public_key, Message::<Secret>::raw(&message), &signature));
@@ -240,6 +298,86 @@ impl<NG, CH: Digest<OutputSize = U32> + Clone> Schnorr<CH, NG> {
         R_implied == R
     }
 
+    /// Verifies a signature on a message under a given public key in **variable time**.
+    ///
+    /// Verification only involves public data so it doesn't need to pay the cost of running in
+    /// constant time. This uses the secp256k1 endomorphism and an interleaved wNAF multiplication
+    /// (see [`op::double_mul_vartime`]) and only accepts [`Public`] inputs so it can't be used by
+    /// mistake on something secret.
+    ///
+    /// [`verify`] does the same thing when all its arguments are marked [`Public`].
+    ///
+    /// [`verify`]: Self::verify
+    /// [`op::double_mul_vartime`]: crate::fun::op::double_mul_vartime
+    #[must_use]
+    pub fn verify_vartime(
+        &self,
+        public_key: &Point<EvenY, Public>,
+        message: Message<'_, Public>,
+        signature: &Signature<Public>,
+    ) -> bool {
+        let (R, s) = signature.as_tuple();
+        let c = self.challenge(&R, public_key, message);
+        let R_implied = op::double_mul_vartime(s, G, s!(-c), public_key);
+        R_implied == R
+    }
+
+    /// Verifies a signature like [`verify`] but on failure diagnoses why.
+    ///
+    /// This is for tracking down incompatibilities with other implementations and is only compiled
+    /// in builds with `debug_assertions` (e.g. tests). It tries the mistakes that are commonly made
+    /// when implementing BIP340 so it does a lot more work than [`verify`] when the signature is
+    /// invalid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use schnorr_fun::{fun::{marker::*, Scalar}, Message, VerifyDiagnosis};
+    /// let schnorr = schnorr_fun::test_instance!();
+    /// let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+    /// let signature = schnorr.sign(&keypair, Message::<Public>::raw(b"hello"));
+    /// let public_key = keypair.public_key();
+    /// assert_eq!(schnorr.verify_explain(&public_key, Message::<Public>::raw(b"hello"), &signature), Ok(()));
+    /// assert_eq!(
+    ///     schnorr.verify_explain(&public_key, Message::<Public>::plain("my-app", b"hello"), &signature),
+    ///     Err(VerifyDiagnosis::AppTagMismatch)
+    /// );
+    /// ```
+    ///
+    /// [`verify`]: Self::verify
+    #[cfg(debug_assertions)]
+    pub fn verify_explain(
+        &self,
+        public_key: &Point<EvenY, impl Secrecy>,
+        message: Message<'_, impl Secrecy>,
+        signature: &Signature<impl Secrecy>,
+    ) -> Result<(), VerifyDiagnosis> {
+        let X = public_key.public();
+        let (R, s) = signature.as_tuple();
+        let s = s.public();
+        let c = self.challenge(&R, &X, message).public();
+        let implied_R = g!(s * G - c * X).normalize();
+        if implied_R == R {
+            return Ok(());
+        }
+        if implied_R == -R {
+            return Err(VerifyDiagnosis::NonceParity);
+        }
+        if g!(s * G + c * X) == R {
+            return Err(VerifyDiagnosis::KeyParity);
+        }
+        if message.app_tag.is_some() {
+            let raw = Message::<Public>::raw(message.bytes.as_inner());
+            if self.verify(&X, raw, signature) {
+                return Err(VerifyDiagnosis::AppTagMismatch);
+            }
+        }
+        Err(VerifyDiagnosis::ChallengeMismatch {
+            challenge: c,
+            implied_R: implied_R.to_bytes(),
+        })
+    }
+
     /// _Anticipates_ a Schnorr signature given the nonce `R` that will be used ahead of time.
     /// Deterministically returns the group element that corresponds to the scalar value of the
     /// signature. i.e `R + c * X`
@@ -254,6 +392,70 @@ impl<NG, CH: Digest<OutputSize = U32> + Clone> Schnorr<CH, NG> {
     }
 }
 
+/// Why a signature failed to verify. Returned by [`Schnorr::verify_explain`].
+#[cfg(debug_assertions)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VerifyDiagnosis {
+    /// `s * G - c * X` is the negation of `R`. The signer didn't negate their nonce when its point
+    /// had an odd y-coordinate.
+    NonceParity,
+    /// `s * G + c * X` is `R`. The signer didn't negate their secret key when its public key had
+    /// an odd y-coordinate.
+    KeyParity,
+    /// The signature is valid for the message bytes without the application tag. One side used
+    /// [`Message::plain`] and the other [`Message::raw`].
+    AppTagMismatch,
+    /// `s * G - c * X` isn't `R` for the verifier's challenge `c`. The signer most likely computed
+    /// a different challenge (e.g. from a different message, public key or hash tag).
+    ChallengeMismatch {
+        /// The challenge the verifier computed.
+        challenge: Scalar<Public, Zero>,
+        /// The encoding of the nonce implied by `challenge` i.e. `s * G - c * X`.
+        implied_R: [u8; 33],
+    },
+}
+
+impl<CH, NG> SignatureScheme for Schnorr<CH, NG>
+where
+    CH: Digest<OutputSize = U32> + Clone,
+    NG: NonceGen,
+{
+    type KeyPair = KeyPair<EvenY>;
+    type PublicKey = Point<EvenY>;
+    type PublicKeyBytes = [u8; 32];
+    type Signature = Signature;
+
+    fn new_keypair(&self, secret_key: Scalar) -> Self::KeyPair {
+        KeyPair::<EvenY>::new(secret_key)
+    }
+
+    fn public_key(&self, keypair: &Self::KeyPair) -> Self::PublicKey {
+        keypair.public_key()
+    }
+
+    /// Signs `message` as a raw BIP340 message.
+    fn sign(&self, keypair: &Self::KeyPair, message: &[u8; 32]) -> Self::Signature {
+        Schnorr::sign(self, keypair, Message::<Public>::raw(message))
+    }
+
+    fn verify(
+        &self,
+        public_key: &Self::PublicKey,
+        message: &[u8; 32],
+        signature: &Self::Signature,
+    ) -> bool {
+        Schnorr::verify(self, public_key, Message::<Public>::raw(message), signature)
+    }
+
+    fn public_key_to_bytes(public_key: &Self::PublicKey) -> Self::PublicKeyBytes {
+        public_key.to_xonly_bytes()
+    }
+
+    fn public_key_from_bytes(bytes: &[u8]) -> Option<Self::PublicKey> {
+        Point::from_xonly_bytes(bytes.try_into().ok()?)
+    }
+}
+
 #[cfg(test)]
 pub mod test {
     use crate::fun::nonce::Deterministic;
@@ -263,6 +465,34 @@ pub mod test {
     #[cfg(target_arch = "wasm32")]
     use wasm_bindgen_test::wasm_bindgen_test as test;
 
+    #[test]
+    fn generic_signature_scheme() {
+        fn roundtrip<S: SignatureScheme>(scheme: &S) {
+            let keypair = scheme.new_keypair(Scalar::random(&mut rand::thread_rng()));
+            let public_key = scheme.public_key(&keypair);
+            let signature = scheme.sign(&keypair, &[42u8; 32]);
+            assert!(scheme.verify(&public_key, &[42u8; 32], &signature));
+            assert!(!scheme.verify(&public_key, &[43u8; 32], &signature));
+            let bytes = S::public_key_to_bytes(&public_key);
+            assert!(S::public_key_from_bytes(bytes.as_ref()) == Some(public_key.clone()));
+            let decoded = S::signature_from_bytes(S::signature_to_bytes(&signature)).unwrap();
+            assert!(scheme.verify(&public_key, &[42u8; 32], &decoded));
+        }
+        let schnorr = crate::test_instance!();
+        roundtrip(&schnorr);
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let signature = SignatureScheme::sign(&schnorr, &keypair, &[1u8; 32]);
+        assert!(schnorr.verify(
+            &keypair.public_key(),
+            Message::<Public>::raw(&[1u8; 32]),
+            &signature
+        ));
+        assert_eq!(
+            <Schnorr<sha2::Sha256, Deterministic<sha2::Sha256>>>::signature_to_bytes(&signature),
+            signature.to_bytes()
+        );
+    }
+
     #[test]
     fn deterministic_nonces_for_different_message_kinds() {
         use core::str::FromStr;
@@ -292,6 +522,52 @@ pub mod test {
         assert_eq!(schnorr.sign(&keypair, Message::<Public>::plain("one", b"foo")), Signature::<Public>::from_str("2fcf6fd140bbc4048e802c62f028e24f6534e0d15d450963265b67eead774d8b4aa7638bec9d70aa60b97e86bc4a60bf43ad2ff58e981ee1bba4f45ce02ff2c0").unwrap());
     }
 
+    #[test]
+    #[cfg(debug_assertions)]
+    fn verify_explain_diagnoses_common_mistakes() {
+        let schnorr = crate::test_instance!();
+        let message = Message::<Public>::plain("test", b"message");
+        // a scalar whose point has an odd y-coordinate
+        let odd = |rng: &mut rand::rngs::ThreadRng| loop {
+            let x = Scalar::random(rng);
+            if g!(x * G).normalize().into_point_with_even_y().1 {
+                break x;
+            }
+        };
+        let (x, r) = (odd(&mut rand::thread_rng()), odd(&mut rand::thread_rng()));
+        let (X, _) = g!(x * G).normalize().into_point_with_even_y();
+        let (R, _) = g!(r * G).normalize().into_point_with_even_y();
+        let c = schnorr.challenge(&R, &X, message);
+
+        let keypair = schnorr.new_keypair(x);
+        let signature = schnorr.sign(&keypair, message);
+        assert_eq!(schnorr.verify_explain(&X, message, &signature), Ok(()));
+
+        let unnegated_nonce = Signature {
+            R,
+            s: s!(r + c * keypair.secret_key()).public(),
+        };
+        assert_eq!(
+            schnorr.verify_explain(&X, message, &unnegated_nonce),
+            Err(VerifyDiagnosis::NonceParity)
+        );
+
+        let unnegated_key = Signature {
+            R,
+            s: s!(-r + c * x).public(),
+        };
+        assert_eq!(
+            schnorr.verify_explain(&X, message, &unnegated_key),
+            Err(VerifyDiagnosis::KeyParity)
+        );
+
+        let other_message = Message::<Public>::plain("test", b"other message");
+        assert!(matches!(
+            schnorr.verify_explain(&X, other_message, &signature),
+            Err(VerifyDiagnosis::ChallengeMismatch { .. })
+        ));
+    }
+
     proptest! {
 
         #[test]
@@ -335,5 +611,23 @@ pub mod test {
                 assert_ne!(signature_1.R, signature_4.R);
             }
         }
+
+        #[test]
+        fn verify_vartime_agrees_with_verify(sk in any::<Scalar>(), other_sk in any::<Scalar>()) {
+            let schnorr = crate::test_instance!();
+            let keypair = schnorr.new_keypair(sk);
+            let other_keypair = schnorr.new_keypair(other_sk);
+            let msg = Message::<Public>::plain("test", b"vartime");
+            let signature = schnorr.sign(&keypair, msg);
+            let public_key = keypair.public_key();
+
+            assert!(schnorr.verify_vartime(&public_key, msg, &signature));
+            assert!(schnorr.verify(&public_key, msg, &signature.clone().set_secrecy::<Secret>()));
+            let wrong_msg = Message::<Public>::plain("test", b"constant time");
+            assert!(!schnorr.verify_vartime(&public_key, wrong_msg, &signature));
+            if keypair != other_keypair {
+                assert!(!schnorr.verify_vartime(&other_keypair.public_key(), msg, &signature));
+            }
+        }
     }
 }
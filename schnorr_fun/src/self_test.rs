@@ -0,0 +1,168 @@
+//! Known-answer self tests of BIP340 signing, verification and adaptor signatures.
+//!
+//! [`self_test`] runs the [curve arithmetic self tests] followed by [BIP340] test vectors and an
+//! adaptor signature round trip and returns a report of what passed. It is meant for deployments
+//! that have to run "power-on self tests" before using the library. The hash it is given must be
+//! the SHA256 implementation the application uses (the vectors fail for anything else) so it is
+//! tested too.
+//!
+//! # Example
+//!
+//! ```
+//! let report = schnorr_fun::self_test::self_test::<sha2::Sha256>();
+//! assert!(report.passed(), "{}", report);
+//! ```
+//!
+//! [curve arithmetic self tests]: crate::fun::self_test
+//! [BIP340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+use crate::{
+    adaptor::{Adaptor, EncryptedSign},
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        hex,
+        marker::*,
+        nonce::{self, NonceRng, Synthetic},
+        self_test::SelfTestReport,
+        Point, Scalar, Tag,
+    },
+    Message, Schnorr, Signature,
+};
+use core::str::FromStr;
+
+/// Test vectors 0 to 3 of BIP340: secret key, aux randomness, message, signature.
+const SIGNING_VECTORS: [(&str, &str, &str, &str); 4] = [
+    (
+        "0000000000000000000000000000000000000000000000000000000000000003",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "E907831F80848D1069A5371B402410364BDF1C5F8307B0084C55F1CE2DCA821525F66A4A85EA8B71E482A74F382D2CE5EBEEE8FDB2172F477DF4900D310536C0",
+    ),
+    (
+        "B7E151628AED2A6ABF7158809CF4F3C762E7160F38B4DA56A784D9045190CFEF",
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+        "6896BD60EEAE296DB48A229FF71DFE071BDE413E6D43F917DC8DCF8C78DE33418906D11AC976ABCCB20B091292BFF4EA897EFCB639EA871CFA95F6DE339E4B0A",
+    ),
+    (
+        "C90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B14E5C9",
+        "C87AA53824B4D7AE2EB035A2B5BBBCCC080E76CDC6D1692C4B0B62D798E6D906",
+        "7E2D58D8B3BCDF1ABADEC7829054F90DDA9805AAB56C77333024B9D0A508B75C",
+        "5831AAEED7B44BB74E5EAB94BA9D4294C49BCF2A60728D8B4C200F50DD313C1BAB745879A5AD954A72C45A91C3A51D3C7ADEA98D82F8481E0E1E03674A6F3FB7",
+    ),
+    (
+        "0B432B2677937381AEF05BB02A66ECD012773062CF3FA2549E44F58ED2401710",
+        "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+        "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+        "7EB0509757E246F19449885651611CB965ECC1A187DD51B64FDA1EDC9637D5EC97582B9CB13DB3933705B32BA982AF5AF25FD78881EBB32771FC5922EFC66EA3",
+    ),
+];
+
+/// Test vectors 4, 7 and 8 of BIP340: public key, message, signature, whether it is valid.
+const VERIFICATION_VECTORS: [(&str, &str, &str, bool); 3] = [
+    (
+        "D69C3509BB99E412E68B0FE8544E72837DFA30746D8BE2AA65975F29D22DC7B9",
+        "4DF3C3F68FCC83B27E9D42C90431A72499F17875C81A599B566C9889B9696703",
+        "00000000000000000000003B78CE563F89A0ED9414F5AA28AD0D96D6795F9C6376AFB1548AF603B3EB45C9F8207DEE1060CB71C04E80F593060B07D28308D7F4",
+        true,
+    ),
+    // negated message
+    (
+        "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+        "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+        "1FA62E331EDBC21C394792D2AB1100A7B432B013DF3F6FF4F99FCB33E0E1515F28890B3EDB6E7189B630448B515CE4F8622A954CFE545735AAEA5134FCCDB2BD",
+        false,
+    ),
+    // negated s value
+    (
+        "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+        "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+        "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769961764B3AA9B2FFCB6EF947B6887A226E8D7C93E00C5ED0C1834FF0D0C2E6DA6",
+        false,
+    ),
+];
+
+/// Always "generates" the same auxiliary randomness for reproducing the signing vectors.
+struct FixedAux([u8; 32]);
+
+impl NonceRng for FixedAux {
+    fn fill_bytes(&self, bytes: &mut [u8]) {
+        bytes.copy_from_slice(&self.0)
+    }
+}
+
+/// Runs the curve arithmetic, BIP340 and adaptor signature self tests with the SHA256
+/// implementation `H`.
+pub fn self_test<H>() -> SelfTestReport
+where
+    H: Digest<OutputSize = U32> + Tag + Default + Clone,
+{
+    let mut report = crate::fun::self_test::self_test();
+
+    report.record(
+        "bip340/sign",
+        SIGNING_VECTORS
+            .iter()
+            .all(|(secret_key, aux, message, signature)| {
+                let aux = hex::decode_array(aux).expect("valid hex");
+                let schnorr = Schnorr::<H, _>::new(Synthetic::<H, _>::new(FixedAux(aux)));
+                let keypair = schnorr.new_keypair(Scalar::from_str(secret_key).expect("valid"));
+                let message = hex::decode_array::<32>(message).expect("valid hex");
+                let message = Message::<Public>::raw(&message);
+                let expected = Signature::<Public>::from_str(signature).expect("valid");
+                let signature = schnorr.sign(&keypair, message);
+                signature == expected && schnorr.verify(&keypair.public_key(), message, &signature)
+            }),
+    );
+
+    let schnorr = Schnorr::<H>::verify_only();
+    report.record(
+        "bip340/verify",
+        VERIFICATION_VECTORS
+            .iter()
+            .all(|(public_key, message, signature, valid)| {
+                let public_key = Point::<EvenY>::from_str(public_key).expect("valid");
+                let message = hex::decode_array::<32>(message).expect("valid hex");
+                let signature = Signature::<Public>::from_str(signature).expect("valid");
+                let message = Message::<Public>::raw(&message);
+                schnorr.verify(&public_key, message, &signature) == *valid
+                    && schnorr.verify_vartime(&public_key, message, &signature) == *valid
+            }),
+    );
+
+    let schnorr = Schnorr::<H, _>::new(nonce::Deterministic::<H>::default());
+    let signing_keypair =
+        schnorr.new_keypair(Scalar::from_str(SIGNING_VECTORS[1].0).expect("valid"));
+    let decryption_key = Scalar::<Secret, NonZero>::from_str(SIGNING_VECTORS[2].0).expect("valid");
+    let encryption_key = schnorr.encryption_key_for(&decryption_key);
+    let message = Message::<Public>::plain("schnorr_fun/self_test", b"adaptor");
+    let encrypted_signature = schnorr.encrypted_sign(&signing_keypair, &encryption_key, message);
+    let signature = schnorr.decrypt_signature(decryption_key, encrypted_signature.clone());
+    report.record(
+        "bip340/adaptor_round_trip",
+        schnorr.verify_encrypted_signature(
+            &signing_keypair.public_key(),
+            &encryption_key,
+            message,
+            &encrypted_signature,
+        ) && schnorr.verify(&signing_keypair.public_key(), message, &signature)
+            && schnorr.recover_decryption_key(&encryption_key, &encrypted_signature, &signature)
+                == Some(decryption_key),
+    );
+
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn self_test_passes() {
+        let report = self_test::<sha2::Sha256>();
+        assert!(report.passed(), "{}", report);
+        assert!(report.results.len() > 3);
+        // the BIP340 vectors only pass with SHA256
+        let report = self_test::<sha2::Sha512_256>();
+        assert!(report.failures().any(|failure| failure == "bip340/sign"));
+    }
+}
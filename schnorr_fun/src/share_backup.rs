@@ -51,7 +51,22 @@
 //! Can be any scalar, but will often be a small integer for simplicity and smaller backups.
 //! By leaving this data piece at the end, we can use the length of the remaining data to
 //! easily decode either a single bech32 char into integer, or 52 chars into a 32 byte scalar.
+//!
+//! ### Checksum - (6)
+//!
+//! The bech32m checksum is a BCH code so as well as detecting mistakes it can correct up to two
+//! wrong characters in a backup that was copied by hand (one in backups with a scalar share index).
+//! Correction can't detect further errors so a badly mangled backup may be "corrected" to the wrong
+//! share. Use [`decode_backup_with_correction`] to get the corrected positions so they can be
+//! confirmed with the user.
+
+pub mod correction;
+pub mod mnemonic;
+pub mod recovery_kit;
+
+pub use correction::decode_backup_with_correction;
 
+use crate::frost::{IntoPartyIndex, PartyId};
 use alloc::{fmt, string::String, vec::Vec};
 use bech32::{u5, FromBase32, ToBase32, Variant::Bech32m};
 use core::str::FromStr;
@@ -83,7 +98,9 @@ pub fn polynomial_identifier<H: Default + Digest<OutputSize = U32>>(
 /// If using general scalar participant indicies, the encoded backup will be 122 bech32 characters.
 ///
 /// Encoding requires that the threshold is no greater than 1024.
-#[derive(Debug, Copy, Clone)]
+///
+/// The `Debug` output shows the identifier as bech32 characters and leaves out the secret share.
+#[derive(Copy, Clone)]
 pub struct ShareBackup {
     /// The number of shares required to reconstruct the joint secret.
     pub threshold: u16,
@@ -105,8 +122,9 @@ impl ShareBackup {
     pub fn new<H: Default + Digest<OutputSize = U32>>(
         polynomial: &[Point<Normal, Public, impl ZeroChoice>],
         secret_share: Scalar<Secret, Zero>,
-        share_index: Scalar<Public>,
+        share_index: impl IntoPartyIndex,
     ) -> Self {
+        let share_index = share_index.into_party_index();
         let threshold = polynomial.len() as u16;
         let identifier = polynomial_identifier::<H>(polynomial);
 
@@ -126,6 +144,29 @@ impl ShareBackup {
             share_index,
         }
     }
+
+    /// The share index as an integer [`PartyId`] if it is one.
+    ///
+    /// Backups of indices below 32 use the short encoding.
+    pub fn party_id(&self) -> Option<PartyId> {
+        PartyId::from_index(self.share_index)
+    }
+}
+
+impl fmt::Debug for ShareBackup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let identifier = self
+            .identifier
+            .iter()
+            .map(|c| c.to_char())
+            .collect::<String>();
+        f.debug_struct("ShareBackup")
+            .field("threshold", &self.threshold)
+            .field("identifier", &identifier)
+            .field("share_index", &self.share_index)
+            .field("secret_share", &format_args!(".."))
+            .finish()
+    }
 }
 
 impl fmt::Display for ShareBackup {
@@ -141,7 +182,12 @@ impl fmt::Display for ShareBackup {
             data[2 + i] = byte;
         }
 
-        let secret_share_u5 = self.secret_share.to_bytes().to_vec().to_base32();
+        let secret_share_u5 = self
+            .secret_share
+            .to_secret_bytes()
+            .expose_secret()
+            .to_vec()
+            .to_base32();
         for (i, byte) in secret_share_u5.into_iter().enumerate() {
             data[2 + 4 + i] = byte;
         }
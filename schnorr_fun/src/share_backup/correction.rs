@@ -0,0 +1,264 @@
+//! Correcting transcription errors in bech32m share backups.
+//!
+//! The bech32m checksum is a [BCH code] over the 32 character alphabet. For backups of up to 89
+//! characters (all backups with a small share index) any two codewords differ in at least five
+//! characters, so errors in up to two characters can be corrected unambiguously.
+//! [`decode_backup_with_correction`] finds the fewest character substitutions (at most two) that
+//! make the checksum valid. Characters that aren't in the bech32 alphabet count as known errors.
+//!
+//! Correcting two errors uses up the code's distance. A backup with three or more wrong characters
+//! can be within two characters of a *different* valid backup and then it is "corrected" to that
+//! backup without any error. The checksum only guarantees that errors in up to two characters are
+//! detected when they are also being corrected.
+//!
+//! For longer backups the code only guarantees that any two codewords differ in four characters, so
+//! only one wrong character is corrected. Bech32m was designed for detecting errors rather than
+//! correcting them ([BIP173] advises against correcting with it), so in every case a corrected
+//! backup is only a *likely* reading of what was written down. Show the [`Correction`]s to the user
+//! and let them check each one against the original. If two different corrections are equally
+//! likely, decoding fails rather than guessing.
+//!
+//! Inserted or deleted characters aren't corrected.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{frost, share_backup::{decode_backup_with_correction, ShareBackup}};
+//! use sha2::Sha256;
+//! let frost = frost::new_with_deterministic_nonces::<Sha256>();
+//! let (frost_key, shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+//! let (index, share) = shares.into_iter().next().unwrap();
+//! let backup = ShareBackup::new::<Sha256>(&frost_key.point_polynomial(), share.mark_zero(), index);
+//! let mut written_down = backup.to_string().into_bytes();
+//! // the user mixed up two characters when copying it
+//! written_down[20] = if written_down[20] == b'q' { b'p' } else { b'q' };
+//! written_down[40] = b'b';
+//! let corrected = decode_backup_with_correction(&String::from_utf8(written_down).unwrap()).unwrap();
+//! assert_eq!(
+//!     corrected.corrections.iter().map(|c| c.position).collect::<Vec<_>>(),
+//!     vec![20, 40]
+//! );
+//! assert_eq!(corrected.backup.secret_share, backup.secret_share);
+//! ```
+//!
+//! [BCH code]: https://en.wikipedia.org/wiki/BCH_code
+//! [BIP173]: https://bips.xyz/173
+use super::{FrostBackupDecodeError, ShareBackup};
+use alloc::{string::String, vec::Vec};
+use core::{fmt, str::FromStr};
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GEN: [u32; 5] = [
+    0x3b6a_57b2,
+    0x2650_8e6d,
+    0x1ea1_19fa,
+    0x3d42_33dd,
+    0x2a14_62b3,
+];
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+const PREFIX: &str = "frost1";
+/// Backups up to this many characters long have a minimum distance of five.
+const MAX_LEN_TWO_CORRECTIONS: usize = 89;
+
+fn polymod_step(chk: u32, value: u8) -> u32 {
+    let top = chk >> 25;
+    GEN.iter()
+        .enumerate()
+        .filter(|(i, _)| (top >> i) & 1 == 1)
+        .fold(
+            ((chk & 0x01ff_ffff) << 5) ^ value as u32,
+            |chk, (_, gen)| chk ^ gen,
+        )
+}
+
+/// A character that [`decode_backup_with_correction`] changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Correction {
+    /// The position of the character in the backup string (counting characters from zero).
+    pub position: usize,
+    /// The character that was there.
+    pub found: char,
+    /// The character it was changed to.
+    pub replacement: char,
+}
+
+/// A backup decoded by [`decode_backup_with_correction`].
+#[derive(Debug, Clone)]
+pub struct CorrectedBackup {
+    /// The decoded backup.
+    pub backup: ShareBackup,
+    /// The backup string with the corrections applied (in lowercase).
+    pub corrected: String,
+    /// The characters that were changed in order of position. Empty if the backup was already
+    /// valid.
+    pub corrections: Vec<Correction>,
+}
+
+/// Error returned by [`decode_backup_with_correction`].
+#[derive(Debug, Clone, Copy)]
+pub enum CorrectionError {
+    /// More characters would have to change to make the checksum valid than can be corrected
+    /// (two for backups of up to 89 characters, one for longer backups).
+    TooManyErrors,
+    /// There is more than one way to correct the backup with the fewest changes.
+    Ambiguous,
+    /// The backup (after any corrections) couldn't be decoded.
+    Decode(FrostBackupDecodeError),
+}
+
+impl fmt::Display for CorrectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CorrectionError::TooManyErrors => write!(f, "too many errors to correct"),
+            CorrectionError::Ambiguous => write!(
+                f,
+                "there is more than one possible correction, check the backup carefully"
+            ),
+            CorrectionError::Decode(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CorrectionError {}
+
+/// Decode a bech32m secret share backup correcting up to two wrong characters (one for backups
+/// longer than 89 characters).
+///
+/// See the [module documentation](crate::share_backup::correction) for what can be corrected.
+pub fn decode_backup_with_correction(encoded: &str) -> Result<CorrectedBackup, CorrectionError> {
+    let chars = encoded.to_lowercase().chars().collect::<Vec<_>>();
+    if chars.len() < PREFIX.len() || !chars.iter().zip(PREFIX.chars()).all(|(a, b)| *a == b) {
+        return Err(CorrectionError::Decode(
+            FrostBackupDecodeError::InvalidHumanReadablePrefix,
+        ));
+    }
+    let max_corrections = if chars.len() <= MAX_LEN_TWO_CORRECTIONS {
+        2
+    } else {
+        1
+    };
+    let data_chars = &chars[PREFIX.len()..];
+    let mut data = Vec::with_capacity(data_chars.len());
+    let mut erasures = Vec::new();
+    for (i, c) in data_chars.iter().enumerate() {
+        match CHARSET.iter().position(|x| *x as char == *c) {
+            Some(value) => data.push(value as u8),
+            None => {
+                erasures.push(i);
+                data.push(0);
+            }
+        }
+    }
+    if erasures.len() > max_corrections {
+        return Err(CorrectionError::TooManyErrors);
+    }
+
+    let hrp = &PREFIX.as_bytes()[..PREFIX.len() - 1];
+    let hrp_expanded = hrp
+        .iter()
+        .map(|c| c >> 5)
+        .chain([0])
+        .chain(hrp.iter().map(|c| c & 0x1f));
+    let syndrome = hrp_expanded
+        .chain(data.iter().copied())
+        .fold(1, polymod_step)
+        ^ BECH32M_CONST;
+
+    // The checksum is linear so the syndrome is the sum of the contributions of each error on
+    // its own. effect[i][e] is what adding e to the character at position i contributes.
+    let mut effect = alloc::vec![[0u32; 32]; data.len()];
+    let mut carried = [1u32, 2, 4, 8, 16];
+    for effect in effect.iter_mut().rev() {
+        for (e, effect) in effect.iter_mut().enumerate() {
+            *effect = (0..5)
+                .filter(|bit| (e >> bit) & 1 == 1)
+                .fold(0, |acc, bit| acc ^ carried[bit]);
+        }
+        for chk in carried.iter_mut() {
+            *chk = polymod_step(*chk, 0);
+        }
+    }
+
+    // an erased position doesn't have to change as long as it ends up as a valid character
+    let min_error = |i: usize| usize::from(!erasures.contains(&i));
+    let mut solutions = Vec::new();
+    for n_errors in erasures.len()..=max_corrections {
+        let others = (0..data.len()).filter(|i| !erasures.contains(i));
+        let candidates: Vec<Vec<usize>> = match n_errors - erasures.len() {
+            0 => alloc::vec![erasures.clone()],
+            1 => others.map(|i| [&erasures[..], &[i]].concat()).collect(),
+            _ => others
+                .flat_map(|i| (i + 1..data.len()).map(move |j| alloc::vec![i, j]))
+                .collect(),
+        };
+        for positions in candidates {
+            solutions.extend(solve(&effect, syndrome, &positions, &min_error));
+        }
+        if !solutions.is_empty() {
+            break;
+        }
+    }
+
+    let mut solution = match solutions.len() {
+        0 => return Err(CorrectionError::TooManyErrors),
+        1 => solutions.remove(0),
+        _ => return Err(CorrectionError::Ambiguous),
+    };
+    solution.sort_unstable();
+
+    let mut corrections = Vec::new();
+    for (i, e) in solution {
+        data[i] ^= e;
+        let replacement = CHARSET[data[i] as usize] as char;
+        if replacement != data_chars[i] {
+            corrections.push(Correction {
+                position: PREFIX.len() + i,
+                found: data_chars[i],
+                replacement,
+            });
+        }
+    }
+    let corrected = PREFIX
+        .chars()
+        .chain(data.iter().map(|value| CHARSET[*value as usize] as char))
+        .collect::<String>();
+    let backup = ShareBackup::from_str(&corrected).map_err(CorrectionError::Decode)?;
+
+    Ok(CorrectedBackup {
+        backup,
+        corrected,
+        corrections,
+    })
+}
+
+/// Finds every way of changing the characters at `positions` so their effects add up to the
+/// syndrome. Each solution has the value to add to the character at each position.
+fn solve(
+    effect: &[[u32; 32]],
+    syndrome: u32,
+    positions: &[usize],
+    min_error: &impl Fn(usize) -> usize,
+) -> Vec<Vec<(usize, u8)>> {
+    match *positions {
+        [] => match syndrome {
+            0 => alloc::vec![Vec::new()],
+            _ => Vec::new(),
+        },
+        [i] => (min_error(i)..32)
+            .filter(|e| effect[i][*e] == syndrome)
+            .map(|e| alloc::vec![(i, e as u8)])
+            .collect(),
+        [i, j] => {
+            let mut solutions = Vec::new();
+            for e_i in min_error(i)..32 {
+                let remaining = syndrome ^ effect[i][e_i];
+                for e_j in (min_error(j)..32).filter(|e_j| effect[j][*e_j] == remaining) {
+                    solutions.push(alloc::vec![(i, e_i as u8), (j, e_j as u8)]);
+                }
+            }
+            solutions
+        }
+        _ => unreachable!("at most two positions"),
+    }
+}
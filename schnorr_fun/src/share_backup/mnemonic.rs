@@ -0,0 +1,237 @@
+//! [BIP39] word encoding of a [`ShareBackup`].
+//!
+//! This encodes the same data as the bech32m backup as words from the BIP39 English word list so
+//! share backups fit workflows (steel plates, wallet UIs) built around seed words. Note that it is
+//! _not_ a BIP39 seed: the words can't be put into a wallet that expects one.
+//!
+//! ## Format
+//!
+//! The data is packed into bits and split into 11 bit words:
+//!
+//! ```text
+//! threshold - 1:  10 bits
+//! identifier:     20 bits
+//! secret_share:   256 bits
+//! share_index:    5 bits (small index) or 256 bits
+//! checksum:       17 or 19 bits
+//! ```
+//!
+//! A backup with a small share index (1, 2, ..., 31) is 28 words and any other is 51 words. The
+//! checksum is the first bits of `H("frost-share-mnemonic" || data)` where `data` is the packed
+//! data padded with zero bits to a whole number of bytes. Like BIP39 the first four letters
+//! identify each word so abbreviated words are accepted when decoding.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{frost, share_backup::ShareBackup};
+//! use sha2::Sha256;
+//! let frost = frost::new_with_deterministic_nonces::<Sha256>();
+//! let (frost_key, shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+//! let (index, share) = shares.into_iter().next().unwrap();
+//! let backup = ShareBackup::new::<Sha256>(&frost_key.point_polynomial(), share.mark_zero(), index);
+//! let words = backup.to_mnemonic::<Sha256>();
+//! assert_eq!(words.split(' ').count(), 28);
+//! let decoded = ShareBackup::from_mnemonic::<Sha256>(&words).unwrap();
+//! assert_eq!(decoded.secret_share, backup.secret_share);
+//! assert_eq!(decoded.share_index, backup.share_index);
+//! ```
+//!
+//! [BIP39]: https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki
+use super::ShareBackup;
+use alloc::{string::String, vec::Vec};
+use bech32::u5;
+use core::fmt;
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    hash::HashAdd,
+    marker::*,
+    Scalar,
+};
+
+/// The BIP39 English word list.
+const WORDLIST: &str = include_str!("bip39_english.txt");
+const CHECKSUM_TAG: &str = "frost-share-mnemonic";
+const SMALL_INDEX_WORDS: usize = 28;
+const SCALAR_INDEX_WORDS: usize = 51;
+const HEADER_BITS: usize = 10 + 20 + 256;
+
+fn words() -> impl Iterator<Item = &'static str> {
+    WORDLIST.lines()
+}
+
+fn word_index(word: &str) -> Option<usize> {
+    words()
+        .position(|candidate| candidate == word || (word.len() >= 4 && candidate.starts_with(word)))
+}
+
+#[derive(Default)]
+struct Bits(Vec<bool>);
+
+impl Bits {
+    fn push(&mut self, value: u32, n_bits: usize) {
+        for i in (0..n_bits).rev() {
+            self.0.push((value >> i) & 1 == 1);
+        }
+    }
+
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.push(*byte as u32, 8);
+        }
+    }
+
+    fn read(&self, start: usize, n_bits: usize) -> u32 {
+        self.0[start..start + n_bits]
+            .iter()
+            .fold(0, |value, bit| (value << 1) | *bit as u32)
+    }
+
+    fn read_bytes<const N: usize>(&self, start: usize) -> [u8; N] {
+        let mut bytes = [0u8; N];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = self.read(start + i * 8, 8) as u8;
+        }
+        bytes
+    }
+
+    fn to_padded_bytes(&self) -> Vec<u8> {
+        self.0
+            .chunks(8)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0u8, |byte, (i, bit)| byte | ((*bit as u8) << (7 - i)))
+            })
+            .collect()
+    }
+}
+
+fn checksum<H: Default + Digest<OutputSize = U32>>(data: &Bits, n_bits: usize) -> u32 {
+    let hash = H::default()
+        .add(CHECKSUM_TAG)
+        .add(&data.to_padded_bytes()[..])
+        .finalize();
+    let mut checksum = Bits::default();
+    checksum.push_bytes(&hash[..4]);
+    checksum.read(0, n_bits)
+}
+
+impl ShareBackup {
+    /// Encodes the backup as space separated BIP39 words.
+    ///
+    /// See the [module documentation](crate::share_backup::mnemonic) for the format.
+    pub fn to_mnemonic<H: Default + Digest<OutputSize = U32>>(&self) -> String {
+        let mut data = Bits::default();
+        data.push((self.threshold - 1) as u32, 10);
+        for c in self.identifier {
+            data.push(c.to_u8() as u32, 5);
+        }
+        data.push_bytes(self.secret_share.to_secret_bytes().expose_secret());
+        let n_words = match self.party_id() {
+            Some(id) if id.get() < 32 => {
+                data.push(id.get(), 5);
+                SMALL_INDEX_WORDS
+            }
+            _ => {
+                data.push_bytes(&self.share_index.to_bytes());
+                SCALAR_INDEX_WORDS
+            }
+        };
+        let n_checksum_bits = n_words * 11 - data.0.len();
+        let checksum = checksum::<H>(&data, n_checksum_bits);
+        data.push(checksum, n_checksum_bits);
+
+        let words = words().collect::<Vec<_>>();
+        (0..n_words)
+            .map(|i| words[data.read(i * 11, 11) as usize])
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Decodes a backup from the words produced by [`to_mnemonic`](Self::to_mnemonic).
+    ///
+    /// Words are separated by whitespace and may be abbreviated to their first four letters.
+    pub fn from_mnemonic<H: Default + Digest<OutputSize = U32>>(
+        mnemonic: &str,
+    ) -> Result<Self, MnemonicDecodeError> {
+        let mut data = Bits::default();
+        let mut n_words = 0;
+        for (i, word) in mnemonic.split_whitespace().enumerate() {
+            let index =
+                word_index(&word.to_lowercase()).ok_or(MnemonicDecodeError::UnknownWord(i))?;
+            data.push(index as u32, 11);
+            n_words += 1;
+        }
+        let index_bits = match n_words {
+            SMALL_INDEX_WORDS => 5,
+            SCALAR_INDEX_WORDS => 256,
+            n_words => return Err(MnemonicDecodeError::WrongNumberOfWords(n_words)),
+        };
+        let data_bits = HEADER_BITS + index_bits;
+        let n_checksum_bits = n_words * 11 - data_bits;
+        let expected_checksum = data.read(data_bits, n_checksum_bits);
+        data.0.truncate(data_bits);
+        if checksum::<H>(&data, n_checksum_bits) != expected_checksum {
+            return Err(MnemonicDecodeError::InvalidChecksum);
+        }
+
+        let threshold = data.read(0, 10) as u16 + 1;
+        let mut identifier = [u5::default(); 4];
+        for (i, c) in identifier.iter_mut().enumerate() {
+            *c = u5::try_from_u8(data.read(10 + i * 5, 5) as u8).expect("only 5 bits");
+        }
+        let secret_share = Scalar::from_bytes(data.read_bytes::<32>(30))
+            .ok_or(MnemonicDecodeError::InvalidSecretShareScalar)?;
+        let share_index = if index_bits == 5 {
+            Scalar::<Public, Zero>::from(data.read(HEADER_BITS, 5))
+        } else {
+            Scalar::from_bytes(data.read_bytes::<32>(HEADER_BITS))
+                .ok_or(MnemonicDecodeError::InvalidShareIndexScalar)?
+        }
+        .non_zero()
+        .ok_or(MnemonicDecodeError::ShareIndexIsZero)?;
+
+        Ok(ShareBackup {
+            threshold,
+            identifier,
+            secret_share,
+            share_index,
+        })
+    }
+}
+
+/// Error returned by [`ShareBackup::from_mnemonic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MnemonicDecodeError {
+    /// The word at this position isn't in the word list.
+    UnknownWord(usize),
+    /// There weren't 28 or 51 words.
+    WrongNumberOfWords(usize),
+    /// The checksum didn't match. A word is probably wrong or in the wrong place.
+    InvalidChecksum,
+    /// Decoded secret share is not a valid secp256k1 scalar
+    InvalidSecretShareScalar,
+    /// Decoded share index is not a valid secp256k1 scalar
+    InvalidShareIndexScalar,
+    /// Decoded share index is zero
+    ShareIndexIsZero,
+}
+
+impl fmt::Display for MnemonicDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use MnemonicDecodeError::*;
+        match self {
+            UnknownWord(i) => write!(f, "word {} is not in the BIP39 word list", i + 1),
+            WrongNumberOfWords(n) => write!(f, "expected 28 or 51 words but got {n}"),
+            InvalidChecksum => write!(f, "the checksum didn't match, check the words"),
+            InvalidSecretShareScalar => write!(f, "secret share was not a valid secp256k1 scalar"),
+            InvalidShareIndexScalar => write!(f, "share index was not a valid secp256k1 scalar"),
+            ShareIndexIsZero => write!(f, "share index was zero"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MnemonicDecodeError {}
@@ -0,0 +1,230 @@
+//! Social recovery kits.
+//!
+//! A [`RecoveryKit`] packages the [`ShareBackup`]s handed out to several people (friends, family,
+//! a lawyer...) into a single structure along with some free-form instructions for whoever ends up
+//! putting the secret back together. Each backup is sealed in an [`Envelope`] that only its
+//! recipient can open so the whole kit can be stored or sent anywhere.
+//!
+//! The envelopes are labeled by recipient rather than by share index so the kit doesn't reveal
+//! which share anyone holds.
+//!
+//! ## Encryption
+//!
+//! Each bech32m backup is encrypted to the recipient's public key with [`secp256kfun::ecies`]. The
+//! kit's instructions and the envelope's label and recipient are the associated data so they are
+//! authenticated along with the backup: envelopes can't have their labels swapped around and the
+//! instructions can't be changed without every envelope failing to open. Set the instructions
+//! before sealing any envelopes.
+//!
+//! ## Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     frost,
+//!     fun::{g, Scalar, G},
+//!     share_backup::{recovery_kit::RecoveryKit, ShareBackup},
+//! };
+//! use sha2::Sha256;
+//! let frost = frost::new_with_deterministic_nonces::<Sha256>();
+//! let (frost_key, shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+//! let mut kit = RecoveryKit::new("You need two of these to recover my wallet.");
+//! let alice = Scalar::random(&mut rand::thread_rng());
+//! # let bob = Scalar::random(&mut rand::thread_rng());
+//! for ((index, share), (label, secret_key)) in shares.iter().zip([("alice", alice), ("bob", bob)]) {
+//!     let backup =
+//!         ShareBackup::new::<Sha256>(&frost_key.point_polynomial(), share.mark_zero(), *index);
+//!     let recipient = g!(secret_key * G).normalize();
+//!     kit.seal::<Sha256>(label, recipient, &backup, &mut rand::thread_rng());
+//! }
+//! // later alice opens her envelope
+//! let (label, backup) = kit.open::<Sha256>(&alice).unwrap();
+//! assert_eq!(label, "alice");
+//! ```
+use super::{FrostBackupDecodeError, ShareBackup};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, str::FromStr};
+use secp256kfun::{
+    digest::{crypto_common::BlockSizeUser, generic_array::typenum::U32, Digest},
+    ecies, g,
+    rand_core::RngCore,
+    Point, Scalar, G,
+};
+
+const AAD_TAG: &[u8] = b"secp256kfun/recovery_kit";
+
+/// A set of encrypted share backups plus instructions for recovering the secret.
+///
+/// See the [module documentation](crate::share_backup::recovery_kit).
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct RecoveryKit {
+    /// Instructions for whoever is recovering the secret.
+    ///
+    /// These are authenticated by every envelope so changing them after sealing stops the
+    /// envelopes from opening.
+    pub instructions: String,
+    /// The sealed backups.
+    pub envelopes: Vec<Envelope>,
+}
+
+impl RecoveryKit {
+    /// Creates an empty recovery kit with some instructions.
+    pub fn new(instructions: impl Into<String>) -> Self {
+        Self {
+            instructions: instructions.into(),
+            envelopes: vec![],
+        }
+    }
+
+    /// Seals `backup` so that only the owner of `recipient` can open it and adds it to the kit.
+    pub fn seal<H: BlockSizeUser + Digest<OutputSize = U32> + Default>(
+        &mut self,
+        label: impl Into<String>,
+        recipient: Point,
+        backup: &ShareBackup,
+        rng: &mut impl RngCore,
+    ) {
+        let label = label.into();
+        let ciphertext = ecies::encrypt::<H>(
+            &recipient,
+            &associated_data(&self.instructions, &label, recipient),
+            backup.to_string().as_bytes(),
+            rng,
+        );
+
+        self.envelopes.push(Envelope {
+            label,
+            recipient,
+            ciphertext,
+        });
+    }
+
+    /// The label and public key of each recipient in the kit.
+    pub fn recipients(&self) -> impl Iterator<Item = (&str, Point)> + '_ {
+        self.envelopes
+            .iter()
+            .map(|envelope| (envelope.label.as_str(), envelope.recipient))
+    }
+
+    /// Opens the envelope addressed to the public key of `secret_key`.
+    ///
+    /// Returns the label of the envelope along with the backup it contained.
+    pub fn open<H: BlockSizeUser + Digest<OutputSize = U32> + Default>(
+        &self,
+        secret_key: &Scalar,
+    ) -> Result<(&str, ShareBackup), RecoveryKitError> {
+        let public_key = g!(secret_key * G).normalize();
+        let envelope = self
+            .envelopes
+            .iter()
+            .find(|envelope| envelope.recipient == public_key)
+            .ok_or(RecoveryKitError::NoEnvelope)?;
+        Ok((
+            envelope.label.as_str(),
+            envelope.open::<H>(&self.instructions, secret_key)?,
+        ))
+    }
+}
+
+fn associated_data(instructions: &str, label: &str, recipient: Point) -> Vec<u8> {
+    let mut data = AAD_TAG.to_vec();
+    for field in [instructions.as_bytes(), label.as_bytes()] {
+        data.extend_from_slice(&(field.len() as u64).to_be_bytes());
+        data.extend_from_slice(field);
+    }
+    data.extend_from_slice(&recipient.to_bytes());
+    data
+}
+
+/// A share backup encrypted to a single recipient.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(
+    feature = "bincode",
+    derive(crate::fun::bincode::Encode, crate::fun::bincode::Decode),
+    bincode(crate = "crate::fun::bincode")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct Envelope {
+    /// A human readable label for the recipient.
+    pub label: String,
+    /// The recipient's public key.
+    pub recipient: Point,
+    /// The bech32m backup encrypted with [`ecies::encrypt`].
+    pub ciphertext: Vec<u8>,
+}
+
+impl fmt::Debug for Envelope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Envelope")
+            .field("label", &self.label)
+            .field("recipient", &self.recipient)
+            .field(
+                "ciphertext",
+                &format_args!("{} bytes", self.ciphertext.len()),
+            )
+            .finish()
+    }
+}
+
+impl Envelope {
+    /// Decrypts the backup with the recipient's secret key.
+    ///
+    /// `instructions` are the [`RecoveryKit::instructions`] of the kit the envelope is from.
+    pub fn open<H: BlockSizeUser + Digest<OutputSize = U32> + Default>(
+        &self,
+        instructions: &str,
+        secret_key: &Scalar,
+    ) -> Result<ShareBackup, RecoveryKitError> {
+        let plaintext = ecies::decrypt::<H>(
+            secret_key,
+            &associated_data(instructions, &self.label, self.recipient),
+            &self.ciphertext,
+        )
+        .map_err(|_| RecoveryKitError::InvalidMac)?;
+        let encoded = String::from_utf8(plaintext).map_err(|_| RecoveryKitError::InvalidMac)?;
+        ShareBackup::from_str(&encoded).map_err(RecoveryKitError::Backup)
+    }
+}
+
+/// Error opening an [`Envelope`].
+#[derive(Debug, Clone, Copy)]
+pub enum RecoveryKitError {
+    /// There is no envelope addressed to the key.
+    NoEnvelope,
+    /// The envelope was not addressed to the key or has been tampered with.
+    InvalidMac,
+    /// The envelope decrypted to something that isn't a valid backup.
+    Backup(FrostBackupDecodeError),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RecoveryKitError {}
+
+impl fmt::Display for RecoveryKitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecoveryKitError::NoEnvelope => write!(f, "No envelope is addressed to this key."),
+            RecoveryKitError::InvalidMac => write!(
+                f,
+                "The envelope was not addressed to this key or has been tampered with."
+            ),
+            RecoveryKitError::Backup(e) => write!(f, "Invalid backup in envelope: {e}"),
+        }
+    }
+}
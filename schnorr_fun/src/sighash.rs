@@ -0,0 +1,244 @@
+//! Typed inputs to the taproot signature message that depend on how an input is being spent.
+//!
+//! The [BIP341] signature message commits to whether the input has an annex and, for script path
+//! spends, to the [BIP342] extension: the leaf hash, the key version and the position of the last
+//! executed `OP_CODESEPARATOR`. Getting any of these wrong produces a valid looking but useless
+//! signature so the usual mistakes are made unrepresentable here:
+//!
+//! - An [`Annex`] can only be created from bytes starting with the `0x50` annex tag and is hashed
+//!   with its compact size length prefix.
+//! - A [`CodeSeparatorPosition`] distinguishes "no `OP_CODESEPARATOR` executed" from position `0`
+//!   instead of relying on callers to remember `0xffffffff`.
+//! - [`SpendPath::KeyPath`] has no leaf or code separator fields so they can't be passed for a key
+//!   path spend.
+//!
+//! [`SpendPath`] doesn't compute the whole signature message (it knows nothing about the
+//! transaction) but provides the pieces of it that it determines.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::sighash::{Annex, CodeSeparatorPosition, SpendPath};
+//! use sha2::Sha256;
+//! let leaf_hash = [42u8; 32];
+//! let spend_path = SpendPath::ScriptPath {
+//!     leaf_hash,
+//!     codesep_pos: CodeSeparatorPosition::after_opcode(3).unwrap(),
+//!     annex: Some(Annex::new(&[0x50, 0x01]).unwrap()),
+//! };
+//! // ext_flag = 1, annex_present = 1
+//! assert_eq!(spend_path.spend_type(), 0x03);
+//! assert!(spend_path.sha_annex::<Sha256>().is_some());
+//! let ext = spend_path.extension().unwrap();
+//! assert_eq!(&ext[..32], &leaf_hash);
+//! assert_eq!(&ext[33..], &3u32.to_le_bytes());
+//! ```
+//!
+//! [BIP341]: https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+//! [BIP342]: https://github.com/bitcoin/bips/blob/master/bip-0342.mediawiki
+use secp256kfun::digest::{generic_array::typenum::U32, Digest};
+
+/// The annex of a taproot input: the last witness element when it starts with [`Annex::TAG`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Annex<'a>(&'a [u8]);
+
+impl<'a> Annex<'a> {
+    /// The first byte of every annex.
+    pub const TAG: u8 = 0x50;
+
+    /// Interprets `bytes` (including the leading tag) as an annex.
+    ///
+    /// Returns `None` if `bytes` doesn't start with [`Annex::TAG`].
+    pub fn new(bytes: &'a [u8]) -> Option<Self> {
+        match bytes.first() {
+            Some(&Self::TAG) => Some(Self(bytes)),
+            _ => None,
+        }
+    }
+
+    /// The bytes of the annex including the leading tag.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// The `sha_annex` field of the signature message: the hash of the annex prefixed with its
+    /// compact size length.
+    ///
+    /// `H` should be SHA256 for the result to match [BIP341].
+    ///
+    /// [BIP341]: https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+    pub fn sha_annex<H: Digest<OutputSize = U32> + Default>(&self) -> [u8; 32] {
+        let len = self.0.len() as u64;
+        let mut hash = H::default();
+        match len {
+            0..=0xfc => hash.update([len as u8]),
+            0xfd..=0xffff => {
+                hash.update([0xfd]);
+                hash.update((len as u16).to_le_bytes());
+            }
+            0x10000..=0xffff_ffff => {
+                hash.update([0xfe]);
+                hash.update((len as u32).to_le_bytes());
+            }
+            _ => {
+                hash.update([0xff]);
+                hash.update(len.to_le_bytes());
+            }
+        }
+        hash.update(self.0);
+        hash.finalize().into()
+    }
+}
+
+/// The opcode position of the last executed `OP_CODESEPARATOR` in a tapscript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CodeSeparatorPosition(u32);
+
+impl CodeSeparatorPosition {
+    /// No `OP_CODESEPARATOR` has been executed.
+    pub const NONE: Self = Self(u32::MAX);
+
+    /// The last executed `OP_CODESEPARATOR` was the opcode at position `pos` (counting from zero).
+    ///
+    /// Returns `None` for `u32::MAX` which is reserved to mean [`NONE`](Self::NONE).
+    pub fn after_opcode(pos: u32) -> Option<Self> {
+        if pos == u32::MAX {
+            None
+        } else {
+            Some(Self(pos))
+        }
+    }
+
+    /// The position of the last executed `OP_CODESEPARATOR` if there was one.
+    pub fn last_executed(&self) -> Option<u32> {
+        if *self == Self::NONE {
+            None
+        } else {
+            Some(self.0)
+        }
+    }
+
+    /// The `codesep_pos` field as it appears in the signature message.
+    pub fn to_le_bytes(&self) -> [u8; 4] {
+        self.0.to_le_bytes()
+    }
+}
+
+impl Default for CodeSeparatorPosition {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// How a taproot input is being spent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpendPath<'a> {
+    /// Spending with a signature from the output key.
+    KeyPath {
+        /// The annex of the input if it has one.
+        annex: Option<Annex<'a>>,
+    },
+    /// Spending with a signature checked by a tapscript.
+    ScriptPath {
+        /// The tapleaf hash of the script being executed.
+        leaf_hash: [u8; 32],
+        /// The position of the last executed `OP_CODESEPARATOR` before the signature check.
+        codesep_pos: CodeSeparatorPosition,
+        /// The annex of the input if it has one.
+        annex: Option<Annex<'a>>,
+    },
+}
+
+impl<'a> SpendPath<'a> {
+    /// The key version [BIP342] defines for tapscript signature checks.
+    ///
+    /// [BIP342]: https://github.com/bitcoin/bips/blob/master/bip-0342.mediawiki
+    pub const KEY_VERSION_0: u8 = 0x00;
+
+    /// The annex of the input if it has one.
+    pub fn annex(&self) -> Option<Annex<'a>> {
+        match self {
+            SpendPath::KeyPath { annex } | SpendPath::ScriptPath { annex, .. } => *annex,
+        }
+    }
+
+    /// The `spend_type` byte of the signature message: `ext_flag * 2 + annex_present`.
+    pub fn spend_type(&self) -> u8 {
+        let ext_flag = match self {
+            SpendPath::KeyPath { .. } => 0,
+            SpendPath::ScriptPath { .. } => 1,
+        };
+        ext_flag * 2 + self.annex().is_some() as u8
+    }
+
+    /// The `sha_annex` field of the signature message which is only present if there's an annex.
+    ///
+    /// See [`Annex::sha_annex`].
+    pub fn sha_annex<H: Digest<OutputSize = U32> + Default>(&self) -> Option<[u8; 32]> {
+        self.annex().map(|annex| annex.sha_annex::<H>())
+    }
+
+    /// The [BIP342] extension appended to the signature message for script path spends:
+    /// `leaf_hash || key_version || codesep_pos`.
+    ///
+    /// Returns `None` for key path spends which have no extension.
+    ///
+    /// [BIP342]: https://github.com/bitcoin/bips/blob/master/bip-0342.mediawiki
+    pub fn extension(&self) -> Option<[u8; 37]> {
+        match self {
+            SpendPath::KeyPath { .. } => None,
+            SpendPath::ScriptPath {
+                leaf_hash,
+                codesep_pos,
+                ..
+            } => {
+                let mut ext = [0u8; 37];
+                ext[..32].copy_from_slice(leaf_hash);
+                ext[32] = Self::KEY_VERSION_0;
+                ext[33..].copy_from_slice(&codesep_pos.to_le_bytes());
+                Some(ext)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+
+    #[test]
+    fn spend_path_fields() {
+        assert_eq!(Annex::new(&[]), None);
+        assert_eq!(Annex::new(&[0x51]), None);
+        let annex_bytes = [Annex::TAG; 300];
+        let annex = Annex::new(&annex_bytes).unwrap();
+        let mut expected = Sha256::default();
+        expected.update([0xfd, 0x2c, 0x01]);
+        expected.update(annex_bytes);
+        assert_eq!(
+            annex.sha_annex::<Sha256>(),
+            <[u8; 32]>::from(expected.finalize())
+        );
+
+        let key_path = SpendPath::KeyPath { annex: None };
+        assert_eq!(key_path.spend_type(), 0);
+        assert_eq!(key_path.sha_annex::<Sha256>(), None);
+        assert_eq!(key_path.extension(), None);
+        assert_eq!(SpendPath::KeyPath { annex: Some(annex) }.spend_type(), 1);
+
+        assert_eq!(CodeSeparatorPosition::after_opcode(u32::MAX), None);
+        assert_eq!(CodeSeparatorPosition::NONE.last_executed(), None);
+        let at_zero = CodeSeparatorPosition::after_opcode(0).unwrap();
+        assert_eq!(at_zero.last_executed(), Some(0));
+        let script_path = SpendPath::ScriptPath {
+            leaf_hash: [7u8; 32],
+            codesep_pos: CodeSeparatorPosition::default(),
+            annex: None,
+        };
+        assert_eq!(script_path.spend_type(), 2);
+        let ext = script_path.extension().unwrap();
+        assert_eq!(ext[32], SpendPath::KEY_VERSION_0);
+        assert_eq!(&ext[33..], &[0xff; 4]);
+    }
+}
@@ -0,0 +1,192 @@
+//! Sign-to-contract: committing to data inside a signature's nonce.
+//!
+//! [`Schnorr::sign_with_commitment`] signs with the nonce `R = R₀ + H(R₀ || data) * G` where `R₀`
+//! is the nonce the signer would otherwise have used. The signature is an ordinary BIP340
+//! signature that doesn't reveal it commits to anything but given the [`CommitmentProof`] (`R₀`)
+//! anyone can check that it commits to `data` and only `data`. This is how you timestamp data in a
+//! signature that you were going to make anyway without it taking up any more space.
+//!
+//! For a hardware signer proving it didn't leak its key through its nonces the host has to choose
+//! the data and the signer has to commit to `R₀` before seeing it. See [`anti_exfil`] for that
+//! protocol.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     fun::{marker::*, Scalar},
+//!     Message,
+//! };
+//! let schnorr = schnorr_fun::test_instance!();
+//! let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let message = Message::<Public>::plain("my-wallet", b"spend all the coins");
+//! let data = b"the hash of a document to timestamp";
+//! let (signature, proof) = schnorr.sign_with_commitment(&keypair, message, data);
+//! assert!(schnorr.verify(&keypair.public_key(), message, &signature));
+//! assert!(schnorr.verify_commitment(&keypair.public_key(), message, &signature, &proof, data));
+//! assert!(!proof.verify::<sha2::Sha256>(&signature, b"something else"));
+//! ```
+//!
+//! [`anti_exfil`]: crate::anti_exfil
+use crate::{
+    fun::{
+        derive_nonce,
+        digest::{generic_array::typenum::U32, Digest},
+        g,
+        hash::{HashAdd, Tag},
+        marker::*,
+        nonce::NonceGen,
+        s, KeyPair, Point, Scalar, G,
+    },
+    Message, Schnorr, Signature,
+};
+
+/// The proof that a signature's nonce commits to some data: the nonce `R₀` before it was tweaked.
+///
+/// Created by [`Schnorr::sign_with_commitment`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct CommitmentProof {
+    R: Point,
+}
+
+impl CommitmentProof {
+    /// The nonce `R₀` before it was tweaked with the data.
+    pub fn R(&self) -> Point {
+        self.R
+    }
+
+    /// The proof as the 33-byte compressed encoding of `R₀`.
+    pub fn to_bytes(&self) -> [u8; 33] {
+        self.R.to_bytes()
+    }
+
+    /// Decodes a proof encoded with [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: [u8; 33]) -> Option<Self> {
+        Some(Self {
+            R: Point::from_bytes(bytes)?,
+        })
+    }
+
+    /// Checks that the nonce of `signature` is `R₀ + H(R₀ || data) * G`.
+    ///
+    /// This doesn't check the signature is valid. Use [`Schnorr::verify_commitment`] for that.
+    /// `H` must be the challenge hash of the [`Schnorr`] instance that made the signature.
+    #[must_use]
+    pub fn verify<H: Digest<OutputSize = U32> + Tag + Default>(
+        &self,
+        signature: &Signature,
+        data: &[u8],
+    ) -> bool {
+        let t = tweak::<H>(&self.R, data);
+        g!(self.R + t * G)
+            .normalize()
+            .non_zero()
+            .map_or(false, |R| {
+                R.to_xonly_bytes() == signature.R.to_xonly_bytes()
+            })
+    }
+}
+
+fn tweak<H: Digest<OutputSize = U32> + Tag + Default>(R: &Point, data: &[u8]) -> Scalar<Public> {
+    Scalar::from_hash(
+        H::default()
+            .tag(b"schnorr_fun/sign_to_contract/point")
+            .add(*R)
+            .add(data),
+    )
+    .public()
+}
+
+impl<CH, NG> Schnorr<CH, NG>
+where
+    CH: Digest<OutputSize = U32> + Tag + Default + Clone,
+    NG: NonceGen + Tag + Clone,
+{
+    /// Signs `message` with a nonce that commits to `data`.
+    ///
+    /// Returns the signature and the proof that it commits to `data`. See the [module
+    /// documentation](crate::sign_to_contract).
+    pub fn sign_with_commitment(
+        &self,
+        keypair: &KeyPair<EvenY>,
+        message: Message<'_, impl Secrecy>,
+        data: &[u8],
+    ) -> (Signature, CommitmentProof) {
+        let (x, X) = keypair.as_tuple();
+        let data_hash: [u8; 32] = CH::default()
+            .tag(b"schnorr_fun/sign_to_contract/data")
+            .add(data)
+            .finalize()
+            .into();
+        // a differently tagged nonce generator so no message passed to `sign` can ever give the
+        // same `r₀`. Signing with `r₀` and `r₀ + t` would reveal the secret key.
+        let r = derive_nonce!(
+            nonce_gen => self.nonce_gen().clone().tag(b"sign_to_contract"),
+            secret => x,
+            public => [X, data_hash, message]
+        );
+        let R = g!(r * G).normalize();
+        let t = tweak::<CH>(&R, data);
+        let r = s!(r + t).non_zero().expect("computationally unreachable");
+        (
+            self.sign_with_nonce(keypair, r, message),
+            CommitmentProof { R },
+        )
+    }
+
+    /// Checks that `signature` is valid and that `proof` shows its nonce commits to `data`.
+    #[must_use]
+    pub fn verify_commitment(
+        &self,
+        public_key: &Point<EvenY>,
+        message: Message<'_, Public>,
+        signature: &Signature,
+        proof: &CommitmentProof,
+        data: &[u8],
+    ) -> bool {
+        proof.verify::<CH>(signature, data) && self.verify(public_key, message, signature)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+
+    #[test]
+    fn sign_to_contract_sign_and_verify() {
+        let schnorr = crate::test_instance!();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let public_key = keypair.public_key();
+        let message = Message::<Public>::plain("test", b"sign-to-contract");
+
+        let (signature, proof) = schnorr.sign_with_commitment(&keypair, message, b"data");
+        assert!(schnorr.verify_commitment(&public_key, message, &signature, &proof, b"data"));
+        assert_eq!(CommitmentProof::from_bytes(proof.to_bytes()), Some(proof));
+        assert_ne!(proof.R().to_xonly_bytes(), signature.R.to_xonly_bytes());
+
+        // it only commits to the data it was made with
+        assert!(!proof.verify::<Sha256>(&signature, b"dat"));
+        assert!(!proof.verify::<Sha256>(&schnorr.sign(&keypair, message), b"data"));
+        // and the signature still has to be valid
+        let other_message = Message::<Public>::plain("test", b"something else");
+        assert!(!schnorr.verify_commitment(
+            &public_key,
+            other_message,
+            &signature,
+            &proof,
+            b"data"
+        ));
+
+        // different data gives a different nonce
+        let (other_signature, other_proof) =
+            schnorr.sign_with_commitment(&keypair, message, b"other data");
+        assert_ne!(other_signature.R, signature.R);
+        assert_ne!(other_proof, proof);
+    }
+}
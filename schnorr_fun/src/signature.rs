@@ -1,4 +1,6 @@
-use crate::fun::{marker::*, rand_core::RngCore, Point, Scalar};
+use crate::fun::{
+    marker::*, rand_core::RngCore, signature::SignatureComponents, FromSliceError, Point, Scalar,
+};
 
 /// A Schnorr signature.
 #[derive(Clone, Eq)]
@@ -18,7 +20,33 @@ impl<S1, S2> PartialEq<Signature<S2>> for Signature<S1> {
     }
 }
 
+impl core::hash::Hash for Signature<Public> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.to_bytes().hash(state)
+    }
+}
+
+impl PartialOrd for Signature<Public> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Signatures are ordered by their 64-byte serialization.
+impl Ord for Signature<Public> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.to_bytes().cmp(&other.to_bytes())
+    }
+}
+
+// The encoding is the x-only nonce followed by the scalar.
+const _: () =
+    assert!(Signature::<Public>::SIZE == Point::<EvenY>::XONLY_SIZE + Scalar::<Public, Zero>::SIZE);
+
 impl<S> Signature<S> {
+    /// The length of the encoding of a signature.
+    pub const SIZE: usize = 64;
+
     /// Serializes the signature as 64 bytes -- First the 32-byte nonce
     /// x-coordinate and then the 32-byte challenge response scalar.
     /// # Examples
@@ -31,7 +59,7 @@ impl<S> Signature<S> {
     pub fn to_bytes(&self) -> [u8; 64] {
         let mut bytes = [0u8; 64];
         bytes[0..32].copy_from_slice(&self.R.to_xonly_bytes());
-        bytes[32..64].copy_from_slice(&self.s.to_bytes());
+        bytes[32..64].copy_from_slice(self.s.to_secret_bytes().expose_secret());
         bytes
     }
 
@@ -109,6 +137,37 @@ impl Signature<Public> {
     }
 }
 
+impl SignatureComponents for Signature<Public> {
+    const SCHEME: &'static str = "bip340";
+
+    fn r(&self) -> [u8; 32] {
+        self.R.to_xonly_bytes()
+    }
+
+    fn R(&self) -> Option<Point<Normal, Public, NonZero>> {
+        Some(self.R.normalize())
+    }
+
+    fn s(&self) -> Scalar<Public, Zero> {
+        self.s
+    }
+
+    fn from_components(r: [u8; 32], s: Scalar<Public, Zero>) -> Option<Self> {
+        Some(Signature {
+            R: Point::from_xonly_bytes(r)?,
+            s,
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for Signature<Public> {
+    type Error = FromSliceError;
+
+    fn try_from(slice: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(FromSliceError::to_array(slice)?).ok_or(FromSliceError::Invalid)
+    }
+}
+
 secp256kfun::impl_fromstr_deserialize! {
     name => "secp256k1 Schnorr signature",
     fn from_bytes<S: Secrecy>(bytes: [u8;64]) -> Option<Signature<S>> {
@@ -138,4 +197,34 @@ mod test {
         let deserialized = bincode::deserialize::<Signature>(&serialized).unwrap();
         assert_eq!(signature, deserialized);
     }
+
+    #[test]
+    fn signature_components() {
+        use super::*;
+        let signature = Signature::random(&mut rand::thread_rng());
+        let (r, s) = signature.to_components();
+        assert_eq!(r, signature.R.to_xonly_bytes());
+        assert_eq!(
+            SignatureComponents::R(&signature),
+            Some(signature.R.normalize())
+        );
+        assert_eq!(Signature::from_components(r, s), Some(signature));
+        // x = 5 is not on the curve
+        let mut not_on_curve = [0u8; 32];
+        not_on_curve[31] = 5;
+        assert_eq!(Signature::from_components(not_on_curve, s), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn signatures_as_map_keys() {
+        use super::*;
+        use std::collections::{BTreeSet, HashSet};
+        let sig1 = Signature::random(&mut rand::thread_rng());
+        let sig2 = Signature::random(&mut rand::thread_rng());
+        let hash_set = HashSet::from([sig1.clone(), sig2.clone(), sig1.clone()]);
+        assert_eq!(hash_set.len(), 2);
+        let btree_set = BTreeSet::from([sig1.clone(), sig2.clone(), sig1]);
+        assert_eq!(btree_set.len(), 2);
+    }
 }
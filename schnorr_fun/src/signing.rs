@@ -0,0 +1,263 @@
+//! The signing arithmetic of FROST and MuSig.
+//!
+//! The `alloc` APIs in [`crate::frost`] and [`crate::musig`] and the array based ones in
+//! [`crate::heapless`] only differ in what they keep the keys and nonces in. Everything they compute
+//! from them goes through the functions here so the two always produce the same sessions, shares and
+//! signatures.
+use crate::{
+    binonce::{Nonce, NonceKeyPair},
+    fun::{
+        digest::{crypto_common::BlockSizeUser, generic_array::typenum::U32, Digest},
+        g,
+        hash::HashAdd,
+        marker::*,
+        s, Point, Scalar, G,
+    },
+    Message,
+};
+
+/// The index of a FROST party's share.
+pub(crate) type PartyIndex = Scalar<Public, NonZero>;
+
+/// The context string of the `FROST(secp256k1, SHA-256)` ciphersuite of RFC 9591.
+pub(crate) const RFC9591_CONTEXT_STRING: &[u8] = b"FROST-secp256k1-SHA256-v1";
+
+/// 2<sup>256</sup> mod the curve order.
+const TWO_POW_256: [u8; 32] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0x45, 0x51, 0x23, 0x19, 0x50, 0xb7, 0x5f, 0xc4,
+    0x40, 0x2d, 0xa1, 0x73, 0x2f, 0xc9, 0xbe, 0xbf,
+];
+
+/// The single binding coefficient of [`crate::frost::Frost::start_sign_session`].
+pub(crate) fn frost_binding_coeff<H: Digest<OutputSize = U32> + Clone>(
+    binding_hash: &H,
+    agg_nonce: &Nonce<Zero>,
+    public_key: Point<EvenY>,
+    message: Message,
+) -> Scalar<Public, Zero> {
+    Scalar::from_hash(
+        binding_hash
+            .clone()
+            .add(agg_nonce.0[0])
+            .add(agg_nonce.0[1])
+            .add(public_key)
+            .add(message),
+    )
+    .public()
+    .mark_zero()
+}
+
+/// The part of the input to every signer's RFC 9591 binding factor that they have in common.
+///
+/// `nonces` must be sorted by index as the RFC's `encode_group_commitment_list` requires.
+pub(crate) fn rfc9591_binding_prefix<H: Digest<OutputSize = U32> + BlockSizeUser>(
+    group_public_key: Point<impl Normalized>,
+    nonces: impl IntoIterator<Item = (PartyIndex, Nonce)>,
+    message: Message,
+) -> [u8; 33 + 32 + 32] {
+    let mut commitment_list = H::new().add(RFC9591_CONTEXT_STRING).add(b"com");
+    for (index, nonce) in nonces {
+        commitment_list = commitment_list
+            .add(index.to_bytes())
+            .add(nonce.0[0].to_bytes())
+            .add(nonce.0[1].to_bytes());
+    }
+    let mut prefix = [0u8; 33 + 32 + 32];
+    prefix[..33].copy_from_slice(&group_public_key.to_bytes());
+    prefix[33..65].copy_from_slice(
+        H::new()
+            .add(RFC9591_CONTEXT_STRING)
+            .add(b"msg")
+            .add(message)
+            .finalize()
+            .as_ref(),
+    );
+    prefix[65..].copy_from_slice(commitment_list.finalize().as_ref());
+    prefix
+}
+
+/// The RFC 9591 binding factor of the signer at `index` from [`rfc9591_binding_prefix`].
+pub(crate) fn rfc9591_binding_factor<H: Digest<OutputSize = U32> + BlockSizeUser>(
+    prefix: &[u8; 33 + 32 + 32],
+    index: PartyIndex,
+) -> Scalar<Public, Zero> {
+    hash_to_field::<H>(b"rho", &[prefix, &index.to_bytes()])
+}
+
+/// The RFC 9591 group commitment `Σ (D_i + rho_i * E_i)` of the signers' nonces and binding
+/// factors.
+pub(crate) fn rfc9591_group_commitment(
+    nonces_and_binding_factors: impl IntoIterator<Item = (Nonce, Scalar<Public, Zero>)>,
+) -> Point<NonNormal, Public, Zero> {
+    nonces_and_binding_factors
+        .into_iter()
+        .map(|(nonce, rho)| {
+            let [D, E] = nonce.0;
+            g!(D + rho * E)
+        })
+        .sum()
+}
+
+/// The RFC's `H1` and `H3`: `hash_to_field` from RFC 9380 with `expand_message_xmd` and the
+/// domain separation tag `CONTEXT_STRING || tag`.
+pub(crate) fn hash_to_field<H: Digest<OutputSize = U32> + BlockSizeUser>(
+    tag: &[u8],
+    messages: &[&[u8]],
+) -> Scalar<Public, Zero> {
+    // 48 bytes is enough to reduce to a scalar with negligible bias
+    const LEN: u8 = 48;
+    let dst = |hash: H| {
+        hash.add(RFC9591_CONTEXT_STRING)
+            .add(tag)
+            .add([(RFC9591_CONTEXT_STRING.len() + tag.len()) as u8])
+    };
+    let mut b_0 = H::new();
+    let mut zero_pad = H::block_size();
+    while zero_pad > 0 {
+        let len = zero_pad.min(32);
+        b_0 = b_0.add(&[0u8; 32][..len]);
+        zero_pad -= len;
+    }
+    for message in messages {
+        b_0 = b_0.add(*message);
+    }
+    let b_0 = dst(b_0.add([0, LEN, 0])).finalize();
+    let b_1 = dst(H::new().add(b_0.as_slice()).add([1u8])).finalize();
+    let mut b_0_xor_b_1 = [0u8; 32];
+    for (byte, (a, b)) in b_0_xor_b_1.iter_mut().zip(b_0.iter().zip(b_1.iter())) {
+        *byte = a ^ b;
+    }
+    let b_2 = dst(H::new().add(b_0_xor_b_1).add([2u8])).finalize();
+
+    // the 48 bytes are b_1 || b_2[..16] interpreted as a big-endian integer
+    let mut high = [0u8; 32];
+    high[16..].copy_from_slice(&b_1[..16]);
+    let mut low = [0u8; 32];
+    low[..16].copy_from_slice(&b_1[16..]);
+    low[16..].copy_from_slice(&b_2[..16]);
+    let high = Scalar::<Public, Zero>::from_bytes_mod_order(high);
+    let low = Scalar::<Public, Zero>::from_bytes_mod_order(low);
+    let two_pow_256 = Scalar::<Public, Zero>::from_bytes_mod_order(TWO_POW_256);
+    s!(high * two_pow_256 + low).public()
+}
+
+/// The binding coefficient of [`crate::musig::MuSig::start_sign_session`].
+pub(crate) fn musig_binding_coeff<H: Digest<OutputSize = U32> + Clone>(
+    nonce_coeff_hash: &H,
+    agg_nonce: &Nonce<Zero>,
+    agg_public_key: Point<EvenY>,
+    message: Message,
+) -> Scalar<Public, Zero> {
+    Scalar::from_hash(
+        nonce_coeff_hash
+            .clone()
+            .add(agg_nonce.to_bytes())
+            .add(agg_public_key)
+            .add(message),
+    )
+    .public()
+    .mark_zero()
+}
+
+/// The key aggregation coefficient of each of `keys` in the order they are given.
+pub(crate) fn musig_key_coeffs<'a, H: Digest<OutputSize = U32> + Clone + 'a>(
+    pk_hash: &H,
+    coeff_hash: &H,
+    keys: &'a [Point],
+) -> impl Iterator<Item = Scalar<Public>> + 'a {
+    let coeff_hash = {
+        let L = pk_hash.clone().add(keys).finalize();
+        coeff_hash.clone().add(L.as_slice())
+    };
+
+    let mut second = None;
+    keys.iter().map(move |key| {
+        // This is the logic for IsSecond from appendix B of the MuSig2 paper
+        if second.is_none() && key != &keys[0] {
+            second = Some(key);
+        }
+        if second != Some(key) {
+            Scalar::from_hash(coeff_hash.clone().add(key))
+        } else {
+            Scalar::one()
+        }
+        .public()
+    })
+}
+
+/// Turns the aggregate of the bound nonces into the `R` of the signature and whether the signers
+/// have to negate their nonces to get it.
+pub(crate) fn session_nonce(agg_nonce: Point<NonNormal, Public, Zero>) -> (Point<EvenY>, bool) {
+    agg_nonce
+        .normalize()
+        .non_zero()
+        .unwrap_or(Point::generator())
+        .into_point_with_even_y()
+}
+
+/// Creates a signature share (FROST) or partial signature (MuSig) `r1 + b * r2 + c * coeff * x`.
+///
+/// `coeff` is what the signer's secret `x` is multiplied by: the lagrange coefficient of a FROST
+/// share or the key aggregation coefficient of a MuSig key. It's negated if `key_needs_negation`
+/// and the nonce is negated if `nonces_need_negation`.
+pub(crate) fn sign(
+    challenge: &Scalar<Public, Zero>,
+    binding_coeff: &Scalar<Public, Zero>,
+    mut coeff: Scalar<Public>,
+    key_needs_negation: bool,
+    nonces_need_negation: bool,
+    secret: &Scalar,
+    secret_nonce: NonceKeyPair,
+) -> Scalar<Public, Zero> {
+    coeff.conditional_negate(key_needs_negation);
+    let [mut r1, mut r2] = secret_nonce.into_secrets();
+    r1.conditional_negate(nonces_need_negation);
+    r2.conditional_negate(nonces_need_negation);
+
+    let b = binding_coeff;
+    let x = secret;
+    let c = challenge;
+    let share = s!(r1 + (r2 * b) + coeff * x * c).public();
+    #[cfg(feature = "zeroize")]
+    {
+        use secp256kfun::zeroize::Zeroize;
+        r1.zeroize();
+        r2.zeroize();
+    }
+    share
+}
+
+/// Checks a share created by [`sign`] against the signer's public key `X` and `nonce`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn verify(
+    challenge: &Scalar<Public, Zero>,
+    binding_coeff: &Scalar<Public, Zero>,
+    mut coeff: Scalar<Public>,
+    key_needs_negation: bool,
+    nonces_need_negation: bool,
+    X: &Point<impl PointType, Public, impl ZeroChoice>,
+    nonce: &Nonce,
+    share: Scalar<Public, Zero>,
+) -> bool {
+    coeff.conditional_negate(key_needs_negation);
+    let [R1, R2] = nonce.0;
+    let R1 = R1.conditional_negate(nonces_need_negation);
+    let R2 = R2.conditional_negate(nonces_need_negation);
+    let b = binding_coeff;
+    let c = challenge;
+    let s = share;
+    g!(R1 + b * R2 + (c * coeff) * X - s * G).is_zero()
+}
+
+/// Sums the shares and adds the challenge times the tweak of the key.
+pub(crate) fn combine(
+    challenge: &Scalar<Public, Zero>,
+    tweak: &Scalar<Public, Zero>,
+    shares: impl IntoIterator<Item = Scalar<Public, Zero>>,
+) -> Scalar<Public, Zero> {
+    shares
+        .into_iter()
+        .fold(s!(challenge * tweak).public(), |acc, share| {
+            s!(acc + share).public()
+        })
+}
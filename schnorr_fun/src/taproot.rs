@@ -0,0 +1,193 @@
+//! [BIP341] taproot key tweaking.
+//!
+//! A taproot output key `Q` commits to an internal key `P` and optionally the merkle root of a
+//! script tree:
+//!
+//! ```text
+//! t = H_TapTweak(P || merkle_root)
+//! Q = P + t * G
+//! ```
+//!
+//! where `merkle_root` is left out if there is no script tree. `Q` is x-only so whether it had to
+//! be negated to get an even y-coordinate is recorded separately as the parity in script path
+//! control blocks. [`output_key`] computes `Q`, [`tweak_keypair`] tweaks the internal keypair so
+//! it can sign for `Q` on the key path and [`verify_commitment`] checks `Q` against a control
+//! block's internal key, parity and merkle root. All the parity handling is done by
+//! [`KeyPair::tweak`] and [`Point::tweak_add`] so you never have to negate anything yourself.
+//!
+//! Everything is only compatible with [BIP341] if `H` is SHA256.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     fun::{marker::*, Scalar},
+//!     taproot, Message,
+//! };
+//! use sha2::Sha256;
+//! let schnorr = schnorr_fun::test_instance!();
+//! let internal_keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! // a key path only output
+//! let (output_key, _) = taproot::output_key::<Sha256>(internal_keypair.public_key(), None).unwrap();
+//! let output_keypair = taproot::tweak_keypair::<Sha256>(internal_keypair, None).unwrap();
+//! assert_eq!(output_keypair.public_key(), output_key);
+//! let message = Message::<Public>::raw(b"a sighash a sighash a sighash!!!");
+//! let signature = schnorr.sign(&output_keypair, message);
+//! assert!(schnorr.verify(&output_key, message, &signature));
+//! ```
+//!
+//! [BIP341]: https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+//! [`KeyPair::tweak`]: crate::fun::KeyPair::tweak
+//! [`Point::tweak_add`]: crate::fun::Point::tweak_add
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    hash::{HashAdd, Tag},
+    marker::*,
+    KeyPair, Point, Scalar,
+};
+
+/// Computes the TapTweak `t = H_TapTweak(P || merkle_root)` for `internal_key`.
+///
+/// Returns `None` if the hash is not less than the curve order (in which case [BIP341] says the
+/// output can't be created).
+///
+/// [BIP341]: https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+pub fn tap_tweak<H: Tag + Default + Digest<OutputSize = U32>>(
+    internal_key: Point<EvenY>,
+    merkle_root: Option<[u8; 32]>,
+) -> Option<Scalar<Public, Zero>> {
+    let mut hash = H::default()
+        .tag(b"TapTweak")
+        .add(internal_key.to_xonly_bytes());
+    if let Some(merkle_root) = merkle_root {
+        hash = hash.add(merkle_root);
+    }
+    Scalar::from_bytes(hash.finalize().into())
+}
+
+/// Computes the output key for `internal_key` and `merkle_root` and whether it had to be negated
+/// to have an even y-coordinate (the parity that goes in control blocks).
+///
+/// Returns `None` if the output key can't be created which only happens with negligible
+/// probability.
+pub fn output_key<H: Tag + Default + Digest<OutputSize = U32>>(
+    internal_key: Point<EvenY>,
+    merkle_root: Option<[u8; 32]>,
+) -> Option<(Point<EvenY>, bool)> {
+    let tweak = tap_tweak::<H>(internal_key, merkle_root)?;
+    internal_key.tweak_add(&tweak)
+}
+
+/// Tweaks the keypair for `internal_key` into the keypair for the [`output_key`] so it can sign
+/// on the key path.
+pub fn tweak_keypair<H: Tag + Default + Digest<OutputSize = U32>>(
+    internal_keypair: KeyPair<EvenY>,
+    merkle_root: Option<[u8; 32]>,
+) -> Option<KeyPair<EvenY>> {
+    let tweak = tap_tweak::<H>(internal_keypair.public_key(), merkle_root)?;
+    internal_keypair.tweak(tweak)
+}
+
+/// Checks that `output_key` with parity `output_key_is_odd` commits to `internal_key` and
+/// `merkle_root`.
+///
+/// This is the check a script path spend makes with the internal key and parity from the control
+/// block and the merkle root computed from the script and merkle path.
+#[must_use]
+pub fn verify_commitment<H: Tag + Default + Digest<OutputSize = U32>>(
+    output_key: Point<EvenY>,
+    output_key_is_odd: bool,
+    internal_key: Point<EvenY>,
+    merkle_root: Option<[u8; 32]>,
+) -> bool {
+    match tap_tweak::<H>(internal_key, merkle_root) {
+        Some(tweak) => internal_key.tweak_add_check(&output_key, output_key_is_odd, &tweak),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use secp256kfun::{g, hex, G};
+    use sha2::Sha256;
+
+    fn xonly(hex: &str) -> Point<EvenY> {
+        Point::from_xonly_bytes(hex::decode_array(hex).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn bip341_wallet_test_vectors() {
+        // from the scriptPubKey section of BIP341's wallet-test-vectors.json
+        let internal_key =
+            xonly("d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d");
+        assert_eq!(
+            tap_tweak::<Sha256>(internal_key, None).unwrap().to_bytes(),
+            hex::decode_array::<32>(
+                "b86e7be8f39bab32a6f2c0443abbc210f0edac0e2c53d501b36b64437d9c6c70"
+            )
+            .unwrap()
+        );
+        let (output, is_odd) = output_key::<Sha256>(internal_key, None).unwrap();
+        assert_eq!(
+            output,
+            xonly("53a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343")
+        );
+        assert!(verify_commitment::<Sha256>(
+            output,
+            is_odd,
+            internal_key,
+            None
+        ));
+        assert!(!verify_commitment::<Sha256>(
+            output,
+            !is_odd,
+            internal_key,
+            None
+        ));
+
+        let internal_key =
+            xonly("187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27");
+        let merkle_root =
+            hex::decode_array("5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21")
+                .unwrap();
+        assert_eq!(
+            tap_tweak::<Sha256>(internal_key, Some(merkle_root))
+                .unwrap()
+                .to_bytes(),
+            hex::decode_array::<32>(
+                "cbd8679ba636c1110ea247542cfbd964131a6be84f873f7f3b62a777528ed001"
+            )
+            .unwrap()
+        );
+        let (output, is_odd) = output_key::<Sha256>(internal_key, Some(merkle_root)).unwrap();
+        assert_eq!(
+            output,
+            xonly("147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3")
+        );
+        assert!(verify_commitment::<Sha256>(
+            output,
+            is_odd,
+            internal_key,
+            Some(merkle_root)
+        ));
+        assert!(!verify_commitment::<Sha256>(
+            output,
+            is_odd,
+            internal_key,
+            None
+        ));
+    }
+
+    #[test]
+    fn tweaked_keypair_matches_output_key() {
+        for _ in 0..8 {
+            let keypair = KeyPair::<EvenY>::new(Scalar::random(&mut rand::thread_rng()));
+            let merkle_root = Some([42u8; 32]);
+            let (output, _) = output_key::<Sha256>(keypair.public_key(), merkle_root).unwrap();
+            let tweaked = tweak_keypair::<Sha256>(keypair, merkle_root).unwrap();
+            assert_eq!(tweaked.public_key(), output);
+            assert_eq!(g!({ tweaked.secret_key() } * G), tweaked.public_key());
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Everything needed to verify signatures made by a single signer, a MuSig group or a FROST group.
+//!
+//! [MuSig](crate::musig) and [FROST](crate::frost) signatures are ordinary BIP340 signatures under
+//! the group's x-only joint key so a verifier doesn't need to know how a signature was made or who
+//! the signers were. It only needs the joint key (e.g. [`FrostKey::public_key`] of the x-only
+//! [`FrostKey`]) and [`verify_threshold_signature`].
+//!
+//! None of this module needs `alloc` so light clients that only verify can depend on `schnorr_fun`
+//! with `default-features = false`. This leaves out the interactive signing machinery of
+//! `musig` and `frost` which are only compiled with the `alloc` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     fun::marker::*,
+//!     verifier::{verify_threshold_signature, Message, Point, Signature},
+//! };
+//! # let frost = schnorr_fun::frost::new_with_deterministic_nonces::<sha2::Sha256>();
+//! # let (frost_key, secret_shares) = frost.simulate_keygen(2, 3, &mut rand::thread_rng());
+//! # let frost_key = frost_key.into_xonly_key();
+//! # let message = Message::<Public>::plain("my-app", b"hello");
+//! # let session_nonces = secret_shares.keys().take(2).map(|i| (*i, frost.gen_nonce(&mut rand::thread_rng()))).collect::<Vec<_>>();
+//! # let session = frost.start_sign_session(&frost_key, session_nonces.iter().map(|(i, nonce)| (*i, nonce.public())).collect(), message);
+//! # let shares = session_nonces.into_iter().map(|(i, nonce)| frost.sign(&frost_key, &session, i, &secret_shares[&i], nonce)).collect::<Vec<_>>();
+//! # let signature = frost.combine_signature_shares(&frost_key, &session, shares);
+//! # let joint_key = frost_key.public_key();
+//! // all the verifier needs is the joint key, the message and the signature
+//! fn check(joint_key: &Point<EvenY>, message: Message, signature: &Signature) -> bool {
+//!     verify_threshold_signature::<sha2::Sha256>(joint_key, message, signature)
+//! }
+//! assert!(check(&joint_key, message, &signature));
+//! ```
+//!
+//! [`FrostKey`]: crate::frost::FrostKey
+//! [`FrostKey::public_key`]: crate::frost::FrostKey::public_key
+pub use crate::{Message, Schnorr, Signature};
+pub use secp256kfun::Point;
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    marker::*,
+    Tag,
+};
+
+/// Verifies a signature made by a MuSig or FROST group under its x-only `joint_key`.
+///
+/// This is the same as [`Schnorr::verify`] with the BIP340 challenge hash `H`. It exists so
+/// verifiers can make it clear that a signature is expected to come from a group.
+#[must_use]
+pub fn verify_threshold_signature<H>(
+    joint_key: &Point<EvenY, impl Secrecy>,
+    message: Message<'_, impl Secrecy>,
+    signature: &Signature<impl Secrecy>,
+) -> bool
+where
+    H: Digest<OutputSize = U32> + Tag + Default + Clone,
+{
+    Schnorr::<H>::verify_only().verify(joint_key, message, signature)
+}
@@ -0,0 +1,214 @@
+//! A verifiable random function (VRF) with BIP340 keys.
+//!
+//! [`Vrf::prove`] maps an input `alpha` to a 32-byte [`VrfOutput`] that looks random to anyone
+//! without the secret key. It also returns a [`VrfProof`] that lets anyone with the public key
+//! check the output is the only one the key could have given for `alpha`. Because there's exactly
+//! one valid output for each key and input, the key holder can't grind for a favourable one. That
+//! makes it a good fit for things like leader election.
+//!
+//! This follows the shape of ECVRF ([RFC 9381]):
+//!
+//! 1. The input point is `H = hash_to_curve(H(domain, X, alpha))` with the [RFC 9380 hash to
+//!    curve](crate::fun::hash_to_curve) so nobody knows its discrete log.
+//! 2. The proof is `Γ = x * H` and a [DLEQ proof](crate::fun::dleq) that `X = x * G` and `Γ` have the
+//!    same discrete log.
+//! 3. The output is a hash of `Γ`.
+//!
+//! RFC 9381 doesn't define a secp256k1 suite, so the outputs won't match any other implementation.
+//! Outputs are tied to the `domain` given to [`Vrf::new`].
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{fun::{nonce, Scalar}, vrf::Vrf};
+//! use sha2::Sha256;
+//! let vrf = Vrf::<Sha256, _>::new("my-chain/leader-election", nonce::Deterministic::<Sha256>::default());
+//! let schnorr = schnorr_fun::test_instance!();
+//! let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let (proof, output) = vrf.prove(&keypair, b"slot 42");
+//! // anyone with the public key can check the output
+//! assert_eq!(vrf.verify(&keypair.public_key(), b"slot 42", &proof), Some(output));
+//! assert_eq!(vrf.verify(&keypair.public_key(), b"slot 43", &proof), None);
+//! let is_leader = output.to_bytes()[0] < 16;
+//! ```
+//!
+//! [RFC 9381]: https://www.rfc-editor.org/rfc/rfc9381.html
+use secp256kfun::{
+    digest::{core_api::BlockSizeUser, generic_array::typenum::U32, Digest},
+    dleq::{Dleq, DleqProof},
+    g,
+    hash::{HashAdd, Tag},
+    hash_to_curve::hash_to_curve,
+    marker::*,
+    nonce::NonceGen,
+    KeyPair, Point, G,
+};
+
+const DST: &[u8] = b"schnorr_fun-VRF-V01-CS01-with-secp256k1_XMD:SHA-256_SSWU_RO_";
+
+/// A VRF for one application. See the [module documentation](crate::vrf).
+#[derive(Clone, Debug)]
+pub struct Vrf<H, NG> {
+    dleq: Dleq<H>,
+    input_hash: H,
+    output_hash: H,
+    nonce_gen: NG,
+}
+
+/// The proof that a [`VrfOutput`] is the output for a key and input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct VrfProof {
+    /// `Γ = x * H` where `H` is the input point.
+    pub gamma: Point,
+    /// The proof that `Γ` and the public key have the same discrete log.
+    pub dleq: DleqProof,
+}
+
+impl VrfProof {
+    /// The proof as `Γ || c || s` (97 bytes).
+    pub fn to_bytes(&self) -> [u8; 97] {
+        let mut bytes = [0u8; 97];
+        bytes[..33].copy_from_slice(&self.gamma.to_bytes());
+        bytes[33..].copy_from_slice(&self.dleq.to_bytes());
+        bytes
+    }
+
+    /// Decodes a proof encoded with [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: [u8; 97]) -> Option<Self> {
+        let mut dleq = [0u8; 64];
+        dleq.copy_from_slice(&bytes[33..]);
+        Some(Self {
+            gamma: Point::from_slice(&bytes[..33])?,
+            dleq: DleqProof::from_bytes(dleq)?,
+        })
+    }
+}
+
+/// The pseudorandom output of a [`Vrf`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::fun::serde::Deserialize, crate::fun::serde::Serialize),
+    serde(crate = "crate::fun::serde")
+)]
+pub struct VrfOutput([u8; 32]);
+
+impl VrfOutput {
+    /// The 32 output bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl<H, NG> Vrf<H, NG>
+where
+    H: BlockSizeUser + Digest<OutputSize = U32> + Tag + Default + Clone,
+{
+    /// Creates the VRF for `domain` which should be unique to the application and what the
+    /// outputs are for.
+    pub fn new(domain: &str, nonce_gen: NG) -> Self {
+        let hash = |kind: &[u8]| H::default().tag_vectored([kind, domain.as_bytes()].into_iter());
+        Self {
+            dleq: Dleq::new("schnorr_fun/vrf"),
+            input_hash: hash(b"schnorr_fun/vrf/input/"),
+            output_hash: hash(b"schnorr_fun/vrf/output/"),
+            nonce_gen,
+        }
+    }
+
+    /// The point `H` that the key holder multiplies by their secret key for `alpha`.
+    pub fn input_point(&self, public_key: &Point<EvenY>, alpha: &[u8]) -> Point {
+        let msg = self
+            .input_hash
+            .clone()
+            .add(*public_key)
+            .add(alpha)
+            .finalize();
+        hash_to_curve::<H>(&msg, DST)
+    }
+
+    /// The output that `proof` is for.
+    ///
+    /// This doesn't check the proof. Use [`verify`](Self::verify) unless you already have.
+    pub fn output(&self, proof: &VrfProof) -> VrfOutput {
+        VrfOutput(self.output_hash.clone().add(proof.gamma).finalize().into())
+    }
+
+    /// Evaluates the VRF on `alpha` with `keypair`'s secret key.
+    ///
+    /// Returns the proof and the output. The output only depends on the key and `alpha`.
+    pub fn prove(&self, keypair: &KeyPair<EvenY>, alpha: &[u8]) -> (VrfProof, VrfOutput)
+    where
+        NG: NonceGen + Tag + Clone,
+    {
+        let (x, X) = keypair.as_tuple();
+        let H = self.input_point(&X, alpha);
+        let proof = VrfProof {
+            gamma: g!(x * H).normalize(),
+            dleq: self.dleq.prove_dleq(&self.nonce_gen, x, G, &H),
+        };
+        (proof, self.output(&proof))
+    }
+
+    /// Checks `proof` shows that the output for `alpha` under `public_key` is the one it commits
+    /// to and returns it.
+    #[must_use]
+    pub fn verify(
+        &self,
+        public_key: &Point<EvenY>,
+        alpha: &[u8],
+        proof: &VrfProof,
+    ) -> Option<VrfOutput> {
+        let H = self.input_point(public_key, alpha);
+        if self
+            .dleq
+            .verify_dleq(G, public_key, &H, &proof.gamma, &proof.dleq)
+        {
+            Some(self.output(proof))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use secp256kfun::{nonce::Deterministic, Scalar};
+    use sha2::Sha256;
+
+    #[test]
+    fn prove_and_verify() {
+        let vrf = Vrf::<Sha256, _>::new("test", Deterministic::<Sha256>::default());
+        let keypair = KeyPair::<EvenY>::new(Scalar::random(&mut rand::thread_rng()));
+        let public_key = keypair.public_key();
+
+        let (proof, output) = vrf.prove(&keypair, b"alpha");
+        assert_eq!(vrf.verify(&public_key, b"alpha", &proof), Some(output));
+        assert_eq!(VrfProof::from_bytes(proof.to_bytes()), Some(proof));
+        // the output is a function of the key and input
+        assert_eq!(vrf.prove(&keypair, b"alpha").1, output);
+        let (other_proof, other_output) = vrf.prove(&keypair, b"beta");
+        assert_ne!(other_output, output);
+        assert_eq!(vrf.verify(&public_key, b"alpha", &other_proof), None);
+
+        // wrong key or input
+        let other_key = KeyPair::<EvenY>::new(Scalar::random(&mut rand::thread_rng()));
+        assert_eq!(vrf.verify(&other_key.public_key(), b"alpha", &proof), None);
+        assert_eq!(vrf.verify(&public_key, b"beta", &proof), None);
+        // a different gamma (and so output) can't be passed off with the proof
+        let mut bad = proof;
+        bad.gamma = other_proof.gamma;
+        assert_eq!(vrf.verify(&public_key, b"alpha", &bad), None);
+
+        // other domains give other outputs
+        let other_vrf = Vrf::<Sha256, _>::new("other", Deterministic::<Sha256>::default());
+        assert_ne!(other_vrf.prove(&keypair, b"alpha").1, output);
+        assert_eq!(other_vrf.verify(&public_key, b"alpha", &proof), None);
+    }
+}
@@ -0,0 +1,9 @@
+//! Batteries-included wallet setups.
+//!
+//! These wire together the low level pieces in this crate ([`frost`], [`share_backup`]) for common
+//! setups. They are intended as a reference for how the pieces fit together as much as something
+//! to use directly.
+//!
+//! [`frost`]: crate::frost
+//! [`share_backup`]: crate::share_backup
+pub mod two_of_three;
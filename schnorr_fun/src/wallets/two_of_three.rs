@@ -0,0 +1,366 @@
+//! A 2-of-3 FROST wallet for personal custody.
+//!
+//! The key is generated on a single device which hands out three [`ShareBackup`]s (e.g. written
+//! down and stored in different places). Any two of them can sign or recover the wallet.
+//!
+//! ## Synopsis
+//!
+//! ```
+//! use schnorr_fun::{
+//!     frost,
+//!     wallets::two_of_three::{Network, TwoOfThree},
+//!     Message,
+//! };
+//! use sha2::Sha256;
+//! let two_of_three = TwoOfThree::new(frost::new_with_synthetic_nonces::<Sha256, rand::rngs::ThreadRng>());
+//! // generate the wallet and the three backups
+//! let (wallet, backups) = two_of_three.generate(&mut rand::thread_rng());
+//! // show the user the backups to write down
+//! let backup_strings = backups.map(|backup| backup.to_string());
+//! // receive coins at the first address
+//! let address = two_of_three.address(&wallet, Network::Bitcoin, 0);
+//! # assert!(address.starts_with("bc1p"));
+//! // ...later restore the wallet from any two of the backups
+//! let shares = [backup_strings[0].parse().unwrap(), backup_strings[2].parse().unwrap()];
+//! let recovered = two_of_three.recover(&shares).unwrap();
+//! assert_eq!(recovered.public_key(), wallet.public_key());
+//! // and sign with them under the key of the first address
+//! let message = Message::<schnorr_fun::fun::marker::Public>::plain("my-app", b"spend it all");
+//! let signature = two_of_three
+//!     .sign(&recovered, 0, [&shares[0], &shares[1]], message, &mut rand::thread_rng())
+//!     .unwrap();
+//! assert!(two_of_three.verify(&wallet, 0, message, &signature));
+//! ```
+//!
+//! ## Key derivation
+//!
+//! The key for each address is derived from the wallet's key by an unhardened tweak of the
+//! derivation index. The key of the address is then tweaked again as a [BIP341] key path only
+//! output. The derivation is specific to this module (it isn't [BIP32]) so other wallet software
+//! won't find the addresses from the wallet's key. The BIP341 tweak is only the standard one if
+//! `H` is SHA256.
+//!
+//! [BIP32]: https://bips.xyz/32
+//! [BIP341]: https://bips.xyz/341
+use crate::{
+    frost::{Frost, FrostKey, PartyIndex},
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        g,
+        hash::{HashAdd, Tag},
+        marker::*,
+        poly,
+        rand_core::RngCore,
+        Point, Scalar, G,
+    },
+    share_backup::{polynomial_identifier, ShareBackup},
+    Message, Signature,
+};
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use bech32::{u5, ToBase32, Variant::Bech32m};
+
+/// The bitcoin network to generate addresses for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Network {
+    /// Bitcoin mainnet
+    Bitcoin,
+    /// Bitcoin testnet
+    Testnet,
+    /// Bitcoin signet
+    Signet,
+    /// Bitcoin regtest
+    Regtest,
+}
+
+impl Network {
+    /// The human readable part of segwit addresses on this network.
+    pub fn hrp(&self) -> &'static str {
+        match self {
+            Network::Bitcoin => "bc",
+            Network::Testnet | Network::Signet => "tb",
+            Network::Regtest => "bcrt",
+        }
+    }
+}
+
+/// The public part of a 2-of-3 wallet.
+///
+/// Created with [`TwoOfThree::generate`] or [`TwoOfThree::recover`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Wallet {
+    frost_key: FrostKey<Normal>,
+}
+
+impl Wallet {
+    /// The root public key of the wallet (before any derivation).
+    pub fn public_key(&self) -> Point {
+        self.frost_key.public_key()
+    }
+
+    /// The underlying [`FrostKey`] for use with the lower level [`frost`](crate::frost) API.
+    pub fn frost_key(&self) -> &FrostKey<Normal> {
+        &self.frost_key
+    }
+}
+
+/// Errors when recovering or signing with a [`Wallet`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The two backups are from different wallets.
+    IncompatibleShares,
+    /// The same share was provided twice.
+    DuplicateShare,
+    /// The share at this index doesn't belong to the wallet.
+    ShareNotInWallet(PartyIndex),
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        use Error::*;
+        match self {
+            IncompatibleShares => write!(f, "the shares are not from the same wallet"),
+            DuplicateShare => write!(f, "the same share was provided twice"),
+            ShareNotInWallet(i) => write!(f, "the share at index {i} is not part of this wallet"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// The 2-of-3 wallet context.
+///
+/// Wraps a [`Frost`] instance which does the actual work.
+#[derive(Clone)]
+pub struct TwoOfThree<H, NG> {
+    /// The FROST instance used for signing.
+    pub frost: Frost<H, NG>,
+}
+
+impl<H, NG> TwoOfThree<H, NG> {
+    /// Create a new 2-of-3 context from a FROST context.
+    pub fn new(frost: Frost<H, NG>) -> Self {
+        Self { frost }
+    }
+}
+
+impl<H, NG> TwoOfThree<H, NG>
+where
+    H: Tag + Default + Digest<OutputSize = U32> + Clone,
+    NG: crate::nonce::NonceGen,
+{
+    /// Generate a new wallet along with its three share backups.
+    ///
+    /// The key is generated on this device so `rng` must be a good source of randomness. The
+    /// secret shares only exist in the returned backups.
+    pub fn generate(&self, rng: &mut impl RngCore) -> (Wallet, [ShareBackup; 3]) {
+        let (frost_key, shares) = self.frost.simulate_keygen(2, 3, rng);
+        let point_poly = frost_key.point_polynomial();
+        let backups = shares
+            .into_iter()
+            .map(|(index, secret_share)| {
+                ShareBackup::new::<H>(&point_poly, secret_share.mark_zero(), index)
+            })
+            .collect::<Vec<_>>()
+            .try_into()
+            .expect("keygen creates three shares");
+        (Wallet { frost_key }, backups)
+    }
+}
+
+impl<H, NG> TwoOfThree<H, NG>
+where
+    H: Tag + Default + Digest<OutputSize = U32> + Clone,
+{
+    /// Recover a wallet from two of its backups.
+    pub fn recover(&self, backups: &[ShareBackup; 2]) -> Result<Wallet, Error> {
+        let [a, b] = backups;
+        if a.identifier != b.identifier {
+            return Err(Error::IncompatibleShares);
+        }
+        if a.share_index == b.share_index {
+            return Err(Error::DuplicateShare);
+        }
+        let images = backups
+            .iter()
+            .map(|backup| {
+                let image = g!(backup.secret_share * G)
+                    .normalize()
+                    .non_zero()
+                    .ok_or(Error::ShareNotInWallet(backup.share_index))?;
+                Ok((backup.share_index, image))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let point_poly = poly::point::interpolate(images)
+            .into_iter()
+            .map(|coeff| coeff.normalize())
+            .collect::<Vec<_>>();
+        if polynomial_identifier::<H>(&point_poly) != a.identifier {
+            return Err(Error::IncompatibleShares);
+        }
+        let frost_key =
+            FrostKey::from_point_polynomial(point_poly).ok_or(Error::IncompatibleShares)?;
+        Ok(Wallet { frost_key })
+    }
+
+    /// Derive the key that signs for the address at `derivation_index`.
+    ///
+    /// The returned key has the [BIP341] tweak applied so its public key is the output key of
+    /// the address.
+    ///
+    /// [BIP341]: https://bips.xyz/341
+    pub fn derive_key(&self, wallet: &Wallet, derivation_index: u32) -> FrostKey<EvenY> {
+        let child_tweak = Scalar::from_hash(
+            H::default()
+                .tag(b"secp256kfun/two_of_three/derive")
+                .add(wallet.public_key())
+                .add(derivation_index.to_be_bytes()),
+        );
+        let internal_key = wallet
+            .frost_key
+            .clone()
+            .tweak(child_tweak)
+            .expect("computationally unreachable")
+            .into_xonly_key();
+        let tap_tweak = Scalar::from_hash(
+            H::default()
+                .tag(b"TapTweak")
+                .add(internal_key.public_key().to_xonly_bytes()),
+        );
+        internal_key
+            .tweak(tap_tweak)
+            .expect("computationally unreachable")
+    }
+
+    /// The segwit v1 (taproot) address at `derivation_index`.
+    pub fn address(&self, wallet: &Wallet, network: Network, derivation_index: u32) -> String {
+        let output_key = self.derive_key(wallet, derivation_index).public_key();
+        let mut data = vec![u5::try_from_u8(1).expect("1 is a valid u5")];
+        data.extend(output_key.to_xonly_bytes().to_base32());
+        bech32::encode(network.hrp(), data, Bech32m).expect("hrp is valid")
+    }
+
+    /// Sign `message` under the key for `derivation_index` with two of the wallet's shares.
+    ///
+    /// This is for when both shares are on the same device (e.g. after entering two backups). To
+    /// sign with shares held on different devices use the [`frost`](crate::frost) API directly
+    /// with the key from [`derive_key`](Self::derive_key).
+    pub fn sign(
+        &self,
+        wallet: &Wallet,
+        derivation_index: u32,
+        shares: [&ShareBackup; 2],
+        message: Message<'_, Public>,
+        nonce_rng: &mut impl RngCore,
+    ) -> Result<Signature, Error> {
+        if shares[0].share_index == shares[1].share_index {
+            return Err(Error::DuplicateShare);
+        }
+        let signers = shares
+            .iter()
+            .map(|share| {
+                let secret_share = share
+                    .secret_share
+                    .non_zero()
+                    .filter(|secret_share| {
+                        wallet.frost_key.verification_share(share.share_index)
+                            == g!(secret_share * G)
+                    })
+                    .ok_or(Error::ShareNotInWallet(share.share_index))?;
+                Ok((share.share_index, secret_share))
+            })
+            .collect::<Result<BTreeMap<_, _>, _>>()?;
+
+        let frost_key = self.derive_key(wallet, derivation_index);
+        let secret_nonces = signers
+            .keys()
+            .map(|index| (*index, self.frost.gen_nonce(nonce_rng)))
+            .collect::<BTreeMap<_, _>>();
+        let session = self.frost.start_sign_session(
+            &frost_key,
+            secret_nonces
+                .iter()
+                .map(|(index, nonce)| (*index, nonce.public()))
+                .collect(),
+            message,
+        );
+        let signature_shares = secret_nonces
+            .into_iter()
+            .map(|(index, nonce)| {
+                self.frost
+                    .sign(&frost_key, &session, index, &signers[&index], nonce)
+            })
+            .collect();
+        Ok(self
+            .frost
+            .combine_signature_shares(&frost_key, &session, signature_shares))
+    }
+
+    /// Verify a signature under the key for `derivation_index`.
+    pub fn verify(
+        &self,
+        wallet: &Wallet,
+        derivation_index: u32,
+        message: Message<'_, Public>,
+        signature: &Signature,
+    ) -> bool {
+        let frost_key = self.derive_key(wallet, derivation_index);
+        self.frost
+            .schnorr
+            .verify(&frost_key.public_key(), message, signature)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frost;
+    use sha2::Sha256;
+
+    #[test]
+    fn generate_recover_and_sign() {
+        let two_of_three = TwoOfThree::new(frost::new_with_deterministic_nonces::<Sha256>());
+        let (wallet, backups) = two_of_three.generate(&mut rand::thread_rng());
+        let message = Message::<Public>::plain("test", b"two of three");
+
+        for (i, j) in [(0, 1), (0, 2), (1, 2)] {
+            let recovered = two_of_three.recover(&[backups[i], backups[j]]).unwrap();
+            assert_eq!(recovered, wallet);
+            let signature = two_of_three
+                .sign(
+                    &wallet,
+                    7,
+                    [&backups[i], &backups[j]],
+                    message,
+                    &mut rand::thread_rng(),
+                )
+                .unwrap();
+            assert!(two_of_three.verify(&wallet, 7, message, &signature));
+            assert!(!two_of_three.verify(&wallet, 8, message, &signature));
+        }
+
+        assert_eq!(
+            two_of_three.recover(&[backups[0], backups[0]]),
+            Err(Error::DuplicateShare)
+        );
+        let (_, other_backups) = two_of_three.generate(&mut rand::thread_rng());
+        assert_eq!(
+            two_of_three.recover(&[backups[0], other_backups[1]]),
+            Err(Error::IncompatibleShares)
+        );
+        assert_eq!(
+            two_of_three.sign(
+                &wallet,
+                0,
+                [&backups[0], &other_backups[1]],
+                message,
+                &mut rand::thread_rng()
+            ),
+            Err(Error::ShareNotInWallet(other_backups[1].share_index))
+        );
+        assert_ne!(
+            two_of_three.address(&wallet, Network::Bitcoin, 0),
+            two_of_three.address(&wallet, Network::Bitcoin, 1)
+        );
+    }
+}
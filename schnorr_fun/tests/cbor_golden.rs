@@ -0,0 +1,105 @@
+#![cfg(feature = "cbor")]
+//! Checks the canonical CBOR encodings against the hex files in `tests/cbor_golden`.
+//!
+//! If an encoding changes intentionally run with `UPDATE_GOLDEN=1` to rewrite the files.
+use schnorr_fun::{
+    binonce::{Nonce, NonceKeyPair},
+    cbor::{self, CanonicalCbor},
+    frost::{KeyGenShareBlame, PartyIndex, SignatureShareBlame},
+    fun::{g, marker::*, poly, s, Point, Scalar, G},
+    musig::PartialSignatureBlame,
+    Signature,
+};
+use std::{collections::BTreeMap, fmt::Debug, path::PathBuf};
+
+fn check<T: CanonicalCbor + PartialEq + Debug>(name: &str, value: T) {
+    let encoded = cbor::to_vec(&value);
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/cbor_golden")
+        .join(format!("{}.hex", name));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, format!("{}\n", hex(&encoded))).unwrap();
+    }
+    let golden = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(hex(&encoded), golden.trim(), "{} encoding changed", name);
+    assert_eq!(cbor::from_slice::<T>(&encoded).unwrap(), value);
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn nonce(a: u32, b: u32) -> Nonce {
+    NonceKeyPair::from_secrets([
+        Scalar::from(a).non_zero().unwrap(),
+        Scalar::from(b).non_zero().unwrap(),
+    ])
+    .public()
+}
+
+fn point_poly() -> Vec<Point> {
+    poly::scalar::to_point_poly(&[s!(1), s!(2)])
+}
+
+fn signature() -> Signature {
+    Signature {
+        R: g!(3 * G).normalize().into_point_with_even_y().0,
+        s: Scalar::from(4),
+    }
+}
+
+fn party(i: u32) -> PartyIndex {
+    Scalar::<Public, Zero>::from(i).non_zero().unwrap()
+}
+
+#[test]
+fn musig_messages() {
+    check("musig_nonce", nonce(1, 2));
+    check("musig_partial_sig", Scalar::<Public, Zero>::from(5));
+    check(
+        "musig_partial_sig_blame",
+        PartialSignatureBlame {
+            index: 1,
+            partial_sig: Scalar::from(5),
+            nonces: vec![nonce(1, 2), nonce(3, 4)],
+            attestation: signature(),
+        },
+    );
+}
+
+#[test]
+fn frost_keygen_messages() {
+    check("frost_keygen_broadcast", (point_poly(), signature()));
+    check(
+        "frost_secret_shares",
+        BTreeMap::from_iter([
+            (party(1), Scalar::<Secret, Zero>::from(6)),
+            (party(2), Scalar::<Secret, Zero>::from(7)),
+        ]),
+    );
+    check(
+        "frost_keygen_share_blame",
+        KeyGenShareBlame {
+            accused: party(1),
+            receiver: party(2),
+            point_poly: point_poly(),
+            secret_share: Scalar::from(8),
+        },
+    );
+}
+
+#[test]
+fn frost_sign_messages() {
+    let nonces = BTreeMap::from_iter([(party(1), nonce(1, 2)), (party(3), nonce(3, 4))]);
+    check("frost_nonces", nonces.clone());
+    check("frost_signature_share", Scalar::<Public, Zero>::from(9));
+    check(
+        "frost_signature_share_blame",
+        SignatureShareBlame {
+            index: party(3),
+            signature_share: Scalar::from(9),
+            nonces,
+            attestation: signature(),
+        },
+    );
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "serde")]
+use schnorr_fun::{
+    binonce::Nonce,
+    fun::{marker::*, serde},
+};
+static TEST_JSON: &str = include_str!("musig/nonce_agg_vectors.json");
+
+#[derive(serde::Deserialize)]
+#[serde(crate = "self::serde")]
+pub struct TestCases {
+    pnonces: Vec<String>,
+    valid_test_cases: Vec<TestCase>,
+    error_test_cases: Vec<TestCase>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(crate = "self::serde")]
+pub struct TestCase {
+    pnonce_indices: Vec<usize>,
+    #[serde(bound(deserialize = "Nonce<Zero>: serde::de::Deserialize<'de>"))]
+    expected: Option<Nonce<Zero>>,
+    #[allow(dead_code)]
+    error: Option<serde_json::Value>,
+}
+
+#[test]
+fn musig_nonce_agg() {
+    let test_cases = serde_json::from_str::<TestCases>(TEST_JSON).unwrap();
+
+    for test_case in &test_cases.valid_test_cases {
+        let nonces = test_case
+            .pnonce_indices
+            .iter()
+            .map(|i| test_cases.pnonces[*i].parse::<Nonce>().unwrap())
+            .collect::<Vec<_>>();
+        let expected = test_case.expected.unwrap();
+        assert_eq!(Nonce::aggregate(nonces.iter().copied()), expected);
+        assert_eq!(Nonce::aggregate(nonces.iter().rev().copied()), expected);
+    }
+
+    for test_case in &test_cases.error_test_cases {
+        assert!(test_case
+            .pnonce_indices
+            .iter()
+            .any(|i| test_cases.pnonces[*i].parse::<Nonce>().is_err()));
+    }
+}
@@ -0,0 +1,137 @@
+#![cfg(feature = "serde")]
+//! Checks the serde encodings of the session state that can be persisted between rounds against
+//! the JSON files in `tests/serde_golden`.
+//!
+//! If an encoding changes intentionally run with `UPDATE_GOLDEN=1` to rewrite the files (and bump
+//! the version of the type if it has one).
+use schnorr_fun::{
+    binonce::{Nonce, NonceKeyPair},
+    frost::{self, KeyGen, PartyIndex},
+    fun::{
+        marker::*,
+        poly,
+        serde::{de::DeserializeOwned, Serialize},
+        Scalar,
+    },
+    musig, Message,
+};
+use sha2::Sha256;
+use std::{collections::BTreeMap, path::PathBuf};
+
+fn check<T: Serialize + DeserializeOwned>(name: &str, value: T) -> T {
+    let encoded = serde_json::to_string(&value).unwrap();
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/serde_golden")
+        .join(format!("{}.json", name));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, format!("{}\n", encoded)).unwrap();
+    }
+    let golden = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(encoded, golden.trim(), "{} encoding changed", name);
+    let decoded = serde_json::from_str::<T>(&encoded).unwrap();
+    assert_eq!(serde_json::to_string(&decoded).unwrap(), encoded);
+    decoded
+}
+
+fn secret(i: u32) -> Scalar {
+    Scalar::from(i).non_zero().unwrap()
+}
+
+fn nonce_key_pair(a: u32, b: u32) -> NonceKeyPair {
+    NonceKeyPair::from_secrets([secret(a), secret(b)])
+}
+
+fn nonce(a: u32, b: u32) -> Nonce {
+    nonce_key_pair(a, b).public()
+}
+
+fn party(i: u32) -> PartyIndex {
+    Scalar::<Public, Zero>::from(i).non_zero().unwrap()
+}
+
+fn keygen() -> KeyGen {
+    let frost = frost::new_with_deterministic_nonces::<Sha256>();
+    let point_polys = BTreeMap::from_iter([
+        (
+            party(1),
+            poly::scalar::to_point_poly(&[secret(1), secret(2)]),
+        ),
+        (
+            party(2),
+            poly::scalar::to_point_poly(&[secret(3), secret(4)]),
+        ),
+    ]);
+    frost
+        .new_keygen(point_polys, &BTreeMap::<PartyIndex, Vec<Scalar>>::new())
+        .unwrap()
+}
+
+#[test]
+fn frost_session_state() {
+    let frost = frost::new_with_deterministic_nonces::<Sha256>();
+    let keygen = check("frost_keygen", keygen());
+    assert_eq!(frost.keygen_id(&keygen), frost.keygen_id(&self::keygen()));
+    let future_version = serde_json::to_string(&keygen)
+        .unwrap()
+        .replacen("[0,", "[1,", 1);
+    assert!(serde_json::from_str::<KeyGen>(&future_version).is_err());
+
+    let secret_shares = BTreeMap::from_iter([(party(1), secret(5)), (party(2), secret(6))]);
+    let (frost_key, _) = frost::split_existing_key(&secret(7), 2, 2, &mut FixedRng);
+    let frost_key = frost_key.frost_key().into_xonly_key();
+    let nonces = BTreeMap::from_iter([(party(1), nonce(1, 2)), (party(2), nonce(3, 4))]);
+    check("frost_nonces", nonces.clone());
+    check(
+        "frost_sign_session",
+        frost.start_sign_session(
+            &frost_key,
+            nonces,
+            Message::<Public>::plain("test", b"persist"),
+        ),
+    );
+    // secret state has an encoding too but it has to be stored like a secret
+    check("nonce_key_pair", nonce_key_pair(1, 2));
+    check("frost_secret_shares", secret_shares);
+}
+
+#[test]
+fn musig_session_state() {
+    let musig = musig::new_with_deterministic_nonces::<Sha256>();
+    let agg_key = musig
+        .new_agg_key(vec![
+            musig.new_keypair(secret(1)).public_key(),
+            musig.new_keypair(secret(2)).public_key(),
+        ])
+        .into_xonly_key();
+    let session = musig.start_sign_session(
+        &agg_key,
+        vec![nonce(1, 2), nonce(3, 4)],
+        Message::<Public>::plain("test", b"persist"),
+    );
+    check("musig_sign_session", session);
+}
+
+/// An rng that always gives the same bytes so the split key is the same every time.
+struct FixedRng;
+
+impl schnorr_fun::fun::rand_core::RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 {
+        42
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        42
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        dest.fill(42)
+    }
+
+    fn try_fill_bytes(
+        &mut self,
+        dest: &mut [u8],
+    ) -> Result<(), schnorr_fun::fun::rand_core::Error> {
+        dest.fill(42);
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
 #![cfg(feature = "share_backup")]
 use core::str::FromStr;
-use schnorr_fun::share_backup::{decode_backup, polynomial_identifier, ShareBackup};
+use schnorr_fun::share_backup::{
+    decode_backup, polynomial_identifier, FrostBackupDecodeError, ShareBackup,
+};
 use secp256kfun::{marker::*, poly, s, Scalar};
 
 #[test]
@@ -101,3 +103,311 @@ fn share_not_on_poly() {
 
     ShareBackup::new::<sha2::Sha256>(&polynomial, secret_share, share_index);
 }
+
+#[test]
+fn plain_integer_share_index() {
+    let secret_poly = vec![s!(6), s!(1), s!(9)];
+    let polynomial: Vec<_> = poly::scalar::to_point_poly(&secret_poly);
+    let secret_share = poly::scalar::eval(&secret_poly, s!(12).public());
+
+    let share_backup = ShareBackup::new::<sha2::Sha256>(&polynomial, secret_share, 12);
+    assert_eq!(share_backup.share_index, s!(12).public());
+    let share_backup_bech32 = format!("{}", share_backup);
+    assert_eq!(share_backup_bech32.len(), 71);
+
+    let decoded_share_backup = decode_backup(share_backup_bech32).unwrap();
+    assert_eq!(decoded_share_backup.party_id().unwrap().get(), 12);
+}
+
+#[test]
+fn long_share_index_has_no_party_id() {
+    let secret_poly = vec![s!(6), s!(1), s!(9)];
+    let polynomial: Vec<_> = poly::scalar::to_point_poly(&secret_poly);
+    let share_index = Scalar::<Secret>::from_str(
+        "34f7ce653cfa8454b3463726a599ef2925736442d2d06455974d6feae9450d90",
+    )
+    .unwrap()
+    .public();
+    let secret_share = poly::scalar::eval(&secret_poly, share_index);
+    let share_backup = ShareBackup::new::<sha2::Sha256>(&polynomial, secret_share, share_index);
+
+    let decoded_share_backup = decode_backup(share_backup.to_string()).unwrap();
+    assert_eq!(decoded_share_backup.party_id(), None);
+}
+
+#[test]
+fn recovery_kit_round_trip() {
+    use schnorr_fun::{
+        fun::{g, G},
+        share_backup::recovery_kit::{RecoveryKit, RecoveryKitError},
+    };
+    let secret_poly = vec![s!(1), s!(2)];
+    let polynomial: Vec<_> = poly::scalar::to_point_poly(&secret_poly);
+    let recipients = [
+        ("alice", Scalar::random(&mut rand::thread_rng())),
+        ("bob", Scalar::random(&mut rand::thread_rng())),
+    ];
+    let mut kit = RecoveryKit::new("Combine any two shares.");
+    for (i, (label, secret_key)) in recipients.iter().enumerate() {
+        let share_index = Scalar::<Public, Zero>::from(i as u32 + 1)
+            .non_zero()
+            .unwrap();
+        let secret_share = poly::scalar::eval(&secret_poly, share_index);
+        let backup = ShareBackup::new::<sha2::Sha256>(&polynomial, secret_share, share_index);
+        kit.seal::<sha2::Sha256>(
+            *label,
+            g!(secret_key * G).normalize(),
+            &backup,
+            &mut rand::thread_rng(),
+        );
+    }
+
+    assert_eq!(
+        kit.recipients().map(|(label, _)| label).collect::<Vec<_>>(),
+        vec!["alice", "bob"]
+    );
+    for (i, (label, secret_key)) in recipients.iter().enumerate() {
+        let (opened_label, backup) = kit.open::<sha2::Sha256>(secret_key).unwrap();
+        assert_eq!(opened_label, *label);
+        let share_index = Scalar::<Public, Zero>::from(i as u32 + 1)
+            .non_zero()
+            .unwrap();
+        assert_eq!(backup.share_index, share_index);
+        assert_eq!(
+            backup.secret_share,
+            poly::scalar::eval(&secret_poly, share_index)
+        );
+    }
+
+    let stranger = Scalar::random(&mut rand::thread_rng());
+    assert!(matches!(
+        kit.open::<sha2::Sha256>(&stranger),
+        Err(RecoveryKitError::NoEnvelope)
+    ));
+    assert!(matches!(
+        kit.envelopes[0].open::<sha2::Sha256>(&kit.instructions, &recipients[1].1),
+        Err(RecoveryKitError::InvalidMac)
+    ));
+
+    let mut tampered = kit.clone();
+    tampered.envelopes.swap(0, 1);
+    tampered.envelopes[0].label = "alice".into();
+    assert!(matches!(
+        tampered.envelopes[0].open::<sha2::Sha256>(&kit.instructions, &recipients[1].1),
+        Err(RecoveryKitError::InvalidMac)
+    ));
+
+    #[cfg(feature = "serde")]
+    {
+        let json = serde_json::to_string(&kit).unwrap();
+        assert_eq!(serde_json::from_str::<RecoveryKit>(&json).unwrap(), kit);
+    }
+}
+
+#[test]
+fn recovery_kit_authenticates_instructions_and_recipient() {
+    use schnorr_fun::{
+        fun::{g, G},
+        share_backup::recovery_kit::{RecoveryKit, RecoveryKitError},
+    };
+    let secret_poly = vec![s!(1), s!(2)];
+    let polynomial: Vec<_> = poly::scalar::to_point_poly(&secret_poly);
+    let share_index = s!(1).public();
+    let secret_share = poly::scalar::eval(&secret_poly, share_index);
+    let backup = ShareBackup::new::<sha2::Sha256>(&polynomial, secret_share, share_index);
+    let secret_key = Scalar::random(&mut rand::thread_rng());
+    let mut kit = RecoveryKit::new("Combine any two shares.");
+    kit.seal::<sha2::Sha256>(
+        "alice",
+        g!(secret_key * G).normalize(),
+        &backup,
+        &mut rand::thread_rng(),
+    );
+    assert!(kit.open::<sha2::Sha256>(&secret_key).is_ok());
+
+    let mut tampered = kit.clone();
+    tampered.instructions = "Send the shares to this address.".into();
+    assert!(matches!(
+        tampered.open::<sha2::Sha256>(&secret_key),
+        Err(RecoveryKitError::InvalidMac)
+    ));
+
+    let other_key = Scalar::random(&mut rand::thread_rng());
+    let mut tampered = kit.clone();
+    tampered.envelopes[0].recipient = g!(other_key * G).normalize();
+    assert!(matches!(
+        tampered.open::<sha2::Sha256>(&other_key),
+        Err(RecoveryKitError::InvalidMac)
+    ));
+}
+
+#[test]
+fn debug_redacts_secret_share() {
+    let secret_poly = vec![s!(6), s!(1), s!(9)];
+    let polynomial: Vec<_> = poly::scalar::to_point_poly(&secret_poly);
+    let share_index = s!(12).public();
+    let secret_share = poly::scalar::eval(&secret_poly, share_index);
+    let share_backup = ShareBackup::new::<sha2::Sha256>(&polynomial, secret_share, share_index);
+
+    let debug = format!("{:?}", share_backup);
+    assert!(debug.contains("threshold: 3"));
+    assert!(!debug.contains(&format!("{}", secret_share)));
+    assert!(!format!("{:#?}", share_backup).contains(&format!("{}", secret_share)));
+}
+
+#[test]
+fn mnemonic_round_trip() {
+    use schnorr_fun::share_backup::mnemonic::MnemonicDecodeError;
+    let secret_poly = vec![s!(6), s!(1), s!(9)];
+    let polynomial: Vec<_> = poly::scalar::to_point_poly(&secret_poly);
+    let small_index = s!(12).public();
+    let long_index = Scalar::<Secret>::from_str(
+        "34f7ce653cfa8454b3463726a599ef2925736442d2d06455974d6feae9450d90",
+    )
+    .unwrap()
+    .public();
+
+    for (share_index, n_words) in [(small_index, 28), (long_index, 51)] {
+        let secret_share = poly::scalar::eval(&secret_poly, share_index);
+        let share_backup = ShareBackup::new::<sha2::Sha256>(&polynomial, secret_share, share_index);
+        let words = share_backup.to_mnemonic::<sha2::Sha256>();
+        assert_eq!(words.split(' ').count(), n_words);
+
+        let decoded = ShareBackup::from_mnemonic::<sha2::Sha256>(&words).unwrap();
+        assert_eq!(decoded.threshold, 3);
+        assert_eq!(decoded.identifier, share_backup.identifier);
+        assert_eq!(decoded.secret_share, secret_share);
+        assert_eq!(decoded.share_index, share_index);
+
+        // the first four letters are enough
+        let abbreviated = words
+            .split(' ')
+            .map(|word| &word[..word.len().min(4)])
+            .collect::<Vec<_>>()
+            .join("  ");
+        let decoded =
+            ShareBackup::from_mnemonic::<sha2::Sha256>(&abbreviated.to_uppercase()).unwrap();
+        assert_eq!(decoded.secret_share, secret_share);
+
+        let mut wrong_word = words.split(' ').collect::<Vec<_>>();
+        wrong_word[10] = if wrong_word[10] == "zoo" {
+            "zero"
+        } else {
+            "zoo"
+        };
+        assert_eq!(
+            ShareBackup::from_mnemonic::<sha2::Sha256>(&wrong_word.join(" ")).unwrap_err(),
+            MnemonicDecodeError::InvalidChecksum
+        );
+    }
+
+    assert_eq!(
+        ShareBackup::from_mnemonic::<sha2::Sha256>("abandon ability bitcoin").unwrap_err(),
+        MnemonicDecodeError::UnknownWord(2)
+    );
+    assert_eq!(
+        ShareBackup::from_mnemonic::<sha2::Sha256>("abandon ability able").unwrap_err(),
+        MnemonicDecodeError::WrongNumberOfWords(3)
+    );
+}
+
+#[test]
+fn correct_transcription_errors() {
+    use schnorr_fun::share_backup::{
+        correction::{Correction, CorrectionError},
+        decode_backup_with_correction,
+    };
+    let secret_poly = vec![s!(6), s!(1), s!(9)];
+    let polynomial: Vec<_> = poly::scalar::to_point_poly(&secret_poly);
+    let long_index = Scalar::<Secret>::from_str(
+        "34f7ce653cfa8454b3463726a599ef2925736442d2d06455974d6feae9450d90",
+    )
+    .unwrap()
+    .public();
+
+    for share_index in [s!(12).public(), long_index] {
+        let secret_share = poly::scalar::eval(&secret_poly, share_index);
+        let share_backup = ShareBackup::new::<sha2::Sha256>(&polynomial, secret_share, share_index);
+        let encoded = share_backup.to_string();
+        // only backups of up to 89 characters have two errors corrected
+        let corrects_two = encoded.len() <= 89;
+        assert_eq!(corrects_two, share_index == s!(12).public());
+
+        let corrected = decode_backup_with_correction(&encoded.to_uppercase()).unwrap();
+        assert!(corrected.corrections.is_empty());
+        assert_eq!(corrected.corrected, encoded);
+
+        let swap = |c: char| if c == 'x' { 'y' } else { 'x' };
+        for positions in [vec![6], vec![30], vec![encoded.len() - 1], vec![7, 60]] {
+            let mut mangled = encoded.chars().collect::<Vec<_>>();
+            for position in &positions {
+                mangled[*position] = swap(mangled[*position]);
+            }
+            let mangled = mangled.into_iter().collect::<String>();
+            assert!(ShareBackup::from_str(&mangled).is_err());
+            if positions.len() > 1 && !corrects_two {
+                assert!(matches!(
+                    decode_backup_with_correction(&mangled),
+                    Err(CorrectionError::TooManyErrors)
+                ));
+                continue;
+            }
+            let corrected = decode_backup_with_correction(&mangled).unwrap();
+            assert_eq!(corrected.corrected, encoded);
+            assert_eq!(corrected.backup.secret_share, secret_share);
+            assert_eq!(corrected.backup.share_index, share_index);
+            assert_eq!(
+                corrected.corrections,
+                positions
+                    .iter()
+                    .map(|position| Correction {
+                        position: *position,
+                        found: mangled.chars().nth(*position).unwrap(),
+                        replacement: encoded.chars().nth(*position).unwrap(),
+                    })
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        // characters that aren't in the alphabet are known to be wrong
+        let mut mangled = encoded.clone();
+        mangled.replace_range(20..21, "b");
+        mangled.replace_range(40..41, "o");
+        if corrects_two {
+            let corrected = decode_backup_with_correction(&mangled).unwrap();
+            assert_eq!(corrected.corrected, encoded);
+            assert_eq!(
+                corrected
+                    .corrections
+                    .iter()
+                    .map(|correction| correction.position)
+                    .collect::<Vec<_>>(),
+                vec![20, 40]
+            );
+        } else {
+            assert!(matches!(
+                decode_backup_with_correction(&mangled),
+                Err(CorrectionError::TooManyErrors)
+            ));
+            mangled.replace_range(40..41, &encoded[40..41]);
+            let corrected = decode_backup_with_correction(&mangled).unwrap();
+            assert_eq!(corrected.corrected, encoded);
+        }
+
+        let mut mangled = encoded.clone();
+        for position in [10, 11, 12] {
+            mangled.replace_range(position..position + 1, "i");
+        }
+        assert!(matches!(
+            decode_backup_with_correction(&mangled),
+            Err(CorrectionError::TooManyErrors)
+        ));
+    }
+
+    assert!(matches!(
+        decode_backup_with_correction("fr0st1qqqq"),
+        Err(CorrectionError::Decode(
+            FrostBackupDecodeError::InvalidHumanReadablePrefix
+        ))
+    ));
+}
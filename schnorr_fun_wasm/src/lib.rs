@@ -0,0 +1,620 @@
+//! [wasm-bindgen] bindings for the high level APIs of [`schnorr_fun`].
+//!
+//! This wraps [`KeyPair`], BIP340 signing and verification, [MuSig2] and [FROST] signing sessions
+//! in types that can be used from JavaScript so that wallets on different platforms share the
+//! behaviour of a single implementation.
+//!
+//! Points, scalars and signatures are passed as `Uint8Array`s using the same encodings as the rust
+//! API:
+//!
+//! - secret keys, secret shares, partial signatures: 32 bytes
+//! - BIP340 (x-only) public keys: 32 bytes
+//! - MuSig public keys: 33 bytes (compressed)
+//! - public nonces: 66 bytes
+//! - signatures: 64 bytes
+//!
+//! Lists of these are passed as the concatenation of their encodings. FROST party indices are
+//! small non-zero integers.
+//!
+//! Secret nonces are consumed when they are used to sign so the same JavaScript object can't be
+//! used twice.
+//!
+//! Build with `wasm-pack build schnorr_fun_wasm`.
+//!
+//! [wasm-bindgen]: https://rustwasm.github.io/docs/wasm-bindgen/
+//! [MuSig2]: schnorr_fun::musig
+//! [FROST]: schnorr_fun::frost
+#![allow(non_snake_case)]
+#![warn(missing_docs)]
+
+use core::num::NonZeroU32;
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use schnorr_fun::{
+    binonce::{self, NonceKeyPair},
+    frost::{self, EncodedFrostKey, PartyIndex},
+    fun::{self, marker::*, nonce, Point, Scalar},
+    musig, Message, Signature,
+};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use wasm_bindgen::prelude::*;
+
+type NonceGen = nonce::Deterministic<Sha256>;
+
+fn array<const N: usize>(bytes: &[u8], what: &str) -> Result<[u8; N], JsError> {
+    bytes
+        .try_into()
+        .map_err(|_| JsError::new(&format!("{what} must be {N} bytes")))
+}
+
+fn chunks<const N: usize, T>(
+    bytes: &[u8],
+    what: &str,
+    decode: impl Fn([u8; N]) -> Option<T>,
+) -> Result<Vec<T>, JsError> {
+    if bytes.len() % N != 0 {
+        return Err(JsError::new(&format!(
+            "{what} must be a multiple of {N} bytes"
+        )));
+    }
+    bytes
+        .chunks(N)
+        .map(|chunk| {
+            decode(chunk.try_into().unwrap())
+                .ok_or_else(|| JsError::new(&format!("invalid {what}")))
+        })
+        .collect()
+}
+
+fn secret_key(bytes: &[u8]) -> Result<Scalar, JsError> {
+    Scalar::from_bytes(array(bytes, "secret key")?)
+        .ok_or_else(|| JsError::new("invalid secret key"))
+}
+
+fn party_index(index: u32) -> Result<PartyIndex, JsError> {
+    NonZeroU32::new(index)
+        .map(|index| Scalar::from_non_zero_u32(index).public())
+        .ok_or_else(|| JsError::new("party index can't be zero"))
+}
+
+/// Pairs each signer's index with its nonce. An index can't appear twice since the session would
+/// silently keep only one of its nonces.
+fn signer_nonces(
+    indices: Vec<u32>,
+    nonces: Vec<binonce::Nonce>,
+) -> Result<BTreeMap<PartyIndex, binonce::Nonce>, &'static str> {
+    if nonces.len() != indices.len() {
+        return Err("there must be a nonce for every index");
+    }
+    let mut signer_nonces = BTreeMap::new();
+    for (index, nonce) in indices.into_iter().zip(nonces) {
+        let index = NonZeroU32::new(index).ok_or("party index can't be zero")?;
+        if signer_nonces
+            .insert(Scalar::from_non_zero_u32(index).public(), nonce)
+            .is_some()
+        {
+            return Err("party indices must be distinct");
+        }
+    }
+    Ok(signer_nonces)
+}
+
+fn signature(bytes: &[u8]) -> Result<Signature, JsError> {
+    Signature::from_bytes(array(bytes, "signature")?)
+        .ok_or_else(|| JsError::new("invalid signature"))
+}
+
+fn partial_signature(bytes: [u8; 32]) -> Option<Scalar<Public, Zero>> {
+    Scalar::from_bytes(bytes)
+}
+
+/// A BIP340 key pair.
+#[wasm_bindgen]
+pub struct KeyPair {
+    inner: fun::KeyPair<EvenY>,
+}
+
+#[wasm_bindgen]
+impl KeyPair {
+    /// Create a key pair from a 32-byte secret key.
+    #[wasm_bindgen(constructor)]
+    pub fn new(secret_key: &[u8]) -> Result<KeyPair, JsError> {
+        Ok(KeyPair {
+            inner: fun::KeyPair::<EvenY>::new(self::secret_key(secret_key)?),
+        })
+    }
+
+    /// The 32-byte x-only public key.
+    #[wasm_bindgen(js_name = publicKey)]
+    pub fn public_key(&self) -> Vec<u8> {
+        self.inner.public_key().to_xonly_bytes().to_vec()
+    }
+}
+
+/// BIP340 signing and verification using deterministic nonces.
+#[wasm_bindgen]
+pub struct Schnorr {
+    inner: schnorr_fun::Schnorr<Sha256, NonceGen>,
+}
+
+#[wasm_bindgen]
+impl Schnorr {
+    /// Create a new Schnorr context.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Schnorr {
+        Schnorr {
+            inner: schnorr_fun::Schnorr::default(),
+        }
+    }
+
+    /// Sign `message` (as-is, without any tagging).
+    pub fn sign(&self, keypair: &KeyPair, message: &[u8]) -> Vec<u8> {
+        self.inner
+            .sign(&keypair.inner, Message::<Public>::raw(message))
+            .to_bytes()
+            .to_vec()
+    }
+
+    /// Verify a signature on `message` under a 32-byte x-only public key.
+    pub fn verify(
+        &self,
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, JsError> {
+        let public_key = Point::<EvenY>::from_xonly_bytes(array(public_key, "public key")?)
+            .ok_or_else(|| JsError::new("invalid public key"))?;
+        Ok(self.inner.verify(
+            &public_key,
+            Message::<Public>::raw(message),
+            &self::signature(signature)?,
+        ))
+    }
+}
+
+impl Default for Schnorr {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A secret nonce for a MuSig or FROST signing session.
+///
+/// It is consumed when you sign with it.
+#[wasm_bindgen]
+pub struct SecretNonce {
+    inner: NonceKeyPair,
+}
+
+#[wasm_bindgen]
+impl SecretNonce {
+    /// The 66-byte public nonce to share with the other signers.
+    #[wasm_bindgen(js_name = publicNonce)]
+    pub fn public_nonce(&self) -> Vec<u8> {
+        self.inner.public().to_bytes().to_vec()
+    }
+}
+
+/// A MuSig2 aggregate key.
+#[wasm_bindgen]
+pub struct MuSigAggKey {
+    inner: musig::AggKey<EvenY>,
+}
+
+#[wasm_bindgen]
+impl MuSigAggKey {
+    /// The 32-byte x-only aggregate public key.
+    #[wasm_bindgen(js_name = publicKey)]
+    pub fn public_key(&self) -> Vec<u8> {
+        self.inner.agg_public_key().to_xonly_bytes().to_vec()
+    }
+}
+
+/// A MuSig2 signing session.
+#[wasm_bindgen]
+pub struct MuSigSignSession {
+    inner: musig::SignSession,
+}
+
+/// The MuSig2 multisignature scheme.
+#[wasm_bindgen]
+pub struct MuSig {
+    inner: musig::MuSig<Sha256, NonceGen>,
+}
+
+#[wasm_bindgen]
+impl MuSig {
+    /// Create a new MuSig context.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> MuSig {
+        MuSig {
+            inner: musig::new_with_deterministic_nonces(),
+        }
+    }
+
+    /// Aggregate the concatenated 33-byte public keys of the signers.
+    #[wasm_bindgen(js_name = aggregateKeys)]
+    pub fn aggregate_keys(&self, public_keys: &[u8]) -> Result<MuSigAggKey, JsError> {
+        let keys = chunks(public_keys, "public keys", Point::<Normal>::from_bytes)?;
+        if keys.is_empty() {
+            return Err(JsError::new("there must be at least one public key"));
+        }
+        Ok(MuSigAggKey {
+            inner: self.inner.new_agg_key(keys).into_xonly_key(),
+        })
+    }
+
+    /// Generate a nonce.
+    ///
+    /// ⚠ `session_id` must be unique for every signing attempt. 32 bytes from
+    /// `crypto.getRandomValues` is the easiest way to ensure this.
+    #[wasm_bindgen(js_name = genNonce)]
+    pub fn gen_nonce(
+        &self,
+        agg_key: &MuSigAggKey,
+        secret_key: &[u8],
+        session_id: &[u8],
+    ) -> Result<SecretNonce, JsError> {
+        let mut nonce_rng: ChaCha20Rng =
+            self.inner
+                .seed_nonce_rng(&agg_key.inner, &self::secret_key(secret_key)?, session_id);
+        Ok(SecretNonce {
+            inner: self.inner.gen_nonce(&mut nonce_rng),
+        })
+    }
+
+    /// Start a signing session with the concatenated public nonces of the signers (in key order).
+    #[wasm_bindgen(js_name = startSignSession)]
+    pub fn start_sign_session(
+        &self,
+        agg_key: &MuSigAggKey,
+        public_nonces: &[u8],
+        message: &[u8],
+    ) -> Result<MuSigSignSession, JsError> {
+        let nonces = chunks(public_nonces, "public nonces", binonce::Nonce::from_bytes)?;
+        if nonces.len() != agg_key.inner.keys().count() {
+            return Err(JsError::new("there must be a nonce for every key"));
+        }
+        Ok(MuSigSignSession {
+            inner: self.inner.start_sign_session(
+                &agg_key.inner,
+                nonces,
+                Message::<Public>::raw(message),
+            ),
+        })
+    }
+
+    /// Create a partial signature. `my_index` is the position of our key in the aggregate key.
+    pub fn sign(
+        &self,
+        agg_key: &MuSigAggKey,
+        session: &MuSigSignSession,
+        my_index: usize,
+        secret_key: &[u8],
+        secret_nonce: SecretNonce,
+    ) -> Result<Vec<u8>, JsError> {
+        let keypair = self.inner.new_keypair(self::secret_key(secret_key)?);
+        if agg_key.inner.keys().nth(my_index) != Some(keypair.public_key()) {
+            return Err(JsError::new("secret key doesn't match the key at my_index"));
+        }
+        Ok(self
+            .inner
+            .sign(
+                &agg_key.inner,
+                &session.inner,
+                my_index,
+                &keypair,
+                secret_nonce.inner,
+            )
+            .to_bytes()
+            .to_vec())
+    }
+
+    /// Verify the partial signature of the signer at `index`.
+    #[wasm_bindgen(js_name = verifyPartialSignature)]
+    pub fn verify_partial_signature(
+        &self,
+        agg_key: &MuSigAggKey,
+        session: &MuSigSignSession,
+        index: usize,
+        partial_signature: &[u8],
+    ) -> Result<bool, JsError> {
+        if index >= agg_key.inner.keys().count() {
+            return Err(JsError::new("index out of range"));
+        }
+        let partial_signature =
+            self::partial_signature(array(partial_signature, "partial signature")?)
+                .ok_or_else(|| JsError::new("invalid partial signature"))?;
+        Ok(self.inner.verify_partial_signature(
+            &agg_key.inner,
+            &session.inner,
+            index,
+            partial_signature,
+        ))
+    }
+
+    /// Combine the concatenated partial signatures into a BIP340 signature.
+    #[wasm_bindgen(js_name = combinePartialSignatures)]
+    pub fn combine_partial_signatures(
+        &self,
+        agg_key: &MuSigAggKey,
+        session: &MuSigSignSession,
+        partial_signatures: &[u8],
+    ) -> Result<Vec<u8>, JsError> {
+        let partial_signatures =
+            chunks(partial_signatures, "partial signatures", partial_signature)?;
+        Ok(self
+            .inner
+            .combine_partial_signatures(&agg_key.inner, &session.inner, partial_signatures)
+            .to_bytes()
+            .to_vec())
+    }
+}
+
+impl Default for MuSig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A FROST key.
+#[wasm_bindgen]
+pub struct FrostKey {
+    inner: frost::FrostKey<Normal>,
+}
+
+#[wasm_bindgen]
+impl FrostKey {
+    /// The 32-byte x-only public key.
+    #[wasm_bindgen(js_name = publicKey)]
+    pub fn public_key(&self) -> Vec<u8> {
+        self.inner
+            .clone()
+            .into_xonly_key()
+            .public_key()
+            .to_xonly_bytes()
+            .to_vec()
+    }
+
+    /// The threshold number of signers.
+    pub fn threshold(&self) -> usize {
+        self.inner.threshold()
+    }
+
+    /// Encode the key as JSON.
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.inner.encode()).expect("encoding can't fail")
+    }
+
+    /// Decode a key from [`to_json`](Self::to_json).
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> Result<FrostKey, JsError> {
+        let encoded: EncodedFrostKey = serde_json::from_str(json)?;
+        Ok(FrostKey {
+            inner: encoded.into_frost_key(),
+        })
+    }
+}
+
+/// The output of [`Frost::simulate_keygen`].
+#[wasm_bindgen]
+pub struct FrostKeyGenOutput {
+    frost_key: frost::FrostKey<Normal>,
+    secret_shares: BTreeMap<PartyIndex, Scalar>,
+}
+
+#[wasm_bindgen]
+impl FrostKeyGenOutput {
+    /// The generated key.
+    #[wasm_bindgen(js_name = frostKey)]
+    pub fn frost_key(&self) -> FrostKey {
+        FrostKey {
+            inner: self.frost_key.clone(),
+        }
+    }
+
+    /// The 32-byte secret share of the party at `index`.
+    #[wasm_bindgen(js_name = secretShare)]
+    pub fn secret_share(&self, index: u32) -> Result<Vec<u8>, JsError> {
+        self.secret_shares
+            .get(&party_index(index)?)
+            .map(|secret_share| secret_share.to_bytes().to_vec())
+            .ok_or_else(|| JsError::new("no party at that index"))
+    }
+}
+
+/// A FROST signing session.
+#[wasm_bindgen]
+pub struct FrostSignSession {
+    inner: frost::SignSession,
+}
+
+/// The FROST threshold signature scheme.
+#[wasm_bindgen]
+pub struct Frost {
+    inner: frost::Frost<Sha256, NonceGen>,
+}
+
+#[wasm_bindgen]
+impl Frost {
+    /// Create a new FROST context.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Frost {
+        Frost {
+            inner: frost::new_with_deterministic_nonces(),
+        }
+    }
+
+    /// Generate a key and the secret shares of parties `1..=n_parties` on this device.
+    ///
+    /// `seed` must be 32 bytes from a secure source of randomness (e.g.
+    /// `crypto.getRandomValues`).
+    #[wasm_bindgen(js_name = simulateKeygen)]
+    pub fn simulate_keygen(
+        &self,
+        threshold: usize,
+        n_parties: usize,
+        seed: &[u8],
+    ) -> Result<FrostKeyGenOutput, JsError> {
+        if threshold == 0 || threshold > n_parties {
+            return Err(JsError::new("threshold must be between 1 and n_parties"));
+        }
+        let mut rng = ChaCha20Rng::from_seed(array(seed, "seed")?);
+        let (frost_key, secret_shares) = self.inner.simulate_keygen(threshold, n_parties, &mut rng);
+        Ok(FrostKeyGenOutput {
+            frost_key,
+            secret_shares,
+        })
+    }
+
+    /// Generate a nonce.
+    ///
+    /// ⚠ `session_id` must be unique for every signing attempt. 32 bytes from
+    /// `crypto.getRandomValues` is the easiest way to ensure this.
+    #[wasm_bindgen(js_name = genNonce)]
+    pub fn gen_nonce(
+        &self,
+        frost_key: &FrostKey,
+        secret_share: &[u8],
+        session_id: &[u8],
+    ) -> Result<SecretNonce, JsError> {
+        let mut nonce_rng: ChaCha20Rng = self.inner.seed_nonce_rng(
+            &frost_key.inner,
+            &self::secret_key(secret_share)?,
+            session_id,
+        );
+        Ok(SecretNonce {
+            inner: self.inner.gen_nonce(&mut nonce_rng),
+        })
+    }
+
+    /// Start a signing session between the parties at `indices` with their concatenated public
+    /// nonces (in the same order).
+    #[wasm_bindgen(js_name = startSignSession)]
+    pub fn start_sign_session(
+        &self,
+        frost_key: &FrostKey,
+        indices: Vec<u32>,
+        public_nonces: &[u8],
+        message: &[u8],
+    ) -> Result<FrostSignSession, JsError> {
+        let nonces = chunks(public_nonces, "public nonces", binonce::Nonce::from_bytes)?;
+        let nonces = signer_nonces(indices, nonces).map_err(JsError::new)?;
+        if nonces.len() < frost_key.inner.threshold() {
+            return Err(JsError::new("fewer signers than the threshold"));
+        }
+        Ok(FrostSignSession {
+            inner: self.inner.start_sign_session(
+                &frost_key.inner.clone().into_xonly_key(),
+                nonces,
+                Message::<Public>::raw(message),
+            ),
+        })
+    }
+
+    /// Create a signature share as the party at `my_index`.
+    pub fn sign(
+        &self,
+        frost_key: &FrostKey,
+        session: &FrostSignSession,
+        my_index: u32,
+        secret_share: &[u8],
+        secret_nonce: SecretNonce,
+    ) -> Result<Vec<u8>, JsError> {
+        let my_index = party_index(my_index)?;
+        if !session.inner.participants().any(|index| index == my_index) {
+            return Err(JsError::new("my_index is not part of the session"));
+        }
+        Ok(self
+            .inner
+            .sign(
+                &frost_key.inner.clone().into_xonly_key(),
+                &session.inner,
+                my_index,
+                &self::secret_key(secret_share)?,
+                secret_nonce.inner,
+            )
+            .to_bytes()
+            .to_vec())
+    }
+
+    /// Verify the signature share of the party at `index`.
+    #[wasm_bindgen(js_name = verifySignatureShare)]
+    pub fn verify_signature_share(
+        &self,
+        frost_key: &FrostKey,
+        session: &FrostSignSession,
+        index: u32,
+        signature_share: &[u8],
+    ) -> Result<bool, JsError> {
+        let index = party_index(index)?;
+        if !session.inner.participants().any(|i| i == index) {
+            return Err(JsError::new("index is not part of the session"));
+        }
+        let signature_share = self::partial_signature(array(signature_share, "signature share")?)
+            .ok_or_else(|| JsError::new("invalid signature share"))?;
+        Ok(self.inner.verify_signature_share(
+            &frost_key.inner.clone().into_xonly_key(),
+            &session.inner,
+            index,
+            signature_share,
+        ))
+    }
+
+    /// Combine the concatenated signature shares into a BIP340 signature.
+    #[wasm_bindgen(js_name = combineSignatureShares)]
+    pub fn combine_signature_shares(
+        &self,
+        frost_key: &FrostKey,
+        session: &FrostSignSession,
+        signature_shares: &[u8],
+    ) -> Result<Vec<u8>, JsError> {
+        let signature_shares = chunks(signature_shares, "signature shares", partial_signature)?;
+        Ok(self
+            .inner
+            .combine_signature_shares(
+                &frost_key.inner.clone().into_xonly_key(),
+                &session.inner,
+                signature_shares,
+            )
+            .to_bytes()
+            .to_vec())
+    }
+}
+
+impl Default for Frost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn signer_nonces_rejects_duplicate_indices() {
+        let mut rng = ChaCha20Rng::from_seed([42; 32]);
+        let nonces = (0..3)
+            .map(|_| NonceKeyPair::random(&mut rng).public())
+            .collect::<Vec<_>>();
+        let signer_nonces = signer_nonces(vec![1, 3, 2], nonces.clone()).unwrap();
+        assert_eq!(
+            signer_nonces.values().copied().collect::<Vec<_>>(),
+            [nonces[0], nonces[2], nonces[1]]
+        );
+        assert_eq!(
+            super::signer_nonces(vec![1, 3, 1], nonces.clone()),
+            Err("party indices must be distinct")
+        );
+        assert_eq!(
+            super::signer_nonces(vec![1, 0, 2], nonces.clone()),
+            Err("party index can't be zero")
+        );
+        assert_eq!(
+            super::signer_nonces(vec![1, 2], nonces),
+            Err("there must be a nonce for every index")
+        );
+    }
+}
@@ -159,5 +159,23 @@ fn multi_mul(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, scalar_mul_point, multi_mul);
+fn dot_product(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dot_product");
+    for n in [16, 64, 128, 256, 1024, 4096] {
+        let scalars = (0..n)
+            .map(|_| Scalar::random(&mut rand::thread_rng()).public())
+            .collect::<Vec<_>>();
+        let points = (0..n)
+            .map(|_| Point::random(&mut rand::thread_rng()))
+            .collect::<Vec<_>>();
+        group.bench_function(format!("point_scalar_dot_product:{}", n), |b| {
+            b.iter(|| op::point_scalar_dot_product(&scalars, &points))
+        });
+        group.bench_function(format!("point_scalar_dot_product_vartime:{}", n), |b| {
+            b.iter(|| op::point_scalar_dot_product_vartime(&scalars, &points))
+        });
+    }
+}
+
+criterion_group!(benches, scalar_mul_point, multi_mul, dot_product);
 criterion_main!(benches);
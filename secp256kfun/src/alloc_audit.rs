@@ -0,0 +1,144 @@
+//! Counting heap allocations in tests to check which call paths are allocation-free.
+//!
+//! Much of this crate (and `schnorr_fun` and `ecdsa_fun`) works without the `alloc` feature but
+//! with it enabled it can be hard to tell whether a particular call allocates. Before relying on a
+//! call path on a target without a heap (or in an interrupt handler) install
+//! [`CountingAllocator`] as the global allocator of a test binary and wrap the call in
+//! [`count_allocations`]:
+//!
+//! ```
+//! use secp256kfun::{alloc_audit, g, Scalar, G};
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: alloc_audit::CountingAllocator = alloc_audit::CountingAllocator::system();
+//!
+//! let x = Scalar::random(&mut rand::thread_rng());
+//! let (_X, count) = alloc_audit::count_allocations(|| g!(x * G).normalize());
+//! assert_eq!(count.allocations, 0);
+//! let (_bytes, count) = alloc_audit::count_allocations(|| x.to_bytes().to_vec());
+//! assert_eq!(count.allocations, 1);
+//! ```
+//!
+//! Only allocations made by the current thread are counted so tests running in parallel don't
+//! interfere with each other. This is for test builds only: it adds a thread local lookup to every
+//! allocation.
+use core::cell::Cell;
+use std::alloc::{GlobalAlloc, Layout, System};
+
+std::thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+    static COUNT: Cell<AllocationCount> = const {
+        Cell::new(AllocationCount {
+            allocations: 0,
+            reallocations: 0,
+            bytes: 0,
+        })
+    };
+}
+
+/// The allocations made during a call to [`count_allocations`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AllocationCount {
+    /// The number of new allocations.
+    pub allocations: usize,
+    /// The number of times an allocation was resized.
+    pub reallocations: usize,
+    /// The total number of bytes requested by allocations and reallocations.
+    pub bytes: usize,
+}
+
+impl AllocationCount {
+    /// Whether there were no allocations or reallocations.
+    pub fn is_zero(&self) -> bool {
+        self.allocations == 0 && self.reallocations == 0
+    }
+}
+
+/// A global allocator that counts the allocations made inside [`count_allocations`].
+///
+/// See the [module documentation](crate::alloc_audit).
+#[derive(Debug, Default)]
+pub struct CountingAllocator<A = System>(pub A);
+
+impl CountingAllocator {
+    /// Counts allocations made with the [`System`] allocator.
+    pub const fn system() -> Self {
+        Self(System)
+    }
+}
+
+fn record(reallocation: bool, bytes: usize) {
+    // `try_with` because the allocator can be called while the thread locals are being destroyed
+    let _ = COUNTING.try_with(|counting| {
+        if counting.get() {
+            let _ = COUNT.try_with(|count| {
+                let mut new_count = count.get();
+                if reallocation {
+                    new_count.reallocations += 1;
+                } else {
+                    new_count.allocations += 1;
+                }
+                new_count.bytes += bytes;
+                count.set(new_count);
+            });
+        }
+    });
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(false, layout.size());
+        self.0.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record(false, layout.size());
+        self.0.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.0.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(true, new_size);
+        self.0.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Calls `f` and returns its result along with the allocations it made on this thread.
+///
+/// The counts are always zero unless [`CountingAllocator`] is the global allocator. Calls can be
+/// nested in which case the outer call's count includes the inner one's.
+pub fn count_allocations<R>(f: impl FnOnce() -> R) -> (R, AllocationCount) {
+    let was_counting = COUNTING.with(|counting| counting.replace(true));
+    let before = COUNT.with(|count| count.take());
+    let result = f();
+    let during = COUNT.with(|count| count.get());
+    COUNT.with(|count| {
+        count.set(AllocationCount {
+            allocations: before.allocations + during.allocations,
+            reallocations: before.reallocations + during.reallocations,
+            bytes: before.bytes + during.bytes,
+        })
+    });
+    COUNTING.with(|counting| counting.set(was_counting));
+    (result, during)
+}
+
+/// Calls `f` and panics if it allocated.
+///
+/// # Panics
+///
+/// If `f` allocated on this thread (only detected when [`CountingAllocator`] is the global
+/// allocator).
+#[track_caller]
+pub fn assert_allocation_free<R>(f: impl FnOnce() -> R) -> R {
+    let (result, count) = count_allocations(f);
+    assert!(
+        count.is_zero(),
+        "expected no allocations but got {:?}",
+        count
+    );
+    result
+}
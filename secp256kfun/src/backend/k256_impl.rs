@@ -25,7 +25,10 @@ impl BackendScalar for Scalar {
     }
 
     fn from_bytes_mod_order(bytes: [u8; 32]) -> Self {
-        Scalar::from_bytes_reduced(&FieldBytes::from(bytes))
+        let result = Scalar::from_bytes_reduced(&FieldBytes::from(bytes));
+        #[cfg(feature = "shadow_arithmetic")]
+        super::shadow::check_reduce(bytes, &result);
+        result
     }
 
     fn from_bytes(bytes: [u8; 32]) -> Option<Self> {
@@ -58,12 +61,371 @@ impl BackendPoint for Point {
     }
 
     fn norm_from_coordinates(x: [u8; 32], y: [u8; 32]) -> Option<Point> {
-        let x = Option::from(FieldElement::from_bytes(&FieldBytes::from(x)))?;
-        let y = Option::from(FieldElement::from_bytes(&FieldBytes::from(y)))?;
+        let x: FieldElement = Option::from(FieldElement::from_bytes(&FieldBytes::from(x)))?;
+        let y: FieldElement = Option::from(FieldElement::from_bytes(&FieldBytes::from(y)))?;
+        // AffinePoint::new doesn't check the point is on the curve
+        if y.square().normalize()
+            != ((x * x * x) + crate::vendor::k256::CURVE_EQUATION_B).normalize()
+        {
+            return None;
+        }
         Some(AffinePoint::new(x, y).into())
     }
 }
 
+/// Square root of a field element (the root that is itself a square).
+pub fn field_sqrt(x: [u8; 32]) -> Option<[u8; 32]> {
+    let x = Option::<FieldElement>::from(FieldElement::from_bytes(&FieldBytes::from(x)))?;
+    Option::<FieldElement>::from(x.sqrt()).map(|root| root.to_bytes().into())
+}
+
+/// The right hand side of the curve equation `x³ + 7`.
+pub fn curve_rhs(x: [u8; 32]) -> Option<[u8; 32]> {
+    let x = Option::<FieldElement>::from(FieldElement::from_bytes(&FieldBytes::from(x)))?;
+    let rhs = (x * x * x) + crate::vendor::k256::CURVE_EQUATION_B;
+    Some(rhs.to_bytes().into())
+}
+
+/// Interprets `bytes` as a big-endian integer modulo `p`.
+pub fn field_reduce(bytes: [u8; 32]) -> [u8; 32] {
+    FieldElement::from_bytes_unchecked(&bytes).to_bytes().into()
+}
+
+fn fe_from_u32(int: u32) -> FieldElement {
+    FieldElement::ONE.mul_single(int).normalize()
+}
+
+fn fe_is_square(x: &FieldElement) -> bool {
+    x.sqrt().is_some().into()
+}
+
+/// The constants the Shallue-van de Woestijne map uses: `c = sqrt(-3)` and `(c - 1) / 2`.
+fn svdw_constants() -> (FieldElement, FieldElement) {
+    let c = (-fe_from_u32(3)).sqrt().unwrap().normalize();
+    let half = fe_from_u32(2).invert().unwrap();
+    (c, ((c + -FieldElement::ONE).normalize() * half).normalize())
+}
+
+/// The point `(x, y)` the Shallue-van de Woestijne map sends `t` to.
+fn svdw(t: &FieldElement) -> (FieldElement, FieldElement) {
+    let (c, c_minus_one_half) = svdw_constants();
+    let t = t.normalize();
+    // 1 + b + t² is never zero on secp256k1 since -8 isn't a square
+    let w = (c * t * (t.square() + fe_from_u32(8)).normalize().invert().unwrap()).normalize();
+    let x1 = (c_minus_one_half + -(t * w).normalize()).normalize();
+    let x2 = (-FieldElement::ONE + -x1).normalize();
+    let candidates = [
+        Some(x1),
+        Some(x2),
+        // w is only zero when t is zero and then x1 is on the curve
+        Option::from(w.square().invert())
+            .map(|winv2: FieldElement| (winv2 + FieldElement::ONE).normalize()),
+    ];
+    for x in candidates.into_iter().flatten() {
+        let rhs = (x.square() * x + crate::vendor::k256::CURVE_EQUATION_B).normalize();
+        if let Some(y) = Option::<FieldElement>::from(rhs.sqrt()) {
+            let y = y.normalize();
+            let y = if fe_is_square(&t) {
+                y
+            } else {
+                (-y).normalize()
+            };
+            return (x, y);
+        }
+    }
+    unreachable!("one of the three x-coordinates is always on the curve")
+}
+
+/// Maps the field element `t` (which must be less than `p`) to a curve point with the
+/// Shallue-van de Woestijne map (the map is never zero on secp256k1).
+pub fn svdw_map(t: [u8; 32]) -> ([u8; 32], [u8; 32]) {
+    let (x, y) = svdw(&FieldElement::from_bytes_unchecked(&t));
+    (x.to_bytes().into(), y.to_bytes().into())
+}
+
+/// Every field element the Shallue-van de Woestijne map sends to the point `(x, y)` (there are at
+/// most four).
+pub fn svdw_preimages(x: [u8; 32], y: [u8; 32]) -> [Option<[u8; 32]>; 4] {
+    let (c, c_minus_one_half) = svdw_constants();
+    let x = FieldElement::from_bytes_unchecked(&x).normalize();
+    let y = FieldElement::from_bytes_unchecked(&y).normalize();
+    let y_is_square = fe_is_square(&y);
+    let with_sign = |t: FieldElement| {
+        let t = t.normalize();
+        if fe_is_square(&t) == y_is_square {
+            t
+        } else {
+            (-t).normalize()
+        }
+    };
+    let mut candidates = [None; 4];
+
+    // x = x1 or x = x2 = -1 - x1 where x1 = (c - 1)/2 - c t² / (1 + b + t²)
+    for (i, x1) in [x, (-FieldElement::ONE + -x).normalize()]
+        .into_iter()
+        .enumerate()
+    {
+        let d = (c_minus_one_half + -x1).normalize();
+        let t2 = Option::from((c + -d).normalize().invert())
+            .map(|inv: FieldElement| (d * fe_from_u32(8) * inv).normalize());
+        candidates[i] = t2
+            .and_then(|t2| Option::<FieldElement>::from(t2.sqrt()))
+            .map(with_sign);
+    }
+
+    // x = x3 = 1 + 1/w² where w = c t / (1 + b + t²) so w t² - c t + w (1 + b) = 0
+    let w = Option::from((x + -FieldElement::ONE).normalize().invert())
+        .and_then(|w2: FieldElement| Option::<FieldElement>::from(w2.sqrt()));
+    if let Some(w) = w {
+        let w = w.normalize();
+        let discriminant =
+            (-fe_from_u32(3) + -(w.square() * fe_from_u32(32)).normalize()).normalize();
+        if let Some(root) = Option::<FieldElement>::from(discriminant.sqrt()) {
+            let root = root.normalize();
+            let inv_2w = (w * fe_from_u32(2)).normalize().invert().unwrap();
+            // negating w negates both roots and only one of t and -t has the right sign
+            candidates[2] = Some(with_sign((c + root).normalize() * inv_2w));
+            candidates[3] = Some(with_sign((c + -root).normalize() * inv_2w));
+        }
+    }
+
+    let mut preimages = [None; 4];
+    let mut n = 0;
+    for t in candidates.into_iter().flatten() {
+        let t_bytes: [u8; 32] = t.to_bytes().into();
+        // the forward map only takes a branch when the earlier ones aren't on the curve
+        if svdw(&t) == (x, y) && !preimages[..n].contains(&Some(t_bytes)) {
+            preimages[n] = Some(t_bytes);
+            n += 1;
+        }
+    }
+    preimages
+}
+
+fn fe_sub(a: &FieldElement, b: &FieldElement) -> FieldElement {
+    (a.normalize() + -b.normalize()).normalize()
+}
+
+fn fe_div(a: &FieldElement, b: &FieldElement) -> FieldElement {
+    (*a * b.invert().unwrap()).normalize()
+}
+
+/// `x³ + 7` for field elements.
+fn fe_curve_rhs(x: &FieldElement) -> FieldElement {
+    (x.square() * x + crate::vendor::k256::CURVE_EQUATION_B).normalize()
+}
+
+/// The x-coordinate the [BIP324] ElligatorSwift decoding `XSwiftEC` sends `(u, t)` to. `u` and `t`
+/// must be less than `p`.
+///
+/// [BIP324]: https://bips.xyz/324
+pub fn xswiftec(u: [u8; 32], t: [u8; 32]) -> [u8; 32] {
+    let mut u = FieldElement::from_bytes_unchecked(&u).normalize();
+    let mut t = FieldElement::from_bytes_unchecked(&t).normalize();
+    // remap the inputs where the map isn't defined
+    if bool::from(u.is_zero()) {
+        u = FieldElement::ONE;
+    }
+    if bool::from(t.is_zero()) {
+        t = FieldElement::ONE;
+    }
+    let g_u = fe_curve_rhs(&u);
+    if bool::from((g_u + t.square()).normalize().is_zero()) {
+        t = t.double().normalize();
+    }
+    let (sqrt_minus_3, _) = svdw_constants();
+    let big_x = fe_div(&fe_sub(&g_u, &t.square()), &t.double());
+    let big_y = fe_div(&(big_x + t).normalize(), &(sqrt_minus_3 * u).normalize());
+    let half_u = fe_div(&u, &fe_from_u32(2));
+    let x_over_2y = fe_div(&big_x, &big_y.double());
+    let candidates = [
+        (u + (big_y.square() * fe_from_u32(4))).normalize(),
+        fe_sub(&(-x_over_2y).normalize(), &half_u),
+        fe_sub(&x_over_2y, &half_u),
+    ];
+    let x = candidates
+        .into_iter()
+        .find(|x| fe_is_square(&fe_curve_rhs(x)))
+        .expect("one of the three x-coordinates is always on the curve");
+    x.to_bytes().into()
+}
+
+/// One of the (up to eight) `t` where [`xswiftec`] sends `(u, t)` to `x`, selected by `case` (`0..8`).
+pub fn xswiftec_inv(x: [u8; 32], u: [u8; 32], case: u8) -> Option<[u8; 32]> {
+    let x = FieldElement::from_bytes_unchecked(&x).normalize();
+    let u = FieldElement::from_bytes_unchecked(&u).normalize();
+    if bool::from(u.is_zero()) {
+        return None;
+    }
+    let g_u = fe_curve_rhs(&u);
+    let (s, v) = if case & 2 == 0 {
+        // x = x1 or x2 but x3 would take precedence if the other one of them is on the curve
+        if fe_is_square(&fe_curve_rhs(&fe_sub(&(-u).normalize(), &x))) {
+            return None;
+        }
+        let denominator = (u.square() + u * x + x.square()).normalize();
+        (fe_div(&(-g_u).normalize(), &denominator), x)
+    } else {
+        let s = fe_sub(&x, &u);
+        let discriminant = (-(s
+            * (g_u * fe_from_u32(4) + s * u.square() * fe_from_u32(3)).normalize()))
+        .normalize();
+        let r = Option::<FieldElement>::from(discriminant.sqrt())?.normalize();
+        if (case & 1 == 1 && bool::from(r.is_zero())) || bool::from(s.is_zero()) {
+            return None;
+        }
+        (s, fe_div(&fe_sub(&fe_div(&r, &s), &u), &fe_from_u32(2)))
+    };
+    let w = Option::<FieldElement>::from(s.sqrt())?.normalize();
+    let (sqrt_minus_3, _) = svdw_constants();
+    let half = fe_from_u32(2).invert().unwrap();
+    let t = match case & 5 {
+        0 => fe_sub(
+            &((fe_sub(&sqrt_minus_3, &FieldElement::ONE) * half * u).normalize()),
+            &v,
+        ),
+        1 => ((sqrt_minus_3 + FieldElement::ONE) * half * u + v).normalize(),
+        4 => (fe_sub(&FieldElement::ONE, &sqrt_minus_3) * half * u + v).normalize(),
+        _ => fe_sub(
+            &((-(sqrt_minus_3 + FieldElement::ONE).normalize()) * half * u).normalize(),
+            &v,
+        ),
+    };
+    Some((w * t).normalize().to_bytes().into())
+}
+
+/// Returns whether the field element is odd.
+pub fn field_is_odd(x: [u8; 32]) -> bool {
+    x[31] & 1 == 1
+}
+
+/// Negates the field element `x` which must be less than `p`.
+pub fn field_negate(x: [u8; 32]) -> [u8; 32] {
+    (-FieldElement::from_bytes_unchecked(&x).normalize())
+        .normalize()
+        .to_bytes()
+        .into()
+}
+
+/// Parses a field element from 64 hex digits at compile time.
+const fn fe_from_hex(hex: &str) -> FieldElement {
+    let hex = hex.as_bytes();
+    let mut bytes = [0u8; 32];
+    let mut i = 0;
+    while i < 64 {
+        let digit = match hex[i] {
+            b'0'..=b'9' => hex[i] - b'0',
+            b'a'..=b'f' => hex[i] - b'a' + 10,
+            _ => panic!("invalid hex digit"),
+        };
+        bytes[i / 2] |= digit << (4 * (1 - i % 2));
+        i += 1;
+    }
+    FieldElement::from_bytes_unchecked(&bytes)
+}
+
+/// `A'` of the curve `y² = x³ + A'x + B'` that is 3-isogenous to secp256k1 ([RFC 9380] E.1).
+///
+/// [RFC 9380]: https://www.rfc-editor.org/rfc/rfc9380.html
+const SSWU_A: FieldElement =
+    fe_from_hex("3f8731abdd661adca08a5558f0f5d272e953d363cb6f0e5d405447c01a444533");
+const SSWU_B: u32 = 1771;
+/// The coefficients of the 3-isogeny's `x_num`, `x_den`, `y_num` and `y_den` from lowest degree
+/// to highest (the denominators are monic so their leading 1 is left out).
+const ISO_X_NUM: [FieldElement; 4] = [
+    fe_from_hex("8e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38daaaaa8c7"),
+    fe_from_hex("07d3d4c80bc321d5b9f315cea7fd44c5d595d2fc0bf63b92dfff1044f17c6581"),
+    fe_from_hex("534c328d23f234e6e2a413deca25caece4506144037c40314ecbd0b53d9dd262"),
+    fe_from_hex("8e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38daaaaa88c"),
+];
+const ISO_X_DEN: [FieldElement; 2] = [
+    fe_from_hex("d35771193d94918a9ca34ccbb7b640dd86cd409542f8487d9fe6b745781eb49b"),
+    fe_from_hex("edadc6f64383dc1df7c4b2d51b54225406d36b641f5e41bbc52a56612a8c6d14"),
+];
+const ISO_Y_NUM: [FieldElement; 4] = [
+    fe_from_hex("4bda12f684bda12f684bda12f684bda12f684bda12f684bda12f684b8e38e23c"),
+    fe_from_hex("c75e0c32d5cb7c0fa9d0a54b12a0a6d5647ab046d686da6fdffc90fc201d71a3"),
+    fe_from_hex("29a6194691f91a73715209ef6512e576722830a201be2018a765e85a9ecee931"),
+    fe_from_hex("2f684bda12f684bda12f684bda12f684bda12f684bda12f684bda12f38e38d84"),
+];
+const ISO_Y_DEN: [FieldElement; 3] = [
+    fe_from_hex("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffff93b"),
+    fe_from_hex("7a06534bb8bdb49fd5e9e6632722c2989467c1bfc8e8d978dfb425d2685c2573"),
+    fe_from_hex("6484aa716545ca2cf3a70c3fa8fe337e0a3d21162f0d6299a7bf8192bfd2a76f"),
+];
+
+/// Evaluates the polynomial with `coefficients` (lowest degree first) at `x` with Horner's method.
+fn fe_polynomial(coefficients: &[FieldElement], monic: bool, x: &FieldElement) -> FieldElement {
+    let init = if monic {
+        FieldElement::ONE
+    } else {
+        FieldElement::ZERO
+    };
+    coefficients
+        .iter()
+        .rev()
+        .fold(init, |acc, c| (acc * x + c).normalize())
+}
+
+/// Interprets 48 bytes as a big-endian integer modulo `p` (the `hash_to_field` reduction of
+/// [RFC 9380] for secp256k1).
+///
+/// [RFC 9380]: https://www.rfc-editor.org/rfc/rfc9380.html
+pub fn field_reduce_wide(bytes: [u8; 48]) -> [u8; 32] {
+    let mut high = [0u8; 32];
+    let mut low = [0u8; 32];
+    high[16..].copy_from_slice(&bytes[..16]);
+    low.copy_from_slice(&bytes[16..]);
+    // 2²⁵⁶ = 2³² + 977 mod p
+    let two_256 = fe_from_hex("00000000000000000000000000000000000000000000000000000001000003d1");
+    let high = FieldElement::from_bytes_unchecked(&high).normalize();
+    let low = FieldElement::from_bytes_unchecked(&low).normalize();
+    (high * two_256 + low).normalize().to_bytes().into()
+}
+
+/// Maps the field element `u` (which must be less than `p`) to a curve point with the simplified
+/// SWU map onto the isogenous curve followed by the 3-isogeny to secp256k1 ([RFC 9380] 6.6.3).
+///
+/// [RFC 9380]: https://www.rfc-editor.org/rfc/rfc9380.html
+pub fn sswu_map(u: [u8; 32]) -> ([u8; 32], [u8; 32]) {
+    let u = FieldElement::from_bytes_unchecked(&u).normalize();
+    let z = (-fe_from_u32(11)).normalize();
+    let b = fe_from_u32(SSWU_B);
+    let curve_rhs = |x: &FieldElement| (x.square() * x + SSWU_A * x + b).normalize();
+
+    let z_u2 = (z * u.square()).normalize();
+    let denominator = (z_u2.square() + z_u2).normalize();
+    let exceptional = denominator.is_zero();
+    let inv_denominator = denominator.invert().unwrap_or(FieldElement::ZERO);
+    let a_inv = SSWU_A.invert().unwrap();
+    let x1 = FieldElement::conditional_select(
+        &((-b).normalize() * a_inv * (FieldElement::ONE + inv_denominator)).normalize(),
+        &(b * (z * SSWU_A).normalize().invert().unwrap()).normalize(),
+        exceptional,
+    );
+    let x2 = (z_u2 * x1).normalize();
+    let y1 = curve_rhs(&x1).sqrt();
+    let y2 = curve_rhs(&x2).sqrt();
+    let gx1_is_square = y1.is_some();
+    let x = FieldElement::conditional_select(&x2, &x1, gx1_is_square);
+    let y = FieldElement::conditional_select(
+        &y2.unwrap_or(FieldElement::ZERO),
+        &y1.unwrap_or(FieldElement::ZERO),
+        gx1_is_square,
+    )
+    .normalize();
+    let y = FieldElement::conditional_select(&y, &(-y).normalize(), u.is_odd() ^ y.is_odd());
+
+    let x_num = fe_polynomial(&ISO_X_NUM, false, &x);
+    let x_den = fe_polynomial(&ISO_X_DEN, true, &x);
+    let y_num = fe_polynomial(&ISO_Y_NUM, false, &x);
+    let y_den = fe_polynomial(&ISO_Y_DEN, true, &x);
+    // the denominators only vanish at the isogeny's kernel which x never lies in
+    let x = (x_num * x_den.invert().unwrap()).normalize();
+    let y = (y * y_num * y_den.invert().unwrap()).normalize();
+    (x.to_bytes().into(), y.to_bytes().into())
+}
+
 pub struct ConstantTime;
 
 impl TimeSensitive for ConstantTime {
@@ -170,19 +532,34 @@ impl TimeSensitive for ConstantTime {
     }
 
     fn scalar_add(lhs: &Scalar, rhs: &Scalar) -> Scalar {
-        lhs + rhs
+        let result = lhs + rhs;
+        #[cfg(feature = "shadow_arithmetic")]
+        super::shadow::check_add(lhs, rhs, &result);
+        result
     }
 
     fn scalar_sub(lhs: &Scalar, rhs: &Scalar) -> Scalar {
-        lhs - rhs
+        let result = lhs - rhs;
+        #[cfg(feature = "shadow_arithmetic")]
+        super::shadow::check_sub(lhs, rhs, &result);
+        result
     }
 
     fn scalar_cond_negate(scalar: &mut Scalar, neg: bool) {
-        scalar.conditional_negate(Choice::from(neg as u8))
+        #[cfg(feature = "shadow_arithmetic")]
+        let original = *scalar;
+        scalar.conditional_negate(Choice::from(neg as u8));
+        #[cfg(feature = "shadow_arithmetic")]
+        if neg {
+            super::shadow::check_negate(&original, scalar);
+        }
     }
 
     fn scalar_is_high(scalar: &Scalar) -> bool {
-        scalar.is_high().into()
+        let result = scalar.is_high().into();
+        #[cfg(feature = "shadow_arithmetic")]
+        super::shadow::check_is_high(scalar, result);
+        result
     }
 
     fn scalar_is_zero(scalar: &Scalar) -> bool {
@@ -190,11 +567,17 @@ impl TimeSensitive for ConstantTime {
     }
 
     fn scalar_mul(lhs: &Scalar, rhs: &Scalar) -> Scalar {
-        lhs * rhs
+        let result = lhs * rhs;
+        #[cfg(feature = "shadow_arithmetic")]
+        super::shadow::check_mul(lhs, rhs, &result);
+        result
     }
 
     fn scalar_invert(scalar: &Scalar) -> Scalar {
-        scalar.invert().unwrap()
+        let result = scalar.invert().unwrap();
+        #[cfg(feature = "shadow_arithmetic")]
+        super::shadow::check_invert(scalar, &result);
+        result
     }
 
     fn scalar_mul_basepoint(scalar: &Scalar, base: &BasePoint) -> Point {
@@ -218,14 +601,14 @@ impl TimeSensitive for ConstantTime {
 
 pub struct VariableTime;
 
-// delegate everything to constant time for now
+// Scalar multiplication uses wNAF. Everything else is delegated to constant time for now.
 impl TimeSensitive for VariableTime {
     fn scalar_mul_norm_point(lhs: &Scalar, rhs: &Point) -> Point {
-        ConstantTime::scalar_mul_norm_point(lhs, rhs)
+        mul::lincomb_vartime(&[rhs], &[lhs])
     }
 
     fn scalar_mul_point(lhs: &Scalar, rhs: &Point) -> Point {
-        ConstantTime::scalar_mul_point(lhs, rhs)
+        mul::lincomb_vartime(&[rhs], &[lhs])
     }
 
     fn scalar_eq(lhs: &Scalar, rhs: &Scalar) -> bool {
@@ -329,7 +712,7 @@ impl TimeSensitive for VariableTime {
     }
 
     fn point_double_mul(x: &Scalar, A: &Point, y: &Scalar, B: &Point) -> Point {
-        ConstantTime::point_double_mul(x, A, y, B)
+        mul::lincomb_vartime(&[A, B], &[x, y])
     }
 
     #[cfg(feature = "alloc")]
@@ -342,18 +725,66 @@ impl TimeSensitive for VariableTime {
         points: A,
         scalars: B,
     ) -> Point {
-        ConstantTime::lincomb_iter(points, scalars)
+        Self::lincomb_iter_with_threshold(points, scalars, DEFAULT_PIPPENGER_THRESHOLD)
     }
 }
 
+/// The number of points above which [`VariableTime::lincomb_iter`] switches to Pippenger's
+/// algorithm.
+pub const DEFAULT_PIPPENGER_THRESHOLD: usize = 128;
+
+impl VariableTime {
+    /// Like [`TimeSensitive::lincomb_iter`] but switching to Pippenger's algorithm above
+    /// `pippenger_threshold` points.
+    #[cfg(feature = "alloc")]
+    pub fn lincomb_iter_with_threshold<AT: AsRef<Point>, BT: AsRef<Scalar>>(
+        points: impl Iterator<Item = AT>,
+        scalars: impl Iterator<Item = BT>,
+        pippenger_threshold: usize,
+    ) -> Point {
+        mul::lincomb_iter_vartime(points.map(|p| *p.as_ref()), scalars, pippenger_threshold)
+    }
+
+    /// Without `alloc` there's no Pippenger's algorithm so the threshold is ignored.
+    #[cfg(not(feature = "alloc"))]
+    pub fn lincomb_iter_with_threshold<AT: AsRef<Point>, BT: AsRef<Scalar>>(
+        points: impl Iterator<Item = AT>,
+        scalars: impl Iterator<Item = BT>,
+        _pippenger_threshold: usize,
+    ) -> Point {
+        <Self as TimeSensitive>::lincomb_iter(points, scalars)
+    }
+}
+
+/// The curve order `n` as a field element.
+const CURVE_ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+/// `p - n`. Only scalars below this have a second x-coordinate, `r + n`, that reduces to them.
+const FIELD_MINUS_ORDER: [u8; 32] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+    0x45, 0x51, 0x23, 0x19, 0x50, 0xb7, 0x5f, 0xc4, 0x40, 0x2d, 0xa1, 0x72, 0x2f, 0xc9, 0xba, 0xee,
+];
+
 impl VariableTime {
     pub fn point_x_eq_scalar(point: &Point, scalar: &Scalar) -> bool {
         if point.is_identity().into() {
             return false;
         }
-        let mut point = *point;
-        Self::point_normalize(&mut point);
-        Scalar::from_bytes_reduced(&point.x.to_bytes()).eq(scalar)
+        // x = X/Z so rather than inverting Z compare X with r*Z and (r + n)*Z
+        let x = point.x.normalize();
+        let r_bytes = scalar.to_bytes();
+        let r = FieldElement::from_bytes(&r_bytes).unwrap();
+        if r.mul(&point.z).normalize() == x {
+            return true;
+        }
+        if r_bytes.as_slice() < &FIELD_MINUS_ORDER[..] {
+            let n = FieldElement::from_bytes(&CURVE_ORDER.into()).unwrap();
+            return (r + n).mul(&point.z).normalize() == x;
+        }
+        false
     }
 }
 
@@ -1,5 +1,7 @@
 //! These traits are for accounting for what methods each backend actually needs.
 mod k256_impl;
+#[cfg(any(test, feature = "shadow_arithmetic"))]
+mod shadow;
 
 pub use k256_impl::*;
 
@@ -0,0 +1,316 @@
+//! Shadow scalar arithmetic for cross-checking the backend.
+//!
+//! With the `shadow_arithmetic` feature every scalar operation the backend does is repeated here
+//! with a deliberately naive big integer implementation (32-bit limbs, schoolbook multiplication
+//! and bit by bit reduction) and the two results are asserted to be equal. It shares no code with
+//! the backend so an arithmetic bug in a new backend shows up as a panic naming the operation and
+//! its inputs rather than as a signature that mysteriously doesn't verify.
+//!
+//! This is slow, variable time and panics with the (possibly secret) operands so it is only for
+//! development.
+use super::{BackendScalar, Scalar};
+use core::fmt;
+
+/// The curve order in little endian 32-bit limbs.
+const N: [u32; 8] = [
+    0xD036_4141,
+    0xBFD2_5E8C,
+    0xAF48_A03B,
+    0xBAAE_DCE6,
+    0xFFFF_FFFE,
+    0xFFFF_FFFF,
+    0xFFFF_FFFF,
+    0xFFFF_FFFF,
+];
+
+type Limbs = [u32; 8];
+
+fn from_be_bytes(bytes: [u8; 32]) -> Limbs {
+    let mut limbs = [0u32; 8];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        let start = 28 - i * 4;
+        *limb = u32::from_be_bytes([
+            bytes[start],
+            bytes[start + 1],
+            bytes[start + 2],
+            bytes[start + 3],
+        ]);
+    }
+    limbs
+}
+
+fn to_be_bytes(limbs: Limbs) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (i, limb) in limbs.iter().enumerate() {
+        let start = 28 - i * 4;
+        bytes[start..start + 4].copy_from_slice(&limb.to_be_bytes());
+    }
+    bytes
+}
+
+/// Whether `a >= b` where both are little endian limbs of the same length.
+fn geq(a: &[u32], b: &[u32]) -> bool {
+    for (a, b) in a.iter().rev().zip(b.iter().rev()) {
+        if a != b {
+            return a > b;
+        }
+    }
+    true
+}
+
+/// `a -= b` ignoring the final borrow.
+fn sub_in_place(a: &mut [u32], b: &[u32]) {
+    let mut borrow = 0u64;
+    for (a, b) in a.iter_mut().zip(b) {
+        let diff = (*a as u64).wrapping_sub(*b as u64 + borrow);
+        *a = diff as u32;
+        borrow = (diff >> 63) & 1;
+    }
+}
+
+/// Reduces a 512-bit integer modulo `N` by binary long division.
+fn reduce_wide(wide: [u32; 16]) -> Limbs {
+    let n9 = {
+        let mut n9 = [0u32; 9];
+        n9[..8].copy_from_slice(&N);
+        n9
+    };
+    let mut rem = [0u32; 9];
+    for bit in (0..512).rev() {
+        let mut carry = (wide[bit / 32] >> (bit % 32)) & 1;
+        for limb in rem.iter_mut() {
+            let next = *limb >> 31;
+            *limb = (*limb << 1) | carry;
+            carry = next;
+        }
+        if geq(&rem, &n9) {
+            sub_in_place(&mut rem, &n9);
+        }
+    }
+    let mut out = [0u32; 8];
+    out.copy_from_slice(&rem[..8]);
+    out
+}
+
+fn reduce(a: Limbs) -> Limbs {
+    let mut wide = [0u32; 16];
+    wide[..8].copy_from_slice(&a);
+    reduce_wide(wide)
+}
+
+fn add(a: Limbs, b: Limbs) -> Limbs {
+    let mut wide = [0u32; 16];
+    let mut carry = 0u64;
+    for i in 0..8 {
+        let sum = a[i] as u64 + b[i] as u64 + carry;
+        wide[i] = sum as u32;
+        carry = sum >> 32;
+    }
+    wide[8] = carry as u32;
+    reduce_wide(wide)
+}
+
+fn negate(a: Limbs) -> Limbs {
+    let a = reduce(a);
+    if a == [0u32; 8] {
+        return a;
+    }
+    let mut out = N;
+    sub_in_place(&mut out, &a);
+    out
+}
+
+fn mul(a: Limbs, b: Limbs) -> Limbs {
+    let mut wide = [0u32; 16];
+    for i in 0..8 {
+        let mut carry = 0u64;
+        for j in 0..8 {
+            let product = a[i] as u64 * b[j] as u64 + wide[i + j] as u64 + carry;
+            wide[i + j] = product as u32;
+            carry = product >> 32;
+        }
+        wide[i + 8] = carry as u32;
+    }
+    reduce_wide(wide)
+}
+
+/// Inverts by Fermat's little theorem (`a^(n-2)`) with square and multiply.
+fn invert(a: Limbs) -> Limbs {
+    let mut exponent = N;
+    sub_in_place(&mut exponent, &[2, 0, 0, 0, 0, 0, 0, 0]);
+    let mut result = [1, 0, 0, 0, 0, 0, 0, 0];
+    for bit in (0..256).rev() {
+        result = mul(result, result);
+        if (exponent[bit / 32] >> (bit % 32)) & 1 == 1 {
+            result = mul(result, a);
+        }
+    }
+    result
+}
+
+fn is_high(a: Limbs) -> bool {
+    // a > (n - 1) / 2  <=>  2a > n - 1  <=>  2a >= n
+    let mut doubled = [0u32; 9];
+    let mut carry = 0;
+    for (i, limb) in a.iter().enumerate() {
+        doubled[i] = (limb << 1) | carry;
+        carry = limb >> 31;
+    }
+    doubled[8] = carry;
+    let mut n9 = [0u32; 9];
+    n9[..8].copy_from_slice(&N);
+    geq(&doubled, &n9)
+}
+
+struct Hex([u8; 32]);
+
+impl fmt::Display for Hex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+fn limbs(scalar: &Scalar) -> Limbs {
+    from_be_bytes(BackendScalar::to_bytes(scalar))
+}
+
+#[track_caller]
+fn check(op: &str, inputs: &[[u8; 32]], expected: Limbs, got: &Scalar) {
+    let got = BackendScalar::to_bytes(got);
+    let expected = to_be_bytes(expected);
+    if got != expected {
+        struct Inputs<'a>(&'a [[u8; 32]]);
+        impl fmt::Display for Inputs<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                for (i, input) in self.0.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", Hex(*input))?;
+                }
+                Ok(())
+            }
+        }
+        panic!(
+            "backend scalar {} disagrees with shadow arithmetic: inputs [{}] gave {} but expected {}",
+            op,
+            Inputs(inputs),
+            Hex(got),
+            Hex(expected)
+        );
+    }
+}
+
+#[track_caller]
+pub fn check_add(lhs: &Scalar, rhs: &Scalar, got: &Scalar) {
+    let inputs = [BackendScalar::to_bytes(lhs), BackendScalar::to_bytes(rhs)];
+    check("addition", &inputs, add(limbs(lhs), limbs(rhs)), got)
+}
+
+#[track_caller]
+pub fn check_sub(lhs: &Scalar, rhs: &Scalar, got: &Scalar) {
+    let inputs = [BackendScalar::to_bytes(lhs), BackendScalar::to_bytes(rhs)];
+    check(
+        "subtraction",
+        &inputs,
+        add(limbs(lhs), negate(limbs(rhs))),
+        got,
+    )
+}
+
+#[track_caller]
+pub fn check_mul(lhs: &Scalar, rhs: &Scalar, got: &Scalar) {
+    let inputs = [BackendScalar::to_bytes(lhs), BackendScalar::to_bytes(rhs)];
+    check("multiplication", &inputs, mul(limbs(lhs), limbs(rhs)), got)
+}
+
+#[track_caller]
+pub fn check_negate(scalar: &Scalar, got: &Scalar) {
+    let inputs = [BackendScalar::to_bytes(scalar)];
+    check("negation", &inputs, negate(limbs(scalar)), got)
+}
+
+#[track_caller]
+pub fn check_invert(scalar: &Scalar, got: &Scalar) {
+    let inputs = [BackendScalar::to_bytes(scalar)];
+    check("inversion", &inputs, invert(limbs(scalar)), got)
+}
+
+#[track_caller]
+pub fn check_reduce(bytes: [u8; 32], got: &Scalar) {
+    check("reduction", &[bytes], reduce(from_be_bytes(bytes)), got)
+}
+
+#[track_caller]
+pub fn check_is_high(scalar: &Scalar, got: bool) {
+    let expected = is_high(limbs(scalar));
+    assert_eq!(
+        got,
+        expected,
+        "backend scalar is_high disagrees with shadow arithmetic for {}",
+        Hex(BackendScalar::to_bytes(scalar))
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn scalar(limbs: Limbs) -> Scalar {
+        BackendScalar::from_bytes(to_be_bytes(limbs)).unwrap()
+    }
+
+    #[test]
+    fn shadow_agrees_with_backend() {
+        let mut minus_one = N;
+        sub_in_place(&mut minus_one, &[1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            to_be_bytes(minus_one),
+            BackendScalar::to_bytes(&Scalar::minus_one())
+        );
+        assert_eq!(mul(minus_one, minus_one), [1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(add(minus_one, [1, 0, 0, 0, 0, 0, 0, 0]), [0u32; 8]);
+        assert!(is_high(minus_one));
+        assert!(!is_high([1, 0, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(reduce([u32::MAX; 8]), add([u32::MAX; 8], [0u32; 8]));
+        check_reduce([0xff; 32], &Scalar::from_bytes_mod_order([0xff; 32]));
+
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut random = || {
+            let mut limbs = [0u32; 8];
+            for limb in limbs.iter_mut() {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                *limb = state as u32;
+            }
+            reduce(limbs)
+        };
+        for _ in 0..20 {
+            let (a, b) = (random(), random());
+            let (x, y) = (scalar(a), scalar(b));
+            check_add(&x, &y, &(x + y));
+            check_sub(&x, &y, &(x - y));
+            check_mul(&x, &y, &(x * y));
+            check_negate(&x, &-x);
+            check_is_high(&x, x.is_high().into());
+            check_reduce(
+                to_be_bytes(a),
+                &Scalar::from_bytes_mod_order(to_be_bytes(a)),
+            );
+            if a != [0u32; 8] {
+                check_invert(&x, &x.invert().unwrap());
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "backend scalar addition disagrees")]
+    fn shadow_catches_wrong_result() {
+        let one = Scalar::from_u32(1);
+        check_add(&one, &one, &one);
+    }
+}
@@ -0,0 +1,895 @@
+//! [BIP32] hierarchical deterministic key derivation.
+//!
+//! An [`Xpriv`] (a secret key and chain code) derives hardened and unhardened children and an
+//! [`Xpub`] (a public key and chain code) can derive the public keys of its unhardened children
+//! without knowing any secret. Watch-only wallets scan for payments by deriving every key up to
+//! some gap limit so [`derive_range`] derives a range of children in one go. The HMAC key (the
+//! chain code) and the serialization of the parent key are the same for every child so they are
+//! precomputed once for the whole range.
+//!
+//! To read and write `xprv...` and `xpub...` strings an extended key is wrapped in an
+//! [`ExtendedKey`] which carries the rest of the fields of the BIP32 serialization (the version,
+//! depth, parent fingerprint and child number).
+//!
+//! `H` must be SHA512 for the keys to match other BIP32 implementations (and `H256` SHA256 where
+//! fingerprints and checksums are needed). This crate doesn't depend on hash implementations so
+//! you have to pass them in.
+//!
+//! # Example
+//!
+//! ```
+//! use secp256kfun::{bip32::Xpub, Point};
+//! # let xpub = Xpub::new(Point::random(&mut rand::thread_rng()), [42u8; 32]);
+//! // scan the first 1000 receive addresses
+//! let receive = xpub.derive_child::<sha2::Sha512>(0).unwrap();
+//! for (index, public_key) in receive.derive_range::<sha2::Sha512>(0..1000) {
+//!     // look for payments to `public_key`
+//! #   assert_eq!(receive.derive_child::<sha2::Sha512>(index).unwrap().public_key, public_key);
+//! }
+//! ```
+//!
+//! Deriving from a seed and serializing the keys:
+//!
+//! ```
+//! use secp256kfun::bip32::{ExtendedKey, Xpriv, HARDENED, XPRV_VERSION};
+//! use sha2::{Sha256, Sha512};
+//! let seed = [7u8; 32];
+//! let master = ExtendedKey::master(XPRV_VERSION, Xpriv::from_seed::<Sha512>(&seed).unwrap());
+//! let account = master
+//!     .derive_child::<Sha512, Sha256>(84 + HARDENED)
+//!     .and_then(|key| key.derive_child::<Sha512, Sha256>(HARDENED))
+//!     .and_then(|key| key.derive_child::<Sha512, Sha256>(HARDENED))
+//!     .unwrap();
+//! # #[cfg(feature = "alloc")] {
+//! let xpub = account.to_xpub().to_base58check::<Sha256>();
+//! assert!(xpub.starts_with("xpub"));
+//! let decoded = ExtendedKey::<secp256kfun::bip32::Xpub>::from_base58check::<Sha256>(&xpub).unwrap();
+//! assert_eq!(decoded.key, account.key.to_xpub());
+//! # }
+//! ```
+//!
+//! [BIP32]: https://bips.xyz/32
+mod ripemd160;
+
+use crate::{
+    digest::{
+        crypto_common::BlockSizeUser,
+        generic_array::{
+            typenum::{U32, U64},
+            GenericArray,
+        },
+        Digest,
+    },
+    g,
+    marker::*,
+    s, Point, Scalar, G,
+};
+use core::{fmt, ops::Range};
+
+/// Child indices at or above this are hardened and can't be derived from an [`Xpub`].
+pub const HARDENED: u32 = 1 << 31;
+
+/// Version bytes of a mainnet extended public key (`xpub...`).
+pub const XPUB_VERSION: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+/// Version bytes of a mainnet extended private key (`xprv...`).
+pub const XPRV_VERSION: [u8; 4] = [0x04, 0x88, 0xad, 0xe4];
+/// Version bytes of a testnet extended public key (`tpub...`).
+pub const TPUB_VERSION: [u8; 4] = [0x04, 0x35, 0x87, 0xcf];
+/// Version bytes of a testnet extended private key (`tprv...`).
+pub const TPRV_VERSION: [u8; 4] = [0x04, 0x35, 0x83, 0x94];
+
+/// The length of a serialized [`ExtendedKey`] (before base58check encoding).
+pub const SERIALIZED_LEN: usize = 78;
+
+/// The chain code of an aggregate key's [`Xpub`] as specified by [BIP328].
+///
+/// An aggregate key (e.g. MuSig or FROST) has no chain code of its own so BIP328 fixes it to
+/// `SHA256("MuSig2MuSig2MuSig2")`. Since every participant uses the same chain code with the same
+/// aggregate key they all derive the same children.
+///
+/// [BIP328]: https://bips.xyz/328
+pub const BIP328_CHAIN_CODE: [u8; 32] = [
+    0x86, 0x80, 0x87, 0xca, 0x02, 0xa6, 0xf9, 0x74, 0xc4, 0x59, 0x89, 0x24, 0xc3, 0x6b, 0x57, 0x76,
+    0x2d, 0x32, 0xcb, 0x45, 0x71, 0x71, 0x67, 0xe3, 0x00, 0x62, 0x2c, 0x71, 0x67, 0xe3, 0x89, 0x65,
+];
+
+/// An extended public key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Xpub {
+    /// The public key.
+    pub public_key: Point,
+    /// The chain code.
+    pub chain_code: [u8; 32],
+}
+
+impl Xpub {
+    /// Creates an extended public key from its public key and chain code.
+    pub fn new(public_key: Point, chain_code: [u8; 32]) -> Self {
+        Self {
+            public_key,
+            chain_code,
+        }
+    }
+
+    /// Derives the unhardened child at `index`.
+    ///
+    /// Returns `None` if `index` is hardened or if the child is invalid (which happens with
+    /// probability less than 2<sup>-127</sup>).
+    pub fn derive_child<H>(&self, index: u32) -> Option<Xpub>
+    where
+        H: BlockSizeUser + Digest<OutputSize = U64> + Default + Clone,
+    {
+        if index >= HARDENED {
+            return None;
+        }
+        ChildHasher::<H>::new(self)
+            .derive(index)
+            .map(|(child, _)| child)
+    }
+
+    /// Derives the unhardened descendant at `path` and the tweak that was added to the public key
+    /// to get it.
+    ///
+    /// The descendant's public key is `self.public_key + tweak * G`. Aggregate keys (which can't
+    /// derive from a secret) apply the tweak to themselves to get a child key their participants
+    /// can still sign under. Returns `None` if any index is hardened or any child is invalid.
+    pub fn derive_path<H>(&self, path: &[u32]) -> Option<(Xpub, Scalar<Public, Zero>)>
+    where
+        H: BlockSizeUser + Digest<OutputSize = U64> + Default + Clone,
+    {
+        let mut xpub = *self;
+        let mut total = Scalar::<Public, Zero>::zero();
+        for &index in path {
+            if index >= HARDENED {
+                return None;
+            }
+            let (child, tweak) = ChildHasher::<H>::new(&xpub).derive(index)?;
+            xpub = child;
+            total = s!(total + tweak).public();
+        }
+        Some((xpub, total))
+    }
+
+    /// Derives the public keys of the unhardened children with indices in `range`.
+    ///
+    /// Children that are invalid are skipped (as BIP32 says to do) so the index of each key is
+    /// returned with it. Indices from [`HARDENED`] onwards are never returned.
+    pub fn derive_range<H>(&self, range: Range<u32>) -> DeriveRange<H>
+    where
+        H: BlockSizeUser + Digest<OutputSize = U64> + Default + Clone,
+    {
+        DeriveRange {
+            hasher: ChildHasher::new(self),
+            range: range.start.min(HARDENED)..range.end.min(HARDENED),
+        }
+    }
+}
+
+/// Derives the public keys of the unhardened children of `xpub` with indices in `range`.
+///
+/// See [`Xpub::derive_range`].
+pub fn derive_range<H>(xpub: &Xpub, range: Range<u32>) -> DeriveRange<H>
+where
+    H: BlockSizeUser + Digest<OutputSize = U64> + Default + Clone,
+{
+    xpub.derive_range(range)
+}
+
+/// An extended private key.
+///
+/// The `Debug` output leaves out the secret key and chain code.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Xpriv {
+    /// The secret key.
+    pub secret_key: Scalar,
+    /// The chain code.
+    pub chain_code: [u8; 32],
+}
+
+impl Xpriv {
+    /// Creates an extended private key from its secret key and chain code.
+    pub fn new(secret_key: Scalar, chain_code: [u8; 32]) -> Self {
+        Self {
+            secret_key,
+            chain_code,
+        }
+    }
+
+    /// Creates the master key of the tree for `seed` (BIP32 says it should be 16 to 64 bytes).
+    ///
+    /// Returns `None` if the master key is invalid (which happens with probability less than
+    /// 2<sup>-127</sup>).
+    pub fn from_seed<H>(seed: &[u8]) -> Option<Xpriv>
+    where
+        H: BlockSizeUser + Digest<OutputSize = U64> + Default + Clone,
+    {
+        let (inner, outer) = hmac_states::<H>(b"Bitcoin seed");
+        let output = hmac_finalize(inner.chain_update(seed), outer);
+        let (secret_key, chain_code) = split_output(&output);
+        Some(Xpriv {
+            secret_key: Scalar::from_bytes(secret_key)?,
+            chain_code,
+        })
+    }
+
+    /// The extended public key with the same chain code.
+    pub fn to_xpub(&self) -> Xpub {
+        Xpub {
+            public_key: g!(self.secret_key * G).normalize(),
+            chain_code: self.chain_code,
+        }
+    }
+
+    /// Derives the child at `index` which is hardened if `index` is at least [`HARDENED`].
+    ///
+    /// The unhardened children have the public keys of the children of [`to_xpub`]. Returns
+    /// `None` if the child is invalid (which happens with probability less than
+    /// 2<sup>-127</sup>).
+    ///
+    /// [`to_xpub`]: Self::to_xpub
+    pub fn derive_child<H>(&self, index: u32) -> Option<Xpriv>
+    where
+        H: BlockSizeUser + Digest<OutputSize = U64> + Default + Clone,
+    {
+        let (mut inner, outer) = hmac_states::<H>(&self.chain_code);
+        if index >= HARDENED {
+            inner.update([0u8]);
+            inner.update(self.secret_key.to_secret_bytes().expose_secret());
+        } else {
+            inner.update(g!(self.secret_key * G).normalize().to_bytes());
+        }
+        let output = hmac_finalize(inner.chain_update(index.to_be_bytes()), outer);
+        let (tweak, chain_code) = split_output(&output);
+        let tweak = Scalar::<Secret, Zero>::from_bytes(tweak)?;
+        Some(Xpriv {
+            secret_key: s!(tweak + self.secret_key).non_zero()?,
+            chain_code,
+        })
+    }
+
+    /// Derives the descendant at `path`.
+    ///
+    /// Returns `None` if any child on the way is invalid.
+    pub fn derive_path<H>(&self, path: &[u32]) -> Option<Xpriv>
+    where
+        H: BlockSizeUser + Digest<OutputSize = U64> + Default + Clone,
+    {
+        path.iter()
+            .try_fold(*self, |xpriv, index| xpriv.derive_child::<H>(*index))
+    }
+}
+
+impl fmt::Debug for Xpriv {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Xpriv(..)")
+    }
+}
+
+/// Iterator over child public keys returned by [`derive_range`].
+#[derive(Clone, Debug)]
+pub struct DeriveRange<H> {
+    hasher: ChildHasher<H>,
+    range: Range<u32>,
+}
+
+impl<H> Iterator for DeriveRange<H>
+where
+    H: BlockSizeUser + Digest<OutputSize = U64> + Default + Clone,
+{
+    type Item = (u32, Point);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let index = self.range.next()?;
+            if let Some((child, _)) = self.hasher.derive(index) {
+                return Some((index, child.public_key));
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.range.len()))
+    }
+}
+
+/// The HMAC states after absorbing the chain code and the serialized parent key.
+#[derive(Clone, Debug)]
+struct ChildHasher<H> {
+    parent: Point,
+    inner: H,
+    outer: H,
+}
+
+impl<H> ChildHasher<H>
+where
+    H: BlockSizeUser + Digest<OutputSize = U64> + Default + Clone,
+{
+    fn new(xpub: &Xpub) -> Self {
+        let (mut inner, outer) = hmac_states::<H>(&xpub.chain_code);
+        inner.update(xpub.public_key.to_bytes());
+        Self {
+            parent: xpub.public_key,
+            inner,
+            outer,
+        }
+    }
+
+    fn derive(&self, index: u32) -> Option<(Xpub, Scalar<Public, Zero>)> {
+        let output = hmac_finalize(
+            self.inner.clone().chain_update(index.to_be_bytes()),
+            self.outer.clone(),
+        );
+        let (tweak, chain_code) = split_output(&output);
+        let tweak = Scalar::<Public, Zero>::from_bytes(tweak)?;
+        let public_key = g!(tweak * G + self.parent).normalize().non_zero()?;
+        Some((
+            Xpub {
+                public_key,
+                chain_code,
+            },
+            tweak,
+        ))
+    }
+}
+
+/// The HMAC inner and outer states after absorbing `key` which must be no longer than the block
+/// size (BIP32 keys always are).
+fn hmac_states<H>(key: &[u8]) -> (H, H)
+where
+    H: BlockSizeUser + Digest + Default,
+{
+    let mut padded_key = GenericArray::<u8, H::BlockSize>::default();
+    padded_key[..key.len()].copy_from_slice(key);
+    let mut inner = H::default();
+    inner.update(
+        padded_key
+            .iter()
+            .map(|byte| byte ^ 0x36)
+            .collect::<GenericArray<u8, H::BlockSize>>(),
+    );
+    let mut outer = H::default();
+    outer.update(
+        padded_key
+            .iter()
+            .map(|byte| byte ^ 0x5c)
+            .collect::<GenericArray<u8, H::BlockSize>>(),
+    );
+    (inner, outer)
+}
+
+fn hmac_finalize<H: Digest<OutputSize = U64>>(inner: H, outer: H) -> GenericArray<u8, U64> {
+    outer.chain_update(inner.finalize()).finalize()
+}
+
+/// Splits the HMAC output into the key (or tweak) and the chain code.
+fn split_output(output: &GenericArray<u8, U64>) -> ([u8; 32], [u8; 32]) {
+    let mut left = [0u8; 32];
+    left.copy_from_slice(&output[..32]);
+    let mut right = [0u8; 32];
+    right.copy_from_slice(&output[32..]);
+    (left, right)
+}
+
+/// The fingerprint of a public key: the first four bytes of `RIPEMD160(SHA256(public_key))`.
+///
+/// `H` must be SHA256.
+pub fn fingerprint<H: Digest<OutputSize = U32> + Default>(public_key: &Point) -> [u8; 4] {
+    let sha256: [u8; 32] = H::default()
+        .chain_update(public_key.to_bytes())
+        .finalize()
+        .into();
+    let mut fingerprint = [0u8; 4];
+    fingerprint.copy_from_slice(&ripemd160::ripemd160(&sha256)[..4]);
+    fingerprint
+}
+
+mod sealed {
+    use super::*;
+
+    /// The key types that can be in an [`ExtendedKey`](super::ExtendedKey).
+    pub trait KeyData: Sized {
+        fn public_key(&self) -> Point;
+        fn key_bytes(&self) -> [u8; 33];
+        fn from_key_bytes(bytes: [u8; 33]) -> Option<Self>;
+        fn chain_code(&self) -> [u8; 32];
+        fn with_chain_code(self, chain_code: [u8; 32]) -> Self;
+        fn derive<H>(&self, index: u32) -> Option<Self>
+        where
+            H: BlockSizeUser + Digest<OutputSize = U64> + Default + Clone;
+    }
+
+    impl KeyData for Xpub {
+        fn public_key(&self) -> Point {
+            self.public_key
+        }
+
+        fn key_bytes(&self) -> [u8; 33] {
+            self.public_key.to_bytes()
+        }
+
+        fn from_key_bytes(bytes: [u8; 33]) -> Option<Self> {
+            Some(Xpub::new(Point::from_bytes(bytes)?, [0u8; 32]))
+        }
+
+        fn chain_code(&self) -> [u8; 32] {
+            self.chain_code
+        }
+
+        fn with_chain_code(self, chain_code: [u8; 32]) -> Self {
+            Xpub { chain_code, ..self }
+        }
+
+        fn derive<H>(&self, index: u32) -> Option<Self>
+        where
+            H: BlockSizeUser + Digest<OutputSize = U64> + Default + Clone,
+        {
+            self.derive_child::<H>(index)
+        }
+    }
+
+    impl KeyData for Xpriv {
+        fn public_key(&self) -> Point {
+            self.to_xpub().public_key
+        }
+
+        fn key_bytes(&self) -> [u8; 33] {
+            let mut bytes = [0u8; 33];
+            bytes[1..].copy_from_slice(self.secret_key.to_secret_bytes().expose_secret());
+            bytes
+        }
+
+        fn from_key_bytes(bytes: [u8; 33]) -> Option<Self> {
+            if bytes[0] != 0 {
+                return None;
+            }
+            let mut secret_key = [0u8; 32];
+            secret_key.copy_from_slice(&bytes[1..]);
+            Some(Xpriv::new(Scalar::from_bytes(secret_key)?, [0u8; 32]))
+        }
+
+        fn chain_code(&self) -> [u8; 32] {
+            self.chain_code
+        }
+
+        fn with_chain_code(self, chain_code: [u8; 32]) -> Self {
+            Xpriv { chain_code, ..self }
+        }
+
+        fn derive<H>(&self, index: u32) -> Option<Self>
+        where
+            H: BlockSizeUser + Digest<OutputSize = U64> + Default + Clone,
+        {
+            self.derive_child::<H>(index)
+        }
+    }
+}
+
+/// An [`Xpub`] or [`Xpriv`] with the rest of the fields of its BIP32 serialization.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtendedKey<K> {
+    /// The version bytes e.g. [`XPUB_VERSION`] or [`XPRV_VERSION`].
+    pub version: [u8; 4],
+    /// The number of derivations from the master key.
+    pub depth: u8,
+    /// The [`fingerprint`] of the parent's public key (zero for the master key).
+    pub parent_fingerprint: [u8; 4],
+    /// The index this key was derived at (zero for the master key).
+    pub child_number: u32,
+    /// The key.
+    pub key: K,
+}
+
+impl<K: sealed::KeyData> ExtendedKey<K> {
+    /// Wraps the master key of a tree.
+    pub fn master(version: [u8; 4], key: K) -> Self {
+        Self {
+            version,
+            depth: 0,
+            parent_fingerprint: [0u8; 4],
+            child_number: 0,
+            key,
+        }
+    }
+
+    /// Derives the child at `index` and fills in its depth, parent fingerprint and child number.
+    ///
+    /// Returns `None` if the child can't be derived or if this key is already at the maximum depth
+    /// of 255.
+    pub fn derive_child<H, H256>(&self, index: u32) -> Option<Self>
+    where
+        H: BlockSizeUser + Digest<OutputSize = U64> + Default + Clone,
+        H256: Digest<OutputSize = U32> + Default,
+    {
+        Some(Self {
+            version: self.version,
+            depth: self.depth.checked_add(1)?,
+            parent_fingerprint: fingerprint::<H256>(&self.key.public_key()),
+            child_number: index,
+            key: self.key.derive::<H>(index)?,
+        })
+    }
+
+    /// The [`fingerprint`] of this key.
+    pub fn fingerprint<H256: Digest<OutputSize = U32> + Default>(&self) -> [u8; 4] {
+        fingerprint::<H256>(&self.key.public_key())
+    }
+
+    /// Serializes the key into the 78 bytes BIP32 specifies.
+    pub fn to_bytes(&self) -> [u8; SERIALIZED_LEN] {
+        let mut bytes = [0u8; SERIALIZED_LEN];
+        bytes[..4].copy_from_slice(&self.version);
+        bytes[4] = self.depth;
+        bytes[5..9].copy_from_slice(&self.parent_fingerprint);
+        bytes[9..13].copy_from_slice(&self.child_number.to_be_bytes());
+        bytes[13..45].copy_from_slice(&self.key.chain_code());
+        bytes[45..].copy_from_slice(&self.key.key_bytes());
+        bytes
+    }
+
+    /// Deserializes a key from its 78 byte BIP32 serialization.
+    ///
+    /// The version isn't checked but the key has to be the right type (a public key for an
+    /// [`Xpub`] and a secret key for an [`Xpriv`]).
+    pub fn from_bytes(bytes: [u8; SERIALIZED_LEN]) -> Result<Self, Bip32DecodeError> {
+        let mut version = [0u8; 4];
+        version.copy_from_slice(&bytes[..4]);
+        let depth = bytes[4];
+        let mut parent_fingerprint = [0u8; 4];
+        parent_fingerprint.copy_from_slice(&bytes[5..9]);
+        let mut child_number = [0u8; 4];
+        child_number.copy_from_slice(&bytes[9..13]);
+        let child_number = u32::from_be_bytes(child_number);
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&bytes[13..45]);
+        let mut key_bytes = [0u8; 33];
+        key_bytes.copy_from_slice(&bytes[45..]);
+
+        if depth == 0 && (parent_fingerprint != [0u8; 4] || child_number != 0) {
+            return Err(Bip32DecodeError::InvalidMasterKey);
+        }
+        let key = K::from_key_bytes(key_bytes)
+            .ok_or(Bip32DecodeError::InvalidKey)?
+            .with_chain_code(chain_code);
+        Ok(Self {
+            version,
+            depth,
+            parent_fingerprint,
+            child_number,
+            key,
+        })
+    }
+
+    /// Encodes the key as a base58check string like `xpub...` or `xprv...`.
+    ///
+    /// `H256` must be SHA256.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn to_base58check<H256: Digest<OutputSize = U32> + Default>(
+        &self,
+    ) -> alloc::string::String {
+        let mut bytes = self.to_bytes().to_vec();
+        let checksum = base58_checksum::<H256>(&bytes);
+        bytes.extend_from_slice(&checksum);
+        base58::encode(&bytes)
+    }
+
+    /// Decodes a key from a base58check string like `xpub...` or `xprv...`.
+    ///
+    /// `H256` must be SHA256.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn from_base58check<H256: Digest<OutputSize = U32> + Default>(
+        encoded: &str,
+    ) -> Result<Self, Bip32DecodeError> {
+        let bytes = base58::decode(encoded).ok_or(Bip32DecodeError::InvalidBase58)?;
+        if bytes.len() != SERIALIZED_LEN + 4 {
+            return Err(Bip32DecodeError::InvalidLength);
+        }
+        let (payload, checksum) = bytes.split_at(SERIALIZED_LEN);
+        if base58_checksum::<H256>(payload) != checksum {
+            return Err(Bip32DecodeError::InvalidChecksum);
+        }
+        Self::from_bytes(payload.try_into().expect("checked length"))
+    }
+}
+
+impl ExtendedKey<Xpriv> {
+    /// The extended public key at the same position in the tree with the public `version` that
+    /// goes with this key's private version ([`XPUB_VERSION`] for [`XPRV_VERSION`] and
+    /// [`TPUB_VERSION`] for [`TPRV_VERSION`]).
+    ///
+    /// Other versions are kept as they are. Set [`version`](ExtendedKey::version) afterwards if
+    /// you need something else.
+    pub fn to_xpub(&self) -> ExtendedKey<Xpub> {
+        let version = match self.version {
+            XPRV_VERSION => XPUB_VERSION,
+            TPRV_VERSION => TPUB_VERSION,
+            version => version,
+        };
+        ExtendedKey {
+            version,
+            depth: self.depth,
+            parent_fingerprint: self.parent_fingerprint,
+            child_number: self.child_number,
+            key: self.key.to_xpub(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn base58_checksum<H256: Digest<OutputSize = U32> + Default>(payload: &[u8]) -> [u8; 4] {
+    let hash = H256::default()
+        .chain_update(H256::default().chain_update(payload).finalize())
+        .finalize();
+    let mut checksum = [0u8; 4];
+    checksum.copy_from_slice(&hash[..4]);
+    checksum
+}
+
+#[cfg(feature = "alloc")]
+mod base58 {
+    use alloc::{string::String, vec::Vec};
+
+    const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+    pub fn encode(bytes: &[u8]) -> String {
+        let zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+        // little endian base 58 digits
+        let mut digits = Vec::<u8>::with_capacity(bytes.len() * 138 / 100 + 1);
+        for byte in &bytes[zeros..] {
+            let mut carry = *byte as u32;
+            for digit in digits.iter_mut() {
+                carry += (*digit as u32) << 8;
+                *digit = (carry % 58) as u8;
+                carry /= 58;
+            }
+            while carry > 0 {
+                digits.push((carry % 58) as u8);
+                carry /= 58;
+            }
+        }
+        core::iter::repeat('1')
+            .take(zeros)
+            .chain(
+                digits
+                    .iter()
+                    .rev()
+                    .map(|digit| ALPHABET[*digit as usize] as char),
+            )
+            .collect()
+    }
+
+    pub fn decode(encoded: &str) -> Option<Vec<u8>> {
+        let zeros = encoded.bytes().take_while(|c| *c == b'1').count();
+        // little endian bytes
+        let mut bytes = Vec::<u8>::with_capacity(encoded.len());
+        for c in encoded.bytes().skip(zeros) {
+            let mut carry = ALPHABET.iter().position(|b| *b == c)? as u32;
+            for byte in bytes.iter_mut() {
+                carry += *byte as u32 * 58;
+                *byte = carry as u8;
+                carry >>= 8;
+            }
+            while carry > 0 {
+                bytes.push(carry as u8);
+                carry >>= 8;
+            }
+        }
+        bytes.extend(core::iter::repeat(0).take(zeros));
+        bytes.reverse();
+        Some(bytes)
+    }
+}
+
+/// Error decoding an [`ExtendedKey`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bip32DecodeError {
+    /// The string had a character that isn't in the base58 alphabet.
+    InvalidBase58,
+    /// The decoded data wasn't 78 bytes and a checksum.
+    InvalidLength,
+    /// The base58check checksum didn't match.
+    InvalidChecksum,
+    /// A depth zero key had a parent fingerprint or child number.
+    InvalidMasterKey,
+    /// The key data wasn't a valid key of the expected type.
+    InvalidKey,
+}
+
+impl fmt::Display for Bip32DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Bip32DecodeError::*;
+        match self {
+            InvalidBase58 => write!(f, "invalid base58 character"),
+            InvalidLength => write!(f, "extended key had the wrong length"),
+            InvalidChecksum => write!(f, "base58check checksum didn't match"),
+            InvalidMasterKey => write!(
+                f,
+                "a key with depth zero can't have a parent fingerprint or child number"
+            ),
+            InvalidKey => write!(f, "the key data wasn't a valid key of the expected type"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Bip32DecodeError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha512;
+
+    fn xpub(chain_code: &str, public_key: &str) -> Xpub {
+        Xpub::new(
+            crate::hex::decode_array(public_key)
+                .ok()
+                .and_then(Point::from_bytes)
+                .unwrap(),
+            crate::hex::decode_array(chain_code).unwrap(),
+        )
+    }
+
+    #[test]
+    fn bip32_test_vector_2() {
+        let master = xpub(
+            "60499f801b896d83179a4374aeb7822aaeaceaa0db1f85ee3e904c4defbd9689",
+            "03cbcaa9c98c877a26977d00825c956a238e8dddfbd322cce4f74b0b5bd6ace4a7",
+        );
+        let child = xpub(
+            "f0909affaa7ee7abe5dd4e100598d4dc53cd709d5a5c2cac40e7412f232f7c9c",
+            "02fc9e5af0ac8d9b3cecfe2a888e2117ba3d089d8585886c9c826b6b22a98d12ea",
+        );
+        assert_eq!(master.derive_child::<Sha512>(0), Some(child));
+        assert_eq!(master.derive_child::<Sha512>(HARDENED), None);
+    }
+
+    #[test]
+    fn derive_range_matches_derive_child() {
+        let xpub = Xpub::new(Point::random(&mut rand::thread_rng()), [7u8; 32]);
+        let mut count = 0;
+        for (index, public_key) in derive_range::<Sha512>(&xpub, 10..30) {
+            assert_eq!(
+                xpub.derive_child::<Sha512>(index).unwrap().public_key,
+                public_key
+            );
+            count += 1;
+        }
+        assert_eq!(count, 20);
+        assert_eq!(
+            xpub.derive_range::<Sha512>(HARDENED - 1..u32::MAX).count(),
+            1
+        );
+    }
+
+    #[test]
+    fn derive_path_tweak() {
+        use sha2::{Digest, Sha256};
+        assert_eq!(
+            BIP328_CHAIN_CODE,
+            <[u8; 32]>::from(Sha256::digest(b"MuSig2MuSig2MuSig2"))
+        );
+        let xpub = Xpub::new(Point::random(&mut rand::thread_rng()), BIP328_CHAIN_CODE);
+        let (child, tweak) = xpub.derive_path::<Sha512>(&[0, 7]).unwrap();
+        assert_eq!(
+            Some(child),
+            xpub.derive_child::<Sha512>(0)
+                .and_then(|child| child.derive_child::<Sha512>(7))
+        );
+        assert_eq!(g!(xpub.public_key + tweak * G), child.public_key);
+        assert_eq!(
+            xpub.derive_path::<Sha512>(&[]),
+            Some((xpub, Scalar::zero()))
+        );
+        assert_eq!(xpub.derive_path::<Sha512>(&[0, HARDENED]), None);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn bip32_test_vector_1() {
+        use sha2::Sha256;
+        let seed = crate::hex::decode_array::<16>("000102030405060708090a0b0c0d0e0f").unwrap();
+        let master = ExtendedKey::master(XPRV_VERSION, Xpriv::from_seed::<Sha512>(&seed).unwrap());
+        let vectors = [
+            (
+                HARDENED,
+                "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw",
+                "xprv9uHRZZhk6KAJC1avXpDAp4MDc3sQKNxDiPvvkX8Br5ngLNv1TxvUxt4cV1rGL5hj6KCesnDYUhd7oWgT11eZG7XnxHrnYeSvkzY7d2bhkJ7",
+            ),
+            (
+                1,
+                "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ",
+                "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs",
+            ),
+            (
+                2 + HARDENED,
+                "xpub6D4BDPcP2GT577Vvch3R8wDkScZWzQzMMUm3PWbmWvVJrZwQY4VUNgqFJPMM3No2dFDFGTsxxpG5uJh7n7epu4trkrX7x7DogT5Uv6fcLW5",
+                "xprv9z4pot5VBttmtdRTWfWQmoH1taj2axGVzFqSb8C9xaxKymcFzXBDptWmT7FwuEzG3ryjH4ktypQSAewRiNMjANTtpgP4mLTj34bhnZX7UiM",
+            ),
+            (
+                2,
+                "xpub6FHa3pjLCk84BayeJxFW2SP4XRrFd1JYnxeLeU8EqN3vDfZmbqBqaGJAyiLjTAwm6ZLRQUMv1ZACTj37sR62cfN7fe5JnJ7dh8zL4fiyLHV",
+                "xprvA2JDeKCSNNZky6uBCviVfJSKyQ1mDYahRjijr5idH2WwLsEd4Hsb2Tyh8RfQMuPh7f7RtyzTtdrbdqqsunu5Mm3wDvUAKRHSC34sJ7in334",
+            ),
+            (
+                1_000_000_000,
+                "xpub6H1LXWLaKsWFhvm6RVpEL9P4KfRZSW7abD2ttkWP3SSQvnyA8FSVqNTEcYFgJS2UaFcxupHiYkro49S8yGasTvXEYBVPamhGW6cFJodrTHy",
+                "xprvA41z7zogVVwxVSgdKUHDy1SKmdb533PjDz7J6N6mV6uS3ze1ai8FHa8kmHScGpWmj4WggLyQjgPie1rFSruoUihUZREPSL39UNdE3BBDu76",
+            ),
+        ];
+        assert_eq!(
+            master.to_base58check::<Sha256>(),
+            "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi"
+        );
+        assert_eq!(
+            master.to_xpub().to_base58check::<Sha256>(),
+            "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8"
+        );
+        assert_eq!(master.fingerprint::<Sha256>(), [0x34, 0x42, 0x19, 0x3e]);
+
+        let mut xprv = master;
+        let mut path = vec![];
+        for (index, expected_xpub, expected_xprv) in vectors {
+            let parent_xpub = xprv.to_xpub();
+            xprv = xprv.derive_child::<Sha512, Sha256>(index).unwrap();
+            path.push(index);
+            assert_eq!(xprv.to_base58check::<Sha256>(), expected_xprv);
+            assert_eq!(xprv.to_xpub().to_base58check::<Sha256>(), expected_xpub);
+            assert_eq!(
+                ExtendedKey::<Xpriv>::from_base58check::<Sha256>(expected_xprv),
+                Ok(xprv)
+            );
+            assert_eq!(
+                ExtendedKey::<Xpub>::from_base58check::<Sha256>(expected_xpub),
+                Ok(xprv.to_xpub())
+            );
+            assert_eq!(master.key.derive_path::<Sha512>(&path), Some(xprv.key));
+            // unhardened children can be derived from the parent's xpub too
+            assert_eq!(
+                parent_xpub.derive_child::<Sha512, Sha256>(index),
+                (index < HARDENED).then(|| xprv.to_xpub())
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn invalid_extended_keys() {
+        use sha2::Sha256;
+        let master = ExtendedKey::master(
+            XPRV_VERSION,
+            Xpriv::from_seed::<Sha512>(&[1u8; 32]).unwrap(),
+        );
+        let xprv = master.to_base58check::<Sha256>();
+        let xpub = master.to_xpub().to_base58check::<Sha256>();
+        assert_eq!(
+            ExtendedKey::<Xpub>::from_base58check::<Sha256>(&xprv),
+            Err(Bip32DecodeError::InvalidKey)
+        );
+        assert_eq!(
+            ExtendedKey::<Xpriv>::from_base58check::<Sha256>(&xpub),
+            Err(Bip32DecodeError::InvalidKey)
+        );
+        assert_eq!(
+            ExtendedKey::<Xpub>::from_base58check::<Sha256>(&xpub.replace('1', "2")),
+            Err(Bip32DecodeError::InvalidChecksum)
+        );
+        assert_eq!(
+            ExtendedKey::<Xpub>::from_base58check::<Sha256>(&xpub[..100]),
+            Err(Bip32DecodeError::InvalidLength)
+        );
+        assert_eq!(
+            ExtendedKey::<Xpub>::from_base58check::<Sha256>(&xpub.replace('1', "0")),
+            Err(Bip32DecodeError::InvalidBase58)
+        );
+        let orphan = ExtendedKey {
+            child_number: 1,
+            ..master.to_xpub()
+        };
+        assert_eq!(
+            ExtendedKey::<Xpub>::from_bytes(orphan.to_bytes()),
+            Err(Bip32DecodeError::InvalidMasterKey)
+        );
+        assert_eq!(format!("{:?}", master.key), "Xpriv(..)");
+    }
+}
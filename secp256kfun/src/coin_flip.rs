@@ -0,0 +1,312 @@
+//! Two-party commit-and-reveal coin flipping for jointly generated randomness.
+//!
+//! When two parties need randomness neither of them can bias (e.g. to pick who goes first or to
+//! seed a [`Shuffle`]) each picks a random contribution and sends a hash commitment to it. Only
+//! once they have the other's commitment do they reveal their contribution. The result is a hash
+//! of both so it is uniformly random as long as one of them is honest.
+//!
+//! The commitments bind the contribution to the party's index and a session id so a party can't
+//! replay the other's commitment back at them or reuse one from another session. Rolling this by
+//! hand tends to get one of those wrong.
+//!
+//! ⚠ Whoever receives the other's reveal first learns the result first and can abort if they
+//! don't like it. No two-party protocol can prevent that so the application has to treat an
+//! abort as a loss for whoever aborted.
+//!
+//! # Example
+//!
+//! ```
+//! use secp256kfun::coin_flip::CoinFlip;
+//! let flip = CoinFlip::<sha2::Sha256>::new("my-game/who-goes-first", b"game-42");
+//! let alice = flip.commit(0, &mut rand::thread_rng());
+//! let bob = flip.commit(1, &mut rand::thread_rng());
+//! // they swap commitments
+//! let (alice_commitment, bob_commitment) = (alice.commitment(), bob.commitment());
+//! // and then reveal
+//! let (alice, alice_reveal) = alice.reveal(1, bob_commitment).unwrap();
+//! let (bob, bob_reveal) = bob.reveal(0, alice_commitment).unwrap();
+//! let alice_result = alice.finish(bob_reveal).unwrap();
+//! let bob_result = bob.finish(alice_reveal).unwrap();
+//! assert_eq!(alice_result, bob_result);
+//! let alice_goes_first = alice_result.coin();
+//! ```
+//!
+//! [`Shuffle`]: crate::shuffle::Shuffle
+use crate::{
+    digest::{generic_array::typenum::U32, Digest},
+    hash::{HashAdd, Tag},
+    marker::*,
+    rand_core::RngCore,
+    Scalar,
+};
+use core::fmt;
+
+/// A coin flip between two parties in one session. See the [module
+/// documentation](crate::coin_flip).
+#[derive(Clone, Debug)]
+pub struct CoinFlip<H> {
+    commit_hash: H,
+    output_hash: H,
+}
+
+/// A commitment to a party's contribution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::serde::Deserialize, crate::serde::Serialize),
+    serde(crate = "crate::serde")
+)]
+pub struct Commitment(pub [u8; 32]);
+
+/// The unbiased result of a coin flip.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SharedRandomness([u8; 32]);
+
+impl SharedRandomness {
+    /// The 32 random bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+
+    /// The randomness as a scalar.
+    pub fn to_scalar(&self) -> Scalar<Public, Zero> {
+        Scalar::from_bytes_mod_order(self.0)
+    }
+
+    /// A single fair coin.
+    pub fn coin(&self) -> bool {
+        self.0[0] & 1 == 1
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Tag + Default + Clone> CoinFlip<H> {
+    /// Creates the coin flip for `domain` in the session `session_id`.
+    ///
+    /// `domain` should be unique to the application and what the randomness is for. Use a new
+    /// `session_id` for every flip.
+    pub fn new(domain: &str, session_id: &[u8]) -> Self {
+        let hash = |kind: &[u8]| {
+            H::default()
+                .tag_vectored([kind, domain.as_bytes()].into_iter())
+                .add((session_id.len() as u64).to_be_bytes())
+                .add(session_id)
+        };
+        Self {
+            commit_hash: hash(b"secp256kfun/coin-flip/commit/"),
+            output_hash: hash(b"secp256kfun/coin-flip/output/"),
+        }
+    }
+
+    /// The commitment of the party at `party` to `contribution`.
+    pub fn commitment_for(
+        &self,
+        party: u32,
+        contribution: &Scalar<impl Secrecy, Zero>,
+    ) -> Commitment {
+        Commitment(
+            self.commit_hash
+                .clone()
+                .add(party.to_be_bytes())
+                .add(contribution)
+                .finalize()
+                .into(),
+        )
+    }
+
+    /// Picks the contribution of the party at `party` and commits to it.
+    ///
+    /// The two parties must use different indices. Send the [`commitment`] to the other party.
+    ///
+    /// [`commitment`]: Committed::commitment
+    pub fn commit(&self, party: u32, rng: &mut impl RngCore) -> Committed<H> {
+        let contribution = Scalar::random(rng).mark_zero();
+        let commitment = self.commitment_for(party, &contribution);
+        Committed {
+            flip: self.clone(),
+            party,
+            contribution,
+            commitment,
+        }
+    }
+}
+
+/// A party that has committed to their contribution.
+pub struct Committed<H> {
+    flip: CoinFlip<H>,
+    party: u32,
+    contribution: Scalar<Secret, Zero>,
+    commitment: Commitment,
+}
+
+impl<H> fmt::Debug for Committed<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Committed")
+            .field("party", &self.party)
+            .field("commitment", &self.commitment)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Tag + Default + Clone> Committed<H> {
+    /// The commitment to send to the other party.
+    pub fn commitment(&self) -> Commitment {
+        self.commitment
+    }
+
+    /// Reveals this party's contribution after receiving the commitment of the other party at
+    /// `their_party`.
+    ///
+    /// Send the returned contribution to the other party.
+    pub fn reveal(
+        self,
+        their_party: u32,
+        their_commitment: Commitment,
+    ) -> Result<(Revealed<H>, Scalar<Public, Zero>), CoinFlipError> {
+        if their_party == self.party {
+            return Err(CoinFlipError::SameParty);
+        }
+        if their_commitment == self.commitment {
+            return Err(CoinFlipError::CopiedCommitment);
+        }
+        let contribution = self.contribution.public();
+        Ok((
+            Revealed {
+                committed: self,
+                their_party,
+                their_commitment,
+            },
+            contribution,
+        ))
+    }
+}
+
+/// A party that has revealed their contribution and is waiting for the other's.
+pub struct Revealed<H> {
+    committed: Committed<H>,
+    their_party: u32,
+    their_commitment: Commitment,
+}
+
+impl<H> fmt::Debug for Revealed<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Revealed")
+            .field("committed", &self.committed)
+            .field("their_party", &self.their_party)
+            .field("their_commitment", &self.their_commitment)
+            .finish()
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Tag + Default + Clone> Revealed<H> {
+    /// Checks the other party's contribution against their commitment and computes the result.
+    pub fn finish(
+        self,
+        their_contribution: Scalar<Public, Zero>,
+    ) -> Result<SharedRandomness, CoinFlipError> {
+        let Committed {
+            flip,
+            party,
+            contribution,
+            commitment,
+        } = self.committed;
+        if flip.commitment_for(self.their_party, &their_contribution) != self.their_commitment {
+            return Err(CoinFlipError::InvalidReveal);
+        }
+        let ours = (party, commitment, contribution.public());
+        let theirs = (self.their_party, self.their_commitment, their_contribution);
+        let (first, second) = if party < self.their_party {
+            (ours, theirs)
+        } else {
+            (theirs, ours)
+        };
+        let output = [first, second]
+            .into_iter()
+            .fold(
+                flip.output_hash,
+                |hash, (party, commitment, contribution)| {
+                    hash.add(party.to_be_bytes())
+                        .add(commitment.0)
+                        .add(contribution)
+                },
+            )
+            .finalize();
+        Ok(SharedRandomness(output.into()))
+    }
+}
+
+/// Error in a [`CoinFlip`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CoinFlipError {
+    /// The other party claimed to have our index.
+    SameParty,
+    /// The other party sent our own commitment back to us.
+    CopiedCommitment,
+    /// The other party's contribution doesn't match their commitment.
+    InvalidReveal,
+}
+
+impl fmt::Display for CoinFlipError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use CoinFlipError::*;
+        match self {
+            SameParty => write!(f, "the other party has the same index as us"),
+            CopiedCommitment => write!(f, "the other party sent our own commitment"),
+            InvalidReveal => write!(
+                f,
+                "the other party's contribution doesn't match their commitment"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CoinFlipError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+
+    #[test]
+    fn coin_flip_checks_reveals() {
+        let flip = CoinFlip::<Sha256>::new("test", b"session");
+        let alice = flip.commit(0, &mut rand::thread_rng());
+        let bob = flip.commit(1, &mut rand::thread_rng());
+        let (alice_commitment, bob_commitment) = (alice.commitment(), bob.commitment());
+        let (alice, alice_reveal) = alice.reveal(1, bob_commitment).unwrap();
+        let (bob, bob_reveal) = bob.reveal(0, alice_commitment).unwrap();
+        let wrong = Scalar::random(&mut rand::thread_rng()).public().mark_zero();
+        assert!(matches!(
+            flip.commit(0, &mut rand::thread_rng())
+                .reveal(1, bob_commitment)
+                .unwrap()
+                .0
+                .finish(wrong),
+            Err(CoinFlipError::InvalidReveal)
+        ));
+        assert_eq!(alice.finish(bob_reveal), bob.finish(alice_reveal));
+
+        let alice = flip.commit(0, &mut rand::thread_rng());
+        let commitment = alice.commitment();
+        assert!(matches!(
+            flip.commit(0, &mut rand::thread_rng())
+                .reveal(0, commitment),
+            Err(CoinFlipError::SameParty)
+        ));
+        assert!(matches!(
+            alice.reveal(1, commitment),
+            Err(CoinFlipError::CopiedCommitment)
+        ));
+
+        // commitments are bound to the party and the session
+        let contribution = Scalar::random(&mut rand::thread_rng()).mark_zero();
+        assert_ne!(
+            flip.commitment_for(0, &contribution),
+            flip.commitment_for(1, &contribution)
+        );
+        assert_ne!(
+            flip.commitment_for(0, &contribution),
+            CoinFlip::<Sha256>::new("test", b"other").commitment_for(0, &contribution)
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{marker::*, FromSliceError, Point};
+
+/// A point stored as its 33-byte compressed encoding.
+///
+/// Decompressing a [`Point`] from bytes requires computing a square root so holding many points
+/// that are mostly just stored, compared or looked up (e.g. in a database or cache) is wasteful.
+/// `CompressedPoint` keeps the bytes and only decompresses them when you call
+/// [`decompress`](Self::decompress). Equality, hashing and ordering are done on the bytes and so are
+/// cheap. The ordering is the same as that of the decompressed points.
+///
+/// Creating a `CompressedPoint` from bytes only checks that the first byte is `0x02` or `0x03`. The
+/// x-coordinate is not checked until it is decompressed so `decompress` may return `None` for a
+/// `CompressedPoint` that was not created from a [`Point`].
+///
+/// It serializes the same as `Point<Normal, Public, NonZero>` does.
+///
+/// # Example
+///
+/// ```
+/// use secp256kfun::{CompressedPoint, Point};
+/// let point = Point::random(&mut rand::thread_rng());
+/// let compressed = CompressedPoint::from(point);
+/// assert_eq!(compressed.to_bytes(), point.to_bytes());
+/// assert_eq!(compressed.decompress(), Some(point));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CompressedPoint([u8; 33]);
+
+impl CompressedPoint {
+    /// The length of the compressed encoding.
+    pub const SIZE: usize = 33;
+
+    /// Creates a `CompressedPoint` from the compressed encoding specified in [_Standards for
+    /// Efficient Cryptography_].
+    ///
+    /// Returns `None` if the first byte isn't `0x02` or `0x03`. The x-coordinate is not checked.
+    ///
+    /// [_Standards for Efficient Cryptography_]: https://www.secg.org/sec1-v2.pdf
+    pub fn from_bytes(bytes: [u8; 33]) -> Option<Self> {
+        match bytes[0] {
+            2 | 3 => Some(Self(bytes)),
+            _ => None,
+        }
+    }
+
+    /// Convenience method for calling [`from_bytes`] with a slice.
+    /// Returns None if [`from_bytes`] would or if `slice` is not [`SIZE`] bytes long.
+    ///
+    /// [`from_bytes`]: Self::from_bytes
+    /// [`SIZE`]: Self::SIZE
+    pub fn from_slice(slice: &[u8]) -> Option<Self> {
+        Self::try_from(slice).ok()
+    }
+
+    /// The compressed encoding of the point.
+    pub fn as_bytes(&self) -> &[u8; 33] {
+        &self.0
+    }
+
+    /// Converts into the compressed encoding of the point.
+    pub fn to_bytes(&self) -> [u8; 33] {
+        self.0
+    }
+
+    /// Decompresses the point.
+    ///
+    /// Returns `None` if the x-coordinate is not on the curve.
+    pub fn decompress(&self) -> Option<Point> {
+        Point::from_bytes(self.0)
+    }
+}
+
+impl<T: Normalized, S> From<Point<T, S, NonZero>> for CompressedPoint {
+    fn from(point: Point<T, S, NonZero>) -> Self {
+        Self(point.to_bytes())
+    }
+}
+
+impl TryFrom<&[u8]> for CompressedPoint {
+    type Error = FromSliceError;
+
+    fn try_from(slice: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(FromSliceError::to_array(slice)?).ok_or(FromSliceError::Invalid)
+    }
+}
+
+impl AsRef<[u8]> for CompressedPoint {
+    fn as_ref(&self) -> &[u8] {
+        &self.0[..]
+    }
+}
+
+crate::impl_display_debug_serialize! {
+    fn to_bytes(compressed_point: &CompressedPoint) -> [u8;33] {
+        compressed_point.0
+    }
+}
+
+crate::impl_fromstr_deserialize! {
+    name => "compressed secp256k1 point",
+    fn from_bytes(bytes: [u8;33]) -> Option<CompressedPoint> {
+        CompressedPoint::from_bytes(bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::G;
+
+    #[test]
+    fn compress_and_decompress() {
+        let point = Point::random(&mut rand::thread_rng());
+        let other = Point::random(&mut rand::thread_rng());
+        let compressed = CompressedPoint::from(point);
+        assert_eq!(compressed.decompress(), Some(point));
+        assert_eq!(
+            CompressedPoint::from(*G),
+            CompressedPoint::from(G.normalize())
+        );
+        assert_eq!(
+            compressed.cmp(&CompressedPoint::from(other)),
+            point.cmp(&other)
+        );
+        assert_eq!(
+            format!("{}", compressed).parse::<CompressedPoint>(),
+            Ok(compressed)
+        );
+
+        let mut bytes = [0u8; 33];
+        bytes[0] = 0x04;
+        assert_eq!(CompressedPoint::from_bytes(bytes), None);
+        // x = 5 is not on the curve but isn't checked until decompression
+        bytes[0] = 0x02;
+        bytes[32] = 5;
+        let not_on_curve = CompressedPoint::from_bytes(bytes).unwrap();
+        assert_eq!(not_on_curve.decompress(), None);
+    }
+}
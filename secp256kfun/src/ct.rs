@@ -0,0 +1,208 @@
+//! Wrappers for secret data that only allow constant time operations.
+//!
+//! Comparing secret bytes with `==` (or `<`, or using them as a map key) usually takes a different
+//! amount of time depending on where the first differing byte is which can leak the secret. The
+//! types here don't implement [`PartialEq`], [`Ord`] or [`Hash`] so doing that is a compile error.
+//! They can only be compared with [`ConstantTimeEq`] and the raw data has to be got out with an
+//! explicit (and easy to grep for) call to `expose_secret`.
+//!
+//! Enabling the `strict_ct` feature also removes [`Scalar::to_bytes`] from secret scalars so that
+//! [`Scalar::to_secret_bytes`] is the only way to get their bytes. This is meant for auditing
+//! builds: code that depends on getting raw bytes from secret scalars will fail to compile.
+//!
+//! # Example
+//!
+//! ```
+//! use secp256kfun::{subtle::ConstantTimeEq, Scalar};
+//! let a = Scalar::random(&mut rand::thread_rng());
+//! let b = Scalar::random(&mut rand::thread_rng());
+//! let same = a.to_secret_bytes().ct_eq(&b.to_secret_bytes());
+//! assert!(!bool::from(same));
+//! ```
+//!
+//! There are also helpers for moving secret data around without leaking where it goes.
+//! [`ct_swap`] conditionally swaps two [`Scalar`]s or [`Point`]s, [`ct_lookup`] reads from a
+//! secret index and [`ct_permute`] applies a secret permutation. These are the building blocks of
+//! oblivious shuffles and private lookups. They touch every element so they take time linear (or
+//! for [`ct_permute`] quadratic) in the number of elements.
+//!
+//! ```
+//! use secp256kfun::{ct, subtle::Choice, Scalar};
+//! let mut a = Scalar::random(&mut rand::thread_rng());
+//! let mut b = Scalar::random(&mut rand::thread_rng());
+//! let (original_a, original_b) = (a, b);
+//! ct::ct_swap(&mut a, &mut b, Choice::from(1));
+//! assert_eq!((a, b), (original_b, original_a));
+//! let secret_index = 1;
+//! assert_eq!(
+//!     Option::from(ct::ct_lookup(&[a, b], secret_index)),
+//!     Some(original_a)
+//! );
+//! ```
+//!
+//! [`Hash`]: core::hash::Hash
+//! [`Scalar`]: crate::Scalar
+//! [`Point`]: crate::Point
+//! [`Scalar::to_bytes`]: crate::Scalar::to_bytes
+//! [`Scalar::to_secret_bytes`]: crate::Scalar::to_secret_bytes
+use core::fmt;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+
+/// Secret bytes that can only be compared in constant time.
+///
+/// See the [module documentation](crate::ct).
+#[derive(Clone, Copy)]
+pub struct SecretBytes<const N: usize>([u8; N]);
+
+impl<const N: usize> SecretBytes<N> {
+    /// Wraps some secret bytes.
+    pub fn new(bytes: [u8; N]) -> Self {
+        Self(bytes)
+    }
+
+    /// Gets a reference to the secret bytes.
+    ///
+    /// Be careful not to use variable time operations on them.
+    pub fn expose_secret(&self) -> &[u8; N] {
+        &self.0
+    }
+
+    /// Unwraps the secret bytes.
+    ///
+    /// Be careful not to use variable time operations on them.
+    pub fn into_inner(self) -> [u8; N] {
+        self.0
+    }
+}
+
+impl<const N: usize> ConstantTimeEq for SecretBytes<N> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0[..].ct_eq(&other.0[..])
+    }
+}
+
+impl<const N: usize> ConditionallySelectable for SecretBytes<N> {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut bytes = a.0;
+        for (byte, b_byte) in bytes.iter_mut().zip(b.0) {
+            byte.conditional_assign(&b_byte, choice);
+        }
+        Self(bytes)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
+impl<const N: usize> zeroize::Zeroize for SecretBytes<N> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<const N: usize> fmt::Debug for SecretBytes<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretBytes<{}>(..)", N)
+    }
+}
+
+/// Swaps `a` and `b` if `choice` is set without branching on it.
+pub fn ct_swap<T: ConditionallySelectable>(a: &mut T, b: &mut T, choice: Choice) {
+    T::conditional_swap(a, b, choice)
+}
+
+/// Gets `items[index]` without the timing or memory accesses depending on `index`.
+///
+/// The result is none if `index` is out of range.
+///
+/// # Panics
+///
+/// If `items` is empty.
+pub fn ct_lookup<T: ConditionallySelectable>(items: &[T], index: usize) -> CtOption<T> {
+    let mut found = Choice::from(0);
+    let mut item = items[0];
+    for (i, candidate) in items.iter().enumerate() {
+        let is_index = i.ct_eq(&index);
+        item.conditional_assign(candidate, is_index);
+        found |= is_index;
+    }
+    CtOption::new(item, found)
+}
+
+/// Applies the secret `permutation` to `items` so that `output[i] = items[permutation[i]]`.
+///
+/// The timing and memory accesses only depend on the number of items. The result is none if
+/// `permutation` isn't a permutation of the indices of `items`.
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn ct_permute<T: ConditionallySelectable>(
+    items: &[T],
+    permutation: &[usize],
+) -> CtOption<alloc::vec::Vec<T>> {
+    if items.len() != permutation.len() {
+        return CtOption::new(alloc::vec::Vec::new(), Choice::from(0));
+    }
+    let mut hits = alloc::vec![0u64; items.len()];
+    let output = permutation
+        .iter()
+        .map(|&index| {
+            let mut item = items[0];
+            for (i, (candidate, hits)) in items.iter().zip(&mut hits).enumerate() {
+                let is_index = i.ct_eq(&index);
+                item.conditional_assign(candidate, is_index);
+                *hits += u64::from(is_index.unwrap_u8());
+            }
+            item
+        })
+        .collect();
+    let is_permutation = hits
+        .iter()
+        .fold(Choice::from(1), |acc, hits| acc & hits.ct_eq(&1));
+    CtOption::new(output, is_permutation)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn constant_time_operations() {
+        let a = SecretBytes::new([1u8; 4]);
+        let b = SecretBytes::new([2u8; 4]);
+        assert!(bool::from(a.ct_eq(&a)));
+        assert!(!bool::from(a.ct_eq(&b)));
+        let selected = SecretBytes::conditional_select(&a, &b, Choice::from(1));
+        assert_eq!(selected.into_inner(), [2u8; 4]);
+        assert_eq!(format!("{:?}", a), "SecretBytes<4>(..)");
+    }
+
+    #[test]
+    fn swap_lookup_and_permute() {
+        use crate::{g, marker::*, Point, Scalar, G};
+        let scalars = [1u32, 2, 3, 4].map(Scalar::<Secret, Zero>::from);
+        let (mut a, mut b) = (scalars[0], scalars[1]);
+        ct_swap(&mut a, &mut b, Choice::from(0));
+        assert_eq!((a, b), (scalars[0], scalars[1]));
+        ct_swap(&mut a, &mut b, Choice::from(1));
+        assert_eq!((a, b), (scalars[1], scalars[0]));
+
+        let points = scalars.map(|x| g!(x * G).normalize());
+        let (mut A, mut B) = (points[0], points[1]);
+        ct_swap(&mut A, &mut B, Choice::from(1));
+        assert_eq!((A, B), (points[1], points[0]));
+
+        assert_eq!(Option::from(ct_lookup(&points, 2)), Some(points[2]));
+        assert_eq!(
+            Option::<Point<Normal, Public, Zero>>::from(ct_lookup(&points, 4)),
+            None
+        );
+
+        let permuted: Option<_> = ct_permute(&scalars, &[2, 0, 3, 1]).into();
+        assert_eq!(
+            permuted,
+            Some(vec![scalars[2], scalars[0], scalars[3], scalars[1]])
+        );
+        assert!(bool::from(ct_permute(&scalars, &[2, 0, 2, 1]).is_none()));
+        assert!(bool::from(ct_permute(&scalars, &[2, 0, 3, 4]).is_none()));
+        assert!(bool::from(ct_permute(&scalars, &[2, 0, 3]).is_none()));
+    }
+}
@@ -0,0 +1,198 @@
+//! Proofs that two points have the same discrete logarithm with respect to two generators.
+//!
+//! Given generators `G₁` and `G₂` and points `X₁ = x * G₁` and `X₂ = x * G₂` a [`DleqProof`] from
+//! [`Dleq::prove_dleq`] convinces anyone that `X₁` and `X₂` share the same `x` without revealing
+//! it. This is the "these two points share a discrete log" step in adaptor signatures, oracle
+//! attestations and VRFs.
+//!
+//! The proof is a Chaum-Pedersen proof `(c, s)` made non-interactive with a Fiat-Shamir challenge
+//! `c` that hashes both generators, both points and the two nonce points under a tag unique to the
+//! application's `domain`. Proofs for one domain never verify in another. The nonce is derived
+//! with a [`NonceGen`] from `x` and the statement.
+//!
+//! # Example
+//!
+//! ```
+//! use secp256kfun::{
+//!     dleq::Dleq, g, hash_to_curve::hash_to_curve, marker::*, nonce, Scalar, G,
+//! };
+//! use sha2::Sha256;
+//! let dleq = Dleq::<Sha256>::new("my-vrf");
+//! let nonce_gen = nonce::Deterministic::<Sha256>::default();
+//! let x = Scalar::random(&mut rand::thread_rng());
+//! let H = hash_to_curve::<Sha256>(b"input", b"my-vrf-with-secp256k1_XMD:SHA-256_SSWU_RO_");
+//! let proof = dleq.prove_dleq(&nonce_gen, &x, G, &H);
+//! let (X, Y) = (g!(x * G).normalize(), g!(x * H).normalize());
+//! assert!(dleq.verify_dleq(G, &X, &H, &Y, &proof));
+//! assert!(!dleq.verify_dleq(G, &X, &H, &X, &proof));
+//! ```
+//!
+//! [`NonceGen`]: crate::nonce::NonceGen
+use crate::{
+    derive_nonce,
+    digest::{generic_array::typenum::U32, Digest},
+    g,
+    hash::{HashAdd, Tag},
+    marker::*,
+    s, Point, Scalar,
+};
+
+/// Creates and verifies [`DleqProof`]s for one application. See the [module
+/// documentation](crate::dleq).
+#[derive(Clone, Debug)]
+pub struct Dleq<H> {
+    challenge_hash: H,
+    domain_id: [u8; 32],
+}
+
+/// A proof that two points have the same discrete logarithm with respect to two generators.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::serde::Deserialize, crate::serde::Serialize),
+    serde(crate = "crate::serde")
+)]
+pub struct DleqProof {
+    /// The Fiat-Shamir challenge.
+    pub c: Scalar<Public, Zero>,
+    /// The response `r + c * x`.
+    pub s: Scalar<Public, Zero>,
+}
+
+impl DleqProof {
+    /// The proof as `c || s`.
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&self.c.to_bytes());
+        bytes[32..].copy_from_slice(&self.s.to_bytes());
+        bytes
+    }
+
+    /// Decodes a proof encoded with [`to_bytes`](Self::to_bytes).
+    ///
+    /// Returns `None` if either scalar isn't less than the curve order.
+    pub fn from_bytes(bytes: [u8; 64]) -> Option<Self> {
+        Some(Self {
+            c: Scalar::from_slice(&bytes[..32])?,
+            s: Scalar::from_slice(&bytes[32..])?,
+        })
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Tag + Default + Clone> Dleq<H> {
+    /// Creates the proof system for `domain` which should be unique to the application and what
+    /// the proofs are for.
+    pub fn new(domain: &str) -> Self {
+        let challenge_hash = H::default()
+            .tag_vectored([&b"secp256kfun/dleq/"[..], domain.as_bytes()].into_iter());
+        Self {
+            domain_id: challenge_hash.clone().finalize().into(),
+            challenge_hash,
+        }
+    }
+
+    fn challenge(
+        &self,
+        bases: (Point, Point),
+        points: (Point, Point),
+        nonces: (Point<Normal, Public, Zero>, Point<Normal, Public, Zero>),
+    ) -> Scalar<Public, Zero> {
+        Scalar::from_hash(
+            self.challenge_hash
+                .clone()
+                .add(bases.0)
+                .add(points.0)
+                .add(bases.1)
+                .add(points.1)
+                .add(nonces.0)
+                .add(nonces.1),
+        )
+        .public()
+        .mark_zero()
+    }
+
+    /// Proves that `x * G1` and `x * G2` have the same discrete logarithm `x`.
+    ///
+    /// The nonce generator is tagged before use so it can be shared with other schemes.
+    pub fn prove_dleq<NG: crate::nonce::NonceGen + Tag + Clone>(
+        &self,
+        nonce_gen: &NG,
+        x: &Scalar,
+        G1: &Point<impl PointType, impl Secrecy>,
+        G2: &Point<impl PointType, impl Secrecy>,
+    ) -> DleqProof {
+        let (G1, G2) = (G1.normalize().public(), G2.normalize().public());
+        let (X1, X2) = (g!(x * G1).normalize(), g!(x * G2).normalize());
+        // the domain goes into the nonce because using the same nonce with another domain's
+        // challenge would reveal x
+        let r = derive_nonce!(
+            nonce_gen => nonce_gen.clone().tag(b"secp256kfun/dleq"),
+            secret => x,
+            public => [self.domain_id, G1, X1, G2, X2]
+        );
+        let (R1, R2) = (g!(r * G1).normalize(), g!(r * G2).normalize());
+        let c = self.challenge((G1, G2), (X1, X2), (R1.mark_zero(), R2.mark_zero()));
+        DleqProof {
+            c,
+            s: s!(r + c * x).public(),
+        }
+    }
+
+    /// Verifies that `X1` and `X2` have the same discrete logarithm with respect to `G1` and `G2`.
+    #[must_use]
+    pub fn verify_dleq(
+        &self,
+        G1: &Point<impl PointType, impl Secrecy>,
+        X1: &Point<impl PointType, impl Secrecy>,
+        G2: &Point<impl PointType, impl Secrecy>,
+        X2: &Point<impl PointType, impl Secrecy>,
+        proof: &DleqProof,
+    ) -> bool {
+        let (G1, G2) = (G1.normalize().public(), G2.normalize().public());
+        let (X1, X2) = (X1.normalize().public(), X2.normalize().public());
+        let DleqProof { c, s } = proof;
+        let R1 = g!(s * G1 - c * X1).normalize();
+        let R2 = g!(s * G2 - c * X2).normalize();
+        self.challenge((G1, G2), (X1, X2), (R1, R2)) == *c
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{hash_to_curve::hash_to_curve, nonce::Deterministic, G};
+    use sha2::Sha256;
+
+    #[test]
+    fn prove_and_verify() {
+        let dleq = Dleq::<Sha256>::new("test");
+        let nonce_gen = Deterministic::<Sha256>::default();
+        let x = Scalar::random(&mut rand::thread_rng());
+        let H = hash_to_curve::<Sha256>(b"second generator", b"test");
+        let (X, Y) = (g!(x * G).normalize(), g!(x * H).normalize());
+
+        let proof = dleq.prove_dleq(&nonce_gen, &x, G, &H);
+        assert!(dleq.verify_dleq(G, &X, &H, &Y, &proof));
+        assert_eq!(DleqProof::from_bytes(proof.to_bytes()), Some(proof));
+        // deterministic
+        assert_eq!(dleq.prove_dleq(&nonce_gen, &x, G, &H), proof);
+
+        // the generators and points have to be in the right order
+        assert!(!dleq.verify_dleq(&H, &Y, G, &X, &proof));
+        assert!(!dleq.verify_dleq(G, &Y, &H, &X, &proof));
+        // points with different discrete logs
+        let Z = g!(x * H + G).normalize().non_zero().unwrap();
+        assert!(!dleq.verify_dleq(G, &X, &H, &Z, &proof));
+        // proofs don't carry over to other domains
+        let other = Dleq::<Sha256>::new("other");
+        assert!(!other.verify_dleq(G, &X, &H, &Y, &proof));
+        // and proving the same statement in another domain uses another nonce
+        let other_proof = other.prove_dleq(&nonce_gen, &x, G, &H);
+        let nonce = |proof: &DleqProof| g!(proof.s * G - proof.c * X).normalize();
+        assert_ne!(nonce(&proof), nonce(&other_proof));
+
+        let mut bad = proof;
+        bad.s = s!(bad.s + 1).public();
+        assert!(!dleq.verify_dleq(G, &X, &H, &Y, &bad));
+    }
+}
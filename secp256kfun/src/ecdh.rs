@@ -0,0 +1,403 @@
+//! Elliptic curve Diffie-Hellman and an [HKDF] key schedule on top of it.
+//!
+//! [`SharedSecret::new`] computes `x * P` for a secret key `x` and the other party's public key
+//! `P`. The shared point shouldn't be used as a key directly. Instead turn it into a
+//! [`KeySchedule`] with an application specific salt and derive every key the application needs
+//! from it with a label and context:
+//!
+//! - The label says what the key is for (e.g. `"encryption"` or `"mac"`). Implement
+//!   [`KeyPurpose`] to give the label a type so [`KeySchedule::derive`] returns a
+//!   [`SymmetricKey`] that can't be mixed up with a key for something else.
+//! - The context is anything else the key should be bound to (e.g. the two public keys and a
+//!   session id).
+//! - The length of the key is part of the derivation so keys of different lengths with the same
+//!   label and context are unrelated.
+//!
+//! The HKDF info of a key is `len(okm) as u16 || len(label) as u8 || label || len(context) as u32
+//! || context` (all big-endian) which is unambiguous so different labels, contexts and lengths
+//! always give independent keys.
+//!
+//! # Example
+//!
+//! ```
+//! use secp256kfun::{
+//!     ecdh::{KeyPurpose, SharedSecret, SymmetricKey},
+//!     g, Scalar, G,
+//! };
+//! use sha2::Sha256;
+//!
+//! struct Encryption;
+//! impl KeyPurpose for Encryption {
+//!     const LABEL: &'static str = "encryption";
+//! }
+//!
+//! let alice = Scalar::random(&mut rand::thread_rng());
+//! let bob = Scalar::random(&mut rand::thread_rng());
+//! let (alice_public, bob_public) = (g!(alice * G).normalize(), g!(bob * G).normalize());
+//! let alice_schedule = SharedSecret::new(&alice, &bob_public).key_schedule::<Sha256>(b"my-app/v1");
+//! let bob_schedule = SharedSecret::new(&bob, &alice_public).key_schedule::<Sha256>(b"my-app/v1");
+//! let alice_key: SymmetricKey<Encryption, 32> = alice_schedule.derive(b"session-1");
+//! let bob_key: SymmetricKey<Encryption, 32> = bob_schedule.derive(b"session-1");
+//! assert_eq!(alice_key.expose_secret(), bob_key.expose_secret());
+//! ```
+//!
+//! # Other conventions
+//!
+//! Two fixed ways of hashing the shared point are common enough to have their own functions:
+//!
+//! - [`shared_secret`] is `SHA256` of the compressed shared point which is what libsecp256k1's
+//!   `secp256k1_ecdh` does by default.
+//! - [`ellswift_shared_secret`] is the x-only ECDH between [`ellswift`] encoded public keys that
+//!   [BIP324] (Bitcoin's v2 P2P transport) uses.
+//!
+//! [HKDF]: https://datatracker.ietf.org/doc/html/rfc5869
+//! [BIP324]: https://bips.xyz/324
+//! [`ellswift`]: crate::ellswift
+use crate::{
+    ct::SecretBytes,
+    digest::{core_api::BlockSizeUser, generic_array::typenum::U32, Digest},
+    ellswift, g,
+    hash::hmac,
+    marker::*,
+    Point, Scalar,
+};
+use core::{fmt, marker::PhantomData};
+use subtle::{Choice, ConstantTimeEq};
+
+/// The point `x * P` shared by the owners of two keys.
+///
+/// The only way to use it is to derive keys from it with [`key_schedule`] or to get its encoding
+/// with [`to_secret_bytes`].
+///
+/// [`key_schedule`]: Self::key_schedule
+/// [`to_secret_bytes`]: Self::to_secret_bytes
+#[derive(Clone)]
+pub struct SharedSecret(SecretBytes<33>);
+
+impl SharedSecret {
+    /// Computes the secret shared between the owners of `secret_key` and `public_key`.
+    pub fn new(secret_key: &Scalar, public_key: &Point<impl PointType, impl Secrecy>) -> Self {
+        let shared = g!(secret_key * public_key).normalize();
+        Self(SecretBytes::new(shared.to_bytes()))
+    }
+
+    /// The compressed encoding of the shared point.
+    pub fn to_secret_bytes(&self) -> SecretBytes<33> {
+        self.0
+    }
+
+    /// Starts a key schedule from the shared secret with an application specific `salt`.
+    pub fn key_schedule<H: BlockSizeUser + Digest + Default>(&self, salt: &[u8]) -> KeySchedule<H> {
+        KeySchedule::new(salt, self.0.expose_secret())
+    }
+}
+
+impl fmt::Debug for SharedSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SharedSecret(..)")
+    }
+}
+
+/// The secret shared between the owners of `secret_key` and `public_key` hashed the way
+/// libsecp256k1's `secp256k1_ecdh` does by default: `SHA256(compressed shared point)`.
+///
+/// `H` must be SHA256 to match libsecp256k1.
+pub fn shared_secret<H: Digest<OutputSize = U32> + Default>(
+    secret_key: &Scalar,
+    public_key: &Point<impl PointType, impl Secrecy>,
+) -> SecretBytes<32> {
+    let shared = SharedSecret::new(secret_key, public_key);
+    let hash = H::default()
+        .chain_update(shared.to_secret_bytes().expose_secret())
+        .finalize();
+    SecretBytes::new(hash.into())
+}
+
+/// The [BIP324] shared secret between our ElligatorSwift encoded public key `ours` (the encoding
+/// of `secret_key * G`) and theirs.
+///
+/// The secret is the tagged hash `bip324_ellswift_xonly_ecdh` of the initiator's encoding, the
+/// responder's encoding and the x-coordinate of the shared point. `initiating` is whether we
+/// started the connection. `H` must be SHA256.
+///
+/// # Example
+///
+/// ```
+/// use secp256kfun::{ecdh, ellswift, g, Scalar, G};
+/// use sha2::Sha256;
+/// let (alice, bob) = (Scalar::random(&mut rand::thread_rng()), Scalar::random(&mut rand::thread_rng()));
+/// let alice_ell = ellswift::encode(&g!(alice * G), &mut rand::thread_rng());
+/// let bob_ell = ellswift::encode(&g!(bob * G), &mut rand::thread_rng());
+/// let alice_secret = ecdh::ellswift_shared_secret::<Sha256>(&alice, &alice_ell, &bob_ell, true);
+/// let bob_secret = ecdh::ellswift_shared_secret::<Sha256>(&bob, &bob_ell, &alice_ell, false);
+/// assert_eq!(alice_secret.expose_secret(), bob_secret.expose_secret());
+/// ```
+///
+/// [BIP324]: https://bips.xyz/324
+pub fn ellswift_shared_secret<H: Digest<OutputSize = U32> + Default>(
+    secret_key: &Scalar,
+    ours: &[u8; 64],
+    theirs: &[u8; 64],
+    initiating: bool,
+) -> SecretBytes<32> {
+    let their_key = ellswift::decode(theirs);
+    let (shared_x, _) = g!(secret_key * their_key).normalize().coordinates();
+    let (initiator, responder) = if initiating {
+        (ours, theirs)
+    } else {
+        (theirs, ours)
+    };
+    let tag = H::default()
+        .chain_update(b"bip324_ellswift_xonly_ecdh")
+        .finalize();
+    let hash = H::default()
+        .chain_update(tag)
+        .chain_update(tag)
+        .chain_update(initiator)
+        .chain_update(responder)
+        .chain_update(shared_x)
+        .finalize();
+    SecretBytes::new(hash.into())
+}
+
+/// What a [`SymmetricKey`] is for.
+pub trait KeyPurpose {
+    /// The label the key is derived with.
+    const LABEL: &'static str;
+}
+
+/// A key derived from a [`KeySchedule`] for the purpose `P`.
+pub struct SymmetricKey<P, const N: usize> {
+    bytes: SecretBytes<N>,
+    purpose: PhantomData<P>,
+}
+
+impl<P, const N: usize> SymmetricKey<P, N> {
+    /// Gets a reference to the key bytes.
+    pub fn expose_secret(&self) -> &[u8; N] {
+        self.bytes.expose_secret()
+    }
+}
+
+impl<P, const N: usize> Clone for SymmetricKey<P, N> {
+    fn clone(&self) -> Self {
+        Self {
+            bytes: self.bytes,
+            purpose: PhantomData,
+        }
+    }
+}
+
+impl<P, const N: usize> ConstantTimeEq for SymmetricKey<P, N> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.bytes.ct_eq(&other.bytes)
+    }
+}
+
+impl<P: KeyPurpose, const N: usize> fmt::Debug for SymmetricKey<P, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SymmetricKey<{:?}, {}>(..)", P::LABEL, N)
+    }
+}
+
+/// An [HKDF] pseudorandom key that symmetric keys are derived from.
+///
+/// See the [module documentation](crate::ecdh).
+///
+/// [HKDF]: https://datatracker.ietf.org/doc/html/rfc5869
+#[derive(Clone)]
+pub struct KeySchedule<H: Digest> {
+    prk: crate::digest::Output<H>,
+}
+
+impl<H: BlockSizeUser + Digest + Default> KeySchedule<H> {
+    /// Starts a key schedule from the input key material `ikm` (HKDF-Extract).
+    ///
+    /// Use [`SharedSecret::key_schedule`] for an ECDH shared secret.
+    pub fn new(salt: &[u8], ikm: &[u8]) -> Self {
+        Self {
+            prk: hmac::<H>(salt, &[ikm]),
+        }
+    }
+
+    /// Fills `okm` with HKDF-Expand of the concatenation of `info`.
+    ///
+    /// This is plain HKDF for interoperating with other key schedules. Prefer
+    /// [`derive_into`](Self::derive_into).
+    ///
+    /// # Panics
+    ///
+    /// If `okm` is longer than 255 times the output size of `H`.
+    pub fn expand(&self, info: &[&[u8]], okm: &mut [u8]) {
+        let output_size = <H as Digest>::output_size();
+        assert!(
+            okm.len() <= 255 * output_size,
+            "HKDF can't produce more than 255 blocks of output"
+        );
+        assert!(info.len() <= 6, "too many pieces of info");
+        let mut block = crate::digest::Output::<H>::default();
+        for (i, chunk) in okm.chunks_mut(output_size).enumerate() {
+            let counter = [i as u8 + 1];
+            let previous = block.clone();
+            let mut message = [&[][..]; 8];
+            if i > 0 {
+                message[0] = &previous;
+            }
+            message[1..=info.len()].copy_from_slice(info);
+            message[info.len() + 1] = &counter;
+            block = hmac::<H>(&self.prk, &message[..info.len() + 2]);
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+    }
+
+    /// Fills `okm` with key material for `label` bound to `context`.
+    ///
+    /// # Panics
+    ///
+    /// If `label` is longer than 255 bytes, `okm` is longer than 255 times the output size of `H`
+    /// or `context` is longer than `u32::MAX` bytes.
+    pub fn derive_into(&self, label: &str, context: &[u8], okm: &mut [u8]) {
+        let okm_len = u16::try_from(okm.len()).expect("okm is too long");
+        let label_len = u8::try_from(label.len()).expect("label must be at most 255 bytes");
+        let context_len = u32::try_from(context.len()).expect("context is too long");
+        self.expand(
+            &[
+                &okm_len.to_be_bytes(),
+                &[label_len],
+                label.as_bytes(),
+                &context_len.to_be_bytes(),
+                context,
+            ],
+            okm,
+        )
+    }
+
+    /// Derives an `N` byte key for the purpose `P` bound to `context`.
+    pub fn derive<P: KeyPurpose, const N: usize>(&self, context: &[u8]) -> SymmetricKey<P, N> {
+        let mut bytes = [0u8; N];
+        self.derive_into(P::LABEL, context, &mut bytes);
+        SymmetricKey {
+            bytes: SecretBytes::new(bytes),
+            purpose: PhantomData,
+        }
+    }
+}
+
+impl<H: Digest> fmt::Debug for KeySchedule<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "KeySchedule(..)")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{hash::hkdf, G};
+    use sha2::Sha256;
+
+    struct Mac;
+    impl KeyPurpose for Mac {
+        const LABEL: &'static str = "mac";
+    }
+
+    #[test]
+    fn key_schedule_separates_keys() {
+        let schedule = KeySchedule::<Sha256>::new(b"salt", b"input key material");
+        let mut okm = [0u8; 80];
+        let mut expected = [0u8; 80];
+        schedule.expand(&[b"in", b"fo"], &mut okm);
+        hkdf::<Sha256>(b"salt", b"input key material", b"info", &mut expected);
+        assert_eq!(okm, expected);
+
+        let key: SymmetricKey<Mac, 32> = schedule.derive(b"context");
+        let mut mac = [0u8; 32];
+        schedule.derive_into("mac", b"context", &mut mac);
+        assert_eq!(key.expose_secret(), &mac);
+        let mut other = [0u8; 32];
+        schedule.derive_into("encryption", b"context", &mut other);
+        assert_ne!(mac, other);
+        schedule.derive_into("mac", b"other context", &mut other);
+        assert_ne!(mac, other);
+        let longer: SymmetricKey<Mac, 64> = schedule.derive(b"context");
+        assert_ne!(&longer.expose_secret()[..32], &mac);
+        assert_eq!(format!("{:?}", key), "SymmetricKey<\"mac\", 32>(..)");
+
+        let x = Scalar::random(&mut rand::thread_rng());
+        let y = Scalar::random(&mut rand::thread_rng());
+        let shared = SharedSecret::new(&x, &g!(y * G));
+        assert_eq!(
+            shared.to_secret_bytes().into_inner(),
+            g!(x * y * G).normalize().to_bytes()
+        );
+    }
+
+    #[test]
+    fn libsecp_shared_secret() {
+        let secret = shared_secret::<Sha256>(&Scalar::from(2).non_zero().unwrap(), &g!(3 * G));
+        assert_eq!(
+            secret.into_inner(),
+            crate::hex::decode_array::<32>(
+                "c7d9ba2fa1496c81be20038e5c608f2fd5d0246d8643783730df6c2bbb855cb2"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn bip324_shared_secret() {
+        // from the BIP324 packet encoding test vectors
+        for (secret_key, ours, theirs, initiating, expected) in [
+            (
+                "61062ea5071d800bbfd59e2e8b53d47d194b095ae5a4df04936b49772ef0d4d7",
+                "ec0adff257bbfe500c188c80b4fdd640f6b45a482bbc15fc7cef5931deff0aa186f6eb9bba7b85dc4dcc28b28722de1e3d9108b985e2967045668f66098e475b",
+                "a4a94dfce69b4a2a0a099313d10f9f7e7d649d60501c9e1d274c300e0d89aafaffffffffffffffffffffffffffffffffffffffffffffffffffffffff8faf88d5",
+                true,
+                "c6992a117f5edbea70c3f511d32d26b9798be4b81a62eaee1a5acaa8459a3592",
+            ),
+            (
+                "1f9c581b35231838f0f17cf0c979835baccb7f3abbbb96ffcc318ab71e6e126f",
+                "a1855e10e94e00baa23041d916e259f7044e491da6171269694763f018c7e63693d29575dcb464ac816baa1be353ba12e3876cba7628bd0bd8e755e721eb0140",
+                "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f0000000000000000000000000000000000000000000000000000000000000000",
+                false,
+                "a0138f564f74d0ad70bc337dacc9d0bf1d2349364caf1188a1e6e8ddb3b7b184",
+            ),
+            (
+                "0286c41cd30913db0fdff7a64ebda5c8e3e7cef10f2aebc00a7650443cf4c60d",
+                "d1ee8a93a01130cbf299249a258f94feb5f469e7d0f2f28f69ee5e9aa8f9b54a60f2c3ff2d023634ec7f4127a96cc11662e402894cf1f694fb9a7eaa5f1d9244",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffff22d5e441524d571a52b3def126189d3f416890a99d4da6ede2b0cde1760ce2c3f98457ae",
+                true,
+                "250b93570d411149105ab8cb0bc5079914906306368c23e9d77c2a33265b994c",
+            ),
+            (
+                "6c77432d1fda31e9f942f8af44607e10f3ad38a65f8a4bddae823e5eff90dc38",
+                "d2685070c1e6376e633e825296634fd461fa9e5bdf2109bcebd735e5a91f3e587c5cb782abb797fbf6bb5074fd1542a474f2a45b673763ec2db7fb99b737bbb9",
+                "56bd0c06f10352c3a1a9f4b4c92f6fa2b26df124b57878353c1fc691c51abea77c8817daeeb9fa546b77c8daf79d89b22b0e1b87574ece42371f00237aa9d83a",
+                false,
+                "1918b741ef5f9d1d7670b050c152b4a4ead2c31be9aecb0681c0cd4324150853",
+            ),
+            (
+                "a6ec25127ca1aa4cf16b20084ba1e6516baae4d32422288e9b36d8bddd2de35a",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffff053d7ecca53e33e185a8b9be4e7699a97c6ff4c795522e5918ab7cd6b6884f67e683f3dc",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffa7730be30000000000000000000000000000000000000000000000000000000000000000",
+                true,
+                "dd210aa6629f20bb328e5d89daa6eb2ac3d1c658a725536ff154f31b536c23b2",
+            ),
+        ] {
+            let secret_key = Scalar::from_bytes(crate::hex::decode_array(secret_key).unwrap())
+                .and_then(|secret_key: Scalar<Secret, Zero>| secret_key.non_zero())
+                .unwrap();
+            let ours = crate::hex::decode_array(ours).unwrap();
+            let theirs = crate::hex::decode_array(theirs).unwrap();
+            // the vectors are x-only encodings so the parity of y is arbitrary
+            assert_eq!(
+                ellswift::decode_x(&ours),
+                g!(secret_key * G).normalize().coordinates().0
+            );
+            assert_eq!(
+                ellswift_shared_secret::<Sha256>(&secret_key, &ours, &theirs, initiating)
+                    .into_inner(),
+                crate::hex::decode_array::<32>(expected).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Encoding points as uniformly random looking bytes with [Elligator Squared].
+//!
+//! The usual encodings of a point are easy to pick out of a stream of bytes: a compressed point
+//! starts with `0x02` or `0x03` and only about half of all x-coordinates are on the curve. This is
+//! a problem for transports that want to be indistinguishable from random noise (e.g. to resist
+//! censorship) but still send public keys or nonces in the clear.
+//!
+//! [`encode`] turns a point into 64 bytes that are indistinguishable from uniformly random bytes
+//! and [`decode`] turns any 64 bytes back into a point. The encoding is the pair of field elements
+//! `(u, v)` where `map_to_curve(u) + map_to_curve(v)` is the point, `u` is random and `v` is
+//! chosen among the preimages of the rest so that the pair is uniformly distributed. The map is
+//! the Shallue-van de Woestijne map [`map_to_curve`].
+//!
+//! Each point has many encodings and `encode` picks one at random so encoding the same point twice
+//! gives different bytes. Encoding takes a few tries on average and isn't constant time so only
+//! encode points whose timing you don't mind leaking (e.g. a public key you are about to send).
+//!
+//! # Example
+//!
+//! ```
+//! use secp256kfun::{elligator_squared, g, Scalar, G};
+//! let point = g!({ Scalar::random(&mut rand::thread_rng()) } * G).normalize();
+//! let bytes = elligator_squared::encode(&point, &mut rand::thread_rng());
+//! assert_eq!(elligator_squared::decode(&bytes), Some(point));
+//! let other_bytes = elligator_squared::encode(&point, &mut rand::thread_rng());
+//! assert_ne!(bytes, other_bytes);
+//! assert_eq!(elligator_squared::decode(&other_bytes), Some(point));
+//! ```
+//!
+//! [Elligator Squared]: https://eprint.iacr.org/2014/043
+use crate::{backend, g, marker::*, rand_core::RngCore, Point};
+
+/// Maps a field element to a point with the Shallue-van de Woestijne map.
+///
+/// `u` is interpreted as a big-endian integer modulo the field prime `p` so any 32 bytes can be
+/// mapped. The image is about 9/16ths of the curve; it's [`decode`] that covers all of it.
+pub fn map_to_curve(u: [u8; 32]) -> Point {
+    let (x, y) = backend::svdw_map(backend::field_reduce(u));
+    point_from_coordinates(x, y)
+}
+
+fn point_from_coordinates(x: [u8; 32], y: [u8; 32]) -> Point {
+    let mut bytes = [0x04u8; 65];
+    bytes[1..33].copy_from_slice(&x);
+    bytes[33..].copy_from_slice(&y);
+    Point::from_bytes_uncompressed(bytes).expect("the map always gives a point on the curve")
+}
+
+/// Encodes `point` as 64 bytes indistinguishable from random.
+///
+/// See the [module documentation](crate::elligator_squared).
+pub fn encode(point: &Point<impl PointType, impl Secrecy>, rng: &mut impl RngCore) -> [u8; 64] {
+    loop {
+        let mut u = [0u8; 32];
+        rng.fill_bytes(&mut u);
+        let u = backend::field_reduce(u);
+        let rest = match g!(point - { map_to_curve(u) }).normalize().non_zero() {
+            Some(rest) => rest,
+            None => continue,
+        };
+        let (x, y) = rest.coordinates();
+        let preimages = backend::svdw_preimages(x, y);
+        // picking one of four slots and retrying on an empty one makes (u, v) uniform
+        if let Some(v) = preimages[(rng.next_u32() % 4) as usize] {
+            let mut bytes = [0u8; 64];
+            bytes[..32].copy_from_slice(&u);
+            bytes[32..].copy_from_slice(&v);
+            return bytes;
+        }
+    }
+}
+
+/// Decodes 64 bytes produced by [`encode`] back into a point.
+///
+/// Any 64 bytes decode to a point or, very rarely, to zero in which case this returns `None`.
+pub fn decode(bytes: &[u8; 64]) -> Option<Point> {
+    let mut u = [0u8; 32];
+    let mut v = [0u8; 32];
+    u.copy_from_slice(&bytes[..32]);
+    v.copy_from_slice(&bytes[32..]);
+    g!({ map_to_curve(u) } + { map_to_curve(v) })
+        .normalize()
+        .non_zero()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Scalar, G};
+
+    #[test]
+    fn preimages_map_back() {
+        let mut found = [0usize; 5];
+        for _ in 0..100 {
+            let mut u = [0u8; 32];
+            rand::thread_rng().fill_bytes(&mut u);
+            let point = map_to_curve(u);
+            let (x, y) = point.coordinates();
+            let preimages = backend::svdw_preimages(x, y);
+            assert!(preimages.contains(&Some(backend::field_reduce(u))));
+            for preimage in preimages.iter().flatten() {
+                assert_eq!(map_to_curve(*preimage), point);
+            }
+            found[preimages.iter().flatten().count()] += 1;
+        }
+        assert_eq!(found[0], 0);
+        assert_eq!(map_to_curve([0u8; 32]), map_to_curve([0u8; 32]));
+    }
+
+    #[test]
+    fn encode_decode_roundtrip() {
+        for _ in 0..20 {
+            let point = g!({ Scalar::random(&mut rand::thread_rng()) } * G).normalize();
+            let bytes = encode(&point, &mut rand::thread_rng());
+            assert_eq!(decode(&bytes), Some(point));
+        }
+        // every encoding decodes
+        assert!(decode(&[0xffu8; 64]).is_some());
+    }
+}
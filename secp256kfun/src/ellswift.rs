@@ -0,0 +1,147 @@
+//! The [BIP324] ElligatorSwift encoding of points as 64 uniformly random looking bytes.
+//!
+//! Like [`elligator_squared`](crate::elligator_squared) this hides public keys in a stream of
+//! random looking bytes but it's the encoding Bitcoin's v2 P2P transport uses (and
+//! libsecp256k1's `ellswift` module implements). The 64 bytes are two field elements `(u, t)`
+//! which the `XSwiftEC` map sends to an x-coordinate. The y-coordinate is the one with the same
+//! parity as `t`.
+//!
+//! [`encode`] picks a random encoding so encoding the same point twice gives different bytes. It
+//! isn't constant time in the point so only encode points whose timing you don't mind leaking
+//! (e.g. a public key you are about to send). Any 64 bytes [`decode`] to a point.
+//!
+//! The same encoding is available as [`Point::to_elligator_swift`] and
+//! [`Point::from_elligator_swift`]. For the BIP324 key exchange on top of it see
+//! [`ecdh::ellswift_shared_secret`].
+//!
+//! # Example
+//!
+//! ```
+//! use secp256kfun::{ellswift, g, Scalar, G};
+//! let point = g!({ Scalar::random(&mut rand::thread_rng()) } * G).normalize();
+//! let bytes = ellswift::encode(&point, &mut rand::thread_rng());
+//! assert_eq!(ellswift::decode(&bytes), point);
+//! assert_ne!(ellswift::encode(&point, &mut rand::thread_rng()), bytes);
+//! ```
+//!
+//! [BIP324]: https://bips.xyz/324
+//! [`ecdh::ellswift_shared_secret`]: crate::ecdh::ellswift_shared_secret
+use crate::{backend, marker::*, rand_core::RngCore, Point};
+
+fn split(bytes: &[u8; 64]) -> ([u8; 32], [u8; 32]) {
+    let mut u = [0u8; 32];
+    let mut t = [0u8; 32];
+    u.copy_from_slice(&bytes[..32]);
+    t.copy_from_slice(&bytes[32..]);
+    (backend::field_reduce(u), backend::field_reduce(t))
+}
+
+/// Encodes `point` as 64 bytes indistinguishable from random.
+///
+/// See the [module documentation](crate::ellswift).
+pub fn encode(point: &Point<impl PointType, impl Secrecy>, rng: &mut impl RngCore) -> [u8; 64] {
+    let (x, y) = point.normalize().coordinates();
+    encode_coordinates(x, y, rng)
+}
+
+pub(crate) fn encode_coordinates(x: [u8; 32], y: [u8; 32], rng: &mut impl RngCore) -> [u8; 64] {
+    loop {
+        let mut u = [0u8; 32];
+        rng.fill_bytes(&mut u);
+        let u = backend::field_reduce(u);
+        // picking one of the eight cases and retrying on an empty one makes (u, t) uniform
+        let case = (rng.next_u32() % 8) as u8;
+        if let Some(t) = backend::xswiftec_inv(x, u, case) {
+            // negating t doesn't change the x-coordinate so it can carry the y-coordinate's parity
+            let t = if backend::field_is_odd(t) == backend::field_is_odd(y) {
+                t
+            } else {
+                backend::field_negate(t)
+            };
+            let mut bytes = [0u8; 64];
+            bytes[..32].copy_from_slice(&u);
+            bytes[32..].copy_from_slice(&t);
+            return bytes;
+        }
+    }
+}
+
+/// Decodes the x-coordinate of 64 bytes produced by [`encode`] (or anything else).
+///
+/// Each half is interpreted as a big-endian integer modulo the field prime so any 64 bytes decode.
+pub fn decode_x(bytes: &[u8; 64]) -> [u8; 32] {
+    let (u, t) = split(bytes);
+    backend::xswiftec(u, t)
+}
+
+/// Decodes 64 bytes produced by [`encode`] (or anything else) into a point.
+pub fn decode(bytes: &[u8; 64]) -> Point {
+    let (_, t) = split(bytes);
+    let mut compressed = [0u8; 33];
+    compressed[0] = 0x02 | backend::field_is_odd(t) as u8;
+    compressed[1..].copy_from_slice(&decode_x(bytes));
+    Point::from_bytes(compressed).expect("XSwiftEC always gives an x-coordinate on the curve")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{g, hex, Scalar, G};
+
+    #[test]
+    fn encode_decode_roundtrip() {
+        for _ in 0..20 {
+            let point = g!({ Scalar::random(&mut rand::thread_rng()) } * G).normalize();
+            let bytes = encode(&point, &mut rand::thread_rng());
+            assert_eq!(decode(&bytes), point);
+            assert_eq!(decode_x(&bytes), point.coordinates().0);
+        }
+    }
+
+    #[test]
+    fn bip324_decode_vectors() {
+        // from the BIP324 ellswift decoding test vectors
+        for (encoding, x, odd_y) in [
+            (
+                "00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+                "edd1fd3e327ce90cc7a3542614289aee9682003e9cf7dcc9cf2ca9743be5aa0c",
+                false,
+            ),
+            (
+                "000000000000000000000000000000000000000000000000000000000000000001d3475bf7655b0fb2d852921035b2ef607f49069b97454e6795251062741771",
+                "b5da00b73cd6560520e7c364086e7cd23a34bf60d0e707be9fc34d4cd5fdfa2c",
+                true,
+            ),
+            (
+                "0000000000000000000000000000000000000000000000000000000000000000fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+                "edd1fd3e327ce90cc7a3542614289aee9682003e9cf7dcc9cf2ca9743be5aa0c",
+                false,
+            ),
+            (
+                "0000000000000000000000000000000000000000000000000000000000000000ffffffffffffffffffffffffffffffffffffffffffffffffffffffff2664bbd5",
+                "50873db31badcc71890e4f67753a65757f97aaa7dd5f1e82b753ace32219064b",
+                false,
+            ),
+            (
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffef64d162750546ce42b0431361e52d4f5242d8f24f33e6b1f99b591647cbc808f462af51",
+                "d41244d11ca4f65240687759f95ca9efbab767ededb38fd18c36e18cd3b6f6a9",
+                true,
+            ),
+            (
+                "fffffffffffffffffffffffffffffffffffffffffffffffffffffffff0e5be52372dd6e894b2a326fc3605a6e8f3c69c710bf27d630dfe2004988b78eb6eab36",
+                "64bf84dd5e03670fdb24c0f5d3c2c365736f51db6c92d95010716ad2d36134c8",
+                false,
+            ),
+            (
+                "fffffffffffffffffffffffffffffffffffffffffffffffffffffffffefbb982fffffffffffffffffffffffffffffffffffffffffffffffffffffffff6d6db1f",
+                "1c92ccdfcf4ac550c28db57cff0c8515cb26936c786584a70114008d6c33a34b",
+                false,
+            ),
+        ] {
+            let point = decode(&hex::decode_array(encoding).unwrap());
+            let (point_x, point_y) = point.coordinates();
+            assert_eq!(point_x, hex::decode_array::<32>(x).unwrap());
+            assert_eq!(point_y[31] & 1 == 1, odd_y);
+        }
+    }
+}
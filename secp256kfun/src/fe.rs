@@ -0,0 +1,108 @@
+//! Arithmetic on secp256k1 field elements.
+//!
+//! Most of the time you shouldn't need to think about the field the curve is defined over but a
+//! few things do: older Schnorr variants (like the pre-[BIP340] drafts) pick the nonce point `R`
+//! whose y-coordinate is a quadratic residue (a square) rather than being even, and adapters to
+//! other libraries sometimes need to recover a y-coordinate themselves.
+//!
+//! Field elements are passed around as their 32 byte big-endian encoding. Encodings of integers
+//! greater than or equal to the field prime `p` are rejected.
+//!
+//! # Example
+//!
+//! Finding the point with a square y-coordinate for an x-coordinate:
+//!
+//! ```
+//! use secp256kfun::{fe, g, Point, Scalar, G};
+//! let R = g!({ Scalar::random(&mut rand::thread_rng()) } * G).normalize();
+//! let (x, _) = R.coordinates();
+//! let y = fe::y_from_x(x).unwrap();
+//! assert!(fe::is_square(y));
+//! let mut bytes = [0x04u8; 65];
+//! bytes[1..33].copy_from_slice(&x);
+//! bytes[33..].copy_from_slice(&y);
+//! let square_y_R = Point::from_bytes_uncompressed(bytes).unwrap();
+//! assert!(square_y_R.eq_up_to_parity(&R));
+//! ```
+//!
+//! [BIP340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+use crate::backend;
+
+/// Computes a square root of `x` modulo `p`.
+///
+/// Every square has two roots; this always returns the one that is itself a square (the other one
+/// is `p` minus it). Returns `None` if `x` isn't a square or isn't a valid field element.
+///
+/// # Example
+///
+/// ```
+/// use secp256kfun::fe;
+/// let mut four = [0u8; 32];
+/// four[31] = 4;
+/// let mut two = [0u8; 32];
+/// two[31] = 2;
+/// assert_eq!(fe::sqrt(four), Some(two));
+/// ```
+pub fn sqrt(x: [u8; 32]) -> Option<[u8; 32]> {
+    backend::field_sqrt(x)
+}
+
+/// Returns whether `x` is a square (a.k.a. quadratic residue) modulo `p`.
+///
+/// Zero counts as a square. Invalid field elements are not squares.
+pub fn is_square(x: [u8; 32]) -> bool {
+    sqrt(x).is_some()
+}
+
+/// Recovers the y-coordinate of the curve point with x-coordinate `x`.
+///
+/// Of the two possible y-coordinates this returns the one that is a square. The other one is its
+/// negation which has the opposite parity. If you want the even or odd one instead use
+/// [`Point::from_xonly_bytes`] or [`Point::from_bytes`].
+///
+/// Returns `None` if there is no point on the curve with that x-coordinate.
+///
+/// [`Point::from_xonly_bytes`]: crate::Point::from_xonly_bytes
+/// [`Point::from_bytes`]: crate::Point::from_bytes
+pub fn y_from_x(x: [u8; 32]) -> Option<[u8; 32]> {
+    sqrt(backend::curve_rhs(x)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{g, hex, marker::*, Point, Scalar, G};
+
+    #[test]
+    fn y_recovery() {
+        for _ in 0..20 {
+            let point = g!({ Scalar::random(&mut rand::thread_rng()) } * G).normalize();
+            let (x, y) = point.coordinates();
+            let square_y = y_from_x(x).unwrap();
+            assert!(is_square(square_y));
+            let (_, neg_y) = (-point).coordinates();
+            assert_eq!(is_square(y), y == square_y);
+            assert_eq!(is_square(neg_y), neg_y == square_y);
+            assert!(square_y == y || square_y == neg_y);
+        }
+        // x = 5 is not on the curve since 5³ + 7 = 132 is not a square
+        let mut five = [0u8; 32];
+        five[31] = 5;
+        assert_eq!(y_from_x(five), None);
+        assert_eq!(Point::<EvenY>::from_xonly_bytes(five), None);
+    }
+
+    #[test]
+    fn sqrt_edge_cases() {
+        assert_eq!(sqrt([0u8; 32]), Some([0u8; 32]));
+        // p - 1 = -1 is not a square since p ≡ 3 mod 4
+        let minus_one =
+            hex::decode_array("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e")
+                .unwrap();
+        assert!(!is_square(minus_one));
+        let p =
+            hex::decode_array("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f")
+                .unwrap();
+        assert!(!is_square(p));
+    }
+}
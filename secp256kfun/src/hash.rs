@@ -5,8 +5,12 @@
 //! [`Digest`]: digest::Digest
 //! [`RustCrypto`]: https://github.com/RustCrypto/hashes
 use crate::digest::{
+    self,
     crypto_common::BlockSizeUser,
-    generic_array::typenum::{PartialDiv, Unsigned},
+    generic_array::{
+        typenum::{PartialDiv, Unsigned},
+        GenericArray,
+    },
     Digest,
 };
 /// Extension trait for some cryptotraphic function that can be domain separated by a tag.
@@ -150,3 +154,147 @@ impl<D: Digest> HashAdd for D {
         self
     }
 }
+
+/// Computes the [HMAC] of `message` under `key`.
+///
+/// `message` is split into pieces that are concatenated.
+///
+/// [HMAC]: https://datatracker.ietf.org/doc/html/rfc2104
+pub fn hmac<H: BlockSizeUser + Digest + Default>(
+    key: &[u8],
+    message: &[&[u8]],
+) -> digest::Output<H> {
+    let mut padded_key = GenericArray::<u8, H::BlockSize>::default();
+    if key.len() > padded_key.len() {
+        let hashed_key = H::digest(key);
+        padded_key[..hashed_key.len()].copy_from_slice(&hashed_key);
+    } else {
+        padded_key[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = H::default();
+    inner.update(
+        padded_key
+            .iter()
+            .map(|byte| byte ^ 0x36)
+            .collect::<GenericArray<u8, H::BlockSize>>(),
+    );
+    for piece in message {
+        inner.update(piece);
+    }
+    let inner = inner.finalize();
+
+    let mut outer = H::default();
+    outer.update(
+        padded_key
+            .iter()
+            .map(|byte| byte ^ 0x5c)
+            .collect::<GenericArray<u8, H::BlockSize>>(),
+    );
+    outer.update(inner);
+    outer.finalize()
+}
+
+/// Fills `okm` with key material derived with [HKDF] from the input key material `ikm`.
+///
+/// # Panics
+///
+/// If `okm` is longer than 255 times the output size of `H`.
+///
+/// # Example
+///
+/// Test case 1 from [RFC 5869](https://datatracker.ietf.org/doc/html/rfc5869#appendix-A.1):
+///
+/// ```
+/// use secp256kfun::{hash::hkdf, hex};
+/// let mut okm = [0u8; 42];
+/// hkdf::<sha2::Sha256>(
+///     &hex::decode("000102030405060708090a0b0c").unwrap(),
+///     &[0x0b; 22],
+///     &hex::decode("f0f1f2f3f4f5f6f7f8f9").unwrap(),
+///     &mut okm,
+/// );
+/// assert_eq!(
+///     hex::encode(&okm),
+///     "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+/// );
+/// ```
+///
+/// [HKDF]: https://datatracker.ietf.org/doc/html/rfc5869
+pub fn hkdf<H: BlockSizeUser + Digest + Default>(
+    salt: &[u8],
+    ikm: &[u8],
+    info: &[u8],
+    okm: &mut [u8],
+) {
+    let output_size = <H as Digest>::output_size();
+    assert!(
+        okm.len() <= 255 * output_size,
+        "HKDF can't produce more than 255 blocks of output"
+    );
+    let prk = hmac::<H>(salt, &[ikm]);
+    let mut block = digest::Output::<H>::default();
+    for (i, chunk) in okm.chunks_mut(output_size).enumerate() {
+        let previous: &[u8] = if i == 0 { &[] } else { &block };
+        block = hmac::<H>(&prk, &[previous, info, &[i as u8 + 1]]);
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+}
+
+/// Fills `out` with a key derived from `password` and `salt` with [PBKDF2] using HMAC-`H` as the
+/// pseudorandom function.
+///
+/// # Panics
+///
+/// If `iterations` is `0`.
+///
+/// # Example
+///
+/// The PBKDF2-HMAC-SHA256 test vector from [RFC 7914](https://datatracker.ietf.org/doc/html/rfc7914#section-11):
+///
+/// ```
+/// use secp256kfun::{hash::pbkdf2, hex};
+/// let mut out = [0u8; 64];
+/// pbkdf2::<sha2::Sha256>(b"passwd", b"salt", 1, &mut out);
+/// assert_eq!(
+///     hex::encode(&out),
+///     "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+/// );
+/// ```
+///
+/// [PBKDF2]: https://datatracker.ietf.org/doc/html/rfc8018#section-5.2
+pub fn pbkdf2<H: BlockSizeUser + Digest + Default>(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    out: &mut [u8],
+) {
+    assert!(iterations > 0, "PBKDF2 needs at least one iteration");
+    for (i, chunk) in out.chunks_mut(<H as Digest>::output_size()).enumerate() {
+        let mut u = hmac::<H>(password, &[salt, &(i as u32 + 1).to_be_bytes()]);
+        let mut block = u.clone();
+        for _ in 1..iterations {
+            u = hmac::<H>(password, &[&u]);
+            for (byte, u_byte) in block.iter_mut().zip(&u) {
+                *byte ^= u_byte;
+            }
+        }
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pbkdf2_many_iterations() {
+        // checked against python's hashlib.pbkdf2_hmac
+        let mut out = [0u8; 32];
+        pbkdf2::<sha2::Sha256>(b"password", b"salt", 4096, &mut out);
+        assert_eq!(
+            crate::hex::encode(&out),
+            "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a"
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Hashing to the curve as specified in [RFC 9380].
+//!
+//! [`hash_to_curve`] hashes a message to a point whose discrete logarithm nobody knows. This is
+//! what you want for a second generator for Pedersen commitments or for the "hash to a point" step
+//! of a VRF. Unlike hashing to an x-coordinate and incrementing until it's on the curve it takes
+//! the same time for every message and gives the same point as every other implementation of the
+//! `secp256k1_XMD:SHA-256_SSWU_RO_` suite (when `H` is SHA256).
+//!
+//! The domain separation tag `dst` should be unique to your application and its use of the
+//! function (see [section 3.1] of the RFC).
+//!
+//! # Example
+//!
+//! ```
+//! use secp256kfun::{g, marker::*, Point, Scalar, G};
+//! use sha2::Sha256;
+//! let H = Point::hash_to_curve::<Sha256>(b"", b"MYAPP-V01-CS01-with-secp256k1_XMD:SHA-256_SSWU_RO_");
+//! // a Pedersen commitment to 42 with a random blinding factor
+//! let blinding = Scalar::random(&mut rand::thread_rng());
+//! let value = Scalar::<Secret, Zero>::from(42u32);
+//! let commitment = g!(value * G + blinding * H).normalize();
+//! ```
+//!
+//! [RFC 9380]: https://www.rfc-editor.org/rfc/rfc9380.html
+//! [section 3.1]: https://www.rfc-editor.org/rfc/rfc9380.html#section-3.1
+use crate::{backend, g, Point};
+use digest::{crypto_common::BlockSizeUser, Digest};
+
+/// Fills `output` with `expand_message_xmd` of `msg` and `dst` ([section 5.3.1] of RFC 9380).
+///
+/// Tags longer than 255 bytes are hashed first as the RFC requires.
+///
+/// # Panics
+///
+/// If `output` is longer than 255 hash outputs or 65535 bytes.
+///
+/// [section 5.3.1]: https://www.rfc-editor.org/rfc/rfc9380.html#section-5.3.1
+pub fn expand_message_xmd<H: BlockSizeUser + Digest + Default>(
+    msg: &[u8],
+    dst: &[u8],
+    output: &mut [u8],
+) {
+    let b_in_bytes = <H as Digest>::output_size();
+    let ell = (output.len() + b_in_bytes - 1) / b_in_bytes;
+    assert!(
+        ell <= 255 && output.len() <= u16::MAX as usize,
+        "expand_message_xmd output too long"
+    );
+    let oversize_dst;
+    let dst = if dst.len() > 255 {
+        oversize_dst = H::default()
+            .chain_update(b"H2C-OVERSIZE-DST-")
+            .chain_update(dst)
+            .finalize();
+        &oversize_dst[..]
+    } else {
+        dst
+    };
+    let dst_prime = [dst.len() as u8];
+
+    let mut b_0 = H::default();
+    for _ in 0..H::block_size() {
+        b_0.update([0u8]);
+    }
+    let b_0 = b_0
+        .chain_update(msg)
+        .chain_update((output.len() as u16).to_be_bytes())
+        .chain_update([0u8])
+        .chain_update(dst)
+        .chain_update(dst_prime)
+        .finalize();
+
+    let mut b_i = H::default()
+        .chain_update(&b_0)
+        .chain_update([1u8])
+        .chain_update(dst)
+        .chain_update(dst_prime)
+        .finalize();
+    for (i, chunk) in output.chunks_mut(b_in_bytes).enumerate() {
+        if i > 0 {
+            let mut xored = b_0.clone();
+            for (x, b) in xored.iter_mut().zip(b_i.iter()) {
+                *x ^= b;
+            }
+            b_i = H::default()
+                .chain_update(xored)
+                .chain_update([i as u8 + 1])
+                .chain_update(dst)
+                .chain_update(dst_prime)
+                .finalize();
+        }
+        chunk.copy_from_slice(&b_i[..chunk.len()]);
+    }
+}
+
+/// Hashes `msg` to two field elements with `hash_to_field` ([section 5.2] of RFC 9380) using
+/// [`expand_message_xmd`].
+///
+/// The field elements are returned as their 32 byte big-endian encoding.
+///
+/// [section 5.2]: https://www.rfc-editor.org/rfc/rfc9380.html#section-5.2
+pub fn hash_to_field<H: BlockSizeUser + Digest + Default>(msg: &[u8], dst: &[u8]) -> [[u8; 32]; 2] {
+    // each element is 48 bytes so that reducing them modulo p is close to uniform
+    let mut uniform_bytes = [0u8; 96];
+    expand_message_xmd::<H>(msg, dst, &mut uniform_bytes);
+    let mut u = [[0u8; 32]; 2];
+    for (u, bytes) in u.iter_mut().zip(uniform_bytes.chunks(48)) {
+        let mut wide = [0u8; 48];
+        wide.copy_from_slice(bytes);
+        *u = backend::field_reduce_wide(wide);
+    }
+    u
+}
+
+/// Maps a field element to a point with the simplified SWU map for secp256k1 ([section 6.6.3] of
+/// RFC 9380).
+///
+/// `u` is interpreted as a big-endian integer modulo the field prime `p`. On its own the map only
+/// reaches about half the curve and its output is easy to tell apart from a random point; use
+/// [`hash_to_curve`] unless you know you need this.
+///
+/// [section 6.6.3]: https://www.rfc-editor.org/rfc/rfc9380.html#section-6.6.3
+pub fn map_to_curve(u: [u8; 32]) -> Point {
+    let (x, y) = backend::sswu_map(backend::field_reduce(u));
+    let mut bytes = [0x04u8; 65];
+    bytes[1..33].copy_from_slice(&x);
+    bytes[33..].copy_from_slice(&y);
+    Point::from_bytes_uncompressed(bytes).expect("the map always gives a point on the curve")
+}
+
+/// Hashes `msg` to a point with `hash_to_curve` ([section 3] of RFC 9380).
+///
+/// With SHA256 as `H` this is the `secp256k1_XMD:SHA-256_SSWU_RO_` suite. See the [module
+/// documentation](crate::hash_to_curve).
+///
+/// [section 3]: https://www.rfc-editor.org/rfc/rfc9380.html#section-3
+pub fn hash_to_curve<H: BlockSizeUser + Digest + Default>(msg: &[u8], dst: &[u8]) -> Point {
+    let [u0, u1] = hash_to_field::<H>(msg, dst);
+    // secp256k1's cofactor is 1 so there's nothing to clear
+    g!({ map_to_curve(u0) } + { map_to_curve(u1) })
+        .normalize()
+        .non_zero()
+        .expect("finding a message that hashes to zero is as hard as breaking the hash")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hex;
+    use sha2::Sha256;
+
+    #[test]
+    fn expand_message_xmd_vectors() {
+        // RFC 9380 K.1
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        for (msg, expected) in [
+            (
+                &b""[..],
+                "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235",
+            ),
+            (
+                &b"abc"[..],
+                "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615",
+            ),
+        ] {
+            let mut output = [0u8; 32];
+            expand_message_xmd::<Sha256>(msg, dst, &mut output);
+            assert_eq!(output, hex::decode_array::<32>(expected).unwrap());
+        }
+    }
+
+    #[test]
+    fn hash_to_curve_vectors() {
+        // RFC 9380 J.8.1
+        let dst = b"QUUX-V01-CS02-with-secp256k1_XMD:SHA-256_SSWU_RO_";
+        let q128 = [&b"q128_"[..], &[b'q'; 128]].concat();
+        let a512 = [&b"a512_"[..], &[b'a'; 512]].concat();
+        for (msg, x, y) in [
+            (
+                &b""[..],
+                "c1cae290e291aee617ebaef1be6d73861479c48b841eaba9b7b5852ddfeb1346",
+                "64fa678e07ae116126f08b022a94af6de15985c996c3a91b64c406a960e51067",
+            ),
+            (
+                &b"abc"[..],
+                "3377e01eab42db296b512293120c6cee72b6ecf9f9205760bd9ff11fb3cb2c4b",
+                "7f95890f33efebd1044d382a01b1bee0900fb6116f94688d487c6c7b9c8371f6",
+            ),
+            (
+                &b"abcdef0123456789"[..],
+                "bac54083f293f1fe08e4a70137260aa90783a5cb84d3f35848b324d0674b0e3a",
+                "4436476085d4c3c4508b60fcf4389c40176adce756b398bdee27bca19758d828",
+            ),
+            (
+                &q128[..],
+                "e2167bc785333a37aa562f021f1e881defb853839babf52a7f72b102e41890e9",
+                "f2401dd95cc35867ffed4f367cd564763719fbc6a53e969fb8496a1e6685d873",
+            ),
+            (
+                &a512[..],
+                "e3c8d35aaaf0b9b647e88a0a0a7ee5d5bed5ad38238152e4e6fd8c1f8cb7c998",
+                "8446eeb6181bf12f56a9d24e262221cc2f0c4725c7e3803024b5888ee5823aa6",
+            ),
+        ] {
+            let point = hash_to_curve::<Sha256>(msg, dst);
+            assert_eq!(
+                point.coordinates(),
+                (
+                    hex::decode_array::<32>(x).unwrap(),
+                    hex::decode_array::<32>(y).unwrap()
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn map_to_curve_exceptional_case() {
+        // u = 0 (and p) take the branch where the SWU denominator is zero
+        let expected = (
+            hex::decode_array::<32>(
+                "bf6ce2abc92f03c7abfb18752134acc036b8e8ef46a7ed2634a86727c12d6ac1",
+            )
+            .unwrap(),
+            hex::decode_array::<32>(
+                "cb18d77a942ce3413cfb072b4f6c28b51ee64786e67fa94cf7b24de22d281a15",
+            )
+            .unwrap(),
+        );
+        assert_eq!(map_to_curve([0u8; 32]).coordinates(), expected);
+        let p = hex::decode_array::<32>(
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+        )
+        .unwrap();
+        assert_eq!(map_to_curve(p).coordinates(), expected);
+    }
+}
@@ -98,3 +98,69 @@ pub fn decode_array<const N: usize>(hex: &str) -> Result<[u8; N], HexError> {
     }
     Ok(bytes)
 }
+
+/// Decode some hex bytes into a fixed length array in constant time.
+///
+/// Unlike [`decode_array`] the time it takes only depends on the length of `hex` and not on the
+/// bytes it encodes. This is what the `FromStr` and human-readable `Deserialize` implementations in
+/// this library use so that loading secret scalars from a config file or keystore doesn't leak them
+/// through timing.
+///
+/// # Examples
+/// ```
+/// use secp256kfun::hex;
+/// let bytes: [u8; 4] = hex::decode_array_ct("deadBEEF").unwrap();
+/// assert_eq!(bytes, [0xde, 0xad, 0xbe, 0xef]);
+/// assert_eq!(hex::decode_array_ct::<2>("0g00"), Err(hex::HexError::InvalidHex));
+/// ```
+pub fn decode_array_ct<const N: usize>(hex: &str) -> Result<[u8; N], HexError> {
+    let mut bytes = [0u8; N];
+    if hex.len() % 2 != 0 {
+        return Err(HexError::InvalidHex);
+    }
+    if hex.len() != N * 2 {
+        return Err(HexError::InvalidLength);
+    }
+    let mut invalid = 0u16;
+    for (hex_byte, byte) in hex.as_bytes().chunks(2).zip(bytes.iter_mut()) {
+        let high = hex_val_ct(hex_byte[0]);
+        let low = hex_val_ct(hex_byte[1]);
+        invalid |= (high | low) & 0xff00;
+        *byte = ((high << 4) | low) as u8;
+    }
+    if invalid != 0 {
+        return Err(HexError::InvalidHex);
+    }
+    Ok(bytes)
+}
+
+/// Returns the value of the hex digit `c` or `0xffff` if it isn't one without branching on `c`.
+///
+/// Each range check is done with the sign bit of a subtraction (see
+/// <https://github.com/RustCrypto/formats/tree/master/base16ct>).
+fn hex_val_ct(c: u8) -> u16 {
+    let c = c as i16;
+    let mut value: i16 = -1;
+    // 0-9
+    value += (((0x2f - c) & (c - 0x3a)) >> 8) & (c - 0x2f);
+    // A-F
+    value += (((0x40 - c) & (c - 0x47)) >> 8) & (c - 0x36);
+    // a-f
+    value += (((0x60 - c) & (c - 0x67)) >> 8) & (c - 0x56);
+    value as u16
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hex_val_ct_matches_hex_val() {
+        for c in 0..=u8::MAX {
+            match hex_val(c) {
+                Ok(value) => assert_eq!(hex_val_ct(c), value as u16),
+                Err(_) => assert_eq!(hex_val_ct(c), 0xffff),
+            }
+        }
+    }
+}
@@ -1,4 +1,10 @@
-use crate::{g, marker::*, Point, Scalar, G};
+use crate::{
+    digest::{generic_array::typenum::U32, Digest},
+    g,
+    hash::{HashAdd, Tag},
+    marker::*,
+    s, Point, Scalar, G,
+};
 /// A secret and public key pair.
 ///
 /// ## Synopsis
@@ -87,6 +93,43 @@ impl KeyPair<EvenY> {
         let pk = Point::even_y_from_scalar_mul(G, &mut secret_key);
         Self { sk: secret_key, pk }
     }
+
+    /// Tweaks the keypair by adding `tweak` to the secret key and `tweak * G` to the x-only public
+    /// key.
+    ///
+    /// The secret key is negated afterwards if need be so the public key has an even
+    /// y-coordinate. This is how [BIP341] tweaks the internal key into the output key. The public
+    /// key is the same as [`Point::tweak_add`] on the original public key. Returns `None` if the
+    /// result is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use secp256kfun::{marker::*, KeyPair, Scalar};
+    /// let keypair = KeyPair::<EvenY>::new(Scalar::random(&mut rand::thread_rng()));
+    /// let tweak = Scalar::random(&mut rand::thread_rng());
+    /// let tweaked = keypair.clone().tweak(tweak).unwrap();
+    /// assert_eq!(
+    ///     keypair.public_key().tweak_add(&tweak).unwrap().0,
+    ///     tweaked.public_key()
+    /// );
+    /// ```
+    ///
+    /// [BIP341]: https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+    pub fn tweak(self, tweak: Scalar<impl Secrecy, impl ZeroChoice>) -> Option<Self> {
+        let secret_key = s!(self.sk + tweak).non_zero()?;
+        Some(Self::new(secret_key))
+    }
+}
+
+impl KeyPair<Normal> {
+    /// Tweaks the keypair by adding `tweak` to the secret key and `tweak * G` to the public key.
+    ///
+    /// Returns `None` if the result is zero.
+    pub fn tweak(self, tweak: Scalar<impl Secrecy, impl ZeroChoice>) -> Option<Self> {
+        let secret_key = s!(self.sk + tweak).non_zero()?;
+        Some(Self::new(secret_key))
+    }
 }
 
 impl<T> KeyPair<T> {
@@ -116,6 +159,36 @@ impl<T> KeyPair<T> {
     {
         (&self.sk, self.pk)
     }
+
+    /// Derives an independent keypair for `purpose` from this keypair's secret key.
+    ///
+    /// The subkey's secret key is the tagged hash of the secret key and `purpose` so keys for
+    /// different purposes (e.g. `"identity"`, `"signing"` and `"ecdh"`) can't be linked to each
+    /// other or to this keypair without the secret key. One stored secret can back several protocol
+    /// roles this way rather than the same key being used in all of them.
+    ///
+    /// # Example
+    /// ```
+    /// use secp256kfun::{marker::*, KeyPair, Scalar};
+    /// let master = KeyPair::<Normal>::new(Scalar::random(&mut rand::thread_rng()));
+    /// let identity = master.derive_subkey::<sha2::Sha256>("identity");
+    /// let signing = KeyPair::<EvenY>::from(master.clone()).derive_subkey::<sha2::Sha256>("signing");
+    /// assert_ne!(identity.public_key(), master.public_key());
+    /// assert_eq!(identity, master.derive_subkey::<sha2::Sha256>("identity"));
+    /// assert!(signing.public_key().is_y_even());
+    /// ```
+    pub fn derive_subkey<H: Tag + Digest<OutputSize = U32> + Default>(&self, purpose: &str) -> Self
+    where
+        Self: From<KeyPair<Normal>>,
+    {
+        let secret_key = Scalar::from_hash(
+            H::default()
+                .tag(b"secp256kfun/subkey")
+                .add(self.sk)
+                .add(purpose),
+        );
+        KeyPair::<Normal>::new(secret_key).into()
+    }
 }
 
 impl From<KeyPair<EvenY>> for KeyPair<Normal> {
@@ -138,7 +211,7 @@ impl From<KeyPair<Normal>> for KeyPair<EvenY> {
 
 crate::impl_serialize! {
     fn to_bytes<T>(kp: &KeyPair<T>) -> [u8;32] {
-        kp.secret_key().to_bytes()
+        kp.secret_key().raw_bytes()
     }
 }
 
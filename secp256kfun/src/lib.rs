@@ -14,21 +14,62 @@ extern crate alloc;
 #[macro_use]
 extern crate std;
 
+#[cfg(feature = "alloc_audit")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc_audit")))]
+pub mod alloc_audit;
+pub mod bip32;
+pub mod coin_flip;
+pub mod ct;
+pub mod dleq;
+pub mod ecdh;
+#[cfg(feature = "ecies")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ecies")))]
+pub mod ecies;
+pub mod elligator_squared;
+pub mod ellswift;
+pub mod fe;
 pub mod hash;
+pub mod hash_to_curve;
 pub mod hex;
 pub mod nonce;
+#[cfg(feature = "pkcs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "pkcs")))]
+pub mod pkcs;
+pub mod prelude;
+pub mod prf;
+pub mod roles;
+#[cfg(all(feature = "mlock", unix))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "mlock", unix))))]
+pub mod secret_box;
+pub mod shuffle;
+pub mod signature;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod tag_registry;
 
-#[cfg(feature = "alloc")]
 pub mod poly;
 
+#[cfg(feature = "alloc")]
+pub mod point_set;
+
+#[cfg(feature = "alloc")]
+pub mod merkle;
+
+#[cfg(feature = "alloc")]
+pub mod self_test;
+
 pub use digest;
 pub use rand_core;
 pub use subtle;
 
+mod compressed_point;
 mod keypair;
+#[cfg(feature = "serde")]
+mod parity_point;
 mod point;
 mod scalar;
 mod slice;
+mod small_scalar;
 
 mod vendor;
 
@@ -38,10 +79,15 @@ mod backend;
 pub mod marker;
 pub mod op;
 
+pub use compressed_point::CompressedPoint;
 pub use keypair::*;
-pub use point::Point;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub use parity_point::{Parity, ParityPoint};
+pub use point::{Point, ZeroPointError};
 pub use scalar::Scalar;
-pub use slice::Slice;
+pub use slice::{FromSliceError, Slice};
+pub use small_scalar::{OutOfRange, SmallScalar};
 
 /// Re-export `serde`
 #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
@@ -53,6 +99,11 @@ pub use serde;
 #[cfg(feature = "bincode")]
 pub use bincode;
 
+/// Re-export `zeroize`
+#[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
+#[cfg(feature = "zeroize")]
+pub use zeroize;
+
 #[doc(hidden)]
 /// these are helpers so we hide them. Actual g! macro is defined in macros.rs
 pub use secp256kfun_arithmetic_macros as arithmetic_macros;
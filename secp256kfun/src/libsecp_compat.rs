@@ -6,7 +6,7 @@ mod v0_28 {
 
     impl From<Scalar> for SecretKey {
         fn from(scalar: Scalar) -> Self {
-            SecretKey::from_slice(scalar.to_bytes().as_ref()).unwrap()
+            SecretKey::from_slice(scalar.raw_bytes().as_ref()).unwrap()
         }
     }
 
@@ -109,7 +109,7 @@ mod v0_27 {
 
     impl From<Scalar> for SecretKey {
         fn from(scalar: Scalar) -> Self {
-            SecretKey::from_slice(scalar.to_bytes().as_ref()).unwrap()
+            SecretKey::from_slice(scalar.raw_bytes().as_ref()).unwrap()
         }
     }
 
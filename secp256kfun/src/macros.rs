@@ -137,6 +137,7 @@ macro_rules! derive_nonce {
         use $crate::hash::HashAdd;
         #[allow(unused_imports)]
         use core::borrow::Borrow;
+        #[allow(unused_imports)]
         use $crate::nonce::NonceGen;
         Scalar::from_hash(
             $nonce_gen.begin_derivation($secret.borrow())$(.add($public))+
@@ -186,6 +187,80 @@ macro_rules! derive_nonce_rng {
     }}
 }
 
+/// Asserts that two [`Point`]s are equal.
+///
+/// Works like [`assert_eq!`] except that on failure it also prints the difference `left - right`
+/// which is often the quickest way to find which term of a protocol's equation is wrong. The points
+/// can have any markers.
+///
+/// # Examples
+///
+/// ```
+/// use secp256kfun::{assert_point_eq, g, Scalar, G};
+/// let x = Scalar::random(&mut rand::thread_rng());
+/// assert_point_eq!(g!(x * G + x * G), g!(2 * x * G));
+/// assert_point_eq!(g!(x * G - x * G), g!(0 * G), "points cancel for {}", x);
+/// ```
+///
+/// [`Point`]: crate::Point
+#[macro_export]
+macro_rules! assert_point_eq {
+    (@inner $left:expr, $right:expr, $($message:tt)+) => {{
+        let (left, right) = (&$left, &$right);
+        if left != right {
+            panic!(
+                "assertion `left == right` failed{}\n  left: {:?}\n right: {:?}\n  diff: {:?} (left - right)",
+                format_args!($($message)+),
+                left,
+                right,
+                $crate::g!(left - right).normalize()
+            );
+        }
+    }};
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::assert_point_eq!(@inner $left, $right, "")
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::assert_point_eq!(@inner $left, $right, ": {}", format_args!($($arg)+))
+    };
+}
+
+/// Asserts that two [`Scalar`]s are equal.
+///
+/// Works like [`assert_eq!`] except that on failure it also prints the difference `left - right`.
+/// The scalars can have any markers.
+///
+/// # Examples
+///
+/// ```
+/// use secp256kfun::{assert_scalar_eq, s, Scalar};
+/// let x = Scalar::random(&mut rand::thread_rng());
+/// assert_scalar_eq!(s!(x + x), s!(2 * x));
+/// ```
+///
+/// [`Scalar`]: crate::Scalar
+#[macro_export]
+macro_rules! assert_scalar_eq {
+    (@inner $left:expr, $right:expr, $($message:tt)+) => {{
+        let (left, right) = (&$left, &$right);
+        if left != right {
+            panic!(
+                "assertion `left == right` failed{}\n  left: {:?}\n right: {:?}\n  diff: {:?} (left - right)",
+                format_args!($($message)+),
+                left,
+                right,
+                $crate::s!(left - right)
+            );
+        }
+    }};
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::assert_scalar_eq!(@inner $left, $right, "")
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::assert_scalar_eq!(@inner $left, $right, ": {}", format_args!($($arg)+))
+    };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! impl_debug {
@@ -255,6 +330,45 @@ macro_rules! impl_display {
     }
 }
 
+/// Implements a binary operator for every combination of owned and borrowed operands.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! impl_binary_op {
+    (impl<$($tp:ident),*> $trait:ident<$rhs:ty>::$method:ident for $lhs:ty => $out:ty $(where [$($bounds:tt)*])? |$a:ident, $b:ident| $body:expr) => {
+        impl<$($tp),*> core::ops::$trait<$rhs> for $lhs $(where $($bounds)*)? {
+            type Output = $out;
+            fn $method(self, rhs: $rhs) -> Self::Output {
+                let ($a, $b) = (self, rhs);
+                $body
+            }
+        }
+
+        impl<'r, $($tp),*> core::ops::$trait<&'r $rhs> for $lhs $(where $($bounds)*)? {
+            type Output = $out;
+            fn $method(self, rhs: &'r $rhs) -> Self::Output {
+                let ($a, $b) = (self, rhs);
+                $body
+            }
+        }
+
+        impl<'l, $($tp),*> core::ops::$trait<$rhs> for &'l $lhs $(where $($bounds)*)? {
+            type Output = $out;
+            fn $method(self, rhs: $rhs) -> Self::Output {
+                let ($a, $b) = (self, rhs);
+                $body
+            }
+        }
+
+        impl<'l, 'r, $($tp),*> core::ops::$trait<&'r $rhs> for &'l $lhs $(where $($bounds)*)? {
+            type Output = $out;
+            fn $method(self, rhs: &'r $rhs) -> Self::Output {
+                let ($a, $b) = (self, rhs);
+                $body
+            }
+        }
+    };
+}
+
 #[macro_export]
 #[doc(hidden)]
 macro_rules! impl_serialize {
@@ -330,23 +444,10 @@ macro_rules! impl_fromstr_deserialize {
             /// Parses the string as hex and interprets tries to convert the
             /// resulting byte array into the desired value.
             fn from_str(hex: &str) -> Result<$type , $crate::hex::HexError> {
-                use $crate::hex::hex_val;
-                if hex.len() % 2 == 1 {
-                    Err($crate::hex::HexError::InvalidHex)
-                } else if $len * 2 != hex.len() {
-                    Err($crate::hex::HexError::InvalidLength)
-                } else {
-                    let mut buf = [0u8; $len];
-
-                    for (i, hex_byte) in hex.as_bytes().chunks(2).enumerate() {
-                        buf[i] = hex_val(hex_byte[0])? << 4 | hex_val(hex_byte[1])?
-                    }
-
-                    let $input = buf;
-                    #[allow(clippy::redundant_closure_call)]
-                    let result = (|| -> Option<$type> {$block})();
-                    result.ok_or($crate::hex::HexError::InvalidEncoding)
-                }
+                let $input = $crate::hex::decode_array_ct::<$len>(hex)?;
+                #[allow(clippy::redundant_closure_call)]
+                let result = (|| -> Option<$type> {$block})();
+                result.ok_or($crate::hex::HexError::InvalidEncoding)
             }
         }
 
@@ -59,7 +59,8 @@ pub struct EvenY;
 /// multiplications. The only example of this is [`G`].
 ///
 /// Note that whether G does have pre-computed tables depends on the current state of the backend.
-/// At the time of writing no pre-computation is done.
+/// At the time of writing only variable time multiplications (e.g. in signature verification) use
+/// pre-computed tables of `G`.
 ///
 /// [`G`]: crate::G
 #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
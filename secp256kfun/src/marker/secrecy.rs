@@ -31,7 +31,15 @@
 /// [`Point`]: crate::marker::Public
 /// [`Scalar`s]: crate::Scalar
 /// [`Point`s]: crate::Point
-pub trait Secrecy: Default + Clone + PartialEq + Eq + Copy + 'static + Ord + PartialOrd {}
+pub trait Secrecy: Default + Clone + PartialEq + Eq + Copy + 'static + Ord + PartialOrd {
+    /// Returns whether operations on values marked with this type must be constant time.
+    ///
+    /// Defaults to `true` so an implementation that doesn't override it is treated like
+    /// [`Secret`].
+    fn is_secret() -> bool {
+        true
+    }
+}
 
 /// Indicates that the value is secret and therefore makes core operations
 /// executed on it to use  _constant time_ versions of the operations.
@@ -48,4 +56,25 @@ pub struct Public;
 
 impl Secrecy for Secret {}
 
-impl Secrecy for Public {}
+impl Secrecy for Public {
+    fn is_secret() -> bool {
+        false
+    }
+}
+
+/// Marks the secrecy types whose scalars can be converted to raw bytes with [`Scalar::to_bytes`].
+///
+/// This is implemented for everything unless the `strict_ct` feature is enabled in which case it's
+/// only implemented for [`Public`]. Secret scalars then have to go through
+/// [`Scalar::to_secret_bytes`] so that comparing them with variable time operations like `==`
+/// doesn't compile.
+///
+/// [`Scalar::to_bytes`]: crate::Scalar::to_bytes
+/// [`Scalar::to_secret_bytes`]: crate::Scalar::to_secret_bytes
+pub trait ExposeBytes {}
+
+#[cfg(not(feature = "strict_ct"))]
+impl<S> ExposeBytes for S {}
+
+#[cfg(feature = "strict_ct")]
+impl ExposeBytes for Public {}
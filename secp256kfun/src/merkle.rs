@@ -0,0 +1,169 @@
+//! Merkle trees with tagged leaf and branch hashes.
+//!
+//! Leaves are hashed with a [BIP340] tagged hash under the leaf tag and each branch is the tagged
+//! hash under the branch tag of its children in lexicographic order (as in [BIP341] script trees).
+//! Sorting the children means a [`MerkleProof`] is just the sibling hashes on the path to the root
+//! and doesn't need to say which side each one is on.
+//!
+//! [`Merkle::root`] builds a balanced tree over the leaves in the order they are given. A node
+//! without a sibling is moved up a level unchanged. Using `TapLeaf` and `TapBranch` as the tags
+//! gives BIP341 compatible roots and proofs. To commit to something other than leaf hashes use
+//! tags unique to the application.
+//!
+//! # Example
+//!
+//! ```
+//! use secp256kfun::merkle::Merkle;
+//! let merkle = Merkle::<sha2::Sha256>::new(b"my-app/leaf", b"my-app/branch");
+//! let leaves = [b"alice".as_slice(), b"bob", b"carol"].map(|leaf| merkle.leaf_hash(leaf));
+//! let root = merkle.root(&leaves).unwrap();
+//! let proof = merkle.proof(&leaves, 2).unwrap();
+//! assert!(merkle.verify(&root, &leaves[2], &proof));
+//! assert!(!merkle.verify(&root, &leaves[1], &proof));
+//! ```
+//!
+//! [BIP340]: https://bips.xyz/340
+//! [BIP341]: https://bips.xyz/341
+use crate::{
+    digest::{generic_array::typenum::U32, Digest},
+    hash::{HashAdd, Tag},
+};
+use alloc::vec::Vec;
+
+/// Hashes leaves and branches of Merkle trees under a pair of tags.
+#[derive(Clone, Debug)]
+pub struct Merkle<H> {
+    leaf: H,
+    branch: H,
+}
+
+/// The sibling hashes on the path from a leaf to the root.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::serde::Deserialize, crate::serde::Serialize),
+    serde(crate = "crate::serde")
+)]
+pub struct MerkleProof {
+    /// The sibling at each level starting from the leaf's.
+    pub path: Vec<[u8; 32]>,
+}
+
+impl<H: Digest<OutputSize = U32> + Tag + Default + Clone> Merkle<H> {
+    /// Creates a `Merkle` that hashes leaves under `leaf_tag` and branches under `branch_tag`.
+    pub fn new(leaf_tag: &[u8], branch_tag: &[u8]) -> Self {
+        Self {
+            leaf: H::default().tag(leaf_tag),
+            branch: H::default().tag(branch_tag),
+        }
+    }
+
+    /// Hashes the data of a leaf.
+    pub fn leaf_hash(&self, leaf: &[u8]) -> [u8; 32] {
+        self.leaf.clone().add(leaf).finalize().into()
+    }
+
+    /// Hashes two children into their branch.
+    pub fn branch_hash(&self, a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        let (left, right) = if a <= b { (a, b) } else { (b, a) };
+        self.branch.clone().add(left).add(right).finalize().into()
+    }
+
+    /// The root of the tree over the leaf hashes `leaves`. Returns `None` if there are no leaves.
+    pub fn root(&self, leaves: &[[u8; 32]]) -> Option<[u8; 32]> {
+        let mut level = leaves.to_vec();
+        while level.len() > 1 {
+            level = self.next_level(&level);
+        }
+        level.first().copied()
+    }
+
+    /// The proof that the leaf at `index` is in the tree over `leaves`.
+    ///
+    /// Returns `None` if `index` is out of range.
+    pub fn proof(&self, leaves: &[[u8; 32]], mut index: usize) -> Option<MerkleProof> {
+        if index >= leaves.len() {
+            return None;
+        }
+        let mut path = Vec::new();
+        let mut level = leaves.to_vec();
+        while level.len() > 1 {
+            if let Some(sibling) = level.get(index ^ 1) {
+                path.push(*sibling);
+            }
+            level = self.next_level(&level);
+            index /= 2;
+        }
+        Some(MerkleProof { path })
+    }
+
+    /// Checks that `proof` shows that `leaf` is in the tree with `root`.
+    #[must_use]
+    pub fn verify(&self, root: &[u8; 32], leaf: &[u8; 32], proof: &MerkleProof) -> bool {
+        let implied_root = proof
+            .path
+            .iter()
+            .fold(*leaf, |node, sibling| self.branch_hash(&node, sibling));
+        implied_root == *root
+    }
+
+    fn next_level(&self, level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+        level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => self.branch_hash(a, b),
+                [single] => *single,
+                _ => unreachable!("chunks of two"),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+
+    #[test]
+    fn every_leaf_has_a_proof() {
+        let merkle = Merkle::<Sha256>::new(b"test/leaf", b"test/branch");
+        assert_eq!(merkle.root(&[]), None);
+        for len in 1..10u8 {
+            let leaves = (0..len).map(|i| merkle.leaf_hash(&[i])).collect::<Vec<_>>();
+            let root = merkle.root(&leaves).unwrap();
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = merkle.proof(&leaves, index).unwrap();
+                assert!(merkle.verify(&root, leaf, &proof));
+                assert!(!merkle.verify(&root, &merkle.leaf_hash(&[len]), &proof));
+            }
+            assert_eq!(merkle.proof(&leaves, leaves.len()), None);
+        }
+    }
+
+    #[test]
+    fn taproot_script_tree() {
+        // a two leaf script tree from the BIP341 wallet test vectors
+        let merkle = Merkle::<Sha256>::new(b"TapLeaf", b"TapBranch");
+        // leaves are the leaf version followed by the length prefixed script
+        let scripts = [
+            "c02220387671353e273264c495656e27e39ba899ea8fee3bb69fb2a680e22093447d48ac",
+            "fa0706424950333431",
+        ];
+        let leaves = scripts.map(|leaf| merkle.leaf_hash(&crate::hex::decode(leaf).unwrap()));
+        assert_eq!(
+            leaves,
+            [
+                "8ad69ec7cf41c2a4001fd1f738bf1e505ce2277acdcaa63fe4765192497f47a7",
+                "f224a923cd0021ab202ab139cc56802ddb92dcfc172b9212261a539df79a112a",
+            ]
+            .map(|hash| crate::hex::decode_array(hash).unwrap())
+        );
+        assert_eq!(
+            merkle.root(&leaves),
+            crate::hex::decode_array(
+                "6c2dc106ab816b73f9d07e3cd1ef2c8c1256f519748e0813e4edd2405d277bef"
+            )
+            .ok()
+        );
+    }
+}
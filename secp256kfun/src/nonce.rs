@@ -209,7 +209,7 @@ where
 {
     type Hash = H;
     fn begin_derivation(&self, secret: &Scalar) -> Self::Hash {
-        let sec_bytes = secret.to_bytes();
+        let sec_bytes = secret.raw_bytes();
         let mut aux_bytes = [0u8; 32];
         self.rng.fill_bytes(&mut aux_bytes[..]);
         let mut aux_hash = self.aux_hash.clone();
@@ -20,32 +20,62 @@ use crate::{
 use core::borrow::Borrow;
 
 /// Computes `x * A + y * B` more efficiently than calling [`scalar_mul_point`] twice.
+///
+/// If every input is [`Public`] this uses a faster variable time algorithm, otherwise it runs in
+/// constant time.
 #[inline(always)]
-pub fn double_mul<ZA, SA, TA, ZX, SX, ZB, SB, TB, ZY, SY>(
+pub fn double_mul<ZA, SA: Secrecy, TA, ZX, SX: Secrecy, ZB, SB: Secrecy, TB, ZY, SY: Secrecy>(
     x: impl Borrow<Scalar<SX, ZX>>,
     A: impl Borrow<Point<TA, SA, ZA>>,
     y: impl Borrow<Scalar<SY, ZY>>,
     B: impl Borrow<Point<TB, SB, ZB>>,
+) -> Point<NonNormal, Public, Zero> {
+    let (x, A, y, B) = (&x.borrow().0, &A.borrow().0, &y.borrow().0, &B.borrow().0);
+    let point = if SX::is_secret() || SA::is_secret() || SY::is_secret() || SB::is_secret() {
+        ConstantTime::point_double_mul(x, A, y, B)
+    } else {
+        VariableTime::point_double_mul(x, A, y, B)
+    };
+    Point::from_inner(point, NonNormal)
+}
+
+/// Computes `x * A + y * B` in **variable time**.
+///
+/// This is what [`double_mul`] does when all its inputs are [`Public`]. It uses the secp256k1
+/// endomorphism and an interleaved wNAF multiplication whose running time leaks information about
+/// the scalars and points so the types are restricted to [`Public`] ones.
+#[inline(always)]
+pub fn double_mul_vartime<ZA, TA, ZX, ZB, TB, ZY>(
+    x: impl Borrow<Scalar<Public, ZX>>,
+    A: impl Borrow<Point<TA, Public, ZA>>,
+    y: impl Borrow<Scalar<Public, ZY>>,
+    B: impl Borrow<Point<TB, Public, ZB>>,
 ) -> Point<NonNormal, Public, Zero> {
     Point::from_inner(
-        ConstantTime::point_double_mul(&x.borrow().0, &A.borrow().0, &y.borrow().0, &B.borrow().0),
+        VariableTime::point_double_mul(&x.borrow().0, &A.borrow().0, &y.borrow().0, &B.borrow().0),
         NonNormal,
     )
 }
 
 /// Computes multiplies the point `P` by the scalar `x`.
+///
+/// If both `x` and `P` are [`Public`] this uses a faster variable time algorithm, otherwise it
+/// runs in constant time.
 #[inline(always)]
-pub fn scalar_mul_point<Z1, S1, T2, S2, Z2>(
+pub fn scalar_mul_point<Z1, S1: Secrecy, T2, S2: Secrecy, Z2>(
     x: impl Borrow<Scalar<S1, Z1>>,
     P: impl Borrow<Point<T2, S2, Z2>>,
 ) -> Point<NonNormal, Public, Z1::Out>
 where
     Z1: DecideZero<Z2>,
 {
-    Point::from_inner(
-        ConstantTime::scalar_mul_point(&x.borrow().0, &P.borrow().0),
-        NonNormal,
-    )
+    let (x, P) = (&x.borrow().0, &P.borrow().0);
+    let point = if S1::is_secret() || S2::is_secret() {
+        ConstantTime::scalar_mul_point(x, P)
+    } else {
+        VariableTime::scalar_mul_point(x, P)
+    };
+    Point::from_inner(point, NonNormal)
 }
 
 /// Multiplies two scalars together (modulo the curve order)
@@ -143,6 +173,80 @@ pub fn point_add<Z1, Z2, S1, S2, T1, T2>(
     )
 }
 
+/// Sums points together.
+///
+/// The sum is accumulated in non-normalized form so it is more efficient than normalizing after
+/// each addition. An empty iterator sums to [`Point::zero`].
+///
+/// # Example
+///
+/// ```
+/// use secp256kfun::{g, op, Point};
+/// let points = [
+///     Point::random(&mut rand::thread_rng()),
+///     Point::random(&mut rand::thread_rng()),
+/// ];
+/// assert_eq!(op::sum_points(&points), g!(points[0] + points[1]));
+/// assert!(op::sum_points(&[] as &[Point]).is_zero());
+/// ```
+#[inline(always)]
+pub fn sum_points<T, S, Z, P: Borrow<Point<T, S, Z>>>(
+    points: impl IntoIterator<Item = P>,
+) -> Point<NonNormal, Public, Zero> {
+    let mut sum = Point::zero();
+    add_points_assign(&mut sum, points);
+    sum
+}
+
+/// Adds points into an existing accumulator in place.
+///
+/// This is the in-place version of [`sum_points`].
+#[inline(always)]
+pub fn add_points_assign<SA, T, S, Z, P: Borrow<Point<T, S, Z>>>(
+    acc: &mut Point<NonNormal, SA, Zero>,
+    points: impl IntoIterator<Item = P>,
+) {
+    for point in points {
+        acc.0 = ConstantTime::point_add_point(&acc.0, &point.borrow().0);
+    }
+}
+
+/// Adds each point in `rhs` to the point at the same position in `lhs` in place.
+///
+/// This is useful for aggregating things that are made up of several points like the coefficients
+/// of point polynomials or nonces with multiple components.
+///
+/// # Panics
+///
+/// If `lhs` and `rhs` have different lengths.
+///
+/// # Example
+///
+/// ```
+/// use secp256kfun::{g, marker::*, op, Point};
+/// let nonce1 = [Point::random(&mut rand::thread_rng()), Point::random(&mut rand::thread_rng())];
+/// let nonce2 = [Point::random(&mut rand::thread_rng()), Point::random(&mut rand::thread_rng())];
+/// let mut agg = [Point::<NonNormal, Public, Zero>::zero(); 2];
+/// op::pairwise_add_points(&mut agg, &nonce1);
+/// op::pairwise_add_points(&mut agg, &nonce2);
+/// assert_eq!(agg[0], g!(nonce1[0] + nonce2[0]));
+/// assert_eq!(agg[1], g!(nonce1[1] + nonce2[1]));
+/// ```
+#[inline(always)]
+pub fn pairwise_add_points<SA, T, S, Z>(
+    lhs: &mut [Point<NonNormal, SA, Zero>],
+    rhs: &[Point<T, S, Z>],
+) {
+    assert_eq!(
+        lhs.len(),
+        rhs.len(),
+        "pairwise addition of different lengths"
+    );
+    for (acc, point) in lhs.iter_mut().zip(rhs) {
+        acc.0 = ConstantTime::point_add_point(&acc.0, &point.0);
+    }
+}
+
 /// Checks if two points are equal
 #[inline(always)]
 pub fn point_eq<Z1, Z2, S1, S2, T1, T2>(A: &Point<T1, S1, Z1>, B: &Point<T2, S2, Z2>) -> bool
@@ -214,6 +318,122 @@ pub fn point_scalar_dot_product<
     )
 }
 
+/// Does a [dot product] of points with scalars in **variable time**.
+///
+/// Like [`point_scalar_dot_product`] but much faster since its running time leaks information about
+/// the scalars and points. The types are restricted to [`Public`] ones. Use it for things like
+/// batch verification where you would otherwise add up many `g!(a * P)` terms.
+///
+/// Up to 128 points this uses the same interleaved wNAF algorithm as [`double_mul_vartime`] over
+/// all the points at once. With more than that it switches to Pippenger's bucket method whose cost
+/// per point goes down as the number of points goes up. Use an [`MsmContext`] to pick where it
+/// switches at runtime.
+///
+/// # Example
+///
+/// ```
+/// use secp256kfun::{g, op, Point, Scalar};
+/// let scalars = (0..200)
+///     .map(|_| Scalar::random(&mut rand::thread_rng()).public())
+///     .collect::<Vec<_>>();
+/// let points = (0..200)
+///     .map(|_| Point::random(&mut rand::thread_rng()))
+///     .collect::<Vec<_>>();
+/// assert_eq!(
+///     op::point_scalar_dot_product_vartime(&scalars, &points),
+///     g!(scalars .* points)
+/// );
+/// ```
+///
+/// [dot product]: https://en.wikipedia.org/wiki/Dot_product
+#[inline(always)]
+pub fn point_scalar_dot_product_vartime<
+    T1,
+    Z1,
+    Z2,
+    I2: Borrow<Scalar<Public, Z2>> + AsRef<backend::Scalar>,
+    I1: Borrow<Point<T1, Public, Z1>> + AsRef<backend::Point>,
+>(
+    scalars: impl IntoIterator<Item = I2>,
+    points: impl IntoIterator<Item = I1>,
+) -> Point<NonNormal, Public, Zero> {
+    Point::from_inner(
+        VariableTime::lincomb_iter(points.into_iter(), scalars.into_iter()),
+        NonNormal,
+    )
+}
+
+/// Parameters for variable time multi-scalar multiplication that are chosen at runtime.
+///
+/// The best point at which to switch from the interleaved wNAF algorithm to Pippenger's depends on
+/// the machine. The default switches above [`DEFAULT_PIPPENGER_THRESHOLD`] points (which is what
+/// [`point_scalar_dot_product_vartime`] does) but you can measure it (e.g. with
+/// `schnorr_fun::calibrate`) and keep the result in a context.
+///
+/// # Example
+///
+/// ```
+/// use secp256kfun::{g, op::MsmContext, Point, Scalar};
+/// let scalars = (0..50)
+///     .map(|_| Scalar::random(&mut rand::thread_rng()).public())
+///     .collect::<Vec<_>>();
+/// let points = (0..50)
+///     .map(|_| Point::random(&mut rand::thread_rng()))
+///     .collect::<Vec<_>>();
+/// let msm = MsmContext {
+///     pippenger_threshold: 32,
+/// };
+/// assert_eq!(
+///     msm.point_scalar_dot_product_vartime(&scalars, &points),
+///     g!(scalars .* points)
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MsmContext {
+    /// The most points (not counting `G`) that are multiplied with the interleaved wNAF algorithm.
+    /// Any more are multiplied with Pippenger's algorithm. Without the `alloc` feature Pippenger's
+    /// algorithm isn't available and this is ignored.
+    pub pippenger_threshold: usize,
+}
+
+/// The default [`MsmContext::pippenger_threshold`].
+pub const DEFAULT_PIPPENGER_THRESHOLD: usize = backend::DEFAULT_PIPPENGER_THRESHOLD;
+
+impl Default for MsmContext {
+    fn default() -> Self {
+        Self {
+            pippenger_threshold: DEFAULT_PIPPENGER_THRESHOLD,
+        }
+    }
+}
+
+impl MsmContext {
+    /// Like [`point_scalar_dot_product_vartime`] but switching to Pippenger's algorithm at
+    /// [`pippenger_threshold`](Self::pippenger_threshold).
+    #[inline(always)]
+    pub fn point_scalar_dot_product_vartime<
+        T1,
+        Z1,
+        Z2,
+        I2: Borrow<Scalar<Public, Z2>> + AsRef<backend::Scalar>,
+        I1: Borrow<Point<T1, Public, Z1>> + AsRef<backend::Point>,
+    >(
+        &self,
+        scalars: impl IntoIterator<Item = I2>,
+        points: impl IntoIterator<Item = I1>,
+    ) -> Point<NonNormal, Public, Zero> {
+        Point::from_inner(
+            VariableTime::lincomb_iter_with_threshold(
+                points.into_iter(),
+                scalars.into_iter(),
+                self.pippenger_threshold,
+            ),
+            NonNormal,
+        )
+    }
+}
+
 /// Does a linear combination of points
 ///
 /// ⚠ deprecated in favor of [`point_scalar_dot_product`] which has a more convienient API and name.
@@ -288,6 +508,44 @@ mod test {
         assert_eq!(R_implied, R_expected);
     }
 
+    #[test]
+    fn dot_product_vartime_sizes() {
+        use crate::op::*;
+        assert!(point_scalar_dot_product_vartime(
+            core::iter::empty::<Scalar<Public>>(),
+            core::iter::empty::<Point>()
+        )
+        .is_zero());
+        // either side of where it switches to pippenger
+        for n in [1, 127, 128, 129, 600] {
+            let scalars = (0..n)
+                .map(|i| match i % 5 {
+                    0 => Scalar::<Public, Zero>::zero(),
+                    _ => Scalar::random(&mut rand::thread_rng()).public().mark_zero(),
+                })
+                .collect::<alloc::vec::Vec<_>>();
+            let points = (0..n)
+                .map(|i| match i % 3 {
+                    0 => Point::<Normal, Public, Zero>::zero(),
+                    // G is multiplied with its own tables
+                    _ if i % 7 == 1 => G.normalize().mark_zero(),
+                    _ => Point::random(&mut rand::thread_rng()).mark_zero(),
+                })
+                .collect::<alloc::vec::Vec<_>>();
+            let expected = point_scalar_dot_product(&scalars, &points);
+            assert_eq!(point_scalar_dot_product_vartime(&scalars, &points), expected);
+            for pippenger_threshold in [0, n / 2, usize::MAX] {
+                let msm = MsmContext {
+                    pippenger_threshold,
+                };
+                assert_eq!(
+                    msm.point_scalar_dot_product_vartime(&scalars, &points),
+                    expected
+                );
+            }
+        }
+    }
+
     use proptest::prelude::*;
 
     proptest! {
@@ -304,5 +562,53 @@ mod test {
             assert_eq!(point_scalar_dot_product([&a,&b,&c], [&A,&B,&C]),
                        point_add(scalar_mul_point(a, A), point_add(scalar_mul_point(b, B), scalar_mul_point(c, C))))
         }
+
+        #[test]
+        fn vartime_against_constant_time(a in any::<Scalar<Public, Zero>>(),
+                                         b in any::<Scalar<Public, Zero>>(),
+                                         A in any::<Point<Normal, Public, Zero>>(),
+                                         B in any::<Point>()
+        ) {
+            use crate::op::*;
+            let ct = double_mul(a.secret(), A, b.secret(), B);
+            assert_eq!(double_mul_vartime(a, A, b, B), ct);
+            assert_eq!(double_mul(a, A, b, B), ct);
+            assert_eq!(double_mul(a, G, b, G), scalar_mul_point(s!(a + b).secret(), G));
+            assert_eq!(double_mul_vartime(a, G, b, B), double_mul(a.secret(), G, b, B));
+            assert_eq!(double_mul_vartime(a, A, b, G), double_mul(a.secret(), A, b, G));
+            assert_eq!(scalar_mul_point(a, G), scalar_mul_point(a.secret(), G));
+            assert_eq!(scalar_mul_point(a, B), scalar_mul_point(a.secret(), B));
+            assert_eq!(scalar_mul_point(-a, A), -scalar_mul_point(a.secret(), A));
+        }
+
+        #[test]
+        fn dot_product_vartime_against_constant_time(a in any::<Scalar<Public, Zero>>(),
+                                                     b in any::<Scalar<Public, Zero>>(),
+                                                     c in any::<Scalar<Public, Zero>>(),
+                                                     A in any::<Point<Normal, Public, Zero>>(),
+                                                     B in any::<Point<Normal, Public, Zero>>(),
+                                                     C in any::<Point<Normal, Public, Zero>>()
+        ) {
+            use crate::op::*;
+            assert_eq!(
+                point_scalar_dot_product_vartime([&a, &b, &c], [&A, &B, &C]),
+                point_scalar_dot_product([&a, &b, &c], [&A, &B, &C])
+            );
+        }
+
+        #[test]
+        fn sum_points_against_add(A in any::<Point>(), B in any::<Point<Normal, Public, Zero>>(), C in any::<Point>()) {
+            use crate::op::*;
+            let expected = point_add(A, point_add(B, C));
+            assert_eq!(sum_points([A.mark_zero(), B, C.mark_zero()]), expected);
+            let mut acc = sum_points([A]);
+            add_points_assign(&mut acc, [B]);
+            add_points_assign(&mut acc, [C]);
+            assert_eq!(acc, expected);
+            let mut pairs = [A.mark_zero().non_normal(), C.mark_zero().non_normal()];
+            pairwise_add_points(&mut pairs, &[B, -B]);
+            assert_eq!(pairs[0], point_add(A, B));
+            assert_eq!(pairs[1], point_sub(C, B));
+        }
     }
 }
@@ -0,0 +1,113 @@
+use crate::{
+    marker::*,
+    serde::{Deserialize, Deserializer, Serialize, Serializer},
+    Point,
+};
+
+/// A [`Point`] that serializes as an object with its x-coordinate and the parity of its
+/// y-coordinate instead of as an opaque compressed encoding.
+///
+/// `Point` serializes as the hex of its 33-byte compressed encoding where the parity is hidden in
+/// the first byte. JSON APIs that want something easier to read by eye can wrap their points in
+/// `ParityPoint` to get `{"x": "<32-byte hex>", "parity": "even"}` (or `"odd"`) instead.
+///
+/// # Example
+///
+/// ```
+/// use secp256kfun::{ParityPoint, G};
+/// let json = serde_json::to_string(&ParityPoint(G.normalize())).unwrap();
+/// assert_eq!(
+///     json,
+///     r#"{"x":"79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798","parity":"even"}"#
+/// );
+/// assert_eq!(
+///     serde_json::from_str::<ParityPoint>(&json).unwrap().0,
+///     G.normalize()
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ParityPoint(pub Point);
+
+/// The parity of a y-coordinate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(crate = "crate::serde", rename_all = "lowercase")]
+pub enum Parity {
+    /// The y-coordinate is even.
+    Even,
+    /// The y-coordinate is odd.
+    Odd,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(crate = "crate::serde")]
+struct Repr {
+    x: Point<EvenY>,
+    parity: Parity,
+}
+
+impl ParityPoint {
+    /// The parity of the point's y-coordinate.
+    pub fn parity(&self) -> Parity {
+        if self.0.is_y_even() {
+            Parity::Even
+        } else {
+            Parity::Odd
+        }
+    }
+}
+
+impl From<Point> for ParityPoint {
+    fn from(point: Point) -> Self {
+        Self(point)
+    }
+}
+
+impl From<ParityPoint> for Point {
+    fn from(point: ParityPoint) -> Self {
+        point.0
+    }
+}
+
+impl Serialize for ParityPoint {
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        let parity = self.parity();
+        let (x, _) = self.0.into_point_with_even_y();
+        Repr { x, parity }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ParityPoint {
+    fn deserialize<Deser: Deserializer<'de>>(deserializer: Deser) -> Result<Self, Deser::Error> {
+        let Repr { x, parity } = Repr::deserialize(deserializer)?;
+        Ok(ParityPoint(x.conditional_negate(parity == Parity::Odd)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parity_point_json_roundtrip() {
+        for _ in 0..10 {
+            let point = Point::random(&mut rand::thread_rng());
+            let json = serde_json::to_value(ParityPoint(point)).unwrap();
+            assert_eq!(
+                json["x"],
+                crate::hex::encode(&point.to_xonly_bytes()).as_str()
+            );
+            assert_eq!(
+                json["parity"],
+                if point.is_y_even() { "even" } else { "odd" }
+            );
+            assert_eq!(
+                serde_json::from_value::<ParityPoint>(json).unwrap(),
+                ParityPoint(point)
+            );
+        }
+        assert!(serde_json::from_str::<ParityPoint>(
+            r#"{"x":"79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798","parity":"up"}"#
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,311 @@
+//! Import and export of keys in the DER formats used by OpenSSL and cloud KMSs.
+//!
+//! - Secret keys as a SEC1 `ECPrivateKey` (what `openssl ec -outform DER` produces) or wrapped in a
+//!   PKCS#8 `PrivateKeyInfo` (what `openssl pkcs8 -topk8 -nocrypt` produces).
+//! - Public keys as a `SubjectPublicKeyInfo` (what `openssl ec -pubout` produces).
+//!
+//! Only unencrypted keys on secp256k1 are supported. Encodings are parsed strictly: the curve must
+//! be secp256k1, a public key embedded alongside a secret key must match it and there mustn't be
+//! any trailing data.
+//!
+//! # Example
+//!
+//! ```
+//! use secp256kfun::{hex, pkcs};
+//! // openssl ec -pubout -outform DER
+//! let der = hex::decode("3036301006072a8648ce3d020106052b8104000a032200035f269a2ad198641eb25a6f7b0cc2689eff5b91a9b3dad2e57ae1da48b203c0e5").unwrap();
+//! let public_key = pkcs::public_key_from_spki_der(&der)?;
+//! assert_eq!(pkcs::public_key_from_spki_der(&pkcs::public_key_to_spki_der(&public_key))?, public_key);
+//! let secret_key = secp256kfun::Scalar::random(&mut rand::thread_rng());
+//! let der = pkcs::secret_key_to_pkcs8_der(&secret_key);
+//! assert_eq!(pkcs::secret_key_from_pkcs8_der(&der)?, secret_key);
+//! # Ok::<(), pkcs::DerError>(())
+//! ```
+use crate::{g, marker::*, Point, Scalar, G};
+use core::fmt;
+
+/// The length of [`secret_key_to_sec1_der`]'s output.
+pub const SEC1_DER_LEN: usize = 118;
+/// The length of [`secret_key_to_pkcs8_der`]'s output.
+pub const PKCS8_DER_LEN: usize = 135;
+/// The length of [`public_key_to_spki_der`]'s output.
+pub const SPKI_DER_LEN: usize = 88;
+
+const SEQUENCE: u8 = 0x30;
+const INTEGER: u8 = 0x02;
+const BIT_STRING: u8 = 0x03;
+const OCTET_STRING: u8 = 0x04;
+const OBJECT_IDENTIFIER: u8 = 0x06;
+const PARAMETERS: u8 = 0xa0;
+const PUBLIC_KEY: u8 = 0xa1;
+
+/// id-ecPublicKey (1.2.840.10045.2.1)
+const EC_PUBLIC_KEY_OID: [u8; 7] = [0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+/// secp256k1 (1.3.132.0.10)
+const SECP256K1_OID: [u8; 5] = [0x2b, 0x81, 0x04, 0x00, 0x0a];
+
+/// The `AlgorithmIdentifier` for an EC key on secp256k1.
+#[rustfmt::skip]
+const ALGORITHM_IDENTIFIER: [u8; 18] = [
+    SEQUENCE, 16,
+    OBJECT_IDENTIFIER, 7, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01,
+    OBJECT_IDENTIFIER, 5, 0x2b, 0x81, 0x04, 0x00, 0x0a,
+];
+
+/// Error returned when DER encoded keys can't be parsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DerError {
+    /// The input isn't valid DER or doesn't have the structure of the expected key format.
+    Malformed,
+    /// The key isn't an EC key on secp256k1.
+    WrongAlgorithm,
+    /// The encoded key isn't a valid key e.g. the secret key is zero or doesn't match the
+    /// public key encoded with it.
+    InvalidKey,
+}
+
+impl fmt::Display for DerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DerError::Malformed => write!(f, "malformed DER key encoding"),
+            DerError::WrongAlgorithm => write!(f, "key is not an EC key on secp256k1"),
+            DerError::InvalidKey => write!(f, "encoded key is invalid"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DerError {}
+
+/// Reads DER tag-length-value elements one after another.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn read(&mut self, tag: u8) -> Result<&'a [u8], DerError> {
+        let (&actual_tag, rest) = self.0.split_first().ok_or(DerError::Malformed)?;
+        if actual_tag != tag {
+            return Err(DerError::Malformed);
+        }
+        let (&first, rest) = rest.split_first().ok_or(DerError::Malformed)?;
+        let (len, rest) = match first {
+            len @ 0..=0x7f => (len as usize, rest),
+            0x81 => match rest.split_first() {
+                Some((&len, rest)) if len >= 0x80 => (len as usize, rest),
+                _ => return Err(DerError::Malformed),
+            },
+            0x82 => match rest {
+                [hi, lo, rest @ ..] if *hi != 0 => (u16::from_be_bytes([*hi, *lo]) as usize, rest),
+                _ => return Err(DerError::Malformed),
+            },
+            _ => return Err(DerError::Malformed),
+        };
+        if rest.len() < len {
+            return Err(DerError::Malformed);
+        }
+        let (value, rest) = rest.split_at(len);
+        self.0 = rest;
+        Ok(value)
+    }
+
+    fn read_optional(&mut self, tag: u8) -> Result<Option<&'a [u8]>, DerError> {
+        if self.0.first() == Some(&tag) {
+            self.read(tag).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn finish(self) -> Result<(), DerError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(DerError::Malformed)
+        }
+    }
+}
+
+/// Reads a single element that must take up all of `der`.
+fn read_only(der: &[u8], tag: u8) -> Result<&[u8], DerError> {
+    let mut reader = Reader(der);
+    let value = reader.read(tag)?;
+    reader.finish()?;
+    Ok(value)
+}
+
+fn read_algorithm_identifier(reader: &mut Reader<'_>) -> Result<(), DerError> {
+    let mut algorithm = Reader(reader.read(SEQUENCE)?);
+    if algorithm.read(OBJECT_IDENTIFIER)? != EC_PUBLIC_KEY_OID
+        || algorithm.read(OBJECT_IDENTIFIER)? != SECP256K1_OID
+    {
+        return Err(DerError::WrongAlgorithm);
+    }
+    algorithm.finish()
+}
+
+/// Parses a public key from the contents of a `BIT STRING` holding a compressed or uncompressed
+/// SEC1 point.
+fn parse_bit_string_point(bit_string: &[u8]) -> Result<Point, DerError> {
+    let point = match bit_string {
+        [0, point @ ..] => point,
+        _ => return Err(DerError::Malformed),
+    };
+    if let Ok(bytes) = <[u8; 33]>::try_from(point) {
+        Point::from_bytes(bytes).ok_or(DerError::InvalidKey)
+    } else if let Ok(bytes) = <[u8; 65]>::try_from(point) {
+        Point::from_bytes_uncompressed(bytes).ok_or(DerError::InvalidKey)
+    } else {
+        Err(DerError::Malformed)
+    }
+}
+
+/// Parses a secret key from a SEC1 `ECPrivateKey`.
+///
+/// The optional curve parameters must be secp256k1 and the optional public key must match the
+/// secret key.
+pub fn secret_key_from_sec1_der(der: &[u8]) -> Result<Scalar, DerError> {
+    let mut reader = Reader(read_only(der, SEQUENCE)?);
+    if reader.read(INTEGER)? != [1] {
+        return Err(DerError::Malformed);
+    }
+    let bytes =
+        <[u8; 32]>::try_from(reader.read(OCTET_STRING)?).map_err(|_| DerError::Malformed)?;
+    let secret_key = Scalar::from_bytes(bytes)
+        .and_then(|scalar| scalar.non_zero())
+        .ok_or(DerError::InvalidKey)?;
+    if let Some(parameters) = reader.read_optional(PARAMETERS)? {
+        if read_only(parameters, OBJECT_IDENTIFIER)? != SECP256K1_OID {
+            return Err(DerError::WrongAlgorithm);
+        }
+    }
+    if let Some(public_key) = reader.read_optional(PUBLIC_KEY)? {
+        let public_key = parse_bit_string_point(read_only(public_key, BIT_STRING)?)?;
+        if public_key != g!(secret_key * G) {
+            return Err(DerError::InvalidKey);
+        }
+    }
+    reader.finish()?;
+    Ok(secret_key)
+}
+
+/// Encodes `secret_key` as a SEC1 `ECPrivateKey` with the curve parameters and uncompressed public
+/// key included like OpenSSL does.
+pub fn secret_key_to_sec1_der(secret_key: &Scalar) -> [u8; SEC1_DER_LEN] {
+    let mut der = [0u8; SEC1_DER_LEN];
+    der[..7].copy_from_slice(&[SEQUENCE, 116, INTEGER, 1, 1, OCTET_STRING, 32]);
+    der[7..39].copy_from_slice(secret_key.to_secret_bytes().expose_secret());
+    der[39..43].copy_from_slice(&[PARAMETERS, 7, OBJECT_IDENTIFIER, 5]);
+    der[43..48].copy_from_slice(&SECP256K1_OID);
+    der[48..53].copy_from_slice(&[PUBLIC_KEY, 68, BIT_STRING, 66, 0]);
+    der[53..].copy_from_slice(&g!(secret_key * G).normalize().to_bytes_uncompressed());
+    der
+}
+
+/// Parses a secret key from an unencrypted PKCS#8 `PrivateKeyInfo`.
+pub fn secret_key_from_pkcs8_der(der: &[u8]) -> Result<Scalar, DerError> {
+    let mut reader = Reader(read_only(der, SEQUENCE)?);
+    if reader.read(INTEGER)? != [0] {
+        return Err(DerError::Malformed);
+    }
+    read_algorithm_identifier(&mut reader)?;
+    let secret_key = secret_key_from_sec1_der(reader.read(OCTET_STRING)?)?;
+    reader.finish()?;
+    Ok(secret_key)
+}
+
+/// Encodes `secret_key` as an unencrypted PKCS#8 `PrivateKeyInfo` like OpenSSL does.
+pub fn secret_key_to_pkcs8_der(secret_key: &Scalar) -> [u8; PKCS8_DER_LEN] {
+    let sec1 = secret_key_to_sec1_der(secret_key);
+    let mut der = [0u8; PKCS8_DER_LEN];
+    der[..6].copy_from_slice(&[SEQUENCE, 0x81, 132, INTEGER, 1, 0]);
+    der[6..24].copy_from_slice(&ALGORITHM_IDENTIFIER);
+    // the curve is already in the algorithm identifier so it's left out of the ECPrivateKey
+    der[24..28].copy_from_slice(&[OCTET_STRING, 109, SEQUENCE, 107]);
+    der[28..65].copy_from_slice(&sec1[2..39]);
+    der[65..].copy_from_slice(&sec1[48..]);
+    der
+}
+
+/// Parses a public key from a `SubjectPublicKeyInfo` with a compressed or uncompressed point.
+pub fn public_key_from_spki_der(der: &[u8]) -> Result<Point, DerError> {
+    let mut reader = Reader(read_only(der, SEQUENCE)?);
+    read_algorithm_identifier(&mut reader)?;
+    let public_key = parse_bit_string_point(reader.read(BIT_STRING)?)?;
+    reader.finish()?;
+    Ok(public_key)
+}
+
+/// Encodes `public_key` as a `SubjectPublicKeyInfo` with an uncompressed point like OpenSSL does.
+pub fn public_key_to_spki_der<T: Normalized>(
+    public_key: &Point<T, impl Secrecy>,
+) -> [u8; SPKI_DER_LEN] {
+    let mut der = [0u8; SPKI_DER_LEN];
+    der[..2].copy_from_slice(&[SEQUENCE, 86]);
+    der[2..20].copy_from_slice(&ALGORITHM_IDENTIFIER);
+    der[20..23].copy_from_slice(&[BIT_STRING, 66, 0]);
+    der[23..].copy_from_slice(&public_key.to_bytes_uncompressed());
+    der
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hex;
+
+    // generated with `openssl ecparam -name secp256k1 -genkey`
+    const SEC1: &str = "307402010104207102a1558a697ccc9bb518b577337e537b3e78aa428509051c590ce909b690d5a00706052b8104000aa144034200045f269a2ad198641eb25a6f7b0cc2689eff5b91a9b3dad2e57ae1da48b203c0e51c954f39baeeba2e930c075ff1291b86bcac2bea20b8c28504069c12f0c2a115";
+    const PKCS8: &str = "308184020100301006072a8648ce3d020106052b8104000a046d306b02010104207102a1558a697ccc9bb518b577337e537b3e78aa428509051c590ce909b690d5a144034200045f269a2ad198641eb25a6f7b0cc2689eff5b91a9b3dad2e57ae1da48b203c0e51c954f39baeeba2e930c075ff1291b86bcac2bea20b8c28504069c12f0c2a115";
+    const SPKI: &str = "3056301006072a8648ce3d020106052b8104000a034200045f269a2ad198641eb25a6f7b0cc2689eff5b91a9b3dad2e57ae1da48b203c0e51c954f39baeeba2e930c075ff1291b86bcac2bea20b8c28504069c12f0c2a115";
+    const SPKI_COMPRESSED: &str = "3036301006072a8648ce3d020106052b8104000a032200035f269a2ad198641eb25a6f7b0cc2689eff5b91a9b3dad2e57ae1da48b203c0e5";
+
+    #[test]
+    fn openssl_keys_round_trip() {
+        let sec1 = hex::decode(SEC1).unwrap();
+        let pkcs8 = hex::decode(PKCS8).unwrap();
+        let spki = hex::decode(SPKI).unwrap();
+        let secret_key = secret_key_from_sec1_der(&sec1).unwrap();
+        let public_key = g!(secret_key * G).normalize();
+
+        assert_eq!(secret_key_from_pkcs8_der(&pkcs8), Ok(secret_key));
+        assert_eq!(public_key_from_spki_der(&spki), Ok(public_key));
+        assert_eq!(
+            public_key_from_spki_der(&hex::decode(SPKI_COMPRESSED).unwrap()),
+            Ok(public_key)
+        );
+        assert_eq!(secret_key_to_sec1_der(&secret_key)[..], sec1[..]);
+        assert_eq!(secret_key_to_pkcs8_der(&secret_key)[..], pkcs8[..]);
+        assert_eq!(public_key_to_spki_der(&public_key)[..], spki[..]);
+
+        // the bare ECPrivateKey without the optional fields
+        let mut minimal = sec1[..39].to_vec();
+        minimal[1] = 37;
+        assert_eq!(secret_key_from_sec1_der(&minimal), Ok(secret_key));
+
+        let mut trailing = spki.clone();
+        trailing.push(0);
+        assert_eq!(
+            public_key_from_spki_der(&trailing),
+            Err(DerError::Malformed)
+        );
+        assert_eq!(
+            secret_key_from_sec1_der(&sec1[..sec1.len() - 1]),
+            Err(DerError::Malformed)
+        );
+
+        // wrong curve (secp384r1)
+        let mut p384 = spki.clone();
+        p384[19] = 0x22;
+        assert_eq!(
+            public_key_from_spki_der(&p384),
+            Err(DerError::WrongAlgorithm)
+        );
+
+        // public key doesn't match the secret key
+        let other = secret_key_to_sec1_der(&Scalar::random(&mut rand::thread_rng()));
+        let mut mismatched = sec1.clone();
+        mismatched[53..].copy_from_slice(&other[53..]);
+        assert_eq!(
+            secret_key_from_sec1_der(&mismatched),
+            Err(DerError::InvalidKey)
+        );
+    }
+}
@@ -2,7 +2,7 @@ use crate::{
     backend::{self, BackendPoint, TimeSensitive},
     hash::HashInto,
     marker::*,
-    op, Scalar,
+    op, FromSliceError, Scalar,
 };
 use core::{
     marker::PhantomData,
@@ -29,6 +29,27 @@ use rand_core::RngCore;
 /// - `S`: A [`Secrecy`] to determine whether operations on this point should be done in constant-time or not. By default points are [`Public`] so operations run in variable time.
 /// - `Z`: A [`ZeroChoice`] to keep track of whether the point might be zero (the point at infinity) or is guaranteed to be non-zero.
 ///
+/// ## Operators
+///
+/// Points (and references to them) implement `Add`, `Sub` and `Neg` and can be multiplied by a
+/// [`Scalar`] on either side with `Mul`. This is for code that can't use the [`g!`] macro (e.g.
+/// because it's generic over the operations). They give the same results with the same markers as
+/// the macro and like it multiplication only runs in variable time if both the scalar and the point
+/// are [`Public`]:
+///
+/// ```
+/// use secp256kfun::{g, marker::*, Point, Scalar, G};
+/// let x = Scalar::random(&mut rand::thread_rng());
+/// let X: Point<NonNormal, Public, NonZero> = &x * G;
+/// assert_eq!(X, g!(x * G));
+/// assert_eq!(G * x, X);
+/// let sum: Point<NonNormal, Public, Zero> = X + G;
+/// assert_eq!(sum, g!(X + G));
+/// assert!((sum - X - G).is_zero());
+/// ```
+///
+/// [`g!`]: crate::g
+///
 /// ## Serialization
 ///
 /// Only points that are normalized (i.e. `T` ≠ `NonNormal`) can be serialized. A Point that is
@@ -113,6 +134,103 @@ impl Point<Normal, Public, NonZero> {
         y.copy_from_slice(&bytes[33..65]);
         backend::Point::norm_from_coordinates(x, y).map(|p| Point::from_inner(p, Normal))
     }
+
+    /// Creates a Point from a 65-byte hybrid encoding specified in [_Standards for Efficient
+    /// Cryptography_]. This is the uncompressed encoding except the first byte is `0x06` or `0x07`
+    /// (rather than `0x04`) which must match whether the y-coordinate is even or odd.
+    ///
+    /// Nothing should produce hybrid encodings but old versions of Bitcoin accepted them so they
+    /// turn up in old data.
+    ///
+    /// [_Standards for Efficient Cryptography_]: https://www.secg.org/sec1-v2.pdf
+    pub fn from_bytes_hybrid(mut bytes: [u8; 65]) -> Option<Self> {
+        let y_odd = match bytes[0] {
+            0x06 => false,
+            0x07 => true,
+            _ => return None,
+        };
+        bytes[0] = 0x04;
+        let point = Self::from_bytes_uncompressed(bytes)?;
+        if point.is_y_even() == y_odd {
+            return None;
+        }
+        Some(point)
+    }
+
+    /// Parses a point from any of the encodings specified in [_Standards for Efficient
+    /// Cryptography_]: compressed (33 bytes), uncompressed or hybrid (65 bytes).
+    ///
+    /// Use this for ingesting keys stored by other software (e.g. old Bitcoin keys or Ethereum
+    /// keys which are stored uncompressed). For data this library produced use [`from_bytes`]
+    /// which only accepts the compressed encoding.
+    ///
+    /// # Examples
+    /// ```
+    /// use secp256kfun::{marker::*, Point, G};
+    /// let point = G.normalize();
+    /// assert_eq!(Point::from_sec1_slice(&point.to_bytes()), Some(point));
+    /// assert_eq!(Point::from_sec1_slice(&point.to_bytes_uncompressed()), Some(point));
+    /// let mut hybrid = point.to_bytes_uncompressed();
+    /// hybrid[0] = 0x06; // G has an even y-coordinate
+    /// assert_eq!(Point::from_sec1_slice(&hybrid), Some(point));
+    /// assert_eq!(Point::from_sec1_slice(&hybrid[..64]), None);
+    /// ```
+    ///
+    /// [_Standards for Efficient Cryptography_]: https://www.secg.org/sec1-v2.pdf
+    /// [`from_bytes`]: Self::from_bytes
+    pub fn from_sec1_slice(slice: &[u8]) -> Option<Self> {
+        if let Ok(bytes) = <[u8; 33]>::try_from(slice) {
+            Self::from_bytes(bytes)
+        } else if let Ok(bytes) = <[u8; 65]>::try_from(slice) {
+            match bytes[0] {
+                0x04 => Self::from_bytes_uncompressed(bytes),
+                _ => Self::from_bytes_hybrid(bytes),
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Decodes a point from its 64-byte [BIP324] ElligatorSwift encoding. Every 64 byte string
+    /// decodes to a point.
+    ///
+    /// See [`to_elligator_swift`](Self::to_elligator_swift) and the [`ellswift`](crate::ellswift)
+    /// module.
+    ///
+    /// [BIP324]: https://bips.xyz/324
+    pub fn from_elligator_swift(bytes: &[u8; 64]) -> Self {
+        crate::ellswift::decode(bytes)
+    }
+
+    /// Hashes `msg` to a point with nobody knowing its discrete logarithm using the `hash_to_curve`
+    /// of [RFC 9380].
+    ///
+    /// With SHA256 as `H` this is the `secp256k1_XMD:SHA-256_SSWU_RO_` suite. `dst` is the
+    /// domain separation tag. See the [`hash_to_curve`](crate::hash_to_curve) module.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use secp256kfun::Point;
+    /// use sha2::Sha256;
+    /// let dst = b"QUUX-V01-CS02-with-secp256k1_XMD:SHA-256_SSWU_RO_";
+    /// let point = Point::hash_to_curve::<Sha256>(b"abc", dst);
+    /// assert_eq!(
+    ///     point.to_xonly_bytes(),
+    ///     secp256kfun::hex::decode_array::<32>(
+    ///         "3377e01eab42db296b512293120c6cee72b6ecf9f9205760bd9ff11fb3cb2c4b"
+    ///     )
+    ///     .unwrap()
+    /// );
+    /// ```
+    ///
+    /// [RFC 9380]: https://www.rfc-editor.org/rfc/rfc9380.html
+    pub fn hash_to_curve<H: digest::crypto_common::BlockSizeUser + digest::Digest + Default>(
+        msg: &[u8],
+        dst: &[u8],
+    ) -> Self {
+        crate::hash_to_curve::hash_to_curve::<H>(msg, dst)
+    }
 }
 
 impl<Z: ZeroChoice, S> Point<Normal, S, Z> {
@@ -153,16 +271,21 @@ impl<Z: ZeroChoice, S> Point<Normal, S, Z> {
     }
 
     /// Convenience method for calling [`from_bytes`] wth a slice.
-    /// Returns None if [`from_bytes`] would or if `slice` is not 33 bytes long.
+    /// Returns None if [`from_bytes`] would or if `slice` is not [`COMPRESSED_SIZE`] bytes long.
+    /// Use `Point::try_from` to find out why it failed.
     ///
     /// [`from_bytes`]: Self::from_bytes
+    /// [`COMPRESSED_SIZE`]: Self::COMPRESSED_SIZE
     pub fn from_slice(slice: &[u8]) -> Option<Self> {
-        if slice.len() != 33 {
-            return None;
-        }
-        let mut bytes = [0u8; 33];
-        bytes.copy_from_slice(slice);
-        Self::from_bytes(bytes)
+        Self::try_from(slice).ok()
+    }
+}
+
+impl<Z: ZeroChoice, S> TryFrom<&[u8]> for Point<Normal, S, Z> {
+    type Error = FromSliceError;
+
+    fn try_from(slice: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(FromSliceError::to_array(slice)?).ok_or(FromSliceError::Invalid)
     }
 }
 
@@ -236,6 +359,13 @@ impl Point<EvenY, Public, NonZero> {
 }
 
 impl<T, S, Z> Point<T, S, Z> {
+    /// The length of the compressed encoding of a point.
+    pub const COMPRESSED_SIZE: usize = 33;
+    /// The length of the uncompressed encoding of a point.
+    pub const UNCOMPRESSED_SIZE: usize = 65;
+    /// The length of the x-only encoding of a point.
+    pub const XONLY_SIZE: usize = 32;
+
     /// Returns true if this point the [`identity element`] of the group A.K.A. the point at infinity.
     ///
     /// [`identity_element`]: https://en.wikipedia.org/wiki/Identity_element
@@ -265,6 +395,32 @@ impl<T, S, Z> Point<T, S, Z> {
         op::point_conditional_negate(*self, cond)
     }
 
+    /// Checks whether this point and `other` are equal or one is the negation of the other i.e.
+    /// whether they have the same x-coordinate.
+    ///
+    /// Use this instead of `==` when comparing keys or nonces whose parity may not have been
+    /// normalized yet (e.g. a MuSig or Taproot key before and after being made [`EvenY`]). The
+    /// zero point is only equal to itself. Like `==` this runs in constant time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use secp256kfun::{g, Point, Scalar, G};
+    /// let X = g!({ Scalar::random(&mut rand::thread_rng()) } * G).normalize();
+    /// let (X_even, _) = X.into_point_with_even_y();
+    /// assert!(X.eq_up_to_parity(&X_even));
+    /// assert!(X.eq_up_to_parity(&-X));
+    /// assert!(!X.eq_up_to_parity(G));
+    /// ```
+    pub fn eq_up_to_parity<T2, S2, Z2>(&self, other: &Point<T2, S2, Z2>) -> bool
+    where
+        T: PointType,
+        T2: PointType,
+    {
+        let negated = -Point::<NonNormal, S2, Z2>::from_inner(other.0, NonNormal);
+        op::point_eq(self, other) | op::point_eq(self, &negated)
+    }
+
     /// Set the [`Secrecy`] of the point.
     pub fn set_secrecy<SNew>(self) -> Point<T, SNew, Z> {
         Point::from_inner(self.0, self.1)
@@ -325,11 +481,56 @@ impl<T, S, Z> Point<T, S, Z> {
 }
 
 impl<Z, T> Point<T, Public, Z> {
-    /// Checks if this point's x-coordiante is the equal to the scalar mod the
-    /// curve order. This is only useful for ECDSA implementations.
+    /// Checks whether this point's x-coordinate reduced mod the curve order is equal to `scalar`.
+    ///
+    /// This is the last step of ECDSA verification (the signature's `R_x` is the x-coordinate of
+    /// its nonce mod the order) and is also what you need to match signatures to nonces. The zero
+    /// point has no x-coordinate so is never equal. The point doesn't have to be normalized: the
+    /// check is done without an inversion.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use secp256kfun::{g, marker::*, Point, Scalar, G};
+    /// let R = g!({ Scalar::random(&mut rand::thread_rng()) } * G);
+    /// let R_x = Scalar::<Public, Zero>::from_bytes_mod_order(R.normalize().to_xonly_bytes());
+    /// assert!(R.x_eq_scalar(&R_x));
+    /// // negating a point doesn't change its x-coordinate
+    /// assert!((-R).x_eq_scalar(&R_x));
+    /// assert!(!R.x_eq_scalar(&Scalar::<Public>::one()));
+    /// ```
     pub fn x_eq_scalar<Z2>(&self, scalar: &Scalar<Public, Z2>) -> bool {
         crate::backend::VariableTime::point_x_eq_scalar(&self.0, &scalar.0)
     }
+
+    /// Finds the first of `points` for which [`x_eq_scalar`] is true.
+    ///
+    /// Returns the position of the point or `None` if none of them have `scalar` as their
+    /// x-coordinate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use secp256kfun::{g, marker::*, Point, Scalar, G};
+    /// let nonces = (0..5)
+    ///     .map(|_| g!({ Scalar::random(&mut rand::thread_rng()) } * G).normalize())
+    ///     .collect::<Vec<_>>();
+    /// let R_x = Scalar::<Public, Zero>::from_bytes_mod_order(nonces[3].to_xonly_bytes());
+    /// assert_eq!(Point::find_x_eq_scalar(&nonces, &R_x), Some(3));
+    /// ```
+    ///
+    /// [`x_eq_scalar`]: Self::x_eq_scalar
+    pub fn find_x_eq_scalar<'a, Z2>(
+        points: impl IntoIterator<Item = &'a Self>,
+        scalar: &Scalar<Public, Z2>,
+    ) -> Option<usize>
+    where
+        Self: 'a,
+    {
+        points
+            .into_iter()
+            .position(|point| point.x_eq_scalar(scalar))
+    }
 }
 
 impl<T: PointType, S, Z> core::ops::Neg for Point<T, S, Z> {
@@ -346,6 +547,28 @@ impl<T: PointType, S, Z> core::ops::Neg for &Point<T, S, Z> {
     }
 }
 
+crate::impl_binary_op! {
+    impl<T1, S1, Z1, T2, S2, Z2> Add<Point<T2, S2, Z2>>::add for Point<T1, S1, Z1> => Point<NonNormal, Public, Zero>
+    |A, B| op::point_add(A, B)
+}
+
+crate::impl_binary_op! {
+    impl<T1, S1, Z1, T2, S2, Z2> Sub<Point<T2, S2, Z2>>::sub for Point<T1, S1, Z1> => Point<NonNormal, Public, Zero>
+    |A, B| op::point_sub(A, B)
+}
+
+crate::impl_binary_op! {
+    impl<S1, Z1, T2, S2, Z2> Mul<Point<T2, S2, Z2>>::mul for Scalar<S1, Z1> => Point<NonNormal, Public, Z1::Out>
+    where [Z1: DecideZero<Z2>, S1: Secrecy, S2: Secrecy]
+    |x, P| op::scalar_mul_point(x, P)
+}
+
+crate::impl_binary_op! {
+    impl<T1, S1, Z1, S2, Z2> Mul<Scalar<S2, Z2>>::mul for Point<T1, S1, Z1> => Point<NonNormal, Public, Z2::Out>
+    where [Z2: DecideZero<Z1>, S1: Secrecy, S2: Secrecy]
+    |P, x| op::scalar_mul_point(x, P)
+}
+
 impl<T1, S1, Z1, T2, S2, Z2> PartialEq<Point<T2, S2, Z2>> for Point<T1, S1, Z1>
 where
     T1: PointType,
@@ -358,7 +581,7 @@ where
 
 impl<T: PointType, S, Z> Eq for Point<T, S, Z> {}
 
-impl core::hash::Hash for Point<Normal, Public, NonZero> {
+impl<Z> core::hash::Hash for Point<Normal, Public, Z> {
     fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
         self.to_bytes().hash(state)
     }
@@ -418,8 +641,74 @@ impl<S> Point<EvenY, S, NonZero> {
         backend::Point::norm_from_bytes_y_oddness(bytes, false)
             .map(|point| Point::from_inner(point, EvenY))
     }
+
+    /// Adds `tweak * G` to the x-only key returning the x-only result and whether it had to be
+    /// negated to have an even y-coordinate.
+    ///
+    /// This is the x-only key tweaking from [BIP341] where the returned `bool` is the parity that
+    /// goes in a control block. Returns `None` if the result is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use secp256kfun::{marker::*, Point, Scalar};
+    /// let internal_key = Point::random(&mut rand::thread_rng()).into_point_with_even_y().0;
+    /// let tweak = Scalar::random(&mut rand::thread_rng());
+    /// let (output_key, is_odd) = internal_key.tweak_add(&tweak).unwrap();
+    /// assert!(internal_key.tweak_add_check(&output_key, is_odd, &tweak));
+    /// ```
+    ///
+    /// [BIP341]: https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+    pub fn tweak_add(
+        self,
+        tweak: &Scalar<impl Secrecy, impl ZeroChoice>,
+    ) -> Option<(Point<EvenY>, bool)> {
+        let tweaked = op::point_add(self, op::scalar_mul_point(tweak, crate::G));
+        Some(tweaked.non_zero()?.into_point_with_even_y())
+    }
+
+    /// Checks that `tweaked` (with the parity `tweaked_is_odd`) is this key tweaked by `tweak` as
+    /// in [`tweak_add`](Self::tweak_add).
+    ///
+    /// This is how a taproot script path spend is checked against the output key.
+    #[must_use]
+    pub fn tweak_add_check(
+        &self,
+        tweaked: &Point<EvenY, impl Secrecy>,
+        tweaked_is_odd: bool,
+        tweak: &Scalar<impl Secrecy, impl ZeroChoice>,
+    ) -> bool {
+        match self.tweak_add(tweak) {
+            Some((expected, is_odd)) => expected == *tweaked && is_odd == tweaked_is_odd,
+            None => false,
+        }
+    }
 }
 
+impl<S> TryFrom<&[u8]> for Point<EvenY, S, NonZero> {
+    type Error = FromSliceError;
+
+    /// Decodes a point from a 32 byte x-coordinate like [`from_xonly_bytes`].
+    ///
+    /// [`from_xonly_bytes`]: Self::from_xonly_bytes
+    fn try_from(slice: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_xonly_bytes(FromSliceError::to_array(slice)?).ok_or(FromSliceError::Invalid)
+    }
+}
+
+/// Error returned when a point is the identity (the point at infinity) but must not be.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ZeroPointError;
+
+impl core::fmt::Display for ZeroPointError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "point is the identity (the point at infinity)")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ZeroPointError {}
+
 impl<T, S> Point<T, S, Zero> {
     /// Convert a point that is marked as `Zero` to `NonZero`.
     ///
@@ -434,6 +723,26 @@ impl<T, S> Point<T, S, Zero> {
         }
     }
 
+    /// Convert a point that is marked as `Zero` to `NonZero` returning a [`ZeroPointError`] if it
+    /// is the identity.
+    ///
+    /// This is [`non_zero`] for checking points received from other parties or computed from
+    /// them (e.g. an aggregate of their keys or nonces) where the identity should be rejected with
+    /// an error rather than an `Option`.
+    ///
+    /// # Example
+    /// ```
+    /// use secp256kfun::{g, marker::*, Point, ZeroPointError, G};
+    /// let their_key = Point::random(&mut rand::thread_rng());
+    /// let aggregate = g!(their_key - their_key).normalize();
+    /// assert_eq!(aggregate.non_identity(), Err(ZeroPointError));
+    /// ```
+    ///
+    /// [`non_zero`]: Point::non_zero
+    pub fn non_identity(self) -> Result<Point<T, S, NonZero>, ZeroPointError> {
+        self.non_zero().ok_or(ZeroPointError)
+    }
+
     /// Returns the [`identity element`] of the group A.K.A. the point at infinity.
     ///
     /// # Example
@@ -476,7 +785,48 @@ impl<S, T: Normalized> Point<T, S, NonZero> {
         self.coordinates().0
     }
 
-    /// Encodes a point as its compressed encoding as specified by [_Standards for Efficient Cryptography_].
+    /// Checks whether the point's x-coordinate is `xonly` (the [BIP340] x-only encoding of a key or
+    /// nonce) ignoring the parity of its y-coordinate.
+    ///
+    /// This lets you compare a point against an x-only key without first making it [`EvenY`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use secp256kfun::{g, Point, Scalar, G};
+    /// let X = g!({ Scalar::random(&mut rand::thread_rng()) } * G).normalize();
+    /// let xonly = X.into_point_with_even_y().0.to_xonly_bytes();
+    /// assert!(X.eq_xonly_bytes(&xonly));
+    /// assert!((-X).eq_xonly_bytes(&xonly));
+    /// ```
+    ///
+    /// [BIP340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+    pub fn eq_xonly_bytes(&self, xonly: &[u8; 32]) -> bool {
+        subtle::ConstantTimeEq::ct_eq(&self.to_xonly_bytes()[..], &xonly[..]).into()
+    }
+
+    /// Encodes the point as 64 bytes that are indistinguishable from uniformly random bytes using
+    /// the [BIP324] ElligatorSwift encoding.
+    ///
+    /// The encoding is randomized with `rng` so encoding the same point twice gives different
+    /// bytes. This isn't constant time so don't use it on secret points.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use secp256kfun::Point;
+    /// let point = Point::random(&mut rand::thread_rng());
+    /// let bytes = point.to_elligator_swift(&mut rand::thread_rng());
+    /// assert_eq!(Point::from_elligator_swift(&bytes), point);
+    /// ```
+    ///
+    /// [BIP324]: https://bips.xyz/324
+    pub fn to_elligator_swift(&self, rng: &mut impl RngCore) -> [u8; 64] {
+        let (x, y) = self.coordinates();
+        crate::ellswift::encode_coordinates(x, y, rng)
+    }
+
+    /// Encodes a point as its uncompressed encoding as specified by [_Standards for Efficient Cryptography_].
     ///
     /// # Example
     ///
@@ -589,11 +939,9 @@ impl<TR, SL, SR, ZR> SubAssign<Point<TR, SR, ZR>> for Point<NonNormal, SL, Zero>
 }
 
 impl<S: Secrecy> core::iter::Sum for Point<NonNormal, S, Zero> {
-    fn sum<I: Iterator<Item = Self>>(mut iter: I) -> Self {
-        let mut sum = iter.next().unwrap_or(Point::zero());
-        for point in iter {
-            sum += point;
-        }
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        let mut sum = Point::zero();
+        crate::op::add_points_assign(&mut sum, iter);
         sum
     }
 }
@@ -799,12 +1147,83 @@ mod test {
         );
     }
 
+    #[test]
+    fn sec1_encodings() {
+        for point in [G.normalize(), (-G).normalize()] {
+            let odd = !point.is_y_even();
+            let uncompressed = point.to_bytes_uncompressed();
+            let mut hybrid = uncompressed;
+            hybrid[0] = 0x06 | odd as u8;
+            assert_eq!(Point::from_bytes_hybrid(hybrid), Some(point));
+            assert_eq!(Point::from_sec1_slice(&hybrid), Some(point));
+            assert_eq!(Point::from_sec1_slice(&uncompressed), Some(point));
+            assert_eq!(Point::from_sec1_slice(&point.to_bytes()), Some(point));
+
+            // the parity in a hybrid encoding has to be right
+            hybrid[0] ^= 1;
+            assert_eq!(Point::from_bytes_hybrid(hybrid), None);
+            assert_eq!(Point::from_sec1_slice(&hybrid), None);
+            // and it's not an uncompressed encoding
+            assert_eq!(Point::from_bytes_hybrid(uncompressed), None);
+        }
+        let mut not_on_curve = G.normalize().to_bytes_uncompressed();
+        not_on_curve[64] ^= 1;
+        assert_eq!(Point::from_sec1_slice(&not_on_curve), None);
+        assert_eq!(Point::from_sec1_slice(&[0u8; 33]), None);
+        assert_eq!(Point::from_sec1_slice(&G.to_bytes()[..32]), None);
+        assert_eq!(Point::from_sec1_slice(&[]), None);
+    }
+
     #[test]
     fn zero_to_and_from_bytes() {
         let zero = Point::<_, Public, _>::zero();
         assert_eq!(Point::<_, _, Zero>::from_bytes(zero.to_bytes()), Some(zero));
     }
 
+    #[cfg(feature = "std")]
+    #[test]
+    fn points_as_map_keys() {
+        use std::collections::HashMap;
+        let point = Point::random(&mut rand::thread_rng());
+        let map = HashMap::from([
+            (point.mark_zero(), 1),
+            (Point::<Normal, Public, Zero>::zero(), 2),
+        ]);
+        assert_eq!(map[&point.mark_zero()], 1);
+        assert_eq!(map[&Point::zero()], 2);
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let point = Point::random(&mut rand::thread_rng());
+        let bytes = point.to_bytes();
+        assert_eq!(Point::try_from(&bytes[..]), Ok(point));
+        assert_eq!(
+            Point::<Normal, Public, NonZero>::try_from(&bytes[1..]),
+            Err(FromSliceError::WrongLength {
+                expected: Point::<Normal>::COMPRESSED_SIZE,
+                got: 32
+            })
+        );
+        assert_eq!(
+            Point::<Normal, Public, NonZero>::try_from(&[0u8; 33][..]),
+            Err(FromSliceError::Invalid)
+        );
+
+        let (xonly, _) = point.into_point_with_even_y();
+        assert_eq!(
+            Point::<EvenY>::try_from(&xonly.to_xonly_bytes()[..]),
+            Ok(xonly)
+        );
+        assert_eq!(
+            Point::<EvenY>::try_from(&bytes[..]),
+            Err(FromSliceError::WrongLength {
+                expected: 32,
+                got: 33
+            })
+        );
+    }
+
     #[test]
     fn zero_cases() {
         use crate::s;
@@ -813,6 +1232,8 @@ mod test {
         let forty_two_pub = s!(42).public();
         assert!(i.is_zero());
         assert!((-i).is_zero());
+        assert_eq!(i.non_identity(), Err(ZeroPointError));
+        assert_eq!(G.normalize().mark_zero().non_identity(), Ok(G.normalize()));
         expression_eq!([i] == [i]);
         expression_eq!([i] == [-i]);
         expression_eq!([i + i] == [i]);
@@ -845,4 +1266,54 @@ mod test {
         a -= b;
         assert_eq!(a, a_orig);
     }
+
+    #[test]
+    fn x_eq_scalar_above_curve_order() {
+        // find a point whose x-coordinate is n + r for a small r
+        let curve_order = crate::hex::decode_array::<32>(
+            "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+        )
+        .unwrap();
+        let (r, point) = (1u32..)
+            .find_map(|r| {
+                let mut x = curve_order;
+                // n + r doesn't carry past the last byte for small r
+                x[31] += r as u8;
+                let point = Point::<EvenY, Public>::from_xonly_bytes(x)?;
+                Some((r, point))
+            })
+            .unwrap();
+        let r = Scalar::from_non_zero_u32(r.try_into().unwrap()).public();
+        let non_normal = g!(point + G - G);
+        assert!(point.x_eq_scalar(&r));
+        assert!(non_normal.x_eq_scalar(&r));
+        assert!(!non_normal.x_eq_scalar(&crate::s!(r + 1).public()));
+        assert!(!Point::<Normal, Public, Zero>::zero().x_eq_scalar(&Scalar::<Public, _>::zero()));
+
+        let points = [
+            G.normalize(),
+            point.normalize(),
+            non_normal.normalize().non_zero().unwrap(),
+        ];
+        assert_eq!(Point::find_x_eq_scalar(&points, &r), Some(1));
+        assert_eq!(
+            Point::find_x_eq_scalar(&points, &crate::s!(r + 1).public()),
+            None
+        );
+    }
+
+    #[test]
+    fn eq_up_to_parity() {
+        let P = Point::random(&mut rand::thread_rng());
+        let (P_even, _) = P.into_point_with_even_y();
+        let non_normal = g!(P + G - G);
+        assert!(non_normal.eq_up_to_parity(&P_even));
+        assert!(P_even.eq_up_to_parity(&-non_normal));
+        assert!(!non_normal.eq_up_to_parity(G));
+        assert!(P.eq_xonly_bytes(&P_even.to_xonly_bytes()));
+        assert!(!P.eq_xonly_bytes(&G.normalize().to_xonly_bytes()));
+        let zero = Point::<Normal, Public, Zero>::zero();
+        assert!(zero.eq_up_to_parity(&zero));
+        assert!(!zero.eq_up_to_parity(&P));
+    }
 }
@@ -0,0 +1,112 @@
+//! Compact serialization for lists of points.
+//!
+//! Serializing `n` points individually takes `33 * n` bytes where 7 bits of every point's first
+//! byte carry no information. Here the y-coordinate parities are instead packed into a bitmap
+//! followed by the 32 byte x-coordinates:
+//!
+//! ```text
+//! parity bitmap (ceil(n / 8) bytes) || x_0 || x_1 || ... || x_{n-1}
+//! ```
+//!
+//! Bit `i % 8` (least significant first) of byte `i / 8` of the bitmap is set when point `i` has
+//! an odd y-coordinate. Unused bits in the last byte must be zero. The number of points isn't
+//! encoded since it can be worked out from the total length so the encoding of a list takes
+//! `32 * n + ceil(n / 8)` bytes.
+//!
+//! This is useful for polynomial commitments and nonce lists sent during key generation over
+//! constrained links.
+//!
+//! # Example
+//!
+//! ```
+//! use secp256kfun::{point_set, poly};
+//! let poly = poly::scalar::generate(10, &mut rand::thread_rng());
+//! let point_poly = poly::scalar::to_point_poly(&poly);
+//! let bytes = point_set::to_bytes(&point_poly);
+//! assert_eq!(bytes.len(), point_set::encoded_len(10));
+//! assert_eq!(bytes.len(), 10 * 32 + 2);
+//! assert_eq!(point_set::from_bytes(&bytes), Some(point_poly));
+//! ```
+use crate::{marker::*, Point};
+use alloc::vec::Vec;
+
+/// The number of bytes `n` points are serialized to by [`to_bytes`].
+pub fn encoded_len(n: usize) -> usize {
+    n * 32 + (n + 7) / 8
+}
+
+/// Serializes a list of points with their y-coordinate parities packed into a bitmap.
+///
+/// See the [module documentation](crate::point_set) for the format.
+pub fn to_bytes<T: Normalized, S>(points: &[Point<T, S, NonZero>]) -> Vec<u8> {
+    let bitmap_len = (points.len() + 7) / 8;
+    let mut bytes = vec![0u8; bitmap_len];
+    bytes.reserve(points.len() * 32);
+    for (i, point) in points.iter().enumerate() {
+        let [parity, xonly @ ..] = point.to_bytes();
+        bytes[i / 8] |= (parity & 1) << (i % 8);
+        bytes.extend_from_slice(&xonly);
+    }
+    bytes
+}
+
+/// Deserializes a list of points serialized with [`to_bytes`].
+///
+/// Returns `None` if the length of `bytes` isn't the length of any list of points, if any of the
+/// x-coordinates aren't on the curve or if the unused bits of the bitmap aren't zero.
+pub fn from_bytes(bytes: &[u8]) -> Option<Vec<Point>> {
+    let n = bytes.len() * 8 / 257;
+    if encoded_len(n) != bytes.len() {
+        return None;
+    }
+    let (bitmap, xonlys) = bytes.split_at((n + 7) / 8);
+    if n % 8 != 0 && bitmap[bitmap.len() - 1] >> (n % 8) != 0 {
+        return None;
+    }
+    xonlys
+        .chunks_exact(32)
+        .enumerate()
+        .map(|(i, xonly)| {
+            let mut point_bytes = [0u8; 33];
+            point_bytes[0] = 0x02 | ((bitmap[i / 8] >> (i % 8)) & 1);
+            point_bytes[1..].copy_from_slice(xonly);
+            Point::from_bytes(point_bytes)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{g, Scalar, G};
+
+    #[test]
+    fn round_trip_all_lengths() {
+        for n in 0..20 {
+            let points = (0..n)
+                .map(|_| {
+                    let x = Scalar::random(&mut rand::thread_rng());
+                    g!(x * G).normalize()
+                })
+                .collect::<Vec<_>>();
+            let bytes = to_bytes(&points);
+            assert_eq!(bytes.len(), encoded_len(n));
+            assert_eq!(from_bytes(&bytes), Some(points));
+            for wrong_len in [bytes.len() + 1, bytes.len().saturating_sub(1)] {
+                if wrong_len != bytes.len() {
+                    let mut wrong = bytes.clone();
+                    wrong.resize(wrong_len, 0);
+                    assert_eq!(from_bytes(&wrong), None);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn non_canonical_bitmap() {
+        let points = [G.normalize(), g!(2 * G).normalize(), g!(3 * G).normalize()];
+        let mut bytes = to_bytes(&points);
+        bytes[0] |= 1 << 3;
+        assert_eq!(from_bytes(&bytes), None);
+    }
+}
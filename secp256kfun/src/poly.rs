@@ -6,10 +6,14 @@
 //!
 //! [`Scalars`]: crate::Scalar
 //! [`Points`]: crate::Point
-use crate::{g, marker::*, s, Point, Scalar, G};
+use crate::{g, marker::*, s, Point, Scalar};
+#[cfg(feature = "alloc")]
+use crate::G;
 #[cfg(feature = "alloc")]
 use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
 use core::iter;
+#[cfg(feature = "alloc")]
 use rand_core::RngCore;
 
 /// Functions for dealing with scalar polynomials
@@ -34,6 +38,8 @@ pub mod scalar {
     ///     .collect::<Vec<_>>();
     /// let point_poly = poly::scalar::to_point_poly(&secret_poly);
     /// ```
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
     pub fn to_point_poly(scalar_poly: &[Scalar]) -> Vec<Point> {
         scalar_poly.iter().map(|a| g!(a * G).normalize()).collect()
     }
@@ -41,6 +47,8 @@ pub mod scalar {
     /// Generate a [`Scalar`] polynomial for key generation
     ///
     /// [`Scalar`]: crate::Scalar
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
     pub fn generate(threshold: usize, rng: &mut impl RngCore) -> Vec<Scalar> {
         (0..threshold).map(|_| Scalar::random(rng)).collect()
     }
@@ -53,6 +61,8 @@ pub mod scalar {
     /// ## Panics
     ///
     /// Panics if the indicies are not unique.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
     pub fn interpolate_and_eval_poly_at_0(
         secrets_at_indices: Vec<(Scalar<Public>, Scalar<Secret, impl ZeroChoice>)>,
     ) -> Scalar<Secret, Zero> {
@@ -84,6 +94,8 @@ pub mod point {
     /// Add the coefficients of two point polynomials.
     ///
     /// Handles mismatched polynomial lengths.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
     pub fn add<T: PointType + Default, S: Secrecy, Z: ZeroChoice>(
         poly1: &[Point<T, S, Z>],
         poly2: &[Point<T, S, Z>],
@@ -111,6 +123,8 @@ pub mod point {
     /// Panics if the indicies are not unique.
     ///
     /// A vector with a tail of zero coefficients means the interpolation was overdetermined.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
     pub fn interpolate(
         points_at_indicies: Vec<(Scalar<Public, impl ZeroChoice>, Point)>,
     ) -> Vec<Point<impl PointType, Public, Zero>> {
@@ -188,3 +202,80 @@ pub fn eval_basis_poly_at_0<'a>(
             s!(acc * x_m / denominator).public()
         })
 }
+
+/// The lagrange coefficients (see [`eval_basis_poly_at_0`]) for every index in a set of indices.
+///
+/// Computing a coefficient takes an inversion so when many signatures are made by the same
+/// quorum it's worth computing the coefficients once and reusing them. Each coefficient only takes
+/// one inversion to compute here rather than one for every other index.
+///
+/// # Example
+///
+/// ```
+/// use secp256kfun::{poly, s};
+/// let indices = [s!(1).public(), s!(4).public(), s!(5).public()];
+/// let basis = poly::LagrangeBasis::new(indices);
+/// assert_eq!(
+///     basis.coefficient(&s!(4).public()),
+///     Some(poly::eval_basis_poly_at_0(s!(4).public(), indices.iter()))
+/// );
+/// assert_eq!(basis.coefficient(&s!(2).public()), None);
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LagrangeBasis {
+    coefficients: alloc::collections::BTreeMap<Scalar<Public>, Scalar<Public>>,
+}
+
+#[cfg(feature = "alloc")]
+impl LagrangeBasis {
+    /// Computes the lagrange coefficient of each of the `indices`.
+    ///
+    /// Duplicate indices are ignored.
+    pub fn new(indices: impl IntoIterator<Item = Scalar<Public>>) -> Self {
+        let indices = indices
+            .into_iter()
+            .collect::<alloc::collections::BTreeSet<_>>();
+        let coefficients = indices
+            .iter()
+            .map(|x_j| {
+                let (numerator, denominator) = indices.iter().filter(|x_m| *x_m != x_j).fold(
+                    (Scalar::<Public>::one(), Scalar::<Public>::one()),
+                    |(numerator, denominator), x_m| {
+                        let difference = s!(x_m - x_j)
+                            .public()
+                            .non_zero()
+                            .expect("the indices are unique");
+                        (
+                            s!(numerator * x_m).public(),
+                            s!(denominator * difference).public(),
+                        )
+                    },
+                );
+                (*x_j, s!(numerator / denominator).public())
+            })
+            .collect();
+        Self { coefficients }
+    }
+
+    /// The lagrange coefficient of `index` or `None` if it isn't one of the indices.
+    pub fn coefficient(&self, index: &Scalar<Public>) -> Option<Scalar<Public>> {
+        self.coefficients.get(index).copied()
+    }
+
+    /// The indices the coefficients are for in ascending order.
+    pub fn indices(&self) -> impl DoubleEndedIterator<Item = Scalar<Public>> + '_ {
+        self.coefficients.keys().copied()
+    }
+
+    /// Whether the basis is for exactly `indices` which must be in ascending order (e.g. the keys
+    /// of a `BTreeMap`).
+    pub fn is_for<'a>(&self, indices: impl IntoIterator<Item = &'a Scalar<Public>>) -> bool {
+        let mut indices = indices.into_iter();
+        self.coefficients
+            .keys()
+            .all(|index| indices.next() == Some(index))
+            && indices.next().is_none()
+    }
+}
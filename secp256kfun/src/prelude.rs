@@ -0,0 +1,19 @@
+//! The stable core of the library in one import.
+//!
+//! ```
+//! use secp256kfun::prelude::*;
+//! let x = Scalar::random(&mut rand::thread_rng());
+//! let X = g!(x * G).normalize();
+//! assert_eq!(KeyPair::<Normal>::new(x).public_key(), X);
+//! ```
+//!
+//! Items are only removed from or renamed in the prelude in a release that bumps the minor
+//! version (the major version while we are pre-1.0) so importing it won't break on patch
+//! upgrades.
+pub use crate::{
+    g,
+    hash::{HashAdd, Tag},
+    marker::*,
+    nonce::{Deterministic, GlobalRng, NoNonces, NonceGen, Synthetic},
+    s, KeyPair, Point, Scalar, G,
+};
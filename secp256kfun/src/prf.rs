@@ -0,0 +1,116 @@
+//! A domain separated pseudorandom function keyed by a [`Scalar`].
+//!
+//! Protocols often need to derive values from a secret and some public context e.g. session
+//! identifiers, blinding factors or pseudorandom indices. [`Prf`] does this in one consistent way.
+//! It is a [BIP340] tagged hash whose tag is the `domain` followed by the key and then each input.
+//! Byte strings are length prefixed so inputs can't be shifted from one to the other.
+//!
+//! # Example
+//!
+//! ```
+//! use secp256kfun::{prf::Prf, Point, Scalar};
+//! let key = Scalar::random(&mut rand::thread_rng());
+//! let their_point = Point::random(&mut rand::thread_rng());
+//! let session_id = Prf::<sha2::Sha256>::new("my-protocol/session-id", &key)
+//!     .add_point(&their_point)
+//!     .add_bytes(b"payment-1")
+//!     .to_bytes();
+//! let blinding_factor = Prf::<sha2::Sha256>::new("my-protocol/blinding", &key)
+//!     .add_bytes(&session_id)
+//!     .to_scalar();
+//! # assert_ne!(session_id, blinding_factor.to_secret_bytes().into_inner());
+//! ```
+//!
+//! [BIP340]: https://bips.xyz/340
+use crate::{
+    digest::{generic_array::typenum::U32, Digest},
+    hash::{HashAdd, Tag},
+    marker::*,
+    Point, Scalar,
+};
+use core::num::NonZeroU32;
+
+/// A pseudorandom function keyed by a scalar. See the [module documentation](crate::prf).
+#[derive(Clone, Debug)]
+pub struct Prf<H> {
+    hash: H,
+}
+
+impl<H: Digest<OutputSize = U32> + Tag + Default + Clone> Prf<H> {
+    /// Starts a PRF evaluation for `domain` under `key`.
+    ///
+    /// `domain` should be unique to the application and to the purpose of the output.
+    pub fn new<S, Z>(domain: &str, key: &Scalar<S, Z>) -> Self {
+        let hash = H::default()
+            .tag_vectored([b"secp256kfun/prf/".as_slice(), domain.as_bytes()].into_iter())
+            .add(key);
+        Self { hash }
+    }
+
+    /// Adds a point to the input.
+    pub fn add_point<T: Normalized, S>(self, point: &Point<T, S, NonZero>) -> Self {
+        Self {
+            hash: self.hash.add(point.to_bytes()),
+        }
+    }
+
+    /// Adds a scalar to the input.
+    pub fn add_scalar<S, Z>(self, scalar: &Scalar<S, Z>) -> Self {
+        Self {
+            hash: self.hash.add(scalar),
+        }
+    }
+
+    /// Adds a byte string to the input.
+    pub fn add_bytes(self, bytes: &[u8]) -> Self {
+        Self {
+            hash: self.hash.add((bytes.len() as u64).to_be_bytes()).add(bytes),
+        }
+    }
+
+    /// Finishes the evaluation returning 32 pseudorandom bytes.
+    pub fn to_bytes(self) -> [u8; 32] {
+        self.hash.finalize().into()
+    }
+
+    /// Finishes the evaluation returning a pseudorandom scalar (e.g. a blinding factor).
+    pub fn to_scalar(self) -> Scalar<Secret, Zero> {
+        Scalar::from_bytes_mod_order(self.to_bytes())
+    }
+
+    /// Finishes the evaluation returning a pseudorandom index less than `bound`.
+    ///
+    /// The bias from reducing the output modulo `bound` is less than 2<sup>-96</sup>.
+    pub fn to_index(self, bound: NonZeroU32) -> u32 {
+        let bytes = self.to_bytes();
+        let int = u128::from_be_bytes(bytes[..16].try_into().expect("16 bytes"));
+        (int % bound.get() as u128) as u32
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+
+    #[test]
+    fn prf_is_domain_separated_and_keyed() {
+        let key = Scalar::random(&mut rand::thread_rng());
+        let other_key = Scalar::random(&mut rand::thread_rng());
+        let prf = |domain: &str, key: &Scalar| Prf::<Sha256>::new(domain, key);
+
+        assert_eq!(prf("a", &key).to_bytes(), prf("a", &key).to_bytes());
+        assert_ne!(prf("a", &key).to_bytes(), prf("b", &key).to_bytes());
+        assert_ne!(prf("a", &key).to_bytes(), prf("a", &other_key).to_bytes());
+        // byte strings can't be shifted between inputs
+        assert_ne!(
+            prf("a", &key).add_bytes(b"ab").add_bytes(b"c").to_bytes(),
+            prf("a", &key).add_bytes(b"a").add_bytes(b"bc").to_bytes()
+        );
+
+        let bound = NonZeroU32::new(3).unwrap();
+        for i in 0..20u32 {
+            assert!(prf("a", &key).add_bytes(&i.to_be_bytes()).to_index(bound) < 3);
+        }
+    }
+}
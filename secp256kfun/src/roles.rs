@@ -0,0 +1,134 @@
+//! Wrappers that give keys a protocol role.
+//!
+//! Most APIs in the crates built on this one take a bare [`Point`] or [`Scalar`] for every key
+//! so nothing stops you from passing an encryption key where the verification key goes. Wrapping
+//! your keys in these types when you create or load them makes the compiler catch that. The
+//! arguments of the adaptor signature APIs accept these wrappers as well as the bare keys they
+//! wrap (through [`Borrow`]) but not a wrapper for a different role.
+//!
+//! Convert back to the bare key with [`From`] or by accessing the field when you need the low
+//! level API.
+//!
+//! # Example
+//!
+//! ```
+//! use secp256kfun::{
+//!     marker::*,
+//!     roles::{DecryptionKey, EncryptionKey, SigningKey, VerificationKey},
+//!     Point, Scalar,
+//! };
+//! let decryption_key = DecryptionKey(Scalar::random(&mut rand::thread_rng()));
+//! let encryption_key: EncryptionKey = decryption_key.encryption_key();
+//! let signing_key = SigningKey(Scalar::random(&mut rand::thread_rng()));
+//! let verification_key: VerificationKey = signing_key.verification_key();
+//! let point: Point = encryption_key.into();
+//! ```
+//!
+//! [`Borrow`]: core::borrow::Borrow
+use crate::{g, marker::*, Point, Scalar, G};
+use core::borrow::Borrow;
+
+macro_rules! role {
+    ($(#[$meta:meta])* $name:ident($inner:ty)) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        #[cfg_attr(
+            feature = "serde",
+            derive(crate::serde::Deserialize, crate::serde::Serialize),
+            serde(crate = "crate::serde")
+        )]
+        pub struct $name(pub $inner);
+
+        impl From<$inner> for $name {
+            fn from(key: $inner) -> Self {
+                Self(key)
+            }
+        }
+
+        impl From<$name> for $inner {
+            fn from(key: $name) -> Self {
+                key.0
+            }
+        }
+
+        impl Borrow<$inner> for $name {
+            fn borrow(&self) -> &$inner {
+                &self.0
+            }
+        }
+    };
+}
+
+role! {
+    /// A secret key used to sign.
+    SigningKey(Scalar)
+}
+
+role! {
+    /// A public key that adaptor signatures are encrypted under.
+    EncryptionKey(Point)
+}
+
+role! {
+    /// The secret key that decrypts adaptor signatures encrypted under an [`EncryptionKey`].
+    DecryptionKey(Scalar)
+}
+
+/// A public key signatures are verified under.
+///
+/// `T` is the type of point e.g. [`EvenY`] for [BIP340] keys.
+///
+/// [BIP340]: https://bips.xyz/340
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(crate::serde::Deserialize, crate::serde::Serialize),
+    serde(
+        crate = "crate::serde",
+        bound(
+            serialize = "Point<T>: crate::serde::Serialize",
+            deserialize = "Point<T>: crate::serde::Deserialize<'de>"
+        )
+    )
+)]
+pub struct VerificationKey<T = Normal>(pub Point<T>);
+
+impl<T: Normalized> PartialEq for VerificationKey<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T: Normalized> Eq for VerificationKey<T> {}
+
+impl<T> From<Point<T>> for VerificationKey<T> {
+    fn from(key: Point<T>) -> Self {
+        Self(key)
+    }
+}
+
+impl<T> From<VerificationKey<T>> for Point<T> {
+    fn from(key: VerificationKey<T>) -> Self {
+        key.0
+    }
+}
+
+impl<T> Borrow<Point<T>> for VerificationKey<T> {
+    fn borrow(&self) -> &Point<T> {
+        &self.0
+    }
+}
+
+impl SigningKey {
+    /// The verification key of this signing key.
+    pub fn verification_key(&self) -> VerificationKey {
+        VerificationKey(g!(self.0 * G).normalize())
+    }
+}
+
+impl DecryptionKey {
+    /// The encryption key of this decryption key.
+    pub fn encryption_key(&self) -> EncryptionKey {
+        EncryptionKey(g!(self.0 * G).normalize())
+    }
+}
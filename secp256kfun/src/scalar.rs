@@ -1,11 +1,12 @@
 //! Scalar arithmetic (integers mod the secp256k1 group order)
-use crate::{backend, hash::HashInto, marker::*, op};
+use crate::{backend, ct::SecretBytes, hash::HashInto, marker::*, op, FromSliceError};
 use core::{
     marker::PhantomData,
     ops::{AddAssign, MulAssign, SubAssign},
 };
-use digest::{generic_array::typenum::U32, Digest};
+use digest::{crypto_common::BlockSizeUser, generic_array::typenum::U32, Digest};
 use rand_core::RngCore;
+use subtle::{Choice, ConditionallyNegatable};
 
 /// A secp256k1 scalar (an integer mod the curve order)
 ///
@@ -34,6 +35,24 @@ use rand_core::RngCore;
 ///
 /// [`G`]: crate::G
 ///
+/// # Operators
+///
+/// Scalars (and references to them) implement `Add`, `Sub`, `Neg` and `Mul` for code that can't
+/// use the [`s!`] macro (e.g. because it's generic over the operations). They give the same
+/// results with the same markers as the macro:
+///
+/// ```
+/// use secp256kfun::{marker::*, s, Scalar};
+/// let a = Scalar::random(&mut rand::thread_rng());
+/// let b = Scalar::random(&mut rand::thread_rng()).public();
+/// let sum: Scalar<Secret, Zero> = &a + &b;
+/// assert_eq!(sum, s!(a + b));
+/// let product: Scalar<Secret, NonZero> = a * b;
+/// assert_eq!(product, s!(a * b));
+/// ```
+///
+/// [`s!`]: crate::s
+///
 /// # Markers
 ///
 /// A `Scalar<S,Z>` has two markers:
@@ -41,6 +60,14 @@ use rand_core::RngCore;
 /// - `S`: A [`Secrecy`] to determine whether operations on this scalar should be done in constant time or not. By default scalars are [`Secret`] so operations run in constant-time.
 /// - `Z`: A [`ZeroChoice`] to keep track of whether the point might be zero or is guaranteed to non-zero.
 ///
+/// # Serialization
+///
+/// Scalars serialize to and from their 32-byte big-endian representation (as hex in human-readable
+/// formats). Parsing hex (with `FromStr` or `Deserialize`) is done with [`hex::decode_array_ct`] so
+/// that secret scalars can be loaded from config files or keystores without leaking their bytes
+/// through timing.
+///
+/// [`hex::decode_array_ct`]: crate::hex::decode_array_ct
 ///
 /// [1]: https://en.wikipedia.org/wiki/One-way_function
 /// [2]: https://en.wikipedia.org/wiki/Discrete_logarithm
@@ -72,8 +99,66 @@ impl<Z> core::hash::Hash for Scalar<Public, Z> {
 }
 
 impl<Z, S> Scalar<S, Z> {
+    /// The length of the encoding of a scalar.
+    pub const SIZE: usize = 32;
+
+    /// Encrypts (blinds) the scalar by adding a one-time `pad` to it modulo the curve order.
+    ///
+    /// Unlike XORing the bytes of the scalar with a pad this always produces a valid scalar and
+    /// there's no byte order to get wrong. The `pad` must be uniformly random and only used once
+    /// (see [`one_time_pad`]). Undo it with [`decrypt_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use secp256kfun::{marker::*, Scalar};
+    /// let share = Scalar::random(&mut rand::thread_rng());
+    /// let pad = Scalar::<Secret, Zero>::one_time_pad::<sha2::Sha256>(b"ecdh shared secret", b"my-app/share");
+    /// let ciphertext = share.encrypt_with(&pad).public();
+    /// assert_eq!(ciphertext.decrypt_with(&pad), share);
+    /// ```
+    ///
+    /// [`one_time_pad`]: Scalar::one_time_pad
+    /// [`decrypt_with`]: Scalar::decrypt_with
+    pub fn encrypt_with(
+        &self,
+        pad: &Scalar<impl Secrecy, impl ZeroChoice>,
+    ) -> Scalar<Secret, Zero> {
+        op::scalar_add(self, pad)
+    }
+
+    /// Decrypts (unblinds) a scalar produced by [`encrypt_with`] by subtracting the `pad`.
+    ///
+    /// [`encrypt_with`]: Scalar::encrypt_with
+    pub fn decrypt_with(
+        &self,
+        pad: &Scalar<impl Secrecy, impl ZeroChoice>,
+    ) -> Scalar<Secret, Zero> {
+        op::scalar_sub(self, pad)
+    }
+
     /// Serializes the scalar to its 32-byte big-endian representation
-    pub fn to_bytes(&self) -> [u8; 32] {
+    ///
+    /// With the `strict_ct` feature enabled this is only available for `Public` scalars. See
+    /// [`to_secret_bytes`].
+    ///
+    /// [`to_secret_bytes`]: Scalar::to_secret_bytes
+    pub fn to_bytes(&self) -> [u8; 32]
+    where
+        S: ExposeBytes,
+    {
+        self.raw_bytes()
+    }
+
+    /// Serializes the scalar to its 32-byte big-endian representation wrapped in
+    /// [`SecretBytes`] so it can only be compared in constant time.
+    ///
+    /// [`SecretBytes`]: crate::ct::SecretBytes
+    pub fn to_secret_bytes(&self) -> SecretBytes<32> {
+        SecretBytes::new(self.raw_bytes())
+    }
+
+    pub(crate) fn raw_bytes(&self) -> [u8; 32] {
         backend::BackendScalar::to_bytes(&self.0)
     }
 
@@ -104,34 +189,136 @@ impl<Z, S> Scalar<S, Z> {
 
     /// Decode a 32 byte long slice to a scalar.
     ///
-    /// Essentially [`from_bytes`] but checks that the slice is `32` bytes long first.
+    /// Essentially [`from_bytes`] but checks that the slice is [`SIZE`] bytes long first. Use
+    /// `Scalar::try_from` to find out why it failed.
     ///
     /// [`from_bytes`]: Self::from_bytes
+    /// [`SIZE`]: Self::SIZE
     pub fn from_slice(slice: &[u8]) -> Option<Self>
     where
         Z: ZeroChoice,
     {
-        if slice.len() != 32 {
-            return None;
-        }
-        let mut bytes = [0u8; 32];
-        bytes.copy_from_slice(slice);
+        Self::try_from(slice).ok()
+    }
+
+    /// Creates a scalar from 32 little-endian encoded bytes.
+    ///
+    /// Like [`from_bytes`] this returns `None` if the integer is greater than or equal to the curve
+    /// order (or zero for a `NonZero` scalar). Use [`from_le_bytes_mod_order`] to reduce it instead.
+    ///
+    /// [`from_bytes`]: Self::from_bytes
+    /// [`from_le_bytes_mod_order`]: Scalar::from_le_bytes_mod_order
+    pub fn from_le_bytes(mut bytes: [u8; 32]) -> Option<Self>
+    where
+        Z: ZeroChoice,
+    {
+        bytes.reverse();
         Self::from_bytes(bytes)
     }
 
+    /// Creates a scalar from four 64-bit limbs, least significant first.
+    ///
+    /// This is the layout of a 256-bit integer as words on 64-bit platforms (e.g. `U256::as_words`
+    /// and `U256::from_words` in `crypto-bigint`). Returns `None` if the integer is out of range
+    /// like [`from_bytes`].
+    ///
+    /// # Example
+    /// ```
+    /// use secp256kfun::{marker::*, Scalar};
+    /// let scalar = Scalar::<Public, Zero>::from_u64_limbs([7, 0, 0, 1]).unwrap();
+    /// assert_eq!(scalar.to_u64_limbs(), [7, 0, 0, 1]);
+    /// assert!(Scalar::<Secret, Zero>::from_u64_limbs([u64::MAX; 4]).is_none());
+    /// ```
+    ///
+    /// [`from_bytes`]: Self::from_bytes
+    pub fn from_u64_limbs(limbs: [u64; 4]) -> Option<Self>
+    where
+        Z: ZeroChoice,
+    {
+        Self::from_bytes(limbs_to_bytes(limbs))
+    }
+
+    /// Creates a scalar from the high and low 128 bits of a 256-bit integer.
+    ///
+    /// Returns `None` if the integer is out of range like [`from_bytes`].
+    ///
+    /// [`from_bytes`]: Self::from_bytes
+    pub fn from_u128_pair(high: u128, low: u128) -> Option<Self>
+    where
+        Z: ZeroChoice,
+    {
+        Self::from_bytes(u128_pair_to_bytes(high, low))
+    }
+
+    /// Serializes the scalar to its 32-byte little-endian representation.
+    pub fn to_le_bytes(&self) -> [u8; 32]
+    where
+        S: ExposeBytes,
+    {
+        let mut bytes = self.raw_bytes();
+        bytes.reverse();
+        bytes
+    }
+
+    /// The scalar as four 64-bit limbs, least significant first.
+    ///
+    /// See [`from_u64_limbs`](Self::from_u64_limbs).
+    pub fn to_u64_limbs(&self) -> [u64; 4]
+    where
+        S: ExposeBytes,
+    {
+        let bytes = self.raw_bytes();
+        let mut limbs = [0u64; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.rchunks(8)) {
+            *limb = u64::from_be_bytes(chunk.try_into().expect("8 bytes"));
+        }
+        limbs
+    }
+
+    /// The scalar as the high and low 128 bits of a 256-bit integer.
+    pub fn to_u128_pair(&self) -> (u128, u128)
+    where
+        S: ExposeBytes,
+    {
+        let bytes = self.raw_bytes();
+        (
+            u128::from_be_bytes(bytes[..16].try_into().expect("16 bytes")),
+            u128::from_be_bytes(bytes[16..].try_into().expect("16 bytes")),
+        )
+    }
+
     /// Negates the scalar in-place if `cond` is true.
     pub fn conditional_negate(&mut self, cond: bool) {
         op::scalar_conditional_negate(self, cond)
     }
 
-    /// Returns whether the scalar is greater than the `curve_order`/2.
-    pub fn is_high(&self) -> bool {
-        op::scalar_is_high(self)
+    /// Negates the scalar in-place if `cond` is set without branching on it.
+    ///
+    /// Use this instead of [`conditional_negate`] when `cond` depends on a secret (e.g. from
+    /// [`is_high_ct`]) so it never has to be turned into a `bool`.
+    ///
+    /// [`conditional_negate`]: Scalar::conditional_negate
+    /// [`is_high_ct`]: Scalar::is_high_ct
+    pub fn conditional_negate_ct(&mut self, cond: Choice) {
+        self.0.conditional_negate(cond)
     }
 
-    /// Returns true if the scalar is equal to zero
-    pub fn is_zero(&self) -> bool {
-        op::scalar_is_zero(self)
+    /// Returns whether the scalar is greater than the `curve_order`/2 in constant time.
+    ///
+    /// For [`Public`] scalars you can use [`is_high`] which returns a `bool`.
+    ///
+    /// [`is_high`]: Scalar::is_high
+    pub fn is_high_ct(&self) -> Choice {
+        self.0.is_high()
+    }
+
+    /// Returns whether the scalar is equal to zero in constant time.
+    ///
+    /// For [`Public`] scalars you can use [`is_zero`] which returns a `bool`.
+    ///
+    /// [`is_zero`]: Scalar::is_zero
+    pub fn is_zero_ct(&self) -> Choice {
+        self.0.is_zero()
     }
 
     pub(crate) fn from_inner(inner: backend::Scalar) -> Self {
@@ -168,6 +355,61 @@ impl<Z, S> Scalar<S, Z> {
     }
 }
 
+impl<Z> Scalar<Public, Z> {
+    /// Returns whether the scalar is greater than the `curve_order`/2.
+    ///
+    /// This is only available for [`Public`] scalars since branching on the result would leak
+    /// information about the scalar. Use [`is_high_ct`] for secret ones.
+    ///
+    /// [`is_high_ct`]: Scalar::is_high_ct
+    pub fn is_high(&self) -> bool {
+        op::scalar_is_high(self)
+    }
+
+    /// Returns true if the scalar is equal to zero.
+    ///
+    /// This is only available for [`Public`] scalars since branching on the result would leak
+    /// information about the scalar. Use [`is_zero_ct`] for secret ones.
+    ///
+    /// [`is_zero_ct`]: Scalar::is_zero_ct
+    pub fn is_zero(&self) -> bool {
+        op::scalar_is_zero(self)
+    }
+
+    /// Converts the scalar to a `u64` if it is small enough.
+    ///
+    /// This is only available for [`Public`] scalars since whether it fits leaks information about
+    /// a secret one.
+    pub fn to_u64(&self) -> Option<u64> {
+        self.to_u128()?.try_into().ok()
+    }
+
+    /// Converts the scalar to a `u128` if it is small enough.
+    ///
+    /// Only available for [`Public`] scalars like [`to_u64`](Self::to_u64).
+    pub fn to_u128(&self) -> Option<u128> {
+        match self.to_u128_pair() {
+            (0, low) => Some(low),
+            _ => None,
+        }
+    }
+}
+
+fn limbs_to_bytes(limbs: [u64; 4]) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (chunk, limb) in bytes.rchunks_mut(8).zip(limbs) {
+        chunk.copy_from_slice(&limb.to_be_bytes());
+    }
+    bytes
+}
+
+fn u128_pair_to_bytes(high: u128, low: u128) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes[..16].copy_from_slice(&high.to_be_bytes());
+    bytes[16..].copy_from_slice(&low.to_be_bytes());
+    bytes
+}
+
 impl<S> Scalar<S, NonZero> {
     /// Returns the multiplicative inverse of the scalar modulo the curve order.
     /// # Example
@@ -251,7 +493,7 @@ impl Scalar<Secret, NonZero> {
     /// hash.update(b"Chancellor on brink of second bailout for banks".as_ref());
     /// let scalar = Scalar::from_hash(hash);
     /// # assert_eq!(
-    /// #     scalar.to_bytes(),
+    /// #     scalar.to_secret_bytes().into_inner(),
     /// #     secp256kfun::hex::decode_array("8131e6f4b45754f2c90bd06688ceeabc0c45055460729928b4eecf11026a9e2d").unwrap()
     /// # );
     /// ```
@@ -276,7 +518,7 @@ impl<S> Scalar<S, Zero> {
     ///
     /// Returns `None` in the case that the scalar was in fact zero.
     pub fn non_zero(self) -> Option<Scalar<S, NonZero>> {
-        if self.is_zero() {
+        if op::scalar_is_zero(&self) {
             None
         } else {
             Some(Scalar::from_inner(self.0))
@@ -301,7 +543,10 @@ impl<S> Scalar<S, Zero> {
     /// # use core::convert::TryInto;
     /// use secp256kfun::{hex, marker::*, s, Scalar};
     /// let scalar = Scalar::<Secret, _>::from_bytes_mod_order(*b"xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx");
-    /// assert_eq!(scalar.to_bytes(), *b"xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx");
+    /// assert_eq!(
+    ///     scalar.to_secret_bytes().into_inner(),
+    ///     *b"xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx"
+    /// );
     /// let scalar_overflowed = Scalar::<Secret, _>::from_bytes_mod_order(
     ///     hex::decode_array("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364142")
     ///         .unwrap(),
@@ -312,6 +557,39 @@ impl<S> Scalar<S, Zero> {
         Self::from_inner(backend::BackendScalar::from_bytes_mod_order(bytes))
     }
 
+    /// Converts 32 little-endian bytes into a scalar by reducing it modulo the curve order `q`.
+    pub fn from_le_bytes_mod_order(mut bytes: [u8; 32]) -> Self {
+        bytes.reverse();
+        Self::from_bytes_mod_order(bytes)
+    }
+
+    /// Converts four 64-bit limbs (least significant first) into a scalar by reducing it modulo
+    /// the curve order `q`.
+    ///
+    /// See [`from_u64_limbs`](Scalar::from_u64_limbs) for the range checked version.
+    pub fn from_u64_limbs_mod_order(limbs: [u64; 4]) -> Self {
+        Self::from_bytes_mod_order(limbs_to_bytes(limbs))
+    }
+
+    /// Converts the high and low 128 bits of a 256-bit integer into a scalar by reducing it modulo
+    /// the curve order `q`.
+    pub fn from_u128_pair_mod_order(high: u128, low: u128) -> Self {
+        Self::from_bytes_mod_order(u128_pair_to_bytes(high, low))
+    }
+
+    /// Converts a `u64` into a scalar.
+    ///
+    /// Unlike `u32` there's no `From<u64>` implementation because it would stop integer literals
+    /// being inferred in `Scalar::from(42)`.
+    pub fn from_u64(int: u64) -> Self {
+        Self::from_u128(int.into())
+    }
+
+    /// Converts a `u128` into a scalar.
+    pub fn from_u128(int: u128) -> Self {
+        Self::from_bytes_mod_order(u128_pair_to_bytes(0, int))
+    }
+
     /// Exactly like [`from_bytes_mod_order`] except
     /// it operates on a 32-byte slice rather than an array.  If the slice is
     /// not 32 bytes long then the function returns `None`.
@@ -339,6 +617,24 @@ impl<S> Scalar<S, Zero> {
     }
 }
 
+impl Scalar<Secret, Zero> {
+    /// Derives a one-time pad for [`encrypt_with`] from a shared secret with [HKDF].
+    ///
+    /// `info` should be unique to the application and to what's being encrypted so that the same
+    /// shared secret never produces the same pad twice.
+    ///
+    /// [`encrypt_with`]: Scalar::encrypt_with
+    /// [HKDF]: crate::hash::hkdf
+    pub fn one_time_pad<H: BlockSizeUser + Digest + Default>(
+        shared_secret: &[u8],
+        info: &[u8],
+    ) -> Self {
+        let mut bytes = [0u8; 32];
+        crate::hash::hkdf::<H>(b"secp256kfun/one_time_pad", shared_secret, info, &mut bytes);
+        Self::from_bytes_mod_order(bytes)
+    }
+}
+
 impl<Z1, Z2, S1, S2> PartialEq<Scalar<S2, Z2>> for Scalar<S1, Z1> {
     fn eq(&self, rhs: &Scalar<S2, Z2>) -> bool {
         crate::op::scalar_eq(self, rhs)
@@ -347,6 +643,20 @@ impl<Z1, Z2, S1, S2> PartialEq<Scalar<S2, Z2>> for Scalar<S1, Z1> {
 
 impl<Z, S> Eq for Scalar<Z, S> {}
 
+impl<S, Z> subtle::ConditionallySelectable for Scalar<S, Z> {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self::from_inner(backend::Scalar::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl<S, Z: ZeroChoice> TryFrom<&[u8]> for Scalar<S, Z> {
+    type Error = FromSliceError;
+
+    fn try_from(slice: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(FromSliceError::to_array(slice)?).ok_or(FromSliceError::Invalid)
+    }
+}
+
 impl<S> From<u32> for Scalar<S, Zero> {
     fn from(int: u32) -> Self {
         Self::from_inner(backend::BackendScalar::from_u32(int))
@@ -362,7 +672,7 @@ crate::impl_fromstr_deserialize! {
 
 crate::impl_display_debug_serialize! {
     fn to_bytes<Z,S>(scalar: &Scalar<S,Z>) -> [u8;32] {
-        scalar.to_bytes()
+        scalar.raw_bytes()
     }
 }
 
@@ -382,9 +692,25 @@ impl<S, Z> core::ops::Neg for &Scalar<S, Z> {
     }
 }
 
+crate::impl_binary_op! {
+    impl<S1, Z1, S2, Z2> Add<Scalar<S2, Z2>>::add for Scalar<S1, Z1> => Scalar<Secret, Zero>
+    |a, b| op::scalar_add(a, b)
+}
+
+crate::impl_binary_op! {
+    impl<S1, Z1, S2, Z2> Sub<Scalar<S2, Z2>>::sub for Scalar<S1, Z1> => Scalar<Secret, Zero>
+    |a, b| op::scalar_sub(a, b)
+}
+
+crate::impl_binary_op! {
+    impl<S1, Z1, S2, Z2> Mul<Scalar<S2, Z2>>::mul for Scalar<S1, Z1> => Scalar<Secret, Z1::Out>
+    where [Z1: DecideZero<Z2>]
+    |a, b| op::scalar_mul(a, b)
+}
+
 impl<S, Z> HashInto for Scalar<S, Z> {
     fn hash_into(self, hash: &mut impl digest::Digest) {
-        hash.update(self.to_bytes())
+        hash.update(self.raw_bytes())
     }
 }
 
@@ -406,6 +732,25 @@ where
     }
 }
 
+/// Overwrites the scalar with zero.
+#[cfg(feature = "zeroize")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
+impl zeroize::Zeroize for Scalar<Secret, Zero> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Overwrites the scalar with one (rather than zero) so that it stays non-zero.
+#[cfg(feature = "zeroize")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
+impl zeroize::Zeroize for Scalar<Secret, NonZero> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+        self.0 = backend::BackendScalar::from_u32(1);
+    }
+}
+
 impl<SL, SR, ZR> AddAssign<Scalar<SR, ZR>> for Scalar<SL, Zero> {
     fn add_assign(&mut self, rhs: Scalar<SR, ZR>) {
         *self = crate::op::scalar_add(*self, rhs).set_secrecy::<SL>();
@@ -566,6 +911,61 @@ mod test {
         assert_eq!(Scalar::<Secret, Zero>::zero(), Scalar::<Secret, _>::from(0));
     }
 
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn zeroize() {
+        use zeroize::Zeroize;
+        let mut x = Scalar::random(&mut rand::thread_rng());
+        x.zeroize();
+        assert_eq!(x, Scalar::<Secret, NonZero>::one());
+        let mut x = Scalar::random(&mut rand::thread_rng()).mark_zero();
+        x.zeroize();
+        assert_eq!(x, Scalar::<Secret, Zero>::zero());
+        let mut poly = vec![Scalar::random(&mut rand::thread_rng()); 3];
+        poly.zeroize();
+        assert!(poly.is_empty());
+    }
+
+    #[test]
+    fn constant_time_predicates() {
+        let zero = Scalar::<Secret, Zero>::zero();
+        let minus_one = Scalar::<Secret, NonZero>::minus_one();
+        assert!(bool::from(zero.is_zero_ct()));
+        assert!(!bool::from(zero.is_high_ct()));
+        assert!(!bool::from(minus_one.is_zero_ct()));
+        assert!(bool::from(minus_one.is_high_ct()));
+        assert_eq!(
+            bool::from(minus_one.is_high_ct()),
+            minus_one.public().is_high()
+        );
+        assert_eq!(bool::from(zero.is_zero_ct()), zero.public().is_zero());
+    }
+
+    #[test]
+    fn conditional_negate_ct() {
+        let mut x = Scalar::<Secret, NonZero>::minus_one();
+        x.conditional_negate_ct(x.is_high_ct());
+        assert_eq!(x, s!(1));
+        x.conditional_negate_ct(x.is_high_ct());
+        assert_eq!(x, s!(1));
+    }
+
+    #[test]
+    fn one_time_pad() {
+        let pad = Scalar::<Secret, Zero>::one_time_pad::<sha2::Sha256>(b"secret", b"first");
+        let other_pad = Scalar::<Secret, Zero>::one_time_pad::<sha2::Sha256>(b"secret", b"second");
+        assert_ne!(pad, other_pad);
+        assert_eq!(
+            pad,
+            Scalar::<Secret, Zero>::one_time_pad::<sha2::Sha256>(b"secret", b"first")
+        );
+        let minus_one = Scalar::<Secret, NonZero>::minus_one();
+        let ciphertext = minus_one.encrypt_with(&pad);
+        assert_ne!(ciphertext, minus_one);
+        assert_eq!(ciphertext.decrypt_with(&pad), minus_one);
+        assert_ne!(ciphertext.decrypt_with(&other_pad), minus_one);
+    }
+
     #[test]
     fn from_slice() {
         assert!(Scalar::<Secret, NonZero>::from_slice(
@@ -592,7 +992,8 @@ mod test {
         assert_eq!(
             Scalar::<Secret, _>::from_slice_mod_order(b"xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx".as_ref())
                 .unwrap()
-                .to_bytes(),
+                .to_secret_bytes()
+                .into_inner(),
             *b"xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx"
         );
 
@@ -622,6 +1023,65 @@ mod test {
         );
     }
 
+    #[test]
+    fn integer_conversions() {
+        let scalar = Scalar::<Public, Zero>::from_bytes(
+            hex::decode_array("0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20")
+                .unwrap(),
+        )
+        .unwrap();
+        let (high, low) = scalar.to_u128_pair();
+        assert_eq!(high, 0x0102030405060708090a0b0c0d0e0f10);
+        assert_eq!(
+            Scalar::<Public, Zero>::from_u128_pair(high, low),
+            Some(scalar)
+        );
+        let limbs = scalar.to_u64_limbs();
+        assert_eq!(limbs[0], 0x191a1b1c1d1e1f20);
+        assert_eq!(Scalar::<Public, Zero>::from_u64_limbs(limbs), Some(scalar));
+        let le_bytes = scalar.to_le_bytes();
+        assert_eq!(le_bytes[0], 0x20);
+        assert_eq!(
+            Scalar::<Public, Zero>::from_le_bytes(le_bytes),
+            Some(scalar)
+        );
+        assert_eq!(scalar.to_u128(), None);
+
+        // the curve order plus one is out of range but reduces to one
+        let order_plus_one = hex::decode_array::<32>(
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364142",
+        )
+        .unwrap();
+        let (high, low) =
+            Scalar::<Public, Zero>::from_bytes_mod_order(order_plus_one).to_u128_pair();
+        assert_eq!((high, low), (0, 1));
+        let high = u128::from_be_bytes(order_plus_one[..16].try_into().unwrap());
+        let low = u128::from_be_bytes(order_plus_one[16..].try_into().unwrap());
+        assert_eq!(Scalar::<Public, Zero>::from_u128_pair(high, low), None);
+        assert_eq!(
+            Scalar::<Public, Zero>::from_u128_pair_mod_order(high, low),
+            Scalar::<Public, Zero>::from(1)
+        );
+        let mut le = order_plus_one;
+        le.reverse();
+        assert_eq!(Scalar::<Public, Zero>::from_le_bytes(le), None);
+        assert_eq!(
+            Scalar::<Public, Zero>::from_le_bytes_mod_order(le),
+            Scalar::<Public, Zero>::from(1)
+        );
+
+        assert_eq!(
+            Scalar::<Public, Zero>::from_u64(u64::MAX).to_u64(),
+            Some(u64::MAX)
+        );
+        assert_eq!(Scalar::<Public, Zero>::from_u128(u128::MAX).to_u64(), None);
+        assert_eq!(
+            Scalar::<Public, Zero>::from_u128(u128::MAX).to_u128(),
+            Some(u128::MAX)
+        );
+        assert_eq!(Scalar::<Public, NonZero>::from_u64_limbs([0; 4]), None);
+    }
+
     #[test]
     fn assign_tests() {
         let mut a = Scalar::<Secret, _>::from(42);
@@ -0,0 +1,153 @@
+//! Locked memory for long-lived secrets.
+//!
+//! A [`SecretBox`] keeps a value (usually a secret [`Scalar`]) in its own memory mapping that is
+//! locked into RAM with `mlock` so it is never written to swap and (on Linux) excluded from core
+//! dumps with `MADV_DONTDUMP`. When the box is dropped the memory is overwritten with zeros before
+//! it is unlocked and unmapped.
+//!
+//! This is meant for servers that hold keys for a long time. It only protects the copy in the box:
+//! the value passed to [`SecretBox::new`] is moved through the stack so create it as close to the
+//! box as possible and don't copy it back out.
+//!
+//! # Example
+//!
+//! ```
+//! use secp256kfun::{g, secret_box::SecretBox, Scalar, G};
+//! let secret_key = SecretBox::new(Scalar::random(&mut rand::thread_rng())).unwrap();
+//! let public_key = g!({ &*secret_key } * G).normalize();
+//! ```
+//!
+//! [`Scalar`]: crate::Scalar
+use core::{
+    fmt, mem,
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+};
+use std::io;
+
+/// A value stored in locked memory that is zeroed when dropped. See the [module
+/// documentation](crate::secret_box).
+pub struct SecretBox<T> {
+    ptr: NonNull<T>,
+    len: usize,
+}
+
+// SAFETY: the box owns its value exclusively like a `Box<T>` does.
+unsafe impl<T: Send> Send for SecretBox<T> {}
+// SAFETY: shared access to the box only gives shared access to the value.
+unsafe impl<T: Sync> Sync for SecretBox<T> {}
+
+impl<T> SecretBox<T> {
+    /// Moves `value` into newly mapped locked memory.
+    ///
+    /// Returns an error if the memory can't be mapped or locked (e.g. because the process's
+    /// `RLIMIT_MEMLOCK` has been reached).
+    pub fn new(value: T) -> io::Result<Self> {
+        // SAFETY: sysconf has no preconditions.
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        assert!(mem::align_of::<T>() <= page_size);
+        let pages = (mem::size_of::<T>().max(1) + page_size - 1) / page_size;
+        let len = pages * page_size;
+        // SAFETY: an anonymous private mapping doesn't alias any other memory.
+        let addr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANON,
+                -1,
+                0,
+            )
+        };
+        if addr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `addr` is a mapping of `len` bytes that we own.
+        let locked = unsafe { libc::mlock(addr, len) } == 0 && exclude_from_core_dumps(addr, len);
+        if !locked {
+            let error = io::Error::last_os_error();
+            // SAFETY: as above and nothing has been written to it yet.
+            unsafe { libc::munmap(addr, len) };
+            return Err(error);
+        }
+        let ptr = addr.cast::<T>();
+        // SAFETY: the mapping is large enough and page aligned so it is aligned for `T`.
+        unsafe { ptr.write(value) };
+        Ok(Self {
+            // SAFETY: `mmap` succeeded so `addr` isn't null.
+            ptr: unsafe { NonNull::new_unchecked(ptr) },
+            len,
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn exclude_from_core_dumps(addr: *mut libc::c_void, len: usize) -> bool {
+    // SAFETY: `addr` is a mapping of `len` bytes that we own.
+    unsafe { libc::madvise(addr, len, libc::MADV_DONTDUMP) == 0 }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn exclude_from_core_dumps(_addr: *mut libc::c_void, _len: usize) -> bool {
+    true
+}
+
+impl<T> Deref for SecretBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the value was written in `new` and lives until `drop`.
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T> DerefMut for SecretBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: as in `deref` and `&mut self` gives us exclusive access.
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<T> Drop for SecretBox<T> {
+    fn drop(&mut self) {
+        let addr = self.ptr.as_ptr();
+        // SAFETY: the value is valid and is never used again. The whole mapping is ours to
+        // overwrite and volatile writes can't be optimized away.
+        unsafe {
+            ptr::drop_in_place(addr);
+            let bytes = addr.cast::<u8>();
+            for i in 0..self.len {
+                ptr::write_volatile(bytes.add(i), 0);
+            }
+            libc::munlock(addr.cast(), self.len);
+            libc::munmap(addr.cast(), self.len);
+        }
+    }
+}
+
+impl<T> fmt::Debug for SecretBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecretBox(..)")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{g, Scalar, G};
+
+    #[test]
+    fn secret_box_holds_its_value() {
+        let secret_key = Scalar::random(&mut rand::thread_rng());
+        let mut boxed = SecretBox::new(secret_key).unwrap();
+        assert_eq!(*boxed, secret_key);
+        assert_eq!(g!({ &*boxed } * G), g!(secret_key * G));
+        *boxed = -secret_key;
+        assert_eq!(*boxed, -secret_key);
+        assert_eq!(format!("{:?}", boxed), "SecretBox(..)");
+
+        // values larger than a page work too
+        let big = SecretBox::new([7u8; 10_000]).unwrap();
+        assert!(big.iter().all(|byte| *byte == 7));
+    }
+}
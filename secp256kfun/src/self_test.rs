@@ -0,0 +1,140 @@
+//! Known-answer self tests for deployments that have to test their cryptography at start up.
+//!
+//! Regulated deployments of cryptographic modules often have to run "power-on self tests" that
+//! check the implementation produces known answers before it is used. [`self_test`] checks the
+//! curve arithmetic of this crate against fixed values and returns a [`SelfTestReport`] of what
+//! passed. `schnorr_fun` and `ecdsa_fun` have their own `self_test` functions that include these
+//! checks as well as their signature schemes' test vectors.
+//!
+//! # Example
+//!
+//! ```
+//! let report = secp256kfun::self_test::self_test();
+//! assert!(report.passed(), "{}", report);
+//! ```
+use crate::{g, marker::*, op, s, Point, Scalar, G};
+use alloc::vec::Vec;
+use core::{fmt, str::FromStr};
+
+/// The results of a set of self tests.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct SelfTestReport {
+    /// The result of each test in the order they were run.
+    pub results: Vec<SelfTestResult>,
+}
+
+/// The result of one self test.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SelfTestResult {
+    /// The name of the test.
+    pub name: &'static str,
+    /// Whether it produced the expected answer.
+    pub passed: bool,
+}
+
+impl SelfTestReport {
+    /// Records the result of the test called `name`.
+    pub fn record(&mut self, name: &'static str, passed: bool) {
+        self.results.push(SelfTestResult { name, passed });
+    }
+
+    /// Adds the results of `other` to the end of this report.
+    pub fn append(&mut self, other: SelfTestReport) {
+        self.results.extend(other.results);
+    }
+
+    /// Whether every test passed.
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|result| result.passed)
+    }
+
+    /// The names of the tests that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.results
+            .iter()
+            .filter(|result| !result.passed)
+            .map(|result| result.name)
+    }
+}
+
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let n_passed = self.results.iter().filter(|result| result.passed).count();
+        write!(f, "{}/{} self tests passed", n_passed, self.results.len())?;
+        for (i, failure) in self.failures().enumerate() {
+            write!(f, "{}{}", if i == 0 { ", failed: " } else { ", " }, failure)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs the known-answer tests of the curve arithmetic.
+pub fn self_test() -> SelfTestReport {
+    let mut report = SelfTestReport::default();
+    let point = |hex: &str| Point::<Normal, Public, NonZero>::from_str(hex).expect("valid point");
+    let two = Scalar::<Public, Zero>::from(2)
+        .non_zero()
+        .expect("non-zero");
+    let three = Scalar::<Public, Zero>::from(3)
+        .non_zero()
+        .expect("non-zero");
+    let k = Scalar::<Secret, NonZero>::from_str(
+        "85e9da1245f20d7c349153cf5a586e4eb446568563b9d45f300e5eacc87e0622",
+    )
+    .expect("valid scalar");
+    let k_G = point("02f523cb2d6535cc62f1c2970c7f0927f68be33e093bd0915921adaa962e8392cd");
+
+    report.record(
+        "secp256k1/point_decoding",
+        point("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798") == *G
+            // x = 5 is not on the curve
+            && Point::<Normal, Public, NonZero>::from_str(
+                "020000000000000000000000000000000000000000000000000000000000000005",
+            )
+            .is_err(),
+    );
+    report.record(
+        "secp256k1/point_addition",
+        g!(G + G) == point("02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5")
+            && g!(G + G + G)
+                == point("02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9")
+            && g!(G - G).is_zero(),
+    );
+    report.record(
+        "secp256k1/scalar_mul",
+        g!(k * G) == k_G
+            && g!(two * G) == g!(G + G)
+            && g!({ Scalar::<Public, _>::minus_one() } * G) == -G.normalize(),
+    );
+    report.record(
+        "secp256k1/scalar_mul_vartime",
+        op::double_mul_vartime(k.public(), G, three, k_G) == g!((k + three * k) * G),
+    );
+    report.record(
+        "secp256k1/scalar_arithmetic",
+        s!(k * { k.invert() }) == Scalar::<Secret, NonZero>::one()
+            && s!(two * three) == Scalar::<Public, Zero>::from(6),
+    );
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn self_test_passes() {
+        let report = self_test();
+        assert!(report.passed(), "{}", report);
+        assert_eq!(report.failures().count(), 0);
+
+        let mut report = report;
+        report.record("broken", false);
+        assert!(!report.passed());
+        assert_eq!(report.failures().collect::<Vec<_>>(), vec!["broken"]);
+        assert_eq!(
+            format!("{}", report),
+            "5/6 self tests passed, failed: broken"
+        );
+    }
+}
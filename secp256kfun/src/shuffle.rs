@@ -0,0 +1,114 @@
+//! Deterministic shuffles and committee selection seeded by public randomness.
+//!
+//! Consensus protocols often need every participant to agree on a random ordering of some set
+//! (e.g. leader rotation) or a random subset of it (e.g. a committee) without anyone being able to
+//! bias the result. The seed should be randomness that no one could choose, such as the output of
+//! a VRF evaluated on the round number or a randomness beacon.
+//!
+//! [`Shuffle`] expands the seed with a [BIP340] tagged hash under a `domain` and uses it to run a
+//! Fisher–Yates shuffle. The same seed and domain always give the same result and different
+//! domains give independent results so one seed can be used for several selections.
+//!
+//! # Example
+//!
+//! ```
+//! use secp256kfun::shuffle::Shuffle;
+//! let vrf_output = [42u8; 32];
+//! let validators = ["alice", "bob", "carol", "dave", "eve"];
+//! let shuffle = Shuffle::<sha2::Sha256>::new("my-chain/committee", &vrf_output);
+//! let committee = shuffle.select(&validators, 3);
+//! assert_eq!(committee.len(), 3);
+//! // everyone with the same seed selects the same committee
+//! assert_eq!(
+//!     Shuffle::<sha2::Sha256>::new("my-chain/committee", &vrf_output).select(&validators, 3),
+//!     committee
+//! );
+//! ```
+//!
+//! [BIP340]: https://bips.xyz/340
+use crate::{
+    digest::{generic_array::typenum::U32, Digest},
+    hash::{HashAdd, Tag},
+};
+
+/// A deterministic shuffle seeded by public randomness. See the [module
+/// documentation](crate::shuffle).
+#[derive(Clone, Debug)]
+pub struct Shuffle<H> {
+    hash: H,
+}
+
+impl<H: Digest<OutputSize = U32> + Tag + Default + Clone> Shuffle<H> {
+    /// Creates a shuffle for `domain` seeded by `seed`.
+    ///
+    /// `domain` should be unique to the application and to what is being selected.
+    pub fn new(domain: &str, seed: &[u8; 32]) -> Self {
+        let hash = H::default()
+            .tag_vectored([b"secp256kfun/shuffle/".as_slice(), domain.as_bytes()].into_iter())
+            .add(seed);
+        Self { hash }
+    }
+
+    /// Shuffles `items` in place.
+    ///
+    /// The bias from reducing the hash output to each index is less than `items.len()` /
+    /// 2<sup>128</sup>.
+    pub fn shuffle<T>(&self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.index(i as u64, i as u128 + 1);
+            items.swap(i, j);
+        }
+    }
+
+    /// Selects `size` of `items` returning them in the order they were selected.
+    ///
+    /// The result is the first `size` items of the [`shuffle`](Self::shuffle) of `items` so the
+    /// selection is uniform and the first one selected can be used as a leader. If `size` is
+    /// greater than the number of items all of them are returned.
+    #[cfg(feature = "alloc")]
+    pub fn select<T: Clone>(&self, items: &[T], size: usize) -> alloc::vec::Vec<T> {
+        let mut items = items.to_vec();
+        self.shuffle(&mut items);
+        items.truncate(size);
+        items
+    }
+
+    fn index(&self, counter: u64, bound: u128) -> usize {
+        let bytes: [u8; 32] = self
+            .hash
+            .clone()
+            .add(counter.to_be_bytes())
+            .finalize()
+            .into();
+        let int = u128::from_be_bytes(bytes[..16].try_into().expect("16 bytes"));
+        (int % bound) as usize
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec::Vec;
+    use sha2::Sha256;
+
+    #[test]
+    fn shuffle_is_a_deterministic_permutation() {
+        let shuffle = |domain: &str, seed: u8| Shuffle::<Sha256>::new(domain, &[seed; 32]);
+        let items = (0..20u32).collect::<Vec<_>>();
+        let shuffled = shuffle("a", 1).select(&items, items.len());
+
+        let mut sorted = shuffled.clone();
+        sorted.sort();
+        assert_eq!(sorted, items);
+        assert_ne!(shuffled, items);
+
+        assert_eq!(shuffle("a", 1).select(&items, items.len()), shuffled);
+        assert_ne!(shuffle("a", 2).select(&items, items.len()), shuffled);
+        assert_ne!(shuffle("b", 1).select(&items, items.len()), shuffled);
+
+        assert_eq!(shuffle("a", 1).select(&items, 5), shuffled[..5]);
+        assert_eq!(shuffle("a", 1).select(&items, 30), shuffled);
+        let mut empty: [u32; 0] = [];
+        shuffle("a", 1).shuffle(&mut empty);
+    }
+}
@@ -0,0 +1,116 @@
+//! Traits for code that is generic over the signature scheme.
+//!
+//! [`SignatureScheme`] is implemented by the `Schnorr` instances of `schnorr_fun` and the `ECDSA`
+//! instances of `ecdsa_fun` so things like channel state machines or certificate layers can be
+//! written once for both. Messages are 32-byte hashes (what ECDSA signs and what BIP340 signs
+//! with a raw message).
+//!
+//! Both BIP340 Schnorr signatures (`schnorr_fun`) and ECDSA signatures (`ecdsa_fun`) are a nonce
+//! part `r` and a scalar `s`. Tools like block explorers or analytics that look at signatures
+//! without caring which scheme made them can be written against [`SignatureComponents`] and take
+//! either.
+//!
+//! `r` is the 32 bytes of the nonce as they appear in the signature: the x-coordinate of `R` for
+//! Schnorr and `x(R) mod q` for ECDSA. Only the Schnorr nonce can be recovered as a point from
+//! `r` (it has an even y-coordinate) so [`SignatureComponents::R`] is `None` for ECDSA.
+//!
+//! # Example
+//!
+//! ```
+//! use secp256kfun::signature::{SignatureComponents, SignatureScheme};
+//! fn sign_and_send<S: SignatureScheme>(scheme: &S, keypair: &S::KeyPair, message: &[u8; 32]) -> [u8; 64] {
+//!     let signature = scheme.sign(keypair, message);
+//!     debug_assert!(scheme.verify(&scheme.public_key(keypair), message, &signature));
+//!     S::signature_to_bytes(&signature)
+//! }
+//!
+//! fn reuses_nonce<S: SignatureComponents>(signatures: &[S]) -> bool {
+//!     signatures
+//!         .iter()
+//!         .enumerate()
+//!         .any(|(i, a)| signatures[..i].iter().any(|b| a.r() == b.r()))
+//! }
+//! ```
+use crate::{marker::*, Point, Scalar};
+
+/// Keys, signing and verification of a signature scheme.
+///
+/// See the [module documentation](crate::signature).
+pub trait SignatureScheme {
+    /// The signer's keypair.
+    type KeyPair: Clone;
+    /// The verifier's public key.
+    type PublicKey: Clone + PartialEq;
+    /// The encoding of a public key.
+    type PublicKeyBytes: AsRef<[u8]>;
+    /// The signatures the scheme produces.
+    type Signature: SignatureComponents + Clone;
+
+    /// Creates the keypair for `secret_key`.
+    fn new_keypair(&self, secret_key: Scalar) -> Self::KeyPair;
+
+    /// The public key of `keypair`.
+    fn public_key(&self, keypair: &Self::KeyPair) -> Self::PublicKey;
+
+    /// Signs the 32-byte `message`.
+    fn sign(&self, keypair: &Self::KeyPair, message: &[u8; 32]) -> Self::Signature;
+
+    /// Verifies `signature` on `message` under `public_key`.
+    #[must_use]
+    fn verify(
+        &self,
+        public_key: &Self::PublicKey,
+        message: &[u8; 32],
+        signature: &Self::Signature,
+    ) -> bool;
+
+    /// Encodes `public_key` the way the scheme usually does.
+    fn public_key_to_bytes(public_key: &Self::PublicKey) -> Self::PublicKeyBytes;
+
+    /// Decodes a public key from [`public_key_to_bytes`](Self::public_key_to_bytes).
+    fn public_key_from_bytes(bytes: &[u8]) -> Option<Self::PublicKey>;
+
+    /// Encodes `signature` as `r || s`.
+    fn signature_to_bytes(signature: &Self::Signature) -> [u8; 64] {
+        let (r, s) = signature.to_components();
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&r);
+        bytes[32..].copy_from_slice(&s.to_bytes());
+        bytes
+    }
+
+    /// Decodes a signature from [`signature_to_bytes`](Self::signature_to_bytes).
+    fn signature_from_bytes(bytes: [u8; 64]) -> Option<Self::Signature> {
+        let r = bytes[..32].try_into().expect("32 bytes");
+        let s = Scalar::from_slice(&bytes[32..])?;
+        Self::Signature::from_components(r, s)
+    }
+}
+
+/// Access to the nonce and scalar components of a signature and reconstruction from them.
+///
+/// See the [module documentation](crate::signature).
+pub trait SignatureComponents: Sized {
+    /// A short name for the signature scheme (e.g. `"bip340"` or `"ecdsa"`).
+    const SCHEME: &'static str;
+
+    /// The 32-byte encoding of the signature's nonce component.
+    fn r(&self) -> [u8; 32];
+
+    /// The signature's nonce point if it is determined by the signature.
+    fn R(&self) -> Option<Point<Normal, Public, NonZero>>;
+
+    /// The signature's scalar component.
+    fn s(&self) -> Scalar<Public, Zero>;
+
+    /// Creates the signature from its components.
+    ///
+    /// Returns `None` if they aren't valid for the scheme (e.g. `r` isn't an x-coordinate of a
+    /// point for Schnorr or either component is zero for ECDSA).
+    fn from_components(r: [u8; 32], s: Scalar<Public, Zero>) -> Option<Self>;
+
+    /// The signature split into `(r, s)`.
+    fn to_components(&self) -> ([u8; 32], Scalar<Public, Zero>) {
+        (self.r(), self.s())
+    }
+}
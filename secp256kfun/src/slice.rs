@@ -2,6 +2,46 @@ use crate::{hash::HashInto, marker::*};
 use core::marker::PhantomData;
 use subtle::ConstantTimeEq;
 
+/// Error returned when a type can't be decoded from a byte slice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FromSliceError {
+    /// The slice isn't the length of the encoding.
+    WrongLength {
+        /// The length of the encoding.
+        expected: usize,
+        /// The length of the slice.
+        got: usize,
+    },
+    /// The slice is the right length but isn't a valid encoding.
+    Invalid,
+}
+
+impl FromSliceError {
+    /// Copies `slice` into an array returning [`WrongLength`] if it isn't `N` bytes long.
+    ///
+    /// [`WrongLength`]: Self::WrongLength
+    pub fn to_array<const N: usize>(slice: &[u8]) -> Result<[u8; N], Self> {
+        slice.try_into().map_err(|_| FromSliceError::WrongLength {
+            expected: N,
+            got: slice.len(),
+        })
+    }
+}
+
+impl core::fmt::Display for FromSliceError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            FromSliceError::WrongLength { expected, got } => {
+                write!(f, "expected {expected} bytes but got {got}")
+            }
+            FromSliceError::Invalid => write!(f, "invalid encoding"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FromSliceError {}
+
 /// Potentially secret bytes of arbitrary length.
 ///
 /// This type exists so you can mark some bytes with a [`Secrecy`]. The only
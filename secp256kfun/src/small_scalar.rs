@@ -0,0 +1,105 @@
+use crate::{marker::*, Scalar};
+
+/// A scalar that is known to be at most `MAX`.
+///
+/// Protocols often use scalars that must be small integers like the indices of secret shares or
+/// the digits of a decomposition. Passing an arbitrary integer or scalar where one of these is
+/// expected would silently wrap around or be out of range so `SmallScalar` checks the range when
+/// it is created.
+///
+/// # Example
+///
+/// ```
+/// use secp256kfun::{marker::*, s, Scalar, SmallScalar};
+/// type Digit = SmallScalar<15>;
+/// assert_eq!(Digit::new(16), None);
+/// let digit = Digit::new(9).unwrap();
+/// assert_eq!(Scalar::<Public, Zero>::from(digit), s!(9));
+/// // range assertions on scalars
+/// assert_eq!(Digit::from_scalar(&s!(9).public()), Some(digit));
+/// assert_eq!(Digit::from_scalar(&s!(-9).public()), None);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SmallScalar<const MAX: u32>(u32);
+
+impl<const MAX: u32> SmallScalar<MAX> {
+    /// The largest value.
+    pub const MAX: Self = Self(MAX);
+
+    /// Creates a `SmallScalar`. Returns `None` if `int` is greater than `MAX`.
+    pub fn new(int: u32) -> Option<Self> {
+        if int <= MAX {
+            Some(Self(int))
+        } else {
+            None
+        }
+    }
+
+    /// Converts `scalar` to a `SmallScalar`. Returns `None` if it's greater than `MAX`.
+    pub fn from_scalar<Z>(scalar: &Scalar<Public, Z>) -> Option<Self> {
+        let bytes = scalar.to_bytes();
+        if bytes[..28].iter().any(|byte| *byte != 0) {
+            return None;
+        }
+        Self::new(u32::from_be_bytes(bytes[28..].try_into().expect("4 bytes")))
+    }
+
+    /// The value as an integer.
+    pub fn get(self) -> u32 {
+        self.0
+    }
+
+    /// The value as a scalar.
+    pub fn to_scalar<S>(self) -> Scalar<S, Zero> {
+        Scalar::from(self.0)
+    }
+}
+
+impl<S, const MAX: u32> From<SmallScalar<MAX>> for Scalar<S, Zero> {
+    fn from(small: SmallScalar<MAX>) -> Self {
+        small.to_scalar()
+    }
+}
+
+impl<const MAX: u32> TryFrom<u32> for SmallScalar<MAX> {
+    type Error = OutOfRange;
+
+    fn try_from(int: u32) -> Result<Self, Self::Error> {
+        Self::new(int).ok_or(OutOfRange)
+    }
+}
+
+/// Error returned when creating a [`SmallScalar`] from an integer greater than its maximum.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutOfRange;
+
+impl core::fmt::Display for OutOfRange {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "integer is out of range")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OutOfRange {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::s;
+
+    #[test]
+    fn range_is_checked() {
+        assert_eq!(SmallScalar::<0>::new(0).map(SmallScalar::get), Some(0));
+        assert_eq!(SmallScalar::<0>::new(1), None);
+        assert_eq!(SmallScalar::<7>::try_from(8u32), Err(OutOfRange));
+        assert_eq!(
+            SmallScalar::<{ u32::MAX }>::from_scalar(&Scalar::<Public, Zero>::from(u32::MAX)),
+            Some(SmallScalar::MAX)
+        );
+        let big = Scalar::<Public, Zero>::from(u32::MAX);
+        assert_eq!(
+            SmallScalar::<{ u32::MAX }>::from_scalar(&s!(big + 1).public()),
+            None
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! A process wide registry of hash tags for catching accidental domain collisions.
+//!
+//! Each protocol built on this crate separates its hashes with a tag (e.g. the tag passed to
+//! [`Tag::tag`] for nonce derivation or a [BIP340] application tag for challenges). In a large
+//! application made of many modules nothing stops two of them from choosing the same tag and
+//! so sharing a hash domain. Have each module [`register`] the tags it uses when it starts up and
+//! debug builds will panic as soon as two different owners register the same tag. In release
+//! builds registering does nothing.
+//!
+//! # Example
+//!
+//! ```
+//! use secp256kfun::tag_registry;
+//! tag_registry::register("my-app/payments/nonce", "payments");
+//! tag_registry::register("my-app/payments/nonce", "payments"); // the same owner is fine
+//! tag_registry::register("my-app/voting/nonce", "voting");
+//! ```
+//!
+//! Registering `"my-app/payments/nonce"` for `"voting"` now would panic in a debug build.
+//!
+//! [`Tag::tag`]: crate::hash::Tag::tag
+//! [BIP340]: https://bips.xyz/340
+use core::fmt;
+
+/// Registers that `owner` uses `tag`.
+///
+/// # Panics
+///
+/// In debug builds if a different `owner` has already registered `tag`.
+pub fn register(tag: &'static str, owner: &'static str) {
+    if let Err(collision) = try_register(tag, owner) {
+        panic!("{}", collision);
+    }
+}
+
+/// Like [`register`] but returns the collision rather than panicking.
+///
+/// In release builds this does nothing and always returns `Ok(())`.
+pub fn try_register(tag: &'static str, owner: &'static str) -> Result<(), TagCollision> {
+    #[cfg(not(debug_assertions))]
+    {
+        let _ = (tag, owner);
+        Ok(())
+    }
+    #[cfg(debug_assertions)]
+    {
+        registry_insert(tag, owner)
+    }
+}
+
+#[cfg(debug_assertions)]
+fn registry_insert(tag: &'static str, owner: &'static str) -> Result<(), TagCollision> {
+    static REGISTRY: std::sync::Mutex<std::vec::Vec<(&str, &str)>> =
+        std::sync::Mutex::new(std::vec::Vec::new());
+    let mut registry = REGISTRY
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    match registry.iter().find(|(registered, _)| *registered == tag) {
+        Some((_, first_owner)) if *first_owner != owner => Err(TagCollision {
+            tag,
+            first_owner,
+            second_owner: owner,
+        }),
+        Some(_) => Ok(()),
+        None => {
+            registry.push((tag, owner));
+            Ok(())
+        }
+    }
+}
+
+/// Two owners registered the same tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TagCollision {
+    /// The tag.
+    pub tag: &'static str,
+    /// The owner that registered it first.
+    pub first_owner: &'static str,
+    /// The owner that tried to register it again.
+    pub second_owner: &'static str,
+}
+
+impl fmt::Display for TagCollision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "hash tag {:?} registered by {} was registered again by {}",
+            self.tag, self.first_owner, self.second_owner
+        )
+    }
+}
+
+impl std::error::Error for TagCollision {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn detects_collisions() {
+        assert_eq!(try_register("tag_registry/test/a", "first"), Ok(()));
+        assert_eq!(try_register("tag_registry/test/a", "first"), Ok(()));
+        assert_eq!(try_register("tag_registry/test/b", "second"), Ok(()));
+        assert_eq!(
+            try_register("tag_registry/test/a", "second"),
+            Err(TagCollision {
+                tag: "tag_registry/test/a",
+                first_owner: "first",
+                second_owner: "second",
+            })
+        );
+    }
+
+    #[cfg(not(debug_assertions))]
+    #[test]
+    fn release_builds_ignore_collisions() {
+        assert_eq!(try_register("tag_registry/test/c", "first"), Ok(()));
+        assert_eq!(try_register("tag_registry/test/c", "second"), Ok(()));
+        register("tag_registry/test/c", "third");
+    }
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "alloc_audit")]
+#![allow(non_snake_case)]
+use secp256kfun::{
+    alloc_audit::{assert_allocation_free, count_allocations, CountingAllocator},
+    g,
+    hash::HashAdd,
+    marker::*,
+    poly, s, Point, Scalar, G,
+};
+use sha2::Sha256;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator::system();
+
+#[test]
+fn curve_arithmetic_is_allocation_free() {
+    let x = Scalar::random(&mut rand::thread_rng());
+    let y = Scalar::random(&mut rand::thread_rng());
+    let X = g!(x * G).normalize();
+    assert_allocation_free(|| {
+        let Y = g!(y * G);
+        let _ = g!(X + Y).normalize();
+        let _ = g!({ x.public() } * G + { y.public() } * X);
+        let _ = s!(x * y).invert();
+        let _ = Point::<EvenY>::from_xonly_bytes(X.to_xonly_bytes());
+        let _ = Scalar::<Secret, _>::from_hash(Sha256::default().add(X).add(b"message"));
+    });
+}
+
+#[test]
+fn allocating_call_paths() {
+    let x = Scalar::random(&mut rand::thread_rng());
+    let y = Scalar::random(&mut rand::thread_rng());
+    let (X, Y) = (g!(x * G).normalize(), g!(y * G).normalize());
+    // constant time multi-scalar multiplication uses allocated tables when `alloc` is enabled
+    let (_, count) = count_allocations(|| g!(x * X + y * Y));
+    assert!(count.allocations > 0);
+
+    let poly = poly::scalar::generate(3, &mut rand::thread_rng());
+    let (_, count) = count_allocations(|| poly::scalar::to_point_poly(&poly));
+    assert!(count.allocations > 0);
+    let ((), count) = count_allocations(|| ());
+    assert!(count.is_zero());
+}
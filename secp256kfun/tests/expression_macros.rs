@@ -1,6 +1,6 @@
 #![allow(non_snake_case)]
 
-use secp256kfun::{g, op, s, Point, Scalar};
+use secp256kfun::{assert_point_eq, assert_scalar_eq, g, op, s, Point, Scalar, G};
 
 #[derive(Clone)]
 struct Has<T> {
@@ -164,3 +164,60 @@ fn g_expressions_give_correct_answers() {
         op::double_mul(x, has_point.has, y, has_has_point.has_has.has)
     );
 }
+
+#[test]
+fn assert_eq_macros_print_the_difference() {
+    let x = Scalar::random(&mut rand::thread_rng());
+    assert_point_eq!(g!(x * G + x * G), g!(2 * x * G));
+    assert_scalar_eq!(s!(x + x), s!(2 * x), "doubling {}", x);
+
+    let panic_message = |result: std::thread::Result<()>| {
+        result
+            .unwrap_err()
+            .downcast_ref::<String>()
+            .unwrap()
+            .clone()
+    };
+    let message = panic_message(std::panic::catch_unwind(|| {
+        assert_point_eq!(g!(3 * G), *G, "off by {}", 2)
+    }));
+    assert!(message.contains(": off by 2"));
+    assert!(message.contains(&format!("diff: {:?}", g!(2 * G).normalize().mark_zero())));
+
+    let message = panic_message(std::panic::catch_unwind(|| assert_scalar_eq!(s!(5), s!(3))));
+    assert!(message.contains(&format!("diff: {:?}", s!(2).mark_zero())));
+}
+
+#[test]
+#[allow(clippy::op_ref)] // checking the impls on references
+fn operators_match_expressions() {
+    use std::ops::{Add, Mul};
+    let a = Scalar::random(&mut rand::thread_rng());
+    let b = Scalar::random(&mut rand::thread_rng()).public();
+    let A = g!(a * G).normalize();
+    let B = Point::random(&mut rand::thread_rng());
+
+    assert_eq!(a + b, s!(a + b));
+    assert_eq!(&a - b, s!(a - b));
+    assert_eq!(a * &b, s!(a * b));
+    assert_eq!(&a * &b * a, s!(a * b * a));
+    assert_eq!(-a, s!(-a));
+
+    assert_eq!(A + B, g!(A + B));
+    assert_eq!(&A - &B, g!(A - B));
+    assert_eq!(a * B, g!(a * B));
+    assert_eq!(&B * a, g!(a * B));
+    assert_eq!(b * G - a * &B, g!(b * G - a * B));
+    assert!((A - A).is_zero());
+
+    // usable where the operations are generic
+    fn double<T: Copy + Add<Output = O>, O>(x: T) -> O {
+        x + x
+    }
+    fn scale<X: Mul<P, Output = O>, P, O>(x: X, point: P) -> O {
+        x * point
+    }
+    assert_eq!(double(a), s!(2 * a));
+    assert_eq!(double(B), g!(2 * B));
+    assert_eq!(scale(a, G), A);
+}
@@ -0,0 +1,81 @@
+//! secp256kfun promises that nothing has to be initialized before it is used (see the README) so
+//! embedded users can call it from interrupt context. The precomputed tables are plain `static`s
+//! whose initializers the compiler evaluates at compile time. This checks that nobody adds a
+//! lazily initialized static or a dependency that provides one.
+use std::{fs, path::Path};
+
+const FORBIDDEN: &[&str] = &[
+    "lazy_static",
+    "once_cell",
+    "OnceCell",
+    "OnceLock",
+    "LazyLock",
+    "LazyCell",
+    "Once::new",
+    "call_once",
+];
+
+fn rust_files(dir: &Path, files: &mut Vec<std::path::PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            rust_files(&path, files);
+        } else if path.extension().map_or(false, |ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+}
+
+#[test]
+fn no_lazily_initialized_statics_in_source() {
+    let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let mut files = vec![];
+    rust_files(&src, &mut files);
+    assert!(!files.is_empty());
+    for file in files {
+        let source = fs::read_to_string(&file).unwrap();
+        for (i, line) in source.lines().enumerate() {
+            for forbidden in FORBIDDEN {
+                assert!(
+                    !line.contains(forbidden),
+                    "{}:{} uses {} which would be initialized lazily",
+                    file.display(),
+                    i + 1,
+                    forbidden
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn no_lazy_static_dependencies() {
+    let manifest =
+        fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml")).unwrap();
+    // only the (non-dev) dependencies end up in the library
+    let dependencies = manifest
+        .split("\n[")
+        .filter(|section| section.starts_with("dependencies]"))
+        .collect::<Vec<_>>();
+    assert_eq!(dependencies.len(), 1);
+    for forbidden in ["lazy_static", "once_cell"] {
+        assert!(
+            !dependencies[0].contains(forbidden),
+            "secp256kfun must not depend on {forbidden}"
+        );
+    }
+}
+
+#[test]
+fn generator_tables_are_statics() {
+    let table = fs::read_to_string(
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("src/vendor/k256/generator_table.rs"),
+    )
+    .unwrap();
+    for name in ["G_TABLE", "LAMBDA_G_TABLE"] {
+        assert!(
+            table.contains(&format!("pub static {name}: [AffinePoint;")),
+            "{name} must stay a static computed at compile time"
+        );
+    }
+}
@@ -10,6 +10,29 @@ fn test_lagrange_lambda() {
     );
 }
 
+#[test]
+fn test_lagrange_basis() {
+    let indices = [
+        s!(5).public(),
+        s!(1).public(),
+        s!(4).public(),
+        s!(2).public(),
+    ];
+    let basis = poly::LagrangeBasis::new(indices);
+    for index in &indices {
+        assert_eq!(
+            basis.coefficient(index),
+            Some(poly::eval_basis_poly_at_0(*index, indices.iter()))
+        );
+    }
+    let mut sorted = indices;
+    sorted.sort();
+    assert!(basis.indices().eq(sorted));
+    assert!(basis.is_for(&sorted));
+    assert!(!basis.is_for(&sorted[1..]));
+    assert!(!basis.is_for(&indices));
+}
+
 #[test]
 fn test_add_poly() {
     let poly1 = vec![g!(1 * G), g!(2 * G), g!(3 * G)];
@@ -12,7 +12,7 @@ use generic_array::{
     ArrayLength, GenericArray,
 };
 pub use secp256kfun as fun;
-use secp256kfun::{g, marker::*, s, Point, Scalar};
+use secp256kfun::{g, marker::*, op, s, Point, Scalar};
 
 /// Proves knowledge of `x` such that `A = x * B` for some `A` and `B` included in the statement.
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -79,7 +79,7 @@ where
     }
 
     fn hash_witness<H: Update>(&self, hash: &mut H, witness: &Self::Witness) {
-        hash.update(witness.to_bytes().as_ref())
+        hash.update(witness.to_secret_bytes().expose_secret())
     }
 
     fn gen_announce_secret<Rng: CryptoRng + RngCore>(
@@ -165,7 +165,7 @@ where
     }
 
     fn hash_witness<H: Update>(&self, hash: &mut H, witness: &Self::Witness) {
-        hash.update(witness.to_bytes().as_ref())
+        hash.update(witness.to_secret_bytes().expose_secret())
     }
 
     fn gen_announce_secret<Rng: CryptoRng + RngCore>(
@@ -177,6 +177,120 @@ where
     }
 }
 
+/// Proves knowledge of `x_1, ..., x_N` such that `P = x_1 * B_1 + ... + x_N * B_N` for some
+/// bases `B_1, ..., B_N` and a point `P` included in the statement (a proof of *representation*).
+///
+/// The statement is `(bases, P)`. For example a Pedersen commitment `C = r * G + c * H` is opened
+/// in zero knowledge with `Representation::<L, 2>` and the statement `([G, H], C)`. The witnesses
+/// may be zero so you can prove knowledge of a commitment to `0`.
+///
+/// ```
+/// use sigma_fun::{
+///     secp256k1::{
+///         fun::{g, marker::*, Point, Scalar, G},
+///         Representation,
+///     },
+///     typenum::U32,
+///     FiatShamir, HashTranscript,
+/// };
+/// use rand_chacha::ChaCha20Rng;
+/// use sha2::Sha256;
+/// let H = Point::random(&mut rand::thread_rng());
+/// let r = Scalar::random(&mut rand::thread_rng()).mark_zero();
+/// let c = Scalar::<Secret, Zero>::from(42u32);
+/// let C = g!(r * G + c * H).normalize().non_zero().unwrap();
+/// let statement = ([G.normalize(), H], C);
+///
+/// let proof_system =
+///     FiatShamir::<Representation<U32, 2>, HashTranscript<Sha256, ChaCha20Rng>>::default();
+/// let proof = proof_system.prove(&[r, c], &statement, Some(&mut rand::thread_rng()));
+/// assert!(proof_system.verify(&statement, &proof));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Representation<L, const N: usize> {
+    challenge_len: PhantomData<L>,
+}
+
+impl<L: ArrayLength<u8>, const N: usize> Sigma for Representation<L, N>
+where
+    L: IsLessOrEqual<U32>,
+    <L as IsLessOrEqual<U32>>::Output: typenum::marker_traits::NonZero,
+{
+    type Witness = [Scalar<Secret, Zero>; N];
+    type Statement = ([Point; N], Point);
+    type AnnounceSecret = [Scalar; N];
+    type Announcement = Point;
+    type Response = [Scalar<Public, Zero>; N];
+    type ChallengeLength = L;
+
+    fn respond(
+        &self,
+        witness: &Self::Witness,
+        _statement: &Self::Statement,
+        announce_secret: Self::AnnounceSecret,
+        _announce: &Self::Announcement,
+        challenge: &GenericArray<u8, Self::ChallengeLength>,
+    ) -> Self::Response {
+        let challenge = normalize_challenge(challenge);
+        core::array::from_fn(|i| {
+            let (r, x) = (&announce_secret[i], &witness[i]);
+            s!(r + challenge * x).public()
+        })
+    }
+
+    fn announce(
+        &self,
+        statement: &Self::Statement,
+        announce_secret: &Self::AnnounceSecret,
+    ) -> Self::Announcement {
+        op::point_scalar_dot_product(announce_secret, statement.0)
+            .normalize()
+            .non_zero()
+            .expect("computationally unreachable")
+    }
+
+    fn sample_response<Rng: CryptoRng + RngCore>(&self, rng: &mut Rng) -> Self::Response {
+        core::array::from_fn(|_| Scalar::random(rng).public().mark_zero())
+    }
+
+    fn implied_announcement(
+        &self,
+        statement: &Self::Statement,
+        challenge: &GenericArray<u8, Self::ChallengeLength>,
+        response: &Self::Response,
+    ) -> Option<Self::Announcement> {
+        let (bases, P) = statement;
+        let challenge = normalize_challenge(challenge);
+        let sB = op::point_scalar_dot_product(response, bases);
+        g!(sB - challenge * P).normalize().non_zero()
+    }
+
+    fn hash_statement<H: Update>(&self, hash: &mut H, statement: &Self::Statement) {
+        for base in &statement.0 {
+            hash.update(base.to_bytes().as_ref());
+        }
+        hash.update(statement.1.to_bytes().as_ref());
+    }
+
+    fn hash_announcement<H: Update>(&self, hash: &mut H, announcement: &Self::Announcement) {
+        hash.update(announcement.to_bytes().as_ref())
+    }
+
+    fn hash_witness<H: Update>(&self, hash: &mut H, witness: &Self::Witness) {
+        for x in witness {
+            hash.update(x.to_secret_bytes().expose_secret())
+        }
+    }
+
+    fn gen_announce_secret<Rng: CryptoRng + RngCore>(
+        &self,
+        _witness: &Self::Witness,
+        rng: &mut Rng,
+    ) -> Self::AnnounceSecret {
+        core::array::from_fn(|_| Scalar::random(rng))
+    }
+}
+
 fn normalize_challenge<L: ArrayLength<u8>>(
     challenge: &GenericArray<u8, L>,
 ) -> Scalar<Public, Zero> {
@@ -193,5 +307,64 @@ impl<L> crate::Writable for DLG<L> {
     }
 }
 
+impl<L, const N: usize> crate::Writable for Representation<L, N> {
+    fn write_to<W: core::fmt::Write>(&self, w: &mut W) -> core::fmt::Result {
+        write!(w, "Representation({N},secp256k1)")
+    }
+}
+
+impl<L, const N: usize> core::fmt::Display for Representation<L, N>
+where
+    Representation<L, N>: Sigma,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        use crate::Writable;
+        self.write_to(f)
+    }
+}
+
 crate::impl_display!(DL<L>);
 crate::impl_display!(DLG<L>);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{typenum::U20, FiatShamir, HashTranscript};
+    use rand_chacha::ChaCha20Rng;
+    use secp256kfun::G;
+    use sha2::Sha256;
+
+    #[test]
+    fn representation_has_correct_name() {
+        assert_eq!(
+            format!("{}", Representation::<U32, 3>::default()),
+            "Representation(3,secp256k1)"
+        );
+    }
+
+    #[test]
+    fn representation_proof() {
+        let [x, y] = [(); 2].map(|_| Scalar::random(&mut rand::thread_rng()));
+        let [H, J] = [(); 2].map(|_| Point::random(&mut rand::thread_rng()));
+        let witness = [x.mark_zero(), y.mark_zero(), Scalar::zero()];
+        let P = g!(x * G + y * H).normalize().non_zero().unwrap();
+        let statement = ([G.normalize(), H, J], P);
+        let proof_system =
+            FiatShamir::<Representation<U20, 3>, HashTranscript<Sha256, ChaCha20Rng>>::default();
+        let proof = proof_system.prove(&witness, &statement, Some(&mut rand::thread_rng()));
+        assert!(proof_system.verify(&statement, &proof));
+
+        let bogus_statement = ([G.normalize(), J, H], P);
+        assert!(!proof_system.verify(&bogus_statement, &proof));
+        let bogus_proof =
+            proof_system.prove(&witness, &bogus_statement, Some(&mut rand::thread_rng()));
+        assert!(!proof_system.verify(&bogus_statement, &bogus_proof));
+
+        #[cfg(feature = "serde")]
+        {
+            let decoded: crate::CompactProof<Representation<U20, 3>> =
+                bincode::deserialize(&bincode::serialize(&proof).unwrap()).unwrap();
+            assert!(proof_system.verify(&statement, &decoded));
+        }
+    }
+}